@@ -0,0 +1,122 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse, FieldsNamed, Generics, Ident, Meta, Token, Visibility};
+
+struct AccessorAttr {
+    skip: bool,
+    copy: bool,
+    vis: Option<Visibility>,
+}
+
+impl Parse for AccessorAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = AccessorAttr {
+            skip: false,
+            copy: false,
+            vis: None,
+        };
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "skip" => attr.skip = true,
+                "copy" => attr.copy = true,
+                "vis" => {
+                    input.parse::<Token![=]>()?;
+                    attr.vis = Some(input.parse()?);
+                }
+                other => panic!("Unknown accessor attribute '{}', expected 'skip', 'copy' or 'vis = ...'", other),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attr)
+    }
+}
+
+fn get_attr(attrs: &[syn::Attribute], name: &str) -> AccessorAttr {
+    attrs.iter().filter_map(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident(name) {
+                let tokens: TokenStream = l.tokens.clone().into();
+                return Some(parse::Parser::parse(AccessorAttr::parse, tokens).unwrap());
+            }
+        }
+        None
+    }).next().unwrap_or(AccessorAttr {
+        skip: false,
+        copy: false,
+        vis: None,
+    })
+}
+
+pub fn getters(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let methods = fields.named.iter().filter_map(|f| {
+        let attr = get_attr(&f.attrs, "getter");
+        if attr.skip {
+            return None;
+        }
+        let field = f.ident.as_ref().unwrap();
+        let field_mut = format_ident!("{}_mut", field);
+        let ty = &f.ty;
+        let vis = attr.vis.unwrap_or_else(|| syn::parse_quote!(pub));
+        let getter = if attr.copy {
+            quote! {
+                #vis fn #field(&self) -> #ty {
+                    self.#field
+                }
+            }
+        } else {
+            quote! {
+                #vis fn #field(&self) -> &#ty {
+                    &self.#field
+                }
+            }
+        };
+        Some(quote! {
+            #getter
+
+            #vis fn #field_mut(&mut self) -> &mut #ty {
+                &mut self.#field
+            }
+        })
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #methods )*
+        }
+    }.into()
+}
+
+pub fn setters(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let methods = fields.named.iter().filter_map(|f| {
+        let attr = get_attr(&f.attrs, "setter");
+        if attr.skip {
+            return None;
+        }
+        if attr.copy {
+            panic!("The 'copy' attribute is only supported for getters!");
+        }
+        let field = f.ident.as_ref().unwrap();
+        let setter = format_ident!("set_{}", field);
+        let ty = &f.ty;
+        let vis = attr.vis.unwrap_or_else(|| syn::parse_quote!(pub));
+        Some(quote! {
+            #vis fn #setter(&mut self, #field: #ty) {
+                self.#field = #field;
+            }
+        })
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #methods )*
+        }
+    }.into()
+}
@@ -0,0 +1,138 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse, Expr, Field, FieldsNamed, Generics, Ident, Meta, Token, Visibility};
+use syn::parse::{Parse, ParseStream};
+
+enum BuilderDefault {
+    None,
+    Default,
+    Value(Expr),
+}
+
+struct BuilderAttr {
+    default: BuilderDefault,
+}
+
+impl Parse for BuilderAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "default" {
+            panic!("Unknown builder attribute '{}', expected 'default' or 'default = value'", key);
+        }
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Ok(BuilderAttr {
+                default: BuilderDefault::Value(input.parse()?),
+            })
+        } else {
+            Ok(BuilderAttr {
+                default: BuilderDefault::Default,
+            })
+        }
+    }
+}
+
+fn get_default(f: &Field) -> BuilderDefault {
+    f.attrs.iter().filter_map(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("builder") {
+                let tokens: TokenStream = l.tokens.clone().into();
+                let attr = parse::Parser::parse(BuilderAttr::parse, tokens).unwrap();
+                return Some(attr.default);
+            }
+        }
+        None
+    }).next().unwrap_or(BuilderDefault::None)
+}
+
+pub fn builder(fields: &FieldsNamed, name: Ident, vis: Visibility, generics: Generics) -> TokenStream {
+    let builder_name = format_ident!("{}Builder", name);
+
+    let fields = fields.named.iter().map(|f| (f, get_default(f))).collect::<Vec<_>>();
+
+    let builder_fields = fields.iter().map(|(f, _)| {
+        let name = &f.ident;
+        let ty = &f.ty;
+        quote! {
+            #name: Option<#ty>
+        }
+    });
+
+    let empty_fields = fields.iter().map(|(f, _)| {
+        let name = &f.ident;
+        quote! {
+            #name: None
+        }
+    });
+
+    let setters = fields.iter().map(|(f, _)| {
+        let name = &f.ident;
+        let ty = &f.ty;
+        quote! {
+            pub fn #name(mut self, value: #ty) -> Self {
+                self.#name = Some(value);
+                self
+            }
+        }
+    });
+
+    let build_fields = fields.iter().map(|(f, default)| {
+        let ty_name = &name;
+        let name = &f.ident;
+        let ty = &f.ty;
+        match default {
+            BuilderDefault::None => quote! {
+                let #name = self.#name.ok_or_else(|| format!("Failed to build {}, missing required field '{}'!", stringify!(#ty_name), stringify!(#name)))?;
+            },
+            BuilderDefault::Default => quote! {
+                let #name = self.#name.unwrap_or_else(<#ty as Default>::default);
+            },
+            BuilderDefault::Value(expr) => quote! {
+                let #name = self.#name.unwrap_or_else(|| #expr);
+            },
+        }
+    });
+
+    let init_struct = fields.iter().map(|(f, _)| {
+        let name = &f.ident;
+        quote! {
+            #name
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        #vis struct #builder_name #impl_generics #where_clause {
+            #( #builder_fields ),*
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn builder() -> #builder_name #ty_generics {
+                #builder_name {
+                    #( #empty_fields ),*
+                }
+            }
+        }
+
+        impl #impl_generics Default for #builder_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #name::builder()
+            }
+        }
+
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #( #setters )*
+
+            pub fn build(self) -> Result<#name #ty_generics, String> {
+                #( #build_fields )*
+
+                Ok(#name {
+                    #( #init_struct ),*
+                })
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
@@ -0,0 +1,101 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse, Expr, Field, FieldsNamed, Generics, Ident, Meta, Token};
+use syn::parse::{Parse, ParseStream};
+
+enum ConfigDefault {
+    None,
+    Default,
+    Value(Expr),
+}
+
+struct ConfigAttr {
+    default: ConfigDefault,
+}
+
+impl Parse for ConfigAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "default" {
+            panic!("Unknown config attribute '{}', expected 'default' or 'default = value'", key);
+        }
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Ok(ConfigAttr {
+                default: ConfigDefault::Value(input.parse()?),
+            })
+        } else {
+            Ok(ConfigAttr {
+                default: ConfigDefault::Default,
+            })
+        }
+    }
+}
+
+fn get_default(f: &Field) -> ConfigDefault {
+    f.attrs.iter().filter_map(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("config") {
+                let tokens: TokenStream = l.tokens.clone().into();
+                let attr = parse::Parser::parse(ConfigAttr::parse, tokens).unwrap();
+                return Some(attr.default);
+            }
+        }
+        None
+    }).next().unwrap_or(ConfigDefault::None)
+}
+
+pub fn config_section(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let fields = fields.named.iter().map(|f| (f, get_default(f))).collect::<Vec<_>>();
+
+    let read_fields = fields.iter().map(|(f, default)| {
+        let name = &f.ident;
+        let ty = &f.ty;
+        match default {
+            ConfigDefault::None => quote! {
+                let #name = config.get::<#ty>(section, stringify!(#name))?
+                    .ok_or_else(|| format!("Missing key '{}' in section [{}]!", stringify!(#name), section))?;
+            },
+            ConfigDefault::Default => quote! {
+                let #name = config.get::<#ty>(section, stringify!(#name))?.unwrap_or_else(<#ty as Default>::default);
+            },
+            ConfigDefault::Value(expr) => quote! {
+                let #name = config.get::<#ty>(section, stringify!(#name))?.unwrap_or_else(|| #expr);
+            },
+        }
+    });
+
+    let init_struct = fields.iter().map(|(f, _)| {
+        let name = &f.ident;
+        quote! {
+            #name
+        }
+    });
+
+    let write_fields = fields.iter().map(|(f, _)| {
+        let name = &f.ident;
+        quote! {
+            config.set(section, stringify!(#name), &self.#name);
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::config::ConfigSection for #name #ty_generics #where_clause {
+            fn from_section(config: &mvutils::config::Config, section: &str) -> Result<Self, String> {
+                #( #read_fields )*
+
+                Ok(#name {
+                    #( #init_struct ),*
+                })
+            }
+
+            fn to_section(&self, config: &mut mvutils::config::Config, section: &str) {
+                #( #write_fields )*
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
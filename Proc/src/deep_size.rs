@@ -0,0 +1,81 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DataEnum, Field, Fields, Generics, Ident, Index, Meta};
+
+fn is_skipped(f: &Field) -> bool {
+    f.attrs.iter().any(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("deep_size") {
+                let ident: Ident = syn::parse2(l.tokens.clone()).expect("Expected 'skip' in deep_size attribute");
+                if ident != "skip" {
+                    panic!("Unknown deep_size attribute '{}', expected 'skip'", ident);
+                }
+                return true;
+            }
+        }
+        false
+    })
+}
+
+pub fn deep_size_struct(fields: &Fields, name: Ident, generics: Generics) -> TokenStream {
+    let sizes = fields.iter().enumerate().filter(|(_, f)| !is_skipped(f)).map(|(i, f)| {
+        let access = match &f.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(i);
+                quote! { #index }
+            }
+        };
+        quote! { + mvutils::deep_size::DeepSizeOf::heap_size(&self.#access) }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::deep_size::DeepSizeOf for #name #ty_generics #where_clause {
+            fn heap_size(&self) -> usize {
+                0 #( #sizes )*
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
+
+pub fn deep_size_enum(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream {
+    let arms = e.variants.iter().map(|v| {
+        let ident = &v.ident;
+        let bindings = v.fields.iter().enumerate().map(|(i, _)| format_ident!("__v{}", i)).collect::<Vec<_>>();
+        let sizes = v.fields.iter().zip(&bindings).filter(|(f, _)| !is_skipped(f)).map(|(_, binding)| {
+            quote! { + mvutils::deep_size::DeepSizeOf::heap_size(#binding) }
+        });
+        let pattern = match &v.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| &f.ident);
+                quote! { #name::#ident { #( #names: #bindings ),* } }
+            }
+            Fields::Unnamed(_) => quote! { #name::#ident ( #( #bindings ),* ) },
+            Fields::Unit => quote! { #name::#ident },
+        };
+        quote! {
+            #[allow(unused_variables)]
+            #pattern => 0 #( #sizes )*,
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::deep_size::DeepSizeOf for #name #ty_generics #where_clause {
+            fn heap_size(&self) -> usize {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #( #arms )*
+                    _ => 0,
+                }
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
@@ -0,0 +1,106 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DataEnum, Field, Fields, Generics, Ident, Index, Meta};
+
+fn is_skipped(f: &Field) -> bool {
+    f.attrs.iter().any(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("diff") {
+                let ident: Ident = syn::parse2(l.tokens.clone()).expect("Expected 'skip' in diff attribute");
+                if ident != "skip" {
+                    panic!("Unknown diff attribute '{}', expected 'skip'", ident);
+                }
+                return true;
+            }
+        }
+        false
+    })
+}
+
+fn field_name(f: &Field, i: usize) -> String {
+    match &f.ident {
+        Some(ident) => ident.to_string(),
+        None => i.to_string(),
+    }
+}
+
+pub fn diff_struct(fields: &Fields, name: Ident, generics: Generics) -> TokenStream {
+    let diffs = fields.iter().enumerate().filter(|(_, f)| !is_skipped(f)).map(|(i, f)| {
+        let access = match &f.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(i);
+                quote! { #index }
+            }
+        };
+        let field = field_name(f, i);
+        quote! {
+            mvutils::diff::Diff::diff_into(&self.#access, &other.#access, &mvutils::diff::join(path, #field), changes);
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::diff::Diff for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<mvutils::diff::Change>) {
+                #( #diffs )*
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
+
+pub fn diff_enum(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream {
+    let arms = e.variants.iter().map(|v| {
+        let ident = &v.ident;
+        let ours = v.fields.iter().enumerate().map(|(i, _)| format_ident!("__a{}", i)).collect::<Vec<_>>();
+        let theirs = v.fields.iter().enumerate().map(|(i, _)| format_ident!("__b{}", i)).collect::<Vec<_>>();
+        let diffs = v.fields.iter().enumerate().filter(|(_, f)| !is_skipped(f)).map(|(i, f)| {
+            let a = &ours[i];
+            let b = &theirs[i];
+            let field = field_name(f, i);
+            quote! {
+                mvutils::diff::Diff::diff_into(#a, #b, &mvutils::diff::join(path, #field), changes);
+            }
+        });
+        let (a, b) = match &v.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                (
+                    quote! { #name::#ident { #( #names: #ours ),* } },
+                    quote! { #name::#ident { #( #names: #theirs ),* } },
+                )
+            }
+            Fields::Unnamed(_) => (
+                quote! { #name::#ident ( #( #ours ),* ) },
+                quote! { #name::#ident ( #( #theirs ),* ) },
+            ),
+            Fields::Unit => (quote! { #name::#ident }, quote! { #name::#ident }),
+        };
+        quote! {
+            (#a, #b) => {
+                #( #diffs )*
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::diff::Diff for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<mvutils::diff::Change>) {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #( #arms )*
+                    _ => changes.push(mvutils::diff::Change::changed(path, self, other)),
+                }
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
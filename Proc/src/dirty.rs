@@ -0,0 +1,133 @@
+use crate::savable::{filter, get_custom};
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Field, FieldsNamed, Generics, Ident};
+
+fn is_mask(f: &Field) -> bool {
+    f.attrs.iter().any(|attr| attr.path().is_ident("dirty_mask"))
+}
+
+pub fn dirty_savable(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let mut masks = fields.named.iter().filter(|f| is_mask(f));
+    let mask = match (masks.next(), masks.next()) {
+        (Some(mask), None) => mask.ident.as_ref().unwrap(),
+        (None, _) => panic!("DirtySavable requires a field of type DirtyMask marked with #[dirty_mask]!"),
+        (Some(_), Some(_)) => panic!("Only one field can be marked with #[dirty_mask]!"),
+    };
+
+    let fields = fields.named.iter().filter(|f| !is_mask(f)).filter(filter).collect::<Vec<_>>();
+    let count = fields.len();
+    if count > 64 {
+        panic!("DirtySavable supports at most 64 saved fields!");
+    }
+    let mask_ty = match count {
+        0..=8 => quote! { u8 },
+        9..=16 => quote! { u16 },
+        17..=32 => quote! { u32 },
+        _ => quote! { u64 },
+    };
+
+    let accessors = fields.iter().enumerate().map(|(i, f)| {
+        let field = f.ident.as_ref().unwrap();
+        let setter = format_ident!("set_{}", field);
+        let field_mut = format_ident!("{}_mut", field);
+        let ty = &f.ty;
+        quote! {
+            pub fn #setter(&mut self, value: #ty) {
+                self.#field = value;
+                self.#mask.set(#i);
+            }
+
+            pub fn #field_mut(&mut self) -> &mut #ty {
+                self.#mask.set(#i);
+                &mut self.#field
+            }
+        }
+    });
+
+    let saves = fields.iter().enumerate().map(|(i, f)| {
+        let field = f.ident.as_ref().unwrap();
+        let save = match get_custom(f) {
+            Some((save, _)) => quote! { #save(saver, &self.#field); },
+            None => quote! { mvutils::save::Savable::save(&self.#field, saver); },
+        };
+        quote! {
+            if dirty_bits & (1u64 << #i) != 0 {
+                #save
+            }
+        }
+    });
+
+    let loads = fields.iter().enumerate().map(|(i, f)| {
+        let field = f.ident.as_ref().unwrap();
+        let ty = &f.ty;
+        let load = match get_custom(f) {
+            Some((_, load)) => quote! {
+                #load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(stringify!(#field)))?
+            },
+            None => quote! {
+                <#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(stringify!(#field)))?
+            },
+        };
+        let local = format_ident!("loaded_{}", field);
+        quote! {
+            let #local = if dirty_bits & (1u64 << #i) != 0 { Some(#load) } else { None };
+        }
+    });
+
+    let assigns = fields.iter().map(|f| {
+        let field = f.ident.as_ref().unwrap();
+        let local = format_ident!("loaded_{}", field);
+        quote! {
+            if let Some(value) = #local {
+                self.#field = value;
+            }
+        }
+    });
+
+    let check = if count < 64 {
+        quote! {
+            if dirty_bits >> #count != 0 {
+                return Err(mvutils::save::SaveError::custom(format!("Invalid dirty mask {:#x} for {}", dirty_bits, stringify!(#name))));
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #accessors )*
+        }
+
+        impl #impl_generics mvutils::save::dirty::DirtySavable for #name #ty_generics #where_clause {
+            const FIELD_COUNT: usize = #count;
+
+            fn dirty_mask(&self) -> mvutils::save::dirty::DirtyMask {
+                self.#mask
+            }
+
+            fn dirty_mask_mut(&mut self) -> &mut mvutils::save::dirty::DirtyMask {
+                &mut self.#mask
+            }
+
+            fn save_dirty(&self, saver: &mut impl mvutils::save::Saver) {
+                let dirty_bits = self.#mask.bits();
+                mvutils::save::Savable::save(&(dirty_bits as #mask_ty), saver);
+                #( #saves )*
+            }
+
+            fn apply(&mut self, loader: &mut impl mvutils::save::Loader) -> Result<(), mvutils::save::SaveError> {
+                let dirty_bits = <#mask_ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field("dirty mask"))? as u64;
+                #check
+                #( #loads )*
+                #( #assigns )*
+                Ok(())
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
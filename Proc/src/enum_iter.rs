@@ -0,0 +1,58 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{DataEnum, Fields, Generics, Ident};
+
+pub fn enum_iter(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream {
+    if e.variants.iter().any(|v| !matches!(v.fields, Fields::Unit)) {
+        panic!("Deriving EnumIter is only supported for enums without fields!");
+    }
+
+    let count = e.variants.len();
+
+    let variants = e.variants.iter().map(|v| {
+        let ident = &v.ident;
+        quote! {
+            #name::#ident
+        }
+    }).collect::<Vec<_>>();
+
+    let from_index = variants.iter().enumerate().map(|(i, v)| {
+        quote! {
+            #i => Some(#v),
+        }
+    });
+
+    let index = variants.iter().enumerate().map(|(i, v)| {
+        quote! {
+            #v => #i,
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub const VARIANTS: &'static [Self] = &[#( #variants ),*];
+            pub const COUNT: usize = #count;
+
+            pub fn iter() -> std::slice::Iter<'static, Self> {
+                Self::VARIANTS.iter()
+            }
+
+            pub fn from_index(index: usize) -> Option<Self> {
+                match index {
+                    #( #from_index )*
+                    _ => None,
+                }
+            }
+
+            pub fn index(&self) -> usize {
+                match self {
+                    #( #index )*
+                }
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
@@ -0,0 +1,333 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DataEnum, Fields, Generics, Ident, Visibility};
+
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+pub fn flags(e: &DataEnum, vis: Visibility, name: Ident, generics: Generics) -> TokenStream {
+    if !generics.params.is_empty() {
+        panic!("Deriving Flags is not supported for generic enums!");
+    }
+    if e.variants.iter().any(|v| !matches!(v.fields, Fields::Unit)) {
+        panic!("Deriving Flags is only supported for enums without fields!");
+    }
+
+    let count = e.variants.len();
+    let bits = match count {
+        0 => panic!("Deriving Flags requires at least one variant!"),
+        1..=8 => quote! { u8 },
+        9..=16 => quote! { u16 },
+        17..=32 => quote! { u32 },
+        33..=64 => quote! { u64 },
+        _ => panic!("Deriving Flags is only supported for enums with up to 64 variants!"),
+    };
+
+    let flags = format_ident!("{}Flags", name);
+    let variants = e.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    let indices = (0..count).collect::<Vec<_>>();
+    let names = variants.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let alt_names = names.iter().map(|n| snake_case(n)).collect::<Vec<_>>();
+    let all = quote! { #bits::MAX >> (#bits::BITS as usize - #count) };
+
+    let doc = format!("A set of [`{}`] flags, stored as a bitset.", name);
+
+    let implementation = quote! {
+        #[doc = #doc]
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+        #vis struct #flags {
+            bits: #bits,
+        }
+
+        impl #name {
+            /// The flag as a set containing only this flag.
+            pub const fn flag(self) -> #flags {
+                let index: usize = match self {
+                    #( #name::#variants => #indices, )*
+                };
+                #flags { bits: 1 << index }
+            }
+        }
+
+        impl #flags {
+            pub const VARIANTS: &'static [#name] = &[#( #name::#variants ),*];
+
+            pub const fn empty() -> Self {
+                #flags { bits: 0 }
+            }
+
+            pub const fn all() -> Self {
+                #flags { bits: #all }
+            }
+
+            pub const fn bits(self) -> #bits {
+                self.bits
+            }
+
+            /// Create the flags from bits, returning `None` if any bit does not belong to a flag.
+            pub const fn from_bits(bits: #bits) -> Option<Self> {
+                if bits & !Self::all().bits == 0 {
+                    Some(#flags { bits })
+                } else {
+                    None
+                }
+            }
+
+            /// Create the flags from bits, ignoring bits which do not belong to a flag.
+            pub const fn from_bits_truncate(bits: #bits) -> Self {
+                #flags { bits: bits & Self::all().bits }
+            }
+
+            pub const fn is_empty(self) -> bool {
+                self.bits == 0
+            }
+
+            pub const fn is_all(self) -> bool {
+                self.bits == Self::all().bits
+            }
+
+            /// The number of set flags.
+            pub const fn len(self) -> usize {
+                self.bits.count_ones() as usize
+            }
+
+            /// Whether all of the given flags are set.
+            pub fn contains(self, flags: impl Into<Self>) -> bool {
+                let flags = flags.into();
+                self.bits & flags.bits == flags.bits
+            }
+
+            /// Whether any of the given flags is set.
+            pub fn intersects(self, flags: impl Into<Self>) -> bool {
+                self.bits & flags.into().bits != 0
+            }
+
+            pub fn insert(&mut self, flags: impl Into<Self>) {
+                self.bits |= flags.into().bits;
+            }
+
+            pub fn remove(&mut self, flags: impl Into<Self>) {
+                self.bits &= !flags.into().bits;
+            }
+
+            pub fn toggle(&mut self, flags: impl Into<Self>) {
+                self.bits ^= flags.into().bits;
+            }
+
+            /// Insert or remove the flags.
+            pub fn set(&mut self, flags: impl Into<Self>, value: bool) {
+                if value {
+                    self.insert(flags);
+                } else {
+                    self.remove(flags);
+                }
+            }
+
+            /// Iterate over the set flags in declaration order.
+            pub fn iter(self) -> impl Iterator<Item = #name> {
+                Self::VARIANTS
+                    .iter()
+                    .enumerate()
+                    .filter(move |(i, _)| self.bits & (1 << i) != 0)
+                    .map(|(i, _)| Self::variant(i))
+            }
+
+            fn variant(index: usize) -> #name {
+                match index {
+                    #( #indices => #name::#variants, )*
+                    _ => unreachable!(),
+                }
+            }
+
+            fn name(index: usize) -> &'static str {
+                match index {
+                    #( #indices => #names, )*
+                    _ => unreachable!(),
+                }
+            }
+
+            fn parse_flag(name: &str) -> Option<#name> {
+                match name {
+                    #( #names | #alt_names => Some(#name::#variants), )*
+                    _ => None,
+                }
+            }
+        }
+
+        impl ::core::convert::From<#name> for #flags {
+            fn from(value: #name) -> Self {
+                value.flag()
+            }
+        }
+
+        impl ::core::iter::FromIterator<#name> for #flags {
+            fn from_iter<I: IntoIterator<Item = #name>>(iter: I) -> Self {
+                let mut flags = Self::empty();
+                for flag in iter {
+                    flags.insert(flag);
+                }
+                flags
+            }
+        }
+
+        impl ::core::iter::Extend<#name> for #flags {
+            fn extend<I: IntoIterator<Item = #name>>(&mut self, iter: I) {
+                for flag in iter {
+                    self.insert(flag);
+                }
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitOr<T> for #flags {
+            type Output = #flags;
+
+            fn bitor(self, rhs: T) -> #flags {
+                #flags { bits: self.bits | rhs.into().bits }
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitOrAssign<T> for #flags {
+            fn bitor_assign(&mut self, rhs: T) {
+                self.bits |= rhs.into().bits;
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitAnd<T> for #flags {
+            type Output = #flags;
+
+            fn bitand(self, rhs: T) -> #flags {
+                #flags { bits: self.bits & rhs.into().bits }
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitAndAssign<T> for #flags {
+            fn bitand_assign(&mut self, rhs: T) {
+                self.bits &= rhs.into().bits;
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitXor<T> for #flags {
+            type Output = #flags;
+
+            fn bitxor(self, rhs: T) -> #flags {
+                #flags { bits: self.bits ^ rhs.into().bits }
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitXorAssign<T> for #flags {
+            fn bitxor_assign(&mut self, rhs: T) {
+                self.bits ^= rhs.into().bits;
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::Sub<T> for #flags {
+            type Output = #flags;
+
+            fn sub(self, rhs: T) -> #flags {
+                #flags { bits: self.bits & !rhs.into().bits }
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::SubAssign<T> for #flags {
+            fn sub_assign(&mut self, rhs: T) {
+                self.bits &= !rhs.into().bits;
+            }
+        }
+
+        impl ::core::ops::Not for #flags {
+            type Output = #flags;
+
+            fn not(self) -> #flags {
+                Self::from_bits_truncate(!self.bits)
+            }
+        }
+
+        impl<T: Into<#flags>> ::core::ops::BitOr<T> for #name {
+            type Output = #flags;
+
+            fn bitor(self, rhs: T) -> #flags {
+                self.flag() | rhs
+            }
+        }
+
+        impl ::core::fmt::Display for #flags {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let mut first = true;
+                for i in 0..#count {
+                    if self.bits & (1 << i) != 0 {
+                        if !first {
+                            f.write_str("|")?;
+                        }
+                        f.write_str(Self::name(i))?;
+                        first = false;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        impl ::core::fmt::Debug for #flags {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "{}({})", stringify!(#flags), self)
+            }
+        }
+
+        impl ::core::str::FromStr for #flags {
+            type Err = String;
+
+            /// Parse flags separated by `|`, like `Read|Write`. An empty string parses to no flags.
+            fn from_str(s: &str) -> Result<Self, String> {
+                let mut flags = Self::empty();
+                for name in s.split('|').map(str::trim).filter(|n| !n.is_empty()) {
+                    let flag = Self::parse_flag(name)
+                        .ok_or_else(|| format!("Unknown flag '{}' for {}", name, stringify!(#name)))?;
+                    flags.insert(flag);
+                }
+                Ok(flags)
+            }
+        }
+
+        impl ::core::convert::TryFrom<&str> for #flags {
+            type Error = String;
+
+            fn try_from(value: &str) -> Result<Self, String> {
+                value.parse()
+            }
+        }
+
+        impl ::core::convert::TryFrom<String> for #flags {
+            type Error = String;
+
+            fn try_from(value: String) -> Result<Self, String> {
+                value.parse()
+            }
+        }
+
+        impl mvutils::save::Savable for #flags {
+            fn save(&self, saver: &mut impl mvutils::save::Saver) {
+                mvutils::save::Savable::save(&self.bits, saver);
+            }
+
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
+                let bits = <#bits as mvutils::save::Savable>::load(loader)?;
+                Self::from_bits(bits).ok_or_else(|| {
+                    mvutils::save::SaveError::custom(format!("Invalid bits {:#x} for {}", bits, stringify!(#flags)))
+                })
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
@@ -0,0 +1,95 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, Data, DeriveInput, Field, Fields};
+
+const FORMAT_ATTRS: &[&str] = &["unsaved", "custom", "save_order", "id", "savable", "discriminant"];
+
+fn describe_attrs(attrs: &[Attribute], out: &mut String) {
+    for attr in attrs {
+        if FORMAT_ATTRS.iter().any(|name| attr.path().is_ident(name)) {
+            out.push_str(&attr.meta.to_token_stream().to_string());
+            out.push(';');
+        }
+    }
+}
+
+/// Describes the fields without their names, which are not saved, and collects the hash of every
+/// field's type, which is the nested format hash if the type has one.
+fn describe_fields(fields: &Fields, out: &mut String, nested: &mut Vec<TokenStream>) {
+    for field in fields {
+        describe_field(field, out, nested);
+    }
+}
+
+fn describe_field(field: &Field, out: &mut String, nested: &mut Vec<TokenStream>) {
+    let ty = &field.ty;
+    let written = fnv1a(ty.to_token_stream().to_string().as_bytes());
+    if field.attrs.iter().any(|a| a.path().is_ident("unsaved") || a.path().is_ident("custom")) {
+        nested.push(quote! { #written });
+    } else {
+        nested.push(quote! {
+            mvutils::save::format::field_hash(<mvutils::save::format::Nested<#ty>>::NESTED, #written)
+        });
+    }
+    out.push('[');
+    describe_attrs(&field.attrs, out);
+    out.push_str("],");
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// A stable description of everything in the type definition that affects how it is saved, apart
+/// from the field types, which are returned separately to be hashed at compile time.
+fn describe(input: &DeriveInput) -> (String, Vec<TokenStream>) {
+    let mut out = String::from("[");
+    let mut nested = Vec::new();
+    describe_attrs(&input.attrs, &mut out);
+    out.push(']');
+    match &input.data {
+        Data::Struct(s) => {
+            out.push_str("struct{");
+            describe_fields(&s.fields, &mut out, &mut nested);
+        }
+        Data::Enum(e) => {
+            out.push_str("enum{");
+            for variant in &e.variants {
+                out.push('[');
+                describe_attrs(&variant.attrs, &mut out);
+                out.push_str("]{");
+                describe_fields(&variant.fields, &mut out, &mut nested);
+                out.push_str("},");
+            }
+        }
+        Data::Union(u) => {
+            out.push_str("union{");
+            for field in &u.fields.named {
+                describe_field(field, &mut out, &mut nested);
+            }
+        }
+    }
+    out.push('}');
+    (out, nested)
+}
+
+pub fn implement(input: &DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let (description, nested) = describe(input);
+    let hash = fnv1a(description.as_bytes());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics mvutils::save::format::FormatHash for #name #ty_generics #where_clause {
+            const FORMAT_HASH: u64 = {
+                use mvutils::save::format::NoFormatHash as _;
+                mvutils::save::format::combine_hashes(#hash, &[#( #nested ),*])
+            };
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Field, Fields, Generics, Ident, Index, Meta};
+
+enum LerpMode {
+    Lerp,
+    Skip,
+    Round,
+}
+
+fn get_mode(f: &Field) -> LerpMode {
+    f.attrs.iter().filter_map(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("lerp") {
+                let ident: Ident = syn::parse2(l.tokens.clone()).expect("Expected 'skip' or 'round' in lerp attribute");
+                return match ident.to_string().as_str() {
+                    "skip" => Some(LerpMode::Skip),
+                    "round" => Some(LerpMode::Round),
+                    other => panic!("Unknown lerp attribute '{}', expected 'skip' or 'round'", other),
+                };
+            }
+        }
+        None
+    }).next().unwrap_or(LerpMode::Lerp)
+}
+
+pub fn lerp(fields: &Fields, name: Ident, generics: Generics) -> TokenStream {
+    let values = fields.iter().enumerate().map(|(i, f)| {
+        let access = match &f.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(i);
+                quote! { #index }
+            }
+        };
+        let value = match get_mode(f) {
+            LerpMode::Lerp => quote! {
+                mvutils::lerp::Lerp::lerp(&self.#access, &other.#access, t)
+            },
+            LerpMode::Skip => quote! {
+                ::std::clone::Clone::clone(&self.#access)
+            },
+            LerpMode::Round => quote! {
+                mvutils::lerp::Lerp::lerp(&self.#access, &other.#access, t).round()
+            },
+        };
+        let binding = format_ident!("__field{}", i);
+        (binding, value)
+    }).collect::<Vec<_>>();
+
+    let bindings = values.iter().map(|(binding, value)| {
+        quote! {
+            let #binding = #value;
+        }
+    });
+
+    let construct = match fields {
+        Fields::Named(_) => {
+            let inits = fields.iter().zip(values.iter()).map(|(f, (binding, _))| {
+                let ident = &f.ident;
+                quote! { #ident: #binding }
+            });
+            quote! { #name { #( #inits ),* } }
+        }
+        Fields::Unnamed(_) => {
+            let inits = values.iter().map(|(binding, _)| binding);
+            quote! { #name ( #( #inits ),* ) }
+        }
+        Fields::Unit => quote! { #name },
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::lerp::Lerp for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                #( #bindings )*
+                #construct
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
@@ -1,33 +1,283 @@
 extern crate proc_macro;
 
-use crate::savable::{enumerator, named, unit, unnamed};
+use crate::builder::builder;
+use crate::enum_iter::enum_iter;
+use crate::savable::{enumerator, named, union, unit, unnamed};
 use proc_macro::TokenStream;
 use std::str::FromStr;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
 mod savable;
+mod builder;
+mod enum_iter;
+mod save_size;
+mod tagged;
+mod schema;
+mod config;
+mod lerp;
+mod accessors;
+mod versioned;
+mod string_pattern;
+mod pod;
+mod dirty;
+mod format_hash;
+mod diff;
+mod flags;
+mod verify;
+mod deep_size;
 
-#[proc_macro_derive(Savable, attributes(unsaved, custom))]
+#[proc_macro_derive(Savable, attributes(unsaved, custom, savable, discriminant, save_order, id))]
 pub fn derive_savable(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
+    let format_hash = format_hash::implement(&input);
+    let deterministic = tagged::is_deterministic(&input.attrs);
     let name = input.ident;
     let generics = input.generics;
 
+    let mut implementation = if tagged::is_tagged(&input.attrs) {
+        match &input.data {
+            Data::Struct(s) => match &s.fields {
+                Fields::Named(fields) => tagged::named(fields, name, generics),
+                Fields::Unnamed(fields) => tagged::unnamed(fields, name, generics),
+                Fields::Unit => unit(name, generics),
+            },
+            _ => panic!("Tagged Savable is only supported for structs!"),
+        }
+    } else {
+        match &input.data {
+            Data::Struct(s) => match &s.fields {
+                Fields::Named(fields) => named(fields, name, generics),
+                Fields::Unnamed(fields) => unnamed(fields, name, generics),
+                Fields::Unit => unit(name, generics),
+            },
+            Data::Enum(e) => enumerator(e, name, generics),
+            Data::Union(u) => union(u, &input.attrs, name, generics),
+        }
+    };
+    if deterministic {
+        implementation = savable::deterministic(implementation);
+    }
+    implementation.extend(TokenStream::from(format_hash));
+    implementation
+}
+
+#[proc_macro_derive(SaveSize, attributes(unsaved, custom, savable, save_order, id))]
+pub fn derive_save_size(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    if tagged::is_tagged(&input.attrs) {
+        return save_size::measured(name, generics);
+    }
+
     match &input.data {
         Data::Struct(s) => match &s.fields {
-            Fields::Named(fields) => named(fields, name, generics),
-            Fields::Unnamed(fields) => unnamed(fields, name, generics),
-            Fields::Unit => unit(name, generics),
+            Fields::Named(fields) => save_size::named(fields, name, generics),
+            Fields::Unnamed(fields) => save_size::unnamed(fields, name, generics),
+            Fields::Unit => save_size::unit(name, generics),
         },
-        Data::Enum(e) => enumerator(e, name, generics),
-        Data::Union(_) => panic!("Deriving Savable for unions is not supported!"),
+        Data::Enum(e) => save_size::enumerator(e, name, generics),
+        Data::Union(_) => panic!("Deriving SaveSize for unions is not supported!"),
     }
 }
 
+#[proc_macro_derive(Schema, attributes(unsaved, custom, savable, save_order, id))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    if tagged::is_tagged(&input.attrs) {
+        panic!("Deriving Schema for tagged structs is not supported!");
+    }
+
+    schema::schema(&input.data, input.ident, input.generics)
+}
+
+#[proc_macro_derive(DirtySavable, attributes(dirty_mask, unsaved, custom))]
+pub fn derive_dirty_savable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => dirty::dirty_savable(fields, name, generics),
+            _ => panic!("Deriving DirtySavable is only supported for structs with named fields!"),
+        },
+        _ => panic!("Deriving DirtySavable is only supported for structs with named fields!"),
+    }
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let vis = input.vis;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => builder(fields, name, vis, generics),
+            _ => panic!("Deriving Builder is only supported for structs with named fields!"),
+        },
+        _ => panic!("Deriving Builder is only supported for structs with named fields!"),
+    }
+}
+
+#[proc_macro_derive(ConfigSection, attributes(config))]
+pub fn derive_config_section(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => config::config_section(fields, name, generics),
+            _ => panic!("Deriving ConfigSection is only supported for structs with named fields!"),
+        },
+        _ => panic!("Deriving ConfigSection is only supported for structs with named fields!"),
+    }
+}
+
+#[proc_macro_derive(Lerp, attributes(lerp))]
+pub fn derive_lerp(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => lerp::lerp(&s.fields, name, generics),
+        _ => panic!("Deriving Lerp is only supported for structs!"),
+    }
+}
+
+#[proc_macro_derive(Diff, attributes(diff))]
+pub fn derive_diff(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => diff::diff_struct(&s.fields, name, generics),
+        Data::Enum(e) => diff::diff_enum(e, name, generics),
+        _ => panic!("Deriving Diff is only supported for structs and enums!"),
+    }
+}
+
+#[proc_macro_derive(Getters, attributes(getter))]
+pub fn derive_getters(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => accessors::getters(fields, name, generics),
+            _ => panic!("Deriving Getters is only supported for structs with named fields!"),
+        },
+        _ => panic!("Deriving Getters is only supported for structs with named fields!"),
+    }
+}
+
+#[proc_macro_derive(Setters, attributes(setter))]
+pub fn derive_setters(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => accessors::setters(fields, name, generics),
+            _ => panic!("Deriving Setters is only supported for structs with named fields!"),
+        },
+        _ => panic!("Deriving Setters is only supported for structs with named fields!"),
+    }
+}
+
+#[proc_macro_derive(Pod)]
+pub fn derive_pod(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => pod::pod(&s.fields, &input.attrs, name, generics),
+        _ => panic!("Deriving Pod is only supported for structs!"),
+    }
+}
+
+#[proc_macro_derive(EnumIter)]
+pub fn derive_enum_iter(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Enum(e) => enum_iter(e, name, generics),
+        _ => panic!("Deriving EnumIter is only supported for enums!"),
+    }
+}
+
+#[proc_macro_derive(Flags)]
+pub fn derive_flags(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Enum(e) => flags::flags(e, input.vis, name, generics),
+        _ => panic!("Deriving Flags is only supported for enums!"),
+    }
+}
+
+#[proc_macro_derive(Verify, attributes(verify))]
+pub fn derive_verify(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => verify::verify_struct(&input.attrs, &s.fields, name, generics),
+        Data::Enum(e) => verify::verify_enum(&input.attrs, e, name, generics),
+        _ => panic!("Deriving Verify is only supported for structs and enums!"),
+    }
+}
+
+#[proc_macro_derive(DeepSizeOf, attributes(deep_size))]
+pub fn derive_deep_size_of(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+    let generics = input.generics;
+
+    match &input.data {
+        Data::Struct(s) => deep_size::deep_size_struct(&s.fields, name, generics),
+        Data::Enum(e) => deep_size::deep_size_enum(e, name, generics),
+        _ => panic!("Deriving DeepSizeOf is only supported for structs and enums!"),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn savable_versioned(attr: TokenStream, input: TokenStream) -> TokenStream {
+    versioned::savable_versioned(attr, input)
+}
+
 #[proc_macro_attribute]
-pub fn try_from_string(_: TokenStream, input: TokenStream) -> TokenStream {
+pub fn try_from_string(args: TokenStream, input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident.clone();
 
@@ -61,6 +311,7 @@ pub fn try_from_string(_: TokenStream, input: TokenStream) -> TokenStream {
                 }
             }.into()
         },
-        _ => panic!("`try_from_string` is only meant for enums")
+        Data::Struct(s) => string_pattern::struct_from_string(args, input.clone(), &s.fields),
+        _ => panic!("`try_from_string` is only meant for enums and structs")
     }
 }
@@ -0,0 +1,40 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Attribute, Fields, Generics, Ident, Meta};
+
+fn has_valid_repr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| match &attr.meta {
+        Meta::List(l) if l.path.is_ident("repr") => {
+            let tokens = l.tokens.to_string();
+            tokens.split(',').any(|t| matches!(t.trim(), "C" | "transparent" | "packed"))
+        }
+        _ => false,
+    })
+}
+
+pub fn pod(fields: &Fields, attrs: &[Attribute], name: Ident, generics: Generics) -> TokenStream {
+    if !generics.params.is_empty() {
+        panic!("Deriving Pod is not supported for generic structs!");
+    }
+    if !has_valid_repr(attrs) {
+        panic!("Deriving Pod requires #[repr(C)], #[repr(transparent)] or #[repr(packed)]!");
+    }
+
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+
+    quote! {
+        const _: () = {
+            fn assert_pod<T: mvutils::unsafe_utils::Pod>() {}
+            #[allow(dead_code)]
+            fn assert_fields() {
+                #( assert_pod::<#types>(); )*
+            }
+            assert!(
+                core::mem::size_of::<#name>() == 0 #( + core::mem::size_of::<#types>() )*,
+                concat!("Pod cannot be derived for ", stringify!(#name), " because it contains padding bytes")
+            );
+        };
+
+        unsafe impl mvutils::unsafe_utils::Pod for #name {}
+    }.into()
+}
@@ -2,7 +2,7 @@ use proc_macro::TokenStream;
 use quote::{quote};
 use std::str::FromStr;
 use syn::__private::Span;
-use syn::{parse, Attribute, DataEnum, Expr, Field, Fields, FieldsNamed, FieldsUnnamed, Generics, Ident, Meta, Token};
+use syn::{parse, Attribute, DataEnum, DataUnion, Expr, Field, Fields, FieldsNamed, FieldsUnnamed, Generics, Ident, Lit, Meta, Token, Variant};
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 
@@ -23,7 +23,7 @@ impl Parse for KeyValue {
     }
 }
 
-fn get_custom(f: &Field) -> Option<(Expr, Expr)> {
+pub(crate) fn get_custom(f: &Field) -> Option<(Expr, Expr)> {
     f.attrs.iter().filter_map(|attr| {
         if let Meta::List(ref l) = attr.meta {
             if l.path.segments.iter().any(|s| s.ident == "custom") {
@@ -45,7 +45,7 @@ fn get_custom(f: &Field) -> Option<(Expr, Expr)> {
     }).next()
 }
 
-fn filter(f: &&Field) -> bool {
+pub(crate) fn filter(f: &&Field) -> bool {
     !f.attrs.iter().any(is_unsaved)
 }
 
@@ -57,14 +57,170 @@ fn is_unsaved(attr: &Attribute) -> bool {
     }
 }
 
+fn get_save_order(f: &Field) -> Option<u64> {
+    f.attrs.iter().find(|attr| attr.path().is_ident("save_order")).map(|attr| {
+        attr.parse_args::<syn::LitInt>()
+            .and_then(|n| n.base10_parse::<u64>())
+            .expect("Expected an integer in save_order attribute")
+    })
+}
+
+/// Sort the saved fields by their `#[save_order(n)]` attribute, keeping declaration order if no field has one.
+pub(crate) fn ordered<T>(fields: Vec<T>, field: impl Fn(&T) -> &Field) -> Vec<T> {
+    let orders = fields.iter().map(|f| get_save_order(field(f))).collect::<Vec<_>>();
+    if orders.iter().all(Option::is_none) {
+        return fields;
+    }
+    if orders.iter().any(Option::is_none) {
+        panic!("Either all saved fields or none must have a save_order attribute!");
+    }
+    let mut sorted = orders.iter().flatten().copied().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    if let Some(w) = sorted.windows(2).find(|w| w[0] == w[1]) {
+        panic!("Duplicate save_order({}) on multiple fields!", w[0]);
+    }
+    let mut keyed = orders.into_iter().flatten().zip(fields).collect::<Vec<_>>();
+    keyed.sort_by_key(|(n, _)| *n);
+    keyed.into_iter().map(|(_, f)| f).collect()
+}
+
+/// Reserve the saved size up front when saving into an empty saver, if the type also implements SaveSize.
+/// Values saved inside a collection are covered by the reservation of the collection.
+fn reserve_hint() -> proc_macro2::TokenStream {
+    quote! {
+        if mvutils::save::Saver::bytes_written(saver) == 0 && mvutils::save::Saver::can_reserve(saver) {
+            if let Some(size) = mvutils::save::Savable::save_size_hint(self) {
+                mvutils::save::Saver::reserve(saver, size);
+            }
+        }
+    }
+}
+
+/// Report the SaveSize of the type as the size hint, if it implements SaveSize.
+fn size_hint() -> proc_macro2::TokenStream {
+    quote! {
+        fn save_size_hint(&self) -> Option<usize> {
+            #[allow(unused_imports)]
+            use mvutils::save::__private::{NoSaveSize as _, ViaSaveSize as _};
+            (&mvutils::save::__private::SizeHint(self)).size_hint()
+        }
+    }
+}
+
+/// Make the generated `save` function wrap the saver in a `Deterministic` saver, for types with
+/// `#[savable(deterministic)]`.
+pub(crate) fn deterministic(implementation: TokenStream) -> TokenStream {
+    let mut file: syn::File = syn::parse(implementation).expect("Failed to parse the generated Savable implementation");
+    for item in &mut file.items {
+        if let syn::Item::Impl(i) = item {
+            for item in &mut i.items {
+                if let syn::ImplItem::Fn(f) = item {
+                    if f.sig.ident == "save" {
+                        f.block.stmts.insert(0, syn::parse_quote! {
+                            let saver = &mut mvutils::save::Deterministic::new(saver);
+                        });
+                    }
+                }
+            }
+        }
+    }
+    quote!(#file).into()
+}
+
+/// Surround the saving of a field with trace hints, which record the field path in a
+/// `TracingSaver` if the `save_trace` feature is enabled.
+fn trace_save(field: String, save: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        mvutils::save::__private::begin_save_field(saver, #field);
+        #save
+        mvutils::save::__private::end_save_field(saver);
+    }
+}
+
+/// Surround the loading of a field with trace hints, see [`trace_save`].
+fn trace_load(field: String, load: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        mvutils::save::__private::begin_load_field(loader, #field);
+        #load
+        mvutils::save::__private::end_load_field(loader);
+    }
+}
+
+fn get_variant_id(v: &Variant) -> Option<u32> {
+    v.attrs.iter().find(|attr| attr.path().is_ident("id")).map(|attr| match &attr.meta {
+        Meta::NameValue(nv) => match &nv.value {
+            Expr::Lit(syn::ExprLit { lit: Lit::Int(n), .. }) => n.base10_parse::<u32>().expect("Expected a u32 in id attribute"),
+            _ => panic!("Expected an integer in id attribute, like #[id = 7]"),
+        },
+        _ => panic!("Expected an integer in id attribute, like #[id = 7]"),
+    })
+}
+
+/// The saved ids of the enum variants. Variants without an `#[id = n]` attribute use the id of the
+/// previous variant plus one, starting at 0, like Rust discriminants.
+pub(crate) fn variant_ids(e: &DataEnum) -> Vec<u32> {
+    let mut next = 0u32;
+    let ids = e.variants.iter().map(|v| {
+        let id = get_variant_id(v).unwrap_or(next);
+        next = id.checked_add(1).unwrap_or_else(|| panic!("Variant id overflow after {}!", v.ident));
+        id
+    }).collect::<Vec<_>>();
+    let mut sorted = ids.clone();
+    sorted.sort_unstable();
+    if let Some(w) = sorted.windows(2).find(|w| w[0] == w[1]) {
+        let names = e.variants.iter().zip(&ids).filter(|(_, id)| **id == w[0]).map(|(v, _)| v.ident.to_string()).collect::<Vec<_>>();
+        panic!("Duplicate variant id {} on {}!", w[0], names.join(" and "));
+    }
+    ids
+}
+
+/// The size in bytes of the saved variant id, 1, 2 or 4.
+pub(crate) fn variant_id_size(ids: &[u32]) -> usize {
+    let bound = ids.iter().map(|id| *id as u64 + 1).max().unwrap_or(0).max(ids.len() as u64);
+    if bound < 256 {
+        1
+    } else if bound < 65536 {
+        2
+    } else {
+        4
+    }
+}
+
 pub fn named(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let (save, load) = named_body(fields);
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let reserve = reserve_hint();
+    let size_hint = size_hint();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
+            fn save(&self, saver: &mut impl mvutils::save::Saver) {
+                #reserve
+                #save
+            }
+
+            #size_hint
+
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
+                #load
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
+
+/// The bodies of the save and load functions for a struct with named fields.
+pub(crate) fn named_body(fields: &FieldsNamed) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     let (fields, unsaved_fields): (Vec<_>, Vec<_>) = fields.named.iter().partition(filter);
 
-    let fields = fields.into_iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
+    let fields = ordered(fields, |f| f).into_iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
 
     let save_fields = fields.iter().map(|(f, custom)| {
         let name = &f.ident;
-        if let Some((save, _)) = custom {
+        let save = if let Some((save, _)) = custom {
             quote! {
                 #save(saver, &self.#name);
             }
@@ -72,21 +228,23 @@ pub fn named(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStre
             quote! {
                 mvutils::save::Savable::save(&self.#name, saver);
             }
-        }
+        };
+        trace_save(f.ident.as_ref().unwrap().to_string(), save)
     });
 
     let load_fields = fields.iter().map(|(f, custom)| {
         let name = &f.ident;
         let ty = &f.ty;
-        if let Some((_, load)) = custom {
+        let load = if let Some((_, load)) = custom {
             quote! {
-            let #name = #load(loader)?;
+            let #name = #load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(stringify!(#name)))?;
         }
         } else {
             quote! {
-                let #name = <#ty as mvutils::save::Savable>::load(loader)?;
+                let #name = <#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(stringify!(#name)))?;
             }
-        }
+        };
+        trace_load(f.ident.as_ref().unwrap().to_string(), load)
     });
 
     let load_default_fields = unsaved_fields.iter().map(|f| {
@@ -111,24 +269,44 @@ pub fn named(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStre
         }
     });
 
+    let t1 = if !fields.is_empty() { quote!{,} } else { quote!{} };
+
+    let save = quote! {
+        #( #save_fields )*
+    };
+
+    let load = quote! {
+        #( #load_fields )*
+        #( #load_default_fields )*
+
+        Ok(Self {
+            #( #init_struct ),*#t1
+            #( #init_default_struct ),*
+        })
+    };
+
+    (save, load)
+}
+
+pub fn unnamed(fields: &FieldsUnnamed, name: Ident, generics: Generics) -> TokenStream {
+    let (save, load) = unnamed_body(fields);
+
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let t1 = if !fields.is_empty() { quote!{,} } else { quote!{} };
+    let reserve = reserve_hint();
+    let size_hint = size_hint();
 
     let implementation = quote! {
         impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
             fn save(&self, saver: &mut impl mvutils::save::Saver) {
-                #( #save_fields )*
+                #reserve
+                #save
             }
 
-            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, String> {
-                #( #load_fields )*
-                #( #load_default_fields )*
+            #size_hint
 
-                Ok(Self {
-                    #( #init_struct ),*#t1
-                    #( #init_default_struct ),*
-                })
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
+                #load
             }
         }
     };
@@ -136,17 +314,18 @@ pub fn named(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStre
     TokenStream::from(implementation)
 }
 
-pub fn unnamed(fields: &FieldsUnnamed, name: Ident, generics: Generics) -> TokenStream {
+/// The bodies of the save and load functions for a tuple struct.
+pub(crate) fn unnamed_body(fields: &FieldsUnnamed) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     let fields: Vec<_> = fields.unnamed.iter().enumerate().collect();
     let amount = fields.len();
 
     let (fields, unsaved_fields): (Vec<_>, Vec<_>) = fields.iter().partition(|(_, f)| filter(f));
 
-    let fields = fields.into_iter().map(|(i, f)| (i, (f, get_custom(f)))).collect::<Vec<_>>();
+    let fields = ordered(fields, |(_, f)| f).into_iter().map(|(i, f)| (i, (f, get_custom(f)))).collect::<Vec<_>>();
 
     let save_fields = fields.iter().map(|(i, (_, custom))| {
         let i = proc_macro2::TokenStream::from_str(&i.to_string()).unwrap();
-        if let Some((save, _)) = custom {
+        let save = if let Some((save, _)) = custom {
             quote! {
                 #save(saver, &self.#i);
             }
@@ -154,21 +333,24 @@ pub fn unnamed(fields: &FieldsUnnamed, name: Ident, generics: Generics) -> Token
             quote! {
                 mvutils::save::Savable::save(&self.#i, saver);
             }
-        }
+        };
+        trace_save(i.to_string(), save)
     });
 
     let load_fields = fields.iter().map(|(i, (f, custom))| {
         let ty = &f.ty;
         let key = key(*i as u32);
-        if let Some((_, load)) = custom {
+        let index = i.to_string();
+        let load = if let Some((_, load)) = custom {
             quote! {
-                let #key = #load(loader)?;
+                let #key = #load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(#index))?;
             }
         } else {
             quote! {
-                let #key = <#ty as mvutils::save::Savable>::load(loader)?;
+                let #key = <#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(#index))?;
             }
-        }
+        };
+        trace_load(index.clone(), load)
     });
 
     let load_unsaved_fields = unsaved_fields.iter().map(|(i, f)| {
@@ -187,23 +369,17 @@ pub fn unnamed(fields: &FieldsUnnamed, name: Ident, generics: Generics) -> Token
         });
     }
 
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-
-    let implementation = quote! {
-        impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
-            fn save(&self, saver: &mut impl mvutils::save::Saver) {
-                #( #save_fields )*
-            }
+    let save = quote! {
+        #( #save_fields )*
+    };
 
-            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, String> {
-                #( #load_fields )*
-                #( #load_unsaved_fields )*
-                Ok(Self(#( #names ),*))
-            }
-        }
+    let load = quote! {
+        #( #load_fields )*
+        #( #load_unsaved_fields )*
+        Ok(Self(#( #names ),*))
     };
 
-    TokenStream::from(implementation)
+    (save, load)
 }
 
 pub fn unit(name: Ident, generics: Generics) -> TokenStream {
@@ -213,7 +389,7 @@ pub fn unit(name: Ident, generics: Generics) -> TokenStream {
         impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
             fn save(&self, saver: &mut impl mvutils::save::Saver) {}
 
-            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, String> {
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
                 Ok(Self)
             }
         }
@@ -223,16 +399,14 @@ pub fn unit(name: Ident, generics: Generics) -> TokenStream {
 }
 
 pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream {
-    let len = e.variants.len();
-    let id_ty = if len < 256 {
-        quote! { u8 }
-    } else if len < 65536 {
-        quote! { u16 }
-    } else {
-        quote! { u32 }
+    let ids = variant_ids(e);
+    let id_ty = match variant_id_size(&ids) {
+        1 => quote! { u8 },
+        2 => quote! { u16 },
+        _ => quote! { u32 },
     };
 
-    let save = e.variants.iter().enumerate().map(|(i, v)| {
+    let save = e.variants.iter().zip(&ids).map(|(v, i)| {
         let ident = &v.ident;
         match &v.fields {
             Fields::Named(fields) => {
@@ -245,11 +419,11 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
 
                 let (fields, _): (Vec<_>, Vec<_>) = fields.named.iter().partition(filter);
 
-                let fields = fields.into_iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
+                let fields = ordered(fields, |f| f).into_iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
 
                 let saves = fields.iter().map(|(f, custom)| {
                     let name = &f.ident;
-                    if let Some((save, _)) = custom {
+                    let save = if let Some((save, _)) = custom {
                         quote! {
                             #save(saver, #name);
                         }
@@ -257,7 +431,8 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
                         quote! {
                             mvutils::save::Savable::save(#name, saver);
                         }
-                    }
+                    };
+                    trace_save(f.ident.as_ref().unwrap().to_string(), save)
                 });
 
                 quote! {
@@ -273,11 +448,11 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
 
                 let (fields, _): (Vec<_>, Vec<_>) = fields.iter().partition(|(_, f)| filter(f));
 
-                let fields = fields.into_iter().map(|(i, f)| (i, (f, get_custom(f)))).collect::<Vec<_>>();
+                let fields = ordered(fields, |(_, f)| f).into_iter().map(|(i, f)| (i, (f, get_custom(f)))).collect::<Vec<_>>();
 
                 let saves = fields.iter().map(|(i, (_, custom))| {
                     let name = key(*i as u32);
-                    if let Some((save, _)) = custom {
+                    let save = if let Some((save, _)) = custom {
                         quote! {
                             #save(saver, #name);
                         }
@@ -285,7 +460,8 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
                         quote! {
                             mvutils::save::Savable::save(#name, saver);
                         }
-                    }
+                    };
+                    trace_save(i.to_string(), save)
                 });
 
                 let mut names = Vec::with_capacity(amount);
@@ -311,15 +487,14 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
         }
     });
 
-    let load = e.variants.iter().enumerate().map(|(i, v)| {
+    let load = e.variants.iter().zip(&ids).map(|(v, i)| {
         let ident = &v.ident;
-        let i = i as u32;
         match &v.fields {
             Fields::Named(fields) => {
                 let (fields, unsaved_fields): (Vec<_>, Vec<_>) =
                     fields.named.iter().partition(filter);
 
-                let fields = fields.into_iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
+                let fields = ordered(fields, |f| f).into_iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
 
                 let names = fields.iter().map(|(f, _)| {
                     let name = &f.ident;
@@ -331,15 +506,16 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
                 let load_fields = fields.iter().map(|(f, custom)| {
                     let name = &f.ident;
                     let ty = &f.ty;
-                    if let Some((_, load)) = custom {
+                    let load = if let Some((_, load)) = custom {
                         quote! {
-                            let #name = #load(loader)?;
+                            let #name = #load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(stringify!(#name)))?;
                         }
                     } else {
                         quote! {
-                            let #name = <#ty as mvutils::save::Savable>::load(loader)?;
+                            let #name = <#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(stringify!(#name)))?;
                         }
-                    }
+                    };
+                    trace_load(f.ident.as_ref().unwrap().to_string(), load)
                 });
 
                 let load_default_fields = unsaved_fields.iter().map(|f| {
@@ -377,20 +553,22 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
 
                 let (fields, unsaved_fields): (Vec<_>, Vec<_>) = fields.iter().partition(|(_, f)| filter(f));
 
-                let fields = fields.into_iter().map(|(i, f)| (i, (f, get_custom(f)))).collect::<Vec<_>>();
+                let fields = ordered(fields, |(_, f)| f).into_iter().map(|(i, f)| (i, (f, get_custom(f)))).collect::<Vec<_>>();
 
                 let loads = fields.iter().map(|(i, (f, custom))| {
                     let name = key(*i as u32);
+                    let index = i.to_string();
                     let ty = &f.ty;
-                    if let Some((_, load)) = custom {
+                    let load = if let Some((_, load)) = custom {
                         quote! {
-                            let #name = #load(loader)?;
+                            let #name = #load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(#index))?;
                         }
                     } else {
                         quote! {
-                            let #name = <#ty as mvutils::save::Savable>::load(loader)?;
+                            let #name = <#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(#index))?;
                         }
-                    }
+                    };
+                    trace_load(index.clone(), load)
                 });
 
                 let unsaved_loads = unsaved_fields.iter().map(|(i, f)| {
@@ -427,18 +605,24 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
 
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    let reserve = reserve_hint();
+    let size_hint = size_hint();
+
     let implementation = quote! {
         impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
             fn save(&self, saver: &mut impl mvutils::save::Saver) {
+                #reserve
                 match self {
                     #( #save )*
                 }
             }
 
-            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, String> {
+            #size_hint
+
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
                 match #id_ty::load(loader)? as u32 {
                     #( #load )*
-                    _ => Err(format!("Failed to load {} from loader!", stringify!(#name)))
+                    other => Err(mvutils::save::SaveError::InvalidDiscriminant { ty: stringify!(#name), value: other as u64 })
                 }
             }
         }
@@ -447,7 +631,7 @@ pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream
     TokenStream::from(implementation)
 }
 
-fn key(mut n: u32) -> Ident {
+pub(crate) fn key(mut n: u32) -> Ident {
     let mut result = String::new();
     loop {
         let remainder = (n % 26) as u8;
@@ -461,3 +645,107 @@ fn key(mut n: u32) -> Ident {
     let s = result.chars().rev().collect::<String>();
     Ident::new_raw(&s, Span::call_site())
 }
+
+fn get_discriminant(attrs: &[Attribute]) -> Option<Expr> {
+    attrs.iter().filter_map(|attr| {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("discriminant") {
+                return Some(syn::parse2::<Expr>(l.tokens.clone()).expect("Expected a field or function path for discriminant attribute"));
+            }
+        }
+        None
+    }).next()
+}
+
+pub fn union(u: &DataUnion, attrs: &[Attribute], name: Ident, generics: Generics) -> TokenStream {
+    let discriminant = get_discriminant(attrs).unwrap_or_else(|| {
+        panic!("Deriving Savable for unions requires a #[discriminant(field_or_fn)] attribute and an unsafe impl of mvutils::save::UnionDiscriminant!")
+    });
+
+    let discriminant = match &discriminant {
+        Expr::Path(p) if u.fields.named.iter().any(|f| f.ident.as_ref().is_some_and(|i| p.path.is_ident(i))) => quote! {
+            (unsafe { self.#discriminant }) as usize
+        },
+        Expr::Path(_) => quote! {
+            (#discriminant)(self) as usize
+        },
+        _ => panic!("Expected a field or function path for discriminant attribute"),
+    };
+
+    let len = u.fields.named.len();
+    let id_ty = if len < 256 {
+        quote! { u8 }
+    } else if len < 65536 {
+        quote! { u16 }
+    } else {
+        quote! { u32 }
+    };
+
+    let fields = u.fields.named.iter().map(|f| (f, get_custom(f))).collect::<Vec<_>>();
+
+    let save = fields.iter().enumerate().map(|(i, (f, custom))| {
+        let field = &f.ident;
+        let save = if let Some((save, _)) = custom {
+            quote! {
+                #save(saver, unsafe { &self.#field });
+            }
+        } else {
+            quote! {
+                mvutils::save::Savable::save(unsafe { &self.#field }, saver);
+            }
+        };
+        quote! {
+            #i => {
+                mvutils::save::Savable::save(&(#i as #id_ty), saver);
+                #save
+            }
+        }
+    });
+
+    let load = fields.iter().enumerate().map(|(i, (f, custom))| {
+        let field = &f.ident;
+        let ty = &f.ty;
+        let i = i as u32;
+        if let Some((_, load)) = custom {
+            quote! {
+                #i => Ok(#name { #field: #load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(stringify!(#field)))? }),
+            }
+        } else {
+            quote! {
+                #i => Ok(#name { #field: <#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(stringify!(#field)))? }),
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let reserve = reserve_hint();
+    let size_hint = size_hint();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
+            fn save(&self, saver: &mut impl mvutils::save::Saver) {
+                // The discriminant is only trusted to select the field to read if the type
+                // implements the unsafe UnionDiscriminant trait.
+                fn requires_union_discriminant<T: mvutils::save::UnionDiscriminant + ?Sized>() {}
+                requires_union_discriminant::<Self>();
+                #reserve
+                match #discriminant {
+                    #( #save )*
+                    other => panic!("Invalid discriminant {} when saving {}!", other, stringify!(#name)),
+                }
+            }
+
+            #size_hint
+
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
+                match <#id_ty as mvutils::save::Savable>::load(loader)? as u32 {
+                    #( #load )*
+                    other => Err(mvutils::save::SaveError::InvalidDiscriminant { ty: stringify!(#name), value: other as u64 })
+                }
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
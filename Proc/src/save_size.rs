@@ -0,0 +1,133 @@
+use crate::savable::{filter, get_custom, key, variant_id_size, variant_ids};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::str::FromStr;
+use syn::{DataEnum, Field, Fields, FieldsNamed, FieldsUnnamed, Generics, Ident};
+
+fn field_size(f: &Field, access: TokenStream2) -> (TokenStream2, TokenStream2) {
+    let ty = &f.ty;
+    if let Some((save, _)) = get_custom(f) {
+        (
+            quote! { None },
+            quote! { mvutils::save::SizeCounter::measure(|saver| #save(saver, #access)) },
+        )
+    } else {
+        (
+            quote! { <#ty as mvutils::save::SaveSize>::FIXED_SIZE },
+            quote! { mvutils::save::SaveSize::save_size(#access) },
+        )
+    }
+}
+
+fn implement(name: Ident, generics: Generics, fixed: TokenStream2, size: TokenStream2) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::save::SaveSize for #name #ty_generics #where_clause {
+            const FIXED_SIZE: Option<usize> = #fixed;
+
+            fn save_size(&self) -> usize {
+                #size
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
+
+pub fn named(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let (fixed, sizes): (Vec<_>, Vec<_>) = fields.named.iter().filter(filter).map(|f| {
+        let name = &f.ident;
+        field_size(f, quote! { &self.#name })
+    }).unzip();
+
+    implement(
+        name,
+        generics,
+        quote! { mvutils::save::fixed_sum(&[#( #fixed ),*]) },
+        quote! { 0 #( + #sizes )* },
+    )
+}
+
+pub fn unnamed(fields: &FieldsUnnamed, name: Ident, generics: Generics) -> TokenStream {
+    let (fixed, sizes): (Vec<_>, Vec<_>) = fields.unnamed.iter().enumerate().filter(|(_, f)| filter(f)).map(|(i, f)| {
+        let i = TokenStream2::from_str(&i.to_string()).unwrap();
+        field_size(f, quote! { &self.#i })
+    }).unzip();
+
+    implement(
+        name,
+        generics,
+        quote! { mvutils::save::fixed_sum(&[#( #fixed ),*]) },
+        quote! { 0 #( + #sizes )* },
+    )
+}
+
+pub fn measured(name: Ident, generics: Generics) -> TokenStream {
+    implement(
+        name,
+        generics,
+        quote! { None },
+        quote! { mvutils::save::SizeCounter::measure(|saver| mvutils::save::Savable::save(self, saver)) },
+    )
+}
+
+pub fn unit(name: Ident, generics: Generics) -> TokenStream {
+    implement(name, generics, quote! { Some(0) }, quote! { 0 })
+}
+
+pub fn enumerator(e: &DataEnum, name: Ident, generics: Generics) -> TokenStream {
+    let id_size = variant_id_size(&variant_ids(e));
+
+    let (fixed, sizes): (Vec<_>, Vec<_>) = e.variants.iter().map(|v| {
+        let ident = &v.ident;
+        match &v.fields {
+            Fields::Named(fields) => {
+                let saved = fields.named.iter().filter(filter).collect::<Vec<_>>();
+                let names = saved.iter().map(|f| &f.ident);
+                let (fixed, sizes): (Vec<_>, Vec<_>) = saved.iter().map(|f| {
+                    let name = &f.ident;
+                    field_size(f, quote! { #name })
+                }).unzip();
+                (
+                    quote! { mvutils::save::fixed_sum(&[Some(#id_size), #( #fixed ),*]) },
+                    quote! { #name::#ident { #( #names, )* .. } => #id_size #( + #sizes )*, },
+                )
+            }
+            Fields::Unnamed(fields) => {
+                let names = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                    if filter(&f) {
+                        let key = key(i as u32);
+                        quote! { #key }
+                    } else {
+                        quote! { _ }
+                    }
+                });
+                let (fixed, sizes): (Vec<_>, Vec<_>) = fields.unnamed.iter().enumerate().filter(|(_, f)| filter(f)).map(|(i, f)| {
+                    let key = key(i as u32);
+                    field_size(f, quote! { #key })
+                }).unzip();
+                (
+                    quote! { mvutils::save::fixed_sum(&[Some(#id_size), #( #fixed ),*]) },
+                    quote! { #name::#ident( #( #names ),* ) => #id_size #( + #sizes )*, },
+                )
+            }
+            Fields::Unit => (
+                quote! { Some(#id_size) },
+                quote! { #name::#ident => #id_size, },
+            ),
+        }
+    }).unzip();
+
+    implement(
+        name.clone(),
+        generics,
+        quote! { mvutils::save::fixed_same(&[#( #fixed ),*]) },
+        quote! {
+            match self {
+                #( #sizes )*
+            }
+        },
+    )
+}
@@ -0,0 +1,99 @@
+use crate::savable::{filter, get_custom, ordered, variant_id_size, variant_ids};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DataEnum, Field, Fields, Generics, Ident};
+
+fn field_def(f: &Field, name: String) -> (TokenStream2, TokenStream2) {
+    let ty = &f.ty;
+    if get_custom(f).is_some() {
+        (
+            quote! {},
+            quote! { mvutils::save::schema::FieldDef::new(#name, mvutils::save::schema::SchemaType::Opaque) },
+        )
+    } else {
+        (
+            quote! { <#ty as mvutils::save::schema::Schema>::definitions(definitions); },
+            quote! { mvutils::save::schema::FieldDef::new(#name, <#ty as mvutils::save::schema::Schema>::schema_type()) },
+        )
+    }
+}
+
+fn field_defs(fields: &Fields) -> (Vec<TokenStream2>, Vec<TokenStream2>) {
+    match fields {
+        Fields::Named(fields) => ordered(fields.named.iter().filter(filter).collect(), |f| f).into_iter().map(|f| {
+            field_def(f, f.ident.as_ref().unwrap().to_string())
+        }).unzip(),
+        Fields::Unnamed(fields) => ordered(fields.unnamed.iter().enumerate().filter(|(_, f)| filter(f)).collect(), |(_, f)| f).into_iter().map(|(i, f)| {
+            field_def(f, format!("_{i}"))
+        }).unzip(),
+        Fields::Unit => (vec![], vec![]),
+    }
+}
+
+fn enum_def(e: &DataEnum, name: &Ident) -> (Vec<TokenStream2>, TokenStream2) {
+    let ids = variant_ids(e);
+    let tag = match variant_id_size(&ids) {
+        1 => quote! { U8 },
+        2 => quote! { U16 },
+        _ => quote! { U32 },
+    };
+
+    let mut all_deps = Vec::new();
+    let variants = e.variants.iter().zip(&ids).map(|(v, id)| {
+        let variant = v.ident.to_string();
+        let (deps, fields) = field_defs(&v.fields);
+        all_deps.extend(deps);
+        quote! {
+            mvutils::save::schema::VariantDef {
+                name: #variant.to_string(),
+                id: #id,
+                fields: vec![#( #fields ),*],
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    (all_deps, quote! {
+        mvutils::save::schema::TypeDef::Enum {
+            name: stringify!(#name),
+            tag: mvutils::save::schema::SchemaType::#tag,
+            variants: vec![#( #variants ),*],
+        }
+    })
+}
+
+pub fn schema(data: &Data, name: Ident, generics: Generics) -> TokenStream {
+    let (deps, def) = match data {
+        Data::Struct(s) => {
+            let (deps, fields) = field_defs(&s.fields);
+            (deps, quote! {
+                mvutils::save::schema::TypeDef::Struct {
+                    name: stringify!(#name),
+                    fields: vec![#( #fields ),*],
+                }
+            })
+        }
+        Data::Enum(e) => enum_def(e, &name),
+        Data::Union(_) => panic!("Deriving Schema for unions is not supported!"),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::save::schema::Schema for #name #ty_generics #where_clause {
+            fn schema_type() -> mvutils::save::schema::SchemaType {
+                mvutils::save::schema::SchemaType::Named(stringify!(#name))
+            }
+
+            fn definitions(definitions: &mut mvutils::save::schema::Definitions) {
+                if !definitions.visit(stringify!(#name)) {
+                    return;
+                }
+                #( #deps )*
+                definitions.push(#def);
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
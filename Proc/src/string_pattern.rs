@@ -0,0 +1,150 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{DeriveInput, Expr, Fields, Ident, Lit, Meta, Token, Type};
+
+enum Segment {
+    Literal(String),
+    Field(String),
+}
+
+fn parse_pattern(pattern: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '{' {
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            let field = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+            if let Some(Segment::Field(previous)) = segments.last() {
+                panic!("Fields '{}' and '{}' must be separated by a literal in the pattern", previous, field);
+            }
+            segments.push(Segment::Field(field.trim().to_string()));
+        } else {
+            literal.push(c);
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    segments
+}
+
+fn get_args(args: TokenStream) -> Vec<Segment> {
+    let metas = syn::parse::Parser::parse(Punctuated::<Meta, Token![,]>::parse_terminated, args)
+        .expect("Expected 'pattern = \"...\"' or 'delimiter = \"...\"' in try_from_string attribute");
+    let string = |value: &Expr| match value {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => s.value(),
+            _ => panic!("Expected a string literal in try_from_string attribute"),
+        },
+        _ => panic!("Expected a string literal in try_from_string attribute"),
+    };
+    match metas.first() {
+        Some(Meta::NameValue(nv)) if nv.path.is_ident("pattern") => parse_pattern(&string(&nv.value)),
+        Some(Meta::NameValue(nv)) if nv.path.is_ident("delimiter") => vec![Segment::Literal(string(&nv.value))],
+        None => vec![Segment::Literal(",".to_string())],
+        _ => panic!("Expected 'pattern = \"...\"' or 'delimiter = \"...\"' in try_from_string attribute"),
+    }
+}
+
+pub fn struct_from_string(args: TokenStream, input: DeriveInput, fields: &Fields) -> TokenStream {
+    let name = &input.ident;
+
+    let fields: Vec<(String, Ident, &Type)> = fields.iter().enumerate().map(|(i, f)| match &f.ident {
+        Some(ident) => (ident.to_string(), format_ident!("__{}", ident), &f.ty),
+        None => (i.to_string(), format_ident!("__field{}", i), &f.ty),
+    }).collect();
+
+    let mut segments = get_args(args);
+    if let [Segment::Literal(delimiter)] = segments.as_slice() {
+        let delimiter = delimiter.clone();
+        segments = fields.iter().enumerate().flat_map(|(i, (field, _, _))| {
+            let mut parts = vec![Segment::Field(field.clone())];
+            if i + 1 < fields.len() {
+                parts.push(Segment::Literal(delimiter.clone()));
+            }
+            parts
+        }).collect();
+    }
+
+    for (field, _, _) in &fields {
+        if !segments.iter().any(|s| matches!(s, Segment::Field(f) if f == field)) {
+            panic!("Field '{}' is missing from the try_from_string pattern", field);
+        }
+    }
+
+    let mut steps = Vec::<TokenStream2>::new();
+    for (i, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Literal(literal) if i == 0 => steps.push(quote! {
+                let rest = rest.strip_prefix(#literal).ok_or_else(|| format!("Expected '{}' at the start of '{}'", #literal, value))?;
+            }),
+            Segment::Literal(_) => {}
+            Segment::Field(field) => {
+                let (_, var, ty) = fields.iter().find(|(f, _, _)| f == field)
+                    .unwrap_or_else(|| panic!("Unknown field '{}' in try_from_string pattern", field));
+                let split = match segments.get(i + 1) {
+                    Some(Segment::Literal(literal)) => quote! {
+                        let (part, rest) = rest.split_once(#literal).ok_or_else(|| format!("Expected '{}' after field {} in '{}'", #literal, #field, value))?;
+                    },
+                    _ => quote! {
+                        let (part, rest) = (rest, "");
+                    },
+                };
+                steps.push(quote! {
+                    #split
+                    let #var = part.trim().parse::<#ty>().map_err(|_| format!("Invalid value '{}' for field {} in '{}'", part.trim(), #field, value))?;
+                });
+            }
+        }
+    }
+
+    let construct = match &input.data {
+        syn::Data::Struct(s) => match &s.fields {
+            Fields::Named(_) => {
+                let inits = s.fields.iter().zip(fields.iter()).map(|(f, (_, var, _))| {
+                    let ident = &f.ident;
+                    quote! { #ident: #var }
+                });
+                quote! { #name { #( #inits ),* } }
+            }
+            Fields::Unnamed(_) => {
+                let vars = fields.iter().map(|(_, var, _)| var);
+                quote! { #name( #( #vars ),* ) }
+            }
+            Fields::Unit => quote! { #name },
+        },
+        _ => unreachable!(),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        #input
+
+        impl #impl_generics core::str::FromStr for #name #ty_generics #where_clause {
+            type Err = String;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                let rest = value;
+                #( #steps )*
+                if !rest.is_empty() {
+                    return Err(format!("Unexpected '{}' at the end of '{}'", rest, value));
+                }
+                Ok(#construct)
+            }
+        }
+
+        impl #impl_generics core::convert::TryFrom<String> for #name #ty_generics #where_clause {
+            type Error = String;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                value.parse()
+            }
+        }
+    }.into()
+}
@@ -0,0 +1,193 @@
+use crate::savable::{filter, get_custom, key};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::HashSet;
+use std::str::FromStr;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, Field, FieldsNamed, FieldsUnnamed, Generics, Ident, Lit, Meta, Token};
+
+fn savable_args(attrs: &[Attribute]) -> Vec<Meta> {
+    attrs.iter().filter(|attr| attr.path().is_ident("savable")).flat_map(|attr| {
+        attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .expect("Invalid savable attribute")
+    }).collect()
+}
+
+pub fn is_tagged(attrs: &[Attribute]) -> bool {
+    savable_args(attrs).iter().any(|m| m.path().is_ident("tagged"))
+}
+
+pub fn is_deterministic(attrs: &[Attribute]) -> bool {
+    savable_args(attrs).iter().any(|m| m.path().is_ident("deterministic"))
+}
+
+fn get_tag(f: &Field) -> Option<u16> {
+    savable_args(&f.attrs).into_iter().find_map(|m| {
+        if let Meta::NameValue(nv) = m {
+            if nv.path.is_ident("tag") {
+                if let Expr::Lit(ref lit) = nv.value {
+                    if let Lit::Int(ref i) = lit.lit {
+                        return Some(i.base10_parse::<u16>().expect("Expected field tag to fit into a u16"));
+                    }
+                }
+                panic!("Expected field tag to be an integer literal");
+            }
+        }
+        None
+    })
+}
+
+struct TaggedField<'a> {
+    field: &'a Field,
+    tag: u16,
+    access: TokenStream2,
+    var: Ident,
+    label: String,
+    custom: Option<(Expr, Expr)>,
+}
+
+fn tagged_fields<'a>(fields: impl Iterator<Item = (usize, &'a Field, TokenStream2, Ident)>) -> Vec<TaggedField<'a>> {
+    let mut tags = HashSet::new();
+    fields.filter(|(_, f, _, _)| filter(f)).map(|(i, field, access, var)| {
+        let tag = get_tag(field).unwrap_or(i as u16);
+        if !tags.insert(tag) {
+            panic!("Duplicate savable tag {} on field '{}'", tag, var);
+        }
+        TaggedField {
+            field,
+            tag,
+            access,
+            var,
+            label: field.ident.as_ref().map_or(i.to_string(), |i| i.to_string()),
+            custom: get_custom(field),
+        }
+    }).collect()
+}
+
+fn implement(name: &Ident, generics: &Generics, fields: &[TaggedField], unsaved: &[(&Field, Ident)], init: TokenStream2) -> TokenStream {
+    let count = fields.len() as u16;
+
+    let save_fields = fields.iter().map(|f| {
+        let tag = f.tag;
+        let access = &f.access;
+        let save = if let Some((save, _)) = &f.custom {
+            quote! { #save(saver, &#access) }
+        } else {
+            quote! { mvutils::save::Savable::save(&#access, saver) }
+        };
+        quote! {
+            saver.push_u16(#tag);
+            if let Some(start) = mvutils::save::__private::begin_sized(saver) {
+                #save;
+                mvutils::save::__private::end_sized(saver, start);
+            } else {
+                let mut buffer = mvutils::save::__private::sized_buffer();
+                {
+                    let saver = &mut buffer;
+                    #save;
+                }
+                mvutils::save::__private::push_sized(saver, &buffer);
+            }
+        }
+    });
+
+    let declare_fields = fields.iter().map(|f| {
+        let var = &f.var;
+        let ty = &f.field.ty;
+        quote! {
+            let mut #var: Option<#ty> = None;
+        }
+    });
+
+    let load_fields = fields.iter().map(|f| {
+        let tag = f.tag;
+        let var = &f.var;
+        let ty = &f.field.ty;
+        let label = &f.label;
+        if let Some((_, load)) = &f.custom {
+            quote! {
+                #tag => #var = Some(#load(loader).map_err(|e| mvutils::save::SaveError::from(e).in_field(#label))?),
+            }
+        } else {
+            quote! {
+                #tag => #var = Some(<#ty as mvutils::save::Savable>::load(loader).map_err(|e| e.in_field(#label))?),
+            }
+        }
+    });
+
+    let default_fields = fields.iter().map(|f| {
+        let var = &f.var;
+        let ty = &f.field.ty;
+        quote! {
+            let #var = #var.unwrap_or_else(<#ty as Default>::default);
+        }
+    });
+
+    let unsaved_fields = unsaved.iter().map(|(f, var)| {
+        let ty = &f.ty;
+        quote! {
+            let #var = <#ty as Default>::default();
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
+            fn save(&self, saver: &mut impl mvutils::save::Saver) {
+                saver.push_u16(#count);
+                #( #save_fields )*
+            }
+
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
+                #( #declare_fields )*
+
+                let count = <u16 as mvutils::save::Savable>::load(loader)?;
+                for _ in 0..count {
+                    let tag = <u16 as mvutils::save::Savable>::load(loader)?;
+                    let len = <u32 as mvutils::save::Savable>::load(loader)?;
+                    match tag {
+                        #( #load_fields )*
+                        _ => {
+                            mvutils::save::Loader::skip(loader, len as usize)?;
+                        }
+                    }
+                }
+
+                #( #default_fields )*
+                #( #unsaved_fields )*
+
+                Ok(#init)
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
+
+pub fn named(fields: &FieldsNamed, name: Ident, generics: Generics) -> TokenStream {
+    let tagged = tagged_fields(fields.named.iter().enumerate().map(|(i, f)| {
+        let ident = f.ident.clone().unwrap();
+        (i, f, quote! { self.#ident }, ident)
+    }));
+
+    let unsaved = fields.named.iter().filter(|f| !filter(f)).map(|f| (f, f.ident.clone().unwrap())).collect::<Vec<_>>();
+
+    let names = fields.named.iter().map(|f| &f.ident);
+
+    implement(&name, &generics, &tagged, &unsaved, quote! { Self { #( #names ),* } })
+}
+
+pub fn unnamed(fields: &FieldsUnnamed, name: Ident, generics: Generics) -> TokenStream {
+    let tagged = tagged_fields(fields.unnamed.iter().enumerate().map(|(i, f)| {
+        let index = TokenStream2::from_str(&i.to_string()).unwrap();
+        (i, f, quote! { self.#index }, key(i as u32))
+    }));
+
+    let unsaved = fields.unnamed.iter().enumerate().filter(|(_, f)| !filter(f)).map(|(i, f)| (f, key(i as u32))).collect::<Vec<_>>();
+
+    let names = (0..fields.unnamed.len()).map(|i| key(i as u32));
+
+    implement(&name, &generics, &tagged, &unsaved, quote! { Self( #( #names ),* ) })
+}
@@ -0,0 +1,183 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse, Attribute, DataEnum, Expr, ExprPath, Field, Fields, Generics, Ident, Index, Meta, Token};
+
+#[derive(Default)]
+struct VerifyAttr {
+    skip: bool,
+    range: Option<Expr>,
+    with: Vec<ExprPath>,
+}
+
+impl Parse for VerifyAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = VerifyAttr::default();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "skip" => attr.skip = true,
+                "range" => {
+                    input.parse::<Token![=]>()?;
+                    attr.range = Some(input.parse()?);
+                }
+                "with" => {
+                    input.parse::<Token![=]>()?;
+                    attr.with.push(input.parse()?);
+                }
+                other => panic!("Unknown verify attribute '{}', expected 'skip', 'range = ...' or 'with = ...'", other),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attr)
+    }
+}
+
+fn get_attr(attrs: &[Attribute]) -> VerifyAttr {
+    let mut result = VerifyAttr::default();
+    for attr in attrs {
+        if let Meta::List(ref l) = attr.meta {
+            if l.path.is_ident("verify") {
+                let tokens: TokenStream = l.tokens.clone().into();
+                let attr = parse::Parser::parse(VerifyAttr::parse, tokens).unwrap();
+                result.skip |= attr.skip;
+                result.range = attr.range.or(result.range);
+                result.with.extend(attr.with);
+            }
+        }
+    }
+    result
+}
+
+fn field_name(f: &Field, i: usize) -> String {
+    match &f.ident {
+        Some(ident) => ident.to_string(),
+        None => i.to_string(),
+    }
+}
+
+/// The checks of a value at `path`, which is a `&str` expression.
+fn checks(attr: &VerifyAttr, value: TokenStream2, path: TokenStream2) -> TokenStream2 {
+    if attr.skip {
+        return quote! {};
+    }
+    let range = attr.range.as_ref().map(|range| {
+        let text = quote!(#range).to_string();
+        quote! {
+            if !(#range).contains(#value) {
+                violations.push(mvutils::utils::Violation::new(path, format!("{:?} is not in the range {}", #value, #text)));
+            }
+        }
+    });
+    let with = attr.with.iter().map(|with| {
+        quote! {
+            if let Err(message) = #with(#value) {
+                violations.push(mvutils::utils::Violation::new(path, message));
+            }
+        }
+    });
+    quote! {
+        {
+            let path: &str = #path;
+            #range
+            #( #with )*
+            mvutils::utils::Verify::verify_into(#value, path, violations);
+        }
+    }
+}
+
+fn implement(attrs: &[Attribute], body: TokenStream2, name: Ident, generics: Generics) -> TokenStream {
+    let attr = get_attr(attrs);
+    if attr.skip || attr.range.is_some() {
+        panic!("Only 'with = ...' is supported as a verify attribute on the type itself!");
+    }
+    let with = attr.with.iter().map(|with| {
+        quote! {
+            if let Err(message) = #with(self) {
+                violations.push(mvutils::utils::Violation::new(path, message));
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let implementation = quote! {
+        impl #impl_generics mvutils::utils::Verify for #name #ty_generics #where_clause {
+            fn verify(&self) -> bool {
+                let mut violations = Vec::new();
+                self.verify_into("", &mut violations);
+                violations.is_empty()
+            }
+
+            #[allow(unused_variables)]
+            fn verify_into(&self, path: &str, violations: &mut Vec<mvutils::utils::Violation>) {
+                #body
+                #( #with )*
+            }
+        }
+    };
+
+    TokenStream::from(implementation)
+}
+
+pub fn verify_struct(attrs: &[Attribute], fields: &Fields, name: Ident, generics: Generics) -> TokenStream {
+    let checks = fields.iter().enumerate().map(|(i, f)| {
+        let access = match &f.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(i);
+                quote! { #index }
+            }
+        };
+        let field = field_name(f, i);
+        checks(
+            &get_attr(&f.attrs),
+            quote! { &self.#access },
+            quote! { &mvutils::diff::join(path, #field) },
+        )
+    });
+
+    implement(attrs, quote! { #( #checks )* }, name, generics)
+}
+
+pub fn verify_enum(attrs: &[Attribute], e: &DataEnum, name: Ident, generics: Generics) -> TokenStream {
+    let arms = e.variants.iter().map(|v| {
+        let ident = &v.ident;
+        let bindings = v.fields.iter().enumerate().map(|(i, _)| format_ident!("__v{}", i)).collect::<Vec<_>>();
+        let checks = v.fields.iter().enumerate().map(|(i, f)| {
+            let binding = &bindings[i];
+            let field = field_name(f, i);
+            checks(
+                &get_attr(&f.attrs),
+                quote! { #binding },
+                quote! { &mvutils::diff::join(path, #field) },
+            )
+        });
+        let pattern = match &v.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| &f.ident);
+                quote! { #name::#ident { #( #names: #bindings ),* } }
+            }
+            Fields::Unnamed(_) => quote! { #name::#ident ( #( #bindings ),* ) },
+            Fields::Unit => quote! { #name::#ident },
+        };
+        quote! {
+            #pattern => {
+                #( #checks )*
+            }
+        }
+    });
+
+    let body = quote! {
+        #[allow(unreachable_patterns)]
+        match self {
+            #( #arms )*
+            _ => {}
+        }
+    };
+
+    implement(attrs, body, name, generics)
+}
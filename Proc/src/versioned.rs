@@ -0,0 +1,99 @@
+use crate::savable::{named_body, unnamed_body};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Expr, Fields, Meta, Token, Type};
+
+struct OldVersion {
+    ty: Type,
+    #[allow(dead_code)]
+    arrow: Token![=>],
+    upgrade: Expr,
+}
+
+impl Parse for OldVersion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(OldVersion {
+            ty: input.parse()?,
+            arrow: input.parse()?,
+            upgrade: input.parse()?,
+        })
+    }
+}
+
+fn is_savable_attr(meta: &Meta) -> bool {
+    meta.path().is_ident("unsaved") || meta.path().is_ident("custom") || meta.path().is_ident("save_order")
+}
+
+pub fn savable_versioned(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let versions = syn::parse::Parser::parse(Punctuated::<OldVersion, Token![,]>::parse_terminated, attr)
+        .expect("Expected a list of 'OldType => upgrade_function' in savable_versioned attribute")
+        .into_iter()
+        .collect::<Vec<_>>();
+    if versions.len() >= u8::MAX as usize {
+        panic!("savable_versioned supports at most 254 old versions!");
+    }
+
+    let mut input = syn::parse::<DeriveInput>(input).unwrap();
+    let name = input.ident.clone();
+
+    let (save, load) = match &mut input.data {
+        Data::Struct(s) => {
+            let bodies = match &s.fields {
+                Fields::Named(fields) => named_body(fields),
+                Fields::Unnamed(fields) => unnamed_body(fields),
+                Fields::Unit => (quote! {}, quote! { Ok(Self) }),
+            };
+            for field in s.fields.iter_mut() {
+                field.attrs.retain(|a| !is_savable_attr(&a.meta));
+            }
+            bodies
+        }
+        _ => panic!("savable_versioned is only supported for structs!"),
+    };
+
+    let current = versions.len() as u8 + 1;
+
+    let old = versions.iter().enumerate().map(|(i, version)| {
+        let number = i as u8 + 1;
+        let ty = &version.ty;
+        let upgrades = versions[i..].iter().map(|v| &v.upgrade);
+        quote! {
+            #number => {
+                let value = <#ty as mvutils::save::Savable>::load(loader)?;
+                #( let value = #upgrades(value); )*
+                Ok(value)
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        #input
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The version written in front of the saved data.
+            pub const SAVE_VERSION: u8 = #current;
+        }
+
+        impl #impl_generics mvutils::save::Savable for #name #ty_generics #where_clause {
+            fn save(&self, saver: &mut impl mvutils::save::Saver) {
+                saver.push_u8(#current);
+                #save
+            }
+
+            fn load(loader: &mut impl mvutils::save::Loader) -> Result<Self, mvutils::save::SaveError> {
+                let version = loader.pop_u8().ok_or(mvutils::save::SaveError::eof(stringify!(#name)))?;
+                match version {
+                    #current => {
+                        #load
+                    }
+                    #( #old )*
+                    other => Err(mvutils::save::SaveError::custom(format!("Unknown version {} of {}", other, stringify!(#name)))),
+                }
+            }
+        }
+    }.into()
+}
@@ -0,0 +1,2 @@
+#[cfg(feature = "tracking_alloc")]
+pub mod tracking;
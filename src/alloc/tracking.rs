@@ -0,0 +1,241 @@
+use crate as mvutils;
+use mvutils_proc_macro::Savable;
+use parking_lot::Mutex;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The maximum amount of distinct tags, including the default `untagged` tag.
+pub const MAX_TAGS: usize = 64;
+
+struct TagCounters {
+    live_bytes: AtomicUsize,
+    live_allocations: AtomicUsize,
+    total_allocations: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+impl TagCounters {
+    const fn new() -> Self {
+        TagCounters {
+            live_bytes: AtomicUsize::new(0),
+            live_allocations: AtomicUsize::new(0),
+            total_allocations: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    fn alloc(&self, size: usize) {
+        let live = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live, Ordering::Relaxed);
+        self.live_allocations.fetch_add(1, Ordering::Relaxed);
+        self.total_allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn dealloc(&self, size: usize) {
+        self.live_bytes.fetch_sub(size, Ordering::Relaxed);
+        self.live_allocations.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self, name: &str) -> TagSnapshot {
+        TagSnapshot {
+            name: name.to_string(),
+            live_bytes: self.live_bytes.load(Ordering::Relaxed) as u64,
+            live_allocations: self.live_allocations.load(Ordering::Relaxed) as u64,
+            total_allocations: self.total_allocations.load(Ordering::Relaxed) as u64,
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed) as u64,
+        }
+    }
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: TagCounters = TagCounters::new();
+
+static TOTAL: TagCounters = TagCounters::new();
+static TAGS: [TagCounters; MAX_TAGS] = [EMPTY; MAX_TAGS];
+static TAG_NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static CURRENT_TAG: Cell<u8> = const { Cell::new(0) };
+}
+
+/// A [`GlobalAlloc`] wrapper that counts live bytes and allocations, globally and per tag.
+///
+/// Every allocation is attributed to the tag active on the allocating thread, see [`tag`]. The tag
+/// is stored in a small header in front of the allocation, so memory freed on another thread or
+/// under another tag is still attributed correctly.
+///
+/// ```ignore
+/// use mvutils::alloc::tracking::TrackingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+/// ```
+pub struct TrackingAllocator<A: GlobalAlloc = System> {
+    inner: A,
+}
+
+impl TrackingAllocator<System> {
+    pub const fn new() -> Self {
+        TrackingAllocator { inner: System }
+    }
+}
+
+impl Default for TrackingAllocator<System> {
+    fn default() -> Self {
+        TrackingAllocator::new()
+    }
+}
+
+impl<A: GlobalAlloc> TrackingAllocator<A> {
+    pub const fn with(inner: A) -> Self {
+        TrackingAllocator { inner }
+    }
+}
+
+fn header_size(layout: &Layout) -> usize {
+    layout.align().max(std::mem::size_of::<usize>())
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let header = header_size(&layout);
+        let Some(size) = layout.size().checked_add(header) else {
+            return std::ptr::null_mut();
+        };
+        let base = self.inner.alloc(Layout::from_size_align_unchecked(size, layout.align()));
+        if base.is_null() {
+            return base;
+        }
+        let tag = CURRENT_TAG.try_with(Cell::get).unwrap_or(0);
+        let ptr = base.add(header);
+        ptr.sub(1).write(tag);
+        TAGS[tag as usize].alloc(layout.size());
+        TOTAL.alloc(layout.size());
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let header = header_size(&layout);
+        let tag = ptr.sub(1).read();
+        TAGS[tag as usize].dealloc(layout.size());
+        TOTAL.dealloc(layout.size());
+        self.inner.dealloc(ptr.sub(header), Layout::from_size_align_unchecked(layout.size() + header, layout.align()));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let header = header_size(&layout);
+        let Some(size) = new_size.checked_add(header) else {
+            return std::ptr::null_mut();
+        };
+        let tag = ptr.sub(1).read();
+        let base = self.inner.realloc(
+            ptr.sub(header),
+            Layout::from_size_align_unchecked(layout.size() + header, layout.align()),
+            size,
+        );
+        if base.is_null() {
+            return base;
+        }
+        for counters in [&TAGS[tag as usize], &TOTAL] {
+            counters.dealloc(layout.size());
+            counters.alloc(new_size);
+            counters.total_allocations.fetch_sub(1, Ordering::Relaxed);
+        }
+        base.add(header)
+    }
+}
+
+/// An allocation tag, created using [`tag`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tag(u8);
+
+impl Tag {
+    /// Get or register the tag with the given name. Returns [`None`] if [`MAX_TAGS`] is exceeded.
+    pub fn get(name: &'static str) -> Option<Tag> {
+        let mut names = TAG_NAMES.lock();
+        if names.is_empty() {
+            names.push("untagged");
+        }
+        if let Some(i) = names.iter().position(|n| *n == name) {
+            return Some(Tag(i as u8));
+        }
+        if names.len() >= MAX_TAGS {
+            return None;
+        }
+        names.push(name);
+        Some(Tag(names.len() as u8 - 1))
+    }
+
+    /// Make this the active tag of the current thread until the returned guard is dropped.
+    pub fn enter(self) -> TagGuard {
+        let previous = CURRENT_TAG.with(|t| t.replace(self.0));
+        TagGuard { previous }
+    }
+}
+
+/// Restores the previously active tag when dropped.
+pub struct TagGuard {
+    previous: u8,
+}
+
+impl Drop for TagGuard {
+    fn drop(&mut self) {
+        CURRENT_TAG.with(|t| t.set(self.previous));
+    }
+}
+
+/// Attribute all allocations of the current thread to the named tag until the returned guard is
+/// dropped. If there are too many tags, allocations stay attributed to the current tag.
+pub fn tag(name: &'static str) -> Option<TagGuard> {
+    Tag::get(name).map(Tag::enter)
+}
+
+/// Run a function with all allocations of the current thread attributed to the named tag.
+pub fn tagged<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let _guard = tag(name);
+    f()
+}
+
+#[derive(Savable, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TagSnapshot {
+    pub name: String,
+    pub live_bytes: u64,
+    pub live_allocations: u64,
+    pub total_allocations: u64,
+    pub peak_bytes: u64,
+}
+
+/// The allocation counters at a point in time. Only allocations made through a
+/// [`TrackingAllocator`] are counted.
+#[derive(Savable, Clone, Debug, Default, Eq, PartialEq)]
+pub struct AllocSnapshot {
+    pub total: TagSnapshot,
+    pub tags: Vec<TagSnapshot>,
+}
+
+impl AllocSnapshot {
+    pub fn tag(&self, name: &str) -> Option<&TagSnapshot> {
+        self.tags.iter().find(|t| t.name == name)
+    }
+
+    /// The amount of allocations made since an earlier snapshot, useful to measure per-frame churn.
+    pub fn allocations_since(&self, earlier: &AllocSnapshot) -> u64 {
+        self.total.total_allocations.saturating_sub(earlier.total.total_allocations)
+    }
+}
+
+pub fn snapshot() -> AllocSnapshot {
+    let names = TAG_NAMES.lock().clone();
+    AllocSnapshot {
+        total: TOTAL.snapshot("total"),
+        tags: names.iter().enumerate().map(|(i, name)| TAGS[i].snapshot(name)).collect(),
+    }
+}
+
+/// Reset the peak counters to the current live bytes.
+pub fn reset_peaks() {
+    for counters in TAGS.iter().chain([&TOTAL]) {
+        counters.peak_bytes.store(counters.live_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,241 @@
+//! A shared memory budget for multiple caches.
+//!
+//! Caches are registered with a [`BudgetManager`] and a weight. When the caches use more memory
+//! than the budget, [`BudgetManager::enforce`] shrinks the caches using more than their weighted
+//! share of the budget.
+//!
+//! ```
+//! use mvutils::budget::BudgetManager;
+//! use mvutils::bytebuffer::BufferPool;
+//! use mvutils::cache::LruCache;
+//! use mvutils::sync::Mutex;
+//! use std::sync::Arc;
+//!
+//! let textures = Arc::new(Mutex::new(LruCache::<u32, [u8; 1024]>::new(1000)));
+//! let buffers = Arc::new(BufferPool::new());
+//!
+//! let budget = BudgetManager::new(64 * 1024);
+//! budget.register("textures", 3.0, textures.clone());
+//! budget.register("buffers", 1.0, buffers.clone());
+//!
+//! for i in 0..100 {
+//!     textures.lock().put(i, [0; 1024]);
+//! }
+//! budget.enforce();
+//! assert!(budget.usage() <= 64 * 1024);
+//! ```
+
+use crate::bytebuffer::BufferPool;
+use crate::cache::LruCache;
+use crate::deep_size::DeepSizeOf;
+use crate::sync::Mutex;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Weak};
+
+/// Something holding memory which can be freed on demand, like a cache or a pool.
+pub trait Budgeted: Send + Sync {
+    /// The approximate number of bytes used.
+    fn memory_usage(&self) -> usize;
+
+    /// Free memory until at most `target` bytes are used, or as close to it as possible.
+    fn shrink_to(&self, target: usize);
+}
+
+/// The inline size of the entries plus what they allocated, see [`DeepSizeOf`].
+impl<K: DeepSizeOf + Hash + Eq + Clone + Send, V: DeepSizeOf + Send, S: BuildHasher + Send> Budgeted for Mutex<LruCache<K, V, S>> {
+    fn memory_usage(&self) -> usize {
+        let cache = self.lock();
+        cache.iter().map(|(k, v)| LruCache::<K, V, S>::ENTRY_SIZE + k.heap_size() + v.heap_size()).sum()
+    }
+
+    fn shrink_to(&self, target: usize) {
+        let mut usage = self.memory_usage();
+        let mut cache = self.lock();
+        while usage > target {
+            match cache.pop_lru() {
+                Some((k, v)) => usage -= LruCache::<K, V, S>::ENTRY_SIZE + k.heap_size() + v.heap_size(),
+                None => break,
+            }
+        }
+    }
+}
+
+impl Budgeted for BufferPool {
+    fn memory_usage(&self) -> usize {
+        self.idle_bytes()
+    }
+
+    fn shrink_to(&self, target: usize) {
+        self.shrink_to(target);
+    }
+}
+
+struct Callbacks<U, S> {
+    usage: U,
+    shrink: S,
+}
+
+impl<U: Fn() -> usize + Send + Sync, S: Fn(usize) + Send + Sync> Budgeted for Callbacks<U, S> {
+    fn memory_usage(&self) -> usize {
+        (self.usage)()
+    }
+
+    fn shrink_to(&self, target: usize) {
+        (self.shrink)(target);
+    }
+}
+
+/// The memory usage of a registered cache.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheUsage {
+    pub name: String,
+    pub weight: f32,
+    pub bytes: usize,
+    /// The part of the budget the cache may use when all caches are full.
+    pub share: usize,
+}
+
+struct Entry {
+    name: String,
+    weight: f32,
+    cache: Weak<dyn Budgeted>,
+}
+
+/// Keeps the total memory usage of registered caches within a budget. Caches are held weakly and
+/// are unregistered when they are dropped.
+pub struct BudgetManager {
+    budget: Mutex<usize>,
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl BudgetManager {
+    /// Create a manager with a budget in bytes.
+    pub fn new(budget: usize) -> Self {
+        BudgetManager {
+            budget: Mutex::new(budget),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        *self.budget.lock()
+    }
+
+    /// Change the budget. Call [`BudgetManager::enforce`] to shrink the caches to a lower budget.
+    pub fn set_budget(&self, budget: usize) {
+        *self.budget.lock() = budget;
+    }
+
+    /// Register a cache. A cache with twice the weight of another one may use twice as much of the
+    /// budget before it is shrunk.
+    pub fn register(&self, name: &str, weight: f32, cache: Arc<impl Budgeted + 'static>) {
+        let cache: Arc<dyn Budgeted> = cache;
+        self.entries.lock().push(Entry {
+            name: name.to_string(),
+            weight: weight.max(0.0),
+            cache: Arc::downgrade(&cache),
+        });
+    }
+
+    /// Register a cache with functions returning its memory usage and shrinking it to a target
+    /// usage. The returned handle must be kept alive, the cache is unregistered when it is dropped.
+    #[must_use = "the cache is unregistered when the handle is dropped"]
+    pub fn register_fn(
+        &self,
+        name: &str,
+        weight: f32,
+        usage: impl Fn() -> usize + Send + Sync + 'static,
+        shrink: impl Fn(usize) + Send + Sync + 'static,
+    ) -> Arc<dyn Budgeted> {
+        let cache = Arc::new(Callbacks { usage, shrink });
+        self.register(name, weight, cache.clone());
+        cache
+    }
+
+    /// Unregister all caches with the name, returning whether there were any.
+    pub fn unregister(&self, name: &str) -> bool {
+        let mut entries = self.entries.lock();
+        let len = entries.len();
+        entries.retain(|e| e.name != name);
+        entries.len() != len
+    }
+
+    /// The registered caches which are still alive.
+    fn caches(&self) -> Vec<(String, f32, Arc<dyn Budgeted>)> {
+        let mut entries = self.entries.lock();
+        entries.retain(|e| e.cache.strong_count() > 0);
+        entries
+            .iter()
+            .filter_map(|e| Some((e.name.clone(), e.weight, e.cache.upgrade()?)))
+            .collect()
+    }
+
+    /// The total memory usage of all registered caches in bytes.
+    pub fn usage(&self) -> usize {
+        self.caches().iter().map(|(_, _, c)| c.memory_usage()).sum()
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.usage() > self.budget()
+    }
+
+    /// The memory usage of every registered cache.
+    pub fn report(&self) -> Vec<CacheUsage> {
+        let caches = self.caches();
+        let shares = self.shares(&caches);
+        caches
+            .into_iter()
+            .zip(shares)
+            .map(|((name, weight, cache), share)| CacheUsage {
+                name,
+                weight,
+                bytes: cache.memory_usage(),
+                share,
+            })
+            .collect()
+    }
+
+    fn shares(&self, caches: &[(String, f32, Arc<dyn Budgeted>)]) -> Vec<usize> {
+        let budget = self.budget();
+        let total = caches.iter().map(|(_, w, _)| *w).sum::<f32>();
+        caches
+            .iter()
+            .map(|(_, weight, _)| {
+                if total > 0.0 {
+                    (budget as f64 * (*weight / total) as f64) as usize
+                } else {
+                    budget / caches.len()
+                }
+            })
+            .collect()
+    }
+
+    /// Shrink the caches if they use more than the budget, returning the number of bytes freed.
+    ///
+    /// Only caches using more than their weighted share of the budget are shrunk, in proportion to
+    /// how far they are over their share, so caches within their share keep their entries.
+    pub fn enforce(&self) -> usize {
+        let caches = self.caches();
+        let usages = caches.iter().map(|(_, _, c)| c.memory_usage()).collect::<Vec<_>>();
+        let total = usages.iter().sum::<usize>();
+        let budget = self.budget();
+        if total <= budget {
+            return 0;
+        }
+        let excess = total - budget;
+        let shares = self.shares(&caches);
+        let over = usages.iter().zip(&shares).map(|(u, s)| u.saturating_sub(*s)).collect::<Vec<_>>();
+        let total_over = over.iter().sum::<usize>().max(1);
+
+        let mut freed = 0;
+        for (((_, _, cache), usage), over) in caches.iter().zip(&usages).zip(&over) {
+            if *over == 0 {
+                continue;
+            }
+            let reduce = (excess as u128 * *over as u128).div_ceil(total_over as u128) as usize;
+            cache.shrink_to(usage.saturating_sub(reduce));
+            freed += usage.saturating_sub(cache.memory_usage());
+        }
+        freed
+    }
+}
@@ -1,4 +1,8 @@
 use bytebuffer::{ByteBuffer, Endian};
+use crate::sync::Mutex;
+use std::ops::{Deref, DerefMut};
+use crate::lazy;
+use crate::save::Savable;
 
 pub trait ByteBufferExtras: Sized {
     fn new_le() -> Self;
@@ -50,4 +54,146 @@ impl ByteBufferExtras for ByteBuffer {
             Self::from_vec_le(data)
         }
     }
-}
\ No newline at end of file
+}
+
+/// A thread-safe pool of reusable [`ByteBuffer`]s, grouped into capacity classes. Buffers
+/// requested with a capacity larger than the largest class are allocated normally, and buffers
+/// larger than the largest class are dropped on release, even if they only grew while in use.
+pub struct BufferPool {
+    classes: Vec<(usize, Mutex<Vec<ByteBuffer>>)>,
+    max_per_class: usize,
+}
+
+lazy! {
+    static GLOBAL_POOL: BufferPool = BufferPool::new();
+}
+
+impl BufferPool {
+    pub const DEFAULT_CLASSES: [usize; 4] = [256, 4096, 65536, 1048576];
+
+    pub fn new() -> Self {
+        Self::with_classes(&Self::DEFAULT_CLASSES, 64)
+    }
+
+    pub fn with_classes(classes: &[usize], max_per_class: usize) -> Self {
+        let mut classes = classes.to_vec();
+        classes.sort_unstable();
+        classes.dedup();
+        BufferPool {
+            classes: classes.into_iter().map(|c| (c, Mutex::new(Vec::new()))).collect(),
+            max_per_class,
+        }
+    }
+
+    pub fn global() -> &'static BufferPool {
+        &GLOBAL_POOL
+    }
+
+    /// Take an empty buffer from the pool that can hold at least `capacity` bytes without
+    /// reallocating. The buffer is returned to the pool when the [`PooledBuffer`] is dropped.
+    pub fn acquire(&self, capacity: usize) -> PooledBuffer<'_> {
+        let buffer = match self.classes.iter().find(|(c, _)| *c >= capacity) {
+            Some((class, buffers)) => buffers
+                .lock()
+                .pop()
+                .unwrap_or_else(|| ByteBuffer::from_vec(Vec::with_capacity(*class))),
+            None => ByteBuffer::from_vec(Vec::with_capacity(capacity)),
+        };
+        PooledBuffer {
+            buffer: Some(buffer),
+            pool: self,
+        }
+    }
+
+    /// Return a buffer to the pool. The buffer is cleared and its endianness is reset.
+    pub fn release(&self, buffer: ByteBuffer) {
+        let mut vec = buffer.into_vec();
+        let capacity = vec.capacity();
+        if self.classes.last().map_or(true, |(c, _)| capacity > *c) {
+            return;
+        }
+        if let Some((_, buffers)) = self.classes.iter().rev().find(|(c, _)| *c <= capacity) {
+            let mut buffers = buffers.lock();
+            if buffers.len() < self.max_per_class {
+                vec.clear();
+                buffers.push(ByteBuffer::from_vec(vec));
+            }
+        }
+    }
+
+    /// The amount of idle buffers currently held by the pool.
+    pub fn idle(&self) -> usize {
+        self.classes.iter().map(|(_, b)| b.lock().len()).sum()
+    }
+
+    /// The approximate capacity of the idle buffers currently held by the pool in bytes, counting
+    /// every buffer with the capacity of its size class.
+    pub fn idle_bytes(&self) -> usize {
+        self.classes.iter().map(|(c, b)| c * b.lock().len()).sum()
+    }
+
+    /// Drop idle buffers, starting with the largest ones, until the idle buffers hold at most
+    /// `target` bytes.
+    pub fn shrink_to(&self, target: usize) {
+        let mut idle = self.idle_bytes();
+        for (class, buffers) in self.classes.iter().rev() {
+            let mut buffers = buffers.lock();
+            while idle > target && buffers.pop().is_some() {
+                idle -= class;
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        for (_, buffers) in &self.classes {
+            buffers.lock().clear();
+        }
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct PooledBuffer<'a> {
+    buffer: Option<ByteBuffer>,
+    pool: &'a BufferPool,
+}
+
+impl PooledBuffer<'_> {
+    /// Take the buffer out of the pool permanently.
+    pub fn detach(mut self) -> ByteBuffer {
+        self.buffer.take().expect("PooledBuffer should never be empty")
+    }
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = ByteBuffer;
+
+    fn deref(&self) -> &Self::Target {
+        self.buffer.as_ref().expect("PooledBuffer should never be empty")
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buffer.as_mut().expect("PooledBuffer should never be empty")
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            self.pool.release(buffer);
+        }
+    }
+}
+
+/// Save a value into a buffer taken from the global [`BufferPool`].
+pub fn save_pooled(value: &impl Savable) -> PooledBuffer<'static> {
+    let mut buffer = BufferPool::global().acquire(0);
+    value.save(buffer.deref_mut());
+    buffer
+}
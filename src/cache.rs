@@ -0,0 +1,475 @@
+use crate as mvutils;
+use crate::platform::{self, Instant};
+use crate::save::{load_len, nested, Loader, Savable, SaveError, Saver, MAX_PREALLOCATION};
+use hashbrown::HashMap;
+use mvutils_proc_macro::Savable;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, SystemTime};
+
+const NIL: usize = usize::MAX;
+
+/// Hit and miss counters of a cache.
+#[derive(Savable, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries removed to make room for new ones.
+    pub evictions: u64,
+    /// Entries removed because their time to live ran out.
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    /// The fraction of lookups which were hits, or 0 if there were no lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+/// A cache holding up to `capacity` entries, evicting the least recently used one when full.
+/// Lookups and insertions are O(1).
+pub struct LruCache<K, V, S = RandomState> {
+    map: HashMap<K, usize, S>,
+    nodes: Vec<Node<K, V>>,
+    head: usize,
+    tail: usize,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
+    /// The inline size of an entry in the list and the map, used to approximate the memory usage.
+    pub(crate) const ENTRY_SIZE: usize = std::mem::size_of::<Node<K, V>>() + std::mem::size_of::<(K, usize)>();
+
+    /// # Panics
+    /// If the capacity is zero.
+    pub fn with_hasher(capacity: usize, hasher: S) -> Self {
+        Self::with_preallocation(capacity, capacity, hasher)
+    }
+
+    /// Create a cache allocating room for only `preallocate` entries up front.
+    fn with_preallocation(capacity: usize, preallocate: usize, hasher: S) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be greater than zero!");
+        LruCache {
+            map: HashMap::with_capacity_and_hasher(preallocate, hasher),
+            nodes: Vec::with_capacity(preallocate),
+            head: NIL,
+            tail: NIL,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.nodes[index].prev, self.nodes[index].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.nodes[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.nodes[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.nodes[index].prev = NIL;
+        self.nodes[index].next = self.head;
+        match self.head {
+            NIL => self.tail = index,
+            head => self.nodes[head].prev = index,
+        }
+        self.head = index;
+    }
+
+    fn touch(&mut self, index: usize) {
+        if self.head != index {
+            self.unlink(index);
+            self.push_front(index);
+        }
+    }
+
+    /// Get the value and mark it as most recently used.
+    pub fn get<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        match self.map.get(key).copied() {
+            Some(index) => {
+                self.stats.hits += 1;
+                self.touch(index);
+                Some(&mut self.nodes[index].value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Get the value without changing the order or the statistics.
+    pub fn peek<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get(key).map(|i| &self.nodes[*i].value)
+    }
+
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Insert the value as most recently used, returning the previous value of the key.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(index) = self.map.get(&key).copied() {
+            self.touch(index);
+            return Some(std::mem::replace(&mut self.nodes[index].value, value));
+        }
+        if self.map.len() >= self.capacity {
+            self.pop_lru();
+            self.stats.evictions += 1;
+        }
+        let node = Node {
+            key: key.clone(),
+            value,
+            prev: NIL,
+            next: NIL,
+        };
+        self.nodes.push(node);
+        let index = self.nodes.len() - 1;
+        self.map.insert(key, index);
+        self.push_front(index);
+        None
+    }
+
+    /// Get the value, or compute, insert and return it if the key is missing.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> &mut V {
+        let index = match self.map.get(&key).copied() {
+            Some(index) => {
+                self.stats.hits += 1;
+                self.touch(index);
+                index
+            }
+            None => {
+                self.stats.misses += 1;
+                self.put(key.clone(), f());
+                self.map[&key]
+            }
+        };
+        &mut self.nodes[index].value
+    }
+
+    /// Remove the least recently used entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let index = self.tail;
+        if index == NIL {
+            return None;
+        }
+        self.remove_index(index)
+    }
+
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.map.get(key).copied()?;
+        self.remove_index(index).map(|(_, v)| v)
+    }
+
+    fn remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.unlink(index);
+        // Swap the last node into the freed slot so the node storage stays dense.
+        let last = self.nodes.len() - 1;
+        if index != last {
+            let (prev, next) = (self.nodes[last].prev, self.nodes[last].next);
+            match prev {
+                NIL => self.head = index,
+                prev => self.nodes[prev].next = index,
+            }
+            match next {
+                NIL => self.tail = index,
+                next => self.nodes[next].prev = index,
+            }
+            *self.map.get_mut(&self.nodes[last].key)? = index;
+        }
+        let node = self.nodes.swap_remove(index);
+        self.map.remove(&node.key);
+        Some((node.key, node.value))
+    }
+
+    /// Change the capacity, evicting the least recently used entries if necessary.
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > 0, "LruCache capacity must be greater than zero!");
+        while self.map.len() > capacity {
+            self.pop_lru();
+            self.stats.evictions += 1;
+        }
+        self.capacity = capacity;
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.nodes.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// Iterate from the most to the least recently used entry.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let mut index = self.head;
+        std::iter::from_fn(move || {
+            let node = self.nodes.get(index)?;
+            index = node.next;
+            Some((&node.key, &node.value))
+        })
+    }
+}
+
+impl<K: Savable + Hash + Eq + Clone, V: Savable, S: BuildHasher + Default> Savable for LruCache<K, V, S> {
+    fn save(&self, saver: &mut impl Saver) {
+        (self.capacity as u64).save(saver);
+        (self.len() as u64).save(saver);
+        let mut index = self.tail;
+        while index != NIL {
+            let node = &self.nodes[index];
+            node.key.save(saver);
+            node.value.save(saver);
+            index = node.prev;
+        }
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let capacity = u64::load(loader)? as usize;
+        if capacity == 0 {
+            return Err(SaveError::custom("LruCache capacity must be greater than zero!"));
+        }
+        let len = load_len(loader)?;
+        let preallocate = len.min(capacity as u64).min(MAX_PREALLOCATION) as usize;
+        let mut cache = LruCache::with_preallocation(capacity, preallocate, S::default());
+        nested(loader, |loader| {
+            for _ in 0..len {
+                let key = K::load(loader)?;
+                let value = V::load(loader)?;
+                cache.put(key, value);
+            }
+            Ok(cache)
+        })
+    }
+}
+
+/// A cache whose entries expire after a time to live.
+///
+/// Expired entries are removed lazily when they are looked up, or all at once using
+/// [`TtlCache::purge_expired`].
+pub struct TtlCache<K, V, S = RandomState> {
+    map: HashMap<K, (V, Instant), S>,
+    ttl: Duration,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq, V> TtlCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        TtlCache::with_hasher(ttl, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> TtlCache<K, V, S> {
+    pub fn with_hasher(ttl: Duration, hasher: S) -> Self {
+        TtlCache {
+            map: HashMap::with_hasher(hasher),
+            ttl,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// The default time to live of new entries.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    /// The number of entries, including expired ones which were not yet removed.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Insert the value with the default time to live, returning the previous value if it did not expire.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_with_ttl(key, value, self.ttl)
+    }
+
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = Instant::now();
+        self.map
+            .insert(key, (value, now + ttl))
+            .filter(|(_, expiry)| *expiry > now)
+            .map(|(v, _)| v)
+    }
+
+    pub fn get<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let expired = match self.map.get(key) {
+            Some((_, expiry)) => *expiry <= Instant::now(),
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        if expired {
+            self.map.remove(key);
+            self.stats.expirations += 1;
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.map.get_mut(key).map(|(v, _)| v)
+    }
+
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.time_to_live(key).is_some()
+    }
+
+    /// The remaining time to live of the entry, or [`None`] if it is missing or expired.
+    pub fn time_to_live<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<Duration>
+    where
+        K: Borrow<Q>,
+    {
+        let (_, expiry) = self.map.get(key)?;
+        expiry.checked_duration_since(Instant::now()).filter(|d| !d.is_zero())
+    }
+
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let (value, expiry) = self.map.remove(key)?;
+        (expiry > Instant::now()).then_some(value)
+    }
+
+    /// Remove all expired entries, returning how many were removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let before = self.map.len();
+        self.map.retain(|_, (_, expiry)| *expiry > now);
+        let removed = before - self.map.len();
+        self.stats.expirations += removed as u64;
+        removed
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Iterate over all entries which did not expire yet, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let now = Instant::now();
+        self.map.iter().filter(move |(_, (_, e))| *e > now).map(|(k, (v, _))| (k, v))
+    }
+}
+
+impl<K: Savable + Hash + Eq, V: Savable, S: BuildHasher + Default> Savable for TtlCache<K, V, S> {
+    /// Saves all entries which did not expire yet, with their expiry as wall-clock time.
+    fn save(&self, saver: &mut impl Saver) {
+        self.ttl.save(saver);
+        let now = Instant::now();
+        (self.map.values().filter(|(_, e)| *e > now).count() as u64).save(saver);
+        for (key, (value, expiry)) in self.map.iter().filter(|(_, (_, e))| *e > now) {
+            key.save(saver);
+            value.save(saver);
+            (platform::system_now() + expiry.saturating_duration_since(now)).save(saver);
+        }
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let ttl = Duration::load(loader)?;
+        let len = load_len(loader)?;
+        let mut cache = TtlCache::with_hasher(ttl, S::default());
+        nested(loader, |loader| {
+            for _ in 0..len {
+                let key = K::load(loader)?;
+                let value = V::load(loader)?;
+                let expiry = SystemTime::load(loader)?;
+                let remaining = expiry.duration_since(platform::system_now()).unwrap_or_default();
+                cache.map.insert(key, (value, Instant::now() + remaining));
+            }
+            Ok(cache)
+        })
+    }
+}
@@ -0,0 +1,158 @@
+//! A fixed rate clock for running simulation ticks independently of the frame rate.
+//!
+//! ```no_run
+//! use mvutils::clock::Clock;
+//! use mvutils::units::TickRate;
+//!
+//! let mut clock = Clock::new(TickRate::per_second(20.0));
+//! loop {
+//!     while clock.ready() {
+//!         // update the simulation
+//!     }
+//!     // render, using clock.alpha() to interpolate
+//! }
+//! ```
+//!
+//! When a tick takes longer than the tick interval, or the process is paused, the loop above never
+//! catches up. A [`CatchUp`] policy limits how many ticks are processed in a row and drops the rest:
+//!
+//! ```no_run
+//! use mvutils::clock::{CatchUp, Clock};
+//! use mvutils::units::TickRate;
+//!
+//! let mut clock = Clock::new(TickRate::per_second(20.0)).with_catch_up(CatchUp::Max(5));
+//! while clock.ready() {
+//!     // update the simulation
+//! }
+//! let missed = clock.missed_ticks();
+//! ```
+
+use crate::platform::Instant;
+use crate::units::{Micros, TickRate, Ticks};
+
+/// What a [`Clock`] does with ticks which are due when it has fallen behind.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum CatchUp {
+    /// Process every due tick, however far behind the clock is.
+    #[default]
+    All,
+    /// Process at most this many ticks in a row, dropping older ones.
+    Max(u64),
+    /// Drop all due ticks except the most recent one.
+    Skip,
+}
+
+impl CatchUp {
+    fn limit(self) -> u64 {
+        match self {
+            CatchUp::All => u64::MAX,
+            CatchUp::Max(max) => max.max(1),
+            CatchUp::Skip => 1,
+        }
+    }
+}
+
+/// A clock producing ticks at a fixed [`TickRate`].
+pub struct Clock {
+    rate: TickRate,
+    start: Instant,
+    ticks: Ticks,
+    catch_up: CatchUp,
+    skipped: Ticks,
+    missed: Ticks,
+}
+
+impl Clock {
+    pub fn new(rate: TickRate) -> Self {
+        Clock {
+            rate,
+            start: Instant::now(),
+            ticks: Ticks::ZERO,
+            catch_up: CatchUp::All,
+            skipped: Ticks::ZERO,
+            missed: Ticks::ZERO,
+        }
+    }
+
+    pub fn with_catch_up(mut self, catch_up: CatchUp) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    pub fn catch_up(&self) -> CatchUp {
+        self.catch_up
+    }
+
+    pub fn set_catch_up(&mut self, catch_up: CatchUp) {
+        self.catch_up = catch_up;
+    }
+
+    pub fn tick_rate(&self) -> TickRate {
+        self.rate
+    }
+
+    /// Whether a tick is due, counting it as processed if so. Call this in a loop to catch up on
+    /// the due ticks, as many as the [`CatchUp`] policy allows.
+    pub fn ready(&mut self) -> bool {
+        let due = self.ticks_elapsed().saturating_sub(self.skipped);
+        let behind = due.saturating_sub(self.ticks).get();
+        let limit = self.catch_up.limit();
+        if behind > limit {
+            let dropped = Ticks(behind - limit);
+            self.skipped += dropped;
+            self.missed += dropped;
+        }
+        if behind > 0 {
+            self.ticks += Ticks(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of ticks processed with [`Clock::ready`].
+    pub fn ticks(&self) -> Ticks {
+        self.ticks
+    }
+
+    /// The number of ticks dropped by the [`CatchUp`] policy since the last call.
+    pub fn missed_ticks(&mut self) -> Ticks {
+        std::mem::take(&mut self.missed)
+    }
+
+    /// The number of ticks dropped by the [`CatchUp`] policy since the clock was started.
+    pub fn skipped_ticks(&self) -> Ticks {
+        self.skipped
+    }
+
+    /// The number of ticks which passed since the clock was started.
+    pub fn ticks_elapsed(&self) -> Ticks {
+        self.rate.ticks_in(self.start.elapsed())
+    }
+
+    /// The time since the clock was started.
+    pub fn elapsed(&self) -> Micros {
+        Micros::from(self.start.elapsed())
+    }
+
+    /// How far the clock is into the next unprocessed tick, between 0 and 1 when caught up. Useful
+    /// to interpolate between the last two simulation states when rendering.
+    pub fn alpha(&self) -> f32 {
+        let ticks = self.start.elapsed().as_secs_f64() * self.rate.ticks_per_second();
+        (ticks - (self.ticks + self.skipped).get() as f64) as f32
+    }
+
+    /// Restart the clock at zero ticks.
+    pub fn reset(&mut self) {
+        self.start = Instant::now();
+        self.ticks = Ticks::ZERO;
+        self.skipped = Ticks::ZERO;
+        self.missed = Ticks::ZERO;
+    }
+
+    /// Change the tick rate, keeping the number of processed and skipped ticks.
+    pub fn set_tick_rate(&mut self, rate: TickRate) {
+        self.start = Instant::now() - (self.ticks + self.skipped).to_duration(rate);
+        self.rate = rate;
+    }
+}
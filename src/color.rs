@@ -0,0 +1,296 @@
+use crate as mvutils;
+use mvutils_proc_macro::Savable;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An 8-bit RGBA color.
+///
+/// Colors can be parsed from `#RGB`, `#RGBA`, `#RRGGBB` and `#RRGGBBAA` hex strings, from
+/// `rgb(r, g, b)` and `rgba(r, g, b, a)` where the alpha is a float between 0 and 1, and from
+/// the names of the basic CSS colors.
+#[derive(Savable, Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+const NAMED: &[(&str, Color)] = &[
+    ("black", Color::BLACK),
+    ("white", Color::WHITE),
+    ("red", Color::RED),
+    ("green", Color::rgb(0, 128, 0)),
+    ("lime", Color::GREEN),
+    ("blue", Color::BLUE),
+    ("yellow", Color::YELLOW),
+    ("cyan", Color::CYAN),
+    ("aqua", Color::CYAN),
+    ("magenta", Color::MAGENTA),
+    ("fuchsia", Color::MAGENTA),
+    ("gray", Color::rgb(128, 128, 128)),
+    ("grey", Color::rgb(128, 128, 128)),
+    ("silver", Color::rgb(192, 192, 192)),
+    ("maroon", Color::rgb(128, 0, 0)),
+    ("olive", Color::rgb(128, 128, 0)),
+    ("purple", Color::rgb(128, 0, 128)),
+    ("teal", Color::rgb(0, 128, 128)),
+    ("navy", Color::rgb(0, 0, 128)),
+    ("orange", Color::rgb(255, 165, 0)),
+    ("pink", Color::rgb(255, 192, 203)),
+    ("brown", Color::rgb(165, 42, 42)),
+    ("transparent", Color::TRANSPARENT),
+];
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const YELLOW: Color = Color::rgb(255, 255, 0);
+    pub const CYAN: Color = Color::rgb(0, 255, 255);
+    pub const MAGENTA: Color = Color::rgb(255, 0, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+
+    /// Create a color from float components between 0 and 1, which are clamped.
+    pub fn from_f32(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Color::rgba(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+    }
+
+    /// Create a color from a `0xRRGGBB` value.
+    pub const fn from_hex(hex: u32) -> Self {
+        Color::rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    /// The color as a `0xRRGGBBAA` value.
+    pub const fn to_u32(self) -> u32 {
+        (self.r as u32) << 24 | (self.g as u32) << 16 | (self.b as u32) << 8 | self.a as u32
+    }
+
+    pub fn r_f32(self) -> f32 {
+        self.r as f32 / 255.0
+    }
+
+    pub fn g_f32(self) -> f32 {
+        self.g as f32 / 255.0
+    }
+
+    pub fn b_f32(self) -> f32 {
+        self.b as f32 / 255.0
+    }
+
+    pub fn a_f32(self) -> f32 {
+        self.a as f32 / 255.0
+    }
+
+    pub fn to_f32_array(self) -> [f32; 4] {
+        [self.r_f32(), self.g_f32(), self.b_f32(), self.a_f32()]
+    }
+
+    pub const fn with_alpha(mut self, a: u8) -> Self {
+        self.a = a;
+        self
+    }
+
+    /// Create a color from a hue in degrees and saturation and value between 0 and 1.
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+        let c = v * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = v - c;
+        Color::from_f32(r + m, g + m, b + m, 1.0)
+    }
+
+    /// Create a color from a hue in degrees and saturation and lightness between 0 and 1.
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = l - c / 2.0;
+        Color::from_f32(r + m, g + m, b + m, 1.0)
+    }
+
+    /// The hue in degrees, saturation and value of the color, ignoring alpha.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (h, max, min) = self.hue();
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (h, s, max)
+    }
+
+    /// The hue in degrees, saturation and lightness of the color, ignoring alpha.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (h, max, min) = self.hue();
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        let s = if d == 0.0 { 0.0 } else { d / (1.0 - (2.0 * l - 1.0).abs()) };
+        (h, s, l)
+    }
+
+    fn hue(self) -> (f32, f32, f32) {
+        let (r, g, b) = (self.r_f32(), self.g_f32(), self.b_f32());
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        let h = if d == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        };
+        (h, max, min)
+    }
+
+    /// Linearly interpolate all components between this color and the other one.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::rgba(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b), mix(self.a, other.a))
+    }
+
+    pub fn named(name: &str) -> Option<Color> {
+        NAMED
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, c)| *c)
+    }
+
+    pub fn parse(s: &str) -> Result<Color, String> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(|| format!("Invalid hex color '{s}'!"));
+        }
+        let lower = s.to_ascii_lowercase();
+        let function = lower
+            .strip_prefix("rgba(")
+            .or_else(|| lower.strip_prefix("rgb("))
+            .and_then(|r| r.strip_suffix(')'));
+        if let Some(args) = function {
+            return parse_function(args).ok_or_else(|| format!("Invalid color function '{s}'!"));
+        }
+        Color::named(s).ok_or_else(|| format!("Unknown color '{s}'!"))
+    }
+}
+
+fn hue_to_rgb(h: f32, c: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let byte = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+    match hex.len() {
+        3 => Some(Color::rgb(digit(0)?, digit(1)?, digit(2)?)),
+        4 => Some(Color::rgba(digit(0)?, digit(1)?, digit(2)?, digit(3)?)),
+        6 => Some(Color::rgb(byte(0)?, byte(1)?, byte(2)?)),
+        8 => Some(Color::rgba(byte(0)?, byte(1)?, byte(2)?, byte(3)?)),
+        _ => None,
+    }
+}
+
+fn parse_function(args: &str) -> Option<Color> {
+    let args = args.split(',').map(str::trim).collect::<Vec<_>>();
+    let rgb = |i: usize| args[i].parse::<u8>().ok();
+    match args.len() {
+        3 => Some(Color::rgb(rgb(0)?, rgb(1)?, rgb(2)?)),
+        4 => {
+            let a = args[3].parse::<f32>().ok().filter(|a| (0.0..=1.0).contains(a))?;
+            Some(Color::rgba(rgb(0)?, rgb(1)?, rgb(2)?, to_u8(a)))
+        }
+        _ => None,
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::parse(s)
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Color::parse(&value)
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Color::parse(value)
+    }
+}
+
+impl Display for Color {
+    /// Formats the color as `#RRGGBB`, or `#RRGGBBAA` if it is not opaque.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from(value: [u8; 4]) -> Self {
+        Color::rgba(value[0], value[1], value[2], value[3])
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from(value: [u8; 3]) -> Self {
+        Color::rgb(value[0], value[1], value[2])
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(value: Color) -> Self {
+        [value.r, value.g, value.b, value.a]
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(value: [f32; 4]) -> Self {
+        Color::from_f32(value[0], value[1], value[2], value[3])
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(value: Color) -> Self {
+        value.to_f32_array()
+    }
+}
@@ -0,0 +1,321 @@
+use crate::utils::format_escaped;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+enum Line {
+    Raw(String),
+    Pair {
+        key: String,
+        value: String,
+        raw: Option<String>,
+    },
+}
+
+struct Section {
+    name: String,
+    header: Option<String>,
+    lines: Vec<Line>,
+}
+
+impl Section {
+    fn new(name: &str) -> Self {
+        Section {
+            name: name.to_string(),
+            header: None,
+            lines: Vec::new(),
+        }
+    }
+}
+
+/// A plain-text config file made of `[sections]` and `key = value` pairs.
+///
+/// Lines starting with `#` or `;` are comments. Values may be quoted, in which case escape sequences
+/// are resolved using [`format_escaped`]. Keys before the first section header belong to the root
+/// section `""`. Comments and formatting of unchanged lines are preserved when writing the config back.
+pub struct Config {
+    sections: Vec<Section>,
+}
+
+fn strip_comment(value: &str) -> &str {
+    let mut prev_space = false;
+    for (i, c) in value.char_indices() {
+        if prev_space && (c == '#' || c == ';') {
+            return value[..i].trim_end();
+        }
+        prev_space = c.is_whitespace();
+    }
+    value
+}
+
+fn parse_value(value: &str, line: usize) -> Result<String, String> {
+    let value = value.trim();
+    let Some(quoted) = value.strip_prefix('"') else {
+        return Ok(strip_comment(value).to_string());
+    };
+
+    let mut escaped = false;
+    for (i, c) in quoted.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => {
+                let rest = quoted[i + 1..].trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') && !rest.starts_with(';') {
+                    return Err(format!("Line {line}: Unexpected characters after quoted value!"));
+                }
+                return Ok(format_escaped(&quoted[..i]));
+            }
+            _ => escaped = false,
+        }
+    }
+    Err(format!("Line {line}: Unterminated quoted value!"))
+}
+
+fn format_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.trim() != value
+        || value.starts_with('"')
+        || value.chars().any(|c| c == '#' || c == ';' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let mut output = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\0' => output.push_str("\\0"),
+            '\x08' => output.push_str("\\b"),
+            '\x0C' => output.push_str("\\f"),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Config {
+            sections: vec![Section::new("")],
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut config = Config::new();
+
+        for (i, raw) in input.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                config.sections.last_mut().unwrap().lines.push(Line::Raw(raw.to_string()));
+            } else if let Some(header) = line.strip_prefix('[') {
+                let end = header.find(']').ok_or(format!("Line {}: Unterminated section header!", i + 1))?;
+                let rest = header[end + 1..].trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') && !rest.starts_with(';') {
+                    return Err(format!("Line {}: Unexpected characters after section header!", i + 1));
+                }
+                let mut section = Section::new(header[..end].trim());
+                section.header = Some(raw.to_string());
+                config.sections.push(section);
+            } else {
+                let (key, value) = line.split_once('=').ok_or(format!("Line {}: Expected 'key = value'!", i + 1))?;
+                let key = key.trim();
+                if key.is_empty() {
+                    return Err(format!("Line {}: Missing key!", i + 1));
+                }
+                config.sections.last_mut().unwrap().lines.push(Line::Pair {
+                    key: key.to_string(),
+                    value: parse_value(value, i + 1)?,
+                    raw: Some(raw.to_string()),
+                });
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let input = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Config::parse(&input)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    fn section_mut(&mut self, name: &str) -> &mut Section {
+        match self.sections.iter().position(|s| s.name == name) {
+            Some(i) => &mut self.sections[i],
+            None => {
+                self.sections.push(Section::new(name));
+                self.sections.last_mut().unwrap()
+            }
+        }
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.sections.iter().map(|s| s.name.as_str())
+    }
+
+    pub fn has_section(&self, section: &str) -> bool {
+        self.section(section).is_some()
+    }
+
+    pub fn keys<'a>(&'a self, section: &str) -> impl Iterator<Item = &'a str> {
+        self.section(section).into_iter().flat_map(|s| {
+            s.lines.iter().filter_map(|l| match l {
+                Line::Pair { key, .. } => Some(key.as_str()),
+                Line::Raw(_) => None,
+            })
+        })
+    }
+
+    pub fn contains(&self, section: &str, key: &str) -> bool {
+        self.get_str(section, key).is_some()
+    }
+
+    pub fn get_str(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?.lines.iter().rev().find_map(|l| match l {
+            Line::Pair { key: k, value, .. } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Parse the value of a key, returning `Ok(None)` if the key does not exist.
+    pub fn get<T: FromStr>(&self, section: &str, key: &str) -> Result<Option<T>, String> {
+        match self.get_str(section, key) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Failed to parse value '{value}' of key '{key}' in section [{section}]!")),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_or<T: FromStr>(&self, section: &str, key: &str, default: T) -> T {
+        self.get(section, key).ok().flatten().unwrap_or(default)
+    }
+
+    /// Set the value of a key, creating the key and section if they do not exist yet.
+    pub fn set(&mut self, section: &str, key: &str, value: impl ToString) {
+        let value = value.to_string();
+        let section = self.section_mut(section);
+
+        for line in section.lines.iter_mut().rev() {
+            if let Line::Pair { key: k, value: v, raw } = line {
+                if k == key {
+                    if *v != value {
+                        *v = value;
+                        *raw = None;
+                    }
+                    return;
+                }
+            }
+        }
+
+        let index = section
+            .lines
+            .iter()
+            .rposition(|l| matches!(l, Line::Pair { .. }))
+            .map_or(0, |i| i + 1);
+        section.lines.insert(index, Line::Pair {
+            key: key.to_string(),
+            value,
+            raw: None,
+        });
+    }
+
+    /// Remove a key from a section. Returns whether the key existed.
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        let Some(section) = self.sections.iter_mut().find(|s| s.name == section) else {
+            return false;
+        };
+        let len = section.lines.len();
+        section.lines.retain(|l| !matches!(l, Line::Pair { key: k, .. } if k == key));
+        section.lines.len() != len
+    }
+
+    /// Remove a section and all its keys. The root section can only be cleared.
+    pub fn remove_section(&mut self, section: &str) -> bool {
+        if section.is_empty() {
+            let root = &mut self.sections[0];
+            let had_keys = root.lines.iter().any(|l| matches!(l, Line::Pair { .. }));
+            root.lines.clear();
+            return had_keys;
+        }
+        let len = self.sections.len();
+        self.sections.retain(|s| s.name != section);
+        self.sections.len() != len
+    }
+
+    /// Read a struct from a section, see [`ConfigSection`].
+    pub fn read<T: ConfigSection>(&self, section: &str) -> Result<T, String> {
+        T::from_section(self, section)
+    }
+
+    /// Write a struct into a section, see [`ConfigSection`].
+    pub fn write<T: ConfigSection>(&mut self, section: &str, value: &T) {
+        value.to_section(self, section);
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
+
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Config::parse(s)
+    }
+}
+
+impl Display for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut written = false;
+        for section in &self.sections {
+            match &section.header {
+                Some(header) => writeln!(f, "{header}")?,
+                None if section.name.is_empty() => {}
+                None => {
+                    if written {
+                        writeln!(f)?;
+                    }
+                    writeln!(f, "[{}]", section.name)?;
+                }
+            }
+            written |= !section.name.is_empty();
+            for line in &section.lines {
+                match line {
+                    Line::Raw(raw) | Line::Pair { raw: Some(raw), .. } => writeln!(f, "{raw}")?,
+                    Line::Pair { key, value, raw: None } => writeln!(f, "{key} = {}", format_value(value))?,
+                }
+                written = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A struct that can be mapped onto a config section, usually derived using
+/// `#[derive(ConfigSection)]`. Every field is stored under its name and must implement
+/// [`FromStr`] and [`ToString`]. Fields marked with `#[config(default)]` or
+/// `#[config(default = value)]` are optional.
+pub trait ConfigSection: Sized {
+    fn from_section(config: &Config, section: &str) -> Result<Self, String>;
+
+    fn to_section(&self, config: &mut Config, section: &str);
+}
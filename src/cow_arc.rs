@@ -0,0 +1,156 @@
+use crate::save::{Loader, Savable, SaveError, SaveSize, Saver};
+use std::borrow::Borrow;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A shared value which is cloned only when it is modified while shared, for large values that are
+/// mostly read but occasionally changed per instance.
+///
+/// Saving writes the value itself, so loading creates an unshared copy for every saved instance.
+///
+/// ```
+/// use mvutils::cow_arc::CowArc;
+///
+/// let config = CowArc::new(vec![1, 2, 3]);
+/// let mut tweaked = config.clone();
+/// assert!(CowArc::ptr_eq(&config, &tweaked));
+///
+/// tweaked.make_mut().push(4);
+/// assert!(!CowArc::ptr_eq(&config, &tweaked));
+/// assert_eq!(*config, vec![1, 2, 3]);
+/// ```
+pub struct CowArc<T> {
+    inner: Arc<T>,
+}
+
+impl<T> CowArc<T> {
+    pub fn new(value: T) -> Self {
+        CowArc { inner: Arc::new(value) }
+    }
+
+    /// Whether both point to the same value.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.inner, &other.inner)
+    }
+
+    /// Whether other instances share the value.
+    pub fn is_shared(this: &Self) -> bool {
+        Arc::strong_count(&this.inner) > 1 || Arc::weak_count(&this.inner) > 0
+    }
+
+    /// The amount of instances sharing the value.
+    pub fn share_count(this: &Self) -> usize {
+        Arc::strong_count(&this.inner)
+    }
+
+    /// A mutable reference to the value if it is not shared.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        Arc::get_mut(&mut this.inner)
+    }
+
+    pub fn into_arc(this: Self) -> Arc<T> {
+        this.inner
+    }
+}
+
+impl<T: Clone> CowArc<T> {
+    /// A mutable reference to the value, cloning it first if it is shared.
+    pub fn make_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// The value, cloning it if it is shared.
+    pub fn into_inner(this: Self) -> T {
+        Arc::try_unwrap(this.inner).unwrap_or_else(|arc| (*arc).clone())
+    }
+}
+
+impl<T> Clone for CowArc<T> {
+    fn clone(&self) -> Self {
+        CowArc { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Deref for CowArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> AsRef<T> for CowArc<T> {
+    fn as_ref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> Borrow<T> for CowArc<T> {
+    fn borrow(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> From<T> for CowArc<T> {
+    fn from(value: T) -> Self {
+        CowArc::new(value)
+    }
+}
+
+impl<T> From<Arc<T>> for CowArc<T> {
+    fn from(value: Arc<T>) -> Self {
+        CowArc { inner: value }
+    }
+}
+
+impl<T: Default> Default for CowArc<T> {
+    fn default() -> Self {
+        CowArc::new(T::default())
+    }
+}
+
+impl<T: Debug> Debug for CowArc<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.inner, f)
+    }
+}
+
+impl<T: Display> Display for CowArc<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&*self.inner, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for CowArc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        CowArc::ptr_eq(self, other) || *self.inner == *other.inner
+    }
+}
+
+impl<T: Eq> Eq for CowArc<T> {}
+
+impl<T: Hash> Hash for CowArc<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash(state)
+    }
+}
+
+impl<T: Savable> Savable for CowArc<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.inner.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        T::load(loader).map(CowArc::new)
+    }
+}
+
+impl<T: SaveSize> SaveSize for CowArc<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn save_size(&self) -> usize {
+        self.inner.save_size()
+    }
+}
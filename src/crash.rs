@@ -0,0 +1,213 @@
+use crate as mvutils;
+use crate::platform;
+use crate::print::{Printer, Style};
+use crate::save::fs::{load_from_file, save_to_file};
+use crate::utils::PanicStyle;
+use mvutils_proc_macro::Savable;
+use std::backtrace::Backtrace;
+use std::fmt::{Display, Formatter};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The file extension of crash dumps written by a [`CrashReporter`].
+pub const CRASH_DUMP_EXTENSION: &str = "mvcrash";
+
+/// Information about a panic, captured by a [`CrashReporter`].
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub struct CrashReport {
+    pub time: SystemTime,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// Custom context attached by the callbacks registered using [`CrashReporter::context`].
+    pub context: Vec<(String, String)>,
+}
+
+impl CrashReport {
+    /// Load a crash dump written by a [`CrashReporter`].
+    pub fn load_dump(path: impl AsRef<Path>) -> Result<CrashReport, String> {
+        load_from_file(path)
+    }
+
+    /// Print the report with colors to the standard output.
+    pub fn print(&self) {
+        let mut printer = Printer::start()
+            .style(Style::Error, &format!("Thread '{}' panicked", self.thread))
+            .text(&format!(" with message '{}'", self.message));
+        if let Some(location) = &self.location {
+            printer = printer.style(Style::Muted, &format!(" at {location}"));
+        }
+        printer = printer.ln();
+        for (name, value) in &self.context {
+            printer = printer.style(Style::Warning, &format!("{name}: ")).text_ln(value);
+        }
+        if !self.backtrace.is_empty() {
+            printer = printer.style_ln(Style::Muted, &self.backtrace);
+        }
+        printer.flush();
+    }
+}
+
+impl Display for CrashReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Thread '{}' panicked with message '{}'", self.thread, self.message)?;
+        if let Some(location) = &self.location {
+            write!(f, " at {location}")?;
+        }
+        writeln!(f)?;
+        for (name, value) in &self.context {
+            writeln!(f, "{name}: {value}")?;
+        }
+        if !self.backtrace.is_empty() {
+            writeln!(f, "{}", self.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+type ContextFn = Box<dyn Fn() -> String + Send + Sync>;
+type ReportFn = Box<dyn Fn(&CrashReport) + Send + Sync>;
+
+/// A panic hook that captures a [`CrashReport`] including a backtrace and custom context, prints it
+/// and optionally persists it as a crash dump. This is an extended version of
+/// [`setup_private_panic`](crate::utils::setup_private_panic).
+///
+/// ```no_run
+/// use mvutils::crash::CrashReporter;
+///
+/// CrashReporter::new()
+///     .dump_dir("crashes")
+///     .context("version", || env!("CARGO_PKG_VERSION").to_string())
+///     .install();
+/// ```
+pub struct CrashReporter {
+    style: PanicStyle,
+    dump_dir: Option<PathBuf>,
+    print: bool,
+    backtrace: bool,
+    context: Vec<(String, ContextFn)>,
+    callbacks: Vec<ReportFn>,
+}
+
+impl CrashReporter {
+    pub fn new() -> Self {
+        CrashReporter {
+            style: PanicStyle::Normal,
+            dump_dir: None,
+            print: true,
+            backtrace: true,
+            context: Vec::new(),
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn style(mut self, style: PanicStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Write a crash dump file into this directory for every panic.
+    pub fn dump_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dump_dir = Some(dir.into());
+        self
+    }
+
+    /// Whether to print the report to the standard output, enabled by default.
+    pub fn print(mut self, print: bool) -> Self {
+        self.print = print;
+        self
+    }
+
+    /// Whether to capture a backtrace, enabled by default.
+    pub fn backtrace(mut self, backtrace: bool) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    /// Attach custom context to every report, for example a snapshot of a [`State`](crate::state::State).
+    /// The callback must not panic.
+    pub fn context(mut self, name: &str, f: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.context.push((name.to_string(), Box::new(f)));
+        self
+    }
+
+    /// Call a function with every captured report, for example to send it to a server.
+    pub fn on_report(mut self, f: impl Fn(&CrashReport) + Send + Sync + 'static) -> Self {
+        self.callbacks.push(Box::new(f));
+        self
+    }
+
+    /// Capture a report for the panic.
+    pub fn capture(&self, info: &PanicHookInfo) -> CrashReport {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&'static str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+
+        CrashReport {
+            time: platform::system_now(),
+            thread: std::thread::current().name().unwrap_or("unknown").to_string(),
+            message,
+            location: info.location().map(|l| l.to_string()),
+            backtrace: if self.backtrace { Backtrace::force_capture().to_string() } else { String::new() },
+            context: self.context.iter().map(|(name, f)| (name.clone(), f())).collect(),
+        }
+    }
+
+    /// Write a crash dump of the report into the dump directory, returning its path.
+    pub fn write_dump(&self, report: &CrashReport) -> Option<PathBuf> {
+        let dir = self.dump_dir.as_ref()?;
+        std::fs::create_dir_all(dir).ok()?;
+        let millis = report.time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let path = dir.join(format!("crash-{millis}-{}.{CRASH_DUMP_EXTENSION}", std::process::id()));
+        save_to_file(&path, report).ok()?;
+        Some(path)
+    }
+
+    fn handle(&self, info: &PanicHookInfo) {
+        let report = self.capture(info);
+        if self.print {
+            report.print();
+        }
+        if let Some(path) = self.write_dump(&report) {
+            if self.print {
+                println!("Crash dump written to {}", path.display());
+            }
+        }
+        for callback in &self.callbacks {
+            callback(&report);
+        }
+        match self.style {
+            PanicStyle::Normal => {}
+            PanicStyle::ForceExit => std::process::exit(1),
+            PanicStyle::Abort => std::process::abort(),
+        }
+    }
+
+    /// Install this reporter as the panic hook.
+    pub fn install(self) {
+        std::panic::set_hook(Box::new(move |info| self.handle(info)));
+    }
+}
+
+impl Default for CrashReporter {
+    fn default() -> Self {
+        CrashReporter::new()
+    }
+}
+
+/// List the crash dumps in a directory, oldest first.
+pub fn list_dumps(dir: impl AsRef<Path>) -> Vec<PathBuf> {
+    let mut dumps = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == CRASH_DUMP_EXTENSION))
+        .collect::<Vec<_>>();
+    dumps.sort();
+    dumps
+}
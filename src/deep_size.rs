@@ -0,0 +1,218 @@
+//! Measuring the memory owned by a value, including what it allocated on the heap.
+//!
+//! Sizes are approximations: the capacity of collections is counted, but the bookkeeping of the
+//! allocator is not, and hash maps and B-trees are estimated from their length and capacity.
+//! Values behind an [`Rc`] or [`Arc`] are counted by every owner, skip shared fields with
+//! `#[deep_size(skip)]` to count them once.
+//!
+//! ```
+//! use mvutils::deep_size::DeepSizeOf;
+//! use mvutils::DeepSizeOf;
+//!
+//! #[derive(DeepSizeOf)]
+//! struct Chunk {
+//!     name: String,
+//!     blocks: Vec<u16>,
+//! }
+//!
+//! let chunk = Chunk { name: String::with_capacity(16), blocks: Vec::with_capacity(4096) };
+//! assert_eq!(chunk.heap_size(), 16 + 4096 * 2);
+//! assert_eq!(chunk.deep_size_of(), std::mem::size_of::<Chunk>() + 16 + 4096 * 2);
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::mem::{size_of, size_of_val};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The memory used by a value.
+pub trait DeepSizeOf {
+    /// The number of bytes the value allocated on the heap, not including its inline size.
+    fn heap_size(&self) -> usize;
+
+    /// The inline size of the value plus [`DeepSizeOf::heap_size`].
+    fn deep_size_of(&self) -> usize {
+        size_of_val(self) + self.heap_size()
+    }
+}
+
+macro_rules! impl_deep_size_inline {
+    ($($t:ty),*) => {
+        $(
+            impl DeepSizeOf for $t {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_deep_size_inline!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, Duration, ());
+
+impl DeepSizeOf for str {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl DeepSizeOf for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: DeepSizeOf + ?Sized> DeepSizeOf for &T {
+    /// References do not own what they point to.
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: DeepSizeOf + ?Sized> DeepSizeOf for Box<T> {
+    fn heap_size(&self) -> usize {
+        (**self).deep_size_of()
+    }
+}
+
+impl<T: DeepSizeOf + ?Sized> DeepSizeOf for Rc<T> {
+    /// Includes the reference counts.
+    fn heap_size(&self) -> usize {
+        2 * size_of::<usize>() + (**self).deep_size_of()
+    }
+}
+
+impl<T: DeepSizeOf + ?Sized> DeepSizeOf for Arc<T> {
+    /// Includes the reference counts.
+    fn heap_size(&self) -> usize {
+        2 * size_of::<usize>() + (**self).deep_size_of()
+    }
+}
+
+impl<T: DeepSizeOf + Copy> DeepSizeOf for Cell<T> {
+    fn heap_size(&self) -> usize {
+        self.get().heap_size()
+    }
+}
+
+impl<T: DeepSizeOf> DeepSizeOf for RefCell<T> {
+    fn heap_size(&self) -> usize {
+        self.borrow().heap_size()
+    }
+}
+
+impl<T: DeepSizeOf> DeepSizeOf for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T: DeepSizeOf, E: DeepSizeOf> DeepSizeOf for Result<T, E> {
+    fn heap_size(&self) -> usize {
+        match self {
+            Ok(value) => value.heap_size(),
+            Err(error) => error.heap_size(),
+        }
+    }
+}
+
+impl<T: DeepSizeOf> DeepSizeOf for [T] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(T::heap_size).sum()
+    }
+}
+
+impl<T: DeepSizeOf, const N: usize> DeepSizeOf for [T; N] {
+    fn heap_size(&self) -> usize {
+        self.as_slice().heap_size()
+    }
+}
+
+impl<T: DeepSizeOf> DeepSizeOf for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.as_slice().heap_size()
+    }
+}
+
+impl<T: DeepSizeOf> DeepSizeOf for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: DeepSizeOf + Ord> DeepSizeOf for BinaryHeap<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+/// The table of a hash map with the capacity, which has a control byte per bucket and about one
+/// bucket in eight left empty.
+fn table_size(capacity: usize, entry: usize) -> usize {
+    if capacity == 0 {
+        0
+    } else {
+        let buckets = (capacity * 8 / 7).next_power_of_two();
+        buckets * (entry + 1)
+    }
+}
+
+/// A B-tree node holds up to 11 entries and two pointers, and is about two thirds full on average.
+fn tree_size(len: usize, entry: usize) -> usize {
+    len * (entry * 3 / 2 + size_of::<usize>())
+}
+
+impl<K: DeepSizeOf, V: DeepSizeOf, S> DeepSizeOf for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        table_size(self.capacity(), size_of::<(K, V)>()) + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+impl<T: DeepSizeOf, S> DeepSizeOf for HashSet<T, S> {
+    fn heap_size(&self) -> usize {
+        table_size(self.capacity(), size_of::<T>()) + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: DeepSizeOf, V: DeepSizeOf, S> DeepSizeOf for hashbrown::HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        table_size(self.capacity(), size_of::<(K, V)>()) + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+impl<T: DeepSizeOf, S> DeepSizeOf for hashbrown::HashSet<T, S> {
+    fn heap_size(&self) -> usize {
+        table_size(self.capacity(), size_of::<T>()) + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: DeepSizeOf, V: DeepSizeOf> DeepSizeOf for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        tree_size(self.len(), size_of::<(K, V)>()) + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+impl<T: DeepSizeOf> DeepSizeOf for BTreeSet<T> {
+    fn heap_size(&self) -> usize {
+        tree_size(self.len(), size_of::<T>()) + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+macro_rules! impl_deep_size_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: DeepSizeOf),+> DeepSizeOf for ($($name,)+) {
+            fn heap_size(&self) -> usize {
+                0 $(+ self.$index.heap_size())+
+            }
+        }
+    };
+}
+
+impl_deep_size_tuple!(A 0);
+impl_deep_size_tuple!(A 0, B 1);
+impl_deep_size_tuple!(A 0, B 1, C 2);
+impl_deep_size_tuple!(A 0, B 1, C 2, D 3);
+impl_deep_size_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_deep_size_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
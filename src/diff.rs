@@ -0,0 +1,396 @@
+//! Diffs of text and of structured values, to show what changed in a readable way.
+//!
+//! ```
+//! use mvutils::diff::{diff, TextDiff};
+//! use mvutils::Diff;
+//!
+//! let text = TextDiff::new("a\nb\nc", "a\nB\nc");
+//! assert_eq!(text.to_string(), " a\n-b\n+B\n c\n");
+//!
+//! #[derive(Diff, Debug)]
+//! struct Window {
+//!     title: String,
+//!     size: (u32, u32),
+//! }
+//!
+//! let old = Window { title: "Game".to_string(), size: (800, 600) };
+//! let new = Window { title: "Game".to_string(), size: (1280, 600) };
+//! assert_eq!(diff(&old, &new).to_string(), "size.0: 800 -> 1280\n");
+//! ```
+
+use crate::print::{Printer, Style};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::time::Duration;
+
+/// A line of a [`TextDiff`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// A line based diff between two texts.
+///
+/// [`Display`] prints it in the unified format, with a `-` before removed lines, a `+` before
+/// added lines and a space before unchanged ones. [`TextDiff::printer`] prints it with colors.
+#[derive(Clone, Debug)]
+pub struct TextDiff<'a> {
+    lines: Vec<DiffLine<'a>>,
+    context: Option<usize>,
+}
+
+impl<'a> TextDiff<'a> {
+    pub fn new(old: &'a str, new: &'a str) -> Self {
+        let old = old.lines().collect::<Vec<_>>();
+        let new = new.lines().collect::<Vec<_>>();
+
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+        let a = &old[prefix..old.len() - suffix];
+        let b = &new[prefix..new.len() - suffix];
+
+        // The length of the longest common subsequence of a[i..] and b[j..].
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let mut lines = old[..prefix].iter().map(|l| DiffLine::Same(l)).collect::<Vec<_>>();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                lines.push(DiffLine::Same(a[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                lines.push(DiffLine::Removed(a[i]));
+                i += 1;
+            } else {
+                lines.push(DiffLine::Added(b[j]));
+                j += 1;
+            }
+        }
+        lines.extend(a[i..].iter().map(|l| DiffLine::Removed(l)));
+        lines.extend(b[j..].iter().map(|l| DiffLine::Added(l)));
+        lines.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Same(l)));
+
+        TextDiff { lines, context: None }
+    }
+
+    /// Only show this many unchanged lines around every change, replacing the others with `...`.
+    pub fn context(mut self, lines: usize) -> Self {
+        self.context = Some(lines);
+        self
+    }
+
+    pub fn lines(&self) -> &[DiffLine<'a>] {
+        &self.lines
+    }
+
+    /// Whether both texts have the same lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|l| matches!(l, DiffLine::Same(_)))
+    }
+
+    /// The lines to show, with `None` for skipped lines.
+    fn visible(&self) -> Vec<Option<DiffLine<'a>>> {
+        let Some(context) = self.context else {
+            return self.lines.iter().copied().map(Some).collect();
+        };
+        let changes = self.lines.iter().enumerate().filter(|(_, l)| !matches!(l, DiffLine::Same(_))).map(|(i, _)| i).collect::<Vec<_>>();
+        let mut visible = Vec::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            let near = changes.iter().any(|c| c.abs_diff(i) <= context);
+            if near {
+                visible.push(Some(*line));
+            } else if visible.last().map_or(true, Option::is_some) {
+                visible.push(None);
+            }
+        }
+        visible
+    }
+
+    /// A printer with the diff, with removed lines styled as errors and added lines as successes.
+    pub fn printer(&self) -> Printer {
+        self.visible().into_iter().fold(Printer::start(), |printer, line| match line {
+            Some(DiffLine::Same(l)) => printer.text_ln(&format!(" {}", l)),
+            Some(DiffLine::Removed(l)) => printer.style_ln(Style::Error, &format!("-{}", l)),
+            Some(DiffLine::Added(l)) => printer.style_ln(Style::Success, &format!("+{}", l)),
+            None => printer.style_ln(Style::Muted, "..."),
+        })
+    }
+
+    pub fn print(&self) {
+        self.printer().flush();
+    }
+}
+
+impl Display for TextDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for line in self.visible() {
+            match line {
+                Some(DiffLine::Same(l)) => writeln!(f, " {}", l)?,
+                Some(DiffLine::Removed(l)) => writeln!(f, "-{}", l)?,
+                Some(DiffLine::Added(l)) => writeln!(f, "+{}", l)?,
+                None => writeln!(f, "...")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A difference between two values at a path, like `inventory.items[2].count`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change {
+    pub path: String,
+    /// The debug representation of the old value, `None` if it was added.
+    pub old: Option<String>,
+    /// The debug representation of the new value, `None` if it was removed.
+    pub new: Option<String>,
+}
+
+impl Change {
+    pub fn changed(path: &str, old: &(impl Debug + ?Sized), new: &(impl Debug + ?Sized)) -> Self {
+        Change {
+            path: path.to_string(),
+            old: Some(format!("{:?}", old)),
+            new: Some(format!("{:?}", new)),
+        }
+    }
+
+    pub fn added(path: &str, new: &(impl Debug + ?Sized)) -> Self {
+        Change {
+            path: path.to_string(),
+            old: None,
+            new: Some(format!("{:?}", new)),
+        }
+    }
+
+    pub fn removed(path: &str, old: &(impl Debug + ?Sized)) -> Self {
+        Change {
+            path: path.to_string(),
+            old: Some(format!("{:?}", old)),
+            new: None,
+        }
+    }
+
+    fn display_path(&self) -> &str {
+        if self.path.is_empty() {
+            "<value>"
+        } else {
+            &self.path
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{}: {} -> {}", self.display_path(), old, new),
+            (None, Some(new)) => write!(f, "{}: added {}", self.display_path(), new),
+            (Some(old), None) => write!(f, "{}: removed {}", self.display_path(), old),
+            (None, None) => write!(f, "{}: changed", self.display_path()),
+        }
+    }
+}
+
+/// All differences between two values, see [`diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Changes(pub Vec<Change>);
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Change> {
+        self.0.iter()
+    }
+
+    /// The change at the given path.
+    pub fn get(&self, path: &str) -> Option<&Change> {
+        self.0.iter().find(|c| c.path == path)
+    }
+
+    /// A printer with one line per change, showing old values as errors and new values as successes.
+    pub fn printer(&self) -> Printer {
+        self.0.iter().fold(Printer::start(), |mut printer, change| {
+            printer = printer.style(Style::Heading, change.display_path()).text(": ");
+            match (&change.old, &change.new) {
+                (Some(old), Some(new)) => printer.style(Style::Error, old).text(" -> ").style_ln(Style::Success, new),
+                (None, Some(new)) => printer.style_ln(Style::Success, &format!("added {}", new)),
+                (Some(old), None) => printer.style_ln(Style::Error, &format!("removed {}", old)),
+                (None, None) => printer.text_ln("changed"),
+            }
+        })
+    }
+
+    pub fn print(&self) {
+        self.printer().flush();
+    }
+}
+
+impl Display for Changes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.0 {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Changes {
+    type Item = Change;
+    type IntoIter = std::vec::IntoIter<Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// A value which can be compared field by field to list what changed.
+///
+/// This can be derived for structs and enums using `#[derive(Diff)]`, if all fields implement
+/// `Diff`. Fields marked with `#[diff(skip)]` are ignored. Enums with different variants are
+/// reported as a single change.
+pub trait Diff: Debug {
+    /// Push the differences to `other` to the changes, with `path` being the path to this value.
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>);
+}
+
+/// List the differences between two values.
+pub fn diff<T: Diff + ?Sized>(old: &T, new: &T) -> Changes {
+    let mut changes = Vec::new();
+    old.diff_into(new, "", &mut changes);
+    Changes(changes)
+}
+
+/// The path of a field inside the value at `path`.
+#[doc(hidden)]
+pub fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+macro_rules! impl_diff_eq {
+    ($($t:ty),*) => {
+        $(
+            impl Diff for $t {
+                fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+                    if self != other {
+                        changes.push(Change::changed(path, self, other));
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_diff_eq!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, String, str, Duration, ());
+
+impl<T: Diff + ?Sized> Diff for &T {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        (**self).diff_into(*other, path, changes);
+    }
+}
+
+impl<T: Diff + ?Sized> Diff for Box<T> {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        (**self).diff_into(other, path, changes);
+    }
+}
+
+impl<T: Diff> Diff for Option<T> {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        match (self, other) {
+            (Some(a), Some(b)) => a.diff_into(b, path, changes),
+            (None, Some(b)) => changes.push(Change::added(path, b)),
+            (Some(a), None) => changes.push(Change::removed(path, a)),
+            (None, None) => {}
+        }
+    }
+}
+
+impl<T: Diff> Diff for [T] {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        for (i, (a, b)) in self.iter().zip(other).enumerate() {
+            a.diff_into(b, &format!("{}[{}]", path, i), changes);
+        }
+        for (i, a) in self.iter().enumerate().skip(other.len()) {
+            changes.push(Change::removed(&format!("{}[{}]", path, i), a));
+        }
+        for (i, b) in other.iter().enumerate().skip(self.len()) {
+            changes.push(Change::added(&format!("{}[{}]", path, i), b));
+        }
+    }
+}
+
+impl<T: Diff> Diff for Vec<T> {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        self.as_slice().diff_into(other.as_slice(), path, changes);
+    }
+}
+
+impl<T: Diff, const N: usize> Diff for [T; N] {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        self.as_slice().diff_into(other.as_slice(), path, changes);
+    }
+}
+
+impl<K: Debug + Ord, V: Diff> Diff for BTreeMap<K, V> {
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        for (key, a) in self {
+            let path = format!("{}[{:?}]", path, key);
+            match other.get(key) {
+                Some(b) => a.diff_into(b, &path, changes),
+                None => changes.push(Change::removed(&path, a)),
+            }
+        }
+        for (key, b) in other.iter().filter(|(key, _)| !self.contains_key(*key)) {
+            changes.push(Change::added(&format!("{}[{:?}]", path, key), b));
+        }
+    }
+}
+
+impl<K: Debug + Ord + Hash, V: Diff> Diff for HashMap<K, V> {
+    /// Keys are visited in sorted order, so the changes are listed in a stable order.
+    fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+        let ours = self.iter().collect::<BTreeMap<_, _>>();
+        let theirs = other.iter().collect::<BTreeMap<_, _>>();
+        ours.diff_into(&theirs, path, changes);
+    }
+}
+
+macro_rules! impl_diff_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Diff),+> Diff for ($($name,)+) {
+            fn diff_into(&self, other: &Self, path: &str, changes: &mut Vec<Change>) {
+                $( self.$index.diff_into(&other.$index, &join(path, stringify!($index)), changes); )+
+            }
+        }
+    };
+}
+
+impl_diff_tuple!(A 0);
+impl_diff_tuple!(A 0, B 1);
+impl_diff_tuple!(A 0, B 1, C 2);
+impl_diff_tuple!(A 0, B 1, C 2, D 3);
+impl_diff_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_diff_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
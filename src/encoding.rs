@@ -0,0 +1,176 @@
+use bytebuffer::ByteBuffer;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64_STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The input length is not valid for the encoding.
+    InvalidLength(usize),
+    /// The input contains a character that is not part of the alphabet, at the given byte index.
+    InvalidCharacter(usize, char),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::InvalidLength(len) => write!(f, "Invalid input length {len}!"),
+            DecodeError::InvalidCharacter(index, c) => write!(f, "Invalid character '{}' at index {index}!", c.escape_default()),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// The alphabet used for base64 encoding.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Base64 {
+    /// The standard alphabet using `+` and `/`, padded with `=`.
+    Standard,
+    /// The URL and filename safe alphabet using `-` and `_`, without padding.
+    UrlSafe,
+}
+
+impl Base64 {
+    fn alphabet(self) -> &'static [u8; 64] {
+        match self {
+            Base64::Standard => BASE64_STANDARD,
+            Base64::UrlSafe => BASE64_URL_SAFE,
+        }
+    }
+
+    fn decode_char(self, c: u8) -> Option<u8> {
+        match (c, self) {
+            (b'A'..=b'Z', _) => Some(c - b'A'),
+            (b'a'..=b'z', _) => Some(c - b'a' + 26),
+            (b'0'..=b'9', _) => Some(c - b'0' + 52),
+            (b'+', Base64::Standard) | (b'-', Base64::UrlSafe) => Some(62),
+            (b'/', Base64::Standard) | (b'_', Base64::UrlSafe) => Some(63),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_char(input: &str, index: usize) -> DecodeError {
+    let c = input.char_indices().take_while(|(i, _)| *i <= index).last().map_or('\0', |(_, c)| c);
+    DecodeError::InvalidCharacter(index, c)
+}
+
+/// Encode bytes as lowercase hex.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        output.push(HEX[(b >> 4) as usize] as char);
+        output.push(HEX[(b & 0xF) as usize] as char);
+    }
+    output
+}
+
+/// Decode hex, accepting both upper and lowercase digits.
+pub fn decode_hex(input: &str) -> Result<Vec<u8>, DecodeError> {
+    let bytes = input.as_bytes();
+    if bytes.len() % 2 != 0 {
+        return Err(DecodeError::InvalidLength(bytes.len()));
+    }
+    let digit = |i: usize| (bytes[i] as char).to_digit(16).map(|d| d as u8).ok_or_else(|| invalid_char(input, i));
+    (0..bytes.len()).step_by(2).map(|i| Ok((digit(i)? << 4) | digit(i + 1)?)).collect()
+}
+
+/// Encode bytes as base64 using the standard alphabet with padding.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    encode_base64_with(bytes, Base64::Standard)
+}
+
+/// Encode bytes as base64 using the URL safe alphabet without padding.
+pub fn encode_base64_url(bytes: &[u8]) -> String {
+    encode_base64_with(bytes, Base64::UrlSafe)
+}
+
+pub fn encode_base64_with(bytes: &[u8], variant: Base64) -> String {
+    let alphabet = variant.alphabet();
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..=chunk.len() {
+            output.push(alphabet[(n >> (18 - i * 6)) as usize & 0x3F] as char);
+        }
+        if variant == Base64::Standard {
+            for _ in chunk.len()..3 {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Decode base64 using the standard alphabet. Padding is optional.
+pub fn decode_base64(input: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_base64_with(input, Base64::Standard)
+}
+
+/// Decode base64 using the URL safe alphabet. Padding is optional.
+pub fn decode_base64_url(input: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_base64_with(input, Base64::UrlSafe)
+}
+
+pub fn decode_base64_with(input: &str, variant: Base64) -> Result<Vec<u8>, DecodeError> {
+    let bytes = input.as_bytes();
+    let data = input.trim_end_matches('=').as_bytes();
+    let padding = bytes.len() - data.len();
+    if data.len() % 4 == 1 || padding > 2 || (padding > 0 && bytes.len() % 4 != 0) {
+        return Err(DecodeError::InvalidLength(bytes.len()));
+    }
+
+    let mut output = Vec::with_capacity(data.len() * 3 / 4);
+    for (chunk_index, chunk) in data.chunks(4).enumerate() {
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = variant.decode_char(*c).ok_or_else(|| invalid_char(input, chunk_index * 4 + i))?;
+            n |= (value as u32) << (18 - i * 6);
+        }
+        for i in 0..chunk.len() - 1 {
+            output.push((n >> (16 - i * 8)) as u8);
+        }
+    }
+    Ok(output)
+}
+
+/// Encoding helpers for the contents of a [`ByteBuffer`].
+pub trait ByteBufferEncoding: Sized {
+    fn to_hex(&self) -> String;
+    fn to_base64(&self) -> String;
+    fn to_base64_url(&self) -> String;
+
+    fn from_hex(input: &str) -> Result<Self, DecodeError>;
+    fn from_base64(input: &str) -> Result<Self, DecodeError>;
+    fn from_base64_url(input: &str) -> Result<Self, DecodeError>;
+}
+
+impl ByteBufferEncoding for ByteBuffer {
+    fn to_hex(&self) -> String {
+        encode_hex(self.as_bytes())
+    }
+
+    fn to_base64(&self) -> String {
+        encode_base64(self.as_bytes())
+    }
+
+    fn to_base64_url(&self) -> String {
+        encode_base64_url(self.as_bytes())
+    }
+
+    fn from_hex(input: &str) -> Result<Self, DecodeError> {
+        decode_hex(input).map(ByteBuffer::from_vec)
+    }
+
+    fn from_base64(input: &str) -> Result<Self, DecodeError> {
+        decode_base64(input).map(ByteBuffer::from_vec)
+    }
+
+    fn from_base64_url(input: &str) -> Result<Self, DecodeError> {
+        decode_base64_url(input).map(ByteBuffer::from_vec)
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    Num(f64),
+    Bool(bool),
+}
+
+impl Value {
+    pub fn as_f64(self) -> Result<f64, String> {
+        match self {
+            Value::Num(n) => Ok(n),
+            Value::Bool(b) => Err(format!("Expected number, found {b}!")),
+        }
+    }
+
+    pub fn as_bool(self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            Value::Num(n) => Err(format!("Expected boolean, found {n}!")),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Num(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Num(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Const(Value),
+    Var(String),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    Call(String, Vec<Node>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 18] = ["==", "!=", "<=", ">=", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "^", "!", "(", ")", ","];
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let c = rest.chars().next().unwrap();
+        if c.is_ascii_digit() || c == '.' {
+            let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+            let number = &rest[..end];
+            tokens.push(Token::Num(number.parse().map_err(|_| format!("Invalid number '{number}'!"))?));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest.find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.').unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| format!("Unexpected character '{c}'!"))?;
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn binary(&mut self, ops: &[(&str, Op)], next: fn(&mut Self) -> Result<Node, String>) -> Result<Node, String> {
+        let mut node = next(self)?;
+        'outer: loop {
+            for (token, op) in ops {
+                if self.eat(token) {
+                    node = Node::Binary(*op, Box::new(node), Box::new(next(self)?));
+                    continue 'outer;
+                }
+            }
+            return Ok(node);
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        self.binary(&[("||", Op::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        self.binary(&[("&&", Op::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Node, String> {
+        let ops = [("==", Op::Eq), ("!=", Op::Ne), ("<=", Op::Le), (">=", Op::Ge), ("<", Op::Lt), (">", Op::Gt)];
+        let node = self.additive()?;
+        for (token, op) in ops {
+            if self.eat(token) {
+                return Ok(Node::Binary(op, Box::new(node), Box::new(self.additive()?)));
+            }
+        }
+        Ok(node)
+    }
+
+    fn additive(&mut self) -> Result<Node, String> {
+        self.binary(&[("+", Op::Add), ("-", Op::Sub)], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, String> {
+        self.binary(&[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.eat("-") {
+            Ok(Node::Neg(Box::new(self.unary()?)))
+        } else if self.eat("!") {
+            Ok(Node::Not(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.primary()?;
+        if self.eat("^") {
+            return Ok(Node::Binary(Op::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("Unexpected end of expression!")?;
+        self.pos += 1;
+        match token {
+            Token::Num(n) => Ok(Node::Const(Value::Num(n))),
+            Token::Ident(name) if name == "true" => Ok(Node::Const(Value::Bool(true))),
+            Token::Ident(name) if name == "false" => Ok(Node::Const(Value::Bool(false))),
+            Token::Ident(name) => {
+                if !self.eat("(") {
+                    return Ok(Node::Var(name));
+                }
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.or()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        if !self.eat(",") {
+                            return Err(format!("Expected ',' or ')' in call to '{name}'!"));
+                        }
+                    }
+                }
+                Ok(Node::Call(name, args))
+            }
+            Token::Op("(") => {
+                let node = self.or()?;
+                if !self.eat(")") {
+                    return Err("Expected ')'!".to_string());
+                }
+                Ok(node)
+            }
+            Token::Op(op) => Err(format!("Unexpected '{op}'!")),
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let expect = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(format!("Function '{name}' expects {n} arguments, found {}!", args.len()))
+        }
+    };
+    match name {
+        "abs" => expect(1).map(|_| args[0].abs()),
+        "floor" => expect(1).map(|_| args[0].floor()),
+        "ceil" => expect(1).map(|_| args[0].ceil()),
+        "round" => expect(1).map(|_| args[0].round()),
+        "sqrt" => expect(1).map(|_| args[0].sqrt()),
+        "clamp" => expect(3).map(|_| args[0].max(args[1]).min(args[2])),
+        "min" | "max" if args.is_empty() => Err(format!("Function '{name}' expects at least 1 argument!")),
+        "min" => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+        "max" => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        _ => Err(format!("Unknown function '{name}'!")),
+    }
+}
+
+/// A parsed arithmetic or boolean expression like `2 * (x + 1) >= 10`.
+///
+/// Supports numbers, `true`/`false`, variables, `+ - * / % ^`, comparisons, `&& || !`, parentheses
+/// and the functions `abs`, `floor`, `ceil`, `round`, `sqrt`, `clamp`, `min` and `max`. Variable
+/// names may contain dots, like `player.health`. `&&` and `||` short-circuit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    root: Node,
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Expr, String> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        let root = parser.or()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(format!("Unexpected token {:?} in expression!", parser.tokens[parser.pos]));
+        }
+        Ok(Expr { root })
+    }
+
+    /// The names of all variables used in the expression, in order of first appearance.
+    pub fn variables(&self) -> Vec<&str> {
+        fn visit<'a>(node: &'a Node, vars: &mut Vec<&'a str>) {
+            match node {
+                Node::Const(_) => {}
+                Node::Var(name) => {
+                    if !vars.contains(&name.as_str()) {
+                        vars.push(name);
+                    }
+                }
+                Node::Neg(n) | Node::Not(n) => visit(n, vars),
+                Node::Binary(_, a, b) => {
+                    visit(a, vars);
+                    visit(b, vars);
+                }
+                Node::Call(_, args) => args.iter().for_each(|a| visit(a, vars)),
+            }
+        }
+        let mut vars = Vec::new();
+        visit(&self.root, &mut vars);
+        vars
+    }
+
+    /// Evaluate the expression, resolving variables using the function.
+    pub fn eval_with(&self, lookup: &impl Fn(&str) -> Option<Value>) -> Result<Value, String> {
+        Self::eval_node(&self.root, lookup)
+    }
+
+    pub fn eval(&self, vars: &HashMap<String, Value>) -> Result<Value, String> {
+        self.eval_with(&|name| vars.get(name).copied())
+    }
+
+    pub fn eval_f64(&self, vars: &HashMap<String, Value>) -> Result<f64, String> {
+        self.eval(vars)?.as_f64()
+    }
+
+    pub fn eval_bool(&self, vars: &HashMap<String, Value>) -> Result<bool, String> {
+        self.eval(vars)?.as_bool()
+    }
+
+    fn eval_node(node: &Node, lookup: &impl Fn(&str) -> Option<Value>) -> Result<Value, String> {
+        let num = |node: &Node| Self::eval_node(node, lookup)?.as_f64();
+        let bool = |node: &Node| Self::eval_node(node, lookup)?.as_bool();
+        Ok(match node {
+            Node::Const(value) => *value,
+            Node::Var(name) => lookup(name).ok_or_else(|| format!("Unknown variable '{name}'!"))?,
+            Node::Neg(n) => Value::Num(-num(n)?),
+            Node::Not(n) => Value::Bool(!bool(n)?),
+            Node::Binary(Op::And, a, b) => Value::Bool(bool(a)? && bool(b)?),
+            Node::Binary(Op::Or, a, b) => Value::Bool(bool(a)? || bool(b)?),
+            Node::Binary(op @ (Op::Eq | Op::Ne), a, b) => {
+                let (a, b) = (Self::eval_node(a, lookup)?, Self::eval_node(b, lookup)?);
+                if std::mem::discriminant(&a) != std::mem::discriminant(&b) {
+                    return Err(format!("Cannot compare {a} with {b}!"));
+                }
+                Value::Bool((a == b) == (*op == Op::Eq))
+            }
+            Node::Binary(op, a, b) => {
+                let (a, b) = (num(a)?, num(b)?);
+                match op {
+                    Op::Add => Value::Num(a + b),
+                    Op::Sub => Value::Num(a - b),
+                    Op::Mul => Value::Num(a * b),
+                    Op::Div => Value::Num(a / b),
+                    Op::Rem => Value::Num(a % b),
+                    Op::Pow => Value::Num(a.powf(b)),
+                    Op::Lt => Value::Bool(a < b),
+                    Op::Le => Value::Bool(a <= b),
+                    Op::Gt => Value::Bool(a > b),
+                    Op::Ge => Value::Bool(a >= b),
+                    Op::Eq | Op::Ne | Op::And | Op::Or => unreachable!(),
+                }
+            }
+            Node::Call(name, args) => {
+                let args = args.iter().map(num).collect::<Result<Vec<_>, _>>()?;
+                Value::Num(call(name, &args)?)
+            }
+        })
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Expr::parse(s)
+    }
+}
+
+/// Parse and evaluate an expression in one step.
+pub fn eval(input: &str, vars: &HashMap<String, Value>) -> Result<Value, String> {
+    Expr::parse(input)?.eval(vars)
+}
@@ -0,0 +1,202 @@
+use crate::save::{Loader, Savable, Saver};
+
+type Guard<S, E> = Box<dyn Fn(&S, &E) -> bool + Send + Sync>;
+type StateHook<S> = Box<dyn FnMut(&S) + Send + Sync>;
+type TransitionHook<S, E> = Box<dyn FnMut(&S, &E, &S) + Send + Sync>;
+
+struct Transition<S, E> {
+    /// The source state, or [`None`] for transitions from any state.
+    from: Option<S>,
+    event: E,
+    to: S,
+    guard: Option<Guard<S, E>>,
+}
+
+/// A finite state machine with transitions of the form `state + event -> state`.
+///
+/// Transitions are checked in the order they were added, so the first transition whose guard allows
+/// it is taken.
+///
+/// ```
+/// use mvutils::fsm::Fsm;
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Door { Open, Closed, Locked }
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Action { Open, Close, Lock, Unlock }
+///
+/// let mut door = Fsm::builder(Door::Closed)
+///     .transition(Door::Closed, Action::Open, Door::Open)
+///     .transition(Door::Open, Action::Close, Door::Closed)
+///     .transition(Door::Closed, Action::Lock, Door::Locked)
+///     .transition(Door::Locked, Action::Unlock, Door::Closed)
+///     .build();
+///
+/// assert!(door.fire(&Action::Lock));
+/// assert!(!door.fire(&Action::Open));
+/// assert_eq!(door.state(), &Door::Locked);
+/// ```
+pub struct Fsm<S, E> {
+    state: S,
+    transitions: Vec<Transition<S, E>>,
+    on_enter: Vec<(S, StateHook<S>)>,
+    on_exit: Vec<(S, StateHook<S>)>,
+    on_transition: Vec<TransitionHook<S, E>>,
+}
+
+pub struct FsmBuilder<S, E> {
+    fsm: Fsm<S, E>,
+}
+
+impl<S: Clone + PartialEq, E: PartialEq> FsmBuilder<S, E> {
+    pub fn transition(mut self, from: S, event: E, to: S) -> Self {
+        self.fsm.transitions.push(Transition {
+            from: Some(from),
+            event,
+            to,
+            guard: None,
+        });
+        self
+    }
+
+    /// Add a transition which is only taken if the guard returns true.
+    pub fn guarded(mut self, from: S, event: E, to: S, guard: impl Fn(&S, &E) -> bool + Send + Sync + 'static) -> Self {
+        self.fsm.transitions.push(Transition {
+            from: Some(from),
+            event,
+            to,
+            guard: Some(Box::new(guard)),
+        });
+        self
+    }
+
+    /// Add a transition which can be taken from every state.
+    pub fn transition_any(mut self, event: E, to: S) -> Self {
+        self.fsm.transitions.push(Transition {
+            from: None,
+            event,
+            to,
+            guard: None,
+        });
+        self
+    }
+
+    /// Call the function whenever the state is entered through a transition.
+    pub fn on_enter(mut self, state: S, f: impl FnMut(&S) + Send + Sync + 'static) -> Self {
+        self.fsm.on_enter.push((state, Box::new(f)));
+        self
+    }
+
+    /// Call the function whenever the state is left through a transition.
+    pub fn on_exit(mut self, state: S, f: impl FnMut(&S) + Send + Sync + 'static) -> Self {
+        self.fsm.on_exit.push((state, Box::new(f)));
+        self
+    }
+
+    /// Call the function with the old state, the event and the new state after every transition.
+    pub fn on_transition(mut self, f: impl FnMut(&S, &E, &S) + Send + Sync + 'static) -> Self {
+        self.fsm.on_transition.push(Box::new(f));
+        self
+    }
+
+    pub fn build(self) -> Fsm<S, E> {
+        self.fsm
+    }
+}
+
+impl<S: Clone + PartialEq, E: PartialEq> Fsm<S, E> {
+    pub fn builder(initial: S) -> FsmBuilder<S, E> {
+        FsmBuilder {
+            fsm: Fsm {
+                state: initial,
+                transitions: Vec::new(),
+                on_enter: Vec::new(),
+                on_exit: Vec::new(),
+                on_transition: Vec::new(),
+            },
+        }
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn is(&self, state: &S) -> bool {
+        self.state == *state
+    }
+
+    fn find(&self, event: &E) -> Option<&Transition<S, E>> {
+        self.transitions.iter().find(|t| {
+            t.event == *event
+                && t.from.as_ref().map_or(true, |f| *f == self.state)
+                && t.guard.as_ref().map_or(true, |g| g(&self.state, event))
+        })
+    }
+
+    /// Whether firing the event would currently cause a transition.
+    pub fn can_fire(&self, event: &E) -> bool {
+        self.find(event).is_some()
+    }
+
+    /// All events which would currently cause a transition.
+    pub fn available_events(&self) -> Vec<&E> {
+        let mut events = Vec::new();
+        for transition in &self.transitions {
+            if !events.contains(&&transition.event) && self.can_fire(&transition.event) {
+                events.push(&transition.event);
+            }
+        }
+        events
+    }
+
+    /// Fire the event, running the exit and entry hooks if a transition is taken. Returns whether the
+    /// state changed.
+    pub fn fire(&mut self, event: &E) -> bool {
+        let Some(to) = self.find(event).map(|t| t.to.clone()) else {
+            return false;
+        };
+        for (state, hook) in &mut self.on_exit {
+            if *state == self.state {
+                hook(&self.state);
+            }
+        }
+        let from = std::mem::replace(&mut self.state, to);
+        for (state, hook) in &mut self.on_enter {
+            if *state == self.state {
+                hook(&self.state);
+            }
+        }
+        for hook in &mut self.on_transition {
+            hook(&from, event, &self.state);
+        }
+        true
+    }
+
+    /// Parse and fire the event, for example for events read from a script or the console.
+    pub fn fire_str(&mut self, event: &str) -> Result<bool, String>
+    where
+        E: TryFrom<String>,
+    {
+        let event = E::try_from(event.to_string()).map_err(|_| format!("Unknown event '{event}'!"))?;
+        Ok(self.fire(&event))
+    }
+
+    /// Set the state directly without running any hooks, for example when restoring a saved game.
+    pub fn set_state(&mut self, state: S) {
+        self.state = state;
+    }
+}
+
+impl<S: Savable + Clone + PartialEq, E: PartialEq> Fsm<S, E> {
+    /// Save the current state. The transitions and hooks are not saved.
+    pub fn save_state(&self, saver: &mut impl Saver) {
+        self.state.save(saver);
+    }
+
+    /// Load a state saved using [`Fsm::save_state`] without running any hooks.
+    pub fn load_state(&mut self, loader: &mut impl Loader) -> Result<(), String> {
+        self.state = S::load(loader)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+use crate as mvutils;
+use mvutils_proc_macro::{Savable, SaveSize};
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Savable, SaveSize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Savable, SaveSize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// A width and height.
+#[derive(Savable, SaveSize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct Extent {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// An axis-aligned rectangle given by its minimum corner and its size. Rectangles with a negative
+/// size are treated as empty.
+#[derive(Savable, SaveSize, Copy, Clone, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+macro_rules! impl_vec_ops {
+    ($t:ident, $($f:ident),*) => {
+        impl Add for $t {
+            type Output = $t;
+
+            fn add(self, rhs: $t) -> $t {
+                $t { $($f: self.$f + rhs.$f),* }
+            }
+        }
+
+        impl Sub for $t {
+            type Output = $t;
+
+            fn sub(self, rhs: $t) -> $t {
+                $t { $($f: self.$f - rhs.$f),* }
+            }
+        }
+
+        impl Mul for $t {
+            type Output = $t;
+
+            fn mul(self, rhs: $t) -> $t {
+                $t { $($f: self.$f * rhs.$f),* }
+            }
+        }
+
+        impl Div for $t {
+            type Output = $t;
+
+            fn div(self, rhs: $t) -> $t {
+                $t { $($f: self.$f / rhs.$f),* }
+            }
+        }
+
+        impl Mul<f32> for $t {
+            type Output = $t;
+
+            fn mul(self, rhs: f32) -> $t {
+                $t { $($f: self.$f * rhs),* }
+            }
+        }
+
+        impl Mul<$t> for f32 {
+            type Output = $t;
+
+            fn mul(self, rhs: $t) -> $t {
+                rhs * self
+            }
+        }
+
+        impl Div<f32> for $t {
+            type Output = $t;
+
+            fn div(self, rhs: f32) -> $t {
+                $t { $($f: self.$f / rhs),* }
+            }
+        }
+
+        impl Neg for $t {
+            type Output = $t;
+
+            fn neg(self) -> $t {
+                $t { $($f: -self.$f),* }
+            }
+        }
+
+        impl AddAssign for $t {
+            fn add_assign(&mut self, rhs: $t) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $t {
+            fn sub_assign(&mut self, rhs: $t) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign<f32> for $t {
+            fn mul_assign(&mut self, rhs: f32) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl DivAssign<f32> for $t {
+            fn div_assign(&mut self, rhs: f32) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl $t {
+            pub fn dot(self, other: $t) -> f32 {
+                0.0 $(+ self.$f * other.$f)*
+            }
+
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            pub fn distance(self, other: $t) -> f32 {
+                (other - self).length()
+            }
+
+            /// The vector scaled to a length of 1, or zero if its length is zero.
+            pub fn normalize(self) -> $t {
+                let length = self.length();
+                if length == 0.0 {
+                    self
+                } else {
+                    self / length
+                }
+            }
+
+            pub fn min(self, other: $t) -> $t {
+                $t { $($f: self.$f.min(other.$f)),* }
+            }
+
+            pub fn max(self, other: $t) -> $t {
+                $t { $($f: self.$f.max(other.$f)),* }
+            }
+
+            pub fn abs(self) -> $t {
+                $t { $($f: self.$f.abs()),* }
+            }
+
+            pub fn lerp(self, other: $t, t: f32) -> $t {
+                $t { $($f: lerp(self.$f, other.$f, t)),* }
+            }
+        }
+    };
+}
+
+impl_vec_ops!(Vec2, x, y);
+impl_vec_ops!(Vec3, x, y, z);
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2::splat(0.0);
+    pub const ONE: Vec2 = Vec2::splat(1.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub const fn splat(v: f32) -> Self {
+        Vec2 { x: v, y: v }
+    }
+
+    /// The z component of the cross product of both vectors extended to 3D.
+    pub fn perp_dot(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::splat(0.0);
+    pub const ONE: Vec3 = Vec3::splat(1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    pub const fn splat(v: f32) -> Self {
+        Vec3 { x: v, y: v, z: v }
+    }
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn truncate(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl Extent {
+    pub const ZERO: Extent = Extent::new(0.0, 0.0);
+
+    pub const fn new(width: f32, height: f32) -> Self {
+        Extent { width, height }
+    }
+
+    pub fn area(self) -> f32 {
+        self.width * self.height
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// The width divided by the height.
+    pub fn aspect_ratio(self) -> f32 {
+        self.width / self.height
+    }
+
+    pub fn scale(self, factor: f32) -> Extent {
+        Extent::new(self.width * factor, self.height * factor)
+    }
+
+    pub fn lerp(self, other: Extent, t: f32) -> Extent {
+        Extent::new(lerp(self.width, other.width, t), lerp(self.height, other.height, t))
+    }
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    pub fn from_pos_extent(pos: Vec2, extent: Extent) -> Self {
+        Rect::new(pos.x, pos.y, extent.width, extent.height)
+    }
+
+    /// The smallest rectangle containing both points.
+    pub fn from_points(a: Vec2, b: Vec2) -> Self {
+        let min = a.min(b);
+        let max = a.max(b);
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    pub fn from_center(center: Vec2, extent: Extent) -> Self {
+        Rect::new(center.x - extent.width / 2.0, center.y - extent.height / 2.0, extent.width, extent.height)
+    }
+
+    pub fn pos(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    pub fn extent(self) -> Extent {
+        Extent::new(self.width, self.height)
+    }
+
+    pub fn min(self) -> Vec2 {
+        self.pos()
+    }
+
+    pub fn max(self) -> Vec2 {
+        Vec2::new(self.x + self.width, self.y + self.height)
+    }
+
+    pub fn center(self) -> Vec2 {
+        Vec2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn area(self) -> f32 {
+        self.extent().area()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.extent().is_empty()
+    }
+
+    /// Whether the point is inside the rectangle. The minimum edges are inclusive, the maximum
+    /// edges exclusive.
+    pub fn contains(self, point: Vec2) -> bool {
+        point.x >= self.x && point.y >= self.y && point.x < self.x + self.width && point.y < self.y + self.height
+    }
+
+    pub fn contains_rect(self, other: Rect) -> bool {
+        other.x >= self.x && other.y >= self.y && other.max().x <= self.max().x && other.max().y <= self.max().y
+    }
+
+    /// Whether both rectangles overlap in an area larger than zero.
+    pub fn intersects(self, other: Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn intersection(self, other: Rect) -> Option<Rect> {
+        let min = self.min().max(other.min());
+        let max = self.max().min(other.max());
+        let rect = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    pub fn union(self, other: Rect) -> Rect {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        let min = self.min().min(other.min());
+        let max = self.max().max(other.max());
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    pub fn translate(self, offset: Vec2) -> Rect {
+        Rect::new(self.x + offset.x, self.y + offset.y, self.width, self.height)
+    }
+
+    /// Grow the rectangle by the amount on every side, or shrink it if the amount is negative.
+    pub fn expand(self, amount: f32) -> Rect {
+        Rect::new(self.x - amount, self.y - amount, self.width + amount * 2.0, self.height + amount * 2.0)
+    }
+
+    pub fn lerp(self, other: Rect, t: f32) -> Rect {
+        Rect::new(
+            lerp(self.x, other.x, t),
+            lerp(self.y, other.y, t),
+            lerp(self.width, other.width, t),
+            lerp(self.height, other.height, t),
+        )
+    }
+}
+
+impl From<(f32, f32)> for Vec2 {
+    fn from((x, y): (f32, f32)) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<[f32; 2]> for Vec2 {
+    fn from([x, y]: [f32; 2]) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Vec2> for [f32; 2] {
+    fn from(value: Vec2) -> Self {
+        [value.x, value.y]
+    }
+}
+
+impl From<(f32, f32, f32)> for Vec3 {
+    fn from((x, y, z): (f32, f32, f32)) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl From<[f32; 3]> for Vec3 {
+    fn from([x, y, z]: [f32; 3]) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl From<Vec3> for [f32; 3] {
+    fn from(value: Vec3) -> Self {
+        [value.x, value.y, value.z]
+    }
+}
+
+impl From<Extent> for Vec2 {
+    fn from(value: Extent) -> Self {
+        Vec2::new(value.width, value.height)
+    }
+}
+
+impl From<Vec2> for Extent {
+    fn from(value: Vec2) -> Self {
+        Extent::new(value.x, value.y)
+    }
+}
+
+impl Display for Vec2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Display for Vec3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl Display for Extent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl Display for Rect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}, {}x{}]", self.x, self.y, self.width, self.height)
+    }
+}
@@ -0,0 +1,144 @@
+//! A debug registry of the globals created with [`lazy!`](crate::lazy), [`lazy_init!`](crate::lazy_init)
+//! and [`create_once!`](crate::create_once), enabled by the `debug_globals` feature.
+//!
+//! Globals are registered before `main` runs, so the report also lists globals that were never
+//! initialized, along with their types, sizes and how long their initialization took.
+//!
+//! ```no_run
+//! use mvutils::{globals, lazy};
+//!
+//! lazy! {
+//!     static NAMES: Vec<String> = Vec::new();
+//! }
+//!
+//! globals::print_report_on_shutdown();
+//! ```
+//!
+//! Registration relies on the platform's static constructors and is supported on Linux, Android,
+//! the BSDs, macOS, iOS and Windows. On other platforms the registry stays empty.
+
+use crate::platform::Instant;
+use parking_lot::Mutex;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// A global which can be listed in the registry.
+pub trait Global: Sync {
+    /// Whether the value was created, or for [`InitOnce`](crate::once::InitOnce) globals, initialized.
+    fn is_initialized(&self) -> bool;
+
+    /// The address used to match initialization timings to the global.
+    #[doc(hidden)]
+    fn address(&self) -> usize;
+}
+
+struct Entry {
+    name: &'static str,
+    kind: &'static str,
+    type_name: &'static str,
+    size: usize,
+    global: &'static dyn Global,
+    init: Option<(Duration, Duration)>,
+}
+
+struct Registry {
+    start: Option<Instant>,
+    entries: Vec<Entry>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry { start: None, entries: Vec::new() });
+
+/// Information about a registered global.
+#[derive(Clone, Debug)]
+pub struct GlobalInfo {
+    /// The path of the global, including its module.
+    pub name: &'static str,
+    /// The kind of global, `Lazy`, `LazyInitOnce` or `CreateOnce`.
+    pub kind: &'static str,
+    pub type_name: &'static str,
+    /// The inline size of the value in bytes, not including heap allocations.
+    pub size: usize,
+    pub initialized: bool,
+    /// How long the initializer ran.
+    pub init_duration: Option<Duration>,
+    /// When the initializer finished, relative to the registration of the first global.
+    pub initialized_at: Option<Duration>,
+}
+
+#[doc(hidden)]
+pub fn register(name: &'static str, kind: &'static str, type_name: &'static str, size: usize, global: &'static dyn Global) {
+    let mut registry = REGISTRY.lock();
+    registry.start.get_or_insert_with(Instant::now);
+    registry.entries.push(Entry {
+        name,
+        kind,
+        type_name,
+        size,
+        global,
+        init: None,
+    });
+}
+
+#[doc(hidden)]
+pub fn record_init(address: usize, duration: Duration) {
+    let mut registry = REGISTRY.lock();
+    let Some(start) = registry.start else { return };
+    let at = start.elapsed();
+    if let Some(entry) = registry.entries.iter_mut().find(|e| e.global.address() == address) {
+        entry.init = Some((duration, at));
+    }
+}
+
+/// All registered globals in order of registration.
+pub fn globals() -> Vec<GlobalInfo> {
+    REGISTRY
+        .lock()
+        .entries
+        .iter()
+        .map(|e| GlobalInfo {
+            name: e.name,
+            kind: e.kind,
+            type_name: e.type_name,
+            size: e.size,
+            initialized: e.global.is_initialized(),
+            init_duration: e.init.map(|(d, _)| d),
+            initialized_at: e.init.map(|(_, at)| at),
+        })
+        .collect()
+}
+
+/// The registered globals which were never initialized.
+pub fn uninitialized() -> Vec<GlobalInfo> {
+    globals().into_iter().filter(|g| !g.initialized).collect()
+}
+
+/// A report listing all registered globals, sorted by size with uninitialized globals first.
+pub fn report() -> String {
+    let mut globals = globals();
+    globals.sort_by(|a, b| a.initialized.cmp(&b.initialized).then(b.size.cmp(&a.size)));
+    let never = globals.iter().filter(|g| !g.initialized).count();
+    let total = globals.iter().map(|g| g.size).sum::<usize>();
+
+    let mut out = format!("{} globals, {} never initialized, {} bytes inline\n", globals.len(), never, total);
+    for global in globals {
+        let _ = write!(out, "  {} ({}<{}>, {} bytes): ", global.name, global.kind, global.type_name, global.size);
+        match (global.initialized, global.init_duration, global.initialized_at) {
+            (false, _, _) => out.push_str("never initialized"),
+            (true, Some(duration), Some(at)) => {
+                let _ = write!(out, "initialized in {:?} at {:?}", duration, at);
+            }
+            (true, _, _) => out.push_str("initialized"),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub fn print_report() {
+    eprint!("{}", report());
+}
+
+/// Print the [`report`] when the application shuts down, see [`shutdown`](crate::shutdown).
+pub fn print_report_on_shutdown() {
+    crate::shutdown::on_shutdown(print_report);
+}
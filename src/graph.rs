@@ -0,0 +1,323 @@
+//! Directed graphs with topological sorting, cycle detection and strongly connected components.
+//!
+//! An edge from `a` to `b` means `a` comes before `b`, for example that asset `b` depends on `a`.
+//! Wherever there is a choice, nodes are visited in the order they were added, so all results are
+//! deterministic.
+//!
+//! ```
+//! use mvutils::graph::Graph;
+//!
+//! let mut assets = Graph::from_edges([("texture", "material"), ("shader", "material"), ("material", "mesh")]);
+//! assert_eq!(assets.topological_sort().unwrap(), [&"texture", &"shader", &"material", &"mesh"]);
+//!
+//! assets.add_edge("mesh", "texture");
+//! let cycle = assets.topological_sort().unwrap_err();
+//! assert_eq!(cycle.to_string(), "\"texture\" -> \"material\" -> \"mesh\" -> \"texture\"");
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+
+/// A cycle in a [`Graph`], listing its nodes in the order of the edges between them. The last node
+/// has an edge back to the first one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N: Debug> Display for Cycle<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for node in &self.0 {
+            write!(f, "{:?} -> ", node)?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{:?}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N: Debug> Error for Cycle<N> {}
+
+/// A directed graph. Adding the same node or edge twice has no effect.
+#[derive(Clone, Debug)]
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    indices: HashMap<N, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<N: Eq + Hash + Clone> Graph<N> {
+    pub fn new() -> Self {
+        Graph::default()
+    }
+
+    /// Create a graph from edges, adding their nodes in the order they appear.
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        let mut graph = Graph::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    /// Add a node without any edges, returning whether it was not in the graph yet.
+    pub fn add_node(&mut self, node: N) -> bool {
+        let len = self.nodes.len();
+        self.index(node) == len
+    }
+
+    /// Add an edge, adding its nodes if they are not in the graph yet.
+    pub fn add_edge(&mut self, from: N, to: N) {
+        let from = self.index(from);
+        let to = self.index(to);
+        if !self.edges[from].contains(&to) {
+            self.edges[from].push(to);
+        }
+    }
+
+    fn index(&mut self, node: N) -> usize {
+        if let Some(index) = self.indices.get(&node) {
+            return *index;
+        }
+        let index = self.nodes.len();
+        self.indices.insert(node.clone(), index);
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        index
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.indices.contains_key(node)
+    }
+
+    pub fn contains_edge(&self, from: &N, to: &N) -> bool {
+        match (self.indices.get(from), self.indices.get(to)) {
+            (Some(from), Some(to)) => self.edges[*from].contains(to),
+            _ => false,
+        }
+    }
+
+    /// The nodes the node has an edge to, in the order the edges were added.
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.indices
+            .get(node)
+            .map_or(&[][..], |i| &self.edges[*i])
+            .iter()
+            .map(|i| &self.nodes[*i])
+    }
+
+    /// The nodes with an edge to the node, in the order they were added.
+    pub fn predecessors<'a>(&'a self, node: &N) -> impl Iterator<Item = &'a N> {
+        let index = self.indices.get(node).copied();
+        self.nodes
+            .iter()
+            .zip(&self.edges)
+            .filter(move |(_, edges)| index.is_some_and(|i| edges.contains(&i)))
+            .map(|(node, _)| node)
+    }
+}
+
+impl<N> Graph<N> {
+    /// The nodes in the order they were added.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N)> {
+        self.edges
+            .iter()
+            .enumerate()
+            .flat_map(move |(from, edges)| edges.iter().map(move |to| (&self.nodes[from], &self.nodes[*to])))
+    }
+
+    /// Sort the nodes so every node comes after all nodes with an edge to it. When several nodes
+    /// could come next, the one added first is chosen.
+    pub fn topological_sort(&self) -> Result<Vec<&N>, Cycle<&N>> {
+        let mut incoming = vec![0usize; self.nodes.len()];
+        for to in self.edges.iter().flatten() {
+            incoming[*to] += 1;
+        }
+        let mut ready = (0..self.nodes.len())
+            .filter(|i| incoming[*i] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(&self.nodes[i]);
+            for to in &self.edges[i] {
+                incoming[*to] -= 1;
+                if incoming[*to] == 0 {
+                    ready.push(Reverse(*to));
+                }
+            }
+        }
+        if order.len() < self.nodes.len() {
+            return Err(self.find_cycle().expect("A graph which cannot be sorted has a cycle"));
+        }
+        Ok(order)
+    }
+
+    /// Find a cycle, starting the search at the nodes in the order they were added.
+    pub fn find_cycle(&self) -> Option<Cycle<&N>> {
+        const NEW: u8 = 0;
+        const ACTIVE: u8 = 1;
+        const DONE: u8 = 2;
+
+        let mut state = vec![NEW; self.nodes.len()];
+        let mut path = Vec::new();
+        for root in 0..self.nodes.len() {
+            if state[root] != NEW {
+                continue;
+            }
+            state[root] = ACTIVE;
+            path.push((root, 0));
+            while let Some((node, edge)) = path.last().copied() {
+                match self.edges[node].get(edge) {
+                    Some(next) => {
+                        path.last_mut().unwrap().1 += 1;
+                        match state[*next] {
+                            NEW => {
+                                state[*next] = ACTIVE;
+                                path.push((*next, 0));
+                            }
+                            ACTIVE => {
+                                let start = path.iter().position(|(n, _)| n == next).unwrap();
+                                return Some(Cycle(path[start..].iter().map(|(n, _)| &self.nodes[*n]).collect()));
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        state[node] = DONE;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.find_cycle().is_some()
+    }
+
+    /// The strongly connected components, which are the largest groups of nodes which can all
+    /// reach each other. Nodes without a cycle through them form a component of their own.
+    ///
+    /// The components are sorted topologically like [`Graph::topological_sort`] sorts nodes, and
+    /// the nodes of a component are in the order they were added.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<&N>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let n = self.nodes.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut next = 0;
+        let mut components = Vec::new();
+
+        for root in 0..n {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            let mut calls = vec![(root, 0)];
+            index[root] = next;
+            low[root] = next;
+            next += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((node, edge)) = calls.last().copied() {
+                if let Some(to) = self.edges[node].get(edge).copied() {
+                    calls.last_mut().unwrap().1 += 1;
+                    if index[to] == UNVISITED {
+                        index[to] = next;
+                        low[to] = next;
+                        next += 1;
+                        stack.push(to);
+                        on_stack[to] = true;
+                        calls.push((to, 0));
+                    } else if on_stack[to] {
+                        low[node] = low[node].min(index[to]);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some((parent, _)) = calls.last() {
+                    low[*parent] = low[*parent].min(low[node]);
+                }
+                if low[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+
+        // Sort the components like topological_sort, choosing the component with the node added
+        // first when several could come next.
+        components.sort_unstable_by_key(|c| c[0]);
+        let mut component_of = vec![0; n];
+        for (c, members) in components.iter().enumerate() {
+            for member in members {
+                component_of[*member] = c;
+            }
+        }
+        let mut incoming = vec![0usize; components.len()];
+        let mut edges = vec![Vec::new(); components.len()];
+        for (from, to) in self.edges.iter().enumerate().flat_map(|(from, e)| e.iter().map(move |to| (from, *to))) {
+            let (from, to) = (component_of[from], component_of[to]);
+            if from != to && !edges[from].contains(&to) {
+                edges[from].push(to);
+                incoming[to] += 1;
+            }
+        }
+        let mut ready = (0..components.len())
+            .filter(|c| incoming[*c] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(components.len());
+        while let Some(Reverse(c)) = ready.pop() {
+            order.push(components[c].iter().map(|i| &self.nodes[*i]).collect());
+            for to in &edges[c] {
+                incoming[*to] -= 1;
+                if incoming[*to] == 0 {
+                    ready.push(Reverse(*to));
+                }
+            }
+        }
+        order
+    }
+}
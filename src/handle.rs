@@ -0,0 +1,328 @@
+use crate::save::{nested, Loader, MAX_PREALLOCATION, Savable, SaveError, Saver};
+use hashbrown::HashMap;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// A copyable reference to a value in a [`HandleStore`]. A handle to a removed value stays invalid,
+/// even if its slot is reused, because the generation of the slot changes.
+///
+/// Handles are saved as a stable 64 bit id made of the index and generation.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: u32, generation: u32) -> Self {
+        Handle {
+            index,
+            generation,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// The stable id, which is also what gets saved.
+    pub fn id(&self) -> u64 {
+        (self.generation as u64) << 32 | self.index as u64
+    }
+
+    pub fn from_id(id: u64) -> Self {
+        Handle::new(id as u32, (id >> 32) as u32)
+    }
+
+    /// A handle which never refers to a value, as its index is never handed out.
+    pub fn dangling() -> Self {
+        Handle::new(u32::MAX, u32::MAX)
+    }
+
+    pub fn is_dangling(&self) -> bool {
+        self.index == u32::MAX
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.index, self.generation).cmp(&(other.index, other.generation))
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state)
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+impl<T> Savable for Handle<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        saver.push_u64(self.id());
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        u64::load(loader).map(Handle::from_id)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Stores values centrally and hands out [`Handle`]s to them, for mutable object graphs where
+/// values refer to each other.
+///
+/// Saving keeps the indices and generations, so handles saved alongside the store stay valid after
+/// loading it. To load values into a store that already contains values, use [`HandleStore::merge`]
+/// and relink the handles using the returned [`HandleMap`].
+///
+/// ```
+/// use mvutils::handle::HandleStore;
+///
+/// let mut store = HandleStore::new();
+/// let a = store.insert("a");
+/// store.remove(a);
+/// let b = store.insert("b");
+/// assert_eq!(store.get(a), None);
+/// assert_eq!(store.get(b), Some(&"b"));
+/// ```
+pub struct HandleStore<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> HandleStore<T> {
+    pub fn new() -> Self {
+        HandleStore {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.value = Some(value);
+                Handle::new(index, slot.generation)
+            }
+            None => {
+                let index = u32::try_from(self.slots.len()).ok().filter(|i| *i != u32::MAX).expect("HandleStore is full");
+                self.slots.push(Slot { generation: 0, value: Some(value) });
+                Handle::new(index, 0)
+            }
+        }
+    }
+
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize).filter(|s| s.generation == handle.generation)?;
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|s| s.generation == handle.generation)
+            .and_then(|s| s.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|s| s.generation == handle.generation)
+            .and_then(|s| s.value.as_mut())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.value.as_ref().map(|v| (Handle::new(i as u32, s.generation), v)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, s)| s.value.as_mut().map(|v| (Handle::new(i as u32, s.generation), v)))
+    }
+
+    pub fn handles(&self) -> impl Iterator<Item = Handle<T>> + '_ {
+        self.iter().map(|(h, _)| h)
+    }
+
+    /// Remove all values, invalidating all handles.
+    pub fn clear(&mut self) {
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(i as u32);
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Move all values of the other store into this one, returning which new handle every old
+    /// handle maps to.
+    pub fn merge(&mut self, other: HandleStore<T>) -> HandleMap<T> {
+        let mut map = HandleMap { map: HashMap::new() };
+        for (i, slot) in other.slots.into_iter().enumerate() {
+            if let Some(value) = slot.value {
+                let old = Handle::new(i as u32, slot.generation);
+                map.map.insert(old, self.insert(value));
+            }
+        }
+        map
+    }
+}
+
+impl<T: Relink<T>> HandleStore<T> {
+    /// Relink the handles inside the values moved by [`HandleStore::merge`], which returned the map.
+    pub fn relink_all(&mut self, map: &HandleMap<T>) {
+        for handle in map.map.values() {
+            if let Some(value) = self.get_mut(*handle) {
+                value.relink(map);
+            }
+        }
+    }
+}
+
+impl<T> Default for HandleStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Savable> Savable for HandleStore<T> {
+    /// Every slot is saved with its generation, including free ones, so handles to removed values
+    /// stay invalid after loading.
+    fn save(&self, saver: &mut impl Saver) {
+        saver.push_u32(self.slots.len() as u32);
+        for slot in &self.slots {
+            saver.push_u32(slot.generation);
+            slot.value.save(saver);
+        }
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let slots = u32::load(loader)?;
+        loader.check_len(slots as u64)?;
+        let mut store = HandleStore::new();
+        nested(loader, |loader| {
+            store.slots = Vec::with_capacity((slots as u64).min(MAX_PREALLOCATION) as usize);
+            for _ in 0..slots {
+                let generation = u32::load(loader)?;
+                let value = Option::<T>::load(loader)?;
+                store.slots.push(Slot { generation, value });
+            }
+            store.free = store.slots.iter().enumerate().rev().filter(|(_, s)| s.value.is_none()).map(|(i, _)| i as u32).collect();
+            store.len = store.slots.len() - store.free.len();
+            Ok(())
+        })?;
+        Ok(store)
+    }
+}
+
+/// Maps old handles to new ones, returned by [`HandleStore::merge`].
+pub struct HandleMap<T> {
+    map: HashMap<Handle<T>, Handle<T>>,
+}
+
+impl<T> HandleMap<T> {
+    pub fn get(&self, handle: Handle<T>) -> Option<Handle<T>> {
+        self.map.get(&handle).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// Values containing handles, which need to be updated after the store they point into was merged.
+pub trait Relink<T> {
+    fn relink(&mut self, map: &HandleMap<T>);
+}
+
+impl<T> Relink<T> for Handle<T> {
+    /// Handles that are not in the map pointed to no value in the merged store, so they become
+    /// [`Handle::dangling`] instead of pointing to an unrelated value.
+    fn relink(&mut self, map: &HandleMap<T>) {
+        *self = map.get(*self).unwrap_or_else(Handle::dangling);
+    }
+}
+
+impl<T, R: Relink<T>> Relink<T> for Option<R> {
+    fn relink(&mut self, map: &HandleMap<T>) {
+        if let Some(r) = self {
+            r.relink(map);
+        }
+    }
+}
+
+impl<T, R: Relink<T>> Relink<T> for Vec<R> {
+    fn relink(&mut self, map: &HandleMap<T>) {
+        for r in self {
+            r.relink(map);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! A small protocol to check that both ends of a connection speak the same protocol version and
+//! agree on optional features before exchanging any messages.
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::handshake::{Compatibility, Handshake};
+//! use mvutils::version::Version;
+//!
+//! const COMPRESSION: u64 = 1 << 0;
+//! const ENCRYPTION: u64 = 1 << 1;
+//!
+//! let server = Handshake::new(*b"MVGM", Version::new(0, 1, 4, 0))
+//!     .features(COMPRESSION | ENCRYPTION)
+//!     .policy(Compatibility::SameMajor);
+//! let client = Handshake::new(*b"MVGM", Version::new(0, 1, 2, 7)).features(COMPRESSION);
+//!
+//! let mut buffer = ByteBuffer::new();
+//! client.write(&mut buffer);
+//!
+//! let report = server.read(&mut buffer).unwrap();
+//! assert_eq!(report.peer_version, Version::new(0, 1, 2, 7));
+//! assert_eq!(report.common_features, COMPRESSION);
+//! assert!(!report.has(ENCRYPTION));
+//! ```
+
+use crate::save::{Loader, Savable, SaveError, Saver};
+use crate::version::Version;
+use bytebuffer::ByteBuffer;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Read, Write};
+
+/// The size of a handshake in bytes: the magic, the version and the feature flags.
+pub const HANDSHAKE_SIZE: usize = 4 + 8 + 8;
+
+/// Which protocol versions of a peer are accepted.
+#[derive(Copy, Clone, Default)]
+pub enum Compatibility {
+    /// The peer must use the exact same version.
+    Exact,
+    /// The peer must use the same variant and major version.
+    #[default]
+    SameMajor,
+    /// The peer must use the same variant, major and minor version.
+    SameMinor,
+    /// The peer must use at least this version.
+    AtLeast(Version),
+    /// Decide with a function, called with our version and the peer's version.
+    Custom(fn(Version, Version) -> bool),
+}
+
+impl Compatibility {
+    pub fn accepts(&self, ours: Version, peer: Version) -> bool {
+        match self {
+            Compatibility::Exact => ours == peer,
+            Compatibility::SameMajor => ours.variant() == peer.variant() && ours.major() == peer.major(),
+            Compatibility::SameMinor => {
+                ours.variant() == peer.variant() && ours.major() == peer.major() && ours.minor() == peer.minor()
+            }
+            Compatibility::AtLeast(min) => peer >= *min,
+            Compatibility::Custom(f) => f(ours, peer),
+        }
+    }
+}
+
+impl Debug for Compatibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compatibility::Exact => f.write_str("Exact"),
+            Compatibility::SameMajor => f.write_str("SameMajor"),
+            Compatibility::SameMinor => f.write_str("SameMinor"),
+            Compatibility::AtLeast(version) => f.debug_tuple("AtLeast").field(version).finish(),
+            Compatibility::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// The result of a successful handshake.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HandshakeReport {
+    pub peer_version: Version,
+    /// All feature flags the peer sent, including ones unknown to us.
+    pub peer_features: u64,
+    /// The features supported by both sides, which can be used on this connection.
+    pub common_features: u64,
+}
+
+impl HandshakeReport {
+    /// Whether all of the given features are supported by both sides.
+    pub fn has(&self, features: u64) -> bool {
+        self.common_features & features == features
+    }
+}
+
+#[derive(Debug)]
+pub enum HandshakeError {
+    Io(io::Error),
+    Load(SaveError),
+    /// The peer does not speak this protocol at all.
+    WrongMagic { expected: [u8; 4], found: [u8; 4] },
+    /// The peer's version is rejected by the [`Compatibility`] policy.
+    IncompatibleVersion { ours: Version, peer: Version },
+    /// The peer does not support all required features.
+    MissingFeatures { missing: u64, report: HandshakeReport },
+}
+
+impl Display for HandshakeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandshakeError::Io(e) => write!(f, "Handshake failed: {}", e),
+            HandshakeError::Load(e) => write!(f, "Invalid handshake: {}", e),
+            HandshakeError::WrongMagic { expected, found } => write!(
+                f,
+                "Wrong protocol, expected magic {:?} but got {:?}",
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(found)
+            ),
+            HandshakeError::IncompatibleVersion { ours, peer } => {
+                write!(f, "Incompatible protocol version {}, expected one compatible with {}", peer, ours)
+            }
+            HandshakeError::MissingFeatures { missing, .. } => {
+                write!(f, "Peer is missing required features {:#x}", missing)
+            }
+        }
+    }
+}
+
+impl Error for HandshakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HandshakeError::Io(e) => Some(e),
+            HandshakeError::Load(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HandshakeError {
+    fn from(value: io::Error) -> Self {
+        HandshakeError::Io(value)
+    }
+}
+
+impl From<SaveError> for HandshakeError {
+    fn from(value: SaveError) -> Self {
+        HandshakeError::Load(value)
+    }
+}
+
+/// Our side of a handshake: a magic identifying the protocol, the protocol [`Version`] and a bitset
+/// of supported features.
+///
+/// Both sides [`write`](Handshake::write) their handshake and [`read`](Handshake::read) the peer's,
+/// which is validated against the magic, the [`Compatibility`] policy and the required features.
+/// The handshake always has a size of [`HANDSHAKE_SIZE`] bytes, so it can be read before any framing
+/// is set up.
+#[derive(Copy, Clone, Debug)]
+pub struct Handshake {
+    magic: [u8; 4],
+    version: Version,
+    features: u64,
+    required: u64,
+    policy: Compatibility,
+}
+
+impl Handshake {
+    pub fn new(magic: [u8; 4], version: Version) -> Self {
+        Handshake {
+            magic,
+            version,
+            features: 0,
+            required: 0,
+            policy: Compatibility::default(),
+        }
+    }
+
+    /// Set the features supported by this side, in addition to the required features.
+    pub fn features(mut self, features: u64) -> Self {
+        self.features = features | self.required;
+        self
+    }
+
+    /// Set the features the peer must support. They are also added to our supported features.
+    pub fn require(mut self, features: u64) -> Self {
+        self.required = features;
+        self.features |= features;
+        self
+    }
+
+    /// Set which peer versions are accepted, [`Compatibility::SameMajor`] by default.
+    pub fn policy(mut self, policy: Compatibility) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn supported_features(&self) -> u64 {
+        self.features
+    }
+
+    pub fn required_features(&self) -> u64 {
+        self.required
+    }
+
+    pub fn write(&self, saver: &mut impl Saver) {
+        saver.push_bytes(&self.magic);
+        self.version.save(saver);
+        saver.push_u64(self.features);
+    }
+
+    /// Read the peer's handshake and check whether it is compatible.
+    pub fn read(&self, loader: &mut impl Loader) -> Result<HandshakeReport, HandshakeError> {
+        let magic = loader.pop_bytes(4).ok_or(SaveError::eof("magic"))?;
+        let found = [magic[0], magic[1], magic[2], magic[3]];
+        if found != self.magic {
+            return Err(HandshakeError::WrongMagic { expected: self.magic, found });
+        }
+        let peer_version = Version::load(loader).map_err(|e| e.in_field("version"))?;
+        let peer_features = u64::load(loader).map_err(|e| e.in_field("features"))?;
+        self.check(peer_version, peer_features)
+    }
+
+    /// Check a peer's version and features without reading them.
+    pub fn check(&self, peer_version: Version, peer_features: u64) -> Result<HandshakeReport, HandshakeError> {
+        if !self.policy.accepts(self.version, peer_version) {
+            return Err(HandshakeError::IncompatibleVersion {
+                ours: self.version,
+                peer: peer_version,
+            });
+        }
+        let report = HandshakeReport {
+            peer_version,
+            peer_features,
+            common_features: self.features & peer_features,
+        };
+        let missing = self.required & !peer_features;
+        if missing != 0 {
+            return Err(HandshakeError::MissingFeatures { missing, report });
+        }
+        Ok(report)
+    }
+
+    /// Write our handshake to the stream and read the peer's, for example right after connecting.
+    pub fn exchange(&self, stream: &mut (impl Read + Write)) -> Result<HandshakeReport, HandshakeError> {
+        let mut buffer = ByteBuffer::new();
+        self.write(&mut buffer);
+        stream.write_all(buffer.as_bytes())?;
+        stream.flush()?;
+
+        let mut peer = [0; HANDSHAKE_SIZE];
+        stream.read_exact(&mut peer)?;
+        self.read(&mut ByteBuffer::from_bytes(&peer))
+    }
+}
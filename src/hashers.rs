@@ -150,4 +150,250 @@ impl BuildHasher for UsizeIdentityHasher {
     fn build_hasher(&self) -> Self::Hasher {
         Self::Hasher::default()
     }
-}
\ No newline at end of file
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 (IEEE) checksum of the bytes.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut hasher = Crc32Hasher::default();
+    hasher.write(bytes);
+    hasher.checksum()
+}
+
+/// Streaming CRC-32 (IEEE) checksum, see [`crc32`].
+pub struct Crc32Hasher {
+    crc: u32,
+}
+
+impl Crc32Hasher {
+    pub fn checksum(&self) -> u32 {
+        !self.crc
+    }
+}
+
+impl Default for Crc32Hasher {
+    fn default() -> Self {
+        Crc32Hasher { crc: !0 }
+    }
+}
+
+impl Hasher for Crc32Hasher {
+    fn finish(&self) -> u64 {
+        self.checksum() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.crc = bytes.iter().fold(self.crc, |crc, b| CRC32_TABLE[((crc ^ *b as u32) & 0xFF) as usize] ^ (crc >> 8));
+    }
+}
+
+impl BuildHasher for Crc32Hasher {
+    type Hasher = Self;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        Self::default()
+    }
+}
+
+const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+const FNV_PRIME: u64 = 0x100000001B3;
+
+/// Computes the 64 bit FNV-1a hash of the bytes.
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1aHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Streaming 64 bit FNV-1a hasher, see [`fnv1a_64`]. Very fast for short keys.
+#[repr(transparent)]
+pub struct Fnv1aHasher {
+    hash: u64,
+}
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher { hash: FNV_OFFSET }
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.hash = (self.hash ^ *b as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+impl BuildHasher for Fnv1aHasher {
+    type Hasher = Self;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        Self::default()
+    }
+}
+
+const XXH_PRIME_1: u64 = 0x9E3779B185EBCA87;
+const XXH_PRIME_2: u64 = 0xC2B2AE3D27D4EB4F;
+const XXH_PRIME_3: u64 = 0x165667B19E3779F9;
+const XXH_PRIME_4: u64 = 0x85EBCA77C2B2AE63;
+const XXH_PRIME_5: u64 = 0x27D4EB2F165667C5;
+
+#[inline(always)]
+fn xxh_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(XXH_PRIME_2)).rotate_left(31).wrapping_mul(XXH_PRIME_1)
+}
+
+#[inline(always)]
+fn xxh_merge(acc: u64, value: u64) -> u64 {
+    (acc ^ xxh_round(0, value)).wrapping_mul(XXH_PRIME_1).wrapping_add(XXH_PRIME_4)
+}
+
+#[inline(always)]
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+/// Computes the 64 bit xxHash (XXH64) of the bytes with seed 0.
+pub fn xxhash64(bytes: &[u8]) -> u64 {
+    xxhash64_with_seed(bytes, 0)
+}
+
+pub fn xxhash64_with_seed(bytes: &[u8], seed: u64) -> u64 {
+    let mut hasher = XxHash64::with_seed(seed);
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Streaming 64 bit xxHash (XXH64), see [`xxhash64`]. Fast for large inputs like file contents.
+#[derive(Clone)]
+pub struct XxHash64 {
+    seed: u64,
+    acc: [u64; 4],
+    buffer: [u8; 32],
+    buffered: usize,
+    len: u64,
+}
+
+impl XxHash64 {
+    pub fn with_seed(seed: u64) -> Self {
+        XxHash64 {
+            seed,
+            acc: [
+                seed.wrapping_add(XXH_PRIME_1).wrapping_add(XXH_PRIME_2),
+                seed.wrapping_add(XXH_PRIME_2),
+                seed,
+                seed.wrapping_sub(XXH_PRIME_1),
+            ],
+            buffer: [0; 32],
+            buffered: 0,
+            len: 0,
+        }
+    }
+
+    fn stripe(acc: &mut [u64; 4], stripe: &[u8]) {
+        for (i, acc) in acc.iter_mut().enumerate() {
+            *acc = xxh_round(*acc, read_u64(&stripe[i * 8..]));
+        }
+    }
+}
+
+impl Default for XxHash64 {
+    fn default() -> Self {
+        XxHash64::with_seed(0)
+    }
+}
+
+impl Hasher for XxHash64 {
+    fn finish(&self) -> u64 {
+        let mut hash = if self.len >= 32 {
+            let [v1, v2, v3, v4] = self.acc;
+            let mut hash = v1.rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            for v in self.acc {
+                hash = xxh_merge(hash, v);
+            }
+            hash
+        } else {
+            self.seed.wrapping_add(XXH_PRIME_5)
+        };
+        hash = hash.wrapping_add(self.len);
+
+        let mut rest = &self.buffer[..self.buffered];
+        while rest.len() >= 8 {
+            hash ^= xxh_round(0, read_u64(rest));
+            hash = hash.rotate_left(27).wrapping_mul(XXH_PRIME_1).wrapping_add(XXH_PRIME_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            hash ^= (u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64).wrapping_mul(XXH_PRIME_1);
+            hash = hash.rotate_left(23).wrapping_mul(XXH_PRIME_2).wrapping_add(XXH_PRIME_3);
+            rest = &rest[4..];
+        }
+        for b in rest {
+            hash ^= (*b as u64).wrapping_mul(XXH_PRIME_5);
+            hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME_1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(XXH_PRIME_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(XXH_PRIME_3);
+        hash ^ (hash >> 32)
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+
+        if self.buffered > 0 {
+            let take = bytes.len().min(32 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&bytes[..take]);
+            self.buffered += take;
+            bytes = &bytes[take..];
+            if self.buffered < 32 {
+                return;
+            }
+            Self::stripe(&mut self.acc, &self.buffer);
+            self.buffered = 0;
+        }
+
+        let mut stripes = bytes.chunks_exact(32);
+        for stripe in &mut stripes {
+            Self::stripe(&mut self.acc, stripe);
+        }
+        let rest = stripes.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+}
+
+impl BuildHasher for XxHash64 {
+    type Hasher = Self;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        Self::with_seed(self.seed)
+    }
+}
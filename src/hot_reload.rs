@@ -0,0 +1,201 @@
+//! Values loaded from files that are reloaded when the file changes.
+//!
+//! ```no_run
+//! use mvutils::hot_reload::HotReload;
+//! use mvutils::{update, when, Savable};
+//! use std::time::Duration;
+//!
+//! #[derive(Savable, Clone)]
+//! struct Settings {
+//!     volume: f32,
+//! }
+//!
+//! let settings = HotReload::<Settings>::new("settings.bin")
+//!     .unwrap()
+//!     .validate(|s| if s.volume <= 1.0 { Ok(()) } else { Err("volume too high".to_string()) });
+//! let _watcher = settings.watch(Duration::from_millis(500));
+//!
+//! let state = settings.state();
+//! loop {
+//!     when!([state] => {
+//!         println!("Volume is now {}", state.read().volume);
+//!         update!([state]);
+//!     });
+//!     # break;
+//! }
+//! ```
+
+use crate::save::fs::load_from_file;
+use crate::save::Savable;
+use crate::state::State;
+use crate::thread::CancellationToken;
+use crate::sync::{Mutex, RwLockReadGuard};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
+
+type LoadFn<T> = Box<dyn Fn(&Path) -> Result<T, String> + Send + Sync>;
+type ValidateFn<T> = Box<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Stamp {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+struct Inner<T> {
+    path: PathBuf,
+    state: State<T>,
+    loader: LoadFn<T>,
+    validator: Option<ValidateFn<T>>,
+    stamp: Mutex<Option<Stamp>>,
+    last_error: Mutex<Option<String>>,
+}
+
+/// A value loaded from a file, which is reloaded when the file changes.
+///
+/// The value lives in a [`State`], so every successful reload bumps its version and
+/// [`when!`](crate::when) / [`update!`](crate::update) consumers of [`HotReload::state`] pick it up.
+/// Changes are detected by polling the modification time and size of the file, either manually
+/// with [`HotReload::check`] or on a background thread with [`HotReload::watch`]. If a reload fails
+/// to load or validate, the previous value is kept and the error is stored in [`HotReload::last_error`].
+pub struct HotReload<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T: Savable> HotReload<T> {
+    /// Load a value from a file written by [`save_to_file`](crate::save::fs::save_to_file).
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::with_loader(path, |path| load_from_file(path))
+    }
+}
+
+impl<T> HotReload<T> {
+    /// Load a value from a file using a custom loader, for example one parsing a [`Config`](crate::config::Config).
+    pub fn with_loader(path: impl AsRef<Path>, loader: impl Fn(&Path) -> Result<T, String> + Send + Sync + 'static) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let stamp = Stamp::of(&path);
+        let value = loader(&path)?;
+        Ok(HotReload {
+            inner: Arc::new(Inner {
+                path,
+                state: State::new(value),
+                loader: Box::new(loader),
+                validator: None,
+                stamp: Mutex::new(stamp),
+                last_error: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// Set a validator which reloaded values have to pass before they replace the current one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after the value was cloned or is being watched.
+    pub fn validate(mut self, validator: impl Fn(&T) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        let inner = Arc::get_mut(&mut self.inner).expect("The validator must be set before the HotReload is shared!");
+        inner.validator = Some(Box::new(validator));
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// A handle to the state holding the value, to use with [`when!`](crate::when) and [`update!`](crate::update).
+    pub fn state(&self) -> State<T> {
+        self.inner.state.clone()
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.inner.state.read()
+    }
+
+    pub fn get_version(&self) -> u64 {
+        self.inner.state.get_version()
+    }
+
+    /// The error of the last failed reload, cleared by the next successful one.
+    pub fn last_error(&self) -> Option<String> {
+        self.inner.last_error.lock().clone()
+    }
+
+    /// Reload the value if the file changed since the last check, returning whether it was replaced.
+    pub fn check(&self) -> Result<bool, String> {
+        self.inner.check()
+    }
+
+    /// Reload the value regardless of whether the file changed.
+    pub fn reload(&self) -> Result<(), String> {
+        self.inner.reload(Stamp::of(&self.inner.path))
+    }
+}
+
+impl<T: 'static> HotReload<T> {
+    /// Check the file for changes every `interval` on a background thread. The thread stops when the
+    /// returned token is cancelled or all handles to this value are dropped.
+    pub fn watch(&self, interval: Duration) -> CancellationToken {
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        let inner: Weak<Inner<T>> = Arc::downgrade(&self.inner);
+        std::thread::Builder::new()
+            .name("hot-reload".to_string())
+            .spawn(move || {
+                while !cancel.wait_timeout(interval) {
+                    let Some(inner) = inner.upgrade() else { break };
+                    let _ = inner.check();
+                }
+            })
+            .expect("Failed to spawn hot reload thread!");
+        token
+    }
+}
+
+impl<T> Inner<T> {
+    fn check(&self) -> Result<bool, String> {
+        let stamp = Stamp::of(&self.path);
+        if stamp.is_none() || stamp == *self.stamp.lock() {
+            return Ok(false);
+        }
+        self.reload(stamp).map(|_| true)
+    }
+
+    fn reload(&self, stamp: Option<Stamp>) -> Result<(), String> {
+        *self.stamp.lock() = stamp;
+        let result = (self.loader)(&self.path).and_then(|value| {
+            if let Some(validator) = &self.validator {
+                validator(&value)?;
+            }
+            Ok(value)
+        });
+        match result {
+            Ok(value) => {
+                *self.state.write() = value;
+                *self.last_error.lock() = None;
+                Ok(())
+            }
+            Err(e) => {
+                *self.last_error.lock() = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<T> Clone for HotReload<T> {
+    fn clone(&self) -> Self {
+        HotReload { inner: self.inner.clone() }
+    }
+}
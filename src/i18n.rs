@@ -0,0 +1,255 @@
+use crate as mvutils;
+use crate::config::Config;
+use crate::lazy;
+use crate::save::fs::load_from_file;
+use crate::template::{MissingKey, Template};
+use crate::utils::Plural;
+use hashbrown::HashMap;
+use mvutils_proc_macro::Savable;
+use crate::sync::RwLock;
+use std::fmt::Display;
+use std::path::Path;
+
+lazy! {
+    static GLOBAL_I18N: I18n = I18n::new("en");
+}
+
+#[derive(Savable, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PluralForm {
+    Zero,
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralForm {
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "zero" => Some(PluralForm::Zero),
+            "one" => Some(PluralForm::One),
+            "few" => Some(PluralForm::Few),
+            "many" => Some(PluralForm::Many),
+            "other" => Some(PluralForm::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Selects the plural form of a message for a count.
+#[derive(Savable, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum PluralRule {
+    /// `one` for 1, `other` otherwise, like English or German.
+    #[default]
+    OneOther,
+    /// `zero` for 0, `one` for 1, `other` otherwise.
+    ZeroOneOther,
+    /// `one` for 0 and 1, `other` otherwise, like French.
+    OneUpToOne,
+    /// `one`, `few` and `many` forms, like Russian or Ukrainian.
+    Slavic,
+    /// Always `other`, like Japanese or Chinese.
+    Other,
+}
+
+impl PluralRule {
+    pub fn form(self, count: u64) -> PluralForm {
+        match self {
+            PluralRule::OneOther if count == 1 => PluralForm::One,
+            PluralRule::ZeroOneOther if count == 0 => PluralForm::Zero,
+            PluralRule::ZeroOneOther if count == 1 => PluralForm::One,
+            PluralRule::OneUpToOne if count <= 1 => PluralForm::One,
+            PluralRule::Slavic => match (count % 10, count % 100) {
+                (1, r) if r != 11 => PluralForm::One,
+                (2..=4, r) if !(12..=14).contains(&r) => PluralForm::Few,
+                _ => PluralForm::Many,
+            },
+            _ => PluralForm::Other,
+        }
+    }
+}
+
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub enum Message {
+    Text(String),
+    Plural(Vec<(PluralForm, String)>),
+}
+
+/// The messages of a single locale.
+///
+/// In the plain-text format, each line is a `key = message` pair as parsed by [`Config`]. Plural
+/// forms are given as separate keys with a `.zero`, `.one`, `.few`, `.many` or `.other` suffix.
+/// Messages may contain `${name}` placeholders, with `${count}` being set for plural messages.
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub struct Catalog {
+    pub locale: String,
+    pub rule: PluralRule,
+    pub messages: std::collections::HashMap<String, Message>,
+}
+
+impl Catalog {
+    pub fn new(locale: &str, rule: PluralRule) -> Self {
+        Catalog {
+            locale: locale.to_string(),
+            rule,
+            messages: std::collections::HashMap::new(),
+        }
+    }
+
+    pub fn parse(locale: &str, rule: PluralRule, input: &str) -> Result<Self, String> {
+        let config = Config::parse(input)?;
+        let mut catalog = Catalog::new(locale, rule);
+        for key in config.keys("") {
+            let value = config.get_str("", key).unwrap_or_default().to_string();
+            match key.rsplit_once('.').and_then(|(k, s)| Some((k, PluralForm::from_suffix(s)?))) {
+                Some((key, form)) => catalog.insert_plural(key, form, value),
+                None => catalog.insert(key, value),
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Load a catalog from a plain-text file.
+    pub fn load_text(locale: &str, rule: PluralRule, path: impl AsRef<Path>) -> Result<Self, String> {
+        let input = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Catalog::parse(locale, rule, &input)
+    }
+
+    /// Load a catalog from a file written using [`save_to_file`](crate::save::fs::save_to_file).
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        load_from_file(path)
+    }
+
+    pub fn insert(&mut self, key: &str, message: impl ToString) {
+        self.messages.insert(key.to_string(), Message::Text(message.to_string()));
+    }
+
+    pub fn insert_plural(&mut self, key: &str, form: PluralForm, message: impl ToString) {
+        let entry = self.messages.entry(key.to_string()).or_insert_with(|| Message::Plural(Vec::new()));
+        if let Message::Text(text) = entry {
+            *entry = Message::Plural(vec![(PluralForm::Other, std::mem::take(text))]);
+        }
+        if let Message::Plural(forms) = entry {
+            forms.retain(|(f, _)| *f != form);
+            forms.push((form, message.to_string()));
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Message> {
+        self.messages.get(key)
+    }
+
+    /// Select the message text for a count. Falls back to the `other` form, and for messages
+    /// without plural forms to [`Plural::plural`].
+    pub fn select(&self, key: &str, count: u64) -> Option<String> {
+        match self.messages.get(key)? {
+            Message::Text(text) => Some(text.plural(count.min(u32::MAX as u64) as u32)),
+            Message::Plural(forms) => {
+                let form = self.rule.form(count);
+                forms
+                    .iter()
+                    .find(|(f, _)| *f == form)
+                    .or_else(|| forms.iter().find(|(f, _)| *f == PluralForm::Other))
+                    .map(|(_, text)| text.clone())
+            }
+        }
+    }
+}
+
+fn format(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    match Template::parse(message) {
+        Ok(template) => template
+            .missing_keys(MissingKey::Keep)
+            .render_with(|key| args.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string()))
+            .unwrap_or_else(|_| message.to_string()),
+        Err(_) => message.to_string(),
+    }
+}
+
+/// A set of catalogs with a current and a fallback locale. Use [`I18n::global`] for a shared
+/// instance, or create and pass around your own.
+pub struct I18n {
+    catalogs: RwLock<HashMap<String, Catalog>>,
+    locale: RwLock<String>,
+    fallback: String,
+}
+
+impl I18n {
+    pub fn new(fallback: &str) -> Self {
+        I18n {
+            catalogs: RwLock::new(HashMap::new()),
+            locale: RwLock::new(fallback.to_string()),
+            fallback: fallback.to_string(),
+        }
+    }
+
+    pub fn global() -> &'static I18n {
+        &GLOBAL_I18N
+    }
+
+    /// Add a catalog, replacing any existing one for the same locale.
+    pub fn add(&self, catalog: Catalog) {
+        self.catalogs.write().insert(catalog.locale.clone(), catalog);
+    }
+
+    pub fn locales(&self) -> Vec<String> {
+        self.catalogs.read().keys().cloned().collect()
+    }
+
+    pub fn locale(&self) -> String {
+        self.locale.read().clone()
+    }
+
+    pub fn set_locale(&self, locale: &str) {
+        *self.locale.write() = locale.to_string();
+    }
+
+    fn lookup(&self, key: &str, count: u64) -> Option<String> {
+        let catalogs = self.catalogs.read();
+        let locale = self.locale.read();
+        [locale.as_str(), self.fallback.as_str()]
+            .iter()
+            .find_map(|l| catalogs.get(*l)?.select(key, count))
+    }
+
+    /// Translate a message, returning the key itself if no catalog contains it.
+    pub fn tr(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        match self.lookup(key, 1) {
+            Some(message) => format(&message, args),
+            None => key.to_string(),
+        }
+    }
+
+    /// Translate a message with plural forms, setting the `${count}` placeholder.
+    pub fn tr_plural(&self, key: &str, count: u64, args: &[(&str, &dyn Display)]) -> String {
+        match self.lookup(key, count) {
+            Some(message) => {
+                let mut all = vec![("count", &count as &dyn Display)];
+                all.extend_from_slice(args);
+                format(&message, &all)
+            }
+            None => key.to_string(),
+        }
+    }
+}
+
+/// Translate a message using the global [`I18n`] instance.
+///
+/// ```
+/// use mvutils::tr;
+///
+/// let greeting = tr!("greeting", name = "Player");
+/// let apples = tr!("apples", count = 3);
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::I18n::global().tr($key, &[])
+    };
+    ($key:expr, count = $count:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::I18n::global().tr_plural($key, $count as u64, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+    ($key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::I18n::global().tr($key, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
@@ -0,0 +1,182 @@
+//! Allocating integer ids which are reused after being freed.
+//!
+//! ```
+//! use mvutils::ids::IdAllocator;
+//!
+//! let mut ids = IdAllocator::new();
+//! let assets = ids.reserve_next(100);
+//! assert_eq!(assets, 0..100);
+//!
+//! let a = ids.allocate();
+//! let b = ids.allocate();
+//! assert_eq!((a, b), (100, 101));
+//!
+//! ids.free(a);
+//! assert_eq!(ids.allocate(), a);
+//! assert_eq!(ids.live_count(), 2);
+//! ```
+
+use crate::save::{Loader, Savable, SaveError, Saver};
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Allocates `u64` ids, reusing freed ids lowest first before handing out new ones.
+///
+/// Ranges of ids can be reserved, for example for static assets with fixed ids, and are never
+/// allocated. The allocator is [`Savable`], so ids stay stable across sessions when it is saved
+/// along with the objects using them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdAllocator {
+    first: u64,
+    next: u64,
+    free: BTreeSet<u64>,
+    reserved: Vec<Range<u64>>,
+    live: u64,
+}
+
+impl IdAllocator {
+    pub fn new() -> Self {
+        IdAllocator::starting_at(0)
+    }
+
+    /// Create an allocator whose first id is `first`, for example to keep `0` as an invalid id.
+    pub fn starting_at(first: u64) -> Self {
+        IdAllocator {
+            first,
+            next: first,
+            free: BTreeSet::new(),
+            reserved: Vec::new(),
+            live: 0,
+        }
+    }
+
+    /// Allocate an id, reusing the lowest freed id if there is one.
+    ///
+    /// # Panics
+    /// If all ids are used.
+    pub fn allocate(&mut self) -> u64 {
+        self.live += 1;
+        if let Some(id) = self.free.pop_first() {
+            return id;
+        }
+        while let Some(range) = self.reserved.iter().find(|r| r.contains(&self.next)) {
+            self.next = range.end;
+        }
+        let id = self.next;
+        self.next = id.checked_add(1).expect("IdAllocator ran out of ids!");
+        id
+    }
+
+    /// Free an id so it can be reused. Returns false if the id was not allocated.
+    pub fn free(&mut self, id: u64) -> bool {
+        if !self.is_live(id) {
+            return false;
+        }
+        self.live -= 1;
+        if id + 1 == self.next {
+            self.next = id;
+            while self.next > 0 && self.free.remove(&(self.next - 1)) {
+                self.next -= 1;
+            }
+        } else {
+            self.free.insert(id);
+        }
+        true
+    }
+
+    pub fn is_live(&self, id: u64) -> bool {
+        id >= self.first && id < self.next && !self.free.contains(&id) && !self.is_reserved(id)
+    }
+
+    pub fn is_reserved(&self, id: u64) -> bool {
+        self.reserved.iter().any(|r| r.contains(&id))
+    }
+
+    /// Reserve a range of ids, which will never be allocated. Fails if any id in the range is
+    /// allocated or already reserved.
+    pub fn reserve(&mut self, range: Range<u64>) -> Result<(), String> {
+        if range.is_empty() {
+            return Ok(());
+        }
+        if let Some(other) = self.reserved.iter().find(|r| r.start < range.end && range.start < r.end) {
+            return Err(format!("Id range {:?} overlaps the reserved range {:?}", range, other));
+        }
+        if let Some(id) = range.clone().take_while(|id| *id < self.next).find(|id| self.is_live(*id)) {
+            return Err(format!("Id {} in range {:?} is already allocated", id, range));
+        }
+        let free = self.free.range(range.clone()).copied().collect::<Vec<_>>();
+        for id in free {
+            self.free.remove(&id);
+        }
+        self.reserved.push(range);
+        Ok(())
+    }
+
+    /// Reserve the first `count` ids after the allocated ids which are not reserved yet,
+    /// returning the reserved range.
+    pub fn reserve_next(&mut self, count: u64) -> Range<u64> {
+        let mut start = self.next;
+        while let Some(end) = self.reserved.iter().filter(|r| r.start < start + count && start < r.end).map(|r| r.end).max() {
+            start = end;
+        }
+        let range = start..start + count;
+        if !range.is_empty() {
+            self.reserved.push(range.clone());
+        }
+        range
+    }
+
+    /// The number of allocated ids.
+    pub fn live_count(&self) -> u64 {
+        self.live
+    }
+
+    /// The reserved ranges in order of reservation.
+    pub fn reserved(&self) -> &[Range<u64>] {
+        &self.reserved
+    }
+
+    /// Free all ids, keeping the reserved ranges.
+    pub fn clear(&mut self) {
+        self.next = self.first;
+        self.free.clear();
+        self.live = 0;
+    }
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        IdAllocator::new()
+    }
+}
+
+impl Savable for IdAllocator {
+    fn save(&self, saver: &mut impl Saver) {
+        self.first.save(saver);
+        self.next.save(saver);
+        self.free.iter().copied().collect::<Vec<_>>().save(saver);
+        self.reserved.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let first = u64::load(loader).map_err(|e| e.in_field("first"))?;
+        let next = u64::load(loader).map_err(|e| e.in_field("next"))?;
+        let free = Vec::<u64>::load(loader).map_err(|e| e.in_field("free"))?;
+        let reserved = Vec::<Range<u64>>::load(loader).map_err(|e| e.in_field("reserved"))?;
+        if next < first || free.iter().any(|id| *id < first || *id >= next) {
+            return Err(SaveError::custom("Invalid IdAllocator, freed ids outside of the allocated ids"));
+        }
+        let free = free.into_iter().collect::<BTreeSet<_>>();
+        let reserved_ids = reserved.iter().map(|r| r.end.min(next).saturating_sub(r.start.max(first))).sum::<u64>();
+        let live = (next - first)
+            .checked_sub(free.len() as u64 + reserved_ids)
+            .ok_or_else(|| SaveError::custom("Invalid IdAllocator, more freed and reserved ids than allocated ids"))?;
+        Ok(IdAllocator {
+            first,
+            next,
+            free,
+            reserved,
+            live,
+        })
+    }
+}
@@ -0,0 +1,258 @@
+use crate::save::{Loader, Savable, SaveError, SaveSize, Saver};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// A string of at most `N` bytes stored inline without heap allocation. `N` may be at most 255, as
+/// the length is saved as a single byte.
+///
+/// Appending more than fits truncates at the last char boundary that fits, like
+/// [`string8_save`](crate::save::custom::string8_save) does for strings longer than 255 bytes.
+///
+/// ```
+/// use mvutils::inline_string::InlineString;
+///
+/// let mut name = InlineString::<8>::from("mv");
+/// name.push_str("utils-extra");
+/// assert_eq!(name, "mvutils-");
+/// assert!(name.is_full());
+/// ```
+#[derive(Copy, Clone)]
+pub struct InlineString<const N: usize> {
+    bytes: [u8; N],
+    len: u8,
+}
+
+impl<const N: usize> InlineString<N> {
+    const CHECK: () = assert!(N <= u8::MAX as usize, "InlineString can hold at most 255 bytes");
+
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::CHECK;
+        InlineString { bytes: [0; N], len: 0 }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len as usize == N
+    }
+
+    /// The amount of bytes that can still be appended.
+    pub const fn remaining(&self) -> usize {
+        N - self.len as usize
+    }
+
+    pub fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(&self.bytes[..self.len()]) }
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        let len = self.len();
+        unsafe { std::str::from_utf8_unchecked_mut(&mut self.bytes[..len]) }
+    }
+
+    /// Append the char, returning false if it does not fit.
+    pub fn push(&mut self, c: char) -> bool {
+        let len = c.len_utf8();
+        if len > self.remaining() {
+            return false;
+        }
+        let start = self.len();
+        c.encode_utf8(&mut self.bytes[start..]);
+        self.len += len as u8;
+        true
+    }
+
+    /// Append as much of the string as fits, returning the amount of bytes appended.
+    pub fn push_str(&mut self, s: &str) -> usize {
+        let mut amount = s.len().min(self.remaining());
+        while !s.is_char_boundary(amount) {
+            amount -= 1;
+        }
+        let start = self.len();
+        self.bytes[start..start + amount].copy_from_slice(&s.as_bytes()[..amount]);
+        self.len += amount as u8;
+        amount
+    }
+
+    /// Append the whole string, or nothing and return false if it does not fit.
+    pub fn try_push_str(&mut self, s: &str) -> bool {
+        if s.len() > self.remaining() {
+            return false;
+        }
+        self.push_str(s);
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.len -= c.len_utf8() as u8;
+        Some(c)
+    }
+
+    /// Shorten the string to the length in bytes. Does nothing if it is already shorter.
+    ///
+    /// # Panics
+    /// If the length is not on a char boundary.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            assert!(self.as_str().is_char_boundary(len), "Truncating InlineString at {len}, which is not a char boundary");
+            self.len = len as u8;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for InlineString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> From<&str> for InlineString<N> {
+    /// Create the string, truncating it if it is longer than `N` bytes.
+    fn from(value: &str) -> Self {
+        let mut s = Self::new();
+        s.push_str(value);
+        s
+    }
+}
+
+impl<const N: usize> FromStr for InlineString<N> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut result = Self::new();
+        if result.try_push_str(s) {
+            Ok(result)
+        } else {
+            Err(format!("String of {} bytes does not fit into InlineString<{}>", s.len(), N))
+        }
+    }
+}
+
+impl<const N: usize> Deref for InlineString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> DerefMut for InlineString<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for InlineString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for InlineString<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Display for InlineString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> Debug for InlineString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> std::fmt::Write for InlineString<N> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if self.try_push_str(s) {
+            Ok(())
+        } else {
+            Err(std::fmt::Error)
+        }
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<InlineString<M>> for InlineString<N> {
+    fn eq(&self, other: &InlineString<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for InlineString<N> {}
+
+impl<const N: usize> PartialEq<str> for InlineString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for InlineString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialOrd for InlineString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for InlineString<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for InlineString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> Savable for InlineString<N> {
+    fn save(&self, saver: &mut impl Saver) {
+        saver.push_u8(self.len);
+        saver.push_bytes(self.as_str().as_bytes());
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = u8::load(loader)? as usize;
+        if len > N {
+            return Err(SaveError::custom(format!("String of {} bytes does not fit into InlineString<{}>", len, N)));
+        }
+        let bytes = loader.pop_bytes(len).ok_or(SaveError::eof("InlineString"))?;
+        let s = std::str::from_utf8(&bytes).map_err(|e| SaveError::custom(format!("Invalid UTF-8: {e}")))?;
+        Ok(Self::from(s))
+    }
+}
+
+impl<const N: usize> SaveSize for InlineString<N> {
+    fn save_size(&self) -> usize {
+        1 + self.len()
+    }
+}
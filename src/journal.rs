@@ -0,0 +1,233 @@
+use crate::hashers::crc32;
+use crate::save::fs::{load_from_file, save_to_file};
+use crate::save::{Loader, Savable, SaveError, Saver};
+use bytebuffer::ByteBuffer;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// A state which can be changed by applying deltas, so it can be kept in a [`Journal`].
+pub trait Journaled: Savable {
+    type Delta: Savable;
+
+    /// Apply a delta to the state. If this fails, the delta is not recorded.
+    fn apply(&mut self, delta: Self::Delta) -> Result<(), String>;
+}
+
+/// A snapshot of the state together with the sequence number of the last delta applied to it.
+struct Snapshot<T> {
+    seq: u64,
+    state: T,
+}
+
+impl<T: Savable> Savable for Snapshot<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.seq.save(saver);
+        self.state.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let seq = u64::load(loader).map_err(|e| e.in_field("seq"))?;
+        let state = T::load(loader).map_err(|e| e.in_field("state"))?;
+        Ok(Snapshot { seq, state })
+    }
+}
+
+fn record(seq: u64, delta: &[u8]) -> Vec<u8> {
+    let mut payload = ByteBuffer::new();
+    payload.push_u64(seq);
+    payload.push_bytes(delta);
+
+    let mut record = ByteBuffer::new();
+    record.push_u32(payload.len() as u32);
+    record.push_u32(crc32(payload.as_bytes()));
+    record.push_bytes(payload.as_bytes());
+    record.into_vec()
+}
+
+/// A state saved as a snapshot file and a journal of the deltas applied since, so changes can be
+/// persisted incrementally without saving the whole state every time.
+///
+/// Every delta is appended to `<file>.journal` with a sequence number and a checksum. On
+/// [`Journal::open`], the deltas newer than the snapshot are replayed into it, and a record torn by
+/// a crash is discarded along with everything after it. [`Journal::compact`] saves the current state
+/// as the new snapshot and empties the journal, which also happens automatically after a
+/// configurable number of records.
+pub struct Journal<T: Journaled> {
+    path: PathBuf,
+    journal_path: PathBuf,
+    file: BufWriter<File>,
+    state: T,
+    seq: u64,
+    records: usize,
+    replayed: usize,
+    compact_after: Option<usize>,
+}
+
+impl<T: Journaled> Journal<T> {
+    /// Open the journal for the snapshot at the given path, starting from `initial` if there is no
+    /// snapshot yet.
+    pub fn open(path: impl AsRef<Path>, initial: impl FnOnce() -> T) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut journal_path = path.file_name().unwrap_or_default().to_os_string();
+        journal_path.push(".journal");
+        let journal_path = path.with_file_name(journal_path);
+
+        let Snapshot { seq: snapshot_seq, mut state } = if path.exists() {
+            load_from_file::<Snapshot<T>>(&path)?
+        } else {
+            Snapshot { seq: 0, state: initial() }
+        };
+
+        let io_error = |e: io::Error| format!("Failed to open {}: {}", journal_path.display(), e);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&journal_path)
+            .map_err(io_error)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(io_error)?;
+        let mut buffer = ByteBuffer::from_vec(data);
+
+        let mut seq = snapshot_seq;
+        let mut records = 0;
+        let mut replayed = 0;
+        let mut valid = 0;
+        while let (Some(len), Some(checksum)) = (buffer.pop_u32(), buffer.pop_u32()) {
+            let Some(payload) = buffer.pop_bytes(len as usize) else {
+                break;
+            };
+            if crc32(&payload) != checksum {
+                break;
+            }
+            let mut payload = ByteBuffer::from_vec(payload);
+            let Some(record_seq) = payload.pop_u64() else {
+                break;
+            };
+            if record_seq > snapshot_seq {
+                if record_seq != seq + 1 {
+                    return Err(format!("Journal {} is missing the record {}", journal_path.display(), seq + 1));
+                }
+                let delta = T::Delta::load(&mut payload).map_err(|e| format!("Failed to load record {}: {}", record_seq, e))?;
+                state.apply(delta).map_err(|e| format!("Failed to replay record {}: {}", record_seq, e))?;
+                seq = record_seq;
+                replayed += 1;
+            }
+            records += 1;
+            valid = buffer.get_rpos() as u64;
+        }
+
+        file.set_len(valid).map_err(io_error)?;
+        file.seek(SeekFrom::Start(valid)).map_err(io_error)?;
+
+        Ok(Journal {
+            path,
+            journal_path,
+            file: BufWriter::new(file),
+            state,
+            seq,
+            records,
+            replayed,
+            compact_after: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn journal_path(&self) -> &Path {
+        &self.journal_path
+    }
+
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+
+    /// The sequence number of the last applied delta.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// The number of records in the journal.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// The number of records replayed into the snapshot when the journal was opened.
+    pub fn replayed(&self) -> usize {
+        self.replayed
+    }
+
+    /// Compact the journal automatically once it holds the given number of records.
+    pub fn set_compact_after(&mut self, records: Option<usize>) {
+        self.compact_after = records;
+    }
+
+    /// Apply a delta to the state and append it to the journal, returning its sequence number. The
+    /// record is buffered, use [`Journal::flush`] to make sure it is on disk.
+    pub fn append(&mut self, delta: T::Delta) -> Result<u64, String> {
+        let mut buffer = ByteBuffer::new();
+        delta.save(&mut buffer);
+        self.state.apply(delta)?;
+
+        self.seq += 1;
+        let record = record(self.seq, buffer.as_bytes());
+        self.file
+            .write_all(&record)
+            .map_err(|e| format!("Failed to write to {}: {}", self.journal_path.display(), e))?;
+        self.records += 1;
+
+        if self.compact_after.is_some_and(|n| self.records >= n) {
+            self.compact()
+                .map_err(|e| format!("Failed to compact {}: {}", self.path.display(), e))?;
+        }
+        Ok(self.seq)
+    }
+
+    /// Write all appended records to disk.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_data()
+    }
+
+    /// Save the current state as the new snapshot and empty the journal. The snapshot is written
+    /// atomically, and records already contained in it are skipped when replaying, so a crash at any
+    /// point leaves a consistent state behind.
+    pub fn compact(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        save_to_file(&self.path, &SnapshotRef { seq: self.seq, state: &self.state })?;
+
+        let file = self.file.get_mut();
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.sync_all()?;
+        self.records = 0;
+        Ok(())
+    }
+}
+
+/// Saves a snapshot by reference, so compacting does not have to clone the state.
+struct SnapshotRef<'a, T> {
+    seq: u64,
+    state: &'a T,
+}
+
+impl<T: Savable> Savable for SnapshotRef<'_, T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.seq.save(saver);
+        self.state.save(saver);
+    }
+
+    fn load(_: &mut impl Loader) -> Result<Self, SaveError> {
+        Err(SaveError::custom("Snapshots are loaded by value"))
+    }
+}
+
+impl<T: Journaled> Drop for Journal<T> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
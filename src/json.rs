@@ -0,0 +1,505 @@
+use crate::save::{load_len, nested, Loader, Savable, SaveError, Saver, MAX_PREALLOCATION};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Write};
+use std::ops::Index;
+use std::str::FromStr;
+
+const MAX_DEPTH: usize = 256;
+
+/// A JSON value. Objects keep their keys sorted, so serializing is deterministic.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Json {
+    #[default]
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+static NULL: Json = Json::Null;
+
+impl Json {
+    pub fn parse(input: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.input.len() {
+            return Err(parser.error("Unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+
+    pub fn object() -> Json {
+        Json::Object(BTreeMap::new())
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Num(n) if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n <= i64::MAX as f64 => Some(*n as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Json>> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Json>> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut BTreeMap<String, Json>> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Get a value of an object by key.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.as_object()?.get(key)
+    }
+
+    /// Insert a value into an object, returning the previous value. Does nothing if this is not an object.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Json>) -> Option<Json> {
+        self.as_object_mut()?.insert(key.into(), value.into())
+    }
+
+    /// Push a value onto an array. Does nothing if this is not an array.
+    pub fn push(&mut self, value: impl Into<Json>) {
+        if let Json::Array(a) = self {
+            a.push(value.into());
+        }
+    }
+
+    /// Serialize with newlines and the given indentation per level.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        let mut output = String::new();
+        self.write(&mut output, Some(indent), 0).unwrap();
+        output
+    }
+
+    fn write(&self, f: &mut impl Write, indent: Option<usize>, level: usize) -> std::fmt::Result {
+        let newline = |f: &mut dyn Write, level: usize| match indent {
+            Some(indent) => write!(f, "\n{:1$}", "", indent * level),
+            None => Ok(()),
+        };
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Num(n) if n.is_finite() => write!(f, "{n}"),
+            Json::Num(_) => f.write_str("null"),
+            Json::Str(s) => write_escaped(f, s),
+            Json::Array(a) => {
+                if a.is_empty() {
+                    return f.write_str("[]");
+                }
+                f.write_char('[')?;
+                for (i, value) in a.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    newline(f, level + 1)?;
+                    value.write(f, indent, level + 1)?;
+                }
+                newline(f, level)?;
+                f.write_char(']')
+            }
+            Json::Object(o) => {
+                if o.is_empty() {
+                    return f.write_str("{}");
+                }
+                f.write_char('{')?;
+                for (i, (key, value)) in o.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    newline(f, level + 1)?;
+                    write_escaped(f, key)?;
+                    f.write_str(if indent.is_some() { ": " } else { ":" })?;
+                    value.write(f, indent, level + 1)?;
+                }
+                newline(f, level)?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_escaped(f: &mut impl Write, s: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\x08' => f.write_str("\\b")?,
+            '\x0C' => f.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at position {} while parsing JSON!", self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.input.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str, value: Json) -> Result<Json, String> {
+        if self.input[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("Unexpected character"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.input.get(self.pos) {
+            None => Err(self.error("Unexpected end of input")),
+            Some(b'n') => self.expect("null", Json::Null),
+            Some(b't') => self.expect("true", Json::Bool(true)),
+            Some(b'f') => self.expect("false", Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::Str),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+        }
+    }
+
+    fn nested(&mut self, f: fn(&mut Self) -> Result<Json, String>) -> Result<Json, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("Maximum nesting depth exceeded"));
+        }
+        self.depth += 1;
+        self.pos += 1;
+        let value = f(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.input.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.input.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        let mut values = BTreeMap::new();
+        self.skip_whitespace();
+        if self.input.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(values));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.input.get(self.pos) != Some(&b'"') {
+                return Err(self.error("Expected object key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.input.get(self.pos) != Some(&b':') {
+                return Err(self.error("Expected ':'"));
+            }
+            self.pos += 1;
+            values.insert(key, self.value()?);
+            self.skip_whitespace();
+            match self.input.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(values));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.input.get(self.pos..self.pos + 4).ok_or_else(|| self.error("Unexpected end of input"))?;
+        let digits = std::str::from_utf8(digits).map_err(|_| self.error("Invalid unicode escape"))?;
+        let value = u32::from_str_radix(digits, 16).map_err(|_| self.error("Invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            match self.input.get(self.pos) {
+                None => return Err(self.error("Unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = match self.input.get(self.pos) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'b') => '\x08',
+                        Some(b'f') => '\x0C',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            let mut code = self.hex4()?;
+                            if (0xD800..0xDC00).contains(&code) && self.input[self.pos..].starts_with(b"\\u") {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                            }
+                            self.pos -= 1;
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    };
+                    self.pos += 1;
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                Some(b) if *b < 0x20 => return Err(self.error("Control character in string")),
+                Some(b) => {
+                    bytes.push(*b);
+                    self.pos += 1;
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("Invalid UTF-8 in string"))
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while matches!(self.input.get(self.pos), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Json::Num)
+            .ok_or_else(|| self.error("Invalid number"))
+    }
+}
+
+impl FromStr for Json {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Json::parse(s)
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, None, 0)
+    }
+}
+
+impl Index<&str> for Json {
+    type Output = Json;
+
+    /// Get a value of an object by key, or [`Json::Null`] if it does not exist.
+    fn index(&self, key: &str) -> &Json {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for Json {
+    type Output = Json;
+
+    /// Get a value of an array by index, or [`Json::Null`] if it does not exist.
+    fn index(&self, index: usize) -> &Json {
+        self.as_array().and_then(|a| a.get(index)).unwrap_or(&NULL)
+    }
+}
+
+macro_rules! impl_from_num {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Json {
+                fn from(value: $t) -> Self {
+                    Json::Num(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_num!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::Str(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::Str(value.to_string())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(value: Vec<T>) -> Self {
+        Json::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<BTreeMap<String, Json>> for Json {
+    fn from(value: BTreeMap<String, Json>) -> Self {
+        Json::Object(value)
+    }
+}
+
+impl Savable for Json {
+    fn save(&self, saver: &mut impl Saver) {
+        match self {
+            Json::Null => saver.push_u8(0),
+            Json::Bool(b) => {
+                saver.push_u8(1);
+                b.save(saver);
+            }
+            Json::Num(n) => {
+                saver.push_u8(2);
+                n.save(saver);
+            }
+            Json::Str(s) => {
+                saver.push_u8(3);
+                s.save(saver);
+            }
+            Json::Array(a) => {
+                saver.push_u8(4);
+                a.save(saver);
+            }
+            Json::Object(o) => {
+                saver.push_u8(5);
+                saver.push_u64(o.len() as u64);
+                for (key, value) in o {
+                    key.save(saver);
+                    value.save(saver);
+                }
+            }
+        }
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        match u8::load(loader)? {
+            0 => Ok(Json::Null),
+            1 => Ok(Json::Bool(bool::load(loader)?)),
+            2 => Ok(Json::Num(f64::load(loader)?)),
+            3 => Ok(Json::Str(String::load(loader)?)),
+            4 => {
+                let len = load_len(loader)?;
+                nested(loader, |loader| {
+                    let mut a = Vec::with_capacity(len.min(MAX_PREALLOCATION) as usize);
+                    for _ in 0..len {
+                        a.push(Json::load(loader)?);
+                    }
+                    Ok(Json::Array(a))
+                })
+            }
+            5 => {
+                let len = load_len(loader)?;
+                nested(loader, |loader| {
+                    let mut o = BTreeMap::new();
+                    for _ in 0..len {
+                        o.insert(String::load(loader)?, Json::load(loader)?);
+                    }
+                    Ok(Json::Object(o))
+                })
+            }
+            other => Err(SaveError::InvalidDiscriminant { ty: "Json", value: other as u64 }),
+        }
+    }
+}
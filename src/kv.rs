@@ -0,0 +1,222 @@
+use crate::hashers::crc32;
+use crate::save::{Loader, Savable, Saver};
+use bytebuffer::ByteBuffer;
+use hashbrown::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"MVKV";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 8;
+const OP_REMOVE: u8 = 0;
+const OP_SET: u8 = 1;
+const COMPACT_MIN_SIZE: u64 = 64 * 1024;
+
+/// A small persistent key-value store. Values are encoded using [`Savable`] and every change is
+/// appended to a log file, which is replayed on [`KvStore::open`]. Each record carries a checksum,
+/// so a record torn by a crash is discarded instead of corrupting the store. The log is compacted
+/// automatically once it grows to more than twice the size of the live data.
+pub struct KvStore {
+    path: PathBuf,
+    file: BufWriter<File>,
+    map: HashMap<String, Vec<u8>>,
+    log_size: u64,
+    auto_compact: bool,
+}
+
+fn record(op: u8, key: &str, value: &[u8]) -> Vec<u8> {
+    let mut payload = ByteBuffer::new();
+    payload.push_u8(op);
+    payload.push_string(key);
+    payload.push_u64(value.len() as u64);
+    payload.push_bytes(value);
+
+    let mut record = ByteBuffer::new();
+    record.push_u32(payload.len() as u32);
+    record.push_u32(crc32(payload.as_bytes()));
+    record.push_bytes(payload.as_bytes());
+    record.into_vec()
+}
+
+fn header() -> Vec<u8> {
+    let mut header = ByteBuffer::new();
+    header.push_bytes(MAGIC);
+    header.push_u32(FORMAT_VERSION);
+    header.into_vec()
+}
+
+fn live_size(map: &HashMap<String, Vec<u8>>) -> u64 {
+    map.iter().map(|(k, v)| 21 + k.len() as u64 + v.len() as u64).sum()
+}
+
+impl KvStore {
+    /// Open the store at the given path, creating it if it does not exist. Fails with
+    /// [`io::ErrorKind::InvalidData`] without touching the file if it is not a KvStore file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let header = header();
+        if (data.len() as u64) < HEADER_SIZE && header.starts_with(&data) {
+            // A new file, or one whose header was torn while creating it.
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(&header)?;
+            file.sync_data()?;
+            data = header;
+        }
+        let mut buffer = ByteBuffer::from_vec(data);
+        if buffer.pop_bytes(4).as_deref() != Some(MAGIC.as_slice()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a KvStore file"));
+        }
+        match buffer.pop_u32() {
+            Some(FORMAT_VERSION) => {}
+            Some(version) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unsupported KvStore version {version}"))),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a KvStore file")),
+        }
+        let mut map = HashMap::new();
+        let mut valid = HEADER_SIZE;
+
+        while let (Some(len), Some(checksum)) = (buffer.pop_u32(), buffer.pop_u32()) {
+            let Some(payload) = buffer.pop_bytes(len as usize) else {
+                break;
+            };
+            if crc32(&payload) != checksum {
+                break;
+            }
+            let mut payload = ByteBuffer::from_vec(payload);
+            let (Some(op), Some(key), Ok(value)) = (payload.pop_u8(), payload.pop_string(), Vec::<u8>::load(&mut payload)) else {
+                break;
+            };
+            match op {
+                OP_SET => map.insert(key, value),
+                OP_REMOVE => map.remove(&key),
+                _ => break,
+            };
+            valid = buffer.get_rpos() as u64;
+        }
+
+        file.set_len(valid)?;
+        file.seek(SeekFrom::Start(valid))?;
+
+        Ok(KvStore {
+            path,
+            file: BufWriter::new(file),
+            map,
+            log_size: valid,
+            auto_compact: true,
+        })
+    }
+
+    pub fn set_auto_compact(&mut self, auto_compact: bool) {
+        self.auto_compact = auto_compact;
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(String::as_str)
+    }
+
+    /// Load the value stored under the key, returning `Ok(None)` if there is none.
+    pub fn get<T: Savable>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.map.get(key) {
+            Some(bytes) => T::load(&mut ByteBuffer::from_bytes(bytes)).map(Some).map_err(Into::into),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_or<T: Savable>(&self, key: &str, default: T) -> T {
+        self.get(key).ok().flatten().unwrap_or(default)
+    }
+
+    pub fn set<T: Savable>(&mut self, key: &str, value: &T) -> io::Result<()> {
+        let mut buffer = ByteBuffer::new();
+        value.save(&mut buffer);
+        let bytes = buffer.into_vec();
+        self.append(&record(OP_SET, key, &bytes))?;
+        self.map.insert(key.to_string(), bytes);
+        self.maybe_compact()
+    }
+
+    /// Remove the value stored under the key. Returns whether there was a value.
+    pub fn remove(&mut self, key: &str) -> io::Result<bool> {
+        if self.map.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.append(&record(OP_REMOVE, key, &[]))?;
+        self.maybe_compact()?;
+        Ok(true)
+    }
+
+    fn append(&mut self, record: &[u8]) -> io::Result<()> {
+        self.file.write_all(record)?;
+        self.log_size += record.len() as u64;
+        Ok(())
+    }
+
+    fn maybe_compact(&mut self) -> io::Result<()> {
+        if self.auto_compact && self.log_size > COMPACT_MIN_SIZE && self.log_size > live_size(&self.map) * 2 {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Write all pending changes to disk.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_data()
+    }
+
+    /// Rewrite the log so it only contains the current values.
+    pub fn compact(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = self.path.with_file_name(tmp_name);
+
+        let mut file = BufWriter::new(File::create(&tmp)?);
+        let header = header();
+        file.write_all(&header)?;
+        let mut size = header.len() as u64;
+        for (key, value) in &self.map {
+            let record = record(OP_SET, key, value);
+            file.write_all(&record)?;
+            size += record.len() as u64;
+        }
+        file.flush()?;
+        file.get_ref().sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp, &self.path)?;
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        file.seek(SeekFrom::End(0))?;
+        self.file = BufWriter::new(file);
+        self.log_size = size;
+        Ok(())
+    }
+}
+
+impl Drop for KvStore {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -0,0 +1,107 @@
+use crate::color::Color;
+use crate::geometry::{Extent, Rect, Vec2, Vec3};
+use std::time::Duration;
+
+/// Linear interpolation between two values, where `t = 0` returns `self` and `t = 1` returns `other`.
+/// Values of `t` outside of this range extrapolate.
+///
+/// This can be derived for structs using `#[derive(Lerp)]`. Fields marked with `#[lerp(skip)]` are
+/// cloned from `self`, and float fields marked with `#[lerp(round)]` are rounded after interpolating.
+pub trait Lerp {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+/// Interpolate between two values, see [`Lerp`].
+pub fn lerp<T: Lerp>(a: &T, b: &T, t: f32) -> T {
+    a.lerp(b, t)
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t as f64
+    }
+}
+
+macro_rules! impl_lerp_int {
+    ($($t:ty),*) => {
+        $(
+            impl Lerp for $t {
+                /// Interpolates as a float and rounds to the nearest integer, saturating at the bounds of the type.
+                fn lerp(&self, other: &Self, t: f32) -> Self {
+                    (*self as f64 + (*other as f64 - *self as f64) * t as f64).round() as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_lerp_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl Lerp for bool {
+    /// Switches from `self` to `other` at `t = 0.5`.
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        if t < 0.5 {
+            *self
+        } else {
+            *other
+        }
+    }
+}
+
+impl Lerp for Duration {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Duration::from_secs_f64(self.as_secs_f64().lerp(&other.as_secs_f64(), t).max(0.0))
+    }
+}
+
+impl<T: Lerp + Clone> Lerp for Option<T> {
+    /// Interpolates if both values are present, otherwise switches at `t = 0.5`.
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Some(a), Some(b)) => Some(a.lerp(b, t)),
+            _ if t < 0.5 => self.clone(),
+            _ => other.clone(),
+        }
+    }
+}
+
+impl<T: Lerp, const N: usize> Lerp for [T; N] {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        std::array::from_fn(|i| self[i].lerp(&other[i], t))
+    }
+}
+
+macro_rules! impl_lerp_tuple {
+    ($($t:ident $i:tt),*) => {
+        impl<$($t: Lerp),*> Lerp for ($($t,)*) {
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                ($(self.$i.lerp(&other.$i, t),)*)
+            }
+        }
+    };
+}
+
+impl_lerp_tuple!(A 0);
+impl_lerp_tuple!(A 0, B 1);
+impl_lerp_tuple!(A 0, B 1, C 2);
+impl_lerp_tuple!(A 0, B 1, C 2, D 3);
+
+macro_rules! impl_lerp_inherent {
+    ($($t:ty),*) => {
+        $(
+            impl Lerp for $t {
+                fn lerp(&self, other: &Self, t: f32) -> Self {
+                    <$t>::lerp(*self, *other, t)
+                }
+            }
+        )*
+    };
+}
+
+impl_lerp_inherent!(Vec2, Vec3, Extent, Rect, Color);
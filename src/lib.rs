@@ -6,17 +6,78 @@ pub mod print;
 pub mod remake;
 pub mod save;
 pub mod static_vec;
+pub mod inline_string;
+pub mod small_vec;
+pub mod cow_arc;
+pub mod handle;
+pub mod scratch;
 pub mod thread;
 pub mod unsafe_utils;
 pub mod utils;
 pub mod version;
 pub mod state;
 pub mod bytebuffer;
+pub mod kv;
+pub mod config;
+pub mod json;
+pub mod encoding;
+pub mod paths;
+pub mod metrics;
+pub mod profiler;
+pub mod alloc;
+pub mod crash;
+pub mod retry;
+pub mod expr;
+pub mod template;
+pub mod i18n;
+pub mod color;
+pub mod geometry;
+pub mod screen;
+pub mod rate_limit;
+pub mod cache;
+pub mod memo;
+pub mod task_graph;
+pub mod scheduler;
+pub mod replay;
+pub mod lerp;
+pub mod undo;
+pub mod fsm;
+pub mod tween;
+pub mod prompt;
+pub mod shutdown;
+pub mod proc;
+pub mod platform;
+pub mod hot_reload;
+pub mod sync;
+pub mod ids;
+pub mod units;
+pub mod clock;
+pub mod journal;
+pub mod message_queue;
+pub mod handshake;
+pub mod diff;
+pub mod services;
+pub mod budget;
+pub mod deep_size;
+pub mod ord_float;
+pub mod graph;
 
 #[cfg(feature = "savable_arc")]
 pub mod savable_arc;
 
-pub use mvutils_proc_macro::{try_from_string, Savable};
+#[cfg(feature = "net")]
+pub mod net;
+
+#[cfg(feature = "plugin")]
+pub mod plugin;
+
+#[cfg(feature = "debug_globals")]
+pub mod globals;
+
+#[cfg(feature = "schema")]
+pub use mvutils_proc_macro::Schema;
+
+pub use mvutils_proc_macro::{savable_versioned, try_from_string, Builder, ConfigSection, DeepSizeOf, Diff, DirtySavable, EnumIter, Flags, Getters, Lerp, Pod, Savable, SaveSize, Setters, Verify};
 
 #[cfg(test)]
 #[allow(dead_code)]
@@ -28,9 +89,12 @@ mod tests {
     use bytebuffer::ByteBuffer;
     use mvutils_proc_macro::try_from_string;
     use mvutils_proc_macro::Savable;
+    use mvutils_proc_macro::Builder;
+    use mvutils_proc_macro::EnumIter;
+    use mvutils_proc_macro::SaveSize;
     use crate::state::State;
     use crate::{update, when};
-    use crate::save::{Loader, Savable, Saver};
+    use crate::save::{Loader, Savable, SaveSize, Saver};
 
     #[derive(Savable)]
     struct A;
@@ -38,7 +102,7 @@ mod tests {
     #[derive(Savable)]
     struct B(String, #[unsaved] u32, #[custom(save = hello, load = world)] i32);
 
-    #[derive(Savable)]
+    #[derive(Savable, SaveSize)]
     struct C {
         a: String,
         #[unsaved]
@@ -83,7 +147,7 @@ mod tests {
         }
     }
 
-    #[derive(Savable, Debug)]
+    #[derive(Savable, SaveSize, Debug)]
     enum E {
         A,
         B(String, #[unsaved] u32, #[custom(save = hello, load = world)] i32),
@@ -255,4 +319,4004 @@ mod tests {
         let mut buffer = ByteBuffer::from_vec_le(buffer.into_vec());
         assert_eq!(buffer.pop_u16(), Some(0x1000));
     }
+
+    #[derive(Builder, Debug, Eq, PartialEq)]
+    struct Config {
+        name: String,
+        #[builder(default)]
+        threads: u32,
+        #[builder(default = 8080)]
+        port: u16,
+    }
+
+    #[test]
+    fn test_builder() {
+        let config = Config::builder()
+            .name("server".to_string())
+            .threads(4)
+            .build()
+            .unwrap();
+
+        assert_eq!(config, Config {
+            name: "server".to_string(),
+            threads: 4,
+            port: 8080,
+        });
+
+        assert!(Config::builder().port(80).build().is_err());
+    }
+
+    #[derive(EnumIter, Savable, Debug, Eq, PartialEq)]
+    enum Direction {
+        North,
+        East,
+        South,
+        West,
+    }
+
+    #[test]
+    fn test_enum_iter() {
+        assert_eq!(Direction::COUNT, 4);
+        assert_eq!(Direction::iter().count(), 4);
+        assert_eq!(Direction::from_index(2), Some(Direction::South));
+        assert_eq!(Direction::from_index(4), None);
+
+        for (i, direction) in Direction::iter().enumerate() {
+            assert_eq!(direction.index(), i);
+
+            let mut buffer = ByteBuffer::new();
+            direction.save(&mut buffer);
+            assert_eq!(buffer.as_bytes(), &[i as u8]);
+        }
+    }
+
+    #[derive(Savable, SaveSize)]
+    struct Vertex {
+        position: [f32; 3],
+        color: u32,
+        #[unsaved]
+        dirty: bool,
+    }
+
+    #[test]
+    fn test_save_size() {
+        assert_eq!(Vertex::FIXED_SIZE, Some(16));
+        assert_eq!(C::FIXED_SIZE, None);
+
+        let vertex = Vertex {
+            position: [1.0, 2.0, 3.0],
+            color: 0xFFFFFFFF,
+            dirty: true,
+        };
+        let mut buffer = ByteBuffer::new();
+        vertex.save(&mut buffer);
+        assert_eq!(vertex.save_size(), buffer.len());
+
+        let e = E::C {
+            a: "Hello".to_string(),
+            _b: 123,
+            c: -123,
+        };
+        let mut buffer = ByteBuffer::new();
+        e.save(&mut buffer);
+        assert_eq!(e.save_size(), buffer.len());
+
+        let vec = vec![Some("a".to_string()), None];
+        let mut buffer = ByteBuffer::new();
+        vec.save(&mut buffer);
+        assert_eq!(vec.save_size(), buffer.len());
+    }
+
+    #[derive(Savable, SaveSize, Debug, Eq, PartialEq)]
+    #[savable(tagged)]
+    struct SaveV1 {
+        a: u32,
+        b: String,
+    }
+
+    #[derive(Savable, SaveSize, Debug, Eq, PartialEq)]
+    #[savable(tagged)]
+    struct SaveV2 {
+        #[savable(tag = 1)]
+        b: String,
+        #[savable(tag = 0)]
+        a: u32,
+        #[unsaved]
+        cache: u32,
+        c: Vec<u64>,
+    }
+
+    #[test]
+    fn test_tagged_savable() {
+        let v2 = SaveV2 {
+            b: "Hello".to_string(),
+            a: 42,
+            cache: 7,
+            c: vec![1, 2, 3],
+        };
+        let mut buffer = ByteBuffer::new();
+        v2.save(&mut buffer);
+        assert_eq!(v2.save_size(), buffer.len());
+
+        let v1 = SaveV1::load(&mut buffer).unwrap();
+        assert_eq!(v1, SaveV1 { a: 42, b: "Hello".to_string() });
+
+        let mut buffer = ByteBuffer::new();
+        v1.save(&mut buffer);
+        let v2 = SaveV2::load(&mut buffer).unwrap();
+        assert_eq!(v2, SaveV2 {
+            b: "Hello".to_string(),
+            a: 42,
+            cache: 0,
+            c: vec![],
+        });
+    }
+
+    #[repr(C)]
+    #[derive(Savable, Clone, Copy)]
+    struct IntValue {
+        kind: u8,
+        value: i64,
+    }
+
+    #[repr(C)]
+    #[derive(Savable, Clone, Copy)]
+    struct FloatValue {
+        kind: u8,
+        value: f64,
+    }
+
+    #[derive(Savable)]
+    #[discriminant(kind)]
+    union Value {
+        kind: u8,
+        int: IntValue,
+        float: FloatValue,
+    }
+
+    // SAFETY: IntValue and FloatValue start with `kind`, which is always 1 or 2 respectively.
+    unsafe impl crate::save::UnionDiscriminant for Value {}
+
+    #[test]
+    fn test_union_savable() {
+        let value = Value {
+            float: FloatValue { kind: 2, value: 1.5 },
+        };
+        let mut buffer = ByteBuffer::new();
+        value.save(&mut buffer);
+        assert_eq!(buffer.len(), 1 + 1 + 8);
+
+        let value = Value::load(&mut buffer).unwrap();
+        unsafe {
+            assert_eq!(value.kind, 2);
+            assert_eq!(value.float.value, 1.5);
+        }
+    }
+
+    #[cfg(feature = "schema")]
+    #[derive(Savable, mvutils_proc_macro::Schema)]
+    struct Player {
+        name: String,
+        position: [f32; 2],
+        inventory: Vec<Option<u32>>,
+        state: PlayerState,
+    }
+
+    #[cfg(feature = "schema")]
+    #[derive(Savable, mvutils_proc_macro::Schema)]
+    enum PlayerState {
+        Idle,
+        Moving { speed: f32 },
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_schema_emit() {
+        use crate::save::schema::{emit_c_header, emit_ts, Definitions};
+
+        let definitions = Definitions::of::<Player>();
+        assert_eq!(definitions.iter().map(|d| d.name()).collect::<Vec<_>>(), vec!["PlayerState", "Player"]);
+
+        let header = emit_c_header(&definitions, "PLAYER_H");
+        assert!(header.contains("float position[2];"));
+        assert!(header.contains("PlayerState state;"));
+
+        let ts = emit_ts(&definitions);
+        assert!(ts.contains("export interface Player {"));
+        assert!(ts.contains("inventory: Array<number | null>;"));
+        assert!(ts.contains("case 1: return { kind: \"Moving\", speed: r.f32() };"));
+    }
+
+    #[test]
+    fn test_buffer_pool() {
+        use crate::bytebuffer::{save_pooled, BufferPool};
+
+        let pool = BufferPool::with_classes(&[16, 1024], 4);
+        {
+            let mut buffer = pool.acquire(100);
+            buffer.push_u64(12);
+            assert_eq!(buffer.len(), 8);
+        }
+        assert_eq!(pool.idle(), 1);
+        let buffer = pool.acquire(1000);
+        assert!(buffer.is_empty());
+        assert_eq!(pool.idle(), 0);
+        let _ = buffer.detach();
+        assert_eq!(pool.idle(), 0);
+        {
+            let mut buffer = pool.acquire(100);
+            buffer.push_bytes(&[0; 5000]);
+        }
+        assert_eq!(pool.idle(), 0);
+        assert_eq!(pool.idle_bytes(), 0);
+
+        let buffer = save_pooled(&"Hello".to_string());
+        assert_eq!(buffer.as_bytes(), &[0, 0, 0, 5, b'H', b'e', b'l', b'l', b'o']);
+    }
+
+    #[test]
+    fn test_save_to_file() {
+        use crate::save::fs::{backup_path, load_from_file, load_with_backups, save_to_file_with, SaveOptions};
+
+        let dir = std::env::temp_dir().join(format!("mvutils_save_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("save.dat");
+        let options = SaveOptions { backups: 2, sync: false };
+
+        for i in 0..4u32 {
+            save_to_file_with(&path, &i, &options).unwrap();
+        }
+        assert_eq!(load_from_file::<u32>(&path), Ok(3));
+        assert_eq!(load_from_file::<u32>(backup_path(&path, 1)), Ok(2));
+        assert_eq!(load_from_file::<u32>(backup_path(&path, 2)), Ok(1));
+        assert!(!backup_path(&path, 3).exists());
+
+        std::fs::write(&path, [0u8]).unwrap();
+        assert_eq!(load_with_backups::<u32>(&path, 2), Ok(2));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_save_archive() {
+        use crate::save::archive::{Compression, SaveArchive};
+
+        let path = std::env::temp_dir().join(format!("mvutils_archive_test_{}.mvar", std::process::id()));
+        {
+            let mut archive = SaveArchive::create(&path).unwrap();
+            archive.save("numbers", &vec![1u32; 1000]).unwrap();
+            archive.save_with("name", &"Hello".to_string(), Compression::None).unwrap();
+            archive.save("numbers", &vec![2u32; 10]).unwrap();
+        }
+
+        let mut archive = SaveArchive::open(&path).unwrap();
+        assert_eq!(archive.names().collect::<Vec<_>>(), vec!["numbers", "name"]);
+        assert_eq!(archive.load::<String>("name"), Ok("Hello".to_string()));
+        assert_eq!(archive.load::<Vec<u32>>("numbers"), Ok(vec![2u32; 10]));
+        assert!(archive.wasted() > 0);
+
+        archive.compact().unwrap();
+        assert_eq!(archive.wasted(), 0);
+        assert!(archive.remove("name"));
+        drop(archive);
+
+        let mut archive = SaveArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.load::<Vec<u32>>("numbers"), Ok(vec![2u32; 10]));
+        assert!(archive.load::<String>("name").is_err());
+
+        // Crashing before the index is flushed leaves the previous state intact
+        archive.save("numbers", &vec![3u32; 1000]).unwrap();
+        std::mem::forget(archive);
+        let mut archive = SaveArchive::open(&path).unwrap();
+        assert_eq!(archive.load::<Vec<u32>>("numbers"), Ok(vec![2u32; 10]));
+        drop(archive);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_kv_store() {
+        use crate::kv::KvStore;
+
+        let path = std::env::temp_dir().join(format!("mvutils_kv_test_{}.log", std::process::id()));
+        {
+            let mut store = KvStore::open(&path).unwrap();
+            store.set("volume", &0.5f32).unwrap();
+            store.set("name", &"Player".to_string()).unwrap();
+            store.set("volume", &0.8f32).unwrap();
+            store.set("temp", &1u8).unwrap();
+            assert!(store.remove("temp").unwrap());
+            assert!(!store.remove("temp").unwrap());
+        }
+
+        let mut data = std::fs::read(&path).unwrap();
+        data.extend_from_slice(&[0, 0, 0, 50, 1, 2]);
+        std::fs::write(&path, data).unwrap();
+
+        let mut store = KvStore::open(&path).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get::<f32>("volume"), Ok(Some(0.8)));
+        assert_eq!(store.get::<String>("name"), Ok(Some("Player".to_string())));
+        assert_eq!(store.get::<u8>("temp"), Ok(None));
+
+        store.compact().unwrap();
+        store.set("level", &3u32).unwrap();
+        drop(store);
+
+        let store = KvStore::open(&path).unwrap();
+        let mut keys = store.keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["level", "name", "volume"]);
+        drop(store);
+
+        std::fs::remove_file(&path).unwrap();
+
+        let foreign = b"not a key-value store, but important user data".to_vec();
+        std::fs::write(&path, &foreign).unwrap();
+        let error = KvStore::open(&path).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&path).unwrap(), foreign);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[derive(crate::ConfigSection, Debug, PartialEq)]
+    struct WindowConfig {
+        width: u32,
+        height: u32,
+        title: String,
+        #[config(default)]
+        fullscreen: bool,
+        #[config(default = 60)]
+        fps: u32,
+    }
+
+    #[test]
+    fn test_config() {
+        use crate::config::Config;
+
+        let input = "# Global settings\nname = test ; inline comment\n\n[window]\nwidth = 800\nheight=600\ntitle = \"My \\\"Game\\\"\\n\" # quoted\n\n[audio]\n; volume in percent\nvolume = 80\n";
+        let mut config = Config::parse(input).unwrap();
+
+        assert_eq!(config.get_str("", "name"), Some("test"));
+        assert_eq!(config.get::<u32>("audio", "volume"), Ok(Some(80)));
+        assert!(config.get::<u32>("", "name").is_err());
+        assert!(!config.get_or("audio", "muted", false));
+
+        let window: WindowConfig = config.read("window").unwrap();
+        assert_eq!(window, WindowConfig {
+            width: 800,
+            height: 600,
+            title: "My \"Game\"\n".to_string(),
+            fullscreen: false,
+            fps: 60,
+        });
+        assert!(config.read::<WindowConfig>("audio").is_err());
+
+        assert_eq!(config.to_string(), input);
+
+        config.set("audio", "volume", 50);
+        config.set("audio", "muted", true);
+        config.write("video", &window);
+        let output = config.to_string();
+        assert!(output.contains("; volume in percent\nvolume = 50\nmuted = true\n"));
+        assert!(output.ends_with("\n[video]\nwidth = 800\nheight = 600\ntitle = \"My \\\"Game\\\"\\n\"\nfullscreen = false\nfps = 60\n"));
+
+        let reparsed = Config::parse(&output).unwrap();
+        assert_eq!(reparsed.read::<WindowConfig>("video").unwrap(), window);
+        assert!(config.remove("audio", "muted"));
+        assert!(config.remove_section("video"));
+        assert_eq!(config.sections().collect::<Vec<_>>(), vec!["", "window", "audio"]);
+    }
+
+    #[test]
+    fn test_json() {
+        use crate::json::Json;
+
+        let input = r#"{"name": "Player \"One\"\n\u00e9\ud83d\ude00", "level": 12, "pos": [1.5, -2e3, 0], "alive": true, "guild": null}"#;
+        let value = Json::parse(input).unwrap();
+        assert_eq!(value["name"].as_str(), Some("Player \"One\"\né😀"));
+        assert_eq!(value["level"].as_i64(), Some(12));
+        assert_eq!(value["pos"][1].as_f64(), Some(-2000.0));
+        assert_eq!(value["alive"].as_bool(), Some(true));
+        assert!(value["guild"].is_null());
+        assert!(value["missing"][3].is_null());
+
+        let compact = value.to_string();
+        assert_eq!(compact, r#"{"alive":true,"guild":null,"level":12,"name":"Player \"One\"\né😀","pos":[1.5,-2000,0]}"#);
+        assert_eq!(Json::parse(&compact).unwrap(), value);
+        assert_eq!(Json::parse(&value.to_string_pretty(2)).unwrap(), value);
+        assert_eq!(Json::from(vec![1, 2]).to_string_pretty(2), "[\n  1,\n  2\n]");
+
+        let mut buffer = ByteBuffer::new();
+        value.save(&mut buffer);
+        assert_eq!(Json::load(&mut buffer).unwrap(), value);
+
+        let mut buffer = ByteBuffer::new();
+        for _ in 0..200_000 {
+            buffer.push_u8(5);
+            buffer.push_u64(1);
+            "a".to_string().save(&mut buffer);
+        }
+        buffer.push_u8(0);
+        let limits = crate::save::validate::LoadLimits::default();
+        assert!(crate::save::validate::ValidatingLoader::new(&mut buffer, limits).load::<Json>().is_err());
+
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("{\"a\" 1}").is_err());
+        assert!(Json::parse("01x").is_err());
+        assert!(Json::parse(&"[".repeat(1000)).is_err());
+    }
+
+    #[test]
+    fn test_encoding() {
+        use crate::encoding::*;
+
+        assert_eq!(encode_hex(&[0x00, 0xAB, 0x7F]), "00ab7f");
+        assert_eq!(decode_hex("00AB7f"), Ok(vec![0x00, 0xAB, 0x7F]));
+        assert_eq!(decode_hex("abc"), Err(DecodeError::InvalidLength(3)));
+        assert_eq!(decode_hex("0g"), Err(DecodeError::InvalidCharacter(1, 'g')));
+        assert_eq!(decode_hex("0ä0"), Err(DecodeError::InvalidCharacter(1, 'ä')));
+
+        let cases: [(&[u8], &str); 5] = [(b"", ""), (b"f", "Zg=="), (b"fo", "Zm8="), (b"foo", "Zm9v"), (b"foobar", "Zm9vYmFy")];
+        for (bytes, encoded) in cases {
+            assert_eq!(encode_base64(bytes), encoded);
+            assert_eq!(decode_base64(encoded).unwrap(), bytes);
+            assert_eq!(decode_base64(encoded.trim_end_matches('=')).unwrap(), bytes);
+        }
+        assert_eq!(encode_base64(&[0xFB, 0xFF]), "+/8=");
+        assert_eq!(encode_base64_url(&[0xFB, 0xFF]), "-_8");
+        assert_eq!(decode_base64_url("-_8"), Ok(vec![0xFB, 0xFF]));
+        assert_eq!(decode_base64("-_8="), Err(DecodeError::InvalidCharacter(0, '-')));
+        assert_eq!(decode_base64("Zm9vY"), Err(DecodeError::InvalidLength(5)));
+        assert_eq!(decode_base64("Zg="), Err(DecodeError::InvalidLength(3)));
+
+        let mut buffer = ByteBuffer::new();
+        "payload".to_string().save(&mut buffer);
+        let encoded = buffer.to_base64();
+        let mut decoded = ByteBuffer::from_base64(&encoded).unwrap();
+        assert_eq!(String::load(&mut decoded).unwrap(), "payload");
+        assert_eq!(ByteBuffer::from_hex(&buffer.to_hex()).unwrap().as_bytes(), buffer.as_bytes());
+    }
+
+    #[test]
+    fn test_content_hashers() {
+        use crate::hashers::*;
+        use std::hash::Hasher;
+
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(fnv1a_64(b""), 0xCBF29CE484222325);
+        assert_eq!(fnv1a_64(b"a"), 0xAF63DC4C8601EC8C);
+        assert_eq!(xxhash64(b""), 0xEF46DB3751D8E999);
+        assert_eq!(xxhash64(b"abc"), 0x44BC2CF5AD770999);
+
+        let data = (0..1000u32).map(|i| (i * 7 + i / 13) as u8).collect::<Vec<_>>();
+        let mut crc = Crc32Hasher::default();
+        let mut fnv = Fnv1aHasher::default();
+        let mut xxh = XxHash64::with_seed(42);
+        for chunk in data.chunks(37) {
+            crc.write(chunk);
+            fnv.write(chunk);
+            xxh.write(chunk);
+        }
+        assert_eq!(crc.checksum(), crc32(&data));
+        assert_eq!(fnv.finish(), fnv1a_64(&data));
+        assert_eq!(xxh.finish(), xxhash64_with_seed(&data, 42));
+        assert_ne!(xxhash64_with_seed(&data, 42), xxhash64(&data));
+
+        let mut map = std::collections::HashMap::with_hasher(XxHash64::default());
+        map.insert("key", 1);
+        assert_eq!(map.get("key"), Some(&1));
+    }
+
+    #[test]
+    fn test_paths() {
+        use crate::paths::*;
+        use std::path::PathBuf;
+
+        assert_eq!(normalize("a/./b/../c/"), PathBuf::from("a/c"));
+        assert_eq!(normalize("../a/../../b"), PathBuf::from("../../b"));
+        assert_eq!(normalize("/../a"), PathBuf::from("/a"));
+        assert_eq!(normalize("a/.."), PathBuf::from("."));
+        assert_eq!(relative("/a/b/c/d.txt", "/a/b/e"), Some(PathBuf::from("../c/d.txt")));
+        assert_eq!(relative("a/b", "a/b"), Some(PathBuf::from(".")));
+        assert_eq!(relative("a", "../b"), None);
+        assert_eq!(relative("/a", "b"), None);
+        assert_eq!(extension("save.MVS"), Some("mvs".to_string()));
+        assert!(has_extension("save.MVS", ".mvs"));
+        assert_eq!(with_extension("save.tmp", ".mvs"), PathBuf::from("save.mvs"));
+        assert_eq!(file_stem("dir/save.mvs"), Some("save".to_string()));
+
+        let dirs = project_dirs("mvutils_test").unwrap();
+        assert!(dirs.config.ends_with("mvutils_test"));
+        assert!(dirs.cache.is_absolute());
+
+        let dir = std::env::temp_dir().join(format!("mvutils_paths_test_{}", std::process::id()));
+        ensure_parent(dir.join("a/b/file.txt")).unwrap();
+        assert!(dir.join("a/b").is_dir());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn test_message_stream() {
+        use crate::net::MessageStream;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = MessageStream::<(u32, String)>::accept(&listener).unwrap();
+            stream.set_compression(Some(6));
+            while let Some(message) = stream.next() {
+                let (id, text) = message.unwrap();
+                stream.send(&(id + 1, text.to_uppercase())).unwrap();
+            }
+        });
+
+        let mut client = MessageStream::<(u32, String)>::connect(addr).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        client.send(&(1, "hello".to_string())).unwrap();
+        assert_eq!(client.recv().unwrap(), (2, "HELLO".to_string()));
+        client.send(&(10, "a".repeat(10000))).unwrap();
+        assert_eq!(client.recv().unwrap(), (11, "A".repeat(10000)));
+
+        client.set_max_frame_size(16);
+        assert!(client.send(&(0, "a".repeat(100))).is_err());
+        client.shutdown().unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_metrics() {
+        use crate::metrics::Metrics;
+
+        let metrics = Metrics::new();
+        metrics.counter("requests").inc();
+        metrics.counter("requests").add(2);
+        metrics.gauge("players").set(5);
+        metrics.gauge("players").sub(1);
+        let timer = metrics.timer("tick");
+        timer.record(Duration::from_millis(2));
+        timer.record(Duration::from_millis(4));
+        timer.time(|| {});
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.counters, vec![("requests".to_string(), 3)]);
+        assert_eq!(snapshot.gauges, vec![("players".to_string(), 4)]);
+        let stats = &snapshot.timers[0].1;
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.max, Duration::from_millis(4));
+        assert!(stats.min < Duration::from_millis(2));
+
+        let text = snapshot.to_string();
+        assert!(text.contains("requests 3\nplayers 4\ntick_count 3\n"));
+
+        let mut buffer = ByteBuffer::new();
+        snapshot.save(&mut buffer);
+        assert_eq!(crate::metrics::MetricsSnapshot::load(&mut buffer).unwrap(), snapshot);
+    }
+
+    #[test]
+    #[cfg(feature = "net")]
+    fn test_remote_sink() {
+        use crate::metrics::{Level, LogRecord, Metrics, RemoteSink, Telemetry};
+        use crate::net::MessageStream;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut sink = RemoteSink::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut collector, _) = MessageStream::<Telemetry>::accept(&listener).unwrap();
+
+        sink.set_level(Level::Info);
+        sink.log(LogRecord::new(Level::Debug, "server", "ignored")).unwrap();
+        sink.log(LogRecord::new(Level::Warn, "server", "low memory")).unwrap();
+        sink.metrics(Metrics::new().snapshot()).unwrap();
+
+        match collector.recv().unwrap() {
+            Telemetry::Log(record) => assert_eq!(record.message, "low memory"),
+            _ => panic!("Expected log record"),
+        }
+        assert!(matches!(collector.recv().unwrap(), Telemetry::Metrics(_)));
+    }
+
+    #[test]
+    fn test_profiler() {
+        use crate::profile;
+        use crate::profiler::end_frame;
+
+        fn step() {
+            profile!();
+            sleep(Duration::from_millis(2));
+        }
+
+        std::thread::Builder::new().name("profiled".to_string()).spawn(|| {
+            profile!("frame");
+            for _ in 0..3 {
+                step();
+            }
+            {
+                profile!("render");
+                sleep(Duration::from_millis(1));
+            }
+        }).unwrap().join().unwrap();
+
+        let report = end_frame();
+        let frame = report.find(&["frame"]).unwrap();
+        let step = frame.children.iter().find(|n| n.name.ends_with("test_profiler::step")).unwrap();
+        assert_eq!(step.calls, 3);
+        assert!(step.inclusive >= Duration::from_millis(6));
+        assert!(report.find(&["frame", "render"]).is_some());
+        assert!(frame.exclusive < frame.inclusive - step.inclusive + Duration::from_millis(1));
+
+        let thread = report.threads.iter().find(|t| t.thread == "profiled").unwrap();
+        assert_eq!(thread.events.len(), 5);
+        let trace = crate::json::Json::parse(&report.to_chrome_trace()).unwrap();
+        assert!(trace.as_array().unwrap().iter().any(|e| e["name"].as_str() == Some("render") && e["ph"].as_str() == Some("X")));
+        assert!(report.to_string().contains("  frame x1 "));
+
+        let mut buffer = ByteBuffer::new();
+        report.save(&mut buffer);
+        assert_eq!(crate::profiler::FrameReport::load(&mut buffer).unwrap(), report);
+    }
+
+    #[cfg(feature = "tracking_alloc")]
+    #[global_allocator]
+    static ALLOCATOR: crate::alloc::tracking::TrackingAllocator = crate::alloc::tracking::TrackingAllocator::new();
+
+    #[test]
+    #[cfg(feature = "tracking_alloc")]
+    fn test_tracking_alloc() {
+        use crate::alloc::tracking::{snapshot, tag, tagged};
+
+        let before = snapshot();
+        let kept = tagged("test_assets", || {
+            let mut v = Vec::<u8>::with_capacity(1000);
+            v.extend_from_slice(&[1; 1000]);
+            v.reserve(3000);
+            let _temp = vec![0u64; 100];
+            v
+        });
+
+        let snap = snapshot();
+        let assets = snap.tag("test_assets").unwrap();
+        assert_eq!(assets.live_bytes, kept.capacity() as u64);
+        assert_eq!(assets.live_allocations, 1);
+        assert_eq!(assets.total_allocations, 2);
+        assert!(assets.peak_bytes >= kept.capacity() as u64 + 800);
+        assert!(snap.allocations_since(&before) >= 2);
+
+        let other = std::thread::spawn(move || {
+            let _guard = tag("test_other");
+            drop(kept);
+        });
+        other.join().unwrap();
+        let snap = snapshot();
+        assert_eq!(snap.tag("test_assets").unwrap().live_bytes, 0);
+        assert_eq!(snap.tag("test_other").unwrap().live_allocations, 0);
+    }
+
+    #[test]
+    fn test_crash_reporter() {
+        use crate::crash::{list_dumps, CrashReport, CrashReporter};
+        use std::sync::mpsc;
+
+        let dir = std::env::temp_dir().join(format!("mvutils_crash_test_{}", std::process::id()));
+        let (sender, receiver) = mpsc::channel();
+        let sender = parking_lot::Mutex::new(sender);
+        let state = State::new(42);
+        let context = state.clone();
+
+        CrashReporter::new()
+            .print(false)
+            .dump_dir(&dir)
+            .context("state", move || format!("{}", *context.read()))
+            .on_report(move |report| {
+                let _ = sender.lock().send(report.clone());
+            })
+            .install();
+        let result = std::thread::Builder::new().name("worker".to_string()).spawn(|| {
+            panic!("Something went wrong: {}", 5);
+        }).unwrap().join();
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+
+        let report = receiver.iter().find(|r| r.thread == "worker").unwrap();
+        assert_eq!(report.message, "Something went wrong: 5");
+        assert!(report.location.as_ref().unwrap().contains("lib.rs"));
+        assert_eq!(report.context, vec![("state".to_string(), "42".to_string())]);
+        assert!(!report.backtrace.is_empty());
+
+        let dumps = list_dumps(&dir);
+        assert!(dumps.iter().any(|path| CrashReport::load_dump(path).unwrap() == report));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recover_ext() {
+        use crate::utils::{IgnorePoison, RecoverExt};
+        use std::sync::Arc;
+
+        assert_eq!("5".parse::<u32>().ok_or_log(), Some(5));
+        assert_eq!("x".parse::<u32>().ok_or_log(), None);
+        assert_eq!("x".parse::<u32>().unwrap_or_default_log(), 0);
+        assert_eq!(Err::<usize, _>("error").recover_with(|e| e.len()), 5);
+
+        let lock = Arc::new(std::sync::Mutex::new(1));
+        let poisoner = lock.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison");
+        }).join();
+        assert!(lock.is_poisoned());
+        *lock.lock().ignore_poison() += 1;
+        assert_eq!(*lock.lock().ignore_poison(), 2);
+
+        let lock = parking_lot::RwLock::new(1);
+        *lock.write().ignore_poison() += 1;
+        assert_eq!(*lock.read().ignore_poison(), 2);
+    }
+
+    #[test]
+    fn test_retry() {
+        use crate::retry::{retry, retry_cancellable, retry_if, RetryPolicy};
+        use crate::thread::CancellationToken;
+        use std::time::Instant;
+
+        let policy = RetryPolicy::exponential(Duration::from_millis(10), Duration::from_millis(25));
+        assert_eq!(policy.base_delay(1), Duration::from_millis(10));
+        assert_eq!(policy.base_delay(2), Duration::from_millis(20));
+        assert_eq!(policy.base_delay(3), Duration::from_millis(25));
+        assert_eq!(policy.base_delay(100), Duration::from_millis(25));
+        for _ in 0..10 {
+            let delay = policy.delay(2);
+            assert!(delay >= Duration::from_millis(10) && delay <= Duration::from_millis(20));
+        }
+
+        let policy = RetryPolicy::fixed(Duration::from_millis(1)).max_attempts(Some(3));
+        let mut calls = 0;
+        assert_eq!(retry(&policy, || { calls += 1; if calls < 3 { Err(calls) } else { Ok(calls) } }), Ok(3));
+        calls = 0;
+        assert_eq!(retry(&policy, || { calls += 1; Err::<(), _>(calls) }), Err(3));
+        calls = 0;
+        assert_eq!(retry_if(&policy, || { calls += 1; Err::<(), _>(calls) }, |e| *e < 2), Err(2));
+
+        let policy = RetryPolicy::fixed(Duration::from_millis(5)).max_attempts(None).max_elapsed(Some(Duration::from_millis(30)));
+        let start = Instant::now();
+        assert!(retry(&policy, || Err::<(), _>(())).is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            sleep(Duration::from_millis(20));
+            canceller.cancel();
+        });
+        let policy = RetryPolicy::fixed(Duration::from_secs(10)).max_attempts(None);
+        let start = Instant::now();
+        assert_eq!(retry_cancellable(&policy, &token, || Err::<(), _>("failed"), |_| true), Err("failed"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_expr() {
+        use crate::expr::{eval, Expr, Value};
+        use std::collections::HashMap;
+
+        let mut vars = HashMap::new();
+        vars.insert("x".to_string(), Value::Num(4.0));
+        vars.insert("player.alive".to_string(), Value::Bool(true));
+
+        let expr = Expr::parse("2 * (x + 1) >= 10").unwrap();
+        assert_eq!(expr.variables(), vec!["x"]);
+        assert_eq!(expr.eval_bool(&vars), Ok(true));
+        assert_eq!(eval("1 + 2 * 3 - 4 / 2", &vars), Ok(Value::Num(5.0)));
+        assert_eq!(eval("-2 ^ 2 + 2 ^ 3 ^ 2", &vars), Ok(Value::Num(508.0)));
+        assert_eq!(eval("max(x, 10, 2) % 3 + clamp(x, 0, 1)", &vars), Ok(Value::Num(2.0)));
+        assert_eq!(eval("!player.alive || x / 0 > 1 && x != 4", &vars), Ok(Value::Bool(false)));
+        assert_eq!(eval("false && missing", &vars), Ok(Value::Bool(false)));
+        assert_eq!(eval("true == (x == 4)", &vars), Ok(Value::Bool(true)));
+
+        assert!(eval("missing + 1", &vars).is_err());
+        assert!(eval("x + true", &vars).is_err());
+        assert!(eval("1 == true", &vars).is_err());
+        assert!(Expr::parse("(1 + 2").is_err());
+        assert!(Expr::parse("1 2").is_err());
+        assert!(Expr::parse("1 = 2").is_err());
+        assert!(eval("foo(1)", &vars).is_err());
+    }
+
+    #[test]
+    fn test_template() {
+        use crate::template::{MissingKey, Template};
+        use std::collections::HashMap;
+
+        let template = Template::parse("[${level}] ${name:anonymous} paid $$${amount} ${missing}$").unwrap();
+        assert_eq!(template.placeholders(), vec!["level", "name", "amount", "missing"]);
+
+        let mut values = HashMap::new();
+        values.insert("level", "INFO".to_string());
+        values.insert("amount", 5.to_string());
+        assert!(template.render(&values).is_err());
+
+        let template = template.missing_keys(MissingKey::Keep);
+        assert_eq!(template.render(&values).unwrap(), "[INFO] anonymous paid $5 ${missing}$");
+        let template = template.missing_keys(MissingKey::Default("?".to_string()));
+        assert_eq!(template.render(&values).unwrap(), "[INFO] anonymous paid $5 ?$");
+        assert_eq!(
+            template.render_with(|key| Some(key.len())).unwrap(),
+            "[5] 4 paid $6 7$"
+        );
+
+        assert!(Template::parse("${unterminated").is_err());
+        assert!(Template::parse("${}").is_err());
+    }
+
+    #[test]
+    fn test_i18n() {
+        use crate::i18n::{Catalog, I18n, PluralForm, PluralRule};
+
+        let en = Catalog::parse("en", PluralRule::OneOther, "greeting = Hello, ${name}!\napples.one = one apple\napples.other = ${count} apples\ncoin = coin\n").unwrap();
+        let mut ru = Catalog::new("ru", PluralRule::Slavic);
+        ru.insert("greeting", "Привет, ${name}!");
+        ru.insert_plural("apples", PluralForm::One, "${count} яблоко");
+        ru.insert_plural("apples", PluralForm::Few, "${count} яблока");
+        ru.insert_plural("apples", PluralForm::Many, "${count} яблок");
+
+        let i18n = I18n::new("en");
+        i18n.add(en.clone());
+        i18n.add(ru);
+
+        assert_eq!(i18n.tr("greeting", &[("name", &"Max")]), "Hello, Max!");
+        assert_eq!(i18n.tr_plural("apples", 1, &[]), "one apple");
+        assert_eq!(i18n.tr_plural("apples", 3, &[]), "3 apples");
+        assert_eq!(i18n.tr_plural("coin", 2, &[]), "coins");
+        assert_eq!(i18n.tr("unknown.key", &[]), "unknown.key");
+
+        i18n.set_locale("ru");
+        assert_eq!(i18n.tr("greeting", &[("name", &"Max")]), "Привет, Max!");
+        assert_eq!(i18n.tr_plural("apples", 21, &[]), "21 яблоко");
+        assert_eq!(i18n.tr_plural("apples", 3, &[]), "3 яблока");
+        assert_eq!(i18n.tr_plural("apples", 11, &[]), "11 яблок");
+        assert_eq!(i18n.tr_plural("coin", 1, &[]), "coin");
+
+        let mut buffer = ByteBuffer::new();
+        en.save(&mut buffer);
+        assert_eq!(Catalog::load(&mut buffer).unwrap(), en);
+
+        I18n::global().add(en);
+        assert_eq!(crate::tr!("greeting", name = "You"), "Hello, You!");
+        assert_eq!(crate::tr!("apples", count = 2), "2 apples");
+    }
+
+    #[test]
+    fn test_color() {
+        use crate::color::Color;
+        use crate::print::{Col, Printer};
+
+        assert_eq!(Color::parse("#ff8000").unwrap(), Color::rgb(255, 128, 0));
+        assert_eq!(Color::parse("#F80").unwrap(), Color::rgb(255, 136, 0));
+        assert_eq!(Color::parse("#00000080").unwrap(), Color::rgba(0, 0, 0, 128));
+        assert_eq!(Color::parse("rgb(1, 2, 3)").unwrap(), Color::rgb(1, 2, 3));
+        assert_eq!(Color::parse("rgba(1, 2, 3, 0.5)").unwrap(), Color::rgba(1, 2, 3, 128));
+        assert_eq!(Color::parse("Orange").unwrap(), Color::rgb(255, 165, 0));
+        assert!(Color::parse("#12345").is_err());
+        assert!(Color::parse("rgb(256, 0, 0)").is_err());
+        assert!(Color::try_from("notacolor".to_string()).is_err());
+
+        let color = Color::rgb(200, 100, 50);
+        let (h, s, v) = color.to_hsv();
+        assert_eq!(Color::from_hsv(h, s, v), color);
+        let (h, s, l) = color.to_hsl();
+        assert_eq!(Color::from_hsl(h, s, l), color);
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 0.5), Color::rgb(128, 128, 128));
+        assert_eq!(color.to_string(), "#c86432");
+        assert_eq!(color.to_string().parse::<Color>().unwrap(), color);
+
+        let mut buffer = ByteBuffer::new();
+        color.save(&mut buffer);
+        assert_eq!(Color::load(&mut buffer).unwrap(), color);
+
+        let printed = Printer::start().col_for(color, "x").bg(Col::Black).to_string();
+        assert!(printed.contains("\x1b[38;2;200;100;50m"));
+    }
+
+    #[test]
+    fn test_geometry() {
+        use crate::geometry::{Extent, Rect, Vec2, Vec3};
+
+        let a = Vec2::new(1.0, 2.0);
+        let b = Vec2::new(3.0, 4.0);
+        assert_eq!(a + b, Vec2::new(4.0, 6.0));
+        assert_eq!(b - a, Vec2::splat(2.0));
+        assert_eq!(a * 2.0, 2.0 * a);
+        assert_eq!(a.dot(b), 11.0);
+        assert_eq!(Vec2::new(3.0, 4.0).length(), 5.0);
+        assert_eq!(a.lerp(b, 0.5), Vec2::new(2.0, 3.0));
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0).cross(Vec3::new(0.0, 1.0, 0.0)), Vec3::new(0.0, 0.0, 1.0));
+
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(rect.contains(Vec2::new(5.0, 5.0)));
+        assert!(!rect.contains(Vec2::new(10.0, 5.0)));
+        assert!(rect.contains_rect(Rect::new(2.0, 2.0, 8.0, 8.0)));
+        assert_eq!(rect.intersection(Rect::new(5.0, 5.0, 10.0, 10.0)), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+        assert!(!rect.intersects(Rect::new(10.0, 0.0, 5.0, 5.0)));
+        assert_eq!(rect.union(Rect::new(5.0, -5.0, 10.0, 10.0)), Rect::new(0.0, -5.0, 15.0, 15.0));
+        assert_eq!(Rect::from_points(b, a), Rect::from_pos_extent(a, Extent::new(2.0, 2.0)));
+        assert_eq!(rect.center(), Vec2::splat(5.0));
+
+        let mut buffer = ByteBuffer::new();
+        rect.save(&mut buffer);
+        a.save(&mut buffer);
+        assert_eq!(Rect::load(&mut buffer).unwrap(), rect);
+        assert_eq!(Vec2::load(&mut buffer).unwrap(), a);
+        assert_eq!(Rect::FIXED_SIZE, Some(16));
+    }
+
+    #[test]
+    fn test_screen_length() {
+        use crate::screen::{Length, Measurement, DEFAULT_DPI};
+
+        assert_eq!(Length::parse("12px").unwrap(), Length::px(12.0));
+        assert_eq!(Length::parse("2.5cm").unwrap(), Length::cm(2.5));
+        assert_eq!(Length::parse(" 50 %").unwrap(), Length::percent(50.0));
+        assert_eq!(Length::parse("-3").unwrap(), Length::px(-3.0));
+        assert_eq!(Length::parse("1IN").unwrap(), Length::inches(1.0));
+        assert!(Length::parse("12parsecs").is_err());
+        assert!(Length::parse("cm").is_err());
+
+        assert_eq!(Length::inches(1.0).to_px(DEFAULT_DPI, 0.0), 96.0);
+        assert_eq!(Length::pt(72.0).to_px(144.0, 0.0), 144.0);
+        assert_eq!(Length::percent(25.0).to_px(DEFAULT_DPI, 200.0), 50.0);
+        assert_eq!(Length::inches(1.0).add(Length::px(48.0), DEFAULT_DPI, 0.0), Length::inches(1.5));
+        assert_eq!(Length::px(96.0).to(Measurement::In, DEFAULT_DPI, 0.0), Length::inches(1.0));
+        assert_eq!(Length::percent(10.0).max(Length::px(30.0), DEFAULT_DPI, 500.0), Length::percent(10.0));
+        assert_eq!(Length::cm(2.5).to_string(), "2.5cm");
+
+        let mut buffer = ByteBuffer::new();
+        Length::percent(50.0).save(&mut buffer);
+        assert_eq!(Length::load(&mut buffer).unwrap(), Length::percent(50.0));
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        use crate::hashers::U64IdentityHasher;
+        use crate::rate_limit::{KeyedRateLimiter, RateLimit, RateLimiter};
+        use std::time::{Duration, Instant};
+
+        let bucket = RateLimiter::token_bucket(3, 100.0);
+        assert!(bucket.try_acquire_n(3));
+        assert!(!bucket.try_acquire());
+        assert!(!bucket.try_acquire_n(4));
+        assert!(bucket.time_until_available(1) > Duration::ZERO);
+        let start = Instant::now();
+        bucket.acquire_blocking_n(2);
+        assert!(start.elapsed() >= Duration::from_millis(15));
+
+        let quota = RateLimiter::token_bucket(5, 0.0);
+        assert!(quota.try_acquire_n(5));
+        assert!(!quota.try_acquire());
+        assert_eq!(quota.time_until_available(1), Duration::MAX);
+        assert_eq!(quota.time_until_available(0), Duration::ZERO);
+        assert!(!quota.acquire_timeout(1, Duration::from_millis(5)));
+        assert!(std::panic::catch_unwind(|| RateLimiter::token_bucket(5, -1.0)).is_err());
+        assert!(std::panic::catch_unwind(|| RateLimiter::token_bucket(5, f64::NAN)).is_err());
+
+        let window = RateLimiter::sliding_window(2, Duration::from_millis(50));
+        assert!(window.try_acquire());
+        assert!(window.try_acquire());
+        assert!(!window.try_acquire());
+        assert!(!window.acquire_timeout(1, Duration::from_millis(5)));
+        assert!(window.acquire_timeout(1, Duration::from_millis(200)));
+        assert!(!window.try_acquire_n(3));
+
+        let keyed = KeyedRateLimiter::with_hasher(RateLimit::SlidingWindow { max: 1, window: Duration::from_millis(20) }, U64IdentityHasher::default());
+        assert!(keyed.try_acquire(&1u64));
+        assert!(!keyed.try_acquire(&1u64));
+        assert!(keyed.try_acquire(&2u64));
+        assert_eq!(keyed.len(), 2);
+        std::thread::sleep(Duration::from_millis(25));
+        keyed.cleanup();
+        assert!(keyed.is_empty());
+
+        #[cfg(feature = "async")]
+        {
+            use std::future::Future;
+            use std::sync::Arc;
+            use std::task::{Context, Poll, Wake};
+
+            struct ThreadWaker(std::thread::Thread);
+
+            impl Wake for ThreadWaker {
+                fn wake(self: Arc<Self>) {
+                    self.0.unpark();
+                }
+            }
+
+            let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+            let limiter = RateLimiter::token_bucket(1, 100.0);
+            limiter.acquire_blocking();
+            let mut future = std::pin::pin!(limiter.acquire_async(1));
+            while future.as_mut().poll(&mut cx) == Poll::Pending {
+                std::thread::park();
+            }
+        }
+    }
+
+    #[test]
+    fn test_caches() {
+        use crate::cache::{LruCache, TtlCache};
+        use crate::hashers::U64IdentityHasher;
+        use std::time::Duration;
+
+        let mut lru = LruCache::new(3);
+        lru.put("a", 1);
+        lru.put("b", 2);
+        lru.put("c", 3);
+        assert_eq!(lru.get("a"), Some(&1));
+        lru.put("d", 4);
+        assert!(!lru.contains("b"));
+        assert_eq!(lru.iter().map(|(k, _)| *k).collect::<Vec<_>>(), ["d", "a", "c"]);
+        assert_eq!(lru.put("c", 30), Some(3));
+        assert_eq!(lru.remove("a"), Some(1));
+        assert_eq!(lru.pop_lru(), Some(("d", 4)));
+        assert_eq!(*lru.get_or_insert_with("e", || 5), 5);
+        assert_eq!(lru.get("b"), None);
+        assert_eq!(lru.stats().hits, 1);
+        assert_eq!(lru.stats().misses, 2);
+        assert_eq!(lru.stats().evictions, 1);
+
+        let mut ids = LruCache::with_hasher(2, U64IdentityHasher::default());
+        for i in 0..10u64 {
+            ids.put(i, i.to_string());
+        }
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids.peek(&9), Some(&"9".to_string()));
+        ids.resize(1);
+        assert!(!ids.contains(&8));
+
+        let mut buffer = ByteBuffer::new();
+        ids.save(&mut buffer);
+        let mut loaded = LruCache::<u64, String, U64IdentityHasher>::load(&mut buffer).unwrap();
+        assert_eq!(loaded.get(&9), Some(&"9".to_string()));
+
+        let mut buffer = ByteBuffer::new();
+        buffer.push_u64(u64::MAX);
+        buffer.push_u64(u64::MAX);
+        assert!(LruCache::<u64, u64>::load(&mut buffer).is_err());
+
+        let mut ttl = TtlCache::new(Duration::from_millis(30));
+        ttl.insert("short".to_string(), 1);
+        ttl.insert_with_ttl("long".to_string(), 2, Duration::from_secs(60));
+        assert_eq!(ttl.get("short"), Some(&1));
+        let mut buffer = ByteBuffer::new();
+        ttl.save(&mut buffer);
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(ttl.get("short"), None);
+        assert!(ttl.contains("long"));
+        assert_eq!(ttl.purge_expired(), 0);
+        assert_eq!(ttl.stats().expirations, 1);
+        let mut loaded = TtlCache::<String, i32>::load(&mut buffer).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.purge_expired(), 1);
+        assert_eq!(loaded.get("long"), Some(&2));
+    }
+
+    #[test]
+    fn test_memo() {
+        use crate::memo::Memo;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let memo = Arc::new(Memo::with_capacity(2, move |x: &u32| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            x * 2
+        }));
+
+        let handles = (0..4).map(|_| {
+            let memo = memo.clone();
+            std::thread::spawn(move || memo.get(21))
+        }).collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 42);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        memo.get(1);
+        memo.get(2);
+        assert!(!memo.contains(&21));
+        assert_eq!(memo.peek(&2), Some(4));
+        memo.invalidate(&2);
+        assert_eq!(memo.get(2), 4);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(memo.stats().evictions, 1);
+
+        crate::memoize! {
+            fn collatz(n: u64) -> u32 {
+                match n {
+                    1 => 0,
+                    n if n % 2 == 0 => collatz(n / 2) + 1,
+                    n => collatz(3 * n + 1) + 1,
+                }
+            }
+
+            #[capacity(4)]
+            fn join(a: String, b: &'static str) -> String {
+                format!("{a}{b}")
+            }
+        }
+
+        assert_eq!(collatz(27), 111);
+        assert_eq!(join("a".to_string(), "b"), "ab");
+    }
+
+    #[test]
+    fn test_task_graph() {
+        use crate::task_graph::{TaskGraph, TaskStatus};
+        use crate::thread::CancellationToken;
+        use parking_lot::Mutex;
+
+        let log = Mutex::new(Vec::new());
+        let mut graph = TaskGraph::new().threads(4);
+        let a = graph.add("a", || {
+            log.lock().push("a");
+            Ok(())
+        });
+        let b = graph.add_after("b", &[a], || {
+            log.lock().push("b");
+            Ok(())
+        });
+        let c = graph.add_after("c", &[a], || {
+            log.lock().push("c");
+            Ok(())
+        });
+        graph.add_after("d", &[b, c], || {
+            log.lock().push("d");
+            Ok(())
+        });
+        let report = graph.execute().unwrap();
+        assert!(report.is_success());
+        let log = log.into_inner();
+        assert_eq!(log.len(), 4);
+        assert_eq!(log[0], "a");
+        assert_eq!(log[3], "d");
+
+        let mut graph = TaskGraph::new();
+        let fail = graph.add("fail", || Err("broken".to_string()));
+        let after = graph.add_after("after", &[fail], || Ok(()));
+        let last = graph.add_after("last", &[after], || Ok(()));
+        let other = graph.add("other", || Ok(()));
+        let boom = graph.add("boom", || panic!("boom"));
+        let report = graph.execute().unwrap();
+        assert_eq!(report.status(fail), &TaskStatus::Failed("broken".to_string()));
+        assert_eq!(report.status(after), &TaskStatus::Skipped);
+        assert_eq!(report.status(last), &TaskStatus::Skipped);
+        assert_eq!(report.status(other), &TaskStatus::Succeeded);
+        assert_eq!(report.status(boom), &TaskStatus::Panicked("boom".to_string()));
+        assert_eq!(report.failures().count(), 4);
+
+        let mut graph = TaskGraph::new();
+        let x = graph.add("x", || Ok(()));
+        let y = graph.add_after("y", &[x], || Ok(()));
+        graph.depend(x, y);
+        assert!(graph.validate().unwrap_err().contains("x, y"));
+
+        let token = CancellationToken::new();
+        let mut graph = TaskGraph::new().threads(1);
+        let first = graph.add("first", || {
+            token.cancel();
+            Ok(())
+        });
+        let second = graph.add_after("second", &[first], || Ok(()));
+        let report = graph.execute_cancellable(&token.clone()).unwrap();
+        assert_eq!(report.status(first), &TaskStatus::Succeeded);
+        assert_eq!(report.status(second), &TaskStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_job_scheduler() {
+        use crate::scheduler::{JobScheduler, Priority};
+        use std::sync::{Arc, Mutex};
+        use std::time::{Duration, Instant};
+
+        let scheduler = Arc::new(JobScheduler::new());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let push = |name: &'static str| {
+            let log = log.clone();
+            move || log.lock().unwrap().push(name)
+        };
+
+        scheduler.submit(Priority::Low, push("low"));
+        scheduler.submit(Priority::Normal, push("normal"));
+        scheduler.submit_with_deadline(Priority::Normal, Instant::now() + Duration::from_secs(60), push("deadline"));
+        scheduler.submit(Priority::Critical, push("critical"));
+        let cancelled = scheduler.submit(Priority::High, push("cancelled"));
+        scheduler.submit_with_deadline(Priority::High, Instant::now(), push("expired"));
+        assert!(scheduler.cancel(cancelled));
+        std::thread::sleep(Duration::from_millis(1));
+
+        let stats = scheduler.run_all();
+        assert_eq!(stats.executed, 4);
+        assert_eq!(stats.expired, 1);
+        assert_eq!(*log.lock().unwrap(), ["critical", "deadline", "normal", "low"]);
+
+        for _ in 0..10 {
+            scheduler.submit(Priority::Normal, || std::thread::sleep(Duration::from_millis(5)));
+        }
+        let stats = scheduler.run_for(Duration::from_millis(12));
+        assert!(stats.executed >= 1 && stats.executed <= 3);
+        assert_eq!(stats.remaining, 10 - stats.executed);
+        assert!(scheduler.average_job_time().unwrap() >= Duration::from_micros(500));
+        scheduler.clear();
+        assert!(scheduler.is_empty());
+
+        let inner = scheduler.clone();
+        scheduler.submit(Priority::Normal, move || {
+            inner.submit(Priority::Normal, || {});
+        });
+        assert_eq!(scheduler.run_all().executed, 2);
+    }
+
+    #[test]
+    fn test_replay() {
+        use crate::replay::{EventKind, Recorder, Replay};
+        use crate::state::State;
+        use std::time::Duration;
+
+        let recorder = Recorder::new();
+        let position = recorder.track(State::new((0i32, 0i32)));
+        let name = recorder.track(State::new("player".to_string()));
+        let input = recorder.channel();
+
+        for step in 1..=3 {
+            recorder.command(input, &format!("move {step}"));
+            let mut pos = position.write();
+            pos.0 += step;
+            pos.1 -= 1;
+        }
+        name.write().push_str(" one");
+        *position.state().write() = (100, 100);
+
+        let mut buffer = ByteBuffer::new();
+        recorder.finish().save(&mut buffer);
+        let replay = Replay::load(&mut buffer).unwrap();
+
+        let fresh = replay.fresh_state::<(i32, i32)>(position.channel()).unwrap();
+        assert_eq!(*fresh.read(), (0, 0));
+        assert_eq!(replay.apply(position.channel(), &fresh).unwrap(), 3);
+        assert_eq!(*fresh.read(), (6, -3));
+        assert_eq!(fresh.get_version(), 3);
+
+        let fresh_name = replay.fresh_state::<String>(name.channel()).unwrap();
+        replay.apply(name.channel(), &fresh_name).unwrap();
+        assert_eq!(*fresh_name.read(), "player one");
+
+        let diverged = State::new((0, 0));
+        *diverged.write() = (1, 1);
+        assert!(replay.apply(position.channel(), &diverged).is_err());
+
+        let commands = replay.commands::<String>(input).unwrap();
+        assert_eq!(commands.iter().map(|(_, c)| c.as_str()).collect::<Vec<_>>(), ["move 1", "move 2", "move 3"]);
+
+        let mut player = replay.player();
+        let events = player.advance(replay.duration());
+        assert_eq!(events.len(), 9);
+        assert_eq!(events[0].kind, EventKind::Initial);
+        assert!(player.is_finished());
+        assert!(player.advance(Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn test_derive_lerp() {
+        use crate as mvutils;
+        use crate::geometry::Vec2;
+        use crate::lerp::Lerp;
+        use mvutils_proc_macro::Lerp;
+
+        #[derive(Lerp, Clone, Debug, PartialEq)]
+        struct Transform {
+            position: Vec2,
+            rotation: f32,
+            #[lerp(round)]
+            frame: f32,
+            health: u32,
+            #[lerp(skip)]
+            name: String,
+        }
+
+        #[derive(Lerp, Debug, PartialEq)]
+        struct Pair(f64, [u8; 2]);
+
+        let a = Transform {
+            position: Vec2::new(0.0, 0.0),
+            rotation: 0.0,
+            frame: 0.0,
+            health: 100,
+            name: "a".to_string(),
+        };
+        let b = Transform {
+            position: Vec2::new(10.0, 20.0),
+            rotation: 90.0,
+            frame: 3.0,
+            health: 51,
+            name: "b".to_string(),
+        };
+        let mid = a.lerp(&b, 0.5);
+        assert_eq!(mid.position, Vec2::new(5.0, 10.0));
+        assert_eq!(mid.rotation, 45.0);
+        assert_eq!(mid.frame, 2.0);
+        assert_eq!(mid.health, 76);
+        assert_eq!(mid.name, "a");
+        assert_eq!(a.lerp(&b, 1.0), Transform { name: "a".to_string(), ..b.clone() });
+
+        assert_eq!(Pair(0.0, [0, 255]).lerp(&Pair(1.0, [255, 0]), 0.25), Pair(0.25, [64, 191]));
+        assert_eq!(crate::lerp::lerp(&Some(1.0f32), &None, 0.75), None);
+    }
+
+    #[test]
+    fn test_undo_stack() {
+        use crate::undo::{Command, UndoStack};
+
+        #[derive(Savable, Debug, PartialEq)]
+        enum Edit {
+            Insert(u32, String),
+            Delete(u32, String),
+        }
+
+        impl Command for Edit {
+            type Target = String;
+
+            fn execute(&mut self, target: &mut String) {
+                match self {
+                    Edit::Insert(at, text) => target.insert_str(*at as usize, text),
+                    Edit::Delete(at, text) => target.replace_range(*at as usize..*at as usize + text.len(), ""),
+                }
+            }
+
+            fn undo(&mut self, target: &mut String) {
+                match self {
+                    Edit::Insert(at, text) => target.replace_range(*at as usize..*at as usize + text.len(), ""),
+                    Edit::Delete(at, text) => target.insert_str(*at as usize, text),
+                }
+            }
+
+            fn merge(&mut self, next: &Self) -> bool {
+                match (self, next) {
+                    (Edit::Insert(at, text), Edit::Insert(next_at, next_text)) if *at as usize + text.len() == *next_at as usize => {
+                        text.push_str(next_text);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        }
+
+        let mut doc = String::new();
+        let mut stack = UndoStack::with_capacity(3);
+        assert!(!stack.is_dirty());
+        stack.execute(Edit::Insert(0, "Hel".to_string()), &mut doc);
+        stack.execute(Edit::Insert(3, "lo".to_string()), &mut doc);
+        assert_eq!(stack.undo_len(), 1);
+        stack.break_merge();
+        stack.execute(Edit::Insert(5, " World".to_string()), &mut doc);
+        assert_eq!(doc, "Hello World");
+        assert!(stack.is_dirty());
+        stack.mark_clean();
+
+        stack.execute(Edit::Delete(0, "Hello ".to_string()), &mut doc);
+        assert_eq!(doc, "World");
+        assert!(stack.undo(&mut doc));
+        assert_eq!(doc, "Hello World");
+        assert!(!stack.is_dirty());
+        assert!(stack.redo(&mut doc));
+        assert!(!stack.redo(&mut doc));
+        assert_eq!(doc, "World");
+
+        let mut buffer = ByteBuffer::new();
+        stack.save(&mut buffer);
+        let mut loaded = UndoStack::<Edit>::load(&mut buffer).unwrap();
+        let mut copy = doc.clone();
+        assert!(loaded.is_dirty());
+        assert!(loaded.undo(&mut copy));
+        assert_eq!(copy, "Hello World");
+        assert!(!loaded.is_dirty());
+
+        stack.execute(Edit::Insert(5, "!".to_string()), &mut doc);
+        stack.execute(Edit::Insert(0, ">".to_string()), &mut doc);
+        assert_eq!(stack.undo_len(), 3);
+        assert_eq!(doc, ">World!");
+        while stack.undo(&mut doc) {}
+        assert_eq!(doc, "Hello World");
+        assert!(!stack.is_dirty());
+    }
+
+    #[test]
+    fn test_fsm() {
+        use crate::fsm::Fsm;
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Savable, Clone, Debug, PartialEq)]
+        enum Ai {
+            Idle,
+            Chase,
+            Attack,
+            Dead,
+        }
+
+        #[try_from_string]
+        #[derive(Debug, PartialEq)]
+        enum Signal {
+            SeePlayer,
+            InRange,
+            LostPlayer,
+            Killed,
+        }
+
+        let entered = Arc::new(AtomicU32::new(0));
+        let counter = entered.clone();
+        let ammo = Arc::new(AtomicU32::new(1));
+        let guard_ammo = ammo.clone();
+        let mut fsm = Fsm::builder(Ai::Idle)
+            .transition(Ai::Idle, Signal::SeePlayer, Ai::Chase)
+            .guarded(Ai::Chase, Signal::InRange, Ai::Attack, move |_, _| guard_ammo.load(Ordering::SeqCst) > 0)
+            .transition(Ai::Chase, Signal::LostPlayer, Ai::Idle)
+            .transition(Ai::Attack, Signal::LostPlayer, Ai::Idle)
+            .transition_any(Signal::Killed, Ai::Dead)
+            .on_enter(Ai::Attack, move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .build();
+
+        assert!(!fsm.fire(&Signal::InRange));
+        assert!(fsm.fire_str("see_player").unwrap());
+        assert!(fsm.fire_str("Unknown").is_err());
+        assert_eq!(fsm.available_events(), [&Signal::InRange, &Signal::LostPlayer, &Signal::Killed]);
+        ammo.store(0, Ordering::SeqCst);
+        assert!(!fsm.can_fire(&Signal::InRange));
+        ammo.store(1, Ordering::SeqCst);
+        assert!(fsm.fire(&Signal::InRange));
+        assert!(fsm.is(&Ai::Attack));
+        assert_eq!(entered.load(Ordering::SeqCst), 1);
+
+        let mut buffer = ByteBuffer::new();
+        fsm.save_state(&mut buffer);
+        assert!(fsm.fire(&Signal::Killed));
+        assert_eq!(fsm.state(), &Ai::Dead);
+        fsm.load_state(&mut buffer).unwrap();
+        assert_eq!(fsm.state(), &Ai::Attack);
+        assert_eq!(entered.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_tween_timeline() {
+        use crate::tween::{Easing, Parallel, Sequence, Timeline, Tween};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+        assert_eq!(Easing::BounceOut.apply(1.0), 1.0);
+        assert_eq!(Easing::Step.apply(0.99), 0.0);
+
+        let mut tween = Tween::new(0u8, 100u8, Duration::from_millis(100));
+        assert_eq!(tween.tick(Duration::from_millis(50)), 50);
+        assert_eq!(tween.tick(Duration::from_millis(100)), 100);
+        assert!(tween.is_finished());
+
+        let x = Arc::new(Mutex::new(0.0f32));
+        let y = Arc::new(Mutex::new(0.0f32));
+        let calls = Arc::new(AtomicU32::new(0));
+        let (tx, ty, tc) = (x.clone(), y.clone(), calls.clone());
+        let mut timeline = Timeline::new(
+            Sequence::new()
+                .then(Parallel::new()
+                    .with(Tween::new(0.0, 10.0, Duration::from_millis(100)).on_update(move |v| *tx.lock().unwrap() = v))
+                    .with(Tween::new(0.0, 20.0, Duration::from_millis(200)).on_update(move |v| *ty.lock().unwrap() = v)))
+                .call(move || { tc.fetch_add(1, Ordering::SeqCst); }),
+        ).repeat(Some(1));
+        assert_eq!(timeline.duration(), Duration::from_millis(200));
+
+        assert!(timeline.tick(Duration::from_millis(150)));
+        assert_eq!(*x.lock().unwrap(), 10.0);
+        assert_eq!(*y.lock().unwrap(), 15.0);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        timeline.pause();
+        timeline.tick(Duration::from_millis(500));
+        assert_eq!(timeline.elapsed(), Duration::from_millis(150));
+        timeline.resume();
+
+        assert!(timeline.tick(Duration::from_millis(100)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(timeline.iteration(), 1);
+        assert_eq!(*x.lock().unwrap(), 5.0);
+
+        assert!(!timeline.tick(Duration::from_millis(1000)));
+        assert!(timeline.is_finished());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(*y.lock().unwrap(), 20.0);
+    }
+
+    #[test]
+    fn test_derive_accessors() {
+        #[derive(crate::Getters, crate::Setters, Default)]
+        struct Player<T> {
+            name: String,
+            #[getter(copy)]
+            health: u32,
+            #[getter(vis = pub(crate))]
+            #[setter(skip)]
+            id: u64,
+            #[getter(skip)]
+            #[setter(skip)]
+            #[allow(dead_code)]
+            secret: T,
+        }
+
+        let mut player = Player::<u8>::default();
+        player.set_name("Steve".to_string());
+        player.set_health(20);
+        *player.id_mut() = 7;
+        player.name_mut().push('!');
+        *player.health_mut() -= 5;
+
+        assert_eq!(player.name(), "Steve!");
+        assert_eq!(player.health(), 15);
+        assert_eq!(*player.id(), 7);
+
+        let mut version = crate::version::Version::new(0, 1, 2, 3);
+        version.set_minor(5);
+        assert_eq!(version.minor(), 5);
+        assert_eq!(version.patch(), 3);
+    }
+
+    #[test]
+    fn test_savable_versioned() {
+        use crate::save::Savable;
+
+        #[derive(crate::Savable)]
+        struct PlayerV1 {
+            name: String,
+            health: u8,
+        }
+
+        #[derive(crate::Savable)]
+        struct PlayerV2 {
+            name: String,
+            health: u32,
+        }
+
+        #[crate::savable_versioned(PlayerV1 => upgrade_v1, PlayerV2 => upgrade_v2)]
+        #[derive(Debug, PartialEq)]
+        struct Player {
+            name: String,
+            health: u32,
+            #[unsaved]
+            cached: u32,
+            level: u16,
+        }
+
+        fn upgrade_v1(old: PlayerV1) -> PlayerV2 {
+            PlayerV2 { name: old.name, health: old.health as u32 * 10 }
+        }
+
+        fn upgrade_v2(old: PlayerV2) -> Player {
+            Player { name: old.name, health: old.health, cached: 0, level: 1 }
+        }
+
+        assert_eq!(Player::SAVE_VERSION, 3);
+
+        let mut buffer = ByteBuffer::new();
+        buffer.push_u8(1);
+        PlayerV1 { name: "old".to_string(), health: 5 }.save(&mut buffer);
+        assert_eq!(Player::load(&mut buffer).unwrap(), Player { name: "old".to_string(), health: 50, cached: 0, level: 1 });
+
+        let mut buffer = ByteBuffer::new();
+        Player { name: "new".to_string(), health: 7, cached: 3, level: 4 }.save(&mut buffer);
+        assert_eq!(Player::load(&mut buffer).unwrap(), Player { name: "new".to_string(), health: 7, cached: 0, level: 4 });
+
+        let mut buffer = ByteBuffer::new();
+        buffer.push_u8(9);
+        assert!(Player::load(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_savable_std_types() {
+        use crate::save::{Savable, SaveSize};
+        use std::ffi::OsString;
+        use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+        use std::num::{NonZeroI32, NonZeroU8};
+        use std::path::PathBuf;
+
+        fn round_trip<T: Savable + SaveSize + PartialEq + std::fmt::Debug>(value: T) {
+            let mut buffer = ByteBuffer::new();
+            value.save(&mut buffer);
+            assert_eq!(buffer.len(), value.save_size());
+            assert_eq!(T::load(&mut buffer).unwrap(), value);
+        }
+
+        round_trip('ß');
+        round_trip(PathBuf::from("assets/textures/grass.png"));
+        round_trip(OsString::from("world"));
+        round_trip(IpAddr::from([127, 0, 0, 1]));
+        round_trip(IpAddr::V6(Ipv6Addr::LOCALHOST));
+        round_trip("192.168.0.1:25565".parse::<SocketAddr>().unwrap());
+        round_trip("[::1]:8080".parse::<SocketAddr>().unwrap());
+        round_trip(NonZeroU8::new(7).unwrap());
+        round_trip(NonZeroI32::new(-3).unwrap());
+
+        let mut buffer = ByteBuffer::new();
+        0u32.save(&mut buffer);
+        assert!(NonZeroI32::load(&mut buffer).is_err());
+
+        let mut buffer = ByteBuffer::new();
+        0xD800u32.save(&mut buffer);
+        assert!(char::load(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_save_custom_encodings() {
+        use crate::save::custom::*;
+
+        for value in [0.0, 1.0, -2.5, 0.1, 65504.0, 1e-7, -0.0] {
+            assert!((f16_to_f32(f32_to_f16(value)) - value).abs() <= value.abs() / 1024.0 + 1e-7);
+        }
+        assert_eq!(f32_to_f16(1.0), 0x3C00);
+        assert_eq!(f32_to_f16(-2.0), 0xC000);
+        assert_eq!(f32_to_f16(1e6), 0x7C00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+        let packed = pack_snorm_1010102([1.0, -1.0, 0.5, -1.0]);
+        let unpacked = unpack_snorm_1010102(packed);
+        assert_eq!(unpacked[0], 1.0);
+        assert_eq!(unpacked[1], -1.0);
+        assert!((unpacked[2] - 0.5).abs() < 0.002);
+        assert_eq!(unpacked[3], -1.0);
+        assert_eq!(unpack_unorm_1010102(pack_unorm_1010102([0.0, 1.0, 2.0, 1.0 / 3.0])), [0.0, 1.0, 1.0, 1.0 / 3.0]);
+
+        #[derive(Savable)]
+        struct Vertex {
+            #[custom(save = f16_save, load = f16_load)]
+            height: f32,
+            #[custom(save = unorm8_save, load = unorm8_load)]
+            shade: f32,
+            #[custom(save = snorm16_save, load = snorm16_load)]
+            tilt: f32,
+            #[custom(save = snorm_1010102_save, load = snorm_1010102_load)]
+            normal: [f32; 4],
+        }
+
+        let mut buffer = ByteBuffer::new();
+        Vertex { height: 12.5, shade: 0.5, tilt: -0.25, normal: [0.0, 1.0, 0.0, 0.0] }.save(&mut buffer);
+        assert_eq!(buffer.len(), 9);
+        let vertex = Vertex::load(&mut buffer).unwrap();
+        assert_eq!(vertex.height, 12.5);
+        assert!((vertex.shade - 0.5).abs() < 0.002);
+        assert!((vertex.tilt + 0.25).abs() < 0.0001);
+        assert_eq!(vertex.normal, [0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_validating_loader() {
+        use crate::save::validate::{LoadLimits, ValidatingLoader};
+        use crate::save::SaveError;
+
+        let limits = LoadLimits {
+            max_collection_len: 8,
+            max_string_len: 5,
+            max_depth: 2,
+            max_bytes: 64,
+        };
+
+        let mut buffer = ByteBuffer::new();
+        vec![vec![1u8, 2], vec![3]].save(&mut buffer);
+        let mut loader = ValidatingLoader::new(&mut buffer, limits.clone());
+        assert_eq!(loader.load::<Vec<Vec<u8>>>().unwrap(), vec![vec![1, 2], vec![3]]);
+        assert_eq!(loader.bytes_read(), 27);
+
+        let mut buffer = ByteBuffer::new();
+        vec![vec![vec![1u8]]].save(&mut buffer);
+        let error = ValidatingLoader::new(&mut buffer, limits.clone()).load::<Vec<Vec<Vec<u8>>>>().unwrap_err();
+        assert!(error.to_string().contains("depth"), "{error}");
+
+        let mut buffer = ByteBuffer::new();
+        u64::MAX.save(&mut buffer);
+        let error = ValidatingLoader::new(&mut buffer, limits.clone()).load::<Vec<u64>>().unwrap_err();
+        assert!(error.to_string().contains("Collection length"), "{error}");
+
+        let mut buffer = ByteBuffer::new();
+        "too long".to_string().save(&mut buffer);
+        let error = ValidatingLoader::new(&mut buffer, limits.clone()).load::<String>().unwrap_err();
+        assert!(error.to_string().contains("String length"), "{error}");
+
+        let mut buffer = ByteBuffer::new();
+        [0u64; 9].save(&mut buffer);
+        let mut loader = ValidatingLoader::new(&mut buffer, limits.clone());
+        let error = loader.load::<[u64; 9]>().unwrap_err();
+        assert!(error.to_string().contains("bytes"), "{error}");
+
+        let mut buffer = ByteBuffer::new();
+        42u32.save(&mut buffer);
+        let error = ValidatingLoader::new(&mut buffer, limits).load_validated::<u32>(|v| if *v < 10 { Ok(()) } else { Err("Too large".to_string()) });
+        assert_eq!(error, Err(SaveError::custom("Too large")));
+
+        let mut buffer = ByteBuffer::new();
+        u64::MAX.save(&mut buffer);
+        assert!(Vec::<u8>::load(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_save_error() {
+        use crate::save::SaveError;
+
+        #[derive(Savable, Debug)]
+        struct Item {
+            id: u32,
+            count: u8,
+        }
+
+        #[derive(Savable, Debug)]
+        struct Inventory {
+            owner: String,
+            items: Vec<Item>,
+        }
+
+        #[derive(Savable, Debug)]
+        enum Slot {
+            Empty,
+            Full(Item),
+        }
+
+        let mut buffer = ByteBuffer::new();
+        "Steve".to_string().save(&mut buffer);
+        1u64.save(&mut buffer);
+        7u32.save(&mut buffer);
+        let error = Inventory::load(&mut buffer).unwrap_err();
+        assert_eq!(error.path(), vec!["items", "count"]);
+        assert_eq!(error.root(), &SaveError::UnexpectedEof { ty: "u8" });
+        assert_eq!(error.to_string(), "items.count: Unexpected end of data while loading u8");
+
+        let mut buffer = ByteBuffer::new();
+        5u8.save(&mut buffer);
+        assert_eq!(Slot::load(&mut buffer).unwrap_err(), SaveError::InvalidDiscriminant { ty: "Slot", value: 5 });
+
+        let mut buffer = ByteBuffer::new();
+        1u8.save(&mut buffer);
+        let error = Slot::load(&mut buffer).unwrap_err();
+        assert_eq!(error.path(), vec!["0", "id"]);
+
+        let message: String = SaveError::custom("broken").into();
+        assert_eq!(message, "broken");
+        assert!(matches!(Slot::load(&mut ByteBuffer::new()).unwrap_err(), SaveError::UnexpectedEof { .. }));
+        let _ = Slot::Empty;
+    }
+
+    #[test]
+    fn test_loader_seek_and_scope() {
+        let mut buffer = ByteBuffer::new();
+        8u32.save(&mut buffer);
+        1u32.save(&mut buffer);
+        2u32.save(&mut buffer);
+        "after".to_string().save(&mut buffer);
+
+        let len = u32::load(&mut buffer).unwrap() as usize;
+        assert_eq!(buffer.position(), Ok(4));
+        {
+            let mut section = buffer.scoped(len).unwrap();
+            assert_eq!(u32::load(&mut section).unwrap(), 1);
+            assert_eq!(section.position(), Ok(4));
+            assert!(u64::load(&mut section).is_err());
+            assert!(section.seek(9).is_err());
+            section.seek(0).unwrap();
+            assert_eq!(section.pop_to_end().unwrap().len(), 8);
+        }
+        assert_eq!(buffer.position(), Ok(12));
+        {
+            buffer.seek(4).unwrap();
+            let mut section = buffer.scoped(len).unwrap();
+            assert_eq!(u32::load(&mut section).unwrap(), 1);
+        }
+        assert_eq!(String::load(&mut buffer).unwrap(), "after");
+
+        buffer.seek(4).unwrap();
+        assert!(buffer.scoped(usize::MAX).is_err());
+        assert!(buffer.scoped(100).is_err());
+        assert_eq!(buffer.position(), Ok(4));
+
+        buffer.seek(0).unwrap();
+        buffer.skip(12).unwrap();
+        assert!(buffer.skip(100).is_err());
+        assert_eq!(String::load(&mut buffer).unwrap(), "after");
+        assert!(buffer.seek(1000).is_err());
+    }
+
+    #[test]
+    fn test_try_from_string_structs() {
+        #[try_from_string(pattern = "{width}x{height}")]
+        #[derive(Debug, PartialEq)]
+        struct Resolution {
+            width: u32,
+            height: u32,
+        }
+
+        #[try_from_string]
+        #[derive(Debug, PartialEq)]
+        struct Position(f32, f32, f32);
+
+        #[try_from_string(delimiter = ";")]
+        #[derive(Debug, PartialEq)]
+        struct Entry {
+            name: String,
+            count: u8,
+        }
+
+        #[try_from_string(pattern = "rgb({r}, {g}, {b})")]
+        #[derive(Debug, PartialEq)]
+        struct Rgb {
+            b: u8,
+            g: u8,
+            r: u8,
+        }
+
+        assert_eq!("640x480".parse(), Ok(Resolution { width: 640, height: 480 }));
+        assert_eq!(Resolution::try_from("1920x1080".to_string()), Ok(Resolution { width: 1920, height: 1080 }));
+        assert!("640".parse::<Resolution>().unwrap_err().contains("Expected 'x'"));
+        assert!("640xabc".parse::<Resolution>().unwrap_err().contains("field height"));
+        assert_eq!("1.0, 2.5,3".parse(), Ok(Position(1.0, 2.5, 3.0)));
+        assert!("1,2".parse::<Position>().is_err());
+        assert!("1,2,3,4".parse::<Position>().is_err());
+        assert_eq!("apple;3".parse(), Ok(Entry { name: "apple".to_string(), count: 3 }));
+        assert_eq!("rgb(1, 2, 3)".parse(), Ok(Rgb { r: 1, g: 2, b: 3 }));
+        assert!("rgb(1, 2, 3".parse::<Rgb>().is_err());
+        assert!("rgb(1, 2, 3)x".parse::<Rgb>().is_err());
+    }
+
+    #[test]
+    fn test_prompt() {
+        use crate::prompt::{confirm_with, input_with, select_with};
+        use std::io::Cursor;
+
+        let mut out = Vec::new();
+        assert!(confirm_with(&mut Cursor::new("maybe\nYes\n"), &mut out, "Continue?").unwrap());
+        assert!(String::from_utf8_lossy(&out).contains("Please answer yes or no"));
+        assert!(!confirm_with(&mut Cursor::new("\n"), &mut Vec::new(), "Continue?").unwrap());
+
+        let mut out = Vec::new();
+        let value: u32 = input_with(&mut Cursor::new("abc\n 42 \n"), &mut out, "Amount").unwrap();
+        assert_eq!(value, 42);
+        assert!(String::from_utf8_lossy(&out).contains("invalid digit"));
+        assert!(input_with::<u32>(&mut Cursor::new(""), &mut Vec::new(), "Amount").is_err());
+
+        let mut out = Vec::new();
+        let index = select_with(&mut Cursor::new("0\n4\n2\n"), &mut out, "Pick", &["a", "b", "c"]).unwrap();
+        assert_eq!(index, 1);
+        assert!(String::from_utf8_lossy(&out).contains("1 to 3"));
+    }
+
+    #[test]
+    fn test_terminal_helpers() {
+        use crate::print::terminal;
+
+        let (columns, rows) = terminal::size();
+        assert!(columns > 0 && rows > 0);
+        assert_eq!(terminal::move_to(0, 4), "\x1b[5;1H");
+        assert_eq!(terminal::move_up(3), "\x1b[3A");
+        assert_eq!(terminal::move_left(0), "");
+    }
+
+    #[test]
+    fn test_shutdown() {
+        use crate::shutdown;
+        use std::sync::{Arc, Mutex};
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for i in 0..3 {
+            let order = order.clone();
+            shutdown::on_shutdown(move || order.lock().unwrap().push(i));
+        }
+        let token = shutdown::token();
+        assert!(!token.is_cancelled());
+
+        std::thread::spawn(shutdown::request_shutdown);
+        shutdown::wait_for_shutdown();
+        assert!(token.is_cancelled() && shutdown::is_shutting_down());
+        assert_eq!(*order.lock().unwrap(), vec![2, 1, 0]);
+
+        let late = order.clone();
+        shutdown::on_shutdown(move || late.lock().unwrap().push(3));
+        assert_eq!(order.lock().unwrap().len(), 4);
+        shutdown::request_shutdown();
+        assert_eq!(order.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_proc() {
+        use crate::proc::{Proc, ProcError, Stream};
+        use std::time::Duration;
+
+        let mut lines = Vec::new();
+        let output = Proc::new("sh")
+            .args(["-c", "echo one; echo two >&2; cat; echo $GREETING"])
+            .env("GREETING", "hi")
+            .stdin("input\n")
+            .stream(|stream, line| lines.push((stream, line.to_string())))
+            .unwrap();
+        assert_eq!(output.stdout, "one\ninput\nhi\n");
+        assert_eq!(output.stderr, "two\n");
+        assert!(lines.contains(&(Stream::Stderr, "two".to_string())));
+        assert_eq!(lines.len(), 4);
+
+        match Proc::new("sh").args(["-c", "echo bad >&2; exit 3"]).output() {
+            Err(ProcError::Exit { code: Some(3), stderr, .. }) => assert_eq!(stderr, "bad\n"),
+            other => panic!("Expected exit error, got {:?}", other),
+        }
+        let error = Proc::new("sleep").arg("5").timeout(Duration::from_millis(100)).output().unwrap_err();
+        assert!(matches!(error, ProcError::Timeout { .. }));
+        assert!(matches!(Proc::new("mvutils-missing-program").output(), Err(ProcError::Spawn { .. })));
+    }
+
+    #[test]
+    fn test_save_order() {
+        use crate::save::Savable;
+        use bytebuffer::ByteBuffer;
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Before {
+            id: u32,
+            flag: bool,
+            name: String,
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct After {
+            #[save_order(2)]
+            name: String,
+            #[save_order(0)]
+            id: u32,
+            #[unsaved]
+            cached: u8,
+            #[save_order(1)]
+            flag: bool,
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Tuple(#[save_order(1)] u8, #[save_order(0)] u16);
+
+        let mut buffer = ByteBuffer::new();
+        Before { id: 7, flag: true, name: "x".to_string() }.save(&mut buffer);
+        let after = After::load(&mut buffer).unwrap();
+        assert_eq!(after, After { name: "x".to_string(), id: 7, cached: 0, flag: true });
+
+        let mut buffer = ByteBuffer::new();
+        Tuple(1, 2).save(&mut buffer);
+        let mut expected = ByteBuffer::new();
+        2u16.save(&mut expected);
+        1u8.save(&mut expected);
+        assert_eq!(buffer.as_bytes(), expected.as_bytes());
+        assert_eq!(Tuple::load(&mut buffer).unwrap(), Tuple(1, 2));
+    }
+
+    #[test]
+    fn test_inline_string() {
+        use crate::inline_string::InlineString;
+        use std::fmt::Write;
+
+        let mut s = InlineString::<6>::new();
+        assert!(s.push('a'));
+        assert_eq!(s.push_str("bcdä"), 5);
+        assert!(!s.push('ö'));
+        assert_eq!(s, "abcdä");
+        assert_eq!(s.push_str("x"), 0);
+        assert!(s.is_full());
+        assert_eq!(s.pop(), Some('ä'));
+        s.truncate(2);
+        assert_eq!(s.to_uppercase(), "AB");
+        assert!(write!(s, "{}", 123).is_ok());
+        assert_eq!(format!("{s}"), "ab123");
+        assert!("toolong".parse::<InlineString<6>>().is_err());
+
+        let mut buffer = ByteBuffer::new();
+        s.save(&mut buffer);
+        assert_eq!(buffer.len(), 6);
+        assert_eq!(InlineString::<6>::load(&mut buffer).unwrap(), s);
+        let mut buffer = ByteBuffer::new();
+        s.save(&mut buffer);
+        assert!(InlineString::<4>::load(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_small_vec() {
+        use crate::small_vec;
+        use crate::small_vec::SmallVec;
+        use crate::utils::SplitInto;
+        use std::rc::Rc;
+
+        let mut vec: SmallVec<u32, 4> = small_vec![1, 2, 3];
+        vec.insert(0, 0);
+        assert!(!vec.spilled());
+        vec.push(4);
+        assert!(vec.spilled());
+        assert_eq!(vec.remove(1), 1);
+        assert_eq!(vec.swap_remove(0), 0);
+        assert_eq!(vec.as_slice(), &[4, 2, 3]);
+        assert_eq!(vec.clone().into_iter().rev().collect::<Vec<_>>(), vec![3, 2, 4]);
+
+        let mut buffer = ByteBuffer::new();
+        vec.save(&mut buffer);
+        assert_eq!(Vec::<u32>::load(&mut buffer).unwrap(), vec![4, 2, 3]);
+        vec![5u32, 6].save(&mut buffer);
+        let loaded = SmallVec::<u32, 4>::load(&mut buffer).unwrap();
+        assert!(!loaded.spilled());
+        assert_eq!(loaded, small_vec![5u32, 6] as SmallVec<u32, 2>);
+
+        let parts = (0..5).collect::<SmallVec<u32, 8>>().split_into(2);
+        assert_eq!(parts.iter().map(|p| p.len()).collect::<Vec<_>>(), vec![3, 2]);
+
+        let counter = Rc::new(());
+        let mut vec = SmallVec::<Rc<()>, 2>::new();
+        vec.push(counter.clone());
+        vec.push(counter.clone());
+        let mut iter = vec.into_iter();
+        drop(iter.next());
+        assert_eq!(Rc::strong_count(&counter), 2);
+        drop(iter);
+        assert_eq!(Rc::strong_count(&counter), 1);
+        let mut vec: SmallVec<Rc<()>, 1> = (0..3).map(|_| counter.clone()).collect();
+        vec.retain(|_| false);
+        vec.push(counter.clone());
+        drop(vec);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_cow_arc() {
+        use crate::cow_arc::CowArc;
+
+        #[derive(Savable, Clone, Debug, PartialEq)]
+        struct Settings {
+            name: String,
+            volume: f32,
+        }
+
+        let shared = CowArc::new(Settings { name: "default".to_string(), volume: 1.0 });
+        let mut copy = shared.clone();
+        assert!(CowArc::is_shared(&shared));
+        assert_eq!(CowArc::share_count(&shared), 2);
+        assert!(CowArc::get_mut(&mut copy).is_none());
+
+        copy.make_mut().volume = 0.5;
+        assert!(!CowArc::is_shared(&shared));
+        assert_eq!(shared.volume, 1.0);
+        let ptr = &*copy as *const Settings;
+        copy.make_mut().volume = 0.25;
+        assert_eq!(&*copy as *const Settings, ptr);
+
+        let mut buffer = ByteBuffer::new();
+        copy.save(&mut buffer);
+        let loaded = CowArc::<Settings>::load(&mut buffer).unwrap();
+        assert_eq!(loaded, copy);
+        assert_eq!(CowArc::into_inner(loaded).volume, 0.25);
+    }
+
+    #[test]
+    fn test_handle_store() {
+        use crate::handle::{Handle, HandleMap, HandleStore, Relink};
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Node {
+            name: String,
+            next: Option<Handle<Node>>,
+        }
+
+        impl Relink<Node> for Node {
+            fn relink(&mut self, map: &HandleMap<Node>) {
+                self.next.relink(map);
+            }
+        }
+
+        let mut store = HandleStore::new();
+        let removed = store.insert(Node { name: "removed".to_string(), next: None });
+        let b = store.insert(Node { name: "b".to_string(), next: None });
+        let a = store.insert(Node { name: "a".to_string(), next: Some(b) });
+        store.remove(removed);
+        assert!(!store.contains(removed));
+        let reused = store.insert(Node { name: "c".to_string(), next: None });
+        assert_eq!(reused.index(), removed.index());
+        assert!(store.get(removed).is_none());
+
+        let mut buffer = ByteBuffer::new();
+        store.save(&mut buffer);
+        let loaded = HandleStore::<Node>::load(&mut buffer).unwrap();
+        assert_eq!(loaded.len(), 3);
+        let next = loaded.get(a).unwrap().next.unwrap();
+        assert_eq!(loaded.get(next).unwrap().name, "b");
+        assert!(loaded.get(removed).is_none());
+        crate::save::testing::fuzz_load(&loaded, 500);
+
+        let mut stale = HandleStore::new();
+        let gone = stale.insert("a".to_string());
+        stale.insert("b".to_string());
+        stale.remove(gone);
+        let mut buffer = ByteBuffer::new();
+        stale.save(&mut buffer);
+        let mut stale = HandleStore::<String>::load(&mut buffer).unwrap();
+        let fresh = stale.insert("c".to_string());
+        assert_eq!(fresh.index(), gone.index());
+        assert_eq!(stale.get(gone), None);
+        assert_eq!(stale.get(fresh).unwrap(), "c");
+
+        let mut live = HandleStore::new();
+        let existing = live.insert(Node { name: "existing".to_string(), next: None });
+        live.get_mut(existing).unwrap().next = Some(existing);
+        let map = live.merge(loaded);
+        live.relink_all(&map);
+        let a = map.get(a).unwrap();
+        let next = live.get(a).unwrap().next.unwrap();
+        assert_eq!(live.get(next).unwrap().name, "b");
+        assert_eq!(live.len(), 4);
+        assert_eq!(live.get(existing).unwrap().next, Some(existing));
+
+        let mut other = HandleStore::new();
+        let removed = other.insert(Node { name: "removed".to_string(), next: None });
+        other.remove(removed);
+        let dangling = other.insert(Node { name: "dangling".to_string(), next: Some(removed) });
+        let map = live.merge(other);
+        live.relink_all(&map);
+        let next = live.get(map.get(dangling).unwrap()).unwrap().next.unwrap();
+        assert!(next.is_dangling());
+        assert!(live.get(next).is_none());
+    }
+
+    #[test]
+    fn test_dangerous_cell() {
+        use crate::unsafe_utils::DangerousCell;
+        use std::rc::Rc;
+
+        let counter = Rc::new(());
+        let cell = DangerousCell::new(Some(counter.clone()));
+        cell.set(None);
+        assert_eq!(Rc::strong_count(&counter), 1);
+        assert_eq!(cell.replace(Some(counter.clone())), None);
+        assert!(cell.take().is_some());
+        assert!(cell.get().is_none());
+
+        let cell = DangerousCell::new(5u32);
+        assert_eq!(cell.update(|v| { *v += 1; *v * 2 }), 12);
+        assert_eq!(cell.get_copy_unsync(), 6);
+        assert_eq!(cell, DangerousCell::new(6));
+        assert!(cell < DangerousCell::new(7));
+
+        let mut buffer = ByteBuffer::new();
+        cell.save(&mut buffer);
+        assert_eq!(DangerousCell::<u32>::load(&mut buffer).unwrap().into_inner(), 6);
+    }
+
+    #[test]
+    fn test_tracked_unsafe_ref() {
+        use crate::unsafe_utils::{Tracked, UnsafeRef};
+
+        let value = Box::new(Tracked::new(5));
+        let reference = unsafe { UnsafeRef::tracked(&value) };
+        assert_eq!(*reference, 5);
+        assert_eq!(*unsafe { reference.cast_bytes::<i32>() }, 5);
+    }
+
+    #[test]
+    #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+    #[should_panic(expected = "Use after free")]
+    fn test_tracked_unsafe_ref_after_free() {
+        use crate::unsafe_utils::{Tracked, UnsafeRef};
+
+        let value = Box::new(Tracked::new(5));
+        let reference = unsafe { UnsafeRef::tracked(&value) };
+        drop(value);
+        let _ = *reference;
+    }
+
+    #[test]
+    fn test_aligned_allocations() {
+        use crate::unsafe_utils::{AlignedBox, RawBuffer, Unsafe};
+
+        let mut value = AlignedBox::<[f32; 3], 64>::new([1.0, 2.0, 3.0]);
+        assert_eq!(value.as_ptr() as usize % 64, 0);
+        value[1] = 5.0;
+        assert_eq!(value.into_inner(), [1.0, 5.0, 3.0]);
+
+        let mut buffer = RawBuffer::alloc(16, 16);
+        assert!(buffer.as_bytes().iter().all(|b| *b == 0));
+        unsafe { buffer.as_mut_slice::<u32>() }.unwrap().copy_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(unsafe { buffer.as_slice::<u64>() }.unwrap().len(), 2);
+        assert!(unsafe { buffer.as_slice::<[u8; 3]>() }.is_err());
+        assert!(unsafe { Unsafe::from_bytes::<u32>(&buffer.as_bytes()[1..5]) }.is_err());
+        assert_eq!(*unsafe { Unsafe::from_bytes::<u32>(&buffer.as_bytes()[4..8]) }.unwrap(), 2);
+        assert_eq!(unsafe { Unsafe::as_bytes(&1u16) }, &1u16.to_ne_bytes());
+        assert!(RawBuffer::alloc(0, 8).is_empty());
+    }
+
+    #[test]
+    fn test_pod() {
+        use crate::unsafe_utils::{bytes_of, cast_slice, cast_slice_mut, from_bytes, read_pod, zeroed, RawBuffer};
+        use crate::Pod;
+
+        #[repr(C)]
+        #[derive(Pod, Copy, Clone, Debug, PartialEq)]
+        struct Vertex {
+            position: [f32; 3],
+            color: u32,
+        }
+
+        let vertex = Vertex { position: [1.0, 2.0, 3.0], color: 0xFF00FF00 };
+        let bytes = bytes_of(&vertex);
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(read_pod::<Vertex>(bytes).unwrap(), vertex);
+        assert_eq!(*from_bytes::<Vertex>(bytes).unwrap(), vertex);
+        assert!(read_pod::<Vertex>(&bytes[1..]).is_err());
+        assert_eq!(zeroed::<Vertex>().color, 0);
+
+        let mut vertices = [vertex, vertex];
+        let floats: &mut [u32] = cast_slice_mut(&mut vertices).unwrap();
+        floats[3] = 7;
+        assert_eq!(vertices[0].color, 7);
+        assert!(cast_slice::<Vertex, [u8; 3]>(&vertices).is_err());
+        assert_eq!(cast_slice::<Vertex, u8>(&vertices).unwrap().len(), 32);
+
+        let mut buffer = RawBuffer::alloc(32, 16);
+        buffer.view_mut::<Vertex>().unwrap()[1] = vertex;
+        assert_eq!(buffer.view::<Vertex>().unwrap()[1], vertex);
+    }
+
+    #[test]
+    fn test_scratch() {
+        use crate::scratch;
+        use std::fmt::Write;
+
+        let total = scratch::scope(|s| {
+            let a = s.alloc(5u64);
+            let text = s.format(format_args!("value {}", a));
+            assert_eq!(text, "value 5");
+            let mut numbers = s.vec::<u32>();
+            numbers.extend(0..1000);
+            let numbers = numbers.into_slice();
+            let big = s.alloc_slice_fill(10_000, 1u8);
+            let mut string = s.string();
+            write!(string, "{}-{}", text, big.len()).unwrap();
+            assert_eq!(string.as_str(), "value 5-10000");
+            *a += 1;
+
+            let before = s.allocated();
+            scratch::scope(|inner| {
+                inner.alloc_str("temporary");
+                assert!(inner.allocated() > before);
+            });
+            assert_eq!(s.allocated(), before);
+            *a as u32 + numbers.iter().sum::<u32>()
+        });
+        assert_eq!(total, 6 + 499_500);
+
+        let nested = std::panic::catch_unwind(|| {
+            scratch::scope(|outer| scratch::scope(|_| {
+                outer.alloc(1u8);
+            }))
+        });
+        assert!(nested.is_err());
+        scratch::scope(|s| assert_eq!(s.allocated(), 0));
+    }
+
+    #[test]
+    fn test_endian_wrappers() {
+        use crate::bytebuffer::ByteBufferExtras;
+        use crate::save::endian::{read_be, write_le, Be, Le};
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Header {
+            magic: Be<u32>,
+            version: Le<u16>,
+            scale: Le<f32>,
+        }
+
+        let header = Header { magic: Be(0x89504E47), version: Le(2), scale: Le(0.5) };
+        for mut buffer in [ByteBuffer::new_le(), ByteBuffer::new_be()] {
+            header.save(&mut buffer);
+            assert_eq!(&buffer.as_bytes()[..6], &[0x89, 0x50, 0x4E, 0x47, 2, 0]);
+            assert_eq!(Header::load(&mut buffer).unwrap(), header);
+        }
+
+        let mut bytes = Vec::new();
+        write_le(&mut bytes, 0x0102u16);
+        assert_eq!(bytes, vec![2, 1]);
+        assert_eq!(read_be::<u16>(&bytes), Some(0x0201));
+        assert_eq!(read_be::<u32>(&bytes), None);
+        assert_eq!(*header.version + 1, 3);
+    }
+
+    #[test]
+    fn test_platform() {
+        use crate::platform::{self, Instant};
+        use crate::utils::Time;
+        use std::time::{Duration, SystemTime};
+
+        let start = Instant::now();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(start.elapsed() >= Duration::from_millis(5));
+
+        let now = platform::system_now();
+        let std_now = SystemTime::now();
+        assert!(std_now.duration_since(now).unwrap_or_default() < Duration::from_secs(1));
+        assert!(u128::time_millis() >= platform::unix_time().as_millis() - 1000);
+        assert!(platform::available_parallelism() >= 1);
+    }
+
+    #[test]
+    fn test_save_testing_helpers() {
+        use crate::save::testing::{assert_round_trip, fuzz_load};
+        use crate::save::SaveError;
+        use std::collections::HashMap;
+
+        #[derive(Savable, Debug, PartialEq)]
+        enum Shape {
+            Circle(f32),
+            Polygon { points: Vec<(i32, i32)>, name: String },
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Scene {
+            shapes: Vec<Shape>,
+            tags: HashMap<String, u64>,
+            parent: Option<Box<Scene>>,
+        }
+
+        let scene = Scene {
+            shapes: vec![Shape::Circle(1.5), Shape::Polygon { points: vec![(0, 0), (4, -2)], name: "tri".to_string() }],
+            tags: HashMap::from([("layer".to_string(), 3)]),
+            parent: Some(Box::new(Scene { shapes: vec![], tags: HashMap::new(), parent: None })),
+        };
+        assert_round_trip(&scene);
+        fuzz_load(&scene, 2000);
+
+        struct Fragile(u8);
+
+        impl Savable for Fragile {
+            fn save(&self, saver: &mut impl Saver) {
+                self.0.save(saver);
+            }
+
+            fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+                let value = u8::load(loader)?;
+                assert!(value < 200);
+                Ok(Fragile(value))
+            }
+        }
+
+        let result = std::panic::catch_unwind(|| fuzz_load(&Fragile(10), 200));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_saver_reserve() {
+        use crate::bytebuffer::ByteBufferExtras;
+        use crate::save::SizeCounter;
+
+        #[derive(Savable, SaveSize, Debug, PartialEq)]
+        struct Particle {
+            position: (f32, f32),
+            name: String,
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Wrapper<T: Savable> {
+            inner: T,
+        }
+
+        let particles = Wrapper {
+            inner: (0..100).map(|i| Particle { position: (i as f32, 1.0), name: format!("p{i}") }).collect::<Vec<_>>(),
+        };
+
+        let mut buffer = ByteBuffer::new_le();
+        assert_eq!(buffer.bytes_written(), 0);
+        particles.inner[0].save(&mut buffer);
+        assert_eq!(buffer.bytes_written(), particles.inner[0].save_size());
+        assert_eq!(&buffer.as_bytes()[..4], &0.0f32.to_le_bytes());
+        assert_eq!(Particle::load(&mut buffer).unwrap(), particles.inner[0]);
+
+        buffer.reserve(1024);
+        particles.save(&mut buffer);
+        assert_eq!(buffer.get_rpos(), particles.inner[0].save_size());
+        assert_eq!(Wrapper::<Vec<Particle>>::load(&mut buffer).unwrap(), particles);
+
+        let size = SizeCounter::measure(|saver| particles.save(saver));
+        assert_eq!(size + particles.inner[0].save_size(), buffer.bytes_written());
+
+        #[derive(Default)]
+        struct Reservations {
+            reserved: Vec<usize>,
+            written: usize,
+        }
+
+        impl Saver for Reservations {
+            fn push_bytes(&mut self, bytes: &[u8]) {
+                self.written += bytes.len();
+            }
+            fn push_bool(&mut self, _: bool) {
+                self.written += 1;
+            }
+            fn push_u8(&mut self, _: u8) {
+                self.written += 1;
+            }
+            fn push_u16(&mut self, _: u16) {
+                self.written += 2;
+            }
+            fn push_u32(&mut self, _: u32) {
+                self.written += 4;
+            }
+            fn push_u64(&mut self, _: u64) {
+                self.written += 8;
+            }
+            fn push_i8(&mut self, _: i8) {
+                self.written += 1;
+            }
+            fn push_i16(&mut self, _: i16) {
+                self.written += 2;
+            }
+            fn push_i32(&mut self, _: i32) {
+                self.written += 4;
+            }
+            fn push_i64(&mut self, _: i64) {
+                self.written += 8;
+            }
+            fn push_f32(&mut self, _: f32) {
+                self.written += 4;
+            }
+            fn push_f64(&mut self, _: f64) {
+                self.written += 8;
+            }
+            fn push_string(&mut self, value: &str) {
+                self.written += 4 + value.len();
+            }
+
+            fn bytes_written(&self) -> usize {
+                self.written
+            }
+
+            fn can_reserve(&self) -> bool {
+                true
+            }
+
+            fn reserve(&mut self, additional: usize) {
+                self.reserved.push(additional);
+            }
+        }
+
+        let mut reservations = Reservations::default();
+        particles.inner.save(&mut reservations);
+        assert_eq!(reservations.reserved, [particles.inner.iter().map(Particle::save_size).sum::<usize>()]);
+    }
+
+    #[test]
+    fn test_string_table() {
+        use crate::bytebuffer::ByteBufferExtras;
+        use crate::save::strings::{load_with_string_table, save_with_string_table, StringTableLoader, StringTableSaver};
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Entity {
+            kind: String,
+            tags: Vec<String>,
+            hp: u32,
+        }
+
+        let entities = (0..50)
+            .map(|i| Entity { kind: ["goblin", "orc"][i % 2].to_string(), tags: vec!["hostile".to_string()], hp: i as u32 })
+            .collect::<Vec<_>>();
+
+        let mut plain = ByteBuffer::new();
+        entities.save(&mut plain);
+        let mut interned = ByteBuffer::new_le();
+        save_with_string_table(&mut interned, &entities);
+        assert!(interned.len() < plain.len() * 2 / 3);
+        assert_eq!(load_with_string_table::<Vec<Entity>>(&mut interned).unwrap(), entities);
+
+        let mut buffer = ByteBuffer::new();
+        let mut saver = StringTableSaver::new(&mut buffer, &entities[0]);
+        assert_eq!(saver.len(), 2);
+        entities[0].save(&mut saver);
+        let mut loader = StringTableLoader::new(&mut buffer).unwrap();
+        assert_eq!(loader.strings(), &["goblin".to_string(), "hostile".to_string()]);
+        assert_eq!(Entity::load(&mut loader).unwrap(), entities[0]);
+
+        let mut corrupt = ByteBuffer::new();
+        Vec::<String>::new().save(&mut corrupt);
+        corrupt.write_u32(0);
+        assert!(load_with_string_table::<String>(&mut corrupt).is_err());
+
+        #[derive(Savable)]
+        #[savable(tagged)]
+        struct TaggedV2 {
+            a: u32,
+            name: String,
+            b: u32,
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        #[savable(tagged)]
+        struct TaggedV1 {
+            a: u32,
+            #[savable(tag = 2)]
+            b: u32,
+        }
+
+        let mut tagged = ByteBuffer::new();
+        save_with_string_table(&mut tagged, &TaggedV2 { a: 1, name: "a long name".to_string(), b: 2 });
+        assert_eq!(load_with_string_table::<TaggedV1>(&mut tagged).unwrap(), TaggedV1 { a: 1, b: 2 });
+    }
+
+    #[test]
+    fn test_dirty_savable() {
+        use crate::save::dirty::{DirtyMask, DirtySavable};
+        use crate::DirtySavable;
+
+        #[derive(DirtySavable, Savable, Default, Debug, PartialEq, Clone)]
+        struct Unit {
+            name: String,
+            hp: u32,
+            #[custom(save = string8_save, load = string8_load)]
+            faction: String,
+            #[unsaved]
+            selected: bool,
+            #[dirty_mask]
+            #[unsaved]
+            dirty: DirtyMask,
+        }
+
+        assert_eq!(Unit::FIELD_COUNT, 3);
+        let mut unit = Unit::default();
+        assert!(!unit.is_dirty());
+        unit.set_hp(50);
+        unit.faction_mut().push_str("red");
+        assert_eq!(unit.dirty_mask().bits(), 0b110);
+
+        let mut buffer = ByteBuffer::new();
+        unit.flush_dirty(&mut buffer);
+        assert!(!unit.is_dirty());
+        assert_eq!(buffer.len(), 1 + 4 + 1 + 3);
+
+        let mut replica = Unit { name: "scout".to_string(), selected: true, ..Default::default() };
+        replica.apply(&mut buffer).unwrap();
+        assert_eq!((replica.name.as_str(), replica.hp, replica.faction.as_str(), replica.selected), ("scout", 50, "red", true));
+        assert!(!replica.is_dirty());
+
+        let mut full = ByteBuffer::new();
+        replica.mark_all_dirty();
+        replica.save_dirty(&mut full);
+        let mut copy = Unit::default();
+        copy.apply(&mut full).unwrap();
+        assert_eq!((copy.name, copy.hp, copy.faction), (replica.name.clone(), 50, "red".to_string()));
+
+        let mut truncated = ByteBuffer::new();
+        0b011u8.save(&mut truncated);
+        "new name".to_string().save(&mut truncated);
+        assert!(replica.apply(&mut truncated).is_err());
+        assert_eq!(replica.name, "scout");
+
+        let mut invalid = ByteBuffer::new();
+        0b1000u8.save(&mut invalid);
+        assert!(replica.apply(&mut invalid).is_err());
+    }
+
+    #[test]
+    fn test_variant_ids() {
+        #[derive(Savable, SaveSize, Debug, PartialEq)]
+        enum Command {
+            Move { x: i32, y: i32 },
+            #[id = 7]
+            Attack(u32),
+            Wait,
+            #[id = 3]
+            Stop,
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        enum Wide {
+            A,
+            #[id = 300]
+            B(u8),
+        }
+
+        let ids = [Command::Move { x: 1, y: -1 }, Command::Attack(5), Command::Wait, Command::Stop].map(|command| {
+            let mut buffer = ByteBuffer::new();
+            command.save(&mut buffer);
+            assert_eq!(buffer.len(), command.save_size());
+            assert_eq!(Command::load(&mut buffer).unwrap(), command);
+            buffer.as_bytes()[0]
+        });
+        assert_eq!(ids, [0, 7, 8, 3]);
+
+        let mut buffer = ByteBuffer::new();
+        Wide::B(9).save(&mut buffer);
+        assert_eq!(buffer.as_bytes(), &[1, 44, 9]);
+        assert_eq!(Wide::load(&mut buffer).unwrap(), Wide::B(9));
+
+        let mut invalid = ByteBuffer::new();
+        1u8.save(&mut invalid);
+        assert!(Command::load(&mut invalid).is_err());
+    }
+
+    #[test]
+    fn test_format_hash() {
+        use crate::save::format::{load_with_format_hash, save_with_format_hash, FormatHash};
+
+        mod v1 {
+            use crate as mvutils;
+            use mvutils_proc_macro::Savable;
+
+            #[derive(Savable, Debug, PartialEq)]
+            pub struct Save {
+                pub level: u32,
+                pub name: String,
+            }
+        }
+
+        mod v2 {
+            use crate as mvutils;
+            use mvutils_proc_macro::Savable;
+
+            /// Documentation and formatting don't change the hash.
+            #[derive(Savable, Debug, PartialEq)]
+            pub struct Save {
+                pub level:   u32,
+                pub name: String,
+            }
+        }
+
+        mod v3 {
+            use crate as mvutils;
+            use mvutils_proc_macro::Savable;
+
+            #[derive(Savable)]
+            pub struct Save {
+                pub level: u64,
+                pub name: String,
+            }
+        }
+
+        mod v4 {
+            use crate as mvutils;
+            use mvutils_proc_macro::Savable;
+
+            #[derive(Savable)]
+            pub struct Save {
+                #[save_order(1)]
+                pub level: u32,
+                #[save_order(0)]
+                pub name: String,
+            }
+        }
+
+        mod v5 {
+            use crate as mvutils;
+            use mvutils_proc_macro::Savable;
+
+            /// Names are not saved, so renaming keeps the hash.
+            #[derive(Savable)]
+            pub struct Renamed {
+                pub depth: u32,
+                pub title: String,
+            }
+        }
+
+        assert_eq!(v1::Save::FORMAT_HASH, v2::Save::FORMAT_HASH);
+        assert_ne!(v1::Save::FORMAT_HASH, v3::Save::FORMAT_HASH);
+        assert_ne!(v1::Save::FORMAT_HASH, v4::Save::FORMAT_HASH);
+        assert_eq!(v1::Save::FORMAT_HASH, v5::Renamed::FORMAT_HASH);
+
+        #[derive(Savable)]
+        struct OuterV1 {
+            save: v1::Save,
+        }
+
+        #[derive(Savable)]
+        struct OuterV3 {
+            save: v3::Save,
+        }
+
+        #[derive(Savable)]
+        struct OuterRenamed {
+            save: v5::Renamed,
+        }
+
+        assert_ne!(OuterV1::FORMAT_HASH, OuterV3::FORMAT_HASH);
+        assert_eq!(OuterV1::FORMAT_HASH, OuterRenamed::FORMAT_HASH);
+
+        #[derive(Savable)]
+        #[discriminant(kind)]
+        union Plain {
+            kind: u8,
+            value: i32,
+        }
+
+        #[derive(Savable)]
+        #[discriminant(kind)]
+        union Custom {
+            kind: u8,
+            #[custom(save = hello, load = world)]
+            value: i32,
+        }
+
+        // SAFETY: Both fields are plain integers, so any discriminant reads a valid value.
+        unsafe impl crate::save::UnionDiscriminant for Plain {}
+        // SAFETY: Both fields are plain integers, so any discriminant reads a valid value.
+        unsafe impl crate::save::UnionDiscriminant for Custom {}
+
+        assert_ne!(Plain::FORMAT_HASH, Custom::FORMAT_HASH);
+
+        #[derive(Savable)]
+        enum A {
+            X,
+            Y,
+        }
+
+        #[derive(Savable)]
+        enum B {
+            X,
+            #[id = 5]
+            Y,
+        }
+
+        assert_ne!(<A as FormatHash>::FORMAT_HASH, <B as FormatHash>::FORMAT_HASH);
+
+        let save = v1::Save { level: 3, name: "forest".to_string() };
+        let mut buffer = ByteBuffer::new();
+        save_with_format_hash(&mut buffer, &save);
+        let error = load_with_format_hash::<v3::Save>(&mut buffer.clone()).err().unwrap();
+        assert!(error.to_string().contains("Format hash mismatch"));
+        assert_eq!(load_with_format_hash::<v2::Save>(&mut buffer).unwrap(), v2::Save { level: 3, name: "forest".to_string() });
+    }
+
+    #[test]
+    fn test_poly_savable() {
+        use crate::register_poly;
+        use crate::save::poly::PolySavable;
+
+        trait Component: PolySavable {
+            fn describe(&self) -> String;
+        }
+
+        #[derive(Savable)]
+        struct Health(u32);
+
+        #[derive(Savable)]
+        struct Name {
+            value: String,
+        }
+
+        impl Component for Health {
+            fn describe(&self) -> String {
+                format!("hp {}", self.0)
+            }
+        }
+
+        impl Component for Name {
+            fn describe(&self) -> String {
+                format!("name {}", self.value)
+            }
+        }
+
+        struct Unregistered;
+
+        impl Component for Unregistered {
+            fn describe(&self) -> String {
+                String::new()
+            }
+        }
+
+        register_poly!(dyn Component: Health, Name);
+
+        #[derive(Savable)]
+        struct Entity {
+            components: Vec<Box<dyn Component>>,
+        }
+
+        let entity = Entity { components: vec![Box::new(Health(10)), Box::new(Name { value: "bob".to_string() })] };
+        let mut buffer = ByteBuffer::new();
+        entity.save(&mut buffer);
+        let loaded = Entity::load(&mut buffer).unwrap();
+        assert_eq!(loaded.components.iter().map(|c| c.describe()).collect::<Vec<_>>(), vec!["hp 10", "name bob"]);
+
+        let mut unknown = ByteBuffer::new();
+        "Position".to_string().save(&mut unknown);
+        let error = Box::<dyn Component>::load(&mut unknown).err().unwrap();
+        assert!(error.to_string().contains("Unknown tag Position"));
+
+        let unregistered: Box<dyn Component> = Box::new(Unregistered);
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unregistered.save(&mut ByteBuffer::new()))).is_err());
+    }
+
+    #[cfg(all(feature = "plugin", target_os = "linux"))]
+    #[test]
+    fn test_plugin_library() {
+        use crate::plugin::{Library, Plugin, PluginDeclaration, PluginError, ABI_VERSION, MVUTILS_VERSION};
+        use crate::version::Version;
+        use std::ffi::{c_char, CString};
+
+        unsafe {
+            let libc = Library::open("libc.so.6").unwrap();
+            let strlen = libc.get::<unsafe extern "C" fn(*const c_char) -> usize>("strlen").unwrap();
+            let text = CString::new("plugin").unwrap();
+            assert_eq!(strlen(text.as_ptr()), 6);
+            assert!(matches!(libc.get::<*const u8>("mvutils_missing_symbol"), Err(PluginError::Symbol { .. })));
+            assert!(matches!(Library::open("/nonexistent/libplugin.so"), Err(PluginError::Open { .. })));
+        }
+
+        struct Dummy;
+
+        impl Plugin for Dummy {
+            fn name(&self) -> &str {
+                "dummy"
+            }
+
+            fn version(&self) -> Version {
+                Version::new(0, 1, 0, 0)
+            }
+        }
+
+        let mut declaration = PluginDeclaration { abi_version: ABI_VERSION, mvutils_version: MVUTILS_VERSION, create: || Box::new(Dummy) };
+        assert!(declaration.check().is_ok());
+        assert_eq!((declaration.create)().name(), "dummy");
+        declaration.mvutils_version = "0.1.0";
+        assert!(matches!(declaration.check(), Err(PluginError::VersionMismatch { .. })));
+        declaration.abi_version = ABI_VERSION + 1;
+        assert!(matches!(declaration.check(), Err(PluginError::AbiMismatch { .. })));
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        use crate::hot_reload::HotReload;
+        use crate::save::fs::save_to_file;
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("mvutils_hot_reload_test_{}.bin", std::process::id()));
+        save_to_file(&path, &5u32).unwrap();
+
+        let value = HotReload::<u32>::new(&path).unwrap().validate(|v| if *v < 100 { Ok(()) } else { Err("too large".to_string()) });
+        let state = value.state();
+        assert_eq!(*value.read(), 5);
+        assert!(!value.check().unwrap());
+        assert!(!state.is_outdated());
+
+        save_to_file(&path, &7u8).unwrap();
+        assert!(value.check().is_err());
+        assert!(value.last_error().is_some());
+        assert_eq!(*value.read(), 5);
+
+        save_to_file(&path, &500u32).unwrap();
+        value.reload().unwrap_err();
+        assert_eq!(value.last_error().as_deref(), Some("too large"));
+        assert!(!state.is_outdated());
+
+        save_to_file(&path, &42u32).unwrap();
+        value.reload().unwrap();
+        assert!(value.last_error().is_none());
+        assert!(state.is_outdated());
+        assert_eq!(*state.read(), 42);
+        crate::update!([state]);
+
+        let token = value.watch(Duration::from_millis(5));
+        save_to_file(&path, &9u32).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + Duration::from_secs(60)).unwrap();
+        for _ in 0..200 {
+            if state.is_outdated() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        token.cancel();
+        let _ = std::fs::remove_file(&path);
+        assert!(state.is_outdated());
+        assert_eq!(*state.read(), 9);
+    }
+
+    #[test]
+    #[cfg(all(feature = "debug_globals", target_os = "linux"))]
+    fn test_debug_globals() {
+        use crate::{create_once, globals, lazy};
+
+        lazy! {
+            static USED: Vec<u64> = (0..100).collect();
+            static UNUSED: [u8; 256] = [0; 256];
+        }
+        create_once! {
+            static CREATED: String;
+        }
+
+        assert_eq!(USED.len(), 100);
+        CREATED.create(|| "hello".to_string());
+
+        let find = |name: &str| globals::globals().into_iter().find(|g| g.name.ends_with(name)).unwrap();
+        let used = find("::USED");
+        assert!(used.initialized);
+        assert_eq!(used.kind, "Lazy");
+        assert!(used.init_duration.is_some());
+        assert!(find("::CREATED").initialized);
+
+        let unused = find("::UNUSED");
+        assert!(!unused.initialized);
+        assert_eq!(unused.size, 256);
+        assert!(globals::uninitialized().iter().any(|g| g.name == unused.name));
+
+        let report = globals::report();
+        assert!(report.contains("UNUSED (Lazy<[u8; 256]>, 256 bytes): never initialized"));
+    }
+
+    #[test]
+    #[cfg(feature = "sync_debug")]
+    fn test_sync_debug() {
+        use crate::sync::{Mutex, RwLock};
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::sync::Arc;
+
+        let ui = Arc::new(Mutex::new(0));
+        let loader = Arc::new(RwLock::new(0));
+
+        let (a, b) = (ui.clone(), loader.clone());
+        std::thread::Builder::new()
+            .name("loader".to_string())
+            .spawn(move || {
+                let _loader = b.write();
+                *a.lock() += 1;
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        {
+            let _first = loader.read();
+            let _second = loader.read_recursive();
+        }
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _first = loader.read();
+            let _second = loader.read();
+        }));
+        assert!(result.unwrap_err().downcast::<String>().unwrap().contains("already held by this thread"));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _first = loader.write();
+            let _second = loader.read_recursive();
+        }));
+        assert!(result.unwrap_err().downcast::<String>().unwrap().contains("already held by this thread"));
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _ui = ui.lock();
+            let _loader = loader.read();
+        }));
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("Potential deadlock"));
+        assert!(message.contains("on thread 'loader'"));
+
+        assert!(ui.try_lock().is_some());
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _first = ui.lock();
+            let _second = ui.lock();
+        }));
+        assert!(result.unwrap_err().downcast::<String>().unwrap().contains("already held by this thread"));
+    }
+
+    #[test]
+    fn test_state_macros() {
+        use crate::{changed, when};
+
+        let a = State::new(1);
+        let b = State::new(2);
+        assert!(!changed!([a, b]));
+        assert_eq!(when!([a, b] => |a, b| *a + *b), None);
+
+        *b.write() = 5;
+        assert!(changed!([a, b]));
+        assert_eq!(when!([a, b] => |a, b| *a + *b), Some(6));
+        update!([a, b]);
+
+        let doubled = a.map(|v| v * 2);
+        *a.write() = 4;
+        assert_eq!(when!([doubled] => |d| *d), Some(8));
+
+        #[cfg(feature = "async")]
+        {
+            use std::future::Future;
+            use std::sync::Arc;
+            use std::task::{Context, Poll, Wake};
+
+            struct ThreadWaker(std::thread::Thread);
+
+            impl Wake for ThreadWaker {
+                fn wake(self: Arc<Self>) {
+                    self.0.unpark();
+                }
+            }
+
+            update!([a]);
+            let writer = b.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(20));
+                *writer.write() = 10;
+            });
+
+            let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+            let mut future = std::pin::pin!(async { when!(await [a, b] => |a, b| *a + *b) });
+            let sum = loop {
+                if let Poll::Ready(sum) = future.as_mut().poll(&mut cx) {
+                    break sum;
+                }
+                std::thread::park();
+            };
+            assert_eq!(sum, 14);
+        }
+    }
+
+    #[test]
+    fn test_id_allocator() {
+        use crate::ids::IdAllocator;
+
+        let mut ids = IdAllocator::starting_at(1);
+        assert_eq!(ids.allocate(), 1);
+        assert_eq!(ids.reserve_next(10), 2..12);
+        assert!(ids.reserve(20..30).is_ok());
+        assert!(ids.reserve(25..35).is_err());
+        assert!(ids.reserve(1..2).is_err());
+
+        let allocated = (0..10).map(|_| ids.allocate()).collect::<Vec<_>>();
+        assert_eq!(allocated, vec![12, 13, 14, 15, 16, 17, 18, 19, 30, 31]);
+        assert!(ids.is_reserved(5));
+        assert!(!ids.is_live(5));
+        assert!(!ids.free(25));
+
+        assert!(ids.free(14));
+        assert!(ids.free(12));
+        assert!(!ids.free(12));
+        assert_eq!(ids.live_count(), 9);
+        assert_eq!(ids.allocate(), 12);
+
+        let mut buffer = ByteBuffer::new();
+        ids.save(&mut buffer);
+        let mut loaded = IdAllocator::load(&mut buffer).unwrap();
+        assert_eq!(loaded, ids);
+        assert_eq!(loaded.allocate(), 14);
+        assert_eq!(loaded.allocate(), 32);
+
+        assert!(ids.free(31));
+        assert!(ids.free(30));
+        assert_eq!(ids.allocate(), 14);
+        assert_eq!(ids.allocate(), 30);
+        ids.clear();
+        assert_eq!(ids.live_count(), 0);
+        assert_eq!(ids.allocate(), 1);
+        assert_eq!(ids.allocate(), 12);
+    }
+
+    #[test]
+    fn test_units_and_clock() {
+        use crate::clock::Clock;
+        use crate::units::{Micros, Millis, TickRate, Ticks};
+
+        let rate = TickRate::per_second(60.0);
+        assert_eq!(Ticks(60).to_millis(rate), Millis(1000));
+        assert_eq!(Ticks(1).to_micros(rate), Micros(16667));
+        assert_eq!(Millis(1000).to_ticks(rate), Ticks(60));
+        assert_eq!(Millis(33).to_ticks(rate), Ticks(1));
+        assert_eq!(Micros::from(Millis(3)), Micros(3000));
+        assert_eq!(Millis::from(Duration::from_micros(2500)), Millis(2));
+        assert_eq!(TickRate::from_interval(Duration::from_millis(50)).ticks_per_second(), 20.0);
+
+        let mut ticks = Ticks(5) * 3 - Ticks(2);
+        ticks += Ticks(1);
+        assert_eq!(ticks, Ticks(14));
+        assert_eq!(ticks / Ticks(4), 3);
+        assert_eq!(ticks % Ticks(4), Ticks(2));
+        assert_eq!(Ticks(1).checked_sub(Ticks(2)), None);
+        assert_eq!([Millis(1), Millis(2)].into_iter().sum::<Millis>(), Millis(3));
+        assert_eq!(Ticks(3).to_string(), "3 ticks");
+        assert_eq!(Millis(3).to_string(), "3ms");
+
+        let mut buffer = ByteBuffer::new();
+        (ticks, rate).save(&mut buffer);
+        assert_eq!(<(Ticks, TickRate)>::load(&mut buffer).unwrap(), (ticks, rate));
+
+        let mut clock = Clock::new(TickRate::per_second(1000.0));
+        std::thread::sleep(Duration::from_millis(20));
+        let elapsed = clock.ticks_elapsed();
+        assert!(elapsed >= Ticks(20));
+        let mut processed = 0;
+        while clock.ready() {
+            processed += 1;
+        }
+        assert!(processed >= elapsed.get());
+        assert_eq!(clock.ticks(), Ticks(processed));
+        assert!(clock.alpha() < 1.0);
+
+        clock.set_tick_rate(TickRate::per_second(10.0));
+        assert_eq!(clock.ticks(), Ticks(processed));
+        assert!(!clock.ready());
+        clock.reset();
+        assert_eq!(clock.ticks(), Ticks::ZERO);
+    }
+
+    #[test]
+    fn test_clock_catch_up() {
+        use crate::clock::{CatchUp, Clock};
+        use crate::units::{TickRate, Ticks};
+
+        let mut clock = Clock::new(TickRate::per_second(1000.0)).with_catch_up(CatchUp::Max(5));
+        std::thread::sleep(Duration::from_millis(30));
+        let mut processed = 0;
+        while clock.ready() && processed < 100 {
+            processed += 1;
+        }
+        let missed = clock.missed_ticks();
+        assert!((5..20).contains(&processed));
+        assert!(missed >= Ticks(20));
+        assert_eq!(clock.skipped_ticks(), missed);
+        assert_eq!(clock.missed_ticks(), Ticks::ZERO);
+        assert!(clock.alpha() < 1.0);
+
+        clock.set_catch_up(CatchUp::Skip);
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(clock.ready());
+        assert!(clock.missed_ticks() >= Ticks(15));
+        assert_eq!(clock.ticks(), Ticks(processed + 1));
+
+        clock.set_tick_rate(TickRate::per_second(10.0));
+        assert!(!clock.ready());
+        clock.reset();
+        assert_eq!(clock.skipped_ticks(), Ticks::ZERO);
+    }
+
+    #[test]
+    fn test_journal() {
+        use crate::journal::{Journal, Journaled};
+        use std::io::Write;
+
+        #[derive(Savable, Debug, Default, PartialEq)]
+        struct Inventory {
+            items: Vec<String>,
+        }
+
+        #[derive(Savable)]
+        enum Change {
+            Add(String),
+            Remove(String),
+        }
+
+        impl Journaled for Inventory {
+            type Delta = Change;
+
+            fn apply(&mut self, delta: Change) -> Result<(), String> {
+                match delta {
+                    Change::Add(item) => self.items.push(item),
+                    Change::Remove(item) => {
+                        let index = self.items.iter().position(|i| *i == item).ok_or(format!("No item {}", item))?;
+                        self.items.remove(index);
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("mvutils_journal_test_{}.bin", std::process::id()));
+        let mut journal = Journal::open(&path, Inventory::default).unwrap();
+        assert_eq!(journal.append(Change::Add("sword".to_string())), Ok(1));
+        assert_eq!(journal.append(Change::Add("shield".to_string())), Ok(2));
+        assert!(journal.append(Change::Remove("bow".to_string())).is_err());
+        assert_eq!(journal.seq(), 2);
+        let journal_path = journal.journal_path().to_path_buf();
+        drop(journal);
+
+        let mut journal = Journal::open(&path, Inventory::default).unwrap();
+        assert_eq!(journal.replayed(), 2);
+        assert_eq!(journal.state().items, vec!["sword", "shield"]);
+
+        journal.compact().unwrap();
+        assert_eq!(journal.records(), 0);
+        journal.append(Change::Remove("sword".to_string())).unwrap();
+        journal.flush().unwrap();
+        drop(journal);
+
+        std::fs::OpenOptions::new().append(true).open(&journal_path).unwrap().write_all(&[0, 0, 0, 9, 1, 2]).unwrap();
+        let mut journal = Journal::open(&path, Inventory::default).unwrap();
+        assert_eq!(journal.replayed(), 1);
+        assert_eq!(journal.seq(), 3);
+        assert_eq!(journal.state().items, vec!["shield"]);
+
+        journal.set_compact_after(Some(2));
+        journal.append(Change::Add("bow".to_string())).unwrap();
+        assert_eq!(journal.records(), 0);
+        journal.append(Change::Add("axe".to_string())).unwrap();
+        drop(journal);
+
+        let journal = Journal::open(&path, Inventory::default).unwrap();
+        assert_eq!(journal.replayed(), 1);
+        assert_eq!(journal.seq(), 5);
+        assert_eq!(journal.state().items, vec!["shield", "bow", "axe"]);
+        drop(journal);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&journal_path);
+    }
+
+    #[test]
+    fn test_task() {
+        use crate::thread::{Task, TaskError};
+
+        let state = State::new(());
+        let (sender, receiver) = std::sync::mpsc::channel::<()>();
+        let task = Task::spawn(move |ctx| {
+            ctx.set_progress(0.5);
+            receiver.recv().unwrap();
+            ctx.set_progress(1.0);
+            21 * 2
+        });
+        task.notify(&state);
+        while task.progress() < 0.5 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!task.is_finished());
+        assert!(task.try_take().is_none());
+        assert!(!state.is_outdated());
+        sender.send(()).unwrap();
+        assert_eq!(task.join(), Ok(42));
+        assert!(state.is_outdated());
+
+        let panicking = Task::spawn(|_| -> u32 { panic!("import failed") });
+        assert_eq!(panicking.join(), Err(TaskError::Panicked("import failed".to_string())));
+
+        let cancelled = Task::spawn(|ctx| {
+            while !ctx.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            "stopped"
+        });
+        cancelled.cancel();
+        let result = cancelled.join();
+        assert!(result == Ok("stopped") || result == Err(TaskError::Cancelled));
+
+        let taken = Task::spawn(|_| 5);
+        let result = loop {
+            if let Some(result) = taken.try_take() {
+                break result;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(result, Ok(5));
+        assert!(taken.is_finished());
+        assert!(taken.try_take().is_none());
+        let joined = std::thread::spawn(move || taken.join()).join();
+        assert!(joined.is_err());
+
+        #[cfg(feature = "async")]
+        {
+            use std::future::Future;
+            use std::sync::Arc;
+            use std::task::{Context, Poll, Wake};
+
+            struct ThreadWaker(std::thread::Thread);
+
+            impl Wake for ThreadWaker {
+                fn wake(self: Arc<Self>) {
+                    self.0.unpark();
+                }
+            }
+
+            let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+            let mut task = std::pin::pin!(Task::spawn(|_| "done"));
+            let result = loop {
+                if let Poll::Ready(result) = task.as_mut().poll(&mut cx) {
+                    break result;
+                }
+                std::thread::park();
+            };
+            assert_eq!(result, Ok("done"));
+        }
+    }
+
+    #[test]
+    fn test_message_queue() {
+        use crate::message_queue::MessageQueue;
+        use crate::scheduler::Priority;
+        use std::sync::Arc;
+
+        let queue = MessageQueue::new();
+        assert!(!queue.push("a", Priority::Low, 1));
+        assert!(!queue.push("b", Priority::Normal, 2));
+        assert!(!queue.push("c", Priority::Normal, 3));
+        assert!(queue.push("b", Priority::Normal, 20));
+        assert!(queue.push_with("a", Priority::Low, 10, |pending, value| *pending += value));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.drain(), vec![("b", 20), ("c", 3), ("a", 11)]);
+
+        queue.push("a", Priority::Low, 1);
+        queue.push("b", Priority::Normal, 2);
+        queue.push("a", Priority::Critical, 3);
+        assert_eq!(queue.pop(), Some(("a", 3)));
+        assert!(queue.contains(&"b"));
+        assert_eq!(queue.remove(&"b"), Some(2));
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop_timeout(Duration::from_millis(5)), None);
+
+        let queue = Arc::new(MessageQueue::new());
+        let producer = {
+            let queue = queue.clone();
+            std::thread::spawn(move || {
+                for i in 0..100 {
+                    queue.push(i % 10, Priority::Normal, i);
+                }
+                queue.push(usize::MAX, Priority::Low, 0);
+            })
+        };
+        let mut received = 0;
+        loop {
+            let (key, _) = queue.pop_blocking();
+            if key == usize::MAX {
+                break;
+            }
+            received += 1;
+        }
+        producer.join().unwrap();
+        assert!((10..=100).contains(&(received + queue.len())));
+    }
+
+    #[test]
+    fn test_handshake() {
+        use crate::handshake::{Compatibility, Handshake, HandshakeError};
+        use crate::version::Version;
+        use bytebuffer::ByteBuffer;
+
+        let server = Handshake::new(*b"TEST", Version::new(0, 2, 1, 0)).features(0b111).require(0b1000);
+
+        let mut buffer = ByteBuffer::new();
+        Handshake::new(*b"TEST", Version::new(0, 2, 0, 5)).features(0b1010).write(&mut buffer);
+        let report = server.read(&mut buffer).unwrap();
+        assert_eq!(report.peer_features, 0b1010);
+        assert_eq!(report.common_features, 0b1010);
+        assert!(report.has(0b10) && !report.has(0b1));
+
+        let mut buffer = ByteBuffer::new();
+        Handshake::new(*b"TEST", Version::new(0, 2, 0, 5)).features(0b1).write(&mut buffer);
+        assert!(matches!(server.read(&mut buffer), Err(HandshakeError::MissingFeatures { missing: 0b1000, .. })));
+
+        let mut buffer = ByteBuffer::new();
+        Handshake::new(*b"TEST", Version::new(0, 3, 0, 0)).write(&mut buffer);
+        assert!(matches!(server.read(&mut buffer), Err(HandshakeError::IncompatibleVersion { .. })));
+
+        let mut buffer = ByteBuffer::new();
+        Handshake::new(*b"NOPE", Version::new(0, 2, 1, 0)).write(&mut buffer);
+        assert!(matches!(server.read(&mut buffer), Err(HandshakeError::WrongMagic { .. })));
+
+        let mut buffer = ByteBuffer::new();
+        buffer.push_bytes(b"TEST");
+        assert!(matches!(server.read(&mut buffer), Err(HandshakeError::Load(_))));
+
+        let at_least = Handshake::new(*b"TEST", Version::new(0, 2, 1, 0)).policy(Compatibility::AtLeast(Version::new(0, 1, 5, 0)));
+        assert!(at_least.check(Version::new(0, 3, 0, 0), 0).is_ok());
+        assert!(at_least.check(Version::new(0, 1, 4, 9), 0).is_err());
+        let exact = at_least.policy(Compatibility::Exact);
+        assert!(exact.check(Version::new(0, 2, 1, 0), 0).is_ok());
+        assert!(exact.check(Version::new(0, 2, 1, 1), 0).is_err());
+        let custom = exact.policy(Compatibility::Custom(|ours, peer| ours.minor() == peer.minor()));
+        assert!(custom.check(Version::new(0, 9, 1, 0), 0).is_ok());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            Handshake::new(*b"TEST", Version::new(0, 2, 3, 0)).features(0b1100).exchange(&mut stream)
+        });
+        let (mut stream, _) = listener.accept().unwrap();
+        let report = server.exchange(&mut stream).unwrap();
+        assert_eq!(report.common_features, 0b1100);
+        assert_eq!(client.join().unwrap().unwrap().common_features, 0b1100);
+    }
+
+    #[test]
+    fn test_print_theme() {
+        use crate::print::{Col, Fmt, Look, Printer, Style, Theme};
+
+        let theme = Theme::plain().with(Style::Error, Look::col(Col::Rgb(255, 0, 0)));
+        let printed = Printer::start().theme(theme).style(Style::Error, "bad").style(Style::Code, "x").to_string();
+        assert!(printed.contains("\x1b[38;2;255;0;0m\x1b[40mbad"));
+        assert!(printed.contains("mx\x1b[0m"));
+
+        assert_eq!(Theme::current().get(Style::Heading), Look::new(Fmt::Bold, Some(Col::White)));
+        Theme::set_default(Theme::light());
+        let printed = Printer::start().style(Style::Code, "code").to_string();
+        Theme::set_default(Theme::dark());
+        assert!(printed.contains("\x1b[35m\x1b[40mcode"));
+        assert!(!Printer::start().style(Style::Code, "code").to_string().contains("\x1b[35m"));
+    }
+
+    #[test]
+    fn test_diff() {
+        use crate::diff::{diff, Change, DiffLine, TextDiff};
+        use mvutils_proc_macro::Diff;
+        use std::collections::HashMap;
+
+        let text = TextDiff::new("a\nb\nc\nd\ne\nf", "a\nc\nd\nx\ne\nf\ng");
+        assert_eq!(text.to_string(), " a\n-b\n c\n d\n+x\n e\n f\n+g\n");
+        assert_eq!(text.lines()[1], DiffLine::Removed("b"));
+        assert_eq!(text.context(0).to_string(), "...\n-b\n...\n+x\n...\n+g\n");
+        assert!(TextDiff::new("same", "same").is_empty());
+        assert!(TextDiff::new("", "new").printer().to_string().contains("+new"));
+
+        #[derive(Diff, Debug, Clone)]
+        enum Shape {
+            Circle { radius: f32 },
+            Square(f32),
+        }
+
+        #[derive(Diff, Debug, Clone)]
+        struct Scene {
+            name: String,
+            shapes: Vec<Shape>,
+            tags: HashMap<String, u32>,
+            parent: Option<Box<Scene>>,
+            #[diff(skip)]
+            #[allow(dead_code)]
+            cache: u64,
+        }
+
+        let old = Scene {
+            name: "level".to_string(),
+            shapes: vec![Shape::Circle { radius: 1.0 }, Shape::Square(2.0)],
+            tags: HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+            parent: None,
+            cache: 0,
+        };
+        let mut new = old.clone();
+        new.shapes[0] = Shape::Circle { radius: 1.5 };
+        new.shapes[1] = Shape::Circle { radius: 2.0 };
+        new.shapes.push(Shape::Square(3.0));
+        new.tags.remove("a");
+        new.tags.insert("b".to_string(), 5);
+        new.cache = 7;
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes.to_string(),
+            "shapes[0].radius: 1.0 -> 1.5\n\
+             shapes[1]: Square(2.0) -> Circle { radius: 2.0 }\n\
+             shapes[2]: added Square(3.0)\n\
+             tags[\"a\"]: removed 1\n\
+             tags[\"b\"]: 2 -> 5\n"
+        );
+        assert_eq!(changes.get("tags[\"b\"]"), Some(&Change::changed("tags[\"b\"]", &2, &5)));
+        assert!(diff(&old, &old.clone()).is_empty());
+
+        new = old.clone();
+        new.parent = Some(Box::new(old.clone()));
+        assert_eq!(diff(&old, &new).iter().next().unwrap().path, "parent");
+        assert_eq!(diff(&1, &2).to_string(), "<value>: 1 -> 2\n");
+    }
+
+    #[test]
+    fn test_static_assertions() {
+        use crate::{assert_align_of, assert_impl, assert_size_of, const_assert};
+
+        const_assert!(std::mem::size_of::<usize>() >= 4);
+        const_assert!(u8::MAX == 255, "u8 should have 8 bits");
+        assert_size_of!([u16; 3], 6);
+        assert_size_of!(Option<Box<u8>>, std::mem::size_of::<usize>());
+        assert_align_of!(u64, std::mem::align_of::<[u64; 2]>());
+        assert_impl!(crate::state::State<u32>: Send + Sync + Clone);
+        assert_impl!(str: Send);
+    }
+
+    #[test]
+    fn test_flags() {
+        use crate::save::Savable;
+        use bytebuffer::ByteBuffer;
+        use mvutils_proc_macro::Flags;
+
+        #[derive(Flags, Debug, Copy, Clone, PartialEq)]
+        enum Permission {
+            Read,
+            Write,
+            Execute,
+            SetUid = 10,
+        }
+
+        let mut flags = Permission::Read | Permission::Execute;
+        assert!(flags.contains(Permission::Read));
+        assert!(!flags.contains(Permission::Read | Permission::Write));
+        assert!(flags.intersects(Permission::Read | Permission::Write));
+        assert_eq!(flags.bits(), 0b101);
+        assert_eq!(flags.len(), 2);
+        flags.insert(Permission::SetUid);
+        flags.remove(Permission::Read);
+        assert_eq!(flags.iter().collect::<Vec<_>>(), vec![Permission::Execute, Permission::SetUid]);
+        assert_eq!(flags.to_string(), "Execute|SetUid");
+        assert_eq!(format!("{:?}", flags), "PermissionFlags(Execute|SetUid)");
+        assert_eq!(!flags, Permission::Read | Permission::Write);
+        assert_eq!(flags - Permission::SetUid, Permission::Execute.flag());
+        assert!((flags | !flags).is_all());
+        assert_eq!(PermissionFlags::all().bits(), 0b1111);
+        assert_eq!(PermissionFlags::from_bits(0b10000), None);
+
+        assert_eq!("Execute | set_uid".parse::<PermissionFlags>(), Ok(flags));
+        assert_eq!(PermissionFlags::try_from(""), Ok(PermissionFlags::empty()));
+        assert!(PermissionFlags::try_from("Read|Delete".to_string()).is_err());
+        assert_eq!([Permission::Read, Permission::Write].into_iter().collect::<PermissionFlags>().bits(), 0b11);
+
+        let mut buffer = ByteBuffer::new();
+        flags.save(&mut buffer);
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(PermissionFlags::load(&mut buffer), Ok(flags));
+        let mut buffer = ByteBuffer::from_bytes(&[0xF0]);
+        assert!(PermissionFlags::load(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_deterministic_save() {
+        use crate::save::{Deterministic, Savable};
+        use bytebuffer::ByteBuffer;
+        use hashbrown::{HashMap, HashSet};
+
+        #[derive(Savable, Debug, PartialEq)]
+        #[savable(deterministic)]
+        struct Assets {
+            names: HashMap<String, u32>,
+            tags: std::collections::HashSet<u64>,
+            nested: Vec<HashMap<u8, HashSet<u16>>>,
+        }
+
+        let assets = |seed: u64| {
+            let mut order = (0..64u64).collect::<Vec<_>>();
+            order.rotate_left(seed as usize);
+            Assets {
+                names: order.iter().map(|i| (format!("asset{}", i), *i as u32)).collect(),
+                tags: order.iter().map(|i| i * 31).collect(),
+                nested: vec![order.iter().map(|i| (*i as u8, (0..*i as u16).collect())).collect()],
+            }
+        };
+        let save = |value: &Assets| {
+            let mut buffer = ByteBuffer::new();
+            value.save(&mut buffer);
+            buffer.into_vec()
+        };
+        let (a, b) = (assets(0), assets(17));
+        assert_eq!(a, b);
+        assert_eq!(save(&a), save(&b));
+        assert_eq!(Assets::load(&mut ByteBuffer::from_vec(save(&a))), Ok(a));
+
+        let set = (0..100u32).collect::<HashSet<_>>();
+        let mut buffer = ByteBuffer::new();
+        set.save(&mut Deterministic::new(&mut buffer));
+        let expected = (0..100u32).collect::<Vec<_>>();
+        buffer.read_u64().unwrap();
+        assert_eq!((0..100).map(|_| buffer.read_u32().unwrap()).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "save_trace")]
+    fn test_save_trace() {
+        use crate::save::trace::{TracingLoader, TracingSaver};
+        use crate::save::Savable;
+        use bytebuffer::ByteBuffer;
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Stats(u16, u32);
+
+        #[derive(Savable, Debug, PartialEq)]
+        enum Item {
+            Empty,
+            Tool { durability: u16, stats: Stats },
+        }
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Slot {
+            index: u8,
+            item: Item,
+        }
+
+        let slot = Slot { index: 3, item: Item::Tool { durability: 250, stats: Stats(1, 2) } };
+        let mut buffer = ByteBuffer::new();
+        let mut saver = TracingSaver::new(&mut buffer);
+        slot.save(&mut saver);
+        let trace = saver.into_trace();
+        let entries = trace
+            .entries()
+            .iter()
+            .map(|e| (e.path.as_str(), e.depth, e.offset, e.len, e.complete))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                ("index", 0, 0, 1, true),
+                ("item", 0, 1, 9, true),
+                ("item.durability", 1, 2, 2, true),
+                ("item.stats", 1, 4, 6, true),
+                ("item.stats.0", 2, 4, 2, true),
+                ("item.stats.1", 2, 6, 4, true),
+            ]
+        );
+        assert_eq!(trace.at(7).unwrap().path, "item.stats.1");
+        assert_eq!(trace.at(10), None);
+        assert!(trace.to_string().contains("    0\n"));
+
+        let mut loader = TracingLoader::new(&mut buffer);
+        assert_eq!(Slot::load(&mut loader), Ok(slot));
+        assert_eq!(loader.into_trace(), trace);
+
+        let mut bytes = ByteBuffer::from_bytes(&buffer.as_bytes()[..8]);
+        let mut loader = TracingLoader::new(&mut bytes);
+        assert!(Slot::load(&mut loader).is_err());
+        let trace = loader.into_trace();
+        let failed = trace.failed().unwrap();
+        assert_eq!((failed.path.as_str(), failed.offset, failed.complete), ("item.stats.1", 6, false));
+        assert!(!trace.entries()[1].complete);
+        assert!(trace.to_string().contains("1 (incomplete)"));
+    }
+
+    #[test]
+    fn test_verify_derive() {
+        use crate::utils::{Verify, Violation};
+        use crate::Verify;
+        use std::collections::BTreeMap;
+
+        fn even(value: &u8) -> Result<(), String> {
+            if value % 2 == 0 {
+                Ok(())
+            } else {
+                Err(format!("{} is odd", value))
+            }
+        }
+
+        fn ordered(bounds: &Bounds) -> Result<(), String> {
+            if bounds.min <= bounds.max {
+                Ok(())
+            } else {
+                Err("min is greater than max".to_string())
+            }
+        }
+
+        #[derive(Verify)]
+        #[verify(with = ordered)]
+        struct Bounds {
+            #[verify(range = 0.0..=1.0)]
+            min: f32,
+            #[verify(range = 0.0..=1.0)]
+            max: f32,
+        }
+
+        struct Broken;
+
+        impl Verify for Broken {
+            fn verify(&self) -> bool {
+                false
+            }
+        }
+
+        #[derive(Verify)]
+        enum Shape {
+            Point,
+            Circle(#[verify(range = 1..=10, with = even)] u8),
+            Boxed { bounds: Bounds, #[verify(skip)] broken: Broken },
+        }
+
+        #[derive(Verify)]
+        struct Scene {
+            shapes: Vec<Shape>,
+            named: BTreeMap<&'static str, (Option<Bounds>, Broken)>,
+        }
+
+        let scene = Scene {
+            shapes: vec![Shape::Point, Shape::Circle(4), Shape::Boxed { bounds: Bounds { min: 0.0, max: 1.0 }, broken: Broken }],
+            named: BTreeMap::new(),
+        };
+        assert!(scene.verify());
+        assert_eq!(scene.report(), Ok(()));
+
+        let mut scene = scene;
+        scene.shapes.push(Shape::Circle(11));
+        scene.shapes.push(Shape::Boxed { bounds: Bounds { min: 0.5, max: 0.25 }, broken: Broken });
+        scene.named.insert("sun", (Some(Bounds { min: -1.0, max: 0.0 }), Broken));
+        assert!(!scene.verify());
+        let report = scene.report().unwrap_err();
+        assert_eq!(
+            report.0,
+            [
+                Violation::new("shapes[3].0", "11 is not in the range 1 ..= 10"),
+                Violation::new("shapes[3].0", "11 is odd"),
+                Violation::new("shapes[4].bounds", "min is greater than max"),
+                Violation::new("named[\"sun\"].0.min", "-1.0 is not in the range 0.0 ..= 1.0"),
+                Violation::new("named[\"sun\"].1", "verification failed"),
+            ]
+        );
+        assert_eq!(report.get("shapes[4].bounds").unwrap().to_string(), "shapes[4].bounds: min is greater than max");
+    }
+
+    #[test]
+    fn test_service_registry() {
+        use crate::services::ServiceRegistry;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        trait Storage: Send + Sync {
+            fn name(&self) -> &str;
+        }
+
+        struct Disk;
+        struct Memory;
+
+        impl Storage for Disk {
+            fn name(&self) -> &str {
+                "disk"
+            }
+        }
+
+        impl Storage for Memory {
+            fn name(&self) -> &str {
+                "memory"
+            }
+        }
+
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+        let services = Arc::new(ServiceRegistry::new());
+        assert!(services.get::<u32>().is_none());
+        services.register(5u32);
+        services.register::<Box<dyn Storage>>(Box::new(Disk));
+        services.provide(|| {
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            vec![1, 2, 3]
+        });
+        assert!(services.contains::<Vec<i32>>());
+        assert_eq!(CREATED.load(Ordering::SeqCst), 0);
+
+        let threads = (0..4)
+            .map(|_| {
+                let services = services.clone();
+                std::thread::spawn(move || services.require::<Vec<i32>>())
+            })
+            .collect::<Vec<_>>();
+        let vecs = threads.into_iter().map(|t| t.join().unwrap()).collect::<Vec<_>>();
+        assert!(vecs.iter().all(|v| Arc::ptr_eq(v, &vecs[0])));
+        assert!(CREATED.load(Ordering::SeqCst) >= 1);
+        assert_eq!(*services.require::<u32>(), 5);
+
+        {
+            let _memory = services.override_with::<Box<dyn Storage>>(Box::new(Memory));
+            assert_eq!(services.require::<Box<dyn Storage>>().name(), "memory");
+            let _number = services.override_with(6u32);
+            let inner = services.override_with(7u32);
+            assert_eq!(*services.require::<u32>(), 7);
+            drop(inner);
+            assert_eq!(*services.require::<u32>(), 6);
+        }
+        assert_eq!(services.require::<Box<dyn Storage>>().name(), "disk");
+        assert_eq!(*services.require::<u32>(), 5);
+
+        assert!(services.remove::<u32>());
+        assert!(!services.remove::<u32>());
+        assert!(services.get::<u32>().is_none());
+    }
+
+    #[test]
+    fn test_chunked_save() {
+        use crate::save::chunked::{load_chunk, split, Chunked};
+        use bytebuffer::ByteBuffer;
+
+        #[derive(Savable, Debug, PartialEq)]
+        struct Tile {
+            id: u16,
+            name: String,
+        }
+
+        let tiles = (0..1000u16).map(|id| Tile { id, name: format!("tile{}", id) }).collect::<Vec<_>>();
+        let chunked = Chunked::new().chunk_len(64).threads(4);
+        let mut buffer = ByteBuffer::new();
+        chunked.save(&tiles, &mut buffer);
+        let bytes = buffer.into_vec();
+
+        assert_eq!(chunked.load::<Tile>(&mut ByteBuffer::from_bytes(&bytes)), Ok(tiles));
+        let single = Chunked::new().threads(1).load::<Tile>(&mut ByteBuffer::from_bytes(&bytes)).unwrap();
+        assert_eq!(single.len(), 1000);
+
+        let chunks = split(&mut ByteBuffer::from_bytes(&bytes)).unwrap();
+        assert_eq!(chunks.len(), 16);
+        let last = load_chunk::<Tile>(&chunks[15]).unwrap();
+        assert_eq!(last.len(), 1000 - 15 * 64);
+        assert_eq!(last[0], Tile { id: 960, name: "tile960".to_string() });
+        assert!(load_chunk::<u8>(&chunks[0]).is_err());
+
+        let mut empty = ByteBuffer::new();
+        chunked.save::<Tile>(&[], &mut empty);
+        assert_eq!(chunked.load::<Tile>(&mut empty), Ok(Vec::new()));
+        assert!(chunked.load::<Tile>(&mut ByteBuffer::from_bytes(&bytes[..bytes.len() - 1])).is_err());
+    }
+
+    #[test]
+    fn test_budget_manager() {
+        use crate::budget::BudgetManager;
+        use crate::bytebuffer::BufferPool;
+        use crate::cache::LruCache;
+        use crate::sync::Mutex;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let big = Arc::new(Mutex::new(LruCache::<u32, [u8; 1000]>::new(100)));
+        let small = Arc::new(Mutex::new(LruCache::<u32, [u8; 1000]>::new(100)));
+        let pool = Arc::new(BufferPool::with_classes(&[1024], 16));
+        let custom = Arc::new(AtomicUsize::new(5000));
+
+        let budget = BudgetManager::new(60_000);
+        budget.register("big", 3.0, big.clone());
+        budget.register("small", 1.0, small.clone());
+        budget.register("pool", 0.0, pool.clone());
+        let handle = {
+            let (usage, shrink) = (custom.clone(), custom.clone());
+            budget.register_fn("custom", 1.0, move || usage.load(Ordering::SeqCst), move |target| shrink.store(target, Ordering::SeqCst))
+        };
+
+        for i in 0..20 {
+            big.lock().put(i, [0; 1000]);
+            small.lock().put(i / 2, [0; 1000]);
+        }
+        let buffers = (0..4).map(|_| pool.acquire(1000)).collect::<Vec<_>>();
+        drop(buffers);
+        assert_eq!(pool.idle(), 4);
+        assert!(!budget.is_exceeded());
+        assert_eq!(budget.enforce(), 0);
+
+        for i in 20..50 {
+            big.lock().put(i, [0; 1000]);
+        }
+        let before = budget.usage();
+        assert!(budget.is_exceeded());
+        let freed = budget.enforce();
+        assert_eq!(before - freed, budget.usage());
+        assert!(budget.usage() <= 60_000);
+        assert_eq!(small.lock().len(), 10);
+        assert_eq!(custom.load(Ordering::SeqCst), 5000);
+        assert!(pool.idle() < 4);
+        assert!(big.lock().contains(&49));
+        assert!(!big.lock().contains(&0));
+
+        let report = budget.report();
+        assert_eq!(report.iter().map(|u| u.name.as_str()).collect::<Vec<_>>(), ["big", "small", "pool", "custom"]);
+        assert_eq!(report[0].share, 36_000);
+
+        drop(handle);
+        assert_eq!(budget.report().len(), 3);
+        assert!(budget.unregister("pool"));
+        budget.set_budget(10_000);
+        budget.enforce();
+        assert!(budget.usage() <= 10_000);
+    }
+
+    #[test]
+    fn test_deep_size_of() {
+        use crate::budget::Budgeted;
+        use crate::cache::LruCache;
+        use crate::deep_size::DeepSizeOf;
+        use crate::sync::Mutex;
+        use crate::DeepSizeOf;
+        use std::collections::{BTreeMap, HashMap};
+        use std::mem::size_of;
+        use std::sync::Arc;
+
+        #[derive(DeepSizeOf)]
+        struct Mesh(Vec<f32>, #[deep_size(skip)] Arc<String>);
+
+        #[derive(DeepSizeOf)]
+        enum Asset {
+            Missing,
+            Text { content: String },
+            Mesh(Box<Mesh>),
+        }
+
+        let shared = Arc::new("shared".repeat(100));
+        let mesh = Mesh(Vec::with_capacity(10), shared.clone());
+        assert_eq!(mesh.heap_size(), 40);
+        assert_eq!(Asset::Missing.heap_size(), 0);
+        assert_eq!(Asset::Text { content: String::with_capacity(7) }.heap_size(), 7);
+        assert_eq!(Asset::Mesh(Box::new(mesh)).heap_size(), size_of::<Mesh>() + 40);
+        assert_eq!(Arc::new(5u64).heap_size(), 2 * size_of::<usize>() + 8);
+        assert_eq!(Some("ab".to_string()).deep_size_of(), size_of::<Option<String>>() + 2);
+
+        let nested = vec![vec![1u8; 3]; 2];
+        assert_eq!(nested.heap_size(), 2 * size_of::<Vec<u8>>() + 6);
+        let mut map = HashMap::with_capacity(3);
+        map.insert(1u32, String::from("one"));
+        assert!(map.heap_size() >= 3 * size_of::<(u32, String)>() + 3);
+        assert_eq!(HashMap::<u32, u32>::new().heap_size(), 0);
+        let tree = (0..10u16).map(|i| (i, i)).collect::<BTreeMap<_, _>>();
+        assert!(tree.heap_size() >= 10 * 4);
+
+        let cache = Mutex::new(LruCache::<u32, String>::new(10));
+        cache.lock().put(1, "a".repeat(1000));
+        cache.lock().put(2, "b".repeat(10));
+        assert!(cache.memory_usage() > 1010);
+        cache.shrink_to(500);
+        assert!(cache.memory_usage() <= 500);
+        assert!(cache.lock().contains(&2));
+    }
+
+    #[test]
+    fn test_ord_float() {
+        use crate::ord_float::{cmp_f64, OrdF32, OrdF64};
+        use crate::save::Savable;
+        use bytebuffer::ByteBuffer;
+        use std::collections::{BTreeSet, HashSet};
+
+        assert_eq!(OrdF32(f32::NAN), OrdF32(-f32::NAN));
+        assert!(OrdF32::NAN > OrdF32::INFINITY);
+        assert!(OrdF32::NEG_INFINITY < OrdF32(-1e30));
+        assert_eq!(OrdF32(-0.0), OrdF32(0.0));
+        assert!(OrdF64(1.5) > 1.0);
+        assert_eq!(OrdF64(2.0), 2.0);
+
+        let set = [0.0, -0.0, f64::NAN, f64::NAN, 1.0].into_iter().map(OrdF64).collect::<HashSet<_>>();
+        assert_eq!(set.len(), 3);
+        let sorted = [3.0, f32::NAN, -2.0, 0.5, 3.0].into_iter().map(OrdF32::from).collect::<BTreeSet<_>>();
+        assert_eq!(sorted.iter().map(|f| f.to_string()).collect::<Vec<_>>(), ["-2", "0.5", "3", "NaN"]);
+
+        let mut values = [1.0, f64::NAN, -1.0];
+        values.sort_by(cmp_f64);
+        assert_eq!(values[..2], [-1.0, 1.0]);
+
+        let mut x = OrdF32(1.0) + OrdF32(2.0) * OrdF32(3.0);
+        x -= OrdF32(1.0);
+        assert_eq!(-x, OrdF32(-6.0));
+        assert_eq!("2.5".parse::<OrdF64>(), Ok(OrdF64(2.5)));
+        assert_eq!(f32::from(OrdF32(4.0)), 4.0);
+
+        let mut buffer = ByteBuffer::new();
+        OrdF64(0.25).save(&mut buffer);
+        assert_eq!(buffer.len(), 8);
+        assert_eq!(OrdF64::load(&mut buffer), Ok(OrdF64(0.25)));
+    }
+
+    #[test]
+    fn test_graph() {
+        use crate::graph::{Cycle, Graph};
+
+        let mut graph = Graph::new();
+        graph.add_node(5);
+        graph.add_edge(3, 1);
+        graph.add_edge(4, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(1, 2);
+        assert!(!graph.add_node(4));
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.edge_count(), 3);
+        assert!(graph.contains_edge(&3, &1));
+        assert!(!graph.contains_edge(&1, &3));
+        assert_eq!(graph.predecessors(&1).collect::<Vec<_>>(), [&3, &4]);
+        assert_eq!(graph.successors(&1).collect::<Vec<_>>(), [&2]);
+        assert_eq!(graph.topological_sort().unwrap(), [&5, &3, &4, &1, &2]);
+        assert!(!graph.has_cycle());
+
+        graph.add_edge(2, 6);
+        graph.add_edge(6, 4);
+        graph.add_edge(6, 7);
+        assert_eq!(graph.topological_sort(), Err(Cycle(vec![&1, &2, &6, &4])));
+        assert_eq!(graph.find_cycle().unwrap().to_string(), "1 -> 2 -> 6 -> 4 -> 1");
+        assert_eq!(
+            graph.strongly_connected_components(),
+            [vec![&5], vec![&3], vec![&1, &4, &2, &6], vec![&7]]
+        );
+
+        let chain = Graph::from_edges((0..100_000).map(|i| (i, i + 1)));
+        assert_eq!(chain.topological_sort().unwrap().len(), 100_001);
+        assert_eq!(chain.strongly_connected_components().len(), 100_001);
+        let mut ring = chain;
+        ring.add_edge(100_000, 0);
+        assert_eq!(ring.find_cycle().unwrap().0.len(), 100_001);
+        assert_eq!(ring.strongly_connected_components().len(), 1);
+    }
 }
@@ -0,0 +1,193 @@
+use crate::cache::{CacheStats, LruCache};
+use hashbrown::HashMap;
+use crate::sync::Mutex;
+use std::hash::Hash;
+use std::sync::{Arc, OnceLock};
+
+enum Store<K, V> {
+    Unbounded(HashMap<K, Arc<OnceLock<V>>>),
+    Lru(LruCache<K, Arc<OnceLock<V>>>),
+}
+
+struct Inner<K, V> {
+    store: Store<K, V>,
+    stats: CacheStats,
+}
+
+/// Wraps a function and caches its results by argument. The function is called at most once per
+/// key at a time: concurrent callers with the same key wait for the first one to finish.
+///
+/// The function must not call the memo with the key it is computing, as that would deadlock.
+///
+/// ```
+/// use mvutils::memo::Memo;
+///
+/// let square = Memo::new(|x: &u64| x * x);
+/// assert_eq!(square.get(4), 16);
+/// assert_eq!(square.stats().misses, 1);
+/// ```
+pub struct Memo<K, V, F = fn(&K) -> V> {
+    f: F,
+    inner: Mutex<Inner<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, F: Fn(&K) -> V> Memo<K, V, F> {
+    /// A memo which keeps all results.
+    pub fn new(f: F) -> Self {
+        Memo {
+            f,
+            inner: Mutex::new(Inner {
+                store: Store::Unbounded(HashMap::new()),
+                stats: CacheStats::default(),
+            }),
+        }
+    }
+
+    /// A memo which keeps the results of the `capacity` most recently used keys.
+    pub fn with_capacity(capacity: usize, f: F) -> Self {
+        Memo {
+            f,
+            inner: Mutex::new(Inner {
+                store: Store::Lru(LruCache::new(capacity)),
+                stats: CacheStats::default(),
+            }),
+        }
+    }
+
+    /// Return the cached result for the key, computing it first if necessary.
+    pub fn get(&self, key: K) -> V {
+        let slot = {
+            let mut inner = self.inner.lock();
+            let Inner { store, stats } = &mut *inner;
+            let existing = match store {
+                Store::Unbounded(map) => map.get(&key).cloned(),
+                Store::Lru(lru) => lru.get(&key).cloned(),
+            };
+            match existing {
+                Some(slot) => {
+                    stats.hits += 1;
+                    slot
+                }
+                None => {
+                    stats.misses += 1;
+                    let slot = Arc::new(OnceLock::new());
+                    match store {
+                        Store::Unbounded(map) => {
+                            map.insert(key.clone(), slot.clone());
+                        }
+                        Store::Lru(lru) => {
+                            if lru.len() == lru.capacity() {
+                                stats.evictions += 1;
+                            }
+                            lru.put(key.clone(), slot.clone());
+                        }
+                    }
+                    slot
+                }
+            }
+        };
+        slot.get_or_init(|| (self.f)(&key)).clone()
+    }
+
+    /// Return the cached result for the key without computing it.
+    pub fn peek(&self, key: &K) -> Option<V> {
+        let inner = self.inner.lock();
+        let slot = match &inner.store {
+            Store::Unbounded(map) => map.get(key),
+            Store::Lru(lru) => lru.peek(key),
+        };
+        slot.and_then(|s| s.get().cloned())
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.peek(key).is_some()
+    }
+
+    /// The number of cached keys, including ones whose result is still being computed.
+    pub fn len(&self) -> usize {
+        match &self.inner.lock().store {
+            Store::Unbounded(map) => map.len(),
+            Store::Lru(lru) => lru.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget the result for the key, so it is computed again on the next call.
+    pub fn invalidate(&self, key: &K) {
+        match &mut self.inner.lock().store {
+            Store::Unbounded(map) => {
+                map.remove(key);
+            }
+            Store::Lru(lru) => {
+                lru.remove(key);
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        match &mut self.inner.lock().store {
+            Store::Unbounded(map) => map.clear(),
+            Store::Lru(lru) => lru.clear(),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.inner.lock().stats
+    }
+}
+
+/// Define functions whose results are cached by their arguments in a global [`Memo`]. Prefix a
+/// function with `#[capacity(n)]` to only keep the results of the `n` most recently used arguments.
+/// All argument types must implement `Hash`, `Eq` and `Clone`, and the return type must implement
+/// `Clone`.
+///
+/// ```
+/// use mvutils::memoize;
+///
+/// memoize! {
+///     fn fib(n: u64) -> u64 {
+///         if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+///     }
+///
+///     #[capacity(16)]
+///     pub fn greet(name: String, excited: bool) -> String {
+///         format!("Hello, {name}{}", if excited { "!" } else { "." })
+///     }
+/// }
+///
+/// assert_eq!(fib(80), 23416728348467685);
+/// assert_eq!(greet("World".to_string(), true), "Hello, World!");
+/// ```
+#[macro_export]
+macro_rules! memoize {
+    {
+        $(
+            $(#[capacity($cap:expr)])?
+            $v:vis fn $name:ident($($arg:ident: $t:ty),* $(,)?) -> $ret:ty $body:block
+        )*
+    } => {
+        $(
+            $v fn $name($($arg: $t),*) -> $ret {
+                fn __compute(key: &($($t,)*)) -> $ret {
+                    let ($($arg,)*) = ::std::clone::Clone::clone(key);
+                    $body
+                }
+
+                $crate::lazy! {
+                    static MEMO: $crate::memo::Memo<($($t,)*), $ret> = $crate::memoize!(@new __compute $(, $cap)?);
+                }
+
+                MEMO.get(($($arg,)*))
+            }
+        )*
+    };
+    (@new $f:ident) => {
+        $crate::memo::Memo::new($f as fn(&_) -> _)
+    };
+    (@new $f:ident, $cap:expr) => {
+        $crate::memo::Memo::with_capacity($cap, $f as fn(&_) -> _)
+    };
+}
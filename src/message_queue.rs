@@ -0,0 +1,172 @@
+//! A thread-safe queue of keyed messages, where a newer message replaces a pending one with the
+//! same key.
+//!
+//! ```
+//! use mvutils::message_queue::MessageQueue;
+//! use mvutils::scheduler::Priority;
+//!
+//! let queue = MessageQueue::new();
+//! queue.push("player", Priority::Normal, (1.0, 2.0));
+//! queue.push("chat", Priority::Low, (0.0, 0.0));
+//! queue.push("player", Priority::Normal, (3.0, 4.0));
+//!
+//! assert_eq!(queue.len(), 2);
+//! assert_eq!(queue.pop(), Some(("player", (3.0, 4.0))));
+//! assert_eq!(queue.pop(), Some(("chat", (0.0, 0.0))));
+//! ```
+
+use crate::scheduler::Priority;
+use hashbrown::HashMap;
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+const PRIORITIES: usize = 4;
+
+struct Inner<K, V> {
+    /// The keys in order of arrival, one queue per priority from low to critical.
+    queues: [VecDeque<K>; PRIORITIES],
+    pending: HashMap<K, (Priority, V)>,
+}
+
+impl<K: Hash + Eq + Clone, V> Inner<K, V> {
+    fn pop(&mut self) -> Option<(K, V)> {
+        let key = self.queues.iter_mut().rev().find_map(|q| q.pop_front())?;
+        let (_, value) = self.pending.remove(&key).expect("Queued key without a pending message");
+        Some((key, value))
+    }
+}
+
+/// A queue of messages identified by keys, which coalesces messages with the same key.
+///
+/// Pushing a message whose key is still pending replaces its value, or merges it with
+/// [`MessageQueue::push_with`], keeping the position of the pending message. If the new message
+/// has a higher [`Priority`], the pending one moves to the back of that priority instead. Messages
+/// are popped by priority, and in order of arrival within a priority. Both ends can be used from
+/// any number of threads.
+pub struct MessageQueue<K, V> {
+    inner: Mutex<Inner<K, V>>,
+    condvar: Condvar,
+}
+
+impl<K: Hash + Eq + Clone, V> MessageQueue<K, V> {
+    pub fn new() -> Self {
+        MessageQueue {
+            inner: Mutex::new(Inner {
+                queues: Default::default(),
+                pending: HashMap::new(),
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Push a message, replacing the pending message with the same key. Returns whether a pending
+    /// message was replaced.
+    pub fn push(&self, key: K, priority: Priority, value: V) -> bool {
+        self.push_with(key, priority, value, |pending, value| *pending = value)
+    }
+
+    /// Push a message, merging it into the pending message with the same key if there is one.
+    /// Returns whether the message was merged.
+    pub fn push_with(&self, key: K, priority: Priority, value: V, merge: impl FnOnce(&mut V, V)) -> bool {
+        let mut guard = self.inner.lock();
+        let inner = &mut *guard;
+        let coalesced = match inner.pending.get_mut(&key) {
+            Some((pending_priority, pending)) => {
+                merge(pending, value);
+                let old = *pending_priority;
+                if priority > old {
+                    *pending_priority = priority;
+                    inner.queues[old as usize].retain(|k| *k != key);
+                    inner.queues[priority as usize].push_back(key);
+                }
+                true
+            }
+            None => {
+                inner.pending.insert(key.clone(), (priority, value));
+                inner.queues[priority as usize].push_back(key);
+                false
+            }
+        };
+        drop(guard);
+        if !coalesced {
+            self.condvar.notify_one();
+        }
+        coalesced
+    }
+
+    /// Pop the oldest message of the highest priority.
+    pub fn pop(&self) -> Option<(K, V)> {
+        self.inner.lock().pop()
+    }
+
+    /// Wait until a message is available and pop it.
+    pub fn pop_blocking(&self) -> (K, V) {
+        let mut inner = self.inner.lock();
+        loop {
+            if let Some(message) = inner.pop() {
+                return message;
+            }
+            self.condvar.wait(&mut inner);
+        }
+    }
+
+    /// Wait until a message is available or the timeout elapsed.
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<(K, V)> {
+        let deadline = Instant::now() + timeout;
+        let mut inner = self.inner.lock();
+        loop {
+            if let Some(message) = inner.pop() {
+                return Some(message);
+            }
+            if self.condvar.wait_until(&mut inner, deadline).timed_out() {
+                return inner.pop();
+            }
+        }
+    }
+
+    /// Pop all messages in the order [`MessageQueue::pop`] would return them.
+    pub fn drain(&self) -> Vec<(K, V)> {
+        let mut inner = self.inner.lock();
+        let mut messages = Vec::with_capacity(inner.pending.len());
+        while let Some(message) = inner.pop() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// Remove the pending message with the given key.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut inner = self.inner.lock();
+        let (priority, value) = inner.pending.remove(key)?;
+        inner.queues[priority as usize].retain(|k| k != key);
+        Some(value)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.inner.lock().pending.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock();
+        inner.pending.clear();
+        for queue in &mut inner.queues {
+            queue.clear();
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Default for MessageQueue<K, V> {
+    fn default() -> Self {
+        MessageQueue::new()
+    }
+}
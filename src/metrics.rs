@@ -0,0 +1,295 @@
+use crate as mvutils;
+use crate::lazy;
+use crate::platform::{self, Instant};
+use hashbrown::HashMap;
+use mvutils_proc_macro::Savable;
+use crate::sync::{Mutex, RwLock};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+lazy! {
+    static GLOBAL_METRICS: Metrics = Metrics::new();
+}
+
+/// A monotonically increasing counter. Cloning returns a handle to the same counter.
+#[derive(Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that can go up and down. Cloning returns a handle to the same gauge.
+#[derive(Clone, Default)]
+pub struct Gauge(Arc<AtomicI64>);
+
+impl Gauge {
+    pub fn set(&self, value: i64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn add(&self, n: i64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn sub(&self, n: i64) {
+        self.0.fetch_sub(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Aggregated durations recorded by a [`Timer`].
+#[derive(Savable, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TimerStats {
+    pub count: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl TimerStats {
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total.as_nanos() / self.count as u128) as u64)
+        }
+    }
+}
+
+/// Records durations. Cloning returns a handle to the same timer.
+#[derive(Clone, Default)]
+pub struct Timer(Arc<Mutex<TimerStats>>);
+
+impl Timer {
+    pub fn record(&self, duration: Duration) {
+        let mut stats = self.0.lock();
+        if stats.count == 0 || duration < stats.min {
+            stats.min = duration;
+        }
+        stats.max = stats.max.max(duration);
+        stats.total += duration;
+        stats.count += 1;
+    }
+
+    /// Start timing, the duration is recorded once the returned guard is dropped.
+    pub fn start(&self) -> TimerGuard {
+        TimerGuard {
+            timer: self.clone(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Time the execution of a function.
+    pub fn time<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.start();
+        f()
+    }
+
+    pub fn stats(&self) -> TimerStats {
+        self.0.lock().clone()
+    }
+}
+
+pub struct TimerGuard {
+    timer: Timer,
+    start: Instant,
+}
+
+impl Drop for TimerGuard {
+    fn drop(&mut self) {
+        self.timer.record(self.start.elapsed());
+    }
+}
+
+/// A registry of named counters, gauges and timers. Metrics are created the first time they are
+/// requested, requesting the same name again returns a handle to the same metric.
+#[derive(Default)]
+pub struct Metrics {
+    counters: RwLock<HashMap<String, Counter>>,
+    gauges: RwLock<HashMap<String, Gauge>>,
+    timers: RwLock<HashMap<String, Timer>>,
+}
+
+fn get_or_create<T: Clone + Default>(map: &RwLock<HashMap<String, T>>, name: &str) -> T {
+    if let Some(metric) = map.read().get(name) {
+        return metric.clone();
+    }
+    map.write().entry(name.to_string()).or_default().clone()
+}
+
+fn sorted<T, R>(map: &RwLock<HashMap<String, T>>, f: impl Fn(&T) -> R) -> Vec<(String, R)> {
+    let mut values = map.read().iter().map(|(name, metric)| (name.clone(), f(metric))).collect::<Vec<_>>();
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    values
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    pub fn global() -> &'static Metrics {
+        &GLOBAL_METRICS
+    }
+
+    pub fn counter(&self, name: &str) -> Counter {
+        get_or_create(&self.counters, name)
+    }
+
+    pub fn gauge(&self, name: &str) -> Gauge {
+        get_or_create(&self.gauges, name)
+    }
+
+    pub fn timer(&self, name: &str) -> Timer {
+        get_or_create(&self.timers, name)
+    }
+
+    /// Remove all metrics. Existing handles keep working, but are no longer part of snapshots.
+    pub fn clear(&self) {
+        self.counters.write().clear();
+        self.gauges.write().clear();
+        self.timers.write().clear();
+    }
+
+    /// Capture the current value of all metrics, sorted by name.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            time: platform::system_now(),
+            counters: sorted(&self.counters, Counter::get),
+            gauges: sorted(&self.gauges, Gauge::get),
+            timers: sorted(&self.timers, Timer::stats),
+        }
+    }
+}
+
+/// The values of all metrics at a point in time. Displays as one `name value` line per metric.
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub struct MetricsSnapshot {
+    pub time: SystemTime,
+    pub counters: Vec<(String, u64)>,
+    pub gauges: Vec<(String, i64)>,
+    pub timers: Vec<(String, TimerStats)>,
+}
+
+impl Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in &self.counters {
+            writeln!(f, "{name} {value}")?;
+        }
+        for (name, value) in &self.gauges {
+            writeln!(f, "{name} {value}")?;
+        }
+        for (name, stats) in &self.timers {
+            writeln!(f, "{name}_count {}", stats.count)?;
+            writeln!(f, "{name}_total_us {}", stats.total.as_micros())?;
+            writeln!(f, "{name}_mean_us {}", stats.mean().as_micros())?;
+            writeln!(f, "{name}_min_us {}", stats.min.as_micros())?;
+            writeln!(f, "{name}_max_us {}", stats.max.as_micros())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "net")]
+pub use remote::*;
+
+#[cfg(feature = "net")]
+mod remote {
+    use crate as mvutils;
+    use crate::metrics::MetricsSnapshot;
+    use crate::net::MessageStream;
+    use crate::platform;
+    use mvutils_proc_macro::Savable;
+    use std::io;
+    use std::net::ToSocketAddrs;
+    use std::time::SystemTime;
+
+    #[derive(Savable, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+    pub enum Level {
+        Trace,
+        Debug,
+        Info,
+        Warn,
+        Error,
+    }
+
+    #[derive(Savable, Clone, Debug, PartialEq)]
+    pub struct LogRecord {
+        pub time: SystemTime,
+        pub level: Level,
+        pub target: String,
+        pub message: String,
+    }
+
+    impl LogRecord {
+        pub fn new(level: Level, target: &str, message: impl ToString) -> Self {
+            LogRecord {
+                time: platform::system_now(),
+                level,
+                target: target.to_string(),
+                message: message.to_string(),
+            }
+        }
+    }
+
+    /// A message sent by a [`RemoteSink`].
+    #[derive(Savable, Clone, Debug, PartialEq)]
+    pub enum Telemetry {
+        Log(LogRecord),
+        Metrics(MetricsSnapshot),
+    }
+
+    /// Ships log records and metric snapshots to a collector over a [`MessageStream`]. The collector
+    /// side receives them using a `MessageStream<Telemetry>`.
+    pub struct RemoteSink {
+        stream: MessageStream<Telemetry>,
+        level: Level,
+    }
+
+    impl RemoteSink {
+        pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+            let mut stream = MessageStream::connect(addr)?;
+            stream.set_compression(Some(6));
+            Ok(RemoteSink::new(stream))
+        }
+
+        pub fn new(stream: MessageStream<Telemetry>) -> Self {
+            RemoteSink {
+                stream,
+                level: Level::Trace,
+            }
+        }
+
+        /// Records below this level are not sent.
+        pub fn set_level(&mut self, level: Level) {
+            self.level = level;
+        }
+
+        pub fn log(&mut self, record: LogRecord) -> io::Result<()> {
+            if record.level < self.level {
+                return Ok(());
+            }
+            self.stream.send(&Telemetry::Log(record))
+        }
+
+        pub fn metrics(&mut self, snapshot: MetricsSnapshot) -> io::Result<()> {
+            self.stream.send(&Telemetry::Metrics(snapshot))
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::save::Savable;
+use bytebuffer::ByteBuffer;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const FLAG_COMPRESSED: u8 = 1;
+
+/// The default maximum frame size of 16 MiB.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Frames smaller than this are never compressed, even if compression is enabled.
+pub const COMPRESSION_THRESHOLD: usize = 256;
+
+/// A blocking, typed message transport over a [`TcpStream`].
+///
+/// Each message is saved using [`Savable`] and sent as a frame: a big endian `u32` length, a flag
+/// byte and the (optionally deflate compressed) payload. Received frames larger than the maximum
+/// frame size are rejected before allocating. If a read times out in the middle of a frame, the
+/// stream is left in an undefined state and should be closed.
+pub struct MessageStream<T: Savable> {
+    stream: TcpStream,
+    compression: Option<u8>,
+    max_frame_size: usize,
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T: Savable> MessageStream<T> {
+    pub fn new(stream: TcpStream) -> Self {
+        MessageStream {
+            stream,
+            compression: None,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(MessageStream::new(stream))
+    }
+
+    pub fn connect_timeout(addr: &SocketAddr, timeout: Duration) -> io::Result<Self> {
+        let stream = TcpStream::connect_timeout(addr, timeout)?;
+        stream.set_nodelay(true)?;
+        Ok(MessageStream::new(stream))
+    }
+
+    /// Accept the next connection from the listener.
+    pub fn accept(listener: &TcpListener) -> io::Result<(Self, SocketAddr)> {
+        let (stream, addr) = listener.accept()?;
+        stream.set_nodelay(true)?;
+        Ok((MessageStream::new(stream), addr))
+    }
+
+    /// Enable deflate compression of outgoing messages with a level from 0 to 10, or disable it.
+    /// Compressed messages are always accepted when receiving.
+    pub fn set_compression(&mut self, level: Option<u8>) {
+        self.compression = level;
+    }
+
+    pub fn set_max_frame_size(&mut self, size: usize) {
+        self.max_frame_size = size;
+    }
+
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(timeout)
+    }
+
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_write_timeout(timeout)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.local_addr()
+    }
+
+    /// Clone the stream, for example to send and receive on different threads.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(MessageStream {
+            stream: self.stream.try_clone()?,
+            compression: self.compression,
+            max_frame_size: self.max_frame_size,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn shutdown(&self) -> io::Result<()> {
+        self.stream.shutdown(Shutdown::Both)
+    }
+
+    pub fn into_inner(self) -> TcpStream {
+        self.stream
+    }
+
+    pub fn send(&mut self, message: &T) -> io::Result<()> {
+        let mut buffer = ByteBuffer::new();
+        message.save(&mut buffer);
+        let mut payload = buffer.into_vec();
+
+        let mut flags = 0;
+        if let Some(level) = self.compression {
+            if payload.len() >= COMPRESSION_THRESHOLD {
+                payload = miniz_oxide::deflate::compress_to_vec(&payload, level);
+                flags |= FLAG_COMPRESSED;
+            }
+        }
+
+        let len = u32::try_from(payload.len() + 1)
+            .ok()
+            .filter(|len| *len as usize <= self.max_frame_size)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Message exceeds the maximum frame size"))?;
+
+        let mut frame = Vec::with_capacity(payload.len() + 5);
+        frame.extend_from_slice(&len.to_be_bytes());
+        frame.push(flags);
+        frame.extend_from_slice(&payload);
+        self.stream.write_all(&frame)?;
+        self.stream.flush()
+    }
+
+    /// Block until the next message is received.
+    pub fn recv(&mut self) -> io::Result<T> {
+        let mut header = [0u8; 5];
+        self.stream.read_exact(&mut header)?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if len == 0 || len > self.max_frame_size {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid frame size {len}")));
+        }
+
+        let mut payload = vec![0u8; len - 1];
+        self.stream.read_exact(&mut payload)?;
+        if header[4] & FLAG_COMPRESSED != 0 {
+            payload = miniz_oxide::inflate::decompress_to_vec_with_limit(&payload, self.max_frame_size)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to decompress frame: {e}")))?;
+        }
+
+        T::load(&mut ByteBuffer::from_vec(payload)).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl<T: Savable> Iterator for MessageStream<T> {
+    type Item = io::Result<T>;
+
+    /// Receive the next message, ending when the connection is closed.
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.recv() {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            result => Some(result),
+        }
+    }
+}
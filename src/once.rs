@@ -9,7 +9,23 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex, Once,
 };
-use crate::save::{Loader, Savable, Saver};
+use crate::save::{Loader, Savable, SaveError, Saver};
+
+/// Runs the initializer of a global, recording how long it took with the `debug_globals` feature.
+#[inline(always)]
+fn timed<G>(global: &G, f: impl FnOnce()) {
+    #[cfg(feature = "debug_globals")]
+    {
+        let start = crate::platform::Instant::now();
+        f();
+        crate::globals::record_init(global as *const G as usize, start.elapsed());
+    }
+    #[cfg(not(feature = "debug_globals"))]
+    {
+        let _ = global;
+        f();
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct AlreadyInitialized;
@@ -54,10 +70,10 @@ impl<T> InitOnce<T> {
             panic!("InitOnce::init called twice");
         }
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let value = unsafe { &mut *self.value.get() };
             f(value);
-        });
+        }));
     }
 
     pub fn safe_init<F>(&self, f: F) -> Result<(), Box<dyn Any + Send + 'static>>
@@ -71,7 +87,7 @@ impl<T> InitOnce<T> {
         let panicked = Arc::new(Mutex::new(Some(Ok(()))));
         let clone = panicked.clone();
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let result = catch_unwind(|| {
                 let value = unsafe { &mut *self.value.get() };
                 f(value);
@@ -80,7 +96,7 @@ impl<T> InitOnce<T> {
             if let Err(e) = result {
                 clone.lock().unwrap().replace(Err(e));
             }
-        });
+        }));
         let res = panicked.lock().unwrap().take().unwrap();
         res
     }
@@ -93,10 +109,10 @@ impl<T> InitOnce<T> {
             return Err(AlreadyInitialized);
         }
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let value = unsafe { &mut *self.value.get() };
             f(value);
-        });
+        }));
 
         Ok(())
     }
@@ -111,7 +127,7 @@ impl<T> InitOnce<T> {
 
         let panicked = Arc::new(Mutex::new(Some(Ok(()))));
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let result = catch_unwind(|| {
                 let value = unsafe { &mut *self.value.get() };
                 f(value);
@@ -123,7 +139,7 @@ impl<T> InitOnce<T> {
                     .unwrap()
                     .replace(Err(InitError::Panicked(e)));
             }
-        });
+        }));
         let res = panicked.lock().unwrap().take().unwrap();
         res
     }
@@ -158,7 +174,7 @@ impl<T: Savable> Savable for InitOnce<T> {
         self.init_called.load(Ordering::Acquire).save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let value = T::load(loader)?;
         let init_called = AtomicBool::new(bool::load(loader)?);
         Ok(InitOnce {
@@ -200,10 +216,10 @@ impl<T> CreateOnce<T> {
             panic!("CreateOnce::create called twice");
         }
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let value = f();
             unsafe { &mut *self.value.get() }.replace(value);
-        });
+        }));
     }
 
     pub fn safe_create<F>(&self, f: F) -> Result<(), Box<dyn Any + Send + 'static>>
@@ -217,7 +233,7 @@ impl<T> CreateOnce<T> {
         let panicked = Arc::new(Mutex::new(Some(Ok(()))));
         let clone = panicked.clone();
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let result = catch_unwind(|| {
                 let value = f();
                 unsafe { &mut *self.value.get() }.replace(value);
@@ -226,7 +242,7 @@ impl<T> CreateOnce<T> {
             if let Err(e) = result {
                 clone.lock().unwrap().replace(Err(e));
             }
-        });
+        }));
         let res = panicked.lock().unwrap().take().unwrap();
         res
     }
@@ -239,10 +255,10 @@ impl<T> CreateOnce<T> {
             return Err(AlreadyInitialized);
         }
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let value = f();
             unsafe { &mut *self.value.get() }.replace(value);
-        });
+        }));
 
         Ok(())
     }
@@ -257,7 +273,7 @@ impl<T> CreateOnce<T> {
 
         let panicked = Arc::new(Mutex::new(Some(Ok(()))));
 
-        self.once.call_once(|| {
+        timed(self, || self.once.call_once(|| {
             let result = catch_unwind(|| {
                 let value = f();
                 unsafe { &mut *self.value.get() }.replace(value);
@@ -269,7 +285,7 @@ impl<T> CreateOnce<T> {
                     .unwrap()
                     .replace(Err(InitError::Panicked(e)));
             }
-        });
+        }));
         let res = panicked.lock().unwrap().take().unwrap();
         res
     }
@@ -322,7 +338,7 @@ impl<T: Savable> Savable for CreateOnce<T> {
         self.value.save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let value = <Option<T>>::load(loader)?;
         Ok(CreateOnce {
             init_called: AtomicBool::new(value.is_some()),
@@ -396,7 +412,7 @@ impl<T: Savable> Savable for Lazy<T> {
         Deref::deref(self).save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let value = T::load(loader)?;
         Ok(Lazy {
             value: CreateOnce {
@@ -515,7 +531,7 @@ impl<T: Savable> Savable for LazyInitOnce<T> {
         Deref::deref(self).save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let value = T::load(loader)?;
         Ok(LazyInitOnce {
             value: CreateOnce {
@@ -536,6 +552,84 @@ unsafe impl<T: Send> Send for LazyInitOnce<T> {}
 unsafe impl<T: Sync> Sync for LazyInitOnce<T> {}
 impl<T> RefUnwindSafe for LazyInitOnce<T> {}
 
+#[cfg(feature = "debug_globals")]
+impl<T> crate::globals::Global for InitOnce<T> where Self: Sync {
+    fn is_initialized(&self) -> bool {
+        self.initialized()
+    }
+
+    fn address(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[cfg(feature = "debug_globals")]
+impl<T> crate::globals::Global for CreateOnce<T> where Self: Sync {
+    fn is_initialized(&self) -> bool {
+        self.created()
+    }
+
+    fn address(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[cfg(feature = "debug_globals")]
+impl<T> crate::globals::Global for Lazy<T> where Self: Sync {
+    fn is_initialized(&self) -> bool {
+        self.created()
+    }
+
+    fn address(&self) -> usize {
+        &self.value as *const CreateOnce<T> as usize
+    }
+}
+
+#[cfg(feature = "debug_globals")]
+impl<T> crate::globals::Global for LazyInitOnce<T> where Self: Sync {
+    fn is_initialized(&self) -> bool {
+        self.value.created() && self.value.initialized()
+    }
+
+    fn address(&self) -> usize {
+        &self.value as *const CreateOnce<InitOnce<T>> as usize
+    }
+}
+
+/// Registers a static global with the [`globals`](crate::globals) registry before `main` runs.
+#[cfg(feature = "debug_globals")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_global {
+    ($n:ident, $kind:literal, $t:ty) => {
+        const _: () = {
+            #[used]
+            #[cfg_attr(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"), link_section = ".init_array")]
+            #[cfg_attr(any(target_os = "macos", target_os = "ios"), link_section = "__DATA,__mod_init_func")]
+            #[cfg_attr(windows, link_section = ".CRT$XCU")]
+            static REGISTER: extern "C" fn() = {
+                extern "C" fn register() {
+                    $crate::globals::register(
+                        concat!(module_path!(), "::", stringify!($n)),
+                        $kind,
+                        ::std::any::type_name::<$t>(),
+                        ::std::mem::size_of::<$t>(),
+                        unsafe { &*::std::ptr::addr_of!($n) },
+                    );
+                }
+                register
+            };
+        };
+    };
+}
+
+#[cfg(not(feature = "debug_globals"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_global {
+    ($n:ident, $kind:literal, $t:ty) => {};
+}
+
 #[macro_export]
 macro_rules! lazy_init {
     {
@@ -545,6 +639,7 @@ macro_rules! lazy_init {
     } => {
         $(
             $v static $n $($k)?: $crate::once::LazyInitOnce<$t> = $crate::once::LazyInitOnce::new(|| { $init });
+            $crate::__register_global!($n, "LazyInitOnce", $t);
         )*
     };
     {
@@ -567,6 +662,7 @@ macro_rules! lazy {
     } => {
         $(
             $v static $n $($k)?: $crate::once::Lazy<$t> = $crate::once::Lazy::new(|| { $init });
+            $crate::__register_global!($n, "Lazy", $t);
         )*
     };
     {
@@ -589,6 +685,7 @@ macro_rules! create_once {
     } => {
         $(
             $v static $n $($k)?: $crate::once::CreateOnce<$t> = $crate::once::CreateOnce::new();
+            $crate::__register_global!($n, "CreateOnce", $t);
         )*
     };
     {
@@ -0,0 +1,213 @@
+//! Floats with a total order, so they can be used as keys of maps and in sorted collections.
+//!
+//! All NaNs are equal to each other and greater than every other value, including infinity, and
+//! `-0.0` is equal to `0.0`. Equal values have equal hashes.
+//!
+//! ```
+//! use mvutils::ord_float::OrdF32;
+//! use std::collections::BTreeMap;
+//!
+//! let mut lods = BTreeMap::new();
+//! lods.insert(OrdF32(50.0), "low");
+//! lods.insert(OrdF32(10.0), "high");
+//! lods.insert(OrdF32(25.0), "medium");
+//! assert_eq!(lods.range(OrdF32(20.0)..).next(), Some((&OrdF32(25.0), &"medium")));
+//!
+//! let mut values = vec![2.0, f32::NAN, -1.0, f32::NEG_INFINITY];
+//! values.sort_by(mvutils::ord_float::cmp_f32);
+//! assert_eq!(values[..3], [f32::NEG_INFINITY, -1.0, 2.0]);
+//! assert!(values[3].is_nan());
+//! ```
+
+use crate::deep_size::DeepSizeOf;
+use crate::lerp::Lerp;
+use crate::save::{Loader, Savable, SaveError, Saver};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+macro_rules! ord_float {
+    ($(#[$meta:meta])* $name:ident, $t:ident, $cmp:ident, $push:ident) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Default)]
+        pub struct $name(pub $t);
+
+        /// Compare two floats with the total order of
+        #[doc = concat!("[`", stringify!($name), "`],")]
+        /// for use with `sort_by` and similar functions.
+        pub fn $cmp(a: &$t, b: &$t) -> Ordering {
+            match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            }
+        }
+
+        impl $name {
+            pub const NAN: $name = $name($t::NAN);
+            pub const INFINITY: $name = $name($t::INFINITY);
+            pub const NEG_INFINITY: $name = $name($t::NEG_INFINITY);
+
+            pub const fn get(self) -> $t {
+                self.0
+            }
+
+            pub fn is_nan(self) -> bool {
+                self.0.is_nan()
+            }
+
+            /// The value with `-0.0` replaced by `0.0` and every NaN replaced by the same NaN, so
+            /// equal values have the same bits.
+            fn canonical(self) -> $t {
+                if self.0.is_nan() {
+                    $t::NAN
+                } else if self.0 == 0.0 {
+                    0.0
+                } else {
+                    self.0
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                $cmp(&self.0, &other.0)
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.canonical().to_bits().hash(state);
+            }
+        }
+
+        impl PartialEq<$t> for $name {
+            fn eq(&self, other: &$t) -> bool {
+                *self == $name(*other)
+            }
+        }
+
+        impl PartialOrd<$t> for $name {
+            fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+                Some(self.cmp(&$name(*other)))
+            }
+        }
+
+        impl From<$t> for $name {
+            fn from(value: $t) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for $t {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                Debug::fmt(&self.0, f)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseFloatError;
+
+            fn from_str(s: &str) -> Result<Self, ParseFloatError> {
+                s.parse().map($name)
+            }
+        }
+
+        impl Savable for $name {
+            fn save(&self, saver: &mut impl Saver) {
+                saver.$push(self.0);
+            }
+
+            fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+                $t::load(loader).map($name)
+            }
+        }
+
+        impl DeepSizeOf for $name {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        }
+
+        impl Lerp for $name {
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                $name(self.0.lerp(&other.0, t))
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+
+        ord_float!(@ops $name, Add add AddAssign add_assign, Sub sub SubAssign sub_assign, Mul mul MulAssign mul_assign, Div div DivAssign div_assign);
+
+        impl Rem for $name {
+            type Output = $name;
+
+            fn rem(self, rhs: $name) -> $name {
+                $name(self.0 % rhs.0)
+            }
+        }
+    };
+    (@ops $name:ident, $($op:ident $f:ident $assign:ident $assign_f:ident),*) => {
+        $(
+            impl $op for $name {
+                type Output = $name;
+
+                fn $f(self, rhs: $name) -> $name {
+                    $name($op::$f(self.0, rhs.0))
+                }
+            }
+
+            impl $assign for $name {
+                fn $assign_f(&mut self, rhs: $name) {
+                    $assign::$assign_f(&mut self.0, rhs.0);
+                }
+            }
+        )*
+    };
+}
+
+ord_float!(
+    /// An [`f32`] with a total order, see the [module documentation](self).
+    OrdF32, f32, cmp_f32, push_f32
+);
+
+ord_float!(
+    /// An [`f64`] with a total order, see the [module documentation](self).
+    OrdF64, f64, cmp_f64, push_f64
+);
@@ -0,0 +1,168 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Lexically normalize a path, removing `.` components and resolving `..` where possible.
+/// The file system is not accessed, so symlinks are not resolved.
+pub fn normalize(path: impl AsRef<Path>) -> PathBuf {
+    let mut output = PathBuf::new();
+    for component in path.as_ref().components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match output.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    output.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => output.push(".."),
+            },
+            c => output.push(c),
+        }
+    }
+    if output.as_os_str().is_empty() {
+        output.push(".");
+    }
+    output
+}
+
+/// The path of `path` relative to `base`, after normalizing both. Returns [`None`] if no relative
+/// path exists, for example if one is absolute and the other is not.
+pub fn relative(path: impl AsRef<Path>, base: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = normalize(path);
+    let base = normalize(base);
+    if path.is_absolute() != base.is_absolute() {
+        return None;
+    }
+
+    let mut path = path.components().filter(|c| *c != Component::CurDir).peekable();
+    let mut base = base.components().filter(|c| *c != Component::CurDir).peekable();
+    while let (Some(a), Some(b)) = (path.peek(), base.peek()) {
+        if a != b {
+            break;
+        }
+        path.next();
+        base.next();
+    }
+
+    let mut output = PathBuf::new();
+    for c in base {
+        match c {
+            Component::Normal(_) => output.push(".."),
+            _ => return None,
+        }
+    }
+    output.extend(path);
+    if output.as_os_str().is_empty() {
+        output.push(".");
+    }
+    Some(output)
+}
+
+/// The lowercase extension of a path, if it has one.
+pub fn extension(path: impl AsRef<Path>) -> Option<String> {
+    path.as_ref().extension().map(|e| e.to_string_lossy().to_lowercase())
+}
+
+/// Whether the path has the given extension, ignoring case.
+pub fn has_extension(path: impl AsRef<Path>, extension: &str) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(extension.trim_start_matches('.')))
+}
+
+/// Replace the extension of a path, or add one if it has none.
+pub fn with_extension(path: impl AsRef<Path>, extension: &str) -> PathBuf {
+    path.as_ref().with_extension(extension.trim_start_matches('.'))
+}
+
+/// The file name of a path without its extension.
+pub fn file_stem(path: impl AsRef<Path>) -> Option<String> {
+    path.as_ref().file_stem().map(|s| s.to_string_lossy().into_owned())
+}
+
+/// Create a directory and all of its parents if they do not exist yet.
+pub fn ensure_dir(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    fs::create_dir_all(path)?;
+    Ok(path.to_path_buf())
+}
+
+/// Create the parent directory of a file path if it does not exist yet.
+pub fn ensure_parent(path: impl AsRef<Path>) -> io::Result<()> {
+    match path.as_ref().parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+/// The platform specific directories of an application.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProjectDirs {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub cache: PathBuf,
+    pub logs: PathBuf,
+}
+
+impl ProjectDirs {
+    /// Create all directories if they do not exist yet.
+    pub fn ensure(&self) -> io::Result<()> {
+        ensure_dir(&self.config)?;
+        ensure_dir(&self.data)?;
+        ensure_dir(&self.cache)?;
+        ensure_dir(&self.logs)?;
+        Ok(())
+    }
+}
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    return env_path("USERPROFILE");
+    #[cfg(not(windows))]
+    return env_path("HOME");
+}
+
+/// Resolve the config, data, cache and log directories for an application, following the platform
+/// conventions: `%APPDATA%` and `%LOCALAPPDATA%` on Windows, `~/Library` on macOS and the XDG base
+/// directories on other unix systems. The directories are not created, see [`ProjectDirs::ensure`].
+/// Returns [`None`] if the home directory cannot be determined.
+pub fn project_dirs(app_name: &str) -> Option<ProjectDirs> {
+    #[cfg(windows)]
+    {
+        let roaming = env_path("APPDATA").or_else(|| Some(home_dir()?.join("AppData").join("Roaming")))?.join(app_name);
+        let local = env_path("LOCALAPPDATA").or_else(|| Some(home_dir()?.join("AppData").join("Local")))?.join(app_name);
+        Some(ProjectDirs {
+            config: roaming.join("config"),
+            data: roaming.join("data"),
+            cache: local.join("cache"),
+            logs: local.join("logs"),
+        })
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let library = home_dir()?.join("Library");
+        Some(ProjectDirs {
+            config: library.join("Preferences").join(app_name),
+            data: library.join("Application Support").join(app_name),
+            cache: library.join("Caches").join(app_name),
+            logs: library.join("Logs").join(app_name),
+        })
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let home = home_dir();
+        let xdg = |key: &str, fallback: &str| env_path(key).filter(|p| p.is_absolute()).or_else(|| Some(home.as_ref()?.join(fallback)));
+        let state = xdg("XDG_STATE_HOME", ".local/state")?;
+        Some(ProjectDirs {
+            config: xdg("XDG_CONFIG_HOME", ".config")?.join(app_name),
+            data: xdg("XDG_DATA_HOME", ".local/share")?.join(app_name),
+            cache: xdg("XDG_CACHE_HOME", ".cache")?.join(app_name),
+            logs: state.join(app_name).join("logs"),
+        })
+    }
+}
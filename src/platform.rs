@@ -0,0 +1,155 @@
+//! Platform abstractions for time and threads, so the crate also works on `wasm32-unknown-unknown`
+//! where [`std::time::Instant::now`], [`SystemTime::now`] and [`std::thread::spawn`] panic.
+//!
+//! On wasm the host has to provide two imports in the `mvutils` module, both returning milliseconds
+//! as an `f64`:
+//!
+//! ```js
+//! const imports = {
+//!     mvutils: {
+//!         performance_now: () => performance.now(),
+//!         date_now: () => Date.now(),
+//!     },
+//! };
+//! ```
+//!
+//! On every other platform this module just forwards to std.
+
+use std::time::{Duration, SystemTime};
+
+pub use imp::Instant;
+
+/// Whether threads can be spawned on this platform.
+pub const HAS_THREADS: bool = !cfg!(target_arch = "wasm32");
+
+/// The time since the unix epoch.
+pub fn unix_time() -> Duration {
+    imp::unix_time()
+}
+
+/// The current system time. Unlike [`SystemTime::now`] this also works on wasm.
+pub fn system_now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + unix_time()
+}
+
+/// The amount of threads work should be split across, always 1 on platforms without threads.
+pub fn available_parallelism() -> usize {
+    if HAS_THREADS {
+        std::thread::available_parallelism().map_or(4, |n| n.get())
+    } else {
+        1
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    use std::time::{Duration, SystemTime};
+
+    pub use std::time::Instant;
+
+    pub fn unix_time() -> Duration {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod imp {
+    use crate::save::{Loader, Savable, SaveError, Saver};
+    use std::ops::{Add, AddAssign, Sub, SubAssign};
+    use std::time::Duration;
+
+    #[link(wasm_import_module = "mvutils")]
+    extern "C" {
+        fn performance_now() -> f64;
+        fn date_now() -> f64;
+    }
+
+    fn millis(ms: f64) -> Duration {
+        Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+    }
+
+    pub fn unix_time() -> Duration {
+        millis(unsafe { date_now() })
+    }
+
+    /// A monotonic point in time backed by `performance.now()`, mirroring [`std::time::Instant`].
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Instant(Duration);
+
+    impl Instant {
+        pub fn now() -> Self {
+            Instant(millis(unsafe { performance_now() }))
+        }
+
+        pub fn duration_since(&self, earlier: Instant) -> Duration {
+            self.saturating_duration_since(earlier)
+        }
+
+        pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+            self.0.checked_sub(earlier.0)
+        }
+
+        pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+            self.0.saturating_sub(earlier.0)
+        }
+
+        pub fn elapsed(&self) -> Duration {
+            Instant::now().saturating_duration_since(*self)
+        }
+
+        pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+            self.0.checked_add(duration).map(Instant)
+        }
+
+        pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+            self.0.checked_sub(duration).map(Instant)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+
+        fn add(self, rhs: Duration) -> Instant {
+            self.checked_add(rhs).expect("overflow when adding duration to instant")
+        }
+    }
+
+    impl AddAssign<Duration> for Instant {
+        fn add_assign(&mut self, rhs: Duration) {
+            *self = *self + rhs;
+        }
+    }
+
+    impl Sub<Duration> for Instant {
+        type Output = Instant;
+
+        fn sub(self, rhs: Duration) -> Instant {
+            self.checked_sub(rhs).expect("overflow when subtracting duration from instant")
+        }
+    }
+
+    impl SubAssign<Duration> for Instant {
+        fn sub_assign(&mut self, rhs: Duration) {
+            *self = *self - rhs;
+        }
+    }
+
+    impl Sub<Instant> for Instant {
+        type Output = Duration;
+
+        fn sub(self, rhs: Instant) -> Duration {
+            self.duration_since(rhs)
+        }
+    }
+
+    impl Savable for Instant {
+        fn save(&self, saver: &mut impl Saver) {
+            unix_time().saturating_sub(self.elapsed()).save(saver);
+        }
+
+        fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+            let duration = Duration::load(loader)?;
+            Ok(Instant((Instant::now().0 + duration).saturating_sub(unix_time())))
+        }
+    }
+}
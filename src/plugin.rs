@@ -0,0 +1,371 @@
+//! Loading plugins from dynamic libraries.
+//!
+//! A plugin crate is built as a `cdylib` against the same version of mvutils and compiler as the
+//! host, and declares its entry point with [`declare_plugin!`](crate::declare_plugin):
+//!
+//! ```ignore
+//! use mvutils::plugin::Plugin;
+//! use mvutils::version::Version;
+//!
+//! #[derive(Default)]
+//! struct Weather;
+//!
+//! impl Plugin for Weather {
+//!     fn name(&self) -> &str {
+//!         "weather"
+//!     }
+//!
+//!     fn version(&self) -> Version {
+//!         Version::new(0, 1, 2, 0)
+//!     }
+//!
+//!     fn on_load(&mut self) {
+//!         println!("It's sunny");
+//!     }
+//! }
+//!
+//! mvutils::declare_plugin!(Weather::default);
+//! ```
+//!
+//! The host then loads it with [`LoadedPlugin::load`], which checks that both sides were built with
+//! a compatible plugin ABI and mvutils version before calling into the library.
+//!
+//! # Unloading
+//!
+//! Dropping a [`LoadedPlugin`] calls [`Plugin::on_unload`], drops the plugin and then unloads the
+//! library. Nothing created by the plugin may outlive it: values with drop glue or vtables from the
+//! plugin, `&'static` references into its data, function pointers, threads it spawned that are still
+//! running, and thread locals with destructors all point into unmapped memory after unloading.
+//! Only plain data copied out of the plugin is safe to keep.
+
+use crate::version::Version;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever [`PluginDeclaration`] or the [`Plugin`] trait change incompatibly.
+pub const ABI_VERSION: u32 = 1;
+
+/// The name of the symbol exported by [`declare_plugin!`](crate::declare_plugin).
+pub const DECLARATION_SYMBOL: &str = "MVUTILS_PLUGIN_DECLARATION";
+
+/// The version of mvutils this crate was built as.
+pub const MVUTILS_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A plugin loaded from a dynamic library.
+pub trait Plugin: Send {
+    fn name(&self) -> &str;
+
+    fn version(&self) -> Version;
+
+    /// Called after the plugin was created and the handshake succeeded.
+    fn on_load(&mut self) {}
+
+    /// Called before the plugin is dropped and its library is unloaded.
+    fn on_unload(&mut self) {}
+}
+
+/// The entry point exported by a plugin library, created with [`declare_plugin!`](crate::declare_plugin).
+#[repr(C)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    pub mvutils_version: &'static str,
+    pub create: fn() -> Box<dyn Plugin>,
+}
+
+impl PluginDeclaration {
+    /// Check that the declaration was built with a plugin ABI and mvutils version compatible with
+    /// this crate. The mvutils versions must have the same major and minor version.
+    pub fn check(&self) -> Result<(), PluginError> {
+        if self.abi_version != ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                expected: ABI_VERSION,
+                found: self.abi_version,
+            });
+        }
+        let host = Version::parse(MVUTILS_VERSION).unwrap_or_default();
+        let plugin = Version::parse(self.mvutils_version);
+        match plugin {
+            Some(plugin) if plugin.major() == host.major() && plugin.minor() == host.minor() => Ok(()),
+            _ => Err(PluginError::VersionMismatch {
+                host: MVUTILS_VERSION.to_string(),
+                plugin: self.mvutils_version.to_string(),
+            }),
+        }
+    }
+}
+
+/// Export the plugin entry point of a library. The argument is a function or closure returning the
+/// plugin, which is called once when the library is loaded.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($create:expr) => {
+        #[no_mangle]
+        pub static MVUTILS_PLUGIN_DECLARATION: $crate::plugin::PluginDeclaration = $crate::plugin::PluginDeclaration {
+            abi_version: $crate::plugin::ABI_VERSION,
+            mvutils_version: $crate::plugin::MVUTILS_VERSION,
+            create: || Box::new(($create)()),
+        };
+    };
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    /// The library could not be loaded.
+    Open { path: PathBuf, message: String },
+    /// The library does not export this symbol.
+    Symbol { name: String, message: String },
+    /// The plugin was built for a different plugin ABI.
+    AbiMismatch { expected: u32, found: u32 },
+    /// The plugin was built against an incompatible version of mvutils.
+    VersionMismatch { host: String, plugin: String },
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::Open { path, message } => write!(f, "Failed to load '{}': {message}", path.display()),
+            PluginError::Symbol { name, message } => write!(f, "Failed to find symbol '{name}': {message}"),
+            PluginError::AbiMismatch { expected, found } => write!(f, "Plugin ABI version {found} does not match {expected}"),
+            PluginError::VersionMismatch { host, plugin } => write!(f, "Plugin was built with mvutils {plugin}, which is incompatible with {host}"),
+        }
+    }
+}
+
+impl Error for PluginError {}
+
+/// A dynamic library, unloaded when dropped.
+pub struct Library {
+    handle: sys::Handle,
+    path: PathBuf,
+}
+
+// The handle is only an opaque pointer, the loader itself is thread safe.
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
+
+impl Library {
+    /// Load the library at the path.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code, which can do anything.
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let path = path.as_ref();
+        sys::open(path)
+            .map(|handle| Library { handle, path: path.to_path_buf() })
+            .map_err(|message| PluginError::Open { path: path.to_path_buf(), message })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Look up a symbol, usually a function pointer or a pointer to a static. The returned symbol
+    /// borrows the library, so it cannot outlive it.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be a pointer sized type matching the actual type of the symbol.
+    pub unsafe fn get<T: Copy>(&self, name: &str) -> Result<Symbol<'_, T>, PluginError> {
+        assert_eq!(std::mem::size_of::<T>(), std::mem::size_of::<*mut ()>(), "Symbols can only be loaded as pointer sized types");
+        let ptr = sys::symbol(self.handle, name).map_err(|message| PluginError::Symbol { name: name.to_string(), message })?;
+        Ok(Symbol {
+            value: std::mem::transmute_copy(&ptr),
+            _library: PhantomData,
+        })
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe { sys::close(self.handle) };
+    }
+}
+
+impl Debug for Library {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Library").field("path", &self.path).finish()
+    }
+}
+
+/// A symbol loaded from a [`Library`].
+pub struct Symbol<'lib, T> {
+    value: T,
+    _library: PhantomData<&'lib Library>,
+}
+
+impl<T> Deref for Symbol<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// A plugin together with the library it was loaded from.
+pub struct LoadedPlugin {
+    plugin: Option<Box<dyn Plugin>>,
+    library: Library,
+}
+
+impl LoadedPlugin {
+    /// Load the library, check the [`PluginDeclaration`] it exports, create the plugin and call
+    /// [`Plugin::on_load`].
+    ///
+    /// # Safety
+    ///
+    /// The library must be a plugin built with [`declare_plugin!`](crate::declare_plugin) by the
+    /// same compiler version as the host, see the [module docs](self) for unloading.
+    pub unsafe fn load(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let library = Library::open(path)?;
+        let declaration = library.get::<*const PluginDeclaration>(DECLARATION_SYMBOL)?;
+        let declaration = &**declaration;
+        declaration.check()?;
+        let mut plugin = (declaration.create)();
+        plugin.on_load();
+        Ok(LoadedPlugin {
+            plugin: Some(plugin),
+            library,
+        })
+    }
+
+    pub fn plugin(&self) -> &dyn Plugin {
+        self.plugin.as_deref().unwrap()
+    }
+
+    pub fn plugin_mut(&mut self) -> &mut dyn Plugin {
+        self.plugin.as_deref_mut().unwrap()
+    }
+
+    /// The library of the plugin, to look up additional symbols.
+    pub fn library(&self) -> &Library {
+        &self.library
+    }
+
+    /// Unload the plugin, the same as dropping it.
+    pub fn unload(self) {}
+}
+
+impl Drop for LoadedPlugin {
+    fn drop(&mut self) {
+        if let Some(mut plugin) = self.plugin.take() {
+            plugin.on_unload();
+        }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{c_char, c_int, c_void, CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub type Handle = *mut c_void;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const RTLD_LOCAL: c_int = 0;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const RTLD_LOCAL: c_int = 4;
+    const RTLD_NOW: c_int = 2;
+
+    #[cfg_attr(any(target_os = "linux", target_os = "android"), link(name = "dl"))]
+    extern "C" {
+        fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        fn dlclose(handle: *mut c_void) -> c_int;
+        fn dlerror() -> *mut c_char;
+    }
+
+    unsafe fn error() -> String {
+        let message = dlerror();
+        if message.is_null() {
+            "Unknown error".to_string()
+        } else {
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        }
+    }
+
+    pub unsafe fn open(path: &Path) -> Result<Handle, String> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+        let handle = dlopen(path.as_ptr(), RTLD_NOW | RTLD_LOCAL);
+        if handle.is_null() {
+            Err(error())
+        } else {
+            Ok(handle)
+        }
+    }
+
+    pub unsafe fn symbol(handle: Handle, name: &str) -> Result<*mut c_void, String> {
+        let name = CString::new(name).map_err(|e| e.to_string())?;
+        dlerror();
+        let symbol = dlsym(handle, name.as_ptr());
+        if symbol.is_null() {
+            Err(error())
+        } else {
+            Ok(symbol)
+        }
+    }
+
+    pub unsafe fn close(handle: Handle) {
+        dlclose(handle);
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::{c_char, c_void, CString};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub type Handle = *mut c_void;
+
+    extern "system" {
+        fn LoadLibraryW(filename: *const u16) -> *mut c_void;
+        fn GetProcAddress(module: *mut c_void, name: *const c_char) -> *mut c_void;
+        fn FreeLibrary(module: *mut c_void) -> i32;
+    }
+
+    pub unsafe fn open(path: &Path) -> Result<Handle, String> {
+        let path = path.as_os_str().encode_wide().chain(Some(0)).collect::<Vec<_>>();
+        let handle = LoadLibraryW(path.as_ptr());
+        if handle.is_null() {
+            Err(std::io::Error::last_os_error().to_string())
+        } else {
+            Ok(handle)
+        }
+    }
+
+    pub unsafe fn symbol(handle: Handle, name: &str) -> Result<*mut c_void, String> {
+        let name = CString::new(name).map_err(|e| e.to_string())?;
+        let symbol = GetProcAddress(handle, name.as_ptr());
+        if symbol.is_null() {
+            Err(std::io::Error::last_os_error().to_string())
+        } else {
+            Ok(symbol)
+        }
+    }
+
+    pub unsafe fn close(handle: Handle) {
+        FreeLibrary(handle);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::path::Path;
+
+    pub type Handle = *mut std::ffi::c_void;
+
+    pub unsafe fn open(_: &Path) -> Result<Handle, String> {
+        Err("Dynamic libraries are not supported on this platform".to_string())
+    }
+
+    pub unsafe fn symbol(_: Handle, _: &str) -> Result<*mut std::ffi::c_void, String> {
+        Err("Dynamic libraries are not supported on this platform".to_string())
+    }
+
+    pub unsafe fn close(_: Handle) {}
+}
@@ -1,7 +1,13 @@
+use crate::color::Color;
 use std::fmt::Display;
 use std::io::Write;
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+pub mod terminal;
+pub mod theme;
+
+pub use theme::{Look, Style, Theme};
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Fmt {
     Default,
     Bold,
@@ -9,7 +15,7 @@ pub enum Fmt {
     UnderlineStop,
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Col {
     Black,
     Red,
@@ -27,6 +33,14 @@ pub enum Col {
     Magenta,
     Cyan,
     White,
+    /// A 24-bit color, which requires a terminal with truecolor support.
+    Rgb(u8, u8, u8),
+}
+
+impl From<Color> for Col {
+    fn from(value: Color) -> Self {
+        Col::Rgb(value.r, value.g, value.b)
+    }
 }
 
 macro_rules! f {
@@ -40,6 +54,7 @@ pub struct Printer {
     last_col: Col,
     last_bg: Col,
     last_fmt: Fmt,
+    theme: Theme,
 }
 
 impl Printer {
@@ -49,9 +64,16 @@ impl Printer {
             last_col: Col::White,
             last_bg: Col::Black,
             last_fmt: Fmt::Default,
+            theme: Theme::current(),
         }
     }
 
+    /// Use a different theme than the default one for [`Printer::style`].
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
     pub fn text(mut self, text: &str) -> Self {
         self.s.push_str(text);
         self
@@ -88,7 +110,8 @@ impl Printer {
         self.fmt(Fmt::Default)
     }
 
-    pub fn col(mut self, col: Col) -> Self {
+    pub fn col(mut self, col: impl Into<Col>) -> Self {
+        let col = col.into();
         match col {
             Col::Black => self.s.push_str(f!(30)),
             Col::Red => self.s.push_str(f!(31)),
@@ -106,12 +129,14 @@ impl Printer {
             Col::Magenta => self.s.push_str(f!(95)),
             Col::Cyan => self.s.push_str(f!(96)),
             Col::White => self.s.push_str(f!(97)),
+            Col::Rgb(r, g, b) => self.s.push_str(f!(format!("38;2;{r};{g};{b}"))),
         }
         self.last_col = col;
         self
     }
 
-    pub fn bg(mut self, col: Col) -> Self {
+    pub fn bg(mut self, col: impl Into<Col>) -> Self {
+        let col = col.into();
         match col {
             Col::Black => self.s.push_str(f!(40)),
             Col::Red => self.s.push_str(f!(41)),
@@ -129,6 +154,7 @@ impl Printer {
             Col::Magenta => self.s.push_str(f!(105)),
             Col::Cyan => self.s.push_str(f!(106)),
             Col::White => self.s.push_str(f!(107)),
+            Col::Rgb(r, g, b) => self.s.push_str(f!(format!("48;2;{r};{g};{b}"))),
         }
         self.last_bg = col;
         self
@@ -149,29 +175,29 @@ impl Printer {
         self.fmt_for(fmt, text).ln()
     }
 
-    pub fn col_for(self, col: Col, text: &str) -> Self {
+    pub fn col_for(self, col: impl Into<Col>, text: &str) -> Self {
         let f = self.last_fmt;
         let c = self.last_col;
         let b = self.last_bg;
         self.col(col).text(text).revert_styles(f, c, b)
     }
 
-    pub fn col_for_ln(self, col: Col, text: &str) -> Self {
+    pub fn col_for_ln(self, col: impl Into<Col>, text: &str) -> Self {
         self.col_for(col, text).ln()
     }
 
-    pub fn bg_for(self, col: Col, text: &str) -> Self {
+    pub fn bg_for(self, col: impl Into<Col>, text: &str) -> Self {
         let f = self.last_fmt;
         let c = self.last_col;
         let b = self.last_bg;
         self.bg(col).text(text).revert_styles(f, c, b)
     }
 
-    pub fn bg_for_ln(self, col: Col, text: &str) -> Self {
+    pub fn bg_for_ln(self, col: impl Into<Col>, text: &str) -> Self {
         self.bg_for(col, text).ln()
     }
 
-    pub fn all_for(self, fmt: Fmt, col: Col, bg: Col, text: &str) -> Self {
+    pub fn all_for(self, fmt: Fmt, col: impl Into<Col>, bg: impl Into<Col>, text: &str) -> Self {
         let f = self.last_fmt;
         let c = self.last_col;
         let b = self.last_bg;
@@ -182,10 +208,28 @@ impl Printer {
             .revert_styles(f, c, b)
     }
 
-    pub fn all_for_ln(self, fmt: Fmt, col: Col, bg: Col, text: &str) -> Self {
+    pub fn all_for_ln(self, fmt: Fmt, col: impl Into<Col>, bg: impl Into<Col>, text: &str) -> Self {
         self.all_for(fmt, col, bg, text).ln()
     }
 
+    /// Print the text with the look the theme gives to the style.
+    pub fn style(self, style: Style, text: &str) -> Self {
+        let look = self.theme.get(style);
+        let f = self.last_fmt;
+        let c = self.last_col;
+        let b = self.last_bg;
+        let mut printer = self.def().fmt(look.fmt).col(look.col.unwrap_or(c)).bg(look.bg.unwrap_or(b)).text(text).def();
+        printer = printer.col(c).bg(b);
+        if f != Fmt::Default {
+            printer = printer.fmt(f);
+        }
+        printer
+    }
+
+    pub fn style_ln(self, style: Style, text: &str) -> Self {
+        self.style(style, text).ln()
+    }
+
     pub fn flush(self) {
         print!("{}", self.def().s);
         std::io::stdout().flush().unwrap();
@@ -0,0 +1,223 @@
+//! Terminal size detection, cursor escape sequences and raw mode, for progress bars, tables and prompts.
+
+/// Clear the entire line the cursor is on.
+pub const CLEAR_LINE: &str = "\x1b[2K\r";
+/// Clear the screen and move the cursor to the top left.
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// Move the cursor to the zero based column and row.
+pub fn move_to(column: u16, row: u16) -> String {
+    format!("\x1b[{};{}H", row + 1, column + 1)
+}
+
+pub fn move_up(lines: u16) -> String {
+    if lines == 0 { String::new() } else { format!("\x1b[{}A", lines) }
+}
+
+pub fn move_down(lines: u16) -> String {
+    if lines == 0 { String::new() } else { format!("\x1b[{}B", lines) }
+}
+
+pub fn move_right(columns: u16) -> String {
+    if columns == 0 { String::new() } else { format!("\x1b[{}C", columns) }
+}
+
+pub fn move_left(columns: u16) -> String {
+    if columns == 0 { String::new() } else { format!("\x1b[{}D", columns) }
+}
+
+/// The size of the terminal as (columns, rows), falling back to the `COLUMNS` and `LINES`
+/// environment variables and then to 80x24 if it cannot be detected.
+pub fn size() -> (u16, u16) {
+    sys::size().unwrap_or_else(|| {
+        let env = |name: &str, default: u16| std::env::var(name).ok().and_then(|v| v.parse().ok()).filter(|v| *v > 0).unwrap_or(default);
+        (env("COLUMNS", 80), env("LINES", 24))
+    })
+}
+
+/// Disables line buffering and echo of the standard input until dropped.
+pub struct RawMode(#[allow(dead_code)] sys::Mode);
+
+impl RawMode {
+    /// Enable raw mode, returning None if the standard input is not a terminal.
+    pub fn enable() -> Option<Self> {
+        sys::Mode::raw().map(RawMode)
+    }
+}
+
+/// Disables echo of the standard input until dropped, while still reading whole lines.
+pub struct NoEcho(#[allow(dead_code)] sys::Mode);
+
+impl NoEcho {
+    /// Disable echo, returning None if the standard input is not a terminal.
+    pub fn enable() -> Option<Self> {
+        sys::Mode::no_echo().map(NoEcho)
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::io::IsTerminal;
+    use std::process::{Command, Stdio};
+
+    #[repr(C)]
+    struct WinSize {
+        rows: u16,
+        columns: u16,
+        x_pixels: u16,
+        y_pixels: u16,
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const TIOCGWINSZ: std::ffi::c_ulong = 0x5413;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const TIOCGWINSZ: std::ffi::c_ulong = 0x40087468;
+
+    extern "C" {
+        fn ioctl(fd: std::ffi::c_int, request: std::ffi::c_ulong, ...) -> std::ffi::c_int;
+    }
+
+    pub fn size() -> Option<(u16, u16)> {
+        let mut size = WinSize { rows: 0, columns: 0, x_pixels: 0, y_pixels: 0 };
+        for fd in 0..3 {
+            if unsafe { ioctl(fd, TIOCGWINSZ, &mut size as *mut WinSize) } == 0 && size.columns > 0 && size.rows > 0 {
+                return Some((size.columns, size.rows));
+            }
+        }
+        None
+    }
+
+    fn stty(args: &[&str]) -> bool {
+        Command::new("stty")
+            .args(args)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    /// The current terminal settings, in the form `stty` accepts to restore them.
+    fn saved_state() -> Option<String> {
+        let output = Command::new("stty").arg("-g").stdin(Stdio::inherit()).stderr(Stdio::null()).output().ok()?;
+        let state = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        (output.status.success() && !state.is_empty()).then_some(state)
+    }
+
+    /// Restores the terminal settings from before the mode was set when dropped.
+    pub struct Mode(String);
+
+    impl Mode {
+        fn set(enable: &[&str]) -> Option<Self> {
+            if !std::io::stdin().is_terminal() {
+                return None;
+            }
+            let saved = saved_state()?;
+            stty(enable).then_some(Mode(saved))
+        }
+
+        pub fn raw() -> Option<Self> {
+            Self::set(&["raw", "-echo"])
+        }
+
+        pub fn no_echo() -> Option<Self> {
+            Self::set(&["-echo"])
+        }
+    }
+
+    impl Drop for Mode {
+        fn drop(&mut self) {
+            stty(&[&self.0]);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+
+    const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const ENABLE_PROCESSED_INPUT: u32 = 0x1;
+    const ENABLE_LINE_INPUT: u32 = 0x2;
+    const ENABLE_ECHO_INPUT: u32 = 0x4;
+    const ENABLE_VIRTUAL_TERMINAL_INPUT: u32 = 0x200;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ScreenBufferInfo {
+        size: [i16; 2],
+        cursor: [i16; 2],
+        attributes: u16,
+        window: [i16; 4],
+        max_window: [i16; 2],
+    }
+
+    extern "system" {
+        fn GetStdHandle(handle: u32) -> *mut c_void;
+        fn GetConsoleMode(handle: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(handle: *mut c_void, mode: u32) -> i32;
+        fn GetConsoleScreenBufferInfo(handle: *mut c_void, info: *mut ScreenBufferInfo) -> i32;
+    }
+
+    pub fn size() -> Option<(u16, u16)> {
+        let mut info = ScreenBufferInfo::default();
+        if unsafe { GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) } == 0 {
+            return None;
+        }
+        let [left, top, right, bottom] = info.window;
+        Some(((right - left + 1) as u16, (bottom - top + 1) as u16))
+    }
+
+    pub struct Mode(u32);
+
+    impl Mode {
+        fn set(f: impl FnOnce(u32) -> u32) -> Option<Self> {
+            unsafe {
+                let handle = GetStdHandle(STD_INPUT_HANDLE);
+                let mut mode = 0;
+                if GetConsoleMode(handle, &mut mode) == 0 || SetConsoleMode(handle, f(mode)) == 0 {
+                    return None;
+                }
+                Some(Mode(mode))
+            }
+        }
+
+        pub fn raw() -> Option<Self> {
+            Self::set(|m| (m & !(ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT)) | ENABLE_VIRTUAL_TERMINAL_INPUT)
+        }
+
+        pub fn no_echo() -> Option<Self> {
+            Self::set(|m| m & !ENABLE_ECHO_INPUT)
+        }
+    }
+
+    impl Drop for Mode {
+        fn drop(&mut self) {
+            unsafe {
+                SetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), self.0);
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub fn size() -> Option<(u16, u16)> {
+        None
+    }
+
+    pub struct Mode;
+
+    impl Mode {
+        pub fn raw() -> Option<Self> {
+            None
+        }
+
+        pub fn no_echo() -> Option<Self> {
+            None
+        }
+    }
+}
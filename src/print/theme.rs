@@ -0,0 +1,130 @@
+//! Semantic styles, so tools print errors, headings and the like consistently and can adapt to light
+//! and dark terminals.
+
+use crate::print::{Col, Fmt};
+use crate::sync::RwLock;
+
+/// The meaning of a piece of text, which a [`Theme`] maps to a [`Look`].
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum Style {
+    Error,
+    Warning,
+    Success,
+    Info,
+    Heading,
+    Code,
+    /// Less important text, like hints and locations.
+    Muted,
+}
+
+impl Style {
+    pub const ALL: [Style; 7] = [
+        Style::Error,
+        Style::Warning,
+        Style::Success,
+        Style::Info,
+        Style::Heading,
+        Style::Code,
+        Style::Muted,
+    ];
+}
+
+/// How text is printed. A missing color keeps the current one.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Look {
+    pub fmt: Fmt,
+    pub col: Option<Col>,
+    pub bg: Option<Col>,
+}
+
+impl Look {
+    /// Text printed without any changes.
+    pub const PLAIN: Look = Look::new(Fmt::Default, None);
+
+    pub const fn new(fmt: Fmt, col: Option<Col>) -> Self {
+        Look { fmt, col, bg: None }
+    }
+
+    pub const fn col(col: Col) -> Self {
+        Look::new(Fmt::Default, Some(col))
+    }
+
+    pub const fn with_bg(mut self, bg: Col) -> Self {
+        self.bg = Some(bg);
+        self
+    }
+}
+
+/// A mapping from every [`Style`] to a [`Look`].
+///
+/// [`Printer::start`](crate::print::Printer::start) uses the default theme, which is
+/// [`Theme::dark`] unless it was replaced with [`Theme::set_default`].
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Theme {
+    looks: [Look; Style::ALL.len()],
+}
+
+static DEFAULT: RwLock<Theme> = RwLock::new(Theme::dark());
+
+impl Theme {
+    /// A theme for terminals with a dark background.
+    pub const fn dark() -> Self {
+        Theme {
+            looks: [
+                Look::col(Col::Red),
+                Look::col(Col::BrightYellow),
+                Look::col(Col::Lime),
+                Look::col(Col::Cyan),
+                Look::new(Fmt::Bold, Some(Col::White)),
+                Look::col(Col::BrightBlue),
+                Look::col(Col::DarkGrey),
+            ],
+        }
+    }
+
+    /// A theme for terminals with a light background.
+    pub const fn light() -> Self {
+        Theme {
+            looks: [
+                Look::col(Col::Red),
+                Look::col(Col::Yellow),
+                Look::col(Col::Green),
+                Look::col(Col::Blue),
+                Look::new(Fmt::Bold, Some(Col::Black)),
+                Look::col(Col::Purple),
+                Look::col(Col::DarkGrey),
+            ],
+        }
+    }
+
+    /// A theme without any colors, for output which is not read in a terminal.
+    pub const fn plain() -> Self {
+        Theme {
+            looks: [Look::PLAIN; Style::ALL.len()],
+        }
+    }
+
+    pub fn get(&self, style: Style) -> Look {
+        self.looks[style as usize]
+    }
+
+    pub fn set(&mut self, style: Style, look: Look) {
+        self.looks[style as usize] = look;
+    }
+
+    /// Replace the look of a style.
+    pub fn with(mut self, style: Style, look: Look) -> Self {
+        self.set(style, look);
+        self
+    }
+
+    /// The theme used by new printers.
+    pub fn current() -> Theme {
+        *DEFAULT.read()
+    }
+
+    /// Replace the theme used by new printers.
+    pub fn set_default(theme: Theme) {
+        *DEFAULT.write() = theme;
+    }
+}
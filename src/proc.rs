@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+/// The output stream a line was read from.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// The captured output of a process that exited successfully.
+#[derive(Clone, Debug)]
+pub struct ProcOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug)]
+pub enum ProcError {
+    /// The process could not be started.
+    Spawn { program: String, error: std::io::Error },
+    /// Reading the output of or waiting for the process failed.
+    Io(std::io::Error),
+    /// The process was killed because it did not finish in time.
+    Timeout { program: String, timeout: Duration, stdout: String, stderr: String },
+    /// The process exited with a non-zero exit code, or was terminated by a signal if the code is None.
+    Exit { program: String, code: Option<i32>, stdout: String, stderr: String },
+}
+
+impl ProcError {
+    /// The captured standard error of the process, if it was started.
+    pub fn stderr(&self) -> Option<&str> {
+        match self {
+            ProcError::Timeout { stderr, .. } | ProcError::Exit { stderr, .. } => Some(stderr),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ProcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcError::Spawn { program, error } => write!(f, "Failed to start '{program}': {error}"),
+            ProcError::Io(e) => write!(f, "Failed to communicate with process: {e}"),
+            ProcError::Timeout { program, timeout, .. } => write!(f, "'{program}' did not finish within {timeout:?}"),
+            ProcError::Exit { program, code: Some(code), .. } => write!(f, "'{program}' exited with code {code}"),
+            ProcError::Exit { program, code: None, .. } => write!(f, "'{program}' was terminated by a signal"),
+        }
+    }
+}
+
+impl Error for ProcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProcError::Spawn { error, .. } | ProcError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProcError {
+    fn from(value: std::io::Error) -> Self {
+        ProcError::Io(value)
+    }
+}
+
+/// A builder for running a process and collecting its output.
+///
+/// ```
+/// use mvutils::proc::Proc;
+/// use std::time::Duration;
+///
+/// # #[cfg(unix)] {
+/// let output = Proc::new("echo").arg("hello").timeout(Duration::from_secs(5)).output().unwrap();
+/// assert_eq!(output.stdout.trim(), "hello");
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Proc {
+    program: OsString,
+    args: Vec<OsString>,
+    env: HashMap<OsString, Option<OsString>>,
+    clear_env: bool,
+    dir: Option<PathBuf>,
+    stdin: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+}
+
+impl Proc {
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Proc {
+            program: program.as_ref().to_os_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            clear_env: false,
+            dir: None,
+            stdin: None,
+            timeout: None,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    pub fn args<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(mut self, args: I) -> Self {
+        self.args.extend(args.into_iter().map(|a| a.as_ref().to_os_string()));
+        self
+    }
+
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.env.insert(key.as_ref().to_os_string(), Some(value.as_ref().to_os_string()));
+        self
+    }
+
+    pub fn env_remove(mut self, key: impl AsRef<OsStr>) -> Self {
+        self.env.insert(key.as_ref().to_os_string(), None);
+        self
+    }
+
+    /// Do not inherit the environment of this process.
+    pub fn env_clear(mut self) -> Self {
+        self.clear_env = true;
+        self
+    }
+
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Write the bytes to the standard input of the process, which is closed afterwards.
+    pub fn stdin(mut self, input: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    /// Kill the process if it did not finish in time.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn program(&self) -> String {
+        self.program.to_string_lossy().into_owned()
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        if self.clear_env {
+            command.env_clear();
+        }
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
+        }
+        command
+            .stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    /// Run the process and capture its output.
+    pub fn output(&self) -> Result<ProcOutput, ProcError> {
+        self.stream(|_, _| {})
+    }
+
+    /// Run the process, calling the callback with every line of output as it is written, for example
+    /// to forward it to a log. The output is captured as well.
+    pub fn stream(&self, mut on_line: impl FnMut(Stream, &str)) -> Result<ProcOutput, ProcError> {
+        let program = self.program();
+        let mut child = self.command().spawn().map_err(|error| ProcError::Spawn { program: program.clone(), error })?;
+        let deadline = self.timeout.map(|t| Instant::now() + t);
+
+        if let (Some(input), Some(mut stdin)) = (self.stdin.clone(), child.stdin.take()) {
+            std::thread::spawn(move || stdin.write_all(&input));
+        }
+        let (sender, receiver) = mpsc::channel();
+        read_lines(child.stdout.take(), Stream::Stdout, sender.clone());
+        read_lines(child.stderr.take(), Stream::Stderr, sender);
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        loop {
+            let received = match deadline {
+                Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok((stream, line)) => {
+                    on_line(stream, line.trim_end_matches(['\r', '\n']));
+                    match stream {
+                        Stream::Stdout => stdout.push_str(&line),
+                        Stream::Stderr => stderr.push_str(&line),
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Err(self.kill(child, stdout, stderr)),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            match deadline {
+                Some(deadline) if Instant::now() >= deadline => return Err(self.kill(child, stdout, stderr)),
+                Some(_) => std::thread::sleep(Duration::from_millis(5)),
+                None => break child.wait()?,
+            }
+        };
+
+        if status.success() {
+            Ok(ProcOutput { status, stdout, stderr })
+        } else {
+            Err(ProcError::Exit { program, code: status.code(), stdout, stderr })
+        }
+    }
+
+    fn kill(&self, mut child: Child, stdout: String, stderr: String) -> ProcError {
+        let _ = child.kill();
+        let _ = child.wait();
+        ProcError::Timeout {
+            program: self.program(),
+            timeout: self.timeout.unwrap_or_default(),
+            stdout,
+            stderr,
+        }
+    }
+}
+
+fn read_lines(source: Option<impl Read + Send + 'static>, stream: Stream, sender: Sender<(Stream, String)>) {
+    if let Some(source) = source {
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(source);
+            let mut line = Vec::new();
+            while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+                if sender.send((stream, String::from_utf8_lossy(&line).into_owned())).is_err() {
+                    break;
+                }
+                line.clear();
+            }
+        });
+    }
+}
@@ -0,0 +1,356 @@
+use crate as mvutils;
+use crate::json::Json;
+use crate::lazy;
+use crate::platform::Instant;
+use crate::print::{Col, Printer};
+use mvutils_proc_macro::Savable;
+use crate::sync::Mutex;
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+lazy! {
+    static EPOCH: Instant = Instant::now();
+    static PENDING: Mutex<Vec<ThreadSpans>> = Mutex::new(Vec::new());
+}
+
+struct SpanRecord {
+    name: &'static str,
+    parent: Option<usize>,
+    start: Instant,
+    end: Instant,
+}
+
+struct ThreadSpans {
+    thread: String,
+    records: Vec<SpanRecord>,
+}
+
+#[derive(Default)]
+struct ThreadBuffer {
+    records: Vec<SpanRecord>,
+    stack: Vec<usize>,
+}
+
+thread_local! {
+    static BUFFER: RefCell<ThreadBuffer> = RefCell::new(ThreadBuffer::default());
+}
+
+/// Enable or disable recording of spans globally. Profiling is enabled by default.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A timing span, which is recorded when it is dropped. Usually created using [`profile!`].
+///
+/// Spans are buffered per thread, and handed to the profiler once the outermost span of the
+/// thread ends. They are then aggregated by the next call to [`end_frame`].
+pub struct Span {
+    index: Option<usize>,
+}
+
+impl Span {
+    pub fn new(name: &'static str) -> Self {
+        if !is_enabled() {
+            return Span { index: None };
+        }
+        let _ = *EPOCH;
+        let now = Instant::now();
+        BUFFER.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            let index = buffer.records.len();
+            let parent = buffer.stack.last().copied();
+            buffer.records.push(SpanRecord {
+                name,
+                parent,
+                start: now,
+                end: now,
+            });
+            buffer.stack.push(index);
+            Span { index: Some(index) }
+        })
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(index) = self.index else {
+            return;
+        };
+        let now = Instant::now();
+        BUFFER.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            buffer.records[index].end = now;
+            buffer.stack.retain(|i| *i != index);
+            if buffer.stack.is_empty() {
+                let records = std::mem::take(&mut buffer.records);
+                let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+                PENDING.lock().push(ThreadSpans { thread, records });
+            }
+        });
+    }
+}
+
+/// Time the rest of the enclosing scope. The span name defaults to the enclosing function path.
+///
+/// ```
+/// use mvutils::profile;
+///
+/// fn update() {
+///     profile!();
+///     {
+///         profile!("physics");
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! profile {
+    () => {
+        let _profile_span = $crate::profiler::Span::new({
+            fn f() {}
+            fn name_of<T>(_: T) -> &'static str {
+                ::std::any::type_name::<T>()
+            }
+            let name = name_of(f);
+            &name[..name.len() - 3]
+        });
+    };
+    ($name:expr) => {
+        let _profile_span = $crate::profiler::Span::new($name);
+    };
+}
+
+/// Aggregated timings of all spans with the same name and parent path.
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub struct ProfileNode {
+    pub name: String,
+    pub calls: u32,
+    /// The time spent in the span including its children.
+    pub inclusive: Duration,
+    /// The time spent in the span excluding its children.
+    pub exclusive: Duration,
+    pub children: Vec<ProfileNode>,
+}
+
+/// A single recorded span, in microseconds since the profiler started.
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub struct SpanEvent {
+    pub name: String,
+    pub start_us: u64,
+    pub duration_us: u64,
+}
+
+#[derive(Savable, Clone, Debug, PartialEq)]
+pub struct ThreadReport {
+    pub thread: String,
+    pub roots: Vec<ProfileNode>,
+    pub events: Vec<SpanEvent>,
+}
+
+/// The spans recorded during a frame, see [`end_frame`].
+#[derive(Savable, Clone, Debug, Default, PartialEq)]
+pub struct FrameReport {
+    pub threads: Vec<ThreadReport>,
+}
+
+fn micros(instant: Instant) -> u64 {
+    instant.saturating_duration_since(*EPOCH).as_micros() as u64
+}
+
+fn build_nodes(records: &[SpanRecord], children: &[Vec<usize>], indices: &[usize]) -> Vec<ProfileNode> {
+    let mut nodes: Vec<ProfileNode> = Vec::new();
+    for &i in indices {
+        let record = &records[i];
+        let inclusive = record.end.saturating_duration_since(record.start);
+        let child_nodes = build_nodes(records, children, &children[i]);
+        let child_time = children[i]
+            .iter()
+            .map(|c| records[*c].end.saturating_duration_since(records[*c].start))
+            .sum::<Duration>();
+
+        match nodes.iter_mut().find(|n| n.name == record.name) {
+            Some(node) => {
+                node.calls += 1;
+                node.inclusive += inclusive;
+                node.exclusive += inclusive.saturating_sub(child_time);
+                for child in child_nodes {
+                    merge_node(&mut node.children, child);
+                }
+            }
+            None => nodes.push(ProfileNode {
+                name: record.name.to_string(),
+                calls: 1,
+                inclusive,
+                exclusive: inclusive.saturating_sub(child_time),
+                children: child_nodes,
+            }),
+        }
+    }
+    nodes
+}
+
+fn merge_node(nodes: &mut Vec<ProfileNode>, node: ProfileNode) {
+    match nodes.iter_mut().find(|n| n.name == node.name) {
+        Some(existing) => {
+            existing.calls += node.calls;
+            existing.inclusive += node.inclusive;
+            existing.exclusive += node.exclusive;
+            for child in node.children {
+                merge_node(&mut existing.children, child);
+            }
+        }
+        None => nodes.push(node),
+    }
+}
+
+/// Mark the end of a frame, aggregating all spans completed since the previous call into a tree
+/// per thread. Spans which are still running are part of the next frame.
+pub fn end_frame() -> FrameReport {
+    let pending = std::mem::take(&mut *PENDING.lock());
+    let mut report = FrameReport::default();
+
+    for spans in pending {
+        let records = spans.records;
+        let mut children = vec![Vec::new(); records.len()];
+        let mut roots = Vec::new();
+        for (i, record) in records.iter().enumerate() {
+            match record.parent {
+                Some(parent) => children[parent].push(i),
+                None => roots.push(i),
+            }
+        }
+        let nodes = build_nodes(&records, &children, &roots);
+        let events = records
+            .iter()
+            .map(|r| SpanEvent {
+                name: r.name.to_string(),
+                start_us: micros(r.start),
+                duration_us: r.end.saturating_duration_since(r.start).as_micros() as u64,
+            })
+            .collect::<Vec<_>>();
+
+        match report.threads.iter_mut().find(|t| t.thread == spans.thread) {
+            Some(thread) => {
+                for node in nodes {
+                    merge_node(&mut thread.roots, node);
+                }
+                thread.events.extend(events);
+            }
+            None => report.threads.push(ThreadReport {
+                thread: spans.thread,
+                roots: nodes,
+                events,
+            }),
+        }
+    }
+
+    report
+}
+
+impl FrameReport {
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+
+    /// Find the aggregated node of a span by its path of names from the root, on any thread.
+    pub fn find(&self, path: &[&str]) -> Option<&ProfileNode> {
+        self.threads.iter().find_map(|thread| {
+            let (first, rest) = path.split_first()?;
+            let mut node = thread.roots.iter().find(|n| n.name == *first)?;
+            for name in rest {
+                node = node.children.iter().find(|n| n.name == *name)?;
+            }
+            Some(node)
+        })
+    }
+
+    /// Export the recorded spans in the chrome tracing format, viewable in `chrome://tracing`
+    /// or Perfetto.
+    pub fn to_chrome_trace(&self) -> String {
+        let mut events = Vec::new();
+        for (tid, thread) in self.threads.iter().enumerate() {
+            let mut meta = Json::object();
+            meta.insert("name", "thread_name");
+            meta.insert("ph", "M");
+            meta.insert("pid", 0);
+            meta.insert("tid", tid);
+            let mut args = Json::object();
+            args.insert("name", thread.thread.as_str());
+            meta.insert("args", args);
+            events.push(meta);
+
+            for event in &thread.events {
+                let mut json = Json::object();
+                json.insert("name", event.name.as_str());
+                json.insert("ph", "X");
+                json.insert("ts", event.start_us);
+                json.insert("dur", event.duration_us);
+                json.insert("pid", 0);
+                json.insert("tid", tid);
+                events.push(json);
+            }
+        }
+        Json::Array(events).to_string()
+    }
+
+    /// Print the span tree with colors to the standard output.
+    pub fn print(&self) {
+        fn print_node(printer: Printer, node: &ProfileNode, depth: usize) -> Printer {
+            let mut printer = printer
+                .text(&"  ".repeat(depth))
+                .col_for(Col::Cyan, &node.name)
+                .text(&format!(" x{} ", node.calls))
+                .col_for(Col::Yellow, &format!("{:.3}ms", node.inclusive.as_secs_f64() * 1000.0))
+                .col_for_ln(Col::DarkGrey, &format!(" (self {:.3}ms)", node.exclusive.as_secs_f64() * 1000.0));
+            for child in &node.children {
+                printer = print_node(printer, child, depth + 1);
+            }
+            printer
+        }
+
+        let mut printer = Printer::start();
+        for thread in &self.threads {
+            printer = printer.col_for_ln(Col::Green, &format!("[{}]", thread.thread));
+            for node in &thread.roots {
+                printer = print_node(printer, node, 1);
+            }
+        }
+        printer.flush();
+    }
+}
+
+impl Display for FrameReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn write_node(f: &mut Formatter<'_>, node: &ProfileNode, depth: usize) -> std::fmt::Result {
+            writeln!(
+                f,
+                "{:indent$}{} x{} {:.3}ms (self {:.3}ms)",
+                "",
+                node.name,
+                node.calls,
+                node.inclusive.as_secs_f64() * 1000.0,
+                node.exclusive.as_secs_f64() * 1000.0,
+                indent = depth * 2
+            )?;
+            for child in &node.children {
+                write_node(f, child, depth + 1)?;
+            }
+            Ok(())
+        }
+
+        for thread in &self.threads {
+            writeln!(f, "[{}]", thread.thread)?;
+            for node in &thread.roots {
+                write_node(f, node, 1)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::print::terminal::{self, NoEcho, RawMode};
+use crate::print::{Printer, Style};
+use std::fmt::Display;
+use std::io::{BufRead, Error, ErrorKind, IsTerminal, Read, Write};
+use std::str::FromStr;
+
+fn question(question: &str, hint: &str) -> Printer {
+    Printer::start()
+        .style(Style::Success, "? ")
+        .style(Style::Heading, question)
+        .style(Style::Muted, hint)
+        .text(" ")
+}
+
+fn error(message: impl Display) -> Printer {
+    Printer::start().style_ln(Style::Error, &format!("  {}", message))
+}
+
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "Input ended before an answer was given"));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Ask a yes or no question, returning false for an empty answer.
+pub fn confirm(question: &str) -> bool {
+    confirm_with(&mut std::io::stdin().lock(), &mut std::io::stdout(), question).unwrap()
+}
+
+/// Like [`confirm`], reading from and writing to the given streams.
+pub fn confirm_with(reader: &mut impl BufRead, writer: &mut impl Write, q: &str) -> std::io::Result<bool> {
+    loop {
+        write!(writer, "{}", question(q, " [y/N]").def())?;
+        writer.flush()?;
+        match read_line(reader)?.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "" | "n" | "no" => return Ok(false),
+            _ => write!(writer, "{}", error("Please answer yes or no"))?,
+        }
+    }
+}
+
+/// Ask for a value, asking again until the answer can be parsed.
+pub fn input<T: FromStr>(question: &str) -> T
+where
+    T::Err: Display,
+{
+    input_with(&mut std::io::stdin().lock(), &mut std::io::stdout(), question).unwrap()
+}
+
+/// Like [`input`], reading from and writing to the given streams.
+pub fn input_with<T: FromStr>(reader: &mut impl BufRead, writer: &mut impl Write, q: &str) -> std::io::Result<T>
+where
+    T::Err: Display,
+{
+    loop {
+        write!(writer, "{}", question(q, "").def())?;
+        writer.flush()?;
+        match read_line(reader)?.trim().parse() {
+            Ok(value) => return Ok(value),
+            Err(e) => write!(writer, "{}", error(e))?,
+        }
+    }
+}
+
+/// Let the user pick one of the options, returning its index.
+///
+/// In a terminal the selection is made with the arrow keys and enter, otherwise the options are
+/// numbered and the number is read as a line.
+pub fn select<T: Display>(question: &str, options: &[T]) -> usize {
+    assert!(!options.is_empty(), "Cannot select from an empty list of options");
+    let mut stdout = std::io::stdout();
+    if std::io::stdin().is_terminal() && stdout.is_terminal() {
+        if let Some(_raw) = RawMode::enable() {
+            return select_interactive(&mut stdout, question, options).unwrap();
+        }
+    }
+    select_with(&mut std::io::stdin().lock(), &mut stdout, question, options).unwrap()
+}
+
+/// Like [`select`] using numbered options, reading from and writing to the given streams.
+pub fn select_with<T: Display>(reader: &mut impl BufRead, writer: &mut impl Write, q: &str, options: &[T]) -> std::io::Result<usize> {
+    write!(writer, "{}", question(q, "").def().ln())?;
+    for (i, option) in options.iter().enumerate() {
+        write!(writer, "{}", Printer::start().style(Style::Info, &format!("  {})", i + 1)).text(&format!(" {}", option)).def().ln())?;
+    }
+    loop {
+        write!(writer, "{}", Printer::start().style(Style::Muted, &format!("  [1-{}]", options.len())).text(" ").def())?;
+        writer.flush()?;
+        match read_line(reader)?.trim().parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+            _ => write!(writer, "{}", error(format!("Please enter a number from 1 to {}", options.len())))?,
+        }
+    }
+}
+
+fn select_interactive<T: Display>(writer: &mut impl Write, q: &str, options: &[T]) -> std::io::Result<usize> {
+    let mut stdin = std::io::stdin().lock();
+    let mut selected = 0;
+    write!(writer, "{}\r\n", question(q, " (use arrow keys)").def())?;
+    loop {
+        for (i, option) in options.iter().enumerate() {
+            let line = if i == selected {
+                Printer::start().style(Style::Info, &format!("> {}", option))
+            } else {
+                Printer::start().text(&format!("  {}", option))
+            };
+            write!(writer, "{}{}\r\n", terminal::CLEAR_LINE, line.def())?;
+        }
+        writer.flush()?;
+
+        let mut byte = [0u8];
+        stdin.read_exact(&mut byte)?;
+        match byte[0] {
+            b'\r' | b'\n' => return Ok(selected),
+            3 => return Err(Error::new(ErrorKind::Interrupted, "Selection was cancelled")),
+            b'k' => selected = selected.saturating_sub(1),
+            b'j' => selected = (selected + 1).min(options.len() - 1),
+            n @ b'1'..=b'9' if ((n - b'1') as usize) < options.len() => selected = (n - b'1') as usize,
+            0x1b => {
+                let mut sequence = [0u8; 2];
+                stdin.read_exact(&mut sequence)?;
+                match sequence {
+                    [b'[', b'A'] => selected = selected.saturating_sub(1),
+                    [b'[', b'B'] => selected = (selected + 1).min(options.len() - 1),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        write!(writer, "{}", terminal::move_up(options.len() as u16))?;
+    }
+}
+
+/// Ask for a password without echoing the typed characters.
+pub fn password(question: &str) -> String {
+    password_with(&mut std::io::stdin().lock(), &mut std::io::stdout(), question).unwrap()
+}
+
+/// Like [`password`], reading from and writing to the given streams. Echo is only disabled if the
+/// standard input is a terminal.
+pub fn password_with(reader: &mut impl BufRead, writer: &mut impl Write, q: &str) -> std::io::Result<String> {
+    write!(writer, "{}", question(q, "").def())?;
+    writer.flush()?;
+    let echo = NoEcho::enable();
+    let line = read_line(reader);
+    drop(echo);
+    writeln!(writer)?;
+    line
+}
@@ -0,0 +1,363 @@
+use crate::platform::Instant;
+use hashbrown::HashMap;
+use crate::sync::Mutex;
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hash};
+use std::time::Duration;
+
+/// The algorithm and limits of a [`RateLimiter`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RateLimit {
+    /// Allows bursts of up to `capacity` permits, refilling at `per_second` permits per second. A
+    /// rate of zero never refills, so the bucket is a fixed quota.
+    TokenBucket { capacity: u32, per_second: f64 },
+    /// Allows at most `max` permits within any time span of length `window`.
+    SlidingWindow { max: u32, window: Duration },
+}
+
+impl RateLimit {
+    fn validate(&self) {
+        if let RateLimit::TokenBucket { per_second, .. } = self {
+            if !per_second.is_finite() || *per_second < 0.0 {
+                panic!("Token bucket rate must be finite and not negative, got {per_second}!");
+            }
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        match self {
+            RateLimit::TokenBucket { capacity, .. } => *capacity,
+            RateLimit::SlidingWindow { max, .. } => *max,
+        }
+    }
+}
+
+enum State {
+    Bucket { tokens: f64, last: Instant },
+    Window { events: VecDeque<Instant> },
+}
+
+struct Inner {
+    limit: RateLimit,
+    state: State,
+}
+
+impl Inner {
+    fn new(limit: RateLimit) -> Self {
+        let state = match limit {
+            RateLimit::TokenBucket { capacity, .. } => State::Bucket {
+                tokens: capacity as f64,
+                last: Instant::now(),
+            },
+            RateLimit::SlidingWindow { max, .. } => State::Window {
+                events: VecDeque::with_capacity(max as usize),
+            },
+        };
+        Inner { limit, state }
+    }
+
+    fn update(&mut self, now: Instant) {
+        match (&self.limit, &mut self.state) {
+            (RateLimit::TokenBucket { capacity, per_second }, State::Bucket { tokens, last }) => {
+                let elapsed = now.saturating_duration_since(*last).as_secs_f64();
+                *tokens = (*tokens + elapsed * per_second).min(*capacity as f64);
+                *last = now;
+            }
+            (RateLimit::SlidingWindow { window, .. }, State::Window { events }) => {
+                while events.front().is_some_and(|t| now.saturating_duration_since(*t) >= *window) {
+                    events.pop_front();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Take the permits, or return how long to wait until they might be available.
+    fn acquire(&mut self, n: u32, now: Instant) -> Result<(), Duration> {
+        self.update(now);
+        match (&self.limit, &mut self.state) {
+            (RateLimit::TokenBucket { per_second, .. }, State::Bucket { tokens, .. }) => {
+                if *tokens >= n as f64 {
+                    *tokens -= n as f64;
+                    Ok(())
+                } else {
+                    Err(refill_time(n as f64 - *tokens, *per_second))
+                }
+            }
+            (RateLimit::SlidingWindow { max, window }, State::Window { events }) => {
+                let free = *max as usize - events.len();
+                if free >= n as usize {
+                    events.extend(std::iter::repeat(now).take(n as usize));
+                    Ok(())
+                } else {
+                    let oldest = events.get(n as usize - free - 1);
+                    Err(oldest.map_or(*window, |t| (*t + *window).saturating_duration_since(now)))
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_idle(&mut self, now: Instant) -> bool {
+        self.update(now);
+        match (&self.limit, &self.state) {
+            (RateLimit::TokenBucket { capacity, .. }, State::Bucket { tokens, .. }) => *tokens >= *capacity as f64,
+            (_, State::Window { events }) => events.is_empty(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The time to refill the missing tokens, or [`Duration::MAX`] if they never refill.
+fn refill_time(missing: f64, per_second: f64) -> Duration {
+    if missing <= 0.0 {
+        return Duration::ZERO;
+    }
+    Duration::try_from_secs_f64(missing / per_second).unwrap_or(Duration::MAX)
+}
+
+fn check_capacity(limit: &RateLimit, n: u32) {
+    if n > limit.capacity() {
+        panic!("Cannot acquire {n} permits from a rate limiter with a capacity of {}!", limit.capacity());
+    }
+}
+
+/// Throttles work to a [`RateLimit`]. All methods take `&self`, so a limiter can be shared between threads.
+///
+/// ```
+/// use mvutils::rate_limit::RateLimiter;
+///
+/// let limiter = RateLimiter::token_bucket(10, 5.0);
+/// assert!(limiter.try_acquire());
+/// limiter.acquire_blocking();
+/// ```
+pub struct RateLimiter {
+    inner: Mutex<Inner>,
+}
+
+impl RateLimiter {
+    /// # Panics
+    /// If the rate of a token bucket is negative or not finite.
+    pub fn new(limit: RateLimit) -> Self {
+        limit.validate();
+        RateLimiter {
+            inner: Mutex::new(Inner::new(limit)),
+        }
+    }
+
+    /// A token bucket limiter which starts full.
+    pub fn token_bucket(capacity: u32, per_second: f64) -> Self {
+        RateLimiter::new(RateLimit::TokenBucket { capacity, per_second })
+    }
+
+    pub fn sliding_window(max: u32, window: Duration) -> Self {
+        RateLimiter::new(RateLimit::SlidingWindow { max, window })
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.inner.lock().limit
+    }
+
+    pub fn try_acquire(&self) -> bool {
+        self.try_acquire_n(1)
+    }
+
+    /// Take the permits if they are all available right now. Never succeeds if more permits than
+    /// the capacity are requested.
+    pub fn try_acquire_n(&self, n: u32) -> bool {
+        self.inner.lock().acquire(n, Instant::now()).is_ok()
+    }
+
+    /// The time until the permits might be available, or zero if they are available right now.
+    /// [`Duration::MAX`] if a token bucket which never refills does not have enough tokens left.
+    pub fn time_until_available(&self, n: u32) -> Duration {
+        let mut inner = self.inner.lock();
+        let now = Instant::now();
+        inner.update(now);
+        match (&inner.limit, &inner.state) {
+            (RateLimit::TokenBucket { per_second, .. }, State::Bucket { tokens, .. }) => {
+                refill_time(n as f64 - *tokens, *per_second)
+            }
+            (RateLimit::SlidingWindow { max, window }, State::Window { events }) => {
+                let free = *max as usize - events.len();
+                match (n as usize).checked_sub(free + 1) {
+                    Some(i) => events.get(i).map_or(*window, |t| (*t + *window).saturating_duration_since(now)),
+                    None => Duration::ZERO,
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn acquire_blocking(&self) {
+        self.acquire_blocking_n(1)
+    }
+
+    /// Block until the permits are available and take them. Blocks forever if they never become
+    /// available.
+    ///
+    /// # Panics
+    /// If more permits than the capacity are requested.
+    pub fn acquire_blocking_n(&self, n: u32) {
+        check_capacity(&self.limit(), n);
+        loop {
+            let result = self.inner.lock().acquire(n, Instant::now());
+            match result {
+                Ok(()) => return,
+                Err(wait) => std::thread::sleep(wait),
+            }
+        }
+    }
+
+    /// Like [`RateLimiter::acquire_blocking_n`], but gives up after the timeout, returning whether the
+    /// permits were taken.
+    pub fn acquire_timeout(&self, n: u32, timeout: Duration) -> bool {
+        if n > self.limit().capacity() {
+            return false;
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let now = Instant::now();
+            let result = self.inner.lock().acquire(n, now);
+            match result {
+                Ok(()) => return true,
+                Err(wait) if now.checked_add(wait).is_some_and(|t| t <= deadline) => std::thread::sleep(wait),
+                Err(_) => return false,
+            }
+        }
+    }
+
+    /// Wait until the permits are available and take them. The future does not depend on any runtime.
+    ///
+    /// # Panics
+    /// If more permits than the capacity are requested.
+    #[cfg(feature = "async")]
+    pub fn acquire_async(&self, n: u32) -> Acquire<'_> {
+        check_capacity(&self.limit(), n);
+        Acquire { limiter: self, n }
+    }
+}
+
+/// The future returned by [`RateLimiter::acquire_async`].
+#[cfg(feature = "async")]
+pub struct Acquire<'a> {
+    limiter: &'a RateLimiter,
+    n: u32,
+}
+
+#[cfg(feature = "async")]
+impl std::future::Future for Acquire<'_> {
+    type Output = ();
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<()> {
+        let result = self.limiter.inner.lock().acquire(self.n, Instant::now());
+        match result {
+            Ok(()) => std::task::Poll::Ready(()),
+            Err(Duration::MAX) => std::task::Poll::Pending,
+            Err(wait) => {
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(wait);
+                    waker.wake();
+                });
+                std::task::Poll::Pending
+            }
+        }
+    }
+}
+
+/// A separate [`RateLimiter`] for every key, for example per client address. Use an identity hasher
+/// from [`hashers`](crate::hashers) for integer keys.
+pub struct KeyedRateLimiter<K, S = RandomState> {
+    limit: RateLimit,
+    limiters: Mutex<HashMap<K, Inner, S>>,
+}
+
+impl<K: Hash + Eq> KeyedRateLimiter<K> {
+    pub fn new(limit: RateLimit) -> Self {
+        KeyedRateLimiter::with_hasher(limit, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher> KeyedRateLimiter<K, S> {
+    /// # Panics
+    /// If the rate of a token bucket is negative or not finite.
+    pub fn with_hasher(limit: RateLimit, hasher: S) -> Self {
+        limit.validate();
+        KeyedRateLimiter {
+            limit,
+            limiters: Mutex::new(HashMap::with_hasher(hasher)),
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// The number of keys currently tracked.
+    pub fn len(&self) -> usize {
+        self.limiters.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limiters.lock().is_empty()
+    }
+
+    fn acquire(&self, key: &K, n: u32) -> Result<(), Duration>
+    where
+        K: Clone,
+    {
+        let mut limiters = self.limiters.lock();
+        let inner = match limiters.get_mut(key) {
+            Some(inner) => inner,
+            None => limiters.entry(key.clone()).or_insert_with(|| Inner::new(self.limit)),
+        };
+        inner.acquire(n, Instant::now())
+    }
+
+    pub fn try_acquire(&self, key: &K) -> bool
+    where
+        K: Clone,
+    {
+        self.try_acquire_n(key, 1)
+    }
+
+    pub fn try_acquire_n(&self, key: &K, n: u32) -> bool
+    where
+        K: Clone,
+    {
+        self.acquire(key, n).is_ok()
+    }
+
+    /// Block until the permits for the key are available and take them.
+    ///
+    /// # Panics
+    /// If more permits than the capacity are requested.
+    pub fn acquire_blocking_n(&self, key: &K, n: u32)
+    where
+        K: Clone,
+    {
+        check_capacity(&self.limit, n);
+        while let Err(wait) = self.acquire(key, n) {
+            std::thread::sleep(wait);
+        }
+    }
+
+    pub fn acquire_blocking(&self, key: &K)
+    where
+        K: Clone,
+    {
+        self.acquire_blocking_n(key, 1)
+    }
+
+    pub fn remove(&self, key: &K) {
+        self.limiters.lock().remove(key);
+    }
+
+    /// Forget all keys whose limiter is back to its initial state, to keep memory bounded.
+    pub fn cleanup(&self) {
+        let now = Instant::now();
+        self.limiters.lock().retain(|_, inner| !inner.is_idle(now));
+    }
+}
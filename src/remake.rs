@@ -1,5 +1,5 @@
 use std::ops::{Deref, DerefMut};
-use crate::save::{Loader, Savable, Saver};
+use crate::save::{Loader, Savable, SaveError, Saver};
 
 pub struct Remake<T> {
     item: Option<T>,
@@ -58,7 +58,7 @@ impl<T: Savable> Savable for Remake<T> {
         self.get().save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         Ok(Remake::new(T::load(loader)?))
     }
 }
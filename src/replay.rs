@@ -0,0 +1,278 @@
+use crate as mvutils;
+use crate::platform::Instant;
+use crate::save::Savable;
+use crate::state::{State, StateWriteGuard};
+use bytebuffer::ByteBuffer;
+use mvutils_proc_macro::Savable;
+use crate::sync::{Mutex, RwLockReadGuard};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Savable, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    /// The value a state was tracked with.
+    Initial,
+    /// The value of a state after a write.
+    Write,
+    /// An explicitly recorded command.
+    Command,
+}
+
+#[derive(Savable, Clone, Debug)]
+pub struct ReplayEvent {
+    /// The time since the recording started.
+    pub time: Duration,
+    pub channel: u32,
+    pub kind: EventKind,
+    /// The version of the state after the write, or 0 for other events.
+    pub version: u64,
+    pub data: ByteBuffer,
+}
+
+impl ReplayEvent {
+    /// Load the recorded state value or command.
+    pub fn value<T: Savable>(&self) -> Result<T, String> {
+        let mut buffer = self.data.clone();
+        buffer.set_rpos(0);
+        T::load(&mut buffer).map_err(Into::into)
+    }
+}
+
+struct RecorderInner {
+    start: Instant,
+    events: Mutex<Vec<ReplayEvent>>,
+    channels: Mutex<u32>,
+}
+
+/// Records writes to [`State`]s and explicit commands, to be replayed later using a [`Replay`].
+/// Cloning returns a handle to the same recording.
+///
+/// ```
+/// use mvutils::replay::Recorder;
+/// use mvutils::state::State;
+///
+/// let recorder = Recorder::new();
+/// let score = recorder.track(State::new(0u32));
+/// *score.write() += 10;
+///
+/// let replay = recorder.finish();
+/// let fresh = replay.fresh_state::<u32>(score.channel()).unwrap();
+/// replay.apply(score.channel(), &fresh).unwrap();
+/// assert_eq!(*fresh.read(), 10);
+/// assert_eq!(fresh.get_version(), score.state().get_version());
+/// ```
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Arc<RecorderInner>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder {
+            inner: Arc::new(RecorderInner {
+                start: Instant::now(),
+                events: Mutex::new(Vec::new()),
+                channels: Mutex::new(0),
+            }),
+        }
+    }
+
+    fn record(&self, channel: u32, kind: EventKind, version: u64, value: &impl Savable) {
+        let mut data = ByteBuffer::new();
+        value.save(&mut data);
+        let time = self.inner.start.elapsed();
+        self.inner.events.lock().push(ReplayEvent {
+            time,
+            channel,
+            kind,
+            version,
+            data,
+        });
+    }
+
+    /// Allocate a new channel for recording commands.
+    pub fn channel(&self) -> u32 {
+        let mut channels = self.inner.channels.lock();
+        *channels += 1;
+        *channels - 1
+    }
+
+    /// Start recording all writes to the state done through the returned wrapper. The current value
+    /// is recorded as the initial value of a new channel.
+    pub fn track<T: Savable>(&self, state: State<T>) -> RecordedState<T> {
+        let channel = self.channel();
+        self.record(channel, EventKind::Initial, state.get_version(), &*state.read());
+        RecordedState {
+            state,
+            channel,
+            recorder: self.clone(),
+        }
+    }
+
+    pub fn command<C: Savable>(&self, channel: u32, command: &C) {
+        self.record(channel, EventKind::Command, 0, command);
+    }
+
+    /// The recording so far.
+    pub fn finish(&self) -> Replay {
+        Replay {
+            events: self.inner.events.lock().clone(),
+        }
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder::new()
+    }
+}
+
+/// A [`State`] whose writes are recorded by a [`Recorder`].
+pub struct RecordedState<T> {
+    state: State<T>,
+    channel: u32,
+    recorder: Recorder,
+}
+
+impl<T: Savable> RecordedState<T> {
+    pub fn channel(&self) -> u32 {
+        self.channel
+    }
+
+    /// The wrapped state. Writes done directly on it are not recorded.
+    pub fn state(&self) -> &State<T> {
+        &self.state
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.state.read()
+    }
+
+    /// Write to the state, recording the new value once the guard is dropped.
+    pub fn write(&self) -> RecordedWriteGuard<'_, T> {
+        RecordedWriteGuard {
+            guard: Some(self.state.write()),
+            state: self,
+        }
+    }
+}
+
+pub struct RecordedWriteGuard<'a, T: Savable> {
+    guard: Option<StateWriteGuard<'a, T>>,
+    state: &'a RecordedState<T>,
+}
+
+impl<T: Savable> Deref for RecordedWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl<T: Savable> DerefMut for RecordedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.as_mut().unwrap()
+    }
+}
+
+impl<T: Savable> Drop for RecordedWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        if let Some(guard) = self.guard.take() {
+            let version = self.state.state.get_version() + 1;
+            self.state.recorder.record(self.state.channel, EventKind::Write, version, &*guard);
+        }
+    }
+}
+
+/// A finished recording, which can be saved and replayed against fresh states.
+#[derive(Savable, Clone, Debug, Default)]
+pub struct Replay {
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    /// The time of the last event.
+    pub fn duration(&self) -> Duration {
+        self.events.last().map_or(Duration::ZERO, |e| e.time)
+    }
+
+    pub fn channel_events(&self, channel: u32) -> impl Iterator<Item = &ReplayEvent> {
+        self.events.iter().filter(move |e| e.channel == channel)
+    }
+
+    /// The value the state recorded on the channel was tracked with.
+    pub fn initial<T: Savable>(&self, channel: u32) -> Result<T, String> {
+        self.channel_events(channel)
+            .find(|e| e.kind == EventKind::Initial)
+            .ok_or_else(|| format!("No state recorded on channel {channel}!"))?
+            .value()
+    }
+
+    /// Create a state with the initial value of the channel.
+    pub fn fresh_state<T: Savable>(&self, channel: u32) -> Result<State<T>, String> {
+        Ok(State::new(self.initial(channel)?))
+    }
+
+    /// Apply all recorded writes of the channel to the state, checking that the state ends up with the
+    /// same version after every write. Returns the number of writes.
+    pub fn apply<T: Savable>(&self, channel: u32, state: &State<T>) -> Result<usize, String> {
+        let mut count = 0;
+        for event in self.channel_events(channel).filter(|e| e.kind == EventKind::Write) {
+            *state.write() = event.value()?;
+            count += 1;
+            if state.get_version() != event.version {
+                return Err(format!(
+                    "Replay diverged on channel {channel}: expected version {}, got {}!",
+                    event.version,
+                    state.get_version()
+                ));
+            }
+        }
+        Ok(count)
+    }
+
+    /// All commands recorded on the channel, with their time.
+    pub fn commands<C: Savable>(&self, channel: u32) -> Result<Vec<(Duration, C)>, String> {
+        self.channel_events(channel)
+            .filter(|e| e.kind == EventKind::Command)
+            .map(|e| Ok((e.time, e.value()?)))
+            .collect()
+    }
+
+    pub fn player(&self) -> ReplayPlayer<'_> {
+        ReplayPlayer {
+            replay: self,
+            time: Duration::ZERO,
+            index: 0,
+        }
+    }
+}
+
+/// Steps through a [`Replay`] in time, for example once per frame.
+pub struct ReplayPlayer<'a> {
+    replay: &'a Replay,
+    time: Duration,
+    index: usize,
+}
+
+impl<'a> ReplayPlayer<'a> {
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.replay.events.len()
+    }
+
+    /// Advance the time, returning all events that happened in between.
+    pub fn advance(&mut self, delta: Duration) -> &'a [ReplayEvent] {
+        self.time += delta;
+        let start = self.index;
+        while self.replay.events.get(self.index).is_some_and(|e| e.time <= self.time) {
+            self.index += 1;
+        }
+        &self.replay.events[start..self.index]
+    }
+}
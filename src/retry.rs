@@ -0,0 +1,157 @@
+use crate::platform::Instant;
+use crate::thread::CancellationToken;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Backoff {
+    /// Wait the same duration between all attempts.
+    Fixed(Duration),
+    /// Start with the initial duration and multiply it by the factor after every attempt, up to the maximum.
+    Exponential {
+        initial: Duration,
+        max: Duration,
+        factor: f64,
+    },
+}
+
+/// Describes how often and how long to wait when retrying an operation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub backoff: Backoff,
+    /// The fraction of the delay which is randomized, from 0 (no jitter) to 1 (full jitter).
+    pub jitter: f64,
+    /// The maximum amount of attempts including the first one, or [`None`] for no limit.
+    pub max_attempts: Option<u32>,
+    /// The maximum time after which no further attempt is started, or [`None`] for no limit.
+    pub max_elapsed: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn fixed(delay: Duration) -> Self {
+        RetryPolicy {
+            backoff: Backoff::Fixed(delay),
+            jitter: 0.0,
+            max_attempts: Some(5),
+            max_elapsed: None,
+        }
+    }
+
+    /// Exponential backoff doubling the delay after every attempt, with 50% jitter.
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        RetryPolicy {
+            backoff: Backoff::Exponential {
+                initial,
+                max,
+                factor: 2.0,
+            },
+            jitter: 0.5,
+            max_attempts: Some(5),
+            max_elapsed: None,
+        }
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn max_elapsed(mut self, max_elapsed: Option<Duration>) -> Self {
+        self.max_elapsed = max_elapsed;
+        self
+    }
+
+    /// The delay after the given failed attempt (starting at 1), without jitter.
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max, factor } => {
+                let secs = initial.as_secs_f64() * factor.powi(attempt.saturating_sub(1).min(i32::MAX as u32) as i32);
+                Duration::try_from_secs_f64(secs).unwrap_or(max).min(max)
+            }
+        }
+    }
+
+    /// The delay after the given failed attempt (starting at 1), with jitter applied.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay(attempt);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 - self.jitter * random)
+    }
+
+    fn next_delay(&self, attempt: u32, start: Instant) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt >= max) {
+            return None;
+        }
+        let delay = self.delay(attempt);
+        if self.max_elapsed.is_some_and(|max| start.elapsed() + delay > max) {
+            return None;
+        }
+        Some(delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Exponential backoff from 100ms to 10s with up to 5 attempts.
+    fn default() -> Self {
+        RetryPolicy::exponential(Duration::from_millis(100), Duration::from_secs(10))
+    }
+}
+
+/// Call the function until it succeeds or the policy gives up, returning the last error.
+pub fn retry<T, E>(policy: &RetryPolicy, f: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    retry_if(policy, f, |_| true)
+}
+
+/// Call the function until it succeeds, it returns an error which is not retryable, or the policy
+/// gives up. Returns the last error.
+pub fn retry_if<T, E>(policy: &RetryPolicy, mut f: impl FnMut() -> Result<T, E>, retryable: impl Fn(&E) -> bool) -> Result<T, E> {
+    let start = Instant::now();
+    let mut attempt = 1;
+    loop {
+        let error = match f() {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        match policy.next_delay(attempt, start) {
+            Some(delay) if retryable(&error) => std::thread::sleep(delay),
+            _ => return Err(error),
+        }
+        attempt += 1;
+    }
+}
+
+/// Like [`retry_if`], but stops waiting and returns the last error as soon as the token is cancelled.
+pub fn retry_cancellable<T, E>(
+    policy: &RetryPolicy,
+    token: &CancellationToken,
+    mut f: impl FnMut() -> Result<T, E>,
+    retryable: impl Fn(&E) -> bool,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let mut attempt = 1;
+    loop {
+        let error = match f() {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        match policy.next_delay(attempt, start) {
+            Some(delay) if retryable(&error) => {
+                if token.wait_timeout(delay) {
+                    return Err(error);
+                }
+            }
+            _ => return Err(error),
+        }
+        attempt += 1;
+    }
+}
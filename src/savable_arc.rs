@@ -1,4 +1,4 @@
-use crate::save::{Loader, Savable, Saver};
+use crate::save::{Loader, Savable, SaveError, Saver};
 use hashbrown::HashMap;
 use std::sync::Arc;
 
@@ -15,7 +15,7 @@ impl<T: Id + StaticallyLoaded + 'static> Savable for Arc<T> {
         saver.push_u64(self.get_id())
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         Ok(T::get_map()
             .get(&u64::load(loader)?)
             .ok_or("Invalid ID".to_string())?
@@ -1,12 +1,40 @@
 use std::cell::{Cell, UnsafeCell};
 use std::hash::Hash;
 use bytebuffer::ByteBuffer;
+use std::ffi::OsString;
+use std::mem::ManuallyDrop;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use std::path::PathBuf;
 use std::ops::{Deref, Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
 use std::time::{Duration, Instant, SystemTime};
 use hashbrown::{HashMap, HashSet};
 use parking_lot::{Mutex, RwLock};
+use crate::unsafe_utils::DangerousCell;
 use crate::utils::Recover;
 
+pub use error::SaveError;
+
+pub mod chunked;
+pub mod dirty;
+pub mod endian;
+pub mod error;
+pub mod format;
+pub mod fs;
+pub mod poly;
+pub mod strings;
+pub mod testing;
+pub mod validate;
+
+#[cfg(feature = "archive")]
+pub mod archive;
+
+#[cfg(feature = "schema")]
+pub mod schema;
+
+#[cfg(feature = "save_trace")]
+pub mod trace;
+
 pub trait Saver {
     fn push_bytes(&mut self, bytes: &[u8]);
     fn push_bool(&mut self, bool: bool);
@@ -21,6 +49,53 @@ pub trait Saver {
     fn push_f32(&mut self, value: f32);
     fn push_f64(&mut self, value: f64);
     fn push_string(&mut self, value: &str);
+
+    /// The amount of bytes written to this saver so far, or 0 if the saver doesn't keep track.
+    fn bytes_written(&self) -> usize {
+        0
+    }
+
+    /// Whether this saver benefits from [`Saver::reserve`]. Derived [`Savable`] implementations only
+    /// compute size hints for savers that return true here, and only reserve when
+    /// [`Saver::bytes_written`] is 0, so savers returning true should also count written bytes.
+    fn can_reserve(&self) -> bool {
+        false
+    }
+
+    /// Hint that at least `additional` more bytes are about to be written.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Whether hash based collections are saved sorted by their saved bytes, so equal values always
+    /// produce the same bytes. See [`Deterministic`].
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+
+    /// Whether [`Saver::patch_u32`] is supported. Tagged structs write the length of each field in
+    /// front of it by patching it in after saving the field, and otherwise save every field into a
+    /// temporary [`ByteBuffer`] first, which loses any encoding specific to this saver.
+    fn can_patch(&self) -> bool {
+        false
+    }
+
+    /// Overwrite the u32 written when [`Saver::bytes_written`] was `position`. Only called if
+    /// [`Saver::can_patch`] returns true.
+    fn patch_u32(&mut self, position: usize, value: u32) {
+        let _ = (position, value);
+        unimplemented!("This saver can't patch written data")
+    }
+
+    /// Called by derived implementations before saving a field, see [`trace`].
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        let _ = name;
+    }
+
+    /// Called by derived implementations after saving a field.
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {}
 }
 
 pub trait Loader {
@@ -129,6 +204,211 @@ pub trait Loader {
     fn peek_f64_unchecked(&mut self) -> f64 {
         self.peek_f64().unwrap()
     }
+
+    /// Called with the length of a collection before its elements are loaded, so loaders of untrusted
+    /// data can reject huge lengths.
+    fn check_len(&mut self, _len: u64) -> Result<(), SaveError> {
+        Ok(())
+    }
+
+    /// Called before loading the contents of a collection or box, so loaders can limit the nesting depth.
+    fn enter(&mut self) -> Result<(), SaveError> {
+        Ok(())
+    }
+
+    /// Called after loading the contents of a collection or box.
+    fn exit(&mut self) {}
+
+    /// The current read position in bytes. Fails with [`SaveError::Unsupported`] by default, for
+    /// loaders reading from a stream.
+    fn position(&self) -> Result<usize, SaveError> {
+        Err(SaveError::Unsupported("position"))
+    }
+
+    /// Move the read position, failing if it lies beyond the end of the data. Fails with
+    /// [`SaveError::Unsupported`] by default, for loaders reading from a stream.
+    fn seek(&mut self, position: usize) -> Result<(), SaveError> {
+        let _ = position;
+        Err(SaveError::Unsupported("seek"))
+    }
+
+    /// Skip over the next bytes, for example an unknown section of a newer format.
+    fn skip(&mut self, amount: usize) -> Result<(), SaveError> {
+        self.pop_bytes(amount).map(|_| ()).ok_or(SaveError::eof("skipped bytes"))
+    }
+
+    /// Called by derived implementations before loading a field, see [`trace`].
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        let _ = name;
+    }
+
+    /// Called by derived implementations after a field was loaded successfully.
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {}
+
+    /// A loader which can only read the next `len` bytes. Once it is dropped, the position of this
+    /// loader is moved to the end of the range, whether or not all of it was read. Fails if this
+    /// loader does not support [`Loader::position`] and [`Loader::seek`].
+    fn scoped(&mut self, len: usize) -> Result<ScopedLoader<'_, Self>, SaveError> where Self: Sized {
+        ScopedLoader::new(self, len)
+    }
+}
+
+/// Collections never preallocate more elements than this, so a corrupted length fails when running
+/// out of data instead of attempting a huge allocation.
+pub(crate) const MAX_PREALLOCATION: u64 = 1 << 16;
+
+pub(crate) fn load_len(loader: &mut impl Loader) -> Result<u64, SaveError> {
+    let len = u64::load(loader)?;
+    loader.check_len(len)?;
+    Ok(len)
+}
+
+pub(crate) fn nested<L: Loader, T>(loader: &mut L, f: impl FnOnce(&mut L) -> Result<T, SaveError>) -> Result<T, SaveError> {
+    loader.enter()?;
+    let result = f(loader);
+    loader.exit();
+    result
+}
+
+/// A [`Loader`] restricted to a range of bytes of another loader, see [`Loader::scoped`]. Positions
+/// are relative to the start of the range.
+pub struct ScopedLoader<'a, L: Loader> {
+    inner: &'a mut L,
+    start: usize,
+    end: usize,
+}
+
+macro_rules! scoped_primitives {
+    ($($t:ty, $pop:ident, $peek:ident),*) => {
+        $(
+            fn $pop(&mut self) -> Option<$t> {
+                if self.remaining() >= std::mem::size_of::<$t>() {
+                    self.inner.$pop()
+                } else {
+                    None
+                }
+            }
+
+            fn $peek(&mut self) -> Option<$t> {
+                if self.remaining() >= std::mem::size_of::<$t>() {
+                    self.inner.$peek()
+                } else {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+impl<'a, L: Loader> ScopedLoader<'a, L> {
+    /// Fails if the loader does not support [`Loader::position`] and [`Loader::seek`], which are
+    /// needed to move to the end of the range when the scoped loader is dropped, or if the range
+    /// does not lie within the data.
+    pub fn new(inner: &'a mut L, len: usize) -> Result<Self, SaveError> {
+        let start = inner.position()?;
+        let end = start.checked_add(len).ok_or(SaveError::eof("scoped range"))?;
+        inner.seek(end)?;
+        inner.seek(start)?;
+        Ok(ScopedLoader { inner, start, end })
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The amount of bytes left in the range.
+    pub fn remaining(&self) -> usize {
+        self.inner.position().map_or(0, |position| self.end.saturating_sub(position))
+    }
+}
+
+impl<L: Loader> Loader for ScopedLoader<'_, L> {
+    fn pop_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        if self.remaining() >= amount {
+            self.inner.pop_bytes(amount)
+        } else {
+            None
+        }
+    }
+
+    fn pop_to_end(&mut self) -> Option<Vec<u8>> {
+        self.inner.pop_bytes(self.remaining())
+    }
+
+    fn pop_string(&mut self) -> Option<String> {
+        let len = self.peek_u32()? as usize;
+        if self.remaining() >= 4 + len {
+            self.inner.pop_string()
+        } else {
+            None
+        }
+    }
+
+    fn peek_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        if self.remaining() >= amount {
+            self.inner.peek_bytes(amount)
+        } else {
+            None
+        }
+    }
+
+    scoped_primitives!(
+        bool, pop_bool, peek_bool, u8, pop_u8, peek_u8, u16, pop_u16, peek_u16, u32, pop_u32, peek_u32,
+        u64, pop_u64, peek_u64, i8, pop_i8, peek_i8, i16, pop_i16, peek_i16, i32, pop_i32, peek_i32,
+        i64, pop_i64, peek_i64, f32, pop_f32, peek_f32, f64, pop_f64, peek_f64
+    );
+
+    fn check_len(&mut self, len: u64) -> Result<(), SaveError> {
+        self.inner.check_len(len)
+    }
+
+    fn enter(&mut self) -> Result<(), SaveError> {
+        self.inner.enter()
+    }
+
+    fn exit(&mut self) {
+        self.inner.exit();
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        self.inner.begin_field(name);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {
+        self.inner.end_field();
+    }
+
+    fn position(&self) -> Result<usize, SaveError> {
+        Ok(self.inner.position()? - self.start)
+    }
+
+    fn seek(&mut self, position: usize) -> Result<(), SaveError> {
+        if position > self.len() {
+            return Err(SaveError::custom(format!("Cannot seek to {} in a scope of {} bytes", position, self.len())));
+        }
+        self.inner.seek(self.start + position)
+    }
+
+    fn skip(&mut self, amount: usize) -> Result<(), SaveError> {
+        if self.remaining() < amount {
+            return Err(SaveError::eof("skipped bytes"));
+        }
+        self.inner.skip(amount)
+    }
+}
+
+impl<L: Loader> Drop for ScopedLoader<'_, L> {
+    fn drop(&mut self) {
+        let _ = self.inner.seek(self.end);
+    }
 }
 
 impl Saver for ByteBuffer {
@@ -183,9 +463,60 @@ impl Saver for ByteBuffer {
     fn push_string(&mut self, value: &str) {
         self.write_string(value);
     }
+
+    fn bytes_written(&self) -> usize {
+        self.len()
+    }
+
+    fn can_reserve(&self) -> bool {
+        true
+    }
+
+    fn can_patch(&self) -> bool {
+        self.get_wpos() == self.len()
+    }
+
+    fn patch_u32(&mut self, position: usize, value: u32) {
+        let wpos = self.get_wpos();
+        self.set_wpos(position);
+        self.write_u32(value);
+        self.set_wpos(wpos);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        // ByteBuffer doesn't expose its Vec's capacity, so take the data out and put it back.
+        self.flush_bits();
+        let (rpos, wpos, endian) = (self.get_rpos(), self.get_wpos(), self.endian());
+        let mut data = std::mem::take(self).into_vec();
+        data.reserve((wpos + additional).saturating_sub(data.len()));
+        *self = ByteBuffer::from_vec(data);
+        self.set_rpos(rpos);
+        self.set_wpos(wpos);
+        self.set_endian(endian);
+    }
 }
 
 impl Loader for ByteBuffer {
+    fn position(&self) -> Result<usize, SaveError> {
+        Ok(self.get_rpos())
+    }
+
+    fn seek(&mut self, position: usize) -> Result<(), SaveError> {
+        if position > self.len() {
+            return Err(SaveError::custom(format!("Cannot seek to {} in a buffer of {} bytes", position, self.len())));
+        }
+        self.set_rpos(position);
+        Ok(())
+    }
+
+    fn skip(&mut self, amount: usize) -> Result<(), SaveError> {
+        if self.len() - self.get_rpos() < amount {
+            return Err(SaveError::eof("skipped bytes"));
+        }
+        self.set_rpos(self.get_rpos() + amount);
+        Ok(())
+    }
+
     fn pop_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
         self.read_bytes(amount).ok()
     }
@@ -332,7 +663,49 @@ pub trait Savable: Sized {
     fn save_consume(self, saver: &mut impl Saver) {
         self.save(saver);
     }
-    fn load(loader: &mut impl Loader) -> Result<Self, String>;
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError>;
+
+    /// The amount of bytes [`Savable::save`] will write, if it is known up front. Collections use
+    /// it to reserve space for all of their elements at once. Derived implementations return
+    /// [`SaveSize::save_size`] if the type implements [`SaveSize`].
+    fn save_size_hint(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Required for unions deriving [`Savable`], as the derived `save` reads the union field selected
+/// by the `#[discriminant(field_or_fn)]` attribute, counting fields from 0 in declaration order.
+///
+/// ```ignore
+/// #[derive(Savable)]
+/// #[discriminant(kind)]
+/// union Value {
+///     kind: u8,
+///     int: IntValue,
+///     float: FloatValue,
+/// }
+///
+/// // SAFETY: IntValue and FloatValue start with `kind`, which is 1 or 2 respectively.
+/// unsafe impl UnionDiscriminant for Value {}
+/// ```
+///
+/// # Safety
+///
+/// For every value of the union that is saved, reading the discriminant must be valid, and it must
+/// return the index of a field which is initialized and holds a valid value of its type. A
+/// discriminant field must therefore be part of every field, for example a common first field of
+/// `#[repr(C)]` structs. Saving with a wrong discriminant reads an inactive field, which is
+/// undefined behavior.
+pub unsafe trait UnionDiscriminant {}
+
+/// Reserve the saved size of the elements of a collection, if the saver can make use of it and
+/// every element reports its size.
+pub(crate) fn reserve_elements(saver: &mut impl Saver, hints: impl Iterator<Item = Option<usize>>) {
+    if saver.can_reserve() {
+        if let Some(size) = hints.sum::<Option<usize>>() {
+            saver.reserve(size);
+        }
+    }
 }
 
 macro_rules! impl_savable_primitive {
@@ -343,8 +716,12 @@ macro_rules! impl_savable_primitive {
                     saver.$pu(*self)
                 }
 
-                fn load(loader: &mut impl Loader) -> Result<Self, String> {
-                    loader.$po().ok_or(format!("Failed to load {} from Loader!", stringify!($t)))
+                fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+                    loader.$po().ok_or(SaveError::eof(stringify!($t)))
+                }
+
+                fn save_size_hint(&self) -> Option<usize> {
+                    Some(std::mem::size_of::<$t>())
                 }
             }
         )*
@@ -369,9 +746,16 @@ macro_rules! impl_savable_tuple {
                 $( $rest.save(saver); )*
             }
 
-            fn load(loader: &mut impl Loader) -> Result<Self, String> {
+            fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
                 Ok(($first::load(loader)?,$($rest::load(loader)?),*))
             }
+
+            #[allow(clippy::needless_question_mark)]
+            fn save_size_hint(&self) -> Option<usize> {
+                #[allow(non_snake_case)]
+                let ($first, $($rest),*) = self;
+                Some($first.save_size_hint()? $( + $rest.save_size_hint()? )*)
+            }
         }
     };
 }
@@ -383,10 +767,14 @@ impl Savable for String {
         saver.push_string(self);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         loader
             .pop_string()
-            .ok_or("Failed to load String from Loader!".to_string())
+            .ok_or(SaveError::eof("String"))
+    }
+
+    fn save_size_hint(&self) -> Option<usize> {
+        Some(4 + self.len())
     }
 }
 
@@ -401,11 +789,11 @@ impl<T: Savable> Savable for Option<T> {
         }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         match u8::load(loader)? {
             0 => Ok(None),
             1 => Ok(Some(T::load(loader)?)),
-            _ => Err("Failed to load Option from Loader!".to_string()),
+            other => Err(SaveError::InvalidDiscriminant { ty: "Option", value: other as u64 }),
         }
     }
 }
@@ -424,21 +812,22 @@ impl<T: Savable, E: Savable> Savable for Result<T, E> {
         }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         match u8::load(loader)? {
             0 => Ok(Ok(T::load(loader)?)),
             1 => Ok(Err(E::load(loader)?)),
-            _ => Err("Failed to load Result from Loader!".to_string()),
+            other => Err(SaveError::InvalidDiscriminant { ty: "Result", value: other as u64 }),
         }
     }
 }
 
 impl<T: Savable, const N: usize> Savable for [T; N] {
     fn save(&self, saver: &mut impl Saver) {
+        reserve_elements(saver, self.iter().map(T::save_size_hint));
         self.iter().for_each(|t| t.save(saver));
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         core::array::try_from_fn(|_| T::load(loader))
     }
 }
@@ -446,18 +835,21 @@ impl<T: Savable, const N: usize> Savable for [T; N] {
 impl<T: Savable> Savable for Vec<T> {
     fn save(&self, saver: &mut impl Saver) {
         saver.push_u64(self.len() as u64);
+        reserve_elements(saver, self.iter().map(T::save_size_hint));
         for t in self {
             t.save(saver);
         }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let len = u64::load(loader)?;
-        let mut vec = Vec::with_capacity(len as usize);
-        for _ in 0..len {
-            vec.push(T::load(loader)?);
-        }
-        Ok(vec)
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = load_len(loader)?;
+        nested(loader, |loader| {
+            let mut vec = Vec::with_capacity(len.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..len {
+                vec.push(T::load(loader)?);
+            }
+            Ok(vec)
+        })
     }
 }
 
@@ -467,7 +859,7 @@ impl Savable for ByteBuffer {
         saver.push_bytes(self.as_bytes());
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         Vec::<u8>::load(loader).map(Into::into)
     }
 }
@@ -477,86 +869,126 @@ impl<T: Savable> Savable for Box<T> {
         self.deref().save(saver)
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(Box::new(T::load(loader)?))
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        nested(loader, |loader| Ok(Box::new(T::load(loader)?)))
+    }
+}
+
+impl<T: Savable> Savable for ManuallyDrop<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.deref().save(saver)
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(ManuallyDrop::new(T::load(loader)?))
     }
 }
 
 impl<T: Savable + Eq + Hash> Savable for std::collections::HashSet<T> {
     fn save(&self, saver: &mut impl Saver) {
         (self.len() as u64).save(saver);
-        self.iter().for_each(|t| t.save(saver));
+        reserve_elements(saver, self.iter().map(T::save_size_hint));
+        if saver.is_deterministic() {
+            sorted_by_bytes(self.iter(), |t, saver| t.save(saver)).into_iter().for_each(|t| t.save(saver));
+        } else {
+            self.iter().for_each(|t| t.save(saver));
+        }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let len = u64::load(loader)?;
-        let mut set = std::collections::HashSet::with_capacity(len as usize);
-        for _ in 0..len {
-            set.insert(T::load(loader)?);
-        }
-        Ok(set)
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = load_len(loader)?;
+        nested(loader, |loader| {
+            let mut set = std::collections::HashSet::with_capacity(len.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..len {
+                set.insert(T::load(loader)?);
+            }
+            Ok(set)
+        })
     }
 }
 
 impl<T: Savable + Eq + Hash> Savable for HashSet<T> {
     fn save(&self, saver: &mut impl Saver) {
         (self.len() as u64).save(saver);
-        self.iter().for_each(|t| t.save(saver));
+        reserve_elements(saver, self.iter().map(T::save_size_hint));
+        if saver.is_deterministic() {
+            sorted_by_bytes(self.iter(), |t, saver| t.save(saver)).into_iter().for_each(|t| t.save(saver));
+        } else {
+            self.iter().for_each(|t| t.save(saver));
+        }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let len = u64::load(loader)?;
-        let mut set = HashSet::with_capacity(len as usize);
-        for _ in 0..len {
-            set.insert(T::load(loader)?);
-        }
-        Ok(set)
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = load_len(loader)?;
+        nested(loader, |loader| {
+            let mut set = HashSet::with_capacity(len.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..len {
+                set.insert(T::load(loader)?);
+            }
+            Ok(set)
+        })
     }
 }
 
 impl<K: Savable + Eq + Hash, V: Savable> Savable for std::collections::HashMap<K, V> {
     fn save(&self, saver: &mut impl Saver) {
         (self.len() as u64).save(saver);
-        self.iter().for_each(|(k, v)| {
-            k.save(saver);
-            v.save(saver);
-        });
-    }
-
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let len = u64::load(loader)?;
-        let mut map = std::collections::HashMap::with_capacity(len as usize);
-
-        for _ in 0..len {
-            let k = K::load(loader)?;
-            let v = V::load(loader)?;
-            map.insert(k, v);
+        reserve_elements(saver, self.iter().map(|(k, v)| Some(k.save_size_hint()? + v.save_size_hint()?)));
+        if saver.is_deterministic() {
+            for (k, v) in sorted_by_bytes(self.iter(), |(k, _), saver| k.save(saver)) {
+                k.save(saver);
+                v.save(saver);
+            }
+        } else {
+            self.iter().for_each(|(k, v)| {
+                k.save(saver);
+                v.save(saver);
+            });
         }
+    }
 
-        Ok(map)
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = load_len(loader)?;
+        nested(loader, |loader| {
+            let mut map = std::collections::HashMap::with_capacity(len.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..len {
+                let k = K::load(loader)?;
+                let v = V::load(loader)?;
+                map.insert(k, v);
+            }
+            Ok(map)
+        })
     }
 }
 
 impl<K: Savable + Eq + Hash, V: Savable> Savable for HashMap<K, V> {
     fn save(&self, saver: &mut impl Saver) {
         (self.len() as u64).save(saver);
-        self.iter().for_each(|(k, v)| {
-            k.save(saver);
-            v.save(saver);
-        });
-    }
-
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let len = u64::load(loader)?;
-        let mut map = HashMap::with_capacity(len as usize);
-
-        for _ in 0..len {
-            let k = K::load(loader)?;
-            let v = V::load(loader)?;
-            map.insert(k, v);
+        reserve_elements(saver, self.iter().map(|(k, v)| Some(k.save_size_hint()? + v.save_size_hint()?)));
+        if saver.is_deterministic() {
+            for (k, v) in sorted_by_bytes(self.iter(), |(k, _), saver| k.save(saver)) {
+                k.save(saver);
+                v.save(saver);
+            }
+        } else {
+            self.iter().for_each(|(k, v)| {
+                k.save(saver);
+                v.save(saver);
+            });
         }
+    }
 
-        Ok(map)
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = load_len(loader)?;
+        nested(loader, |loader| {
+            let mut map = HashMap::with_capacity(len.min(MAX_PREALLOCATION) as usize);
+            for _ in 0..len {
+                let k = K::load(loader)?;
+                let v = V::load(loader)?;
+                map.insert(k, v);
+            }
+            Ok(map)
+        })
     }
 }
 
@@ -566,7 +998,7 @@ impl Savable for Duration {
         saver.push_u32(self.subsec_nanos());
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let secs = u64::load(loader)?;
         let nanos = u32::load(loader)?;
 
@@ -580,7 +1012,7 @@ impl Savable for Instant {
         duration.save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let duration = Duration::load(loader)?;
         Ok(Instant::now() + duration - SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default())
     }
@@ -591,133 +1023,829 @@ impl Savable for SystemTime {
         self.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
         let duration = Duration::load(loader)?;
         Ok(SystemTime::UNIX_EPOCH + duration)
     }
 }
 
-impl<T: Savable> Savable for Range<T> {
+impl Savable for char {
+    /// Saved as the `u32` scalar value.
     fn save(&self, saver: &mut impl Saver) {
-        self.start.save(saver);
-        self.end.save(saver);
+        saver.push_u32(*self as u32);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let start = T::load(loader)?;
-        let end = T::load(loader)?;
-        Ok(Range { start, end })
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let value = u32::load(loader)?;
+        char::from_u32(value).ok_or(SaveError::Custom(format!("Invalid char value {value:#x}!")))
     }
 }
 
-impl<T: Savable> Savable for RangeInclusive<T> {
+impl Savable for OsString {
+    /// Saved as a UTF-8 string so saves can be shared between platforms. Invalid UTF-8 is replaced
+    /// with U+FFFD, so such strings do not survive a round trip.
     fn save(&self, saver: &mut impl Saver) {
-        self.start().save(saver);
-        self.end().save(saver);
+        saver.push_string(&self.to_string_lossy());
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let start = T::load(loader)?;
-        let end = T::load(loader)?;
-        Ok(RangeInclusive::new(start, end))
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        String::load(loader).map(Into::into)
     }
 }
 
-impl<T: Savable> Savable for RangeFrom<T> {
+impl Savable for PathBuf {
+    /// Saved like [`OsString`]. The separators are kept as is, so absolute or Windows style paths
+    /// may not be meaningful on other platforms.
     fn save(&self, saver: &mut impl Saver) {
-        self.start.save(saver);
+        saver.push_string(&self.to_string_lossy());
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let start = T::load(loader)?;
-        Ok(RangeFrom { start })
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        String::load(loader).map(Into::into)
     }
 }
 
-impl<T: Savable> Savable for RangeTo<T> {
+impl Savable for Ipv4Addr {
     fn save(&self, saver: &mut impl Saver) {
-        self.end.save(saver);
+        self.octets().save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let end = T::load(loader)?;
-        Ok(RangeTo { end })
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        <[u8; 4]>::load(loader).map(Into::into)
     }
 }
 
-impl<T: Savable> Savable for RangeToInclusive<T> {
+impl Savable for Ipv6Addr {
     fn save(&self, saver: &mut impl Saver) {
-        self.end.save(saver);
+        self.octets().save(saver);
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        let end = T::load(loader)?;
-        Ok(RangeToInclusive { end })
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        <[u8; 16]>::load(loader).map(Into::into)
     }
 }
 
-impl<T: Savable> Savable for std::sync::RwLock<T> {
+impl Savable for IpAddr {
+    /// Saved as a `u8` of 4 or 6 followed by the octets.
     fn save(&self, saver: &mut impl Saver) {
-        self.read().recover().save(saver);
+        match self {
+            IpAddr::V4(ip) => {
+                saver.push_u8(4);
+                ip.save(saver);
+            }
+            IpAddr::V6(ip) => {
+                saver.push_u8(6);
+                ip.save(saver);
+            }
+        }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(std::sync::RwLock::new(T::load(loader)?))
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        match u8::load(loader)? {
+            4 => Ok(IpAddr::V4(Ipv4Addr::load(loader)?)),
+            6 => Ok(IpAddr::V6(Ipv6Addr::load(loader)?)),
+            other => Err(SaveError::InvalidDiscriminant { ty: "IpAddr", value: other as u64 }),
+        }
     }
 }
 
-impl<T: Savable> Savable for RwLock<T> {
+impl Savable for SocketAddrV4 {
     fn save(&self, saver: &mut impl Saver) {
-        self.read().save(saver);
+        self.ip().save(saver);
+        saver.push_u16(self.port());
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(RwLock::new(T::load(loader)?))
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(SocketAddrV4::new(Ipv4Addr::load(loader)?, u16::load(loader)?))
     }
 }
 
-impl<T: Savable> Savable for std::sync::Mutex<T> {
+impl Savable for SocketAddrV6 {
     fn save(&self, saver: &mut impl Saver) {
-        self.lock().recover().save(saver);
+        self.ip().save(saver);
+        saver.push_u16(self.port());
+        saver.push_u32(self.flowinfo());
+        saver.push_u32(self.scope_id());
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(std::sync::Mutex::new(T::load(loader)?))
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(SocketAddrV6::new(Ipv6Addr::load(loader)?, u16::load(loader)?, u32::load(loader)?, u32::load(loader)?))
     }
 }
 
-impl<T: Savable> Savable for Mutex<T> {
+impl Savable for SocketAddr {
+    /// Saved as a `u8` of 4 or 6 followed by the address.
     fn save(&self, saver: &mut impl Saver) {
-        self.lock().save(saver);
+        match self {
+            SocketAddr::V4(addr) => {
+                saver.push_u8(4);
+                addr.save(saver);
+            }
+            SocketAddr::V6(addr) => {
+                saver.push_u8(6);
+                addr.save(saver);
+            }
+        }
     }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(Mutex::new(T::load(loader)?))
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        match u8::load(loader)? {
+            4 => Ok(SocketAddr::V4(SocketAddrV4::load(loader)?)),
+            6 => Ok(SocketAddr::V6(SocketAddrV6::load(loader)?)),
+            other => Err(SaveError::InvalidDiscriminant { ty: "SocketAddr", value: other as u64 }),
+        }
     }
 }
 
-impl<T: Savable> Savable for UnsafeCell<T> {
-    fn save(&self, saver: &mut impl Saver) {
-        unsafe { self.get().as_ref().unwrap() }.save(saver);
-    }
+macro_rules! impl_savable_non_zero {
+    ($($t:ty => $inner:ty),*) => {
+        $(
+            impl Savable for $t {
+                fn save(&self, saver: &mut impl Saver) {
+                    self.get().save(saver);
+                }
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(UnsafeCell::new(T::load(loader)?))
-    }
-}
+                fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+                    <$t>::new(<$inner>::load(loader)?).ok_or(SaveError::Custom(format!("Loaded zero for {}!", stringify!($t))))
+                }
+            }
 
-impl<T: Savable> Savable for Cell<T> {
-    fn save(&self, saver: &mut impl Saver) {
-        unsafe { self.as_ptr().as_ref().unwrap() }.save(saver);
-    }
+            impl SaveSize for $t {
+                const FIXED_SIZE: Option<usize> = Some(std::mem::size_of::<$inner>());
 
-    fn load(loader: &mut impl Loader) -> Result<Self, String> {
-        Ok(Cell::new(T::load(loader)?))
-    }
+                fn save_size(&self) -> usize {
+                    std::mem::size_of::<$inner>()
+                }
+            }
+        )*
+    };
 }
 
-pub mod custom {
-    use crate::save::{Loader, Savable, Saver};
+impl_savable_non_zero!(
+    NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64,
+    NonZeroI8 => i8, NonZeroI16 => i16, NonZeroI32 => i32, NonZeroI64 => i64
+);
+
+impl<T: Savable> Savable for Range<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.start.save(saver);
+        self.end.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let start = T::load(loader)?;
+        let end = T::load(loader)?;
+        Ok(Range { start, end })
+    }
+}
+
+impl<T: Savable> Savable for RangeInclusive<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.start().save(saver);
+        self.end().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let start = T::load(loader)?;
+        let end = T::load(loader)?;
+        Ok(RangeInclusive::new(start, end))
+    }
+}
+
+impl<T: Savable> Savable for RangeFrom<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.start.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let start = T::load(loader)?;
+        Ok(RangeFrom { start })
+    }
+}
+
+impl<T: Savable> Savable for RangeTo<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.end.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let end = T::load(loader)?;
+        Ok(RangeTo { end })
+    }
+}
+
+impl<T: Savable> Savable for RangeToInclusive<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.end.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let end = T::load(loader)?;
+        Ok(RangeToInclusive { end })
+    }
+}
+
+impl<T: Savable> Savable for std::sync::RwLock<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.read().recover().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(std::sync::RwLock::new(T::load(loader)?))
+    }
+}
+
+impl<T: Savable> Savable for RwLock<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.read().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(RwLock::new(T::load(loader)?))
+    }
+}
+
+impl<T: Savable> Savable for std::sync::Mutex<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.lock().recover().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(std::sync::Mutex::new(T::load(loader)?))
+    }
+}
+
+impl<T: Savable> Savable for Mutex<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.lock().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(Mutex::new(T::load(loader)?))
+    }
+}
+
+#[cfg(feature = "sync_debug")]
+impl<T: Savable> Savable for crate::sync::RwLock<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.read().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(crate::sync::RwLock::new(T::load(loader)?))
+    }
+}
+
+#[cfg(feature = "sync_debug")]
+impl<T: Savable> Savable for crate::sync::Mutex<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.lock().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(crate::sync::Mutex::new(T::load(loader)?))
+    }
+}
+
+impl<T: Savable> Savable for UnsafeCell<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        unsafe { self.get().as_ref().unwrap() }.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(UnsafeCell::new(T::load(loader)?))
+    }
+}
+
+impl<T: Savable> Savable for Cell<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        unsafe { self.as_ptr().as_ref().unwrap() }.save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(Cell::new(T::load(loader)?))
+    }
+}
+
+impl<T: Savable> Savable for DangerousCell<T> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.get().save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(DangerousCell::new(T::load(loader)?))
+    }
+}
+
+/// Reports how many bytes a value will occupy when saved using [`Savable::save`], so buffers can
+/// be allocated up front. Types whose saved size never changes expose it via [`SaveSize::FIXED_SIZE`].
+pub trait SaveSize {
+    const FIXED_SIZE: Option<usize> = None;
+
+    fn save_size(&self) -> usize;
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use crate::save::{Loader, SaveSize, Saver};
+    use bytebuffer::ByteBuffer;
+
+    /// Lets derived [`Savable`](super::Savable) implementations use [`SaveSize`] if the type
+    /// implements it, and fall back to no hint otherwise.
+    pub struct SizeHint<'a, T>(pub &'a T);
+
+    pub trait ViaSaveSize {
+        fn size_hint(&self) -> Option<usize>;
+    }
+
+    impl<T: SaveSize> ViaSaveSize for SizeHint<'_, T> {
+        fn size_hint(&self) -> Option<usize> {
+            Some(self.0.save_size())
+        }
+    }
+
+    pub trait NoSaveSize {
+        fn size_hint(&self) -> Option<usize> {
+            None
+        }
+    }
+
+    impl<T> NoSaveSize for &SizeHint<'_, T> {}
+
+    /// Starts a section prefixed with its length in bytes, returning where the length has to be
+    /// patched in, or `None` if the saver can't patch and the section has to be saved into a
+    /// [`sized_buffer`] instead.
+    pub fn begin_sized(saver: &mut impl Saver) -> Option<usize> {
+        if !saver.can_patch() {
+            return None;
+        }
+        let start = saver.bytes_written();
+        saver.push_u32(0);
+        Some(start)
+    }
+
+    pub fn end_sized(saver: &mut impl Saver, start: usize) {
+        let len = saver.bytes_written() - start - 4;
+        saver.patch_u32(start, sized_len(len));
+    }
+
+    pub fn sized_buffer() -> ByteBuffer {
+        ByteBuffer::new()
+    }
+
+    pub fn push_sized(saver: &mut impl Saver, buffer: &ByteBuffer) {
+        saver.push_u32(sized_len(buffer.len()));
+        saver.push_bytes(buffer.as_bytes());
+    }
+
+    fn sized_len(len: usize) -> u32 {
+        u32::try_from(len).unwrap_or_else(|_| panic!("Section of {len} bytes is too large, the length is saved as a u32!"))
+    }
+
+    #[inline(always)]
+    pub fn begin_save_field(saver: &mut impl Saver, name: &'static str) {
+        #[cfg(feature = "save_trace")]
+        saver.begin_field(name);
+        #[cfg(not(feature = "save_trace"))]
+        let _ = (saver, name);
+    }
+
+    #[inline(always)]
+    pub fn end_save_field(saver: &mut impl Saver) {
+        #[cfg(feature = "save_trace")]
+        saver.end_field();
+        #[cfg(not(feature = "save_trace"))]
+        let _ = saver;
+    }
+
+    #[inline(always)]
+    pub fn begin_load_field(loader: &mut impl Loader, name: &'static str) {
+        #[cfg(feature = "save_trace")]
+        loader.begin_field(name);
+        #[cfg(not(feature = "save_trace"))]
+        let _ = (loader, name);
+    }
+
+    #[inline(always)]
+    pub fn end_load_field(loader: &mut impl Loader) {
+        #[cfg(feature = "save_trace")]
+        loader.end_field();
+        #[cfg(not(feature = "save_trace"))]
+        let _ = loader;
+    }
+}
+
+/// Sums up the fixed sizes, returning [`None`] if any of them is not fixed.
+pub const fn fixed_sum(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut total = 0;
+    let mut i = 0;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) => total += size,
+            None => return None,
+        }
+        i += 1;
+    }
+    Some(total)
+}
+
+/// Returns the fixed size if all sizes are fixed and equal, [`None`] otherwise.
+pub const fn fixed_same(sizes: &[Option<usize>]) -> Option<usize> {
+    if sizes.is_empty() {
+        return None;
+    }
+    let first = match sizes[0] {
+        Some(size) => size,
+        None => return None,
+    };
+    let mut i = 1;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) if size == first => {}
+            _ => return None,
+        }
+        i += 1;
+    }
+    Some(first)
+}
+
+/// A [`Saver`] that discards all data and only counts the bytes that would have been written,
+/// using the same layout as [`ByteBuffer`].
+#[derive(Default)]
+pub struct SizeCounter {
+    size: usize,
+}
+
+impl SizeCounter {
+    pub fn new() -> Self {
+        SizeCounter { size: 0 }
+    }
+
+    pub fn measure(f: impl FnOnce(&mut SizeCounter)) -> usize {
+        let mut counter = SizeCounter::new();
+        f(&mut counter);
+        counter.size
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Saver for SizeCounter {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.size += bytes.len();
+    }
+
+    fn push_bool(&mut self, _: bool) {
+        self.size += 1;
+    }
+
+    fn push_u8(&mut self, _: u8) {
+        self.size += 1;
+    }
+
+    fn push_u16(&mut self, _: u16) {
+        self.size += 2;
+    }
+
+    fn push_u32(&mut self, _: u32) {
+        self.size += 4;
+    }
+
+    fn push_u64(&mut self, _: u64) {
+        self.size += 8;
+    }
+
+    fn push_i8(&mut self, _: i8) {
+        self.size += 1;
+    }
+
+    fn push_i16(&mut self, _: i16) {
+        self.size += 2;
+    }
+
+    fn push_i32(&mut self, _: i32) {
+        self.size += 4;
+    }
+
+    fn push_i64(&mut self, _: i64) {
+        self.size += 8;
+    }
+
+    fn push_f32(&mut self, _: f32) {
+        self.size += 4;
+    }
+
+    fn push_f64(&mut self, _: f64) {
+        self.size += 8;
+    }
+
+    fn push_string(&mut self, value: &str) {
+        self.size += 4 + value.len();
+    }
+
+    fn bytes_written(&self) -> usize {
+        self.size
+    }
+
+    fn can_patch(&self) -> bool {
+        true
+    }
+
+    fn patch_u32(&mut self, _: usize, _: u32) {}
+}
+
+/// A [`Saver`] wrapper which saves hash based collections like [`HashMap`] and [`HashSet`] sorted
+/// by the saved bytes of their keys, so that equal values always produce identical bytes, for
+/// example for content hashes or binary diffs. The saved data is loaded like any other.
+///
+/// Types can always be saved this way using `#[savable(deterministic)]`.
+pub struct Deterministic<'a> {
+    inner: &'a mut dyn Saver,
+}
+
+impl<'a> Deterministic<'a> {
+    pub fn new(inner: &'a mut dyn Saver) -> Self {
+        Deterministic { inner }
+    }
+}
+
+macro_rules! forward_push {
+    ($($t:ty, $push:ident),*) => {
+        $(
+            fn $push(&mut self, value: $t) {
+                self.inner.$push(value);
+            }
+        )*
+    };
+}
+
+impl Saver for Deterministic<'_> {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.inner.push_bytes(bytes);
+    }
+
+    forward_push!(
+        bool, push_bool, u8, push_u8, u16, push_u16, u32, push_u32, u64, push_u64, i8, push_i8,
+        i16, push_i16, i32, push_i32, i64, push_i64, f32, push_f32, f64, push_f64, &str, push_string
+    );
+
+    fn bytes_written(&self) -> usize {
+        self.inner.bytes_written()
+    }
+
+    fn can_reserve(&self) -> bool {
+        self.inner.can_reserve()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn can_patch(&self) -> bool {
+        self.inner.can_patch()
+    }
+
+    fn patch_u32(&mut self, position: usize, value: u32) {
+        self.inner.patch_u32(position, value);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        self.inner.begin_field(name);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {
+        self.inner.end_field();
+    }
+}
+
+/// Sort the items of a hash based collection by the bytes `key` saves for them.
+fn sorted_by_bytes<T>(items: impl Iterator<Item = T>, key: impl Fn(&T, &mut Deterministic)) -> Vec<T> {
+    let mut keyed = items
+        .map(|item| {
+            let mut buffer = ByteBuffer::new();
+            key(&item, &mut Deterministic::new(&mut buffer));
+            (buffer.into_vec(), item)
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    keyed.into_iter().map(|(_, item)| item).collect()
+}
+
+macro_rules! impl_save_size_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl SaveSize for $t {
+                const FIXED_SIZE: Option<usize> = Some(std::mem::size_of::<$t>());
+
+                fn save_size(&self) -> usize {
+                    std::mem::size_of::<$t>()
+                }
+            }
+        )*
+    };
+}
+
+impl_save_size_primitive!(bool, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+macro_rules! impl_save_size_tuple {
+    () => {};
+    ($first:ident $($rest:ident)*) => {
+        impl_save_size_tuple!($($rest)*);
+        impl<$first: SaveSize, $($rest: SaveSize),*> SaveSize for ($first, $($rest),*) {
+            const FIXED_SIZE: Option<usize> = fixed_sum(&[$first::FIXED_SIZE, $($rest::FIXED_SIZE),*]);
+
+            fn save_size(&self) -> usize {
+                #[allow(non_snake_case)]
+                let ($first, $($rest),*) = self;
+                $first.save_size() $( + $rest.save_size() )*
+            }
+        }
+    };
+}
+
+impl_save_size_tuple!(E D C B A Z Y X W V U T);
+
+impl SaveSize for String {
+    fn save_size(&self) -> usize {
+        4 + self.len()
+    }
+}
+
+impl<T: SaveSize> SaveSize for Option<T> {
+    fn save_size(&self) -> usize {
+        1 + self.as_ref().map_or(0, T::save_size)
+    }
+}
+
+impl<T: SaveSize, E: SaveSize> SaveSize for Result<T, E> {
+    fn save_size(&self) -> usize {
+        1 + match self {
+            Ok(t) => t.save_size(),
+            Err(e) => e.save_size(),
+        }
+    }
+}
+
+impl<T: SaveSize, const N: usize> SaveSize for [T; N] {
+    const FIXED_SIZE: Option<usize> = match T::FIXED_SIZE {
+        Some(size) => Some(size * N),
+        None => None,
+    };
+
+    fn save_size(&self) -> usize {
+        match T::FIXED_SIZE {
+            Some(size) => size * N,
+            None => self.iter().map(T::save_size).sum(),
+        }
+    }
+}
+
+impl<T: SaveSize> SaveSize for Vec<T> {
+    fn save_size(&self) -> usize {
+        8 + match T::FIXED_SIZE {
+            Some(size) => size * self.len(),
+            None => self.iter().map(T::save_size).sum(),
+        }
+    }
+}
+
+impl SaveSize for ByteBuffer {
+    fn save_size(&self) -> usize {
+        8 + self.len()
+    }
+}
+
+impl<T: SaveSize> SaveSize for Box<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn save_size(&self) -> usize {
+        self.deref().save_size()
+    }
+}
+
+impl<T: SaveSize> SaveSize for std::collections::HashSet<T> {
+    fn save_size(&self) -> usize {
+        8 + self.iter().map(T::save_size).sum::<usize>()
+    }
+}
+
+impl<T: SaveSize> SaveSize for HashSet<T> {
+    fn save_size(&self) -> usize {
+        8 + self.iter().map(T::save_size).sum::<usize>()
+    }
+}
+
+impl<K: SaveSize, V: SaveSize> SaveSize for std::collections::HashMap<K, V> {
+    fn save_size(&self) -> usize {
+        8 + self.iter().map(|(k, v)| k.save_size() + v.save_size()).sum::<usize>()
+    }
+}
+
+impl<K: SaveSize, V: SaveSize> SaveSize for HashMap<K, V> {
+    fn save_size(&self) -> usize {
+        8 + self.iter().map(|(k, v)| k.save_size() + v.save_size()).sum::<usize>()
+    }
+}
+
+macro_rules! impl_save_size_fixed {
+    ($($t:ty => $size:expr),*) => {
+        $(
+            impl SaveSize for $t {
+                const FIXED_SIZE: Option<usize> = Some($size);
+
+                fn save_size(&self) -> usize {
+                    $size
+                }
+            }
+        )*
+    };
+}
+
+impl_save_size_fixed!(
+    Duration => 12, Instant => 12, SystemTime => 12, char => 4, Ipv4Addr => 4, Ipv6Addr => 16,
+    SocketAddrV4 => 6, SocketAddrV6 => 26
+);
+
+impl SaveSize for OsString {
+    fn save_size(&self) -> usize {
+        4 + self.to_string_lossy().len()
+    }
+}
+
+impl SaveSize for PathBuf {
+    fn save_size(&self) -> usize {
+        4 + self.to_string_lossy().len()
+    }
+}
+
+impl SaveSize for IpAddr {
+    fn save_size(&self) -> usize {
+        match self {
+            IpAddr::V4(_) => 5,
+            IpAddr::V6(_) => 17,
+        }
+    }
+}
+
+impl SaveSize for SocketAddr {
+    fn save_size(&self) -> usize {
+        match self {
+            SocketAddr::V4(_) => 7,
+            SocketAddr::V6(_) => 27,
+        }
+    }
+}
+
+impl<T: SaveSize> SaveSize for Range<T> {
+    const FIXED_SIZE: Option<usize> = fixed_sum(&[T::FIXED_SIZE, T::FIXED_SIZE]);
+
+    fn save_size(&self) -> usize {
+        self.start.save_size() + self.end.save_size()
+    }
+}
+
+impl<T: SaveSize> SaveSize for RangeInclusive<T> {
+    const FIXED_SIZE: Option<usize> = fixed_sum(&[T::FIXED_SIZE, T::FIXED_SIZE]);
+
+    fn save_size(&self) -> usize {
+        self.start().save_size() + self.end().save_size()
+    }
+}
+
+impl<T: SaveSize> SaveSize for RangeFrom<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn save_size(&self) -> usize {
+        self.start.save_size()
+    }
+}
+
+impl<T: SaveSize> SaveSize for RangeTo<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn save_size(&self) -> usize {
+        self.end.save_size()
+    }
+}
+
+impl<T: SaveSize> SaveSize for RangeToInclusive<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn save_size(&self) -> usize {
+        self.end.save_size()
+    }
+}
+
+pub mod custom {
+    use crate::save::{Loader, Savable, SaveError, Saver};
 
     pub fn string8_save(saver: &mut impl Saver, str: &String) {
         let bytes = str.as_bytes();
@@ -729,10 +1857,10 @@ pub mod custom {
         }
     }
 
-    pub fn string8_load(loader: &mut impl Loader) -> Result<String, String> {
+    pub fn string8_load(loader: &mut impl Loader) -> Result<String, SaveError> {
         let len = u8::load(loader)?;
-        let bytes = loader.pop_bytes(len as usize).ok_or("Failed to load String8 from Loader!")?;
-        Ok(String::from_utf8(bytes).map_err(|e| e.to_string())?)
+        let bytes = loader.pop_bytes(len as usize).ok_or(SaveError::eof("String8"))?;
+        Ok(String::from_utf8(bytes)?)
     }
 
     pub fn string16_save(saver: &mut impl Saver, str: &String) {
@@ -745,10 +1873,10 @@ pub mod custom {
         }
     }
 
-    pub fn string16_load(loader: &mut impl Loader) -> Result<String, String> {
+    pub fn string16_load(loader: &mut impl Loader) -> Result<String, SaveError> {
         let len = u16::load(loader)?;
-        let bytes = loader.pop_bytes(len as usize).ok_or("Failed to load String16 from Loader!")?;
-        Ok(String::from_utf8(bytes).map_err(|e| e.to_string())?)
+        let bytes = loader.pop_bytes(len as usize).ok_or(SaveError::eof("String16"))?;
+        Ok(String::from_utf8(bytes)?)
     }
 
     pub fn string64_save(saver: &mut impl Saver, str: &String) {
@@ -757,10 +1885,10 @@ pub mod custom {
         saver.push_bytes(bytes);
     }
 
-    pub fn string64_load(loader: &mut impl Loader) -> Result<String, String> {
+    pub fn string64_load(loader: &mut impl Loader) -> Result<String, SaveError> {
         let len = u64::load(loader)?;
-        let bytes = loader.pop_bytes(len as usize).ok_or("Failed to load String64 from Loader!")?;
-        Ok(String::from_utf8(bytes).map_err(|e| e.to_string())?)
+        let bytes = loader.pop_bytes(len as usize).ok_or(SaveError::eof("String64"))?;
+        Ok(String::from_utf8(bytes)?)
     }
 
     pub fn vec8_save<T: Savable>(saver: &mut impl Saver, vec: &Vec<T>) {
@@ -776,8 +1904,9 @@ pub mod custom {
         }
     }
 
-    pub fn vec8_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, String> {
+    pub fn vec8_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, SaveError> {
         let len = u8::load(loader)?;
+        loader.check_len(len as u64)?;
         let mut vec = Vec::with_capacity(len as usize);
         for _ in 0..len {
             vec.push(T::load(loader)?);
@@ -798,8 +1927,9 @@ pub mod custom {
         }
     }
 
-    pub fn vec16_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, String> {
+    pub fn vec16_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, SaveError> {
         let len = u16::load(loader)?;
+        loader.check_len(len as u64)?;
         let mut vec = Vec::with_capacity(len as usize);
         for _ in 0..len {
             vec.push(T::load(loader)?);
@@ -820,9 +1950,10 @@ pub mod custom {
         }
     }
 
-    pub fn vec32_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, String> {
+    pub fn vec32_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, SaveError> {
         let len = u32::load(loader)?;
-        let mut vec = Vec::with_capacity(len as usize);
+        loader.check_len(len as u64)?;
+        let mut vec = Vec::with_capacity((len as u64).min(super::MAX_PREALLOCATION) as usize);
         for _ in 0..len {
             vec.push(T::load(loader)?);
         }
@@ -835,7 +1966,7 @@ pub mod custom {
         }
     }
     
-    pub fn raw_vec_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, String> {
+    pub fn raw_vec_load<T: Savable>(loader: &mut impl Loader) -> Result<Vec<T>, SaveError> {
         let mut vec = Vec::new();
         while let Ok(t) = T::load(loader) {
             vec.push(t);
@@ -847,11 +1978,11 @@ pub mod custom {
         saver.push_bytes(&vec);
     }
     
-    pub fn raw_bytes_load(loader: &mut impl Loader) -> Result<Vec<u8>, String> {
-        loader.pop_to_end().ok_or("Failed to load bytes from loader!".to_string())
+    pub fn raw_bytes_load(loader: &mut impl Loader) -> Result<Vec<u8>, SaveError> {
+        loader.pop_to_end().ok_or(SaveError::eof("bytes"))
     }
     
-    pub fn empty_vec_load<T>(_: &mut impl Loader) -> Result<Vec<T>, String> {
+    pub fn empty_vec_load<T>(_: &mut impl Loader) -> Result<Vec<T>, SaveError> {
         Ok(Vec::new())
     }
     
@@ -861,12 +1992,171 @@ pub mod custom {
         item.save(saver);
     }
     
-    pub fn load_default<T: Default>(_: &mut impl Loader) -> Result<T, String> {
+    pub fn load_default<T: Default>(_: &mut impl Loader) -> Result<T, SaveError> {
         Ok(T::default())
     }
     
-    pub fn load<T: Savable>(loader: &mut impl Loader) -> Result<T, String> {
+    pub fn load<T: Savable>(loader: &mut impl Loader) -> Result<T, SaveError> {
         T::load(loader)
     }
-}
 
+    /// Convert a float to the bits of the nearest half precision float, rounding ties to even.
+    /// Values too large for a half become infinity.
+    pub fn f32_to_f16(value: f32) -> u16 {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exp = ((bits >> 23) & 0xFF) as i32;
+        let man = bits & 0x7FFFFF;
+
+        if exp == 0xFF {
+            let nan = if man != 0 { 0x200 | (man >> 13) as u16 } else { 0 };
+            return sign | 0x7C00 | nan;
+        }
+
+        let exp = exp - 127 + 15;
+        if exp >= 0x1F {
+            return sign | 0x7C00;
+        }
+
+        let (half, rem, halfway) = if exp <= 0 {
+            if exp < -10 {
+                return sign;
+            }
+            let man = man | 0x800000;
+            let shift = (14 - exp) as u32;
+            (man >> shift, man & ((1 << shift) - 1), 1 << (shift - 1))
+        } else {
+            (((exp as u32) << 10) | (man >> 13), man & 0x1FFF, 0x1000)
+        };
+        let round = rem > halfway || (rem == halfway && half & 1 == 1);
+        sign | (half + round as u32) as u16
+    }
+
+    /// Convert the bits of a half precision float to a float.
+    pub fn f16_to_f32(half: u16) -> f32 {
+        let sign = ((half & 0x8000) as u32) << 16;
+        let exp = ((half >> 10) & 0x1F) as u32;
+        let man = (half & 0x3FF) as u32;
+
+        match exp {
+            0 => {
+                let value = man as f32 / (1 << 24) as f32;
+                if sign != 0 {
+                    -value
+                } else {
+                    value
+                }
+            }
+            0x1F => f32::from_bits(sign | 0x7F800000 | (man << 13)),
+            _ => f32::from_bits(sign | ((exp + 112) << 23) | (man << 13)),
+        }
+    }
+
+    fn to_unorm(value: f32, max: u32) -> u32 {
+        (value.clamp(0.0, 1.0) * max as f32).round() as u32
+    }
+
+    fn from_unorm(value: u32, max: u32) -> f32 {
+        value as f32 / max as f32
+    }
+
+    fn to_snorm(value: f32, max: i32) -> i32 {
+        (value.clamp(-1.0, 1.0) * max as f32).round() as i32
+    }
+
+    fn from_snorm(value: i32, max: i32) -> f32 {
+        (value as f32 / max as f32).max(-1.0)
+    }
+
+    /// Save a float as a half precision float in 2 bytes.
+    pub fn f16_save(saver: &mut impl Saver, value: &f32) {
+        saver.push_u16(f32_to_f16(*value));
+    }
+
+    pub fn f16_load(loader: &mut impl Loader) -> Result<f32, SaveError> {
+        loader.pop_u16().map(f16_to_f32).ok_or(SaveError::eof("f16"))
+    }
+
+    /// Save a float between 0 and 1 in a single byte. Values outside of this range are clamped.
+    pub fn unorm8_save(saver: &mut impl Saver, value: &f32) {
+        saver.push_u8(to_unorm(*value, u8::MAX as u32) as u8);
+    }
+
+    pub fn unorm8_load(loader: &mut impl Loader) -> Result<f32, SaveError> {
+        loader.pop_u8().map(|v| from_unorm(v as u32, u8::MAX as u32)).ok_or(SaveError::eof("unorm8"))
+    }
+
+    /// Save a float between 0 and 1 in 2 bytes. Values outside of this range are clamped.
+    pub fn unorm16_save(saver: &mut impl Saver, value: &f32) {
+        saver.push_u16(to_unorm(*value, u16::MAX as u32) as u16);
+    }
+
+    pub fn unorm16_load(loader: &mut impl Loader) -> Result<f32, SaveError> {
+        loader.pop_u16().map(|v| from_unorm(v as u32, u16::MAX as u32)).ok_or(SaveError::eof("unorm16"))
+    }
+
+    /// Save a float between -1 and 1 in a single byte. Values outside of this range are clamped.
+    pub fn snorm8_save(saver: &mut impl Saver, value: &f32) {
+        saver.push_i8(to_snorm(*value, i8::MAX as i32) as i8);
+    }
+
+    pub fn snorm8_load(loader: &mut impl Loader) -> Result<f32, SaveError> {
+        loader.pop_i8().map(|v| from_snorm(v as i32, i8::MAX as i32)).ok_or(SaveError::eof("snorm8"))
+    }
+
+    /// Save a float between -1 and 1 in 2 bytes. Values outside of this range are clamped.
+    pub fn snorm16_save(saver: &mut impl Saver, value: &f32) {
+        saver.push_i16(to_snorm(*value, i16::MAX as i32) as i16);
+    }
+
+    pub fn snorm16_load(loader: &mut impl Loader) -> Result<f32, SaveError> {
+        loader.pop_i16().map(|v| from_snorm(v as i32, i16::MAX as i32)).ok_or(SaveError::eof("snorm16"))
+    }
+
+    /// Pack a vector with components between 0 and 1 into 10 bits for x, y and z and 2 bits for w.
+    pub fn pack_unorm_1010102(value: [f32; 4]) -> u32 {
+        to_unorm(value[0], 1023) | to_unorm(value[1], 1023) << 10 | to_unorm(value[2], 1023) << 20 | to_unorm(value[3], 3) << 30
+    }
+
+    pub fn unpack_unorm_1010102(packed: u32) -> [f32; 4] {
+        [
+            from_unorm(packed & 0x3FF, 1023),
+            from_unorm((packed >> 10) & 0x3FF, 1023),
+            from_unorm((packed >> 20) & 0x3FF, 1023),
+            from_unorm(packed >> 30, 3),
+        ]
+    }
+
+    /// Pack a vector with components between -1 and 1 into 10 bits for x, y and z and 2 bits for w, for
+    /// example normals and tangents.
+    pub fn pack_snorm_1010102(value: [f32; 4]) -> u32 {
+        let bits = |v: f32, max: i32, mask: u32| to_snorm(v, max) as u32 & mask;
+        bits(value[0], 511, 0x3FF) | bits(value[1], 511, 0x3FF) << 10 | bits(value[2], 511, 0x3FF) << 20 | bits(value[3], 1, 0x3) << 30
+    }
+
+    pub fn unpack_snorm_1010102(packed: u32) -> [f32; 4] {
+        let component = |shift: u32, bits: u32, max: i32| {
+            let value = ((packed << (32 - bits - shift)) as i32) >> (32 - bits);
+            from_snorm(value, max)
+        };
+        [component(0, 10, 511), component(10, 10, 511), component(20, 10, 511), component(30, 2, 1)]
+    }
+
+    /// Save a vector with components between 0 and 1 in 4 bytes, see [`pack_unorm_1010102`].
+    pub fn unorm_1010102_save(saver: &mut impl Saver, value: &[f32; 4]) {
+        saver.push_u32(pack_unorm_1010102(*value));
+    }
+
+    pub fn unorm_1010102_load(loader: &mut impl Loader) -> Result<[f32; 4], SaveError> {
+        loader.pop_u32().map(unpack_unorm_1010102).ok_or(SaveError::eof("unorm 10-10-10-2 vector"))
+    }
+
+    /// Save a vector with components between -1 and 1 in 4 bytes, see [`pack_snorm_1010102`].
+    pub fn snorm_1010102_save(saver: &mut impl Saver, value: &[f32; 4]) {
+        saver.push_u32(pack_snorm_1010102(*value));
+    }
+
+    pub fn snorm_1010102_load(loader: &mut impl Loader) -> Result<[f32; 4], SaveError> {
+        loader.pop_u32().map(unpack_snorm_1010102).ok_or(SaveError::eof("snorm 10-10-10-2 vector"))
+    }
+}
@@ -0,0 +1,303 @@
+use crate as mvutils;
+use crate::hashers::crc32;
+use crate::save::{Loader, Savable, SaveError, Saver};
+use bytebuffer::ByteBuffer;
+use mvutils_proc_macro::Savable;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"MVAR";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 16;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Compression {
+    None,
+    /// Deflate compression with a level from 0 (fastest) to 10 (smallest).
+    Deflate(u8),
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::Deflate(6)
+    }
+}
+
+impl Savable for Compression {
+    fn save(&self, saver: &mut impl Saver) {
+        match self {
+            Compression::None => saver.push_u8(0),
+            Compression::Deflate(level) => {
+                saver.push_u8(1);
+                saver.push_u8(*level);
+            }
+        }
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        match u8::load(loader)? {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Deflate(u8::load(loader)?)),
+            other => Err(SaveError::InvalidDiscriminant { ty: "Compression", value: other as u64 }),
+        }
+    }
+}
+
+/// The index record of a single archive entry.
+#[derive(Savable, Clone, Debug, Eq, PartialEq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub offset: u64,
+    pub stored_size: u64,
+    pub size: u64,
+    pub checksum: u32,
+    pub compression: Compression,
+}
+
+/// A pack file holding multiple named, individually compressed entries.
+///
+/// Layout: a 16 byte header (`MVAR`, format version, index offset), followed by the entry data
+/// and the index. New or replaced entries are appended after the end of the file and a new index
+/// is appended on [`SaveArchive::flush`], only then is the header updated to point to it. Nothing
+/// the header points to is ever overwritten, so a crash while writing leaves the previous state
+/// intact. Space of replaced or removed entries and old indices is reclaimed by
+/// [`SaveArchive::compact`].
+pub struct SaveArchive {
+    path: PathBuf,
+    file: File,
+    entries: Vec<ArchiveEntry>,
+    /// The end of the file, where new data is appended.
+    end: u64,
+    /// The size of the index the header points to.
+    index_size: u64,
+    dirty: bool,
+}
+
+impl SaveArchive {
+    /// Create a new empty archive, truncating the file if it exists.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path)?;
+        let mut archive = SaveArchive {
+            path,
+            file,
+            entries: Vec::new(),
+            end: HEADER_SIZE,
+            index_size: 0,
+            dirty: true,
+        };
+        archive.flush()?;
+        Ok(archive)
+    }
+
+    /// Open an existing archive. Only the index is read, entries are loaded lazily.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+
+        let mut header = [0u8; HEADER_SIZE as usize];
+        file.read_exact(&mut header)?;
+        let mut header = ByteBuffer::from_bytes(&header);
+        if header.pop_bytes(4).as_deref() != Some(MAGIC.as_slice()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a SaveArchive file"));
+        }
+        let version = header.pop_u32_unchecked();
+        if version != FORMAT_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unsupported SaveArchive version {version}")));
+        }
+        let index_offset = header.pop_u64_unchecked();
+
+        file.seek(SeekFrom::Start(index_offset))?;
+        let mut index = Vec::new();
+        file.read_to_end(&mut index)?;
+        let entries = Vec::<ArchiveEntry>::load(&mut ByteBuffer::from_bytes(&index))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(SaveArchive {
+            path,
+            file,
+            entries,
+            end: index_offset + index.len() as u64,
+            index_size: index.len() as u64,
+            dirty: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entry(name).is_some()
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&ArchiveEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.name.as_str())
+    }
+
+    /// Read the stored bytes of an entry, checking that the index does not point past the end of
+    /// the file before allocating.
+    fn read_stored(&mut self, entry: &ArchiveEntry) -> io::Result<Vec<u8>> {
+        let len = self.file.metadata()?.len();
+        if entry.offset.checked_add(entry.stored_size).map_or(true, |end| end > len) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Entry '{}' extends past the end of the archive, the archive is corrupted!", entry.name),
+            ));
+        }
+        let mut stored = vec![0u8; entry.stored_size as usize];
+        self.file.seek(SeekFrom::Start(entry.offset))?;
+        self.file.read_exact(&mut stored)?;
+        Ok(stored)
+    }
+
+    /// Read and decompress the raw bytes of an entry, verifying its checksum.
+    pub fn read_bytes(&mut self, name: &str) -> Result<Vec<u8>, String> {
+        let entry = self.entry(name).ok_or(format!("No entry named '{name}' in archive!"))?.clone();
+
+        let stored = self.read_stored(&entry).map_err(|e| e.to_string())?;
+
+        let bytes = match entry.compression {
+            Compression::None => stored,
+            Compression::Deflate(_) => miniz_oxide::inflate::decompress_to_vec_with_limit(&stored, entry.size as usize)
+                .map_err(|e| format!("Failed to decompress entry '{name}': {e}"))?,
+        };
+
+        if bytes.len() as u64 != entry.size || crc32(&bytes) != entry.checksum {
+            return Err(format!("Checksum mismatch for entry '{name}', the archive is corrupted!"));
+        }
+        Ok(bytes)
+    }
+
+    pub fn load<T: Savable>(&mut self, name: &str) -> Result<T, String> {
+        let bytes = self.read_bytes(name)?;
+        T::load(&mut ByteBuffer::from_vec(bytes)).map_err(Into::into)
+    }
+
+    /// Write an entry, replacing any existing entry with the same name.
+    pub fn write_bytes(&mut self, name: &str, bytes: &[u8], compression: Compression) -> io::Result<()> {
+        let stored = match compression {
+            Compression::None => bytes.to_vec(),
+            Compression::Deflate(level) => miniz_oxide::deflate::compress_to_vec(bytes, level),
+        };
+
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(&stored)?;
+
+        let entry = ArchiveEntry {
+            name: name.to_string(),
+            offset: self.end,
+            stored_size: stored.len() as u64,
+            size: bytes.len() as u64,
+            checksum: crc32(bytes),
+            compression,
+        };
+        self.end += entry.stored_size;
+
+        match self.entries.iter_mut().find(|e| e.name == name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn save<T: Savable>(&mut self, name: &str, value: &T) -> io::Result<()> {
+        self.save_with(name, value, Compression::default())
+    }
+
+    pub fn save_with<T: Savable>(&mut self, name: &str, value: &T, compression: Compression) -> io::Result<()> {
+        let mut buffer = ByteBuffer::new();
+        value.save(&mut buffer);
+        self.write_bytes(name, buffer.as_bytes(), compression)
+    }
+
+    /// Remove an entry from the index. Returns whether the entry existed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.name != name);
+        self.dirty |= self.entries.len() != len;
+        self.entries.len() != len
+    }
+
+    /// The amount of bytes occupied by replaced or removed entries and old indices.
+    pub fn wasted(&self) -> u64 {
+        self.end - HEADER_SIZE - self.index_size - self.entries.iter().map(|e| e.stored_size).sum::<u64>()
+    }
+
+    /// Append the index and update the header to point to it.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let mut index = ByteBuffer::new();
+        self.entries.save(&mut index);
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(index.as_bytes())?;
+        self.file.set_len(self.end + index.len() as u64)?;
+        self.file.sync_data()?;
+
+        let mut header = ByteBuffer::new();
+        header.push_bytes(MAGIC);
+        header.push_u32(FORMAT_VERSION);
+        header.push_u64(self.end);
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(header.as_bytes())?;
+        self.file.sync_data()?;
+
+        self.index_size = index.len() as u64;
+        self.end += self.index_size;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Rewrite the archive without the space wasted by replaced or removed entries.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = self.path.with_file_name(tmp_name);
+
+        let mut compacted = SaveArchive::create(&tmp)?;
+        // The temporary file is not live yet, so its empty index may be overwritten.
+        compacted.end = HEADER_SIZE;
+        compacted.index_size = 0;
+        for entry in self.entries.clone() {
+            let stored = self.read_stored(&entry)?;
+            compacted.file.seek(SeekFrom::Start(compacted.end))?;
+            compacted.file.write_all(&stored)?;
+            compacted.entries.push(ArchiveEntry {
+                offset: compacted.end,
+                ..entry
+            });
+            compacted.end += stored.len() as u64;
+        }
+        compacted.dirty = true;
+        compacted.flush()?;
+
+        fs::rename(&tmp, &self.path)?;
+        compacted.path = self.path.clone();
+        self.dirty = false;
+        *self = compacted;
+        Ok(())
+    }
+}
+
+impl Drop for SaveArchive {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -0,0 +1,117 @@
+//! Saving large lists in independently loadable chunks, so they can be loaded on multiple threads.
+//!
+//! The list is saved as a `Vec<ByteBuffer>` with one buffer per chunk, and every chunk contains a
+//! part of the list saved as a `Vec<T>`. A single chunk can be loaded without the others with
+//! [`load_chunk`].
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::save::chunked::Chunked;
+//!
+//! let tiles = (0..10000u32).collect::<Vec<_>>();
+//! let chunked = Chunked::new().chunk_len(1024);
+//!
+//! let mut buffer = ByteBuffer::new();
+//! chunked.save(&tiles, &mut buffer);
+//! assert_eq!(chunked.load::<u32>(&mut buffer).unwrap(), tiles);
+//! ```
+
+use crate::platform;
+use crate::save::{Loader, Savable, SaveError, Saver, MAX_PREALLOCATION};
+use bytebuffer::ByteBuffer;
+
+/// Settings for saving and loading chunked lists.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Chunked {
+    chunk_len: usize,
+    threads: usize,
+}
+
+impl Default for Chunked {
+    fn default() -> Self {
+        Chunked::new()
+    }
+}
+
+impl Chunked {
+    pub fn new() -> Self {
+        Chunked {
+            chunk_len: 65536,
+            threads: platform::available_parallelism(),
+        }
+    }
+
+    /// The number of items in each chunk, defaults to 65536.
+    pub fn chunk_len(mut self, chunk_len: usize) -> Self {
+        self.chunk_len = chunk_len.max(1);
+        self
+    }
+
+    /// The maximum number of threads used to save and load chunks, defaults to the available
+    /// parallelism. No more threads than there are chunks are used.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Save the items in chunks, saving the chunks on multiple threads.
+    pub fn save<T: Savable + Sync>(&self, items: &[T], saver: &mut impl Saver) {
+        let chunks = items.chunks(self.chunk_len).collect::<Vec<_>>();
+        let buffers = self.run(&chunks, |chunk| {
+            let mut buffer = ByteBuffer::new();
+            buffer.push_u64(chunk.len() as u64);
+            for item in *chunk {
+                item.save(&mut buffer);
+            }
+            buffer
+        });
+        buffers.save(saver);
+    }
+
+    /// Load items saved with [`Chunked::save`], loading the chunks on multiple threads.
+    pub fn load<T: Savable + Send>(&self, loader: &mut impl Loader) -> Result<Vec<T>, SaveError> {
+        let chunks = split(loader)?;
+        let loaded = self.run(&chunks, |chunk| load_chunk::<T>(chunk));
+        let mut items = Vec::with_capacity(chunks.len().saturating_mul(self.chunk_len).min(MAX_PREALLOCATION as usize));
+        for chunk in loaded {
+            items.extend(chunk?);
+        }
+        Ok(items)
+    }
+
+    /// Apply the function to every input, splitting the inputs into one contiguous group per
+    /// thread, and return the results in order.
+    fn run<I: Sync, O: Send>(&self, inputs: &[I], f: impl Fn(&I) -> O + Sync) -> Vec<O> {
+        let threads = self.threads.min(inputs.len());
+        if threads <= 1 || !platform::HAS_THREADS {
+            return inputs.iter().map(f).collect();
+        }
+        let group = inputs.len().div_ceil(threads);
+        let f = &f;
+        std::thread::scope(|scope| {
+            let handles = inputs
+                .chunks(group)
+                .map(|inputs| scope.spawn(move || inputs.iter().map(f).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_else(|payload| std::panic::resume_unwind(payload)))
+                .collect()
+        })
+    }
+}
+
+/// Read the raw chunks of a list saved with [`Chunked::save`], without loading their items.
+pub fn split(loader: &mut impl Loader) -> Result<Vec<ByteBuffer>, SaveError> {
+    Vec::load(loader)
+}
+
+/// Load the items of a single chunk returned by [`split`].
+pub fn load_chunk<T: Savable>(chunk: &ByteBuffer) -> Result<Vec<T>, SaveError> {
+    let mut loader = ByteBuffer::from_bytes(chunk.as_bytes());
+    let items = Vec::<T>::load(&mut loader)?;
+    if loader.get_rpos() != loader.len() {
+        return Err(SaveError::custom(format!("{} unexpected bytes after the chunk", loader.len() - loader.get_rpos())));
+    }
+    Ok(items)
+}
@@ -0,0 +1,111 @@
+//! Incremental saving of changed fields, see [`DirtySavable`](crate::DirtySavable).
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::save::dirty::{DirtyMask, DirtySavable};
+//! use mvutils::DirtySavable;
+//!
+//! #[derive(DirtySavable, Default, Debug, PartialEq)]
+//! struct Player {
+//!     name: String,
+//!     hp: u32,
+//!     position: (f32, f32),
+//!     #[dirty_mask]
+//!     dirty: DirtyMask,
+//! }
+//!
+//! let mut player = Player::default();
+//! player.set_hp(80);
+//! player.position_mut().0 += 1.5;
+//!
+//! let mut buffer = ByteBuffer::new();
+//! player.flush_dirty(&mut buffer);
+//! assert!(!player.is_dirty());
+//!
+//! let mut replica = Player::default();
+//! replica.apply(&mut buffer).unwrap();
+//! assert_eq!(replica, player);
+//! ```
+
+use crate::save::{Loader, SaveError, Saver};
+
+/// The dirty bits of a struct deriving [`DirtySavable`](crate::DirtySavable), one per saved field.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct DirtyMask(u64);
+
+impl DirtyMask {
+    pub fn new() -> Self {
+        DirtyMask(0)
+    }
+
+    pub fn from_bits(bits: u64) -> Self {
+        DirtyMask(bits)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0 |= 1 << index;
+    }
+
+    pub fn unset(&mut self, index: usize) {
+        self.0 &= !(1 << index);
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.0 & (1 << index) != 0
+    }
+
+    /// Mark the first `count` fields as dirty.
+    pub fn set_all(&mut self, count: usize) {
+        self.0 = if count >= 64 { u64::MAX } else { (1 << count) - 1 };
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Saving only the fields that changed since the dirty bits were last cleared.
+///
+/// The saved data starts with a bitmask of the included fields, using the smallest unsigned integer
+/// that fits all fields, followed by the changed fields in declaration order.
+pub trait DirtySavable {
+    /// The amount of fields tracked by the dirty mask.
+    const FIELD_COUNT: usize;
+
+    fn dirty_mask(&self) -> DirtyMask;
+
+    fn dirty_mask_mut(&mut self) -> &mut DirtyMask;
+
+    /// Save the bitmask followed by all dirty fields, without clearing the dirty bits.
+    fn save_dirty(&self, saver: &mut impl Saver);
+
+    /// Load the fields saved with [`DirtySavable::save_dirty`] into this value. The dirty bits are
+    /// left untouched.
+    fn apply(&mut self, loader: &mut impl Loader) -> Result<(), SaveError>;
+
+    fn is_dirty(&self) -> bool {
+        !self.dirty_mask().is_empty()
+    }
+
+    fn mark_all_dirty(&mut self) {
+        self.dirty_mask_mut().set_all(Self::FIELD_COUNT);
+    }
+
+    fn clear_dirty(&mut self) {
+        self.dirty_mask_mut().clear();
+    }
+
+    /// Save all dirty fields and clear the dirty bits.
+    fn flush_dirty(&mut self, saver: &mut impl Saver) {
+        self.save_dirty(saver);
+        self.clear_dirty();
+    }
+}
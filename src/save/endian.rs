@@ -0,0 +1,146 @@
+use crate::save::{Loader, Savable, SaveError, SaveSize, Saver};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Primitives that can be converted to and from bytes in a specific byte order.
+pub trait EndianBytes: Copy {
+    const SIZE: usize;
+
+    fn write_le(self, bytes: &mut [u8]);
+    fn write_be(self, bytes: &mut [u8]);
+    fn read_le(bytes: &[u8]) -> Self;
+    fn read_be(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_endian_bytes {
+    ($($t:ty),*) => {
+        $(
+            impl EndianBytes for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_le(self, bytes: &mut [u8]) {
+                    bytes.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn write_be(self, bytes: &mut [u8]) {
+                    bytes.copy_from_slice(&self.to_be_bytes());
+                }
+
+                fn read_le(bytes: &[u8]) -> Self {
+                    <$t>::from_le_bytes(bytes.try_into().unwrap())
+                }
+
+                fn read_be(bytes: &[u8]) -> Self {
+                    <$t>::from_be_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_endian_bytes!(u16, u32, u64, u128, i16, i32, i64, i128, f32, f64);
+
+/// Read a little endian value from the start of the bytes, or None if there are not enough bytes.
+pub fn read_le<T: EndianBytes>(bytes: &[u8]) -> Option<T> {
+    bytes.get(..T::SIZE).map(T::read_le)
+}
+
+/// Read a big endian value from the start of the bytes, or None if there are not enough bytes.
+pub fn read_be<T: EndianBytes>(bytes: &[u8]) -> Option<T> {
+    bytes.get(..T::SIZE).map(T::read_be)
+}
+
+/// Append the value to the bytes in little endian order.
+pub fn write_le<T: EndianBytes>(bytes: &mut Vec<u8>, value: T) {
+    let start = bytes.len();
+    bytes.resize(start + T::SIZE, 0);
+    value.write_le(&mut bytes[start..]);
+}
+
+/// Append the value to the bytes in big endian order.
+pub fn write_be<T: EndianBytes>(bytes: &mut Vec<u8>, value: T) {
+    let start = bytes.len();
+    bytes.resize(start + T::SIZE, 0);
+    value.write_be(&mut bytes[start..]);
+}
+
+macro_rules! endian_wrapper {
+    ($name:ident, $write:ident, $read:ident, $doc:literal) => {
+        #[doc = $doc]
+        #[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $name<T>(pub T);
+
+        impl<T> $name<T> {
+            pub fn new(value: T) -> Self {
+                $name(value)
+            }
+
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T: Copy> $name<T> {
+            pub fn get(&self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                $name(value)
+            }
+        }
+
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl<T: Debug> Debug for $name<T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl<T: Display> Display for $name<T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl<T: EndianBytes> Savable for $name<T> {
+            fn save(&self, saver: &mut impl Saver) {
+                let mut bytes = [0u8; 16];
+                self.0.$write(&mut bytes[..T::SIZE]);
+                saver.push_bytes(&bytes[..T::SIZE]);
+            }
+
+            fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+                let bytes = loader.pop_bytes(T::SIZE).ok_or(SaveError::eof(stringify!($name)))?;
+                Ok($name(T::$read(&bytes)))
+            }
+        }
+
+        impl<T: EndianBytes> SaveSize for $name<T> {
+            const FIXED_SIZE: Option<usize> = Some(T::SIZE);
+
+            fn save_size(&self) -> usize {
+                T::SIZE
+            }
+        }
+    };
+}
+
+endian_wrapper!(Le, write_le, read_le, "A value that is always saved in little endian order, regardless of the endianness of the saver.");
+endian_wrapper!(Be, write_be, read_be, "A value that is always saved in big endian order, regardless of the endianness of the saver.");
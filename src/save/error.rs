@@ -0,0 +1,112 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::string::FromUtf8Error;
+
+/// The error returned when loading a [`Savable`](crate::save::Savable) fails.
+///
+/// Errors raised inside derived types are wrapped in [`SaveError::Field`] for every field they pass
+/// through, so the message shows where loading failed, for example `inventory.items: Unexpected end
+/// of data while loading u8`. Use [`SaveError::root`] to match on the underlying error.
+///
+/// It converts from and into [`String`], so custom load functions returning `Result<T, String>` keep
+/// working with `?`.
+#[derive(Debug, PartialEq)]
+pub enum SaveError {
+    /// The loader ran out of data while loading a value of this type.
+    UnexpectedEof { ty: &'static str },
+    /// An enum or tagged value was saved with a discriminant that is not known.
+    InvalidDiscriminant { ty: &'static str, value: u64 },
+    Utf8(FromUtf8Error),
+    Custom(String),
+    /// The loader does not support this operation, for example seeking in a stream.
+    Unsupported(&'static str),
+    /// The error occurred while loading the field with this name.
+    Field { name: &'static str, error: Box<SaveError> },
+}
+
+impl SaveError {
+    pub fn eof(ty: &'static str) -> Self {
+        SaveError::UnexpectedEof { ty }
+    }
+
+    pub fn custom(message: impl Into<String>) -> Self {
+        SaveError::Custom(message.into())
+    }
+
+    /// Wrap the error to record that it occurred while loading the field.
+    pub fn in_field(self, name: &'static str) -> Self {
+        SaveError::Field {
+            name,
+            error: Box::new(self),
+        }
+    }
+
+    /// The error without any field information.
+    pub fn root(&self) -> &SaveError {
+        match self {
+            SaveError::Field { error, .. } => error.root(),
+            other => other,
+        }
+    }
+
+    /// The names of the fields from the outermost to the innermost one in which the error occurred.
+    pub fn path(&self) -> Vec<&'static str> {
+        let mut path = Vec::new();
+        let mut error = self;
+        while let SaveError::Field { name, error: inner } = error {
+            path.push(*name);
+            error = inner;
+        }
+        path
+    }
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = self.path();
+        if !path.is_empty() {
+            write!(f, "{}: ", path.join("."))?;
+        }
+        match self.root() {
+            SaveError::UnexpectedEof { ty } => write!(f, "Unexpected end of data while loading {ty}"),
+            SaveError::InvalidDiscriminant { ty, value } => write!(f, "Invalid discriminant {value} for {ty}"),
+            SaveError::Utf8(e) => write!(f, "Invalid UTF-8: {e}"),
+            SaveError::Custom(message) => f.write_str(message),
+            SaveError::Unsupported(operation) => write!(f, "The loader does not support {operation}"),
+            SaveError::Field { .. } => unreachable!(),
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.root() {
+            SaveError::Utf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for SaveError {
+    fn from(value: String) -> Self {
+        SaveError::Custom(value)
+    }
+}
+
+impl From<&str> for SaveError {
+    fn from(value: &str) -> Self {
+        SaveError::Custom(value.to_string())
+    }
+}
+
+impl From<FromUtf8Error> for SaveError {
+    fn from(value: FromUtf8Error) -> Self {
+        SaveError::Utf8(value)
+    }
+}
+
+impl From<SaveError> for String {
+    fn from(value: SaveError) -> Self {
+        value.to_string()
+    }
+}
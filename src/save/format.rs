@@ -0,0 +1,111 @@
+//! Fingerprints of the saved layout of types, to detect data saved by a different version of a type.
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::save::format::{load_with_format_hash, save_with_format_hash, FormatHash};
+//! use mvutils::Savable;
+//!
+//! #[derive(Savable, Debug, PartialEq)]
+//! struct Settings {
+//!     volume: f32,
+//!     fullscreen: bool,
+//! }
+//!
+//! #[derive(Savable)]
+//! struct NewSettings {
+//!     volume: f32,
+//!     fullscreen: bool,
+//!     vsync: bool,
+//! }
+//!
+//! assert_ne!(Settings::FORMAT_HASH, NewSettings::FORMAT_HASH);
+//!
+//! let mut buffer = ByteBuffer::new();
+//! save_with_format_hash(&mut buffer, &Settings { volume: 0.5, fullscreen: true });
+//! assert!(load_with_format_hash::<NewSettings>(&mut buffer.clone()).is_err());
+//! assert!(load_with_format_hash::<Settings>(&mut buffer).is_ok());
+//! ```
+
+use crate::save::{Loader, Savable, SaveError, Saver};
+use std::marker::PhantomData;
+
+/// A hash of the saved layout of a type, implemented by the [`Savable`](crate::Savable) derive.
+///
+/// The hash covers the types and order of the saved fields, the enum variants and their ids, and
+/// attributes changing the saved layout such as `#[unsaved]` and `#[custom(..)]`. Names of the type,
+/// its fields and its variants are not saved, so renaming them keeps the hash.
+///
+/// Fields whose type implements [`FormatHash`] itself contribute that hash, so a layout change in a
+/// nested derived type also changes the hash of the outer type. All other field types are hashed by
+/// how they are written, including generic parameters and derived types inside containers such as
+/// `Vec<T>` or `Option<T>`, so changes inside those types are not detected.
+pub trait FormatHash {
+    const FORMAT_HASH: u64;
+}
+
+#[doc(hidden)]
+pub struct Nested<T>(PhantomData<T>);
+
+#[doc(hidden)]
+pub trait NoFormatHash {
+    const NESTED: Option<u64> = None;
+}
+
+impl<T> NoFormatHash for Nested<T> {}
+
+impl<T: FormatHash> Nested<T> {
+    /// Takes precedence over [`NoFormatHash::NESTED`] for types with a format hash.
+    #[doc(hidden)]
+    pub const NESTED: Option<u64> = Some(T::FORMAT_HASH);
+}
+
+#[doc(hidden)]
+pub const fn field_hash(nested: Option<u64>, written: u64) -> u64 {
+    match nested {
+        Some(hash) => hash,
+        None => written,
+    }
+}
+
+#[doc(hidden)]
+pub const fn combine_hashes(base: u64, fields: &[u64]) -> u64 {
+    let mut hash = base;
+    let mut i = 0;
+    while i < fields.len() {
+        let bytes = fields[i].to_le_bytes();
+        let mut j = 0;
+        while j < bytes.len() {
+            hash ^= bytes[j] as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+            j += 1;
+        }
+        i += 1;
+    }
+    hash
+}
+
+/// Save the format hash of `T` followed by the value.
+pub fn save_with_format_hash<T: Savable + FormatHash>(saver: &mut impl Saver, value: &T) {
+    T::FORMAT_HASH.save(saver);
+    value.save(saver);
+}
+
+/// Load a value saved with [`save_with_format_hash`], failing if it was saved with a different format.
+pub fn load_with_format_hash<T: Savable + FormatHash>(loader: &mut impl Loader) -> Result<T, SaveError> {
+    verify_format_hash::<T>(loader)?;
+    T::load(loader)
+}
+
+/// Load a format hash and check that it matches the one of `T`.
+pub fn verify_format_hash<T: FormatHash>(loader: &mut impl Loader) -> Result<(), SaveError> {
+    let hash = u64::load(loader).map_err(|e| e.in_field("format hash"))?;
+    if hash != T::FORMAT_HASH {
+        return Err(SaveError::custom(format!(
+            "Format hash mismatch for {}: expected {:#018x}, found {:#018x}",
+            std::any::type_name::<T>(),
+            T::FORMAT_HASH,
+            hash
+        )));
+    }
+    Ok(())
+}
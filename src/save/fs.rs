@@ -0,0 +1,116 @@
+use crate::save::Savable;
+use bytebuffer::ByteBuffer;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Options for [`save_to_file_with`].
+#[derive(Clone, Debug)]
+pub struct SaveOptions {
+    /// The amount of previous versions to keep as `<file>.bak1` (newest) to `<file>.bakN` (oldest).
+    pub backups: usize,
+    /// Whether to fsync the file and its directory, so the save survives a power loss.
+    pub sync: bool,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        SaveOptions {
+            backups: 0,
+            sync: true,
+        }
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// The path of the n-th backup of a file, with 1 being the most recent one.
+pub fn backup_path(path: impl AsRef<Path>, n: usize) -> PathBuf {
+    with_suffix(path.as_ref(), &format!(".bak{n}"))
+}
+
+fn rotate_backups(path: &Path, backups: usize) -> io::Result<()> {
+    if backups == 0 || !path.exists() {
+        return Ok(());
+    }
+    for n in (1..backups).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+fn sync_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Save a value to a file using the default [`SaveOptions`].
+pub fn save_to_file(path: impl AsRef<Path>, value: &impl Savable) -> io::Result<()> {
+    save_to_file_with(path, value, &SaveOptions::default())
+}
+
+/// Save a value to a file atomically. The data is written to a temporary file next to the target,
+/// which is then renamed over it, so a crash never leaves a partially written file behind.
+pub fn save_to_file_with(path: impl AsRef<Path>, value: &impl Savable, options: &SaveOptions) -> io::Result<()> {
+    let path = path.as_ref();
+    let tmp = with_suffix(path, ".tmp");
+
+    let mut buffer = ByteBuffer::new();
+    value.save(&mut buffer);
+
+    let result = (|| {
+        let mut file = File::create(&tmp)?;
+        file.write_all(buffer.as_bytes())?;
+        if options.sync {
+            file.sync_all()?;
+        }
+        drop(file);
+
+        rotate_backups(path, options.backups)?;
+        fs::rename(&tmp, path)?;
+        if options.sync {
+            sync_dir(path);
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Load a value from a file written by [`save_to_file`].
+pub fn load_from_file<T: Savable>(path: impl AsRef<Path>) -> Result<T, String> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    T::load(&mut ByteBuffer::from_vec(bytes)).map_err(Into::into)
+}
+
+/// Load a value from a file, falling back to its backups from newest to oldest if the file is
+/// missing or cannot be loaded. Returns the error of the main file if all attempts fail.
+pub fn load_with_backups<T: Savable>(path: impl AsRef<Path>, backups: usize) -> Result<T, String> {
+    let path = path.as_ref();
+    let error = match load_from_file(path) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    (1..=backups)
+        .find_map(|n| load_from_file(backup_path(path, n)).ok())
+        .ok_or(error)
+}
@@ -0,0 +1,146 @@
+//! Saving trait objects by registering the concrete types behind them, see [`register_poly!`](crate::register_poly).
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::save::poly::PolySavable;
+//! use mvutils::save::Savable;
+//! use mvutils::{register_poly, Savable};
+//!
+//! trait Shape: PolySavable {
+//!     fn area(&self) -> f32;
+//! }
+//!
+//! #[derive(Savable)]
+//! struct Circle(f32);
+//!
+//! #[derive(Savable)]
+//! struct Square(f32);
+//!
+//! impl Shape for Circle {
+//!     fn area(&self) -> f32 {
+//!         3.14 * self.0 * self.0
+//!     }
+//! }
+//!
+//! impl Shape for Square {
+//!     fn area(&self) -> f32 {
+//!         self.0 * self.0
+//!     }
+//! }
+//!
+//! register_poly!(dyn Shape: Circle = 1, Square = 2);
+//!
+//! let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Circle(1.0)), Box::new(Square(2.0))];
+//! let mut buffer = ByteBuffer::new();
+//! shapes.save(&mut buffer);
+//!
+//! let loaded = Vec::<Box<dyn Shape>>::load(&mut buffer).unwrap();
+//! assert_eq!(loaded[1].area(), 4.0);
+//! ```
+
+use crate::save::{Loader, Savable, SaveError, Saver};
+use std::any::Any;
+use std::fmt::Display;
+
+/// Supertrait for traits whose boxed trait objects are saved with [`register_poly!`](crate::register_poly).
+/// It is implemented for every `'static` type.
+pub trait PolySavable: Any {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> PolySavable for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A tag identifying a registered type, either a `&'static str` or a `u32`.
+pub trait PolyTag: Copy {
+    type Loaded: Display;
+
+    fn save_tag(&self, saver: &mut impl Saver);
+
+    fn load_tag(loader: &mut impl Loader) -> Result<Self::Loaded, SaveError>;
+
+    fn matches(&self, loaded: &Self::Loaded) -> bool;
+}
+
+impl PolyTag for &'static str {
+    type Loaded = String;
+
+    fn save_tag(&self, saver: &mut impl Saver) {
+        saver.push_string(self);
+    }
+
+    fn load_tag(loader: &mut impl Loader) -> Result<String, SaveError> {
+        String::load(loader)
+    }
+
+    fn matches(&self, loaded: &String) -> bool {
+        *self == loaded
+    }
+}
+
+impl PolyTag for u32 {
+    type Loaded = u32;
+
+    fn save_tag(&self, saver: &mut impl Saver) {
+        saver.push_u32(*self);
+    }
+
+    fn load_tag(loader: &mut impl Loader) -> Result<u32, SaveError> {
+        u32::load(loader)
+    }
+
+    fn matches(&self, loaded: &u32) -> bool {
+        self == loaded
+    }
+}
+
+#[doc(hidden)]
+pub fn load_tag<T: PolyTag>(loader: &mut impl Loader, _tags: &[T]) -> Result<T::Loaded, SaveError> {
+    T::load_tag(loader).map_err(|e| e.in_field("tag"))
+}
+
+/// Implements [`Savable`] for `Box<dyn Trait>` by saving a tag of the concrete type followed by the value.
+///
+/// The trait needs [`PolySavable`] as a supertrait. Tags default to the type names, or can be
+/// given explicitly as string or integer literals, which keeps data loadable after renaming types.
+///
+/// ```ignore
+/// register_poly!(dyn Shape: Circle, Square);
+/// register_poly!(dyn Shape: Circle = "circle", Square = "square");
+/// register_poly!(dyn Shape: Circle = 1, Square = 2);
+/// ```
+#[macro_export]
+macro_rules! register_poly {
+    (dyn $tr:path: $($ty:ty),+ $(,)?) => {
+        $crate::register_poly!(dyn $tr: $($ty = stringify!($ty)),+);
+    };
+    (dyn $tr:path: $($ty:ty = $tag:expr),+ $(,)?) => {
+        impl $crate::save::Savable for Box<dyn $tr> {
+            fn save(&self, saver: &mut impl $crate::save::Saver) {
+                let any = $crate::save::poly::PolySavable::as_any(&**self);
+                $(
+                    if let Some(value) = any.downcast_ref::<$ty>() {
+                        $crate::save::poly::PolyTag::save_tag(&$tag, saver);
+                        $crate::save::Savable::save(value, saver);
+                        return;
+                    }
+                )+
+                panic!("Saving a type that is not registered for Box<dyn {}>!", stringify!($tr));
+            }
+
+            fn load(loader: &mut impl $crate::save::Loader) -> Result<Self, $crate::save::SaveError> {
+                let tag = $crate::save::poly::load_tag(loader, &[$($tag),+])?;
+                $(
+                    if $crate::save::poly::PolyTag::matches(&$tag, &tag) {
+                        let value = <$ty as $crate::save::Savable>::load(loader).map_err(|e| e.in_field(stringify!($ty)))?;
+                        return Ok(Box::new(value));
+                    }
+                )+
+                Err($crate::save::SaveError::custom(format!("Unknown tag {} for Box<dyn {}>", tag, stringify!($tr))))
+            }
+        }
+    };
+}
@@ -0,0 +1,551 @@
+use crate::save::Savable;
+use bytebuffer::ByteBuffer;
+use hashbrown::{HashMap, HashSet};
+use std::fmt::Write;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Describes the wire format of a single saved value, as written by [`Savable::save`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SchemaType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    String,
+    Option(Box<SchemaType>),
+    Vec(Box<SchemaType>),
+    Array(Box<SchemaType>, usize),
+    Tuple(Vec<SchemaType>),
+    Map(Box<SchemaType>, Box<SchemaType>),
+    /// A reference to a struct or enum listed in the [`Definitions`].
+    Named(&'static str),
+    /// A value saved using custom functions, the layout of which is unknown.
+    Opaque,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FieldDef {
+    pub name: String,
+    pub ty: SchemaType,
+}
+
+impl FieldDef {
+    pub fn new(name: impl Into<String>, ty: SchemaType) -> Self {
+        FieldDef {
+            name: name.into(),
+            ty,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VariantDef {
+    pub name: String,
+    /// The saved id of the variant.
+    pub id: u32,
+    pub fields: Vec<FieldDef>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TypeDef {
+    Struct {
+        name: &'static str,
+        fields: Vec<FieldDef>,
+    },
+    Enum {
+        name: &'static str,
+        tag: SchemaType,
+        variants: Vec<VariantDef>,
+    },
+}
+
+impl TypeDef {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TypeDef::Struct { name, .. } => name,
+            TypeDef::Enum { name, .. } => name,
+        }
+    }
+}
+
+/// The set of struct and enum definitions reachable from one or more root types, ordered so that
+/// every definition comes after the definitions it depends on.
+#[derive(Default, Debug)]
+pub struct Definitions {
+    defs: Vec<TypeDef>,
+    visited: HashSet<&'static str>,
+}
+
+impl Definitions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn of<T: Schema>() -> Self {
+        let mut defs = Self::new();
+        defs.add::<T>();
+        defs
+    }
+
+    pub fn add<T: Schema>(&mut self) {
+        T::definitions(self);
+    }
+
+    /// Marks the type as visited, returning false if it was visited before. Used by the derive
+    /// to avoid infinite recursion.
+    pub fn visit(&mut self, name: &'static str) -> bool {
+        self.visited.insert(name)
+    }
+
+    pub fn push(&mut self, def: TypeDef) {
+        self.defs.push(def);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TypeDef> {
+        self.defs.iter()
+    }
+}
+
+pub trait Schema: Savable {
+    fn schema_type() -> SchemaType;
+
+    fn definitions(_definitions: &mut Definitions) {}
+}
+
+macro_rules! impl_schema_primitive {
+    ($($t:ty => $s:ident),*) => {
+        $(
+            impl Schema for $t {
+                fn schema_type() -> SchemaType {
+                    SchemaType::$s
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_primitive!(
+    bool => Bool, u8 => U8, u16 => U16, u32 => U32, u64 => U64, i8 => I8, i16 => I16,
+    i32 => I32, i64 => I64, f32 => F32, f64 => F64, String => String
+);
+
+macro_rules! impl_schema_tuple {
+    () => {};
+    ($first:ident $($rest:ident)*) => {
+        impl_schema_tuple!($($rest)*);
+        impl<$first: Schema, $($rest: Schema),*> Schema for ($first, $($rest),*) {
+            fn schema_type() -> SchemaType {
+                SchemaType::Tuple(vec![$first::schema_type(), $($rest::schema_type()),*])
+            }
+
+            fn definitions(definitions: &mut Definitions) {
+                $first::definitions(definitions);
+                $( $rest::definitions(definitions); )*
+            }
+        }
+    };
+}
+
+impl_schema_tuple!(E D C B A Z Y X W V U T);
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema_type() -> SchemaType {
+        SchemaType::Option(Box::new(T::schema_type()))
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        T::definitions(definitions);
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema_type() -> SchemaType {
+        SchemaType::Vec(Box::new(T::schema_type()))
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        T::definitions(definitions);
+    }
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    fn schema_type() -> SchemaType {
+        SchemaType::Array(Box::new(T::schema_type()), N)
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        T::definitions(definitions);
+    }
+}
+
+impl<T: Schema> Schema for Box<T> {
+    fn schema_type() -> SchemaType {
+        T::schema_type()
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        T::definitions(definitions);
+    }
+}
+
+impl Schema for ByteBuffer {
+    fn schema_type() -> SchemaType {
+        SchemaType::Vec(Box::new(SchemaType::U8))
+    }
+}
+
+impl<T: Schema + Eq + std::hash::Hash> Schema for std::collections::HashSet<T> {
+    fn schema_type() -> SchemaType {
+        SchemaType::Vec(Box::new(T::schema_type()))
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        T::definitions(definitions);
+    }
+}
+
+impl<T: Schema + Eq + std::hash::Hash> Schema for HashSet<T> {
+    fn schema_type() -> SchemaType {
+        SchemaType::Vec(Box::new(T::schema_type()))
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        T::definitions(definitions);
+    }
+}
+
+impl<K: Schema + Eq + std::hash::Hash, V: Schema> Schema for std::collections::HashMap<K, V> {
+    fn schema_type() -> SchemaType {
+        SchemaType::Map(Box::new(K::schema_type()), Box::new(V::schema_type()))
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        K::definitions(definitions);
+        V::definitions(definitions);
+    }
+}
+
+impl<K: Schema + Eq + std::hash::Hash, V: Schema> Schema for HashMap<K, V> {
+    fn schema_type() -> SchemaType {
+        SchemaType::Map(Box::new(K::schema_type()), Box::new(V::schema_type()))
+    }
+
+    fn definitions(definitions: &mut Definitions) {
+        K::definitions(definitions);
+        V::definitions(definitions);
+    }
+}
+
+macro_rules! impl_schema_time {
+    ($($t:ty),*) => {
+        $(
+            impl Schema for $t {
+                fn schema_type() -> SchemaType {
+                    SchemaType::Tuple(vec![SchemaType::U64, SchemaType::U32])
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_time!(Duration, Instant, SystemTime);
+
+fn c_type(ty: &SchemaType) -> String {
+    match ty {
+        SchemaType::Bool => "bool".to_string(),
+        SchemaType::U8 => "uint8_t".to_string(),
+        SchemaType::U16 => "uint16_t".to_string(),
+        SchemaType::U32 => "uint32_t".to_string(),
+        SchemaType::U64 => "uint64_t".to_string(),
+        SchemaType::I8 => "int8_t".to_string(),
+        SchemaType::I16 => "int16_t".to_string(),
+        SchemaType::I32 => "int32_t".to_string(),
+        SchemaType::I64 => "int64_t".to_string(),
+        SchemaType::F32 => "float".to_string(),
+        SchemaType::F64 => "double".to_string(),
+        SchemaType::String => "mv_string".to_string(),
+        SchemaType::Option(inner) => format!("struct {{ bool present; {} }}", c_field(inner, "value")),
+        SchemaType::Vec(inner) => format!("struct {{ uint64_t len; {}* data; }}", c_type(inner)),
+        SchemaType::Array(inner, _) => c_type(inner),
+        SchemaType::Tuple(items) => {
+            let fields = items.iter().enumerate().map(|(i, t)| c_field(t, &format!("_{i}"))).collect::<Vec<_>>();
+            format!("struct {{ {} }}", fields.join(" "))
+        }
+        SchemaType::Map(k, v) => format!("struct {{ uint64_t len; {}* keys; {}* values; }}", c_type(k), c_type(v)),
+        SchemaType::Named(name) => name.to_string(),
+        SchemaType::Opaque => "mv_bytes".to_string(),
+    }
+}
+
+fn c_field(ty: &SchemaType, name: &str) -> String {
+    match ty {
+        SchemaType::Array(inner, len) => format!("{} {}[{}];", c_type(inner), name, len),
+        _ => format!("{} {};", c_type(ty), name),
+    }
+}
+
+/// Emits a C header containing struct definitions mirroring the saved layout of all definitions,
+/// as well as prototypes for read and write functions. Strings are represented as `mv_string`,
+/// values saved with custom functions as `mv_bytes`.
+pub fn emit_c_header(definitions: &Definitions, guard: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "#ifndef {guard}");
+    let _ = writeln!(out, "#define {guard}\n");
+    out.push_str("#include <stdbool.h>\n#include <stddef.h>\n#include <stdint.h>\n\n");
+    out.push_str("typedef struct mv_string { uint32_t len; char* data; } mv_string;\n");
+    out.push_str("typedef struct mv_bytes { uint64_t len; uint8_t* data; } mv_bytes;\n");
+
+    for def in definitions.iter() {
+        out.push('\n');
+        match def {
+            TypeDef::Struct { name, fields } => {
+                let _ = writeln!(out, "typedef struct {name} {{");
+                for field in fields {
+                    let _ = writeln!(out, "    {}", c_field(&field.ty, &field.name));
+                }
+                let _ = writeln!(out, "}} {name};");
+            }
+            TypeDef::Enum { name, tag, variants } => {
+                let _ = writeln!(out, "enum {{");
+                for variant in variants {
+                    let _ = writeln!(out, "    {}_{} = {},", name, variant.name, variant.id);
+                }
+                let _ = writeln!(out, "}};\n");
+                let _ = writeln!(out, "typedef struct {name} {{");
+                let _ = writeln!(out, "    {}", c_field(tag, "tag"));
+                if variants.iter().any(|v| !v.fields.is_empty()) {
+                    out.push_str("    union {\n");
+                    for variant in variants.iter().filter(|v| !v.fields.is_empty()) {
+                        out.push_str("        struct {\n");
+                        for field in &variant.fields {
+                            let _ = writeln!(out, "            {}", c_field(&field.ty, &field.name));
+                        }
+                        let _ = writeln!(out, "        }} {};", variant.name);
+                    }
+                    out.push_str("    } value;\n");
+                }
+                let _ = writeln!(out, "}} {name};");
+            }
+        }
+        let name = def.name();
+        let _ = writeln!(out, "\nsize_t {name}_read(const uint8_t* data, size_t len, {name}* out);");
+        let _ = writeln!(out, "size_t {name}_write(const {name}* value, uint8_t* out, size_t capacity);");
+    }
+
+    let _ = writeln!(out, "\n#endif // {guard}");
+    out
+}
+
+fn ts_type(ty: &SchemaType) -> String {
+    match ty {
+        SchemaType::Bool => "boolean".to_string(),
+        SchemaType::U64 | SchemaType::I64 => "bigint".to_string(),
+        SchemaType::String => "string".to_string(),
+        SchemaType::Option(inner) => format!("{} | null", ts_type(inner)),
+        SchemaType::Vec(inner) | SchemaType::Array(inner, _) => format!("Array<{}>", ts_type(inner)),
+        SchemaType::Tuple(items) => format!("[{}]", items.iter().map(ts_type).collect::<Vec<_>>().join(", ")),
+        SchemaType::Map(k, v) => format!("Map<{}, {}>", ts_type(k), ts_type(v)),
+        SchemaType::Named(name) => name.to_string(),
+        SchemaType::Opaque => "unknown".to_string(),
+        _ => "number".to_string(),
+    }
+}
+
+fn ts_method(ty: &SchemaType) -> Option<&'static str> {
+    Some(match ty {
+        SchemaType::Bool => "bool",
+        SchemaType::U8 => "u8",
+        SchemaType::U16 => "u16",
+        SchemaType::U32 => "u32",
+        SchemaType::U64 => "u64",
+        SchemaType::I8 => "i8",
+        SchemaType::I16 => "i16",
+        SchemaType::I32 => "i32",
+        SchemaType::I64 => "i64",
+        SchemaType::F32 => "f32",
+        SchemaType::F64 => "f64",
+        SchemaType::String => "string",
+        _ => return None,
+    })
+}
+
+fn ts_read(ty: &SchemaType) -> String {
+    if let Some(method) = ts_method(ty) {
+        return format!("r.{method}()");
+    }
+    match ty {
+        SchemaType::Option(inner) => format!("(r.u8() !== 0 ? {} : null)", ts_read(inner)),
+        SchemaType::Vec(inner) => format!("Array.from({{ length: Number(r.u64()) }}, () => {})", ts_read(inner)),
+        SchemaType::Array(inner, len) => format!("Array.from({{ length: {len} }}, () => {})", ts_read(inner)),
+        SchemaType::Tuple(items) => format!("[{}] as {}", items.iter().map(ts_read).collect::<Vec<_>>().join(", "), ts_type(ty)),
+        SchemaType::Map(k, v) => format!(
+            "new Map(Array.from({{ length: Number(r.u64()) }}, () => [{}, {}] as [{}, {}]))",
+            ts_read(k), ts_read(v), ts_type(k), ts_type(v)
+        ),
+        SchemaType::Named(name) => format!("read{name}(r)"),
+        _ => "(() => { throw new Error(\"Cannot read value saved with custom functions\"); })()".to_string(),
+    }
+}
+
+fn ts_write(ty: &SchemaType) -> String {
+    let t = ts_type(ty);
+    if let Some(method) = ts_method(ty) {
+        return format!("(x: {t}) => w.{method}(x)");
+    }
+    match ty {
+        SchemaType::Option(inner) => format!("(x: {t}) => {{ if (x === null) {{ w.u8(0); }} else {{ w.u8(1); ({})(x); }} }}", ts_write(inner)),
+        SchemaType::Vec(inner) => format!("(x: {t}) => {{ w.u64(BigInt(x.length)); x.forEach({}); }}", ts_write(inner)),
+        SchemaType::Array(inner, _) => format!("(x: {t}) => x.forEach({})", ts_write(inner)),
+        SchemaType::Tuple(items) => {
+            let writes = items.iter().enumerate().map(|(i, t)| format!("({})(x[{i}]);", ts_write(t))).collect::<Vec<_>>();
+            format!("(x: {t}) => {{ {} }}", writes.join(" "))
+        }
+        SchemaType::Map(k, v) => format!(
+            "(x: {t}) => {{ w.u64(BigInt(x.size)); x.forEach((v, k) => {{ ({})(k); ({})(v); }}); }}",
+            ts_write(k), ts_write(v)
+        ),
+        SchemaType::Named(name) => format!("(x: {t}) => write{name}(w, x)"),
+        _ => "(_: unknown) => { throw new Error(\"Cannot write value saved with custom functions\"); }".to_string(),
+    }
+}
+
+const TS_RUNTIME: &str = r#"export class SaveReader {
+    private pos = 0;
+
+    constructor(private view: DataView, private littleEndian = false) {}
+
+    bool(): boolean { return this.u8() !== 0; }
+    u8(): number { const v = this.view.getUint8(this.pos); this.pos += 1; return v; }
+    u16(): number { const v = this.view.getUint16(this.pos, this.littleEndian); this.pos += 2; return v; }
+    u32(): number { const v = this.view.getUint32(this.pos, this.littleEndian); this.pos += 4; return v; }
+    u64(): bigint { const v = this.view.getBigUint64(this.pos, this.littleEndian); this.pos += 8; return v; }
+    i8(): number { const v = this.view.getInt8(this.pos); this.pos += 1; return v; }
+    i16(): number { const v = this.view.getInt16(this.pos, this.littleEndian); this.pos += 2; return v; }
+    i32(): number { const v = this.view.getInt32(this.pos, this.littleEndian); this.pos += 4; return v; }
+    i64(): bigint { const v = this.view.getBigInt64(this.pos, this.littleEndian); this.pos += 8; return v; }
+    f32(): number { const v = this.view.getFloat32(this.pos, this.littleEndian); this.pos += 4; return v; }
+    f64(): number { const v = this.view.getFloat64(this.pos, this.littleEndian); this.pos += 8; return v; }
+    string(): string {
+        const len = this.u32();
+        const bytes = new Uint8Array(this.view.buffer, this.view.byteOffset + this.pos, len);
+        this.pos += len;
+        return new TextDecoder().decode(bytes);
+    }
+}
+
+export class SaveWriter {
+    private chunks: Uint8Array[] = [];
+
+    constructor(private littleEndian = false) {}
+
+    private push(size: number, f: (view: DataView) => void) {
+        const chunk = new Uint8Array(size);
+        f(new DataView(chunk.buffer));
+        this.chunks.push(chunk);
+    }
+
+    bool(v: boolean) { this.u8(v ? 1 : 0); }
+    u8(v: number) { this.push(1, d => d.setUint8(0, v)); }
+    u16(v: number) { this.push(2, d => d.setUint16(0, v, this.littleEndian)); }
+    u32(v: number) { this.push(4, d => d.setUint32(0, v, this.littleEndian)); }
+    u64(v: bigint) { this.push(8, d => d.setBigUint64(0, v, this.littleEndian)); }
+    i8(v: number) { this.push(1, d => d.setInt8(0, v)); }
+    i16(v: number) { this.push(2, d => d.setInt16(0, v, this.littleEndian)); }
+    i32(v: number) { this.push(4, d => d.setInt32(0, v, this.littleEndian)); }
+    i64(v: bigint) { this.push(8, d => d.setBigInt64(0, v, this.littleEndian)); }
+    f32(v: number) { this.push(4, d => d.setFloat32(0, v, this.littleEndian)); }
+    f64(v: number) { this.push(8, d => d.setFloat64(0, v, this.littleEndian)); }
+    string(v: string) {
+        const bytes = new TextEncoder().encode(v);
+        this.u32(bytes.length);
+        this.chunks.push(bytes);
+    }
+
+    finish(): Uint8Array {
+        const out = new Uint8Array(this.chunks.reduce((n, c) => n + c.length, 0));
+        let pos = 0;
+        for (const chunk of this.chunks) {
+            out.set(chunk, pos);
+            pos += chunk.length;
+        }
+        return out;
+    }
+}
+"#;
+
+fn ts_fields(fields: &[FieldDef]) -> String {
+    fields.iter().map(|f| format!("{}: {}", f.name, ts_type(&f.ty))).collect::<Vec<_>>().join("; ")
+}
+
+/// Emits TypeScript type definitions for all definitions, together with `readX`/`writeX`
+/// functions and the small `SaveReader`/`SaveWriter` runtime they use.
+pub fn emit_ts(definitions: &Definitions) -> String {
+    let mut out = TS_RUNTIME.to_string();
+
+    for def in definitions.iter() {
+        out.push('\n');
+        match def {
+            TypeDef::Struct { name, fields } => {
+                let _ = writeln!(out, "export interface {name} {{");
+                for field in fields {
+                    let _ = writeln!(out, "    {}: {};", field.name, ts_type(&field.ty));
+                }
+                out.push_str("}\n\n");
+
+                let _ = writeln!(out, "export function read{name}(r: SaveReader): {name} {{");
+                out.push_str("    return {\n");
+                for field in fields {
+                    let _ = writeln!(out, "        {}: {},", field.name, ts_read(&field.ty));
+                }
+                out.push_str("    };\n}\n\n");
+
+                let _ = writeln!(out, "export function write{name}(w: SaveWriter, value: {name}) {{");
+                for field in fields {
+                    let _ = writeln!(out, "    ({})(value.{});", ts_write(&field.ty), field.name);
+                }
+                out.push_str("}\n");
+            }
+            TypeDef::Enum { name, tag, variants } => {
+                let types = variants.iter().map(|v| {
+                    if v.fields.is_empty() {
+                        format!("{{ kind: \"{}\" }}", v.name)
+                    } else {
+                        format!("{{ kind: \"{}\"; {} }}", v.name, ts_fields(&v.fields))
+                    }
+                }).collect::<Vec<_>>();
+                let _ = writeln!(out, "export type {name} =\n    | {};\n", types.join("\n    | "));
+
+                let _ = writeln!(out, "export function read{name}(r: SaveReader): {name} {{");
+                let _ = writeln!(out, "    const tag = {};", ts_read(tag));
+                out.push_str("    switch (tag) {\n");
+                for variant in variants {
+                    let fields = variant.fields.iter().map(|f| format!(", {}: {}", f.name, ts_read(&f.ty))).collect::<String>();
+                    let _ = writeln!(out, "        case {}: return {{ kind: \"{}\"{fields} }};", variant.id, variant.name);
+                }
+                let _ = writeln!(out, "        default: throw new Error(`Invalid tag ${{tag}} for {name}`);");
+                out.push_str("    }\n}\n\n");
+
+                let _ = writeln!(out, "export function write{name}(w: SaveWriter, value: {name}) {{");
+                out.push_str("    switch (value.kind) {\n");
+                for variant in variants {
+                    let _ = writeln!(out, "        case \"{}\":", variant.name);
+                    let _ = writeln!(out, "            ({})({});", ts_write(tag), variant.id);
+                    for field in &variant.fields {
+                        let _ = writeln!(out, "            ({})(value.{});", ts_write(&field.ty), field.name);
+                    }
+                    out.push_str("            break;\n");
+                }
+                out.push_str("    }\n}\n");
+            }
+        }
+    }
+
+    out
+}
@@ -0,0 +1,264 @@
+//! Saving with a string table, so repeated strings are written once and referenced by index.
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::save::strings::{load_with_string_table, save_with_string_table};
+//! use mvutils::save::Savable;
+//!
+//! let entities = vec![String::from("goblin"); 100];
+//!
+//! let mut plain = ByteBuffer::new();
+//! entities.save(&mut plain);
+//!
+//! let mut interned = ByteBuffer::new();
+//! save_with_string_table(&mut interned, &entities);
+//! assert!(interned.len() < plain.len() / 2);
+//!
+//! let loaded: Vec<String> = load_with_string_table(&mut interned).unwrap();
+//! assert_eq!(loaded, entities);
+//! ```
+
+use crate::save::{Loader, Savable, SaveError, Saver};
+use std::collections::HashMap;
+
+/// Save the value with all of its strings moved into a table that is written in front of it.
+pub fn save_with_string_table<T: Savable>(saver: &mut impl Saver, value: &T) {
+    value.save(&mut StringTableSaver::new(saver, value));
+}
+
+/// Load a value saved with [`save_with_string_table`].
+pub fn load_with_string_table<T: Savable>(loader: &mut impl Loader) -> Result<T, SaveError> {
+    T::load(&mut StringTableLoader::new(loader)?)
+}
+
+#[derive(Default)]
+struct StringCollector {
+    strings: Vec<String>,
+    indices: HashMap<String, u32>,
+    size: usize,
+}
+
+macro_rules! collect_primitives {
+    ($($t:ty, $push:ident),*) => {
+        $(
+            fn $push(&mut self, _: $t) {
+                self.size += std::mem::size_of::<$t>();
+            }
+        )*
+    };
+}
+
+impl Saver for StringCollector {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.size += bytes.len();
+    }
+
+    collect_primitives!(
+        bool, push_bool, u8, push_u8, u16, push_u16, u32, push_u32, u64, push_u64, i8, push_i8,
+        i16, push_i16, i32, push_i32, i64, push_i64, f32, push_f32, f64, push_f64
+    );
+
+    fn push_string(&mut self, value: &str) {
+        if !self.indices.contains_key(value) {
+            self.indices.insert(value.to_string(), self.strings.len() as u32);
+            self.strings.push(value.to_string());
+        }
+        self.size += 4;
+    }
+
+    fn bytes_written(&self) -> usize {
+        self.size
+    }
+
+    fn can_patch(&self) -> bool {
+        true
+    }
+
+    fn patch_u32(&mut self, _: usize, _: u32) {}
+}
+
+macro_rules! forward_push {
+    ($($t:ty, $push:ident),*) => {
+        $(
+            fn $push(&mut self, value: $t) {
+                self.inner.$push(value);
+            }
+        )*
+    };
+}
+
+/// A [`Saver`] that writes strings as indices into a table, which [`StringTableSaver::new`]
+/// writes to the inner saver first. Load the data with a [`StringTableLoader`].
+pub struct StringTableSaver<'a, S: Saver> {
+    inner: &'a mut S,
+    indices: HashMap<String, u32>,
+}
+
+impl<'a, S: Saver> StringTableSaver<'a, S> {
+    /// Collect the strings of the value and write them to the saver as a table. The value itself
+    /// still has to be saved into the returned saver.
+    pub fn new<T: Savable>(inner: &'a mut S, value: &T) -> Self {
+        let mut collector = StringCollector::default();
+        value.save(&mut collector);
+        collector.strings.save(inner);
+        StringTableSaver {
+            inner,
+            indices: collector.indices,
+        }
+    }
+
+    /// The amount of distinct strings in the table.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+impl<S: Saver> Saver for StringTableSaver<'_, S> {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.inner.push_bytes(bytes);
+    }
+
+    forward_push!(
+        bool, push_bool, u8, push_u8, u16, push_u16, u32, push_u32, u64, push_u64, i8, push_i8,
+        i16, push_i16, i32, push_i32, i64, push_i64, f32, push_f32, f64, push_f64
+    );
+
+    fn push_string(&mut self, value: &str) {
+        let index = *self
+            .indices
+            .get(value)
+            .unwrap_or_else(|| panic!("String {value:?} is not in the string table, the value changed while saving!"));
+        self.inner.push_u32(index);
+    }
+
+    fn bytes_written(&self) -> usize {
+        self.inner.bytes_written()
+    }
+
+    fn can_reserve(&self) -> bool {
+        self.inner.can_reserve()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.inner.is_deterministic()
+    }
+
+    fn can_patch(&self) -> bool {
+        self.inner.can_patch()
+    }
+
+    fn patch_u32(&mut self, position: usize, value: u32) {
+        self.inner.patch_u32(position, value);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        self.inner.begin_field(name);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {
+        self.inner.end_field();
+    }
+}
+
+macro_rules! forward_pop {
+    ($($t:ty, $pop:ident, $peek:ident),*) => {
+        $(
+            fn $pop(&mut self) -> Option<$t> {
+                self.inner.$pop()
+            }
+
+            fn $peek(&mut self) -> Option<$t> {
+                self.inner.$peek()
+            }
+        )*
+    };
+}
+
+/// A [`Loader`] for data saved with a [`StringTableSaver`], resolving string indices through the table.
+pub struct StringTableLoader<'a, L: Loader> {
+    inner: &'a mut L,
+    strings: Vec<String>,
+}
+
+impl<'a, L: Loader> StringTableLoader<'a, L> {
+    /// Read the string table from the loader.
+    pub fn new(inner: &'a mut L) -> Result<Self, SaveError> {
+        let strings = Vec::<String>::load(inner).map_err(|e| e.in_field("string table"))?;
+        Ok(StringTableLoader { inner, strings })
+    }
+
+    pub fn strings(&self) -> &[String] {
+        &self.strings
+    }
+}
+
+impl<L: Loader> Loader for StringTableLoader<'_, L> {
+    fn pop_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        self.inner.pop_bytes(amount)
+    }
+
+    fn pop_to_end(&mut self) -> Option<Vec<u8>> {
+        self.inner.pop_to_end()
+    }
+
+    fn pop_string(&mut self) -> Option<String> {
+        let index = self.inner.peek_u32()? as usize;
+        let string = self.strings.get(index)?.clone();
+        self.inner.pop_u32();
+        Some(string)
+    }
+
+    fn peek_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        self.inner.peek_bytes(amount)
+    }
+
+    forward_pop!(
+        bool, pop_bool, peek_bool, u8, pop_u8, peek_u8, u16, pop_u16, peek_u16, u32, pop_u32, peek_u32,
+        u64, pop_u64, peek_u64, i8, pop_i8, peek_i8, i16, pop_i16, peek_i16, i32, pop_i32, peek_i32,
+        i64, pop_i64, peek_i64, f32, pop_f32, peek_f32, f64, pop_f64, peek_f64
+    );
+
+    fn check_len(&mut self, len: u64) -> Result<(), SaveError> {
+        self.inner.check_len(len)
+    }
+
+    fn enter(&mut self) -> Result<(), SaveError> {
+        self.inner.enter()
+    }
+
+    fn exit(&mut self) {
+        self.inner.exit();
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        self.inner.begin_field(name);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {
+        self.inner.end_field();
+    }
+
+    fn position(&self) -> Result<usize, SaveError> {
+        self.inner.position()
+    }
+
+    fn seek(&mut self, position: usize) -> Result<(), SaveError> {
+        self.inner.seek(position)
+    }
+
+    fn skip(&mut self, amount: usize) -> Result<(), SaveError> {
+        self.inner.skip(amount)
+    }
+}
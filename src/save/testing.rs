@@ -0,0 +1,89 @@
+//! Helpers for testing [`Savable`] implementations, meant to be called from the tests of crates
+//! deriving [`Savable`].
+//!
+//! ```
+//! use mvutils::save::testing::{assert_round_trip, fuzz_load};
+//!
+//! let value = (42u32, String::from("hello"), vec![1.5f32, -2.0]);
+//! assert_round_trip(&value);
+//! fuzz_load(&value, 500);
+//! ```
+
+use crate::save::Savable;
+use bytebuffer::ByteBuffer;
+use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const DEFAULT_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Save the value, load it back and assert that the result is equal and that all saved bytes were read.
+pub fn assert_round_trip<T: Savable + PartialEq + Debug>(value: &T) {
+    let mut buffer = ByteBuffer::new();
+    value.save(&mut buffer);
+    let len = buffer.len();
+    let loaded = T::load(&mut buffer).unwrap_or_else(|e| panic!("Failed to load {value:?} after saving it: {e}"));
+    assert_eq!(&loaded, value, "Loaded value differs from the saved value");
+    assert_eq!(buffer.get_rpos(), len, "Loading {value:?} read {} of {len} saved bytes", buffer.get_rpos());
+}
+
+/// Save the value and load it from `iterations` randomly corrupted copies of the saved bytes, asserting
+/// that loading never panics. Errors are fine, as is loading a different value.
+pub fn fuzz_load<T: Savable>(value: &T, iterations: usize) {
+    fuzz_load_seeded(value, iterations, DEFAULT_SEED);
+}
+
+/// Like [`fuzz_load`], with a specific seed for the mutations.
+pub fn fuzz_load_seeded<T: Savable>(value: &T, iterations: usize, seed: u64) {
+    let mut buffer = ByteBuffer::new();
+    value.save(&mut buffer);
+    fuzz_bytes::<T>(buffer.as_bytes(), iterations, seed);
+}
+
+/// Load `T` from `iterations` randomly corrupted copies of the bytes, asserting that loading never panics.
+pub fn fuzz_bytes<T: Savable>(bytes: &[u8], iterations: usize, seed: u64) {
+    let mut rng = XorShift(seed | 1);
+    for iteration in 0..iterations {
+        let mut data = bytes.to_vec();
+        for _ in 0..=rng.below(4) {
+            mutate(&mut data, &mut rng);
+        }
+        let mut buffer = ByteBuffer::from_vec(data.clone());
+        if catch_unwind(AssertUnwindSafe(|| T::load(&mut buffer))).is_err() {
+            panic!(
+                "Loading {} panicked on iteration {iteration} (seed {seed:#x}) with the bytes {:02x?}",
+                std::any::type_name::<T>(),
+                data
+            );
+        }
+    }
+}
+
+fn mutate(data: &mut Vec<u8>, rng: &mut XorShift) {
+    let index = rng.below(data.len().max(1));
+    match rng.below(6) {
+        0 if !data.is_empty() => data[index] ^= 1 << rng.below(8),
+        1 if !data.is_empty() => data[index] = rng.next() as u8,
+        2 if !data.is_empty() => data[index] = if rng.below(2) == 0 { 0xFF } else { 0x00 },
+        3 => data.truncate(index),
+        4 => data.insert(index.min(data.len()), rng.next() as u8),
+        _ if !data.is_empty() => {
+            data.remove(index);
+        }
+        _ => data.push(rng.next() as u8),
+    }
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
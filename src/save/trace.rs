@@ -0,0 +1,328 @@
+//! Tracing which fields of derived [`Savable`](crate::save::Savable) types are saved to and loaded
+//! from which bytes, to find out where corrupted or incompatible data stops making sense.
+//!
+//! ```
+//! use bytebuffer::ByteBuffer;
+//! use mvutils::save::trace::{TracingLoader, TracingSaver};
+//! use mvutils::save::Savable;
+//! use mvutils::Savable;
+//!
+//! #[derive(Savable)]
+//! struct Player {
+//!     name: String,
+//!     position: (f32, f32),
+//! }
+//!
+//! let mut buffer = ByteBuffer::new();
+//! let mut saver = TracingSaver::new(&mut buffer);
+//! Player { name: "Steve".to_string(), position: (1.0, 2.0) }.save(&mut saver);
+//! let trace = saver.into_trace();
+//! assert_eq!(trace.entries()[1].path, "position");
+//! assert_eq!(trace.entries()[1].offset, 9);
+//!
+//! let mut bytes = ByteBuffer::from_bytes(&buffer.as_bytes()[..11]);
+//! let mut loader = TracingLoader::new(&mut bytes);
+//! assert!(Player::load(&mut loader).is_err());
+//! let failed = loader.trace().failed().unwrap().clone();
+//! assert_eq!(failed.path, "position");
+//! ```
+
+use crate::print::{Printer, Style};
+use crate::save::{Loader, SaveError, Saver};
+use std::fmt::{Display, Formatter};
+
+/// A field which was saved or loaded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceEntry {
+    /// The path of the field from the outermost traced value, like `inventory.items`.
+    pub path: String,
+    /// The nesting depth of the field, 0 for fields of the outermost value.
+    pub depth: usize,
+    /// The position of the first byte of the field.
+    pub offset: usize,
+    /// The number of bytes the field occupies, or consumed before loading it failed.
+    pub len: usize,
+    /// False if saving or loading the field did not finish, because loading it failed.
+    pub complete: bool,
+}
+
+impl TraceEntry {
+    fn name(&self) -> &str {
+        self.path.rsplit('.').next().unwrap_or(&self.path)
+    }
+}
+
+/// An ordered log of the fields saved or loaded by a [`TracingSaver`] or [`TracingLoader`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trace {
+    entries: Vec<TraceEntry>,
+}
+
+impl Trace {
+    /// All fields in the order they were started.
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// The innermost field which did not finish loading.
+    pub fn failed(&self) -> Option<&TraceEntry> {
+        self.entries.iter().filter(|e| !e.complete).max_by_key(|e| e.depth)
+    }
+
+    /// The innermost field containing the byte at the offset.
+    pub fn at(&self, offset: usize) -> Option<&TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|e| offset >= e.offset && offset < e.offset + e.len)
+            .max_by_key(|e| e.depth)
+    }
+
+    /// A printer with one line per field, indented by depth.
+    pub fn printer(&self) -> Printer {
+        self.entries.iter().fold(Printer::start(), |printer, e| {
+            let printer = printer
+                .style(Style::Muted, &format!("{:>10} {:>8} ", e.offset, e.len))
+                .text(&"  ".repeat(e.depth));
+            if e.complete {
+                printer.text_ln(e.name())
+            } else {
+                printer.style_ln(Style::Error, &format!("{} (incomplete)", e.name()))
+            }
+        })
+    }
+
+    pub fn print(&self) {
+        self.printer().flush();
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:>10} {:>8} field", "offset", "length")?;
+        for e in &self.entries {
+            write!(f, "{:>10} {:>8} {}{}", e.offset, e.len, "  ".repeat(e.depth), e.name())?;
+            if e.complete {
+                writeln!(f)?;
+            } else {
+                writeln!(f, " (incomplete)")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The fields which are currently being saved or loaded.
+#[derive(Default)]
+struct Tracer {
+    trace: Trace,
+    open: Vec<usize>,
+}
+
+impl Tracer {
+    fn begin(&mut self, name: &'static str, offset: usize) {
+        let path = match self.open.last() {
+            Some(parent) => format!("{}.{}", self.trace.entries[*parent].path, name),
+            None => name.to_string(),
+        };
+        self.open.push(self.trace.entries.len());
+        self.trace.entries.push(TraceEntry {
+            path,
+            depth: self.open.len() - 1,
+            offset,
+            len: 0,
+            complete: false,
+        });
+    }
+
+    fn end(&mut self, offset: usize) {
+        if let Some(index) = self.open.pop() {
+            let entry = &mut self.trace.entries[index];
+            entry.len = offset - entry.offset;
+            entry.complete = true;
+        }
+    }
+
+    /// The trace with the lengths of unfinished fields set to the bytes they consumed so far.
+    fn trace(&self, offset: usize) -> Trace {
+        let mut trace = self.trace.clone();
+        for index in &self.open {
+            let entry = &mut trace.entries[*index];
+            entry.len = offset.saturating_sub(entry.offset);
+        }
+        trace
+    }
+}
+
+macro_rules! forward_push {
+    ($($t:ty, $push:ident),*) => {
+        $(
+            fn $push(&mut self, value: $t) {
+                self.inner.$push(value);
+            }
+        )*
+    };
+}
+
+/// A [`Saver`] wrapper which records the fields of derived types as they are saved.
+pub struct TracingSaver<'a, S: Saver> {
+    inner: &'a mut S,
+    tracer: Tracer,
+}
+
+impl<'a, S: Saver> TracingSaver<'a, S> {
+    pub fn new(inner: &'a mut S) -> Self {
+        TracingSaver {
+            inner,
+            tracer: Tracer::default(),
+        }
+    }
+
+    pub fn trace(&self) -> Trace {
+        self.tracer.trace(self.inner.bytes_written())
+    }
+
+    pub fn into_trace(self) -> Trace {
+        self.trace()
+    }
+}
+
+impl<S: Saver> Saver for TracingSaver<'_, S> {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.inner.push_bytes(bytes);
+    }
+
+    forward_push!(
+        bool, push_bool, u8, push_u8, u16, push_u16, u32, push_u32, u64, push_u64, i8, push_i8,
+        i16, push_i16, i32, push_i32, i64, push_i64, f32, push_f32, f64, push_f64, &str, push_string
+    );
+
+    fn bytes_written(&self) -> usize {
+        self.inner.bytes_written()
+    }
+
+    fn can_reserve(&self) -> bool {
+        self.inner.can_reserve()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.inner.is_deterministic()
+    }
+
+    fn can_patch(&self) -> bool {
+        self.inner.can_patch()
+    }
+
+    fn patch_u32(&mut self, position: usize, value: u32) {
+        self.inner.patch_u32(position, value);
+    }
+
+    fn begin_field(&mut self, name: &'static str) {
+        self.tracer.begin(name, self.inner.bytes_written());
+        self.inner.begin_field(name);
+    }
+
+    fn end_field(&mut self) {
+        self.inner.end_field();
+        self.tracer.end(self.inner.bytes_written());
+    }
+}
+
+macro_rules! forward_pop {
+    ($($t:ty, $pop:ident, $peek:ident),*) => {
+        $(
+            fn $pop(&mut self) -> Option<$t> {
+                self.inner.$pop()
+            }
+
+            fn $peek(&mut self) -> Option<$t> {
+                self.inner.$peek()
+            }
+        )*
+    };
+}
+
+/// A [`Loader`] wrapper which records the fields of derived types as they are loaded. If loading
+/// fails, the fields which did not finish loading are marked as incomplete.
+pub struct TracingLoader<'a, L: Loader> {
+    inner: &'a mut L,
+    tracer: Tracer,
+}
+
+impl<'a, L: Loader> TracingLoader<'a, L> {
+    pub fn new(inner: &'a mut L) -> Self {
+        TracingLoader {
+            inner,
+            tracer: Tracer::default(),
+        }
+    }
+
+    pub fn trace(&self) -> Trace {
+        self.tracer.trace(self.inner.position().unwrap_or(0))
+    }
+
+    pub fn into_trace(self) -> Trace {
+        self.trace()
+    }
+}
+
+impl<L: Loader> Loader for TracingLoader<'_, L> {
+    fn pop_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        self.inner.pop_bytes(amount)
+    }
+
+    fn pop_to_end(&mut self) -> Option<Vec<u8>> {
+        self.inner.pop_to_end()
+    }
+
+    fn pop_string(&mut self) -> Option<String> {
+        self.inner.pop_string()
+    }
+
+    fn peek_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        self.inner.peek_bytes(amount)
+    }
+
+    forward_pop!(
+        bool, pop_bool, peek_bool, u8, pop_u8, peek_u8, u16, pop_u16, peek_u16, u32, pop_u32, peek_u32,
+        u64, pop_u64, peek_u64, i8, pop_i8, peek_i8, i16, pop_i16, peek_i16, i32, pop_i32, peek_i32,
+        i64, pop_i64, peek_i64, f32, pop_f32, peek_f32, f64, pop_f64, peek_f64
+    );
+
+    fn check_len(&mut self, len: u64) -> Result<(), SaveError> {
+        self.inner.check_len(len)
+    }
+
+    fn enter(&mut self) -> Result<(), SaveError> {
+        self.inner.enter()
+    }
+
+    fn exit(&mut self) {
+        self.inner.exit();
+    }
+
+    fn position(&self) -> Result<usize, SaveError> {
+        self.inner.position()
+    }
+
+    fn seek(&mut self, position: usize) -> Result<(), SaveError> {
+        self.inner.seek(position)
+    }
+
+    fn skip(&mut self, amount: usize) -> Result<(), SaveError> {
+        self.inner.skip(amount)
+    }
+
+    fn begin_field(&mut self, name: &'static str) {
+        self.tracer.begin(name, self.inner.position().unwrap_or(0));
+        self.inner.begin_field(name);
+    }
+
+    fn end_field(&mut self) {
+        self.inner.end_field();
+        self.tracer.end(self.inner.position().unwrap_or(0));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::save::{Loader, Savable, SaveError};
+use std::mem::size_of;
+
+/// Limits enforced by a [`ValidatingLoader`].
+#[derive(Clone, Debug)]
+pub struct LoadLimits {
+    /// The maximum amount of elements in a single collection.
+    pub max_collection_len: u64,
+    /// The maximum length of a single string in bytes.
+    pub max_string_len: usize,
+    /// The maximum nesting depth of collections and boxes.
+    pub max_depth: usize,
+    /// The maximum amount of bytes read in total.
+    pub max_bytes: u64,
+}
+
+impl Default for LoadLimits {
+    fn default() -> Self {
+        LoadLimits {
+            max_collection_len: 1 << 20,
+            max_string_len: 1 << 20,
+            max_depth: 64,
+            max_bytes: 64 << 20,
+        }
+    }
+}
+
+/// Wraps a [`Loader`] to load untrusted data, for example packets received over the network, with
+/// limits on collection lengths, string sizes, nesting depth and the total amount of bytes read.
+///
+/// Strings are checked by peeking their `u32` length prefix, which matches the [`ByteBuffer`](bytebuffer::ByteBuffer)
+/// string encoding.
+///
+/// ```
+/// use bytebuffer::ByteBuffer;
+/// use mvutils::save::Savable;
+/// use mvutils::save::validate::{LoadLimits, ValidatingLoader};
+///
+/// let mut buffer = ByteBuffer::new();
+/// u64::MAX.save(&mut buffer);
+///
+/// let mut loader = ValidatingLoader::new(&mut buffer, LoadLimits::default());
+/// let error = loader.load::<Vec<u8>>().unwrap_err();
+/// assert!(error.to_string().contains("exceeds the limit"));
+/// ```
+pub struct ValidatingLoader<'a, L: Loader> {
+    inner: &'a mut L,
+    limits: LoadLimits,
+    depth: usize,
+    bytes: u64,
+    error: Option<String>,
+}
+
+macro_rules! validated_primitives {
+    ($($t:ty, $pop:ident, $peek:ident),*) => {
+        $(
+            fn $pop(&mut self) -> Option<$t> {
+                if self.consume(size_of::<$t>() as u64) {
+                    self.inner.$pop()
+                } else {
+                    None
+                }
+            }
+
+            fn $peek(&mut self) -> Option<$t> {
+                self.inner.$peek()
+            }
+        )*
+    };
+}
+
+impl<'a, L: Loader> ValidatingLoader<'a, L> {
+    pub fn new(inner: &'a mut L, limits: LoadLimits) -> Self {
+        ValidatingLoader {
+            inner,
+            limits,
+            depth: 0,
+            bytes: 0,
+            error: None,
+        }
+    }
+
+    pub fn limits(&self) -> &LoadLimits {
+        &self.limits
+    }
+
+    /// The amount of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The limit that was violated, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Load a value, returning a description of the violated limit if loading failed because of one.
+    pub fn load<T: Savable>(&mut self) -> Result<T, SaveError> {
+        T::load(self).map_err(|e| self.error.take().map_or(e, SaveError::Custom))
+    }
+
+    /// Load a value and check it using the validator, for example to reject out of range values.
+    pub fn load_validated<T: Savable>(&mut self, validator: impl FnOnce(&T) -> Result<(), String>) -> Result<T, SaveError> {
+        let value = self.load()?;
+        validator(&value)?;
+        Ok(value)
+    }
+
+    fn fail(&mut self, error: String) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    fn consume(&mut self, amount: u64) -> bool {
+        self.bytes = self.bytes.saturating_add(amount);
+        if self.bytes > self.limits.max_bytes {
+            self.fail(format!("Read {} bytes, which exceeds the limit of {} bytes", self.bytes, self.limits.max_bytes));
+            false
+        } else {
+            true
+        }
+    }
+}
+
+impl<L: Loader> Loader for ValidatingLoader<'_, L> {
+    fn pop_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        if self.consume(amount as u64) {
+            self.inner.pop_bytes(amount)
+        } else {
+            None
+        }
+    }
+
+    fn pop_to_end(&mut self) -> Option<Vec<u8>> {
+        let bytes = self.inner.pop_to_end()?;
+        if self.consume(bytes.len() as u64) {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    fn pop_string(&mut self) -> Option<String> {
+        let len = self.inner.peek_u32()? as usize;
+        if len > self.limits.max_string_len {
+            self.fail(format!("String length {} exceeds the limit of {}", len, self.limits.max_string_len));
+            return None;
+        }
+        if self.consume(4 + len as u64) {
+            self.inner.pop_string()
+        } else {
+            None
+        }
+    }
+
+    fn peek_bytes(&mut self, amount: usize) -> Option<Vec<u8>> {
+        self.inner.peek_bytes(amount)
+    }
+
+    validated_primitives!(
+        bool, pop_bool, peek_bool, u8, pop_u8, peek_u8, u16, pop_u16, peek_u16, u32, pop_u32, peek_u32,
+        u64, pop_u64, peek_u64, i8, pop_i8, peek_i8, i16, pop_i16, peek_i16, i32, pop_i32, peek_i32,
+        i64, pop_i64, peek_i64, f32, pop_f32, peek_f32, f64, pop_f64, peek_f64
+    );
+
+    fn check_len(&mut self, len: u64) -> Result<(), SaveError> {
+        if len > self.limits.max_collection_len {
+            return Err(SaveError::Custom(format!("Collection length {} exceeds the limit of {}", len, self.limits.max_collection_len)));
+        }
+        self.inner.check_len(len)
+    }
+
+    fn enter(&mut self) -> Result<(), SaveError> {
+        self.depth += 1;
+        if self.depth > self.limits.max_depth {
+            self.depth -= 1;
+            return Err(SaveError::Custom(format!("Nesting depth exceeds the limit of {}", self.limits.max_depth)));
+        }
+        self.inner.enter()
+    }
+
+    fn exit(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.inner.exit();
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn begin_field(&mut self, name: &'static str) {
+        self.inner.begin_field(name);
+    }
+
+    #[cfg(feature = "save_trace")]
+    fn end_field(&mut self) {
+        self.inner.end_field();
+    }
+
+    fn position(&self) -> Result<usize, SaveError> {
+        self.inner.position()
+    }
+
+    fn seek(&mut self, position: usize) -> Result<(), SaveError> {
+        self.inner.seek(position)
+    }
+
+    fn skip(&mut self, amount: usize) -> Result<(), SaveError> {
+        if !self.consume(amount as u64) {
+            return Err(SaveError::Custom(self.error.take().unwrap_or_default()));
+        }
+        self.inner.skip(amount)
+    }
+}
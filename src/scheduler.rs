@@ -0,0 +1,196 @@
+use crate::platform::Instant;
+use hashbrown::HashMap;
+use crate::sync::Mutex;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+/// Identifies a job submitted to a [`JobScheduler`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct JobId(u64);
+
+/// What happened during a call to [`JobScheduler::run_for`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RunStats {
+    pub executed: usize,
+    /// Jobs discarded because their deadline passed before they could run.
+    pub expired: usize,
+    /// Jobs still waiting after the call.
+    pub remaining: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Eq, PartialEq)]
+struct Entry {
+    priority: Priority,
+    deadline: Option<Instant>,
+    id: u64,
+}
+
+impl Ord for Entry {
+    /// Higher priorities first, then earlier deadlines, then in submission order.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let deadline = |e: &Entry| Reverse(e.deadline.map_or((1, None), |d| (0, Some(d))));
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| deadline(self).cmp(&deadline(other)))
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct Queue {
+    heap: BinaryHeap<Entry>,
+    jobs: HashMap<u64, (Option<Instant>, Job)>,
+    next_id: u64,
+    average: Option<Duration>,
+}
+
+/// A queue of prioritized jobs that are executed within a time budget, usually once per frame.
+///
+/// Jobs can be submitted from any thread, including from within other jobs. Jobs with a deadline
+/// are discarded if they did not start before it.
+///
+/// ```
+/// use mvutils::scheduler::{JobScheduler, Priority};
+/// use std::time::Duration;
+///
+/// let scheduler = JobScheduler::new();
+/// scheduler.submit(Priority::High, || println!("Upload texture"));
+/// scheduler.submit(Priority::Low, || println!("Rebuild cache"));
+///
+/// // In the game loop:
+/// let stats = scheduler.run_for(Duration::from_millis(2));
+/// assert_eq!(stats.executed, 2);
+/// ```
+pub struct JobScheduler {
+    queue: Mutex<Queue>,
+}
+
+impl JobScheduler {
+    pub fn new() -> Self {
+        JobScheduler {
+            queue: Mutex::new(Queue {
+                heap: BinaryHeap::new(),
+                jobs: HashMap::new(),
+                next_id: 0,
+                average: None,
+            }),
+        }
+    }
+
+    pub fn submit(&self, priority: Priority, job: impl FnOnce() + Send + 'static) -> JobId {
+        self.push(priority, None, Box::new(job))
+    }
+
+    /// Submit a job which is discarded if it did not start before the deadline.
+    pub fn submit_with_deadline(&self, priority: Priority, deadline: Instant, job: impl FnOnce() + Send + 'static) -> JobId {
+        self.push(priority, Some(deadline), Box::new(job))
+    }
+
+    fn push(&self, priority: Priority, deadline: Option<Instant>, job: Job) -> JobId {
+        let mut queue = self.queue.lock();
+        let id = queue.next_id;
+        queue.next_id += 1;
+        queue.heap.push(Entry { priority, deadline, id });
+        queue.jobs.insert(id, (deadline, job));
+        JobId(id)
+    }
+
+    /// Remove a job which did not start yet, returning whether it was found.
+    pub fn cancel(&self, job: JobId) -> bool {
+        self.queue.lock().jobs.remove(&job.0).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.lock().jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().jobs.is_empty()
+    }
+
+    /// The moving average of the duration of the executed jobs.
+    pub fn average_job_time(&self) -> Option<Duration> {
+        self.queue.lock().average
+    }
+
+    fn pop(&self, now: Instant, expired: &mut usize) -> Option<Job> {
+        let mut queue = self.queue.lock();
+        while let Some(entry) = queue.heap.pop() {
+            match queue.jobs.remove(&entry.id) {
+                Some((Some(deadline), _)) if deadline < now => *expired += 1,
+                Some((_, job)) => return Some(job),
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// Execute jobs in order of priority until the budget is used up, deferring the rest to the next
+    /// call. A job is only started if the average job time suggests it fits in the remaining budget,
+    /// except for the first job of every call, so that the queue always makes progress.
+    pub fn run_for(&self, budget: Duration) -> RunStats {
+        let start = Instant::now();
+        let mut stats = RunStats::default();
+        loop {
+            let elapsed = start.elapsed();
+            if stats.executed > 0 {
+                let estimate = self.average_job_time().unwrap_or_default();
+                if elapsed + estimate > budget {
+                    break;
+                }
+            }
+            let Some(job) = self.pop(Instant::now(), &mut stats.expired) else {
+                break;
+            };
+            let job_start = Instant::now();
+            job();
+            let took = job_start.elapsed();
+            let mut queue = self.queue.lock();
+            queue.average = Some(match queue.average {
+                Some(average) => (average * 7 + took) / 8,
+                None => took,
+            });
+            drop(queue);
+            stats.executed += 1;
+        }
+        stats.elapsed = start.elapsed();
+        stats.remaining = self.len();
+        stats
+    }
+
+    /// Execute all jobs, including jobs submitted while running.
+    pub fn run_all(&self) -> RunStats {
+        self.run_for(Duration::MAX)
+    }
+
+    /// Discard all jobs.
+    pub fn clear(&self) {
+        let mut queue = self.queue.lock();
+        queue.heap.clear();
+        queue.jobs.clear();
+    }
+}
+
+impl Default for JobScheduler {
+    fn default() -> Self {
+        JobScheduler::new()
+    }
+}
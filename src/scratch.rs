@@ -0,0 +1,380 @@
+//! A thread local bump allocator for temporary data, which is freed all at once at the end of a
+//! [`scope`].
+//!
+//! ```
+//! use mvutils::scratch;
+//!
+//! let len = scratch::scope(|s| {
+//!     let name = s.format(format_args!("{}-{}", "frame", 42));
+//!     let numbers = s.alloc_slice(&[1, 2, 3]);
+//!     numbers[0] = 10;
+//!     name.len() + numbers.iter().sum::<i32>() as usize
+//! });
+//! assert_eq!(len, 8 + 15);
+//! ```
+
+use std::alloc::Layout;
+use std::cell::UnsafeCell;
+use std::fmt::{Arguments, Debug, Display, Formatter, Write};
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+const MIN_CHUNK: usize = 4096;
+
+#[derive(Copy, Clone)]
+struct Mark {
+    chunk: usize,
+    offset: usize,
+}
+
+/// A chunk of memory owned through a raw pointer, so handing out pointers into it never requires a
+/// reference to the whole chunk, which would invalidate references allocated from it earlier.
+struct Chunk {
+    base: NonNull<u8>,
+    len: usize,
+}
+
+impl Chunk {
+    fn new(len: usize) -> Self {
+        let chunk = Box::into_raw(vec![0u8; len].into_boxed_slice());
+        Chunk {
+            base: unsafe { NonNull::new_unchecked(chunk as *mut u8) },
+            len,
+        }
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(self.base.as_ptr(), self.len))) };
+    }
+}
+
+struct Arena {
+    chunks: Vec<Chunk>,
+    current: usize,
+    offset: usize,
+    depth: usize,
+    allocated: usize,
+}
+
+impl Arena {
+    fn mark(&self) -> Mark {
+        Mark {
+            chunk: self.current,
+            offset: self.offset,
+        }
+    }
+
+    fn alloc(&mut self, layout: Layout) -> NonNull<u8> {
+        if layout.size() == 0 {
+            return unsafe { NonNull::new_unchecked(layout.align() as *mut u8) };
+        }
+        loop {
+            if let Some(chunk) = self.chunks.get(self.current) {
+                let base = chunk.base.as_ptr() as usize;
+                let start = (base + self.offset).next_multiple_of(layout.align()) - base;
+                if start + layout.size() <= chunk.len {
+                    self.offset = start + layout.size();
+                    self.allocated += layout.size();
+                    return unsafe { NonNull::new_unchecked(chunk.base.as_ptr().add(start)) };
+                }
+                if self.current + 1 < self.chunks.len() && self.chunks[self.current + 1].len >= layout.size() + layout.align() {
+                    self.current += 1;
+                    self.offset = 0;
+                    continue;
+                }
+            }
+            let size = (layout.size() + layout.align())
+                .max(self.chunks.last().map_or(MIN_CHUNK, |c| c.len * 2));
+            let index = (self.current + 1).min(self.chunks.len());
+            self.chunks.insert(index, Chunk::new(size));
+            self.current = index;
+            self.offset = 0;
+        }
+    }
+
+    fn reset(&mut self, mark: Mark, allocated: usize) {
+        self.current = mark.chunk;
+        self.offset = mark.offset;
+        self.allocated = allocated;
+    }
+}
+
+thread_local! {
+    static ARENA: UnsafeCell<Arena> = const {
+        UnsafeCell::new(Arena {
+            chunks: Vec::new(),
+            current: 0,
+            offset: 0,
+            depth: 0,
+            allocated: 0,
+        })
+    };
+}
+
+fn with_arena<R>(f: impl FnOnce(&mut Arena) -> R) -> R {
+    // The arena is only accessed for the duration of the closure and never reentrantly.
+    ARENA.with(|arena| f(unsafe { &mut *arena.get() }))
+}
+
+/// Allocates from the thread local scratch region, see [`scope`].
+pub struct Scratch {
+    depth: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+struct ResetGuard {
+    mark: Mark,
+    allocated: usize,
+}
+
+impl Drop for ResetGuard {
+    fn drop(&mut self) {
+        with_arena(|arena| {
+            arena.depth -= 1;
+            arena.reset(self.mark, self.allocated);
+        });
+    }
+}
+
+/// Run the function with a [`Scratch`] allocator. Everything allocated in it is freed when the
+/// function returns. Scopes can be nested, but only the innermost scope can be allocated from.
+pub fn scope<R>(f: impl FnOnce(&Scratch) -> R) -> R {
+    let (mark, allocated, depth) = with_arena(|arena| {
+        arena.depth += 1;
+        (arena.mark(), arena.allocated, arena.depth)
+    });
+    let _guard = ResetGuard { mark, allocated };
+    f(&Scratch {
+        depth,
+        _not_send: PhantomData,
+    })
+}
+
+impl Scratch {
+    fn raw_alloc(&self, layout: Layout) -> NonNull<u8> {
+        with_arena(|arena| {
+            assert_eq!(arena.depth, self.depth, "Allocating from a Scratch while a nested scope is active!");
+            arena.alloc(layout)
+        })
+    }
+
+    fn alloc_array<T: Copy>(&self, len: usize) -> *mut T {
+        let layout = Layout::array::<T>(len).expect("Scratch allocation too large");
+        self.raw_alloc(layout).as_ptr() as *mut T
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_array::<T>(1);
+        unsafe {
+            ptr.write(value);
+            &mut *ptr
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Copy>(&self, values: &[T]) -> &mut [T] {
+        let ptr = self.alloc_array::<T>(values.len());
+        unsafe {
+            ptr.copy_from_nonoverlapping(values.as_ptr(), values.len());
+            std::slice::from_raw_parts_mut(ptr, values.len())
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_fill<T: Copy>(&self, len: usize, value: T) -> &mut [T] {
+        let ptr = self.alloc_array::<T>(len);
+        unsafe {
+            for i in 0..len {
+                ptr.add(i).write(value);
+            }
+            std::slice::from_raw_parts_mut(ptr, len)
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, s: &str) -> &mut str {
+        unsafe { std::str::from_utf8_unchecked_mut(self.alloc_slice(s.as_bytes())) }
+    }
+
+    /// Format the arguments into the scratch region.
+    pub fn format(&self, args: Arguments) -> &str {
+        let mut s = self.string();
+        s.write_fmt(args).unwrap();
+        s.into_str()
+    }
+
+    /// A growable vector in the scratch region. Growing leaves the previous storage unused until the
+    /// end of the scope.
+    pub fn vec<T: Copy>(&self) -> ScratchVec<'_, T> {
+        ScratchVec {
+            scratch: self,
+            ptr: NonNull::dangling().as_ptr(),
+            len: 0,
+            capacity: if size_of::<T>() == 0 { usize::MAX } else { 0 },
+        }
+    }
+
+    pub fn vec_with_capacity<T: Copy>(&self, capacity: usize) -> ScratchVec<'_, T> {
+        let mut vec = self.vec();
+        vec.reserve(capacity);
+        vec
+    }
+
+    pub fn string(&self) -> ScratchString<'_> {
+        ScratchString { bytes: self.vec() }
+    }
+
+    /// The amount of bytes allocated in this scope and all scopes it is nested in.
+    pub fn allocated(&self) -> usize {
+        with_arena(|arena| arena.allocated)
+    }
+}
+
+/// A growable vector allocated in a [`Scratch`] region.
+pub struct ScratchVec<'s, T: Copy> {
+    scratch: &'s Scratch,
+    ptr: *mut T,
+    len: usize,
+    capacity: usize,
+}
+
+impl<'s, T: Copy> ScratchVec<'s, T> {
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("ScratchVec capacity overflow");
+        if required <= self.capacity {
+            return;
+        }
+        let capacity = required.max(self.capacity * 2).max(4);
+        let ptr = self.scratch.alloc_array::<T>(capacity);
+        unsafe { ptr.copy_from_nonoverlapping(self.ptr, self.len) };
+        self.ptr = ptr;
+        self.capacity = capacity;
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        unsafe { self.ptr.add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.ptr.add(self.len).read() })
+    }
+
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        self.reserve(values.len());
+        unsafe { self.ptr.add(self.len).copy_from_nonoverlapping(values.as_ptr(), values.len()) };
+        self.len += values.len();
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// The elements as a slice that lives until the end of the scope.
+    pub fn into_slice(self) -> &'s mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<T: Copy> Deref for ScratchVec<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<T: Copy> DerefMut for ScratchVec<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<T: Copy> Extend<T> for ScratchVec<'_, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Copy + Debug> Debug for ScratchVec<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A growable string allocated in a [`Scratch`] region.
+pub struct ScratchString<'s> {
+    bytes: ScratchVec<'s, u8>,
+}
+
+impl<'s> ScratchString<'s> {
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    pub fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(&self.bytes) }
+    }
+
+    /// The string as a str that lives until the end of the scope.
+    pub fn into_str(self) -> &'s mut str {
+        unsafe { std::str::from_utf8_unchecked_mut(self.bytes.into_slice()) }
+    }
+}
+
+impl Deref for ScratchString<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl Write for ScratchString<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl Display for ScratchString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl Debug for ScratchString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,194 @@
+use crate as mvutils;
+use mvutils_proc_macro::{try_from_string, Savable};
+use std::fmt::{Display, Formatter};
+use std::ops::{Mul, Neg};
+use std::str::FromStr;
+
+/// The DPI of a standard desktop display, at which one CSS pixel equals one device pixel.
+pub const DEFAULT_DPI: f32 = 96.0;
+
+/// A unit of length on a screen.
+#[try_from_string]
+#[derive(Savable, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Measurement {
+    Px,
+    Mm,
+    Cm,
+    In,
+    Pt,
+    Pc,
+    /// A percentage of the size of the parent.
+    Percent,
+}
+
+impl Measurement {
+    /// The number of pixels per unit at the given DPI and parent size.
+    pub fn pixels(self, dpi: f32, parent: f32) -> f32 {
+        match self {
+            Measurement::Px => 1.0,
+            Measurement::Mm => dpi / 25.4,
+            Measurement::Cm => dpi / 2.54,
+            Measurement::In => dpi,
+            Measurement::Pt => dpi / 72.0,
+            Measurement::Pc => dpi / 6.0,
+            Measurement::Percent => parent / 100.0,
+        }
+    }
+
+    /// Convert a value in this unit to pixels.
+    pub fn compute(self, value: f32, dpi: f32, parent: f32) -> f32 {
+        value * self.pixels(dpi, parent)
+    }
+
+    /// The suffix used when parsing and formatting lengths.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Measurement::Px => "px",
+            Measurement::Mm => "mm",
+            Measurement::Cm => "cm",
+            Measurement::In => "in",
+            Measurement::Pt => "pt",
+            Measurement::Pc => "pc",
+            Measurement::Percent => "%",
+        }
+    }
+}
+
+/// A value paired with its [`Measurement`], like `12px` or `2.5cm`.
+///
+/// Resolving a length to pixels requires the DPI of the screen and, for percentages, the size of
+/// the parent in pixels. Lengths without a unit are parsed as pixels.
+#[derive(Savable, Copy, Clone, Debug, PartialEq)]
+pub struct Length {
+    pub value: f32,
+    pub unit: Measurement,
+}
+
+impl Length {
+    pub const ZERO: Length = Length::px(0.0);
+
+    pub const fn new(value: f32, unit: Measurement) -> Self {
+        Length { value, unit }
+    }
+
+    pub const fn px(value: f32) -> Self {
+        Length::new(value, Measurement::Px)
+    }
+
+    pub const fn mm(value: f32) -> Self {
+        Length::new(value, Measurement::Mm)
+    }
+
+    pub const fn cm(value: f32) -> Self {
+        Length::new(value, Measurement::Cm)
+    }
+
+    pub const fn inches(value: f32) -> Self {
+        Length::new(value, Measurement::In)
+    }
+
+    pub const fn pt(value: f32) -> Self {
+        Length::new(value, Measurement::Pt)
+    }
+
+    pub const fn percent(value: f32) -> Self {
+        Length::new(value, Measurement::Percent)
+    }
+
+    pub fn to_px(self, dpi: f32, parent: f32) -> f32 {
+        self.unit.compute(self.value, dpi, parent)
+    }
+
+    /// Convert the length to another unit.
+    pub fn to(self, unit: Measurement, dpi: f32, parent: f32) -> Length {
+        if unit == self.unit {
+            return self;
+        }
+        Length::new(self.to_px(dpi, parent) / unit.pixels(dpi, parent), unit)
+    }
+
+    /// Add both lengths, returning the result in the unit of this length.
+    pub fn add(self, other: Length, dpi: f32, parent: f32) -> Length {
+        Length::new(self.value + other.to(self.unit, dpi, parent).value, self.unit)
+    }
+
+    /// Subtract the other length, returning the result in the unit of this length.
+    pub fn sub(self, other: Length, dpi: f32, parent: f32) -> Length {
+        Length::new(self.value - other.to(self.unit, dpi, parent).value, self.unit)
+    }
+
+    pub fn min(self, other: Length, dpi: f32, parent: f32) -> Length {
+        if self.to_px(dpi, parent) <= other.to_px(dpi, parent) {
+            self
+        } else {
+            other
+        }
+    }
+
+    pub fn max(self, other: Length, dpi: f32, parent: f32) -> Length {
+        if self.to_px(dpi, parent) >= other.to_px(dpi, parent) {
+            self
+        } else {
+            other
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Length, String> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+            .unwrap_or(s.len());
+        let (value, unit) = s.split_at(split);
+        let value = value.parse::<f32>().map_err(|_| format!("Invalid length value in '{s}'!"))?;
+        let unit = match unit.trim() {
+            "" => Measurement::Px,
+            "%" => Measurement::Percent,
+            unit => Measurement::try_from(unit.to_ascii_lowercase()).map_err(|_| format!("Unknown unit '{unit}' in length '{s}'!"))?,
+        };
+        Ok(Length::new(value, unit))
+    }
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::ZERO
+    }
+}
+
+impl Mul<f32> for Length {
+    type Output = Length;
+
+    fn mul(self, rhs: f32) -> Length {
+        Length::new(self.value * rhs, self.unit)
+    }
+}
+
+impl Neg for Length {
+    type Output = Length;
+
+    fn neg(self) -> Length {
+        Length::new(-self.value, self.unit)
+    }
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Length::parse(s)
+    }
+}
+
+impl TryFrom<String> for Length {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Length::parse(&value)
+    }
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.suffix())
+    }
+}
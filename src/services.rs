@@ -0,0 +1,166 @@
+//! A registry of services, which are shared instances looked up by their type.
+//!
+//! Unlike globals created with [`lazy!`](crate::lazy), services can be replaced, so tests can swap
+//! in their own implementation with [`ServiceRegistry::override_with`].
+//!
+//! ```
+//! use mvutils::services::ServiceRegistry;
+//! use std::sync::Arc;
+//!
+//! struct Config {
+//!     name: String,
+//! }
+//!
+//! struct Greeter {
+//!     config: Arc<Config>,
+//! }
+//!
+//! let services = ServiceRegistry::global();
+//! services.register(Config { name: "world".to_string() });
+//! services.provide(|| Greeter { config: ServiceRegistry::global().require() });
+//!
+//! assert_eq!(services.require::<Greeter>().config.name, "world");
+//!
+//! let guard = services.override_with(Config { name: "test".to_string() });
+//! assert_eq!(services.require::<Config>().name, "test");
+//! drop(guard);
+//! assert_eq!(services.require::<Config>().name, "world");
+//! ```
+
+use crate::lazy;
+use crate::sync::RwLock;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+type Instance = Arc<dyn Any + Send + Sync>;
+type Provider = Arc<dyn Fn() -> Instance + Send + Sync>;
+
+enum Service {
+    Ready(Instance),
+    Lazy(Provider),
+}
+
+#[derive(Default)]
+struct Services {
+    services: HashMap<TypeId, Service>,
+    overrides: HashMap<TypeId, Vec<(u64, Instance)>>,
+    next_override: u64,
+}
+
+lazy! {
+    static GLOBAL_SERVICES: ServiceRegistry = ServiceRegistry::new();
+}
+
+/// A thread safe map from types to shared instances of them.
+#[derive(Default)]
+pub struct ServiceRegistry {
+    inner: RwLock<Services>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        ServiceRegistry::default()
+    }
+
+    pub fn global() -> &'static ServiceRegistry {
+        &GLOBAL_SERVICES
+    }
+
+    /// Register an instance, replacing any previously registered instance or provider of the type.
+    pub fn register<T: Send + Sync + 'static>(&self, service: T) {
+        self.register_arc(Arc::new(service));
+    }
+
+    pub fn register_arc<T: Send + Sync + 'static>(&self, service: Arc<T>) {
+        self.inner.write().services.insert(TypeId::of::<T>(), Service::Ready(service));
+    }
+
+    /// Register a provider which creates the instance the first time it is requested. The provider
+    /// is called without holding any locks, so it can request other services.
+    pub fn provide<T: Send + Sync + 'static>(&self, provider: impl Fn() -> T + Send + Sync + 'static) {
+        let provider: Provider = Arc::new(move || Arc::new(provider()) as Instance);
+        self.inner.write().services.insert(TypeId::of::<T>(), Service::Lazy(provider));
+    }
+
+    /// Whether an instance or provider of the type was registered, or it is overridden.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        let inner = self.inner.read();
+        let id = TypeId::of::<T>();
+        inner.services.contains_key(&id) || inner.overrides.contains_key(&id)
+    }
+
+    /// Remove the registered instance or provider of the type, returning whether there was one.
+    /// Overrides stay active.
+    pub fn remove<T: Send + Sync + 'static>(&self) -> bool {
+        self.inner.write().services.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    /// The instance of the type, creating it if it was registered with a provider.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let id = TypeId::of::<T>();
+        let provider = {
+            let inner = self.inner.read();
+            if let Some((_, instance)) = inner.overrides.get(&id).and_then(|o| o.last()) {
+                return Some(downcast(instance.clone()));
+            }
+            match inner.services.get(&id)? {
+                Service::Ready(instance) => return Some(downcast(instance.clone())),
+                Service::Lazy(provider) => provider.clone(),
+            }
+        };
+
+        let created = provider();
+        let mut inner = self.inner.write();
+        let instance = match inner.services.get(&id) {
+            Some(Service::Lazy(current)) if Arc::ptr_eq(current, &provider) => {
+                inner.services.insert(id, Service::Ready(created.clone()));
+                created
+            }
+            // Another thread created it first, or it was replaced while the provider was running.
+            Some(Service::Ready(instance)) => instance.clone(),
+            _ => created,
+        };
+        Some(downcast(instance))
+    }
+
+    /// The instance of the type, panicking if none was registered.
+    pub fn require<T: Send + Sync + 'static>(&self) -> Arc<T> {
+        self.get().unwrap_or_else(|| panic!("No service of type {} was registered!", type_name::<T>()))
+    }
+
+    /// Use another instance of the type until the returned guard is dropped. Overrides can be
+    /// nested, the most recent one is used.
+    pub fn override_with<T: Send + Sync + 'static>(&self, service: T) -> ServiceOverride<'_> {
+        let mut inner = self.inner.write();
+        let key = inner.next_override;
+        inner.next_override += 1;
+        let id = TypeId::of::<T>();
+        inner.overrides.entry(id).or_default().push((key, Arc::new(service)));
+        ServiceOverride { registry: self, id, key }
+    }
+}
+
+fn downcast<T: Send + Sync + 'static>(instance: Instance) -> Arc<T> {
+    instance.downcast().unwrap_or_else(|_| unreachable!())
+}
+
+/// Removes an override created with [`ServiceRegistry::override_with`] when dropped.
+#[must_use = "the override is removed when the guard is dropped"]
+pub struct ServiceOverride<'a> {
+    registry: &'a ServiceRegistry,
+    id: TypeId,
+    key: u64,
+}
+
+impl Drop for ServiceOverride<'_> {
+    fn drop(&mut self) {
+        let mut inner = self.registry.inner.write();
+        if let Some(overrides) = inner.overrides.get_mut(&self.id) {
+            overrides.retain(|(key, _)| *key != self.key);
+            if overrides.is_empty() {
+                inner.overrides.remove(&self.id);
+            }
+        }
+    }
+}
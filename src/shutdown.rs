@@ -0,0 +1,203 @@
+//! Graceful shutdown on Ctrl-C and SIGTERM.
+//!
+//! ```no_run
+//! use mvutils::shutdown;
+//!
+//! shutdown::install();
+//! shutdown::on_shutdown(|| println!("Saving state"));
+//!
+//! let token = shutdown::token();
+//! std::thread::spawn(move || {
+//!     while !token.is_cancelled() {
+//!         // work
+//!     }
+//! });
+//!
+//! shutdown::wait_for_shutdown();
+//! ```
+
+use crate::lazy;
+use crate::thread::CancellationToken;
+use crate::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+lazy! {
+    static TOKEN: CancellationToken = CancellationToken::new();
+    static FINISHED: CancellationToken = CancellationToken::new();
+    static CALLBACKS: Mutex<Vec<Callback>> = Mutex::new(Vec::new());
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// The global token, which is cancelled when a shutdown is requested.
+pub fn token() -> CancellationToken {
+    TOKEN.clone()
+}
+
+pub fn is_shutting_down() -> bool {
+    TOKEN.is_cancelled()
+}
+
+/// Register a callback to run when shutting down, for example to flush loggers or save state.
+/// Callbacks run in reverse order of registration. If the shutdown already happened, the callback
+/// runs immediately.
+pub fn on_shutdown(callback: impl FnOnce() + Send + 'static) {
+    let mut callbacks = CALLBACKS.lock();
+    if FINISHED.is_cancelled() {
+        drop(callbacks);
+        callback();
+    } else {
+        callbacks.push(Box::new(callback));
+    }
+}
+
+/// Request a shutdown, cancelling the global token and running the registered callbacks. Only the
+/// first call has an effect, later calls return immediately.
+pub fn request_shutdown() {
+    if REQUESTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    TOKEN.cancel();
+    loop {
+        let mut callbacks = CALLBACKS.lock();
+        match callbacks.pop() {
+            Some(callback) => {
+                drop(callbacks);
+                callback();
+            }
+            None => {
+                FINISHED.cancel();
+                break;
+            }
+        }
+    }
+}
+
+/// Block until a shutdown was requested and all callbacks have finished.
+pub fn wait_for_shutdown() {
+    FINISHED.wait();
+}
+
+/// Wait until a shutdown was requested and all callbacks have finished. The future does not depend
+/// on any runtime.
+#[cfg(feature = "async")]
+pub fn wait_for_shutdown_async() -> WaitForShutdown {
+    WaitForShutdown { waiting: false }
+}
+
+/// The future returned by [`wait_for_shutdown_async`].
+#[cfg(feature = "async")]
+pub struct WaitForShutdown {
+    waiting: bool,
+}
+
+#[cfg(feature = "async")]
+impl std::future::Future for WaitForShutdown {
+    type Output = ();
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<()> {
+        if FINISHED.is_cancelled() {
+            return std::task::Poll::Ready(());
+        }
+        if !self.waiting {
+            self.waiting = true;
+            let waker = cx.waker().clone();
+            std::thread::spawn(move || {
+                FINISHED.wait();
+                waker.wake();
+            });
+        }
+        std::task::Poll::Pending
+    }
+}
+
+/// Install the Ctrl-C and SIGTERM handlers, which call [`request_shutdown`]. A second signal while
+/// the callbacks are still running exits the process immediately. Installing more than once has no
+/// effect.
+pub fn install() {
+    if !INSTALLED.swap(true, Ordering::SeqCst) {
+        sys::install();
+    }
+}
+
+fn signalled() {
+    if REQUESTED.load(Ordering::SeqCst) {
+        std::process::exit(130);
+    }
+    request_shutdown();
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{c_int, c_void};
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    const SIGINT: c_int = 2;
+    const SIGTERM: c_int = 15;
+
+    extern "C" {
+        fn signal(signal: c_int, handler: usize) -> usize;
+        fn pipe(fds: *mut c_int) -> c_int;
+        fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+        fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    }
+
+    static WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
+    // Only async-signal-safe functions may be called here, so the signal is forwarded through a pipe.
+    extern "C" fn handler(_: c_int) {
+        let byte = 1u8;
+        unsafe {
+            write(WRITE_FD.load(Ordering::SeqCst), &byte as *const u8 as *const c_void, 1);
+        }
+    }
+
+    pub fn install() {
+        let mut fds = [0 as c_int; 2];
+        if unsafe { pipe(fds.as_mut_ptr()) } != 0 {
+            panic!("Failed to create the shutdown signal pipe");
+        }
+        WRITE_FD.store(fds[1], Ordering::SeqCst);
+        std::thread::Builder::new()
+            .name("shutdown".to_string())
+            .spawn(move || {
+                let mut byte = 0u8;
+                while unsafe { read(fds[0], &mut byte as *mut u8 as *mut c_void, 1) } == 1 {
+                    std::thread::spawn(super::signalled);
+                }
+            })
+            .expect("Failed to spawn the shutdown thread");
+        unsafe {
+            signal(SIGINT, handler as extern "C" fn(c_int) as usize);
+            signal(SIGTERM, handler as extern "C" fn(c_int) as usize);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+
+    // Windows calls the handler on a new thread, so it can run the callbacks directly. For close,
+    // logoff and shutdown events the process is terminated when the handler returns.
+    unsafe extern "system" fn handler(_: u32) -> i32 {
+        super::signalled();
+        1
+    }
+
+    pub fn install() {
+        unsafe {
+            SetConsoleCtrlHandler(Some(handler), 1);
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub fn install() {}
+}
@@ -0,0 +1,433 @@
+use crate::save::{load_len, nested, reserve_elements, Loader, Savable, SaveError, SaveSize, Saver};
+use crate::utils::SplitInto;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+enum Data<T, const N: usize> {
+    Inline([MaybeUninit<T>; N], usize),
+    Heap(Vec<T>),
+}
+
+fn uninit<T, const N: usize>() -> [MaybeUninit<T>; N] {
+    // An array of MaybeUninit does not require initialization.
+    unsafe { MaybeUninit::uninit().assume_init() }
+}
+
+/// A vector storing up to `N` elements inline, which spills to the heap when more are pushed.
+///
+/// It is saved in the same format as a [`Vec`], so the two can be exchanged without changing the
+/// saved data.
+///
+/// ```
+/// use mvutils::small_vec::SmallVec;
+///
+/// let mut vec = SmallVec::<u32, 2>::new();
+/// vec.push(1);
+/// vec.push(2);
+/// assert!(!vec.spilled());
+/// vec.push(3);
+/// assert!(vec.spilled());
+/// assert_eq!(vec.as_slice(), &[1, 2, 3]);
+/// ```
+pub struct SmallVec<T, const N: usize> {
+    data: Data<T, N>,
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub fn new() -> Self {
+        SmallVec { data: Data::Inline(uninit(), 0) }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            SmallVec { data: Data::Heap(Vec::with_capacity(capacity)) }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.data {
+            Data::Inline(_, len) => *len,
+            Data::Heap(vec) => vec.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.data {
+            Data::Inline(..) => N,
+            Data::Heap(vec) => vec.capacity(),
+        }
+    }
+
+    /// Whether the elements were moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.data, Data::Heap(_))
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        match &self.data {
+            Data::Inline(buf, len) => unsafe { std::slice::from_raw_parts(buf.as_ptr() as *const T, *len) },
+            Data::Heap(vec) => vec,
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.data {
+            Data::Inline(buf, len) => unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut T, *len) },
+            Data::Heap(vec) => vec,
+        }
+    }
+
+    fn spill(&mut self, additional: usize) {
+        if let Data::Inline(buf, len) = &mut self.data {
+            let mut vec = Vec::with_capacity((*len + additional).max(N * 2));
+            for item in &buf[..*len] {
+                vec.push(unsafe { item.assume_init_read() });
+            }
+            *len = 0;
+            self.data = Data::Heap(vec);
+        }
+    }
+
+    /// Reserve space for additional elements, spilling to the heap if they do not fit inline.
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.data {
+            Data::Inline(_, len) if *len + additional <= N => {}
+            Data::Inline(..) => self.spill(additional),
+            Data::Heap(vec) => vec.reserve(additional),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        match &mut self.data {
+            Data::Inline(buf, len) => {
+                buf[*len].write(value);
+                *len += 1;
+            }
+            Data::Heap(vec) => vec.push(value),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.data {
+            Data::Inline(_, 0) => None,
+            Data::Inline(buf, len) => {
+                *len -= 1;
+                Some(unsafe { buf[*len].assume_init_read() })
+            }
+            Data::Heap(vec) => vec.pop(),
+        }
+    }
+
+    /// # Panics
+    /// If the index is greater than the length.
+    pub fn insert(&mut self, index: usize, value: T) {
+        let length = self.len();
+        assert!(index <= length, "Insertion index {index} out of bounds for length {length}!");
+        self.reserve(1);
+        match &mut self.data {
+            Data::Inline(buf, len) => unsafe {
+                let p = buf.as_mut_ptr().add(index);
+                ptr::copy(p, p.add(1), *len - index);
+                (*p).write(value);
+                *len += 1;
+            },
+            Data::Heap(vec) => vec.insert(index, value),
+        }
+    }
+
+    /// # Panics
+    /// If the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let length = self.len();
+        assert!(index < length, "Index {index} out of bounds for length {length}!");
+        match &mut self.data {
+            Data::Inline(buf, len) => unsafe {
+                let p = buf.as_mut_ptr().add(index);
+                let value = (*p).assume_init_read();
+                ptr::copy(p.add(1), p, *len - index - 1);
+                *len -= 1;
+                value
+            },
+            Data::Heap(vec) => vec.remove(index),
+        }
+    }
+
+    /// Remove the element by replacing it with the last one, which does not preserve the order.
+    ///
+    /// # Panics
+    /// If the index is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let last = self.len().wrapping_sub(1);
+        self.as_mut_slice().swap(index, last);
+        self.pop().unwrap()
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        match &mut self.data {
+            Data::Inline(buf, len) => {
+                while *len > new_len {
+                    *len -= 1;
+                    unsafe { buf[*len].assume_init_drop() };
+                }
+            }
+            Data::Heap(vec) => vec.truncate(new_len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let mut i = 0;
+        while i < self.len() {
+            if f(&self[i]) {
+                i += 1;
+            } else {
+                self.remove(i);
+            }
+        }
+    }
+
+    pub fn into_vec(mut self) -> Vec<T> {
+        match std::mem::replace(&mut self.data, Data::Inline(uninit(), 0)) {
+            Data::Heap(vec) => vec,
+            Data::Inline(buf, len) => buf.into_iter().take(len).map(|item| unsafe { item.assume_init() }).collect(),
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        if let Data::Inline(..) = self.data {
+            self.clear();
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<SmallVec<T, M>> for SmallVec<T, N> {
+    fn eq(&self, other: &SmallVec<T, M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T]> for SmallVec<T, N> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Hash, const N: usize> Hash for SmallVec<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for SmallVec<T, N> {
+    /// Keeps the allocation of the vector if it does not fit inline.
+    fn from(value: Vec<T>) -> Self {
+        if value.len() <= N {
+            value.into_iter().collect()
+        } else {
+            SmallVec { data: Data::Heap(value) }
+        }
+    }
+}
+
+impl<T, const N: usize> From<SmallVec<T, N>> for Vec<T> {
+    fn from(value: SmallVec<T, N>) -> Self {
+        value.into_vec()
+    }
+}
+
+/// The owning iterator of a [`SmallVec`].
+pub struct IntoIter<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    front: usize,
+    back: usize,
+    heap: Option<std::vec::IntoIter<T>>,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if let Some(heap) = &mut self.heap {
+            return heap.next();
+        }
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(unsafe { self.buf[self.front - 1].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.heap {
+            Some(heap) => heap.size_hint(),
+            None => (self.back - self.front, Some(self.back - self.front)),
+        }
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if let Some(heap) = &mut self.heap {
+            return heap.next_back();
+        }
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(unsafe { self.buf[self.back].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        for item in &mut self.buf[self.front..self.back] {
+            unsafe { item.assume_init_drop() };
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        match std::mem::replace(&mut self.data, Data::Inline(uninit(), 0)) {
+            Data::Inline(buf, len) => IntoIter { buf, front: 0, back: len, heap: None },
+            Data::Heap(vec) => IntoIter { buf: uninit(), front: 0, back: 0, heap: Some(vec.into_iter()) },
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> SplitInto for SmallVec<T, N> {
+    fn split_into(self, n: usize) -> Vec<Self> {
+        self.into_vec().split_into(n).into_iter().map(Into::into).collect()
+    }
+}
+
+impl<T: Savable, const N: usize> Savable for SmallVec<T, N> {
+    fn save(&self, saver: &mut impl Saver) {
+        saver.push_u64(self.len() as u64);
+        reserve_elements(saver, self.iter().map(T::save_size_hint));
+        for t in self {
+            t.save(saver);
+        }
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        let len = load_len(loader)?;
+        nested(loader, |loader| {
+            let mut vec = Self::new();
+            for _ in 0..len {
+                vec.push(T::load(loader)?);
+            }
+            Ok(vec)
+        })
+    }
+}
+
+impl<T: SaveSize, const N: usize> SaveSize for SmallVec<T, N> {
+    fn save_size(&self) -> usize {
+        8 + match T::FIXED_SIZE {
+            Some(size) => size * self.len(),
+            None => self.iter().map(T::save_size).sum(),
+        }
+    }
+}
+
+/// Create a [`SmallVec`] like [`vec!`].
+#[macro_export]
+macro_rules! small_vec {
+    () => {
+        $crate::small_vec::SmallVec::new()
+    };
+    ($($x:expr),+ $(,)?) => {
+        <$crate::small_vec::SmallVec<_, _> as core::iter::FromIterator<_>>::from_iter([$($x),+])
+    };
+}
@@ -1,29 +1,48 @@
 use std::ops::{Deref, DerefMut};
 use std::sync::{Arc};
-use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use crate::unsafe_utils::DangerousCell;
 
+/// Tasks waiting for a state to change, see [`Changed`].
+#[derive(Default)]
+struct Wakers {
+    #[cfg(feature = "async")]
+    wakers: crate::sync::Mutex<Vec<std::task::Waker>>,
+}
+
+impl Wakers {
+    fn wake_all(&self) {
+        #[cfg(feature = "async")]
+        for waker in std::mem::take(&mut *self.wakers.lock()) {
+            waker.wake();
+        }
+    }
+}
+
 pub struct State<T> {
-    inner: Arc<(DangerousCell<u64>, RwLock<T>)>,
+    inner: Arc<(DangerousCell<u64>, Wakers, RwLock<T>)>,
     local_version: DangerousCell<u64>,
 }
 
 impl<T> State<T> {
     pub fn new(value: T) -> Self {
         Self {
-            inner: Arc::new((DangerousCell::new(0), RwLock::new(value))),
+            inner: Arc::new((DangerousCell::new(0), Wakers::default(), RwLock::new(value))),
             local_version: DangerousCell::new(0),
         }
     }
 
+    #[track_caller]
     pub fn read(&self) -> RwLockReadGuard<T> {
-        self.inner.1.read()
+        self.inner.2.read()
     }
 
+    #[track_caller]
     pub fn write(&self) -> StateWriteGuard<T> {
         StateWriteGuard {
-            inner: self.inner.1.write(),
+            inner: self.inner.2.write(),
             ptr: self.inner.0.get_mut(),
+            wakers: &self.inner.1,
         }
     }
 
@@ -97,6 +116,7 @@ impl<T> Ord for State<T> {
 pub struct StateWriteGuard<'a, T: ?Sized + 'a> {
     inner: RwLockWriteGuard<'a, T>,
     ptr: &'a mut u64,
+    wakers: &'a Wakers,
 }
 
 impl<'a, T: ?Sized + 'a> Deref for StateWriteGuard<'a, T> {
@@ -116,11 +136,39 @@ impl<'a, T: ?Sized + 'a> DerefMut for StateWriteGuard<'a, T> {
 impl<'a, T: ?Sized + 'a> Drop for StateWriteGuard<'a, T> {
     fn drop(&mut self) {
         *self.ptr += 1;
+        self.wakers.wake_all();
     }
 }
 
+/// Run code when any of the dependencies is outdated.
+///
+/// With a block, the block runs as a statement. With a closure-like body, the read guards of the
+/// dependencies are bound to its parameters and the expression evaluates to `Some` of the body's
+/// value, or `None` if nothing changed. With `await` in front, it waits for a change first, see
+/// [`changed_async!`](crate::changed_async), and evaluates to the body's value.
+///
+/// ```ignore
+/// when!([a, b] => { println!("changed"); });
+/// let sum = when!([a, b] => |a, b| *a + *b).unwrap_or(0);
+/// let sum = when!(await [a, b] => |a, b| *a + *b);
+/// ```
 #[macro_export]
 macro_rules! when {
+    (await [$($dependency:expr),+$(,)?] => |$($value:ident),+$(,)?| $body:expr) => {
+        {
+            $crate::changed_async!([$($dependency),+]).await;
+            $( let $value = $dependency.read(); )+
+            $body
+        }
+    };
+    ([$($dependency:expr),+$(,)?] => |$($value:ident),+$(,)?| $body:expr) => {
+        if $($dependency.is_outdated())||+ {
+            $( let $value = $dependency.read(); )+
+            Some($body)
+        } else {
+            None
+        }
+    };
     ([$($dependency:expr),+$(,)?] => $code:block) => {
         if $($dependency.is_outdated())||+ $code
     };
@@ -142,6 +190,111 @@ macro_rules! update {
     ([]) => {};
 }
 
+/// Whether any of the dependencies is outdated, as an expression.
+///
+/// ```ignore
+/// if changed!([a, b]) && a.read().is_empty() {
+///     update!([a, b]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! changed {
+    ([$($dependency:expr),+$(,)?]) => {
+        ($($dependency.is_outdated())||+)
+    };
+    ([]) => { false };
+}
+
+/// A future completing once any of the dependencies is outdated. The future does not depend on
+/// any runtime. Requires the `async` feature.
+#[macro_export]
+macro_rules! changed_async {
+    ([$($dependency:expr),+$(,)?]) => {
+        $crate::state::Changed::new(vec![$(&$dependency as &dyn $crate::state::Watch),+])
+    };
+}
+
+/// A state whose changes can be waited for, implemented by [`State`] and [`MappedState`].
+#[cfg(feature = "async")]
+pub trait Watch {
+    fn is_outdated(&self) -> bool;
+
+    /// Register a waker to be woken on the next write to the state.
+    fn register(&self, waker: &std::task::Waker);
+}
+
+#[cfg(feature = "async")]
+impl Wakers {
+    fn register(&self, waker: &std::task::Waker) {
+        let mut wakers = self.wakers.lock();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Watch for State<T> {
+    fn is_outdated(&self) -> bool {
+        State::is_outdated(self)
+    }
+
+    fn register(&self, waker: &std::task::Waker) {
+        self.inner.1.register(waker);
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, U> Watch for MappedState<T, U> {
+    fn is_outdated(&self) -> bool {
+        MappedState::is_outdated(self)
+    }
+
+    fn register(&self, waker: &std::task::Waker) {
+        self.old.inner.1.register(waker);
+    }
+}
+
+/// The future returned by [`changed_async!`](crate::changed_async) and [`State::changed`].
+#[cfg(feature = "async")]
+pub struct Changed<'a> {
+    states: Vec<&'a dyn Watch>,
+}
+
+#[cfg(feature = "async")]
+impl<'a> Changed<'a> {
+    pub fn new(states: Vec<&'a dyn Watch>) -> Self {
+        Changed { states }
+    }
+}
+
+#[cfg(feature = "async")]
+impl std::future::Future for Changed<'_> {
+    type Output = ();
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<()> {
+        if self.states.iter().any(|s| s.is_outdated()) {
+            return std::task::Poll::Ready(());
+        }
+        for state in &self.states {
+            state.register(cx.waker());
+        }
+        // A write may have happened before the wakers were registered.
+        if self.states.iter().any(|s| s.is_outdated()) {
+            return std::task::Poll::Ready(());
+        }
+        std::task::Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> State<T> {
+    /// Wait until the state is outdated.
+    pub fn changed(&self) -> Changed<'_> {
+        Changed::new(vec![self])
+    }
+}
+
 #[derive(Clone)]
 pub struct MappedState<T, U> {
     mapper: fn(&T) -> U,
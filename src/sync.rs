@@ -0,0 +1,484 @@
+//! The [`Mutex`] and [`RwLock`] used throughout the crate, with optional lock order tracking.
+//!
+//! With the `sync_debug` feature, every blocking acquisition records which other locks the
+//! current thread already holds. Acquiring two locks in an order which contradicts an order seen
+//! before, possibly on another thread, panics with a report of the cycle instead of deadlocking
+//! later. Without the feature, these are the [`parking_lot`] locks themselves.
+//!
+//! ```should_panic
+//! use mvutils::sync::Mutex;
+//!
+//! let ui = Mutex::new(0);
+//! let loader = Mutex::new(0);
+//!
+//! {
+//!     let _ui = ui.lock();
+//!     let _loader = loader.lock();
+//! }
+//!
+//! // With `sync_debug` this panics, as another thread doing the above could deadlock with it.
+//! let _loader = loader.lock();
+//! let _ui = ui.lock();
+//! # #[cfg(not(feature = "sync_debug"))]
+//! # panic!();
+//! ```
+
+#[cfg(not(feature = "sync_debug"))]
+pub use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(feature = "sync_debug")]
+pub use debug::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(feature = "sync_debug")]
+mod debug {
+    use std::fmt::{Debug, Display, Formatter};
+    use std::ops::{Deref, DerefMut};
+
+    /// A mutual exclusion lock, see [`parking_lot::Mutex`].
+    pub struct Mutex<T: ?Sized> {
+        id: tracking::LockId,
+        inner: parking_lot::Mutex<T>,
+    }
+
+    impl<T> Mutex<T> {
+        pub const fn new(value: T) -> Self {
+            Mutex {
+                id: tracking::LockId::new(),
+                inner: parking_lot::Mutex::new(value),
+            }
+        }
+
+        pub fn into_inner(self) -> T {
+            self.inner.into_inner()
+        }
+    }
+
+    impl<T: ?Sized> Mutex<T> {
+        #[track_caller]
+        pub fn lock(&self) -> MutexGuard<'_, T> {
+            let held = self.id.acquire(false, false);
+            MutexGuard {
+                inner: self.inner.lock(),
+                _held: held,
+            }
+        }
+
+        #[track_caller]
+        pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+            let inner = self.inner.try_lock()?;
+            Some(MutexGuard {
+                inner,
+                _held: self.id.hold(false),
+            })
+        }
+
+        pub fn get_mut(&mut self) -> &mut T {
+            self.inner.get_mut()
+        }
+    }
+
+    impl<T: Default> Default for Mutex<T> {
+        fn default() -> Self {
+            Mutex::new(T::default())
+        }
+    }
+
+    impl<T> From<T> for Mutex<T> {
+        fn from(value: T) -> Self {
+            Mutex::new(value)
+        }
+    }
+
+    impl<T: ?Sized + Debug> Debug for Mutex<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            self.inner.fmt(f)
+        }
+    }
+
+    pub struct MutexGuard<'a, T: ?Sized> {
+        inner: parking_lot::MutexGuard<'a, T>,
+        _held: tracking::Held,
+    }
+
+    impl<T: ?Sized + Debug> Debug for MutexGuard<'_, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (**self).fmt(f)
+        }
+    }
+
+    impl<T: ?Sized + Display> Display for MutexGuard<'_, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (**self).fmt(f)
+        }
+    }
+
+    impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.inner
+        }
+    }
+
+    impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.inner
+        }
+    }
+
+    /// A reader-writer lock, see [`parking_lot::RwLock`].
+    ///
+    /// With `sync_debug`, any recursive acquisition panics, including a second [`RwLock::read`] while
+    /// already reading, as that deadlocks if a writer is waiting in between. Use
+    /// [`RwLock::read_recursive`] to read recursively.
+    pub struct RwLock<T: ?Sized> {
+        id: tracking::LockId,
+        inner: parking_lot::RwLock<T>,
+    }
+
+    impl<T> RwLock<T> {
+        pub const fn new(value: T) -> Self {
+            RwLock {
+                id: tracking::LockId::new(),
+                inner: parking_lot::RwLock::new(value),
+            }
+        }
+
+        pub fn into_inner(self) -> T {
+            self.inner.into_inner()
+        }
+    }
+
+    impl<T: ?Sized> RwLock<T> {
+        #[track_caller]
+        pub fn read(&self) -> RwLockReadGuard<'_, T> {
+            let held = self.id.acquire(true, false);
+            RwLockReadGuard {
+                inner: self.inner.read(),
+                _held: held,
+            }
+        }
+
+        /// Take a read lock even if this thread already holds one, see
+        /// [`parking_lot::RwLock::read_recursive`].
+        #[track_caller]
+        pub fn read_recursive(&self) -> RwLockReadGuard<'_, T> {
+            let held = self.id.acquire(true, true);
+            RwLockReadGuard {
+                inner: self.inner.read_recursive(),
+                _held: held,
+            }
+        }
+
+        #[track_caller]
+        pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+            let held = self.id.acquire(false, false);
+            RwLockWriteGuard {
+                inner: self.inner.write(),
+                _held: held,
+            }
+        }
+
+        #[track_caller]
+        pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+            let inner = self.inner.try_read()?;
+            Some(RwLockReadGuard {
+                inner,
+                _held: self.id.hold(true),
+            })
+        }
+
+        #[track_caller]
+        pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+            let inner = self.inner.try_write()?;
+            Some(RwLockWriteGuard {
+                inner,
+                _held: self.id.hold(false),
+            })
+        }
+
+        pub fn get_mut(&mut self) -> &mut T {
+            self.inner.get_mut()
+        }
+    }
+
+    impl<T: Default> Default for RwLock<T> {
+        fn default() -> Self {
+            RwLock::new(T::default())
+        }
+    }
+
+    impl<T> From<T> for RwLock<T> {
+        fn from(value: T) -> Self {
+            RwLock::new(value)
+        }
+    }
+
+    impl<T: ?Sized + Debug> Debug for RwLock<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            self.inner.fmt(f)
+        }
+    }
+
+    pub struct RwLockReadGuard<'a, T: ?Sized> {
+        inner: parking_lot::RwLockReadGuard<'a, T>,
+        _held: tracking::Held,
+    }
+
+    impl<T: ?Sized + Debug> Debug for RwLockReadGuard<'_, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (**self).fmt(f)
+        }
+    }
+
+    impl<T: ?Sized + Display> Display for RwLockReadGuard<'_, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (**self).fmt(f)
+        }
+    }
+
+    impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.inner
+        }
+    }
+
+    pub struct RwLockWriteGuard<'a, T: ?Sized> {
+        inner: parking_lot::RwLockWriteGuard<'a, T>,
+        _held: tracking::Held,
+    }
+
+    impl<T: ?Sized + Debug> Debug for RwLockWriteGuard<'_, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (**self).fmt(f)
+        }
+    }
+
+    impl<T: ?Sized + Display> Display for RwLockWriteGuard<'_, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            (**self).fmt(f)
+        }
+    }
+
+    impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.inner
+        }
+    }
+
+    impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.inner
+        }
+    }
+
+    mod tracking {
+        use hashbrown::{HashMap, HashSet};
+        use std::cell::RefCell;
+        use std::collections::VecDeque;
+        use std::fmt::Write;
+        use std::panic::Location;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        /// Lock ids are assigned on first use, so they are never reused even if the memory of a lock is.
+        pub(super) struct LockId(AtomicUsize);
+
+        struct HeldLock {
+            id: usize,
+            shared: bool,
+            location: &'static Location<'static>,
+        }
+
+        /// An observed acquisition of `to` while holding `from`.
+        struct Edge {
+            from: usize,
+            to: usize,
+            from_location: &'static Location<'static>,
+            to_location: &'static Location<'static>,
+            thread: String,
+        }
+
+        thread_local! {
+            static HELD: RefCell<Vec<HeldLock>> = const { RefCell::new(Vec::new()) };
+        }
+
+        /// The lock order graph. Edges of a lock are removed when it is dropped, so the graph only
+        /// contains locks that still exist.
+        #[derive(Default)]
+        struct Graph {
+            /// Outgoing edges by lock.
+            edges: HashMap<usize, Vec<Edge>>,
+            /// Locks with an edge to the lock.
+            incoming: HashMap<usize, Vec<usize>>,
+            pairs: HashSet<(usize, usize)>,
+        }
+
+        impl Graph {
+            fn insert(&mut self, edge: Edge) {
+                if self.pairs.insert((edge.from, edge.to)) {
+                    self.incoming.entry(edge.to).or_default().push(edge.from);
+                    self.edges.entry(edge.from).or_default().push(edge);
+                }
+            }
+
+            fn remove(&mut self, id: usize) {
+                for edge in self.edges.remove(&id).unwrap_or_default() {
+                    self.pairs.remove(&(id, edge.to));
+                    if let Some(incoming) = self.incoming.get_mut(&edge.to) {
+                        incoming.retain(|from| *from != id);
+                    }
+                }
+                for from in self.incoming.remove(&id).unwrap_or_default() {
+                    self.pairs.remove(&(from, id));
+                    if let Some(edges) = self.edges.get_mut(&from) {
+                        edges.retain(|e| e.to != id);
+                    }
+                }
+            }
+
+            fn find_path(&self, from: usize, to: usize) -> Option<Vec<&Edge>> {
+                let mut parents: HashMap<usize, &Edge> = HashMap::new();
+                let mut queue = VecDeque::from([from]);
+                while let Some(node) = queue.pop_front() {
+                    for edge in self.edges.get(&node).into_iter().flatten() {
+                        if edge.to == to {
+                            let mut path = vec![edge];
+                            let mut node = edge.from;
+                            while node != from {
+                                let edge = parents[&node];
+                                path.push(edge);
+                                node = edge.from;
+                            }
+                            path.reverse();
+                            return Some(path);
+                        }
+                        if edge.to != from && !parents.contains_key(&edge.to) {
+                            parents.insert(edge.to, edge);
+                            queue.push_back(edge.to);
+                        }
+                    }
+                }
+                None
+            }
+        }
+
+        static GRAPH: parking_lot::Mutex<Option<Graph>> = parking_lot::Mutex::new(None);
+
+        /// Removes the lock from the locks held by the current thread when dropped.
+        pub(super) struct Held {
+            id: usize,
+        }
+
+        impl Drop for Held {
+            fn drop(&mut self) {
+                let _ = HELD.try_with(|held| {
+                    let mut held = held.borrow_mut();
+                    if let Some(i) = held.iter().rposition(|h| h.id == self.id) {
+                        held.remove(i);
+                    }
+                });
+            }
+        }
+
+        impl Drop for LockId {
+            fn drop(&mut self) {
+                let id = *self.0.get_mut();
+                if id != 0 {
+                    if let Some(graph) = GRAPH.lock().as_mut() {
+                        graph.remove(id);
+                    }
+                }
+            }
+        }
+
+        impl LockId {
+            pub(super) const fn new() -> Self {
+                LockId(AtomicUsize::new(0))
+            }
+
+            fn get(&self) -> usize {
+                let id = self.0.load(Ordering::Acquire);
+                if id != 0 {
+                    return id;
+                }
+                let new = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+                match self.0.compare_exchange(0, new, Ordering::AcqRel, Ordering::Acquire) {
+                    Ok(_) => new,
+                    Err(id) => id,
+                }
+            }
+
+            /// Check the acquisition against the recorded lock order before blocking on the lock.
+            /// A `recursive` shared acquisition may happen while this thread already holds the lock
+            /// shared.
+            #[track_caller]
+            pub(super) fn acquire(&self, shared: bool, recursive: bool) -> Held {
+                let id = self.get();
+                let location = Location::caller();
+                let report = HELD
+                    .try_with(|held| {
+                        let held = held.borrow();
+                        if let Some(h) = held.iter().find(|h| h.id == id) {
+                            if !(recursive && shared && h.shared) {
+                                return Some(format!(
+                                    "Deadlock: lock #{} acquired at {} is already held by this thread, acquired at {}",
+                                    id, location, h.location
+                                ));
+                            }
+                        }
+
+                        let mut graph = GRAPH.lock();
+                        let graph = graph.get_or_insert_with(Graph::default);
+                        for h in held.iter().filter(|h| h.id != id) {
+                            if graph.pairs.contains(&(h.id, id)) {
+                                continue;
+                            }
+                            if let Some(path) = graph.find_path(id, h.id) {
+                                let mut report = format!(
+                                    "Potential deadlock: acquiring lock #{} at {} while holding lock #{} acquired at {}, but the opposite order was seen before:\n",
+                                    id, location, h.id, h.location
+                                );
+                                for edge in path {
+                                    let _ = writeln!(
+                                        report,
+                                        "  lock #{} acquired at {} while holding lock #{} acquired at {} on thread '{}'",
+                                        edge.to, edge.to_location, edge.from, edge.from_location, edge.thread
+                                    );
+                                }
+                                return Some(report);
+                            }
+                            graph.insert(Edge {
+                                from: h.id,
+                                to: id,
+                                from_location: h.location,
+                                to_location: location,
+                                thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+                            });
+                        }
+                        None
+                    })
+                    .ok()
+                    .flatten();
+                if let Some(report) = report {
+                    panic!("{}", report);
+                }
+                self.hold(shared)
+            }
+
+            /// Record the lock as held by the current thread without checking the lock order.
+            #[track_caller]
+            pub(super) fn hold(&self, shared: bool) -> Held {
+                let id = self.get();
+                let location = Location::caller();
+                let _ = HELD.try_with(|held| held.borrow_mut().push(HeldLock { id, shared, location }));
+                Held { id }
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::platform;
+use crate::thread::CancellationToken;
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+type TaskFn<'a> = Box<dyn FnOnce() -> Result<(), String> + Send + 'a>;
+
+/// Identifies a task within the [`TaskGraph`] that created it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct TaskId(usize);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskStatus {
+    Succeeded,
+    Failed(String),
+    Panicked(String),
+    /// Not run because one of its dependencies did not succeed.
+    Skipped,
+    /// Not run because the graph was cancelled.
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn is_success(&self) -> bool {
+        matches!(self, TaskStatus::Succeeded)
+    }
+}
+
+impl Display for TaskStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::Succeeded => f.write_str("succeeded"),
+            TaskStatus::Failed(e) => write!(f, "failed: {e}"),
+            TaskStatus::Panicked(e) => write!(f, "panicked: {e}"),
+            TaskStatus::Skipped => f.write_str("skipped"),
+            TaskStatus::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+
+/// The outcome of every task after executing a [`TaskGraph`].
+#[derive(Clone, Debug)]
+pub struct GraphReport {
+    tasks: Vec<(String, TaskStatus)>,
+}
+
+impl GraphReport {
+    pub fn status(&self, task: TaskId) -> &TaskStatus {
+        &self.tasks[task.0].1
+    }
+
+    pub fn status_by_name(&self, name: &str) -> Option<&TaskStatus> {
+        self.tasks.iter().find(|(n, _)| n == name).map(|(_, s)| s)
+    }
+
+    /// Whether all tasks succeeded.
+    pub fn is_success(&self) -> bool {
+        self.tasks.iter().all(|(_, s)| s.is_success())
+    }
+
+    /// All tasks which did not succeed, with their status.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &TaskStatus)> {
+        self.tasks.iter().filter(|(_, s)| !s.is_success()).map(|(n, s)| (n.as_str(), s))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TaskStatus)> {
+        self.tasks.iter().map(|(n, s)| (n.as_str(), s))
+    }
+}
+
+struct Task<'a> {
+    name: String,
+    f: TaskFn<'a>,
+    dependencies: Vec<usize>,
+}
+
+struct ExecState<'a> {
+    fns: Vec<Option<TaskFn<'a>>>,
+    deps_left: Vec<usize>,
+    status: Vec<Option<TaskStatus>>,
+    ready: VecDeque<usize>,
+    resolved: usize,
+    stop: bool,
+}
+
+impl ExecState<'_> {
+    fn resolve(&mut self, task: usize, status: TaskStatus) {
+        self.status[task] = Some(status);
+        self.resolved += 1;
+    }
+
+    fn skip_dependents(&mut self, task: usize, dependents: &[Vec<usize>]) {
+        let mut stack = dependents[task].clone();
+        while let Some(d) = stack.pop() {
+            if self.status[d].is_none() {
+                self.resolve(d, TaskStatus::Skipped);
+                stack.extend_from_slice(&dependents[d]);
+            }
+        }
+    }
+
+    fn cancel_pending(&mut self) {
+        self.ready.clear();
+        for i in 0..self.status.len() {
+            if self.status[i].is_none() && self.fns[i].is_some() {
+                self.fns[i] = None;
+                self.resolve(i, TaskStatus::Cancelled);
+            }
+        }
+    }
+}
+
+/// A set of tasks with dependencies between them. Executing the graph runs every task after all of
+/// its dependencies succeeded, running independent tasks in parallel on scoped worker threads.
+///
+/// ```
+/// use mvutils::task_graph::TaskGraph;
+///
+/// let mut graph = TaskGraph::new();
+/// let textures = graph.add("textures", || Ok(()));
+/// let models = graph.add("models", || Ok(()));
+/// graph.add_after("pack", &[textures, models], || Ok(()));
+/// assert!(graph.execute().unwrap().is_success());
+/// ```
+pub struct TaskGraph<'a> {
+    tasks: Vec<Task<'a>>,
+    threads: usize,
+    fail_fast: bool,
+}
+
+impl<'a> TaskGraph<'a> {
+    pub fn new() -> Self {
+        TaskGraph {
+            tasks: Vec::new(),
+            threads: platform::available_parallelism(),
+            fail_fast: false,
+        }
+    }
+
+    /// The maximum number of tasks to run at the same time, defaults to the available parallelism.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Whether to cancel all tasks which did not start yet as soon as one task fails. Otherwise only
+    /// the dependents of a failed task are skipped.
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub fn add(&mut self, name: &str, f: impl FnOnce() -> Result<(), String> + Send + 'a) -> TaskId {
+        self.tasks.push(Task {
+            name: name.to_string(),
+            f: Box::new(f),
+            dependencies: Vec::new(),
+        });
+        TaskId(self.tasks.len() - 1)
+    }
+
+    pub fn add_after(&mut self, name: &str, dependencies: &[TaskId], f: impl FnOnce() -> Result<(), String> + Send + 'a) -> TaskId {
+        let id = self.add(name, f);
+        for dependency in dependencies {
+            self.depend(id, *dependency);
+        }
+        id
+    }
+
+    /// Make the task run only after the other task succeeded.
+    pub fn depend(&mut self, task: TaskId, on: TaskId) {
+        let dependencies = &mut self.tasks[task.0].dependencies;
+        if !dependencies.contains(&on.0) {
+            dependencies.push(on.0);
+        }
+    }
+
+    pub fn name(&self, task: TaskId) -> &str {
+        &self.tasks[task.0].name
+    }
+
+    /// Return the tasks in an order in which every task comes after its dependencies, or an error
+    /// naming the tasks involved in a dependency cycle.
+    pub fn validate(&self) -> Result<Vec<TaskId>, String> {
+        let n = self.tasks.len();
+        if let Some(task) = self.tasks.iter().find(|t| t.dependencies.iter().any(|d| *d >= n)) {
+            return Err(format!("Task '{}' depends on a task from another graph!", task.name));
+        }
+        let mut deps_left = self.tasks.iter().map(|t| t.dependencies.len()).collect::<Vec<_>>();
+        let dependents = self.dependents();
+        let mut queue = (0..n).filter(|i| deps_left[*i] == 0).collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(n);
+        while let Some(i) = queue.pop_front() {
+            order.push(TaskId(i));
+            for d in &dependents[i] {
+                deps_left[*d] -= 1;
+                if deps_left[*d] == 0 {
+                    queue.push_back(*d);
+                }
+            }
+        }
+        if order.len() < n {
+            let cycle = (0..n)
+                .filter(|i| deps_left[*i] > 0)
+                .map(|i| self.tasks[i].name.as_str())
+                .collect::<Vec<_>>();
+            return Err(format!("Dependency cycle between tasks: {}!", cycle.join(", ")));
+        }
+        Ok(order)
+    }
+
+    fn dependents(&self) -> Vec<Vec<usize>> {
+        let mut dependents = vec![Vec::new(); self.tasks.len()];
+        for (i, task) in self.tasks.iter().enumerate() {
+            for d in &task.dependencies {
+                dependents[*d].push(i);
+            }
+        }
+        dependents
+    }
+
+    /// Validate and run the graph, blocking until all tasks are resolved.
+    pub fn execute(self) -> Result<GraphReport, String> {
+        self.run(None)
+    }
+
+    /// Like [`TaskGraph::execute`], but cancels all tasks which did not start yet once the token is cancelled.
+    pub fn execute_cancellable(self, token: &CancellationToken) -> Result<GraphReport, String> {
+        self.run(Some(token))
+    }
+
+    fn run(self, token: Option<&CancellationToken>) -> Result<GraphReport, String> {
+        self.validate()?;
+        let n = self.tasks.len();
+        let dependents = self.dependents();
+        let mut names = Vec::with_capacity(n);
+        let mut fns = Vec::with_capacity(n);
+        let mut deps_left = Vec::with_capacity(n);
+        for task in self.tasks {
+            names.push(task.name);
+            fns.push(Some(task.f));
+            deps_left.push(task.dependencies.len());
+        }
+        let ready = (0..n).filter(|i| deps_left[*i] == 0).collect();
+        let state = Mutex::new(ExecState {
+            fns,
+            deps_left,
+            status: vec![None; n],
+            ready,
+            resolved: 0,
+            stop: false,
+        });
+        let condvar = Condvar::new();
+        let fail_fast = self.fail_fast;
+
+        let worker = || loop {
+            let mut st = state.lock();
+            let (task, f) = loop {
+                if st.resolved == n {
+                    return;
+                }
+                if st.stop || token.is_some_and(|t| t.is_cancelled()) {
+                    st.cancel_pending();
+                    condvar.notify_all();
+                } else if let Some(i) = st.ready.pop_front() {
+                    if let Some(f) = st.fns[i].take() {
+                        break (i, f);
+                    }
+                    continue;
+                }
+                if st.resolved == n {
+                    return;
+                }
+                condvar.wait_for(&mut st, Duration::from_millis(10));
+            };
+            drop(st);
+
+            let status = match catch_unwind(AssertUnwindSafe(f)) {
+                Ok(Ok(())) => TaskStatus::Succeeded,
+                Ok(Err(e)) => TaskStatus::Failed(e),
+                Err(payload) => TaskStatus::Panicked(
+                    payload
+                        .downcast_ref::<&'static str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default(),
+                ),
+            };
+
+            let mut st = state.lock();
+            if status.is_success() {
+                for d in &dependents[task] {
+                    st.deps_left[*d] -= 1;
+                    if st.deps_left[*d] == 0 && st.status[*d].is_none() {
+                        st.ready.push_back(*d);
+                    }
+                }
+            } else {
+                st.skip_dependents(task, &dependents);
+                st.stop |= fail_fast;
+            }
+            st.resolve(task, status);
+            condvar.notify_all();
+        };
+
+        if self.threads.min(n) <= 1 || !platform::HAS_THREADS {
+            worker();
+        } else {
+            std::thread::scope(|scope| {
+                for _ in 1..self.threads.min(n) {
+                    scope.spawn(worker);
+                }
+                worker();
+            });
+        }
+
+        let status = state.into_inner().status;
+        Ok(GraphReport {
+            tasks: names.into_iter().zip(status).map(|(n, s)| (n, s.unwrap_or(TaskStatus::Cancelled))).collect(),
+        })
+    }
+}
+
+impl Default for TaskGraph<'_> {
+    fn default() -> Self {
+        TaskGraph::new()
+    }
+}
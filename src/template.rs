@@ -0,0 +1,152 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// What to do when rendering a placeholder whose key cannot be resolved and has no inline default.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum MissingKey {
+    /// Fail rendering with an error.
+    #[default]
+    Error,
+    /// Replace the placeholder with nothing.
+    Empty,
+    /// Leave the placeholder in the output unchanged.
+    Keep,
+    /// Replace the placeholder with this value.
+    Default(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder { key: String, default: Option<String> },
+}
+
+/// A string template with `${key}` placeholders, parsed once and rendered many times.
+///
+/// A placeholder may specify an inline default using `${key:default}`, which is used if the key
+/// cannot be resolved. A literal `$` is written as `$$`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    source: String,
+    parts: Vec<Part>,
+    missing: MissingKey,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(i) = rest.find('$') {
+            text.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            if let Some(r) = rest.strip_prefix('$') {
+                text.push('$');
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('{') {
+                let end = r.find('}').ok_or_else(|| format!("Unterminated placeholder at index {} in template!", source.len() - rest.len() - 1))?;
+                let (key, default) = match r[..end].split_once(':') {
+                    Some((key, default)) => (key.trim(), Some(default.to_string())),
+                    None => (r[..end].trim(), None),
+                };
+                if key.is_empty() {
+                    return Err(format!("Empty placeholder at index {} in template!", source.len() - rest.len() - 1));
+                }
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Part::Placeholder {
+                    key: key.to_string(),
+                    default,
+                });
+                rest = &r[end + 1..];
+            } else {
+                text.push('$');
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Template {
+            source: source.to_string(),
+            parts,
+            missing: MissingKey::default(),
+        })
+    }
+
+    /// Set the policy for keys that cannot be resolved.
+    pub fn missing_keys(mut self, missing: MissingKey) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The keys of all placeholders, in order of first appearance.
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        for part in &self.parts {
+            if let Part::Placeholder { key, .. } = part {
+                if !keys.contains(&key.as_str()) {
+                    keys.push(key.as_str());
+                }
+            }
+        }
+        keys
+    }
+
+    /// Render the template, resolving keys using the function.
+    pub fn render_with<D: Display>(&self, lookup: impl Fn(&str) -> Option<D>) -> Result<String, String> {
+        let mut output = String::with_capacity(self.source.len());
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Placeholder { key, default } => match (lookup(key), default) {
+                    (Some(value), _) => output.push_str(&value.to_string()),
+                    (None, Some(default)) => output.push_str(default),
+                    (None, None) => match &self.missing {
+                        MissingKey::Error => return Err(format!("Missing value for placeholder '{key}'!")),
+                        MissingKey::Empty => {}
+                        MissingKey::Keep => {
+                            output.push_str("${");
+                            output.push_str(key);
+                            output.push('}');
+                        }
+                        MissingKey::Default(value) => output.push_str(value),
+                    },
+                },
+            }
+        }
+        Ok(output)
+    }
+
+    pub fn render<K, V>(&self, values: &HashMap<K, V>) -> Result<String, String>
+    where
+        K: Borrow<str> + Eq + Hash,
+        V: Display,
+    {
+        self.render_with(|key| values.get(key))
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::parse(s)
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
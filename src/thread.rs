@@ -1,9 +1,19 @@
 use crate::once::Lazy;
-use crate::utils::Recover;
+use crate::platform;
+use crate::state::State;
+use crate::sync::Mutex;
+use crate::lazy;
 use hashbrown::HashMap;
+use parking_lot::Condvar;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::ops::{Deref, DerefMut};
-use std::sync::Mutex;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 use std::thread::ThreadId;
+use std::time::{Duration, Instant};
 
 pub struct ThreadUnique<T> {
     inner: Lazy<Mutex<HashMap<ThreadId, T>>>,
@@ -19,8 +29,9 @@ impl<T> ThreadUnique<T> {
     }
 
     #[allow(clippy::mut_from_ref)]
+    #[track_caller]
     pub fn get(&self) -> &mut T {
-        let mut inner = self.inner.lock().recover();
+        let mut inner = self.inner.lock();
         let ptr = inner
             .entry(std::thread::current().id())
             .or_insert((self.gen)()) as *mut T;
@@ -63,3 +74,284 @@ macro_rules! thread_unique {
         )*
     };
 }
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: parking_lot::Mutex<bool>,
+    condvar: Condvar,
+}
+
+/// A shared flag to request cancellation of running work. Cloning returns a handle to the same token.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancellationState>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Cancel the token, waking up all threads waiting on it.
+    pub fn cancel(&self) {
+        *self.state.cancelled.lock() = true;
+        self.state.condvar.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.cancelled.lock()
+    }
+
+    /// Block until the token is cancelled.
+    pub fn wait(&self) {
+        let mut cancelled = self.state.cancelled.lock();
+        while !*cancelled {
+            self.state.condvar.wait(&mut cancelled);
+        }
+    }
+
+    /// Block until the token is cancelled or the timeout elapsed, returning whether it was cancelled.
+    /// This can be used as an interruptible sleep.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut cancelled = self.state.cancelled.lock();
+        while !*cancelled {
+            if self.state.condvar.wait_until(&mut cancelled, deadline).timed_out() {
+                break;
+            }
+        }
+        *cancelled
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The shared pool running [`Task`]s, with one worker per available core. Workers are started on
+/// first use.
+struct Pool {
+    queue: parking_lot::Mutex<VecDeque<Job>>,
+    condvar: Condvar,
+    workers: std::sync::Once,
+}
+
+lazy! {
+    static POOL: Pool = Pool {
+        queue: parking_lot::Mutex::new(VecDeque::new()),
+        condvar: Condvar::new(),
+        workers: std::sync::Once::new(),
+    };
+}
+
+impl Pool {
+    fn submit(&'static self, job: Job) {
+        if !platform::HAS_THREADS {
+            job();
+            return;
+        }
+        self.workers.call_once(|| {
+            for i in 0..platform::available_parallelism() {
+                let _ = std::thread::Builder::new()
+                    .name(format!("mvutils-pool-{}", i))
+                    .spawn(move || self.work());
+            }
+        });
+        self.queue.lock().push_back(job);
+        self.condvar.notify_one();
+    }
+
+    fn work(&self) {
+        loop {
+            let job = {
+                let mut queue = self.queue.lock();
+                loop {
+                    match queue.pop_front() {
+                        Some(job) => break job,
+                        None => self.condvar.wait(&mut queue),
+                    }
+                }
+            };
+            job();
+        }
+    }
+}
+
+/// Why a [`Task`] did not produce a result.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskError {
+    /// The task was cancelled before it started.
+    Cancelled,
+    /// The task panicked with the given message.
+    Panicked(String),
+}
+
+impl Display for TaskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::Cancelled => f.write_str("Task was cancelled"),
+            TaskError::Panicked(message) => write!(f, "Task panicked: {}", message),
+        }
+    }
+}
+
+impl Error for TaskError {}
+
+/// Passed to the work of a [`Task`] to report progress and check for cancellation.
+pub struct TaskContext {
+    progress: Arc<AtomicU32>,
+    token: CancellationToken,
+}
+
+impl TaskContext {
+    /// Set the progress, usually between 0 and 1.
+    pub fn set_progress(&self, progress: f32) {
+        self.progress.store(progress.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+struct TaskShared<T> {
+    /// `None` while running, `Some(None)` once the result was taken.
+    result: parking_lot::Mutex<Option<Option<Result<T, TaskError>>>>,
+    finished: Condvar,
+    notify: parking_lot::Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+    #[cfg(feature = "async")]
+    wakers: parking_lot::Mutex<Vec<std::task::Waker>>,
+}
+
+impl<T> TaskShared<T> {
+    fn finish(&self, result: Result<T, TaskError>) {
+        *self.result.lock() = Some(Some(result));
+        self.finished.notify_all();
+        for notify in std::mem::take(&mut *self.notify.lock()) {
+            notify();
+        }
+        #[cfg(feature = "async")]
+        for waker in std::mem::take(&mut *self.wakers.lock()) {
+            waker.wake();
+        }
+    }
+}
+
+/// A handle to work running on the shared thread pool.
+///
+/// The result can be polled with [`Task::try_take`] or waited for with [`Task::join`], and with
+/// the `async` feature the task can be awaited. The work receives a [`TaskContext`] to report
+/// progress and to check whether the task was cancelled. A task cancelled before it started never
+/// runs, while running work has to check the context and return early itself.
+pub struct Task<T> {
+    shared: Arc<TaskShared<T>>,
+    progress: Arc<AtomicU32>,
+    token: CancellationToken,
+}
+
+impl<T: Send + 'static> Task<T> {
+    pub fn spawn(work: impl FnOnce(&TaskContext) -> T + Send + 'static) -> Self {
+        let task = Task {
+            shared: Arc::new(TaskShared {
+                result: parking_lot::Mutex::new(None),
+                finished: Condvar::new(),
+                notify: parking_lot::Mutex::new(Vec::new()),
+                #[cfg(feature = "async")]
+                wakers: parking_lot::Mutex::new(Vec::new()),
+            }),
+            progress: Arc::new(AtomicU32::new(0f32.to_bits())),
+            token: CancellationToken::new(),
+        };
+
+        let shared = task.shared.clone();
+        let context = TaskContext {
+            progress: task.progress.clone(),
+            token: task.token.clone(),
+        };
+        POOL.submit(Box::new(move || {
+            if context.is_cancelled() {
+                shared.finish(Err(TaskError::Cancelled));
+                return;
+            }
+            let result = catch_unwind(AssertUnwindSafe(|| work(&context))).map_err(|e| {
+                let message = e
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown".to_string());
+                TaskError::Panicked(message)
+            });
+            shared.finish(result);
+        }));
+        task
+    }
+}
+
+impl<T> Task<T> {
+    /// The progress last reported by the work.
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.shared.result.lock().is_some()
+    }
+
+    /// Take the result if the task finished. Returns `None` if it is still running or the result
+    /// was already taken.
+    pub fn try_take(&self) -> Option<Result<T, TaskError>> {
+        self.shared.result.lock().as_mut().and_then(Option::take)
+    }
+
+    /// Block until the task finished and return its result.
+    ///
+    /// # Panics
+    /// If the result was already taken with [`Task::try_take`].
+    pub fn join(self) -> Result<T, TaskError> {
+        let mut result = self.shared.result.lock();
+        while result.is_none() {
+            self.shared.finished.wait(&mut result);
+        }
+        result.as_mut().and_then(Option::take).expect("The result of the task was already taken!")
+    }
+
+    /// Bump the version of the state once the task finished, so [`when!`](crate::when) consumers
+    /// notice it. If the task already finished, the version is bumped immediately.
+    pub fn notify<S: 'static>(&self, state: &State<S>) {
+        let state = state.clone();
+        let notify = move || drop(state.write());
+        let result = self.shared.result.lock();
+        if result.is_some() {
+            drop(result);
+            notify();
+        } else {
+            self.shared.notify.lock().push(Box::new(notify));
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> std::future::Future for Task<T> {
+    type Output = Result<T, TaskError>;
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+        let mut result = self.shared.result.lock();
+        match result.as_mut() {
+            Some(result) => std::task::Poll::Ready(result.take().expect("The result of the task was already taken!")),
+            None => {
+                self.shared.wakers.lock().push(cx.waker().clone());
+                std::task::Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use crate::lerp::Lerp;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// Easing curves mapping linear progress from 0 to 1 onto animated progress.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    /// Overshoots slightly backwards before moving forward.
+    BackIn,
+    /// Overshoots the target slightly before settling.
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    BounceIn,
+    BounceOut,
+    /// Jumps to the end at the end.
+    Step,
+}
+
+const BACK: f32 = 1.70158;
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+impl Easing {
+    /// Apply the curve to the progress, which is clamped between 0 and 1. The result may leave this
+    /// range for the back and elastic curves.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::ExpoIn if t == 0.0 => 0.0,
+            Easing::ExpoIn => 2f32.powf(10.0 * t - 10.0),
+            Easing::ExpoOut if t == 1.0 => 1.0,
+            Easing::ExpoOut => 1.0 - 2f32.powf(-10.0 * t),
+            Easing::ExpoInOut if t == 0.0 || t == 1.0 => t,
+            Easing::ExpoInOut if t < 0.5 => 2f32.powf(20.0 * t - 10.0) / 2.0,
+            Easing::ExpoInOut => (2.0 - 2f32.powf(-20.0 * t + 10.0)) / 2.0,
+            Easing::BackIn => (BACK + 1.0) * t * t * t - BACK * t * t,
+            Easing::BackOut => 1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2),
+            Easing::BackInOut => {
+                let c = BACK * 1.525;
+                if t < 0.5 {
+                    (2.0 * t).powi(2) * ((c + 1.0) * 2.0 * t - c) / 2.0
+                } else {
+                    ((2.0 * t - 2.0).powi(2) * ((c + 1.0) * (t * 2.0 - 2.0) + c) + 2.0) / 2.0
+                }
+            }
+            Easing::ElasticIn if t == 0.0 || t == 1.0 => t,
+            Easing::ElasticIn => -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin(),
+            Easing::ElasticOut if t == 0.0 || t == 1.0 => t,
+            Easing::ElasticOut => 2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0,
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::Step if t < 1.0 => 0.0,
+            Easing::Step => 1.0,
+        }
+    }
+}
+
+/// Something that changes over a fixed duration and can be moved to any point in time.
+pub trait Animation: Send {
+    fn duration(&self) -> Duration;
+
+    /// Apply the animation at the time, which is clamped to the duration.
+    fn seek(&mut self, time: Duration);
+
+    /// Restore the animation to before it started, for example so that one-time callbacks fire again.
+    fn reset(&mut self) {}
+}
+
+/// Interpolates between two values over a duration using an easing curve, passing every new value
+/// to an optional callback.
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    duration: Duration,
+    easing: Easing,
+    elapsed: Duration,
+    on_update: Option<Box<dyn FnMut(T) + Send>>,
+}
+
+impl<T: Lerp + Send> Tween<T> {
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Tween {
+            from,
+            to,
+            duration,
+            easing: Easing::Linear,
+            elapsed: Duration::ZERO,
+            on_update: None,
+        }
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Call the function with the new value whenever the tween is advanced.
+    pub fn on_update(mut self, f: impl FnMut(T) + Send + 'static) -> Self {
+        self.on_update = Some(Box::new(f));
+        self
+    }
+
+    /// The value at the time, without changing the tween.
+    pub fn value_at(&self, time: Duration) -> T {
+        let progress = if self.duration.is_zero() {
+            1.0
+        } else {
+            time.as_secs_f32() / self.duration.as_secs_f32()
+        };
+        self.from.lerp(&self.to, self.easing.apply(progress))
+    }
+
+    pub fn value(&self) -> T {
+        self.value_at(self.elapsed)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Advance the tween and return the new value.
+    pub fn tick(&mut self, delta: Duration) -> T {
+        self.seek(self.elapsed + delta);
+        self.value()
+    }
+}
+
+impl<T: Lerp + Send> Animation for Tween<T> {
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn seek(&mut self, time: Duration) {
+        self.elapsed = time.min(self.duration);
+        if self.on_update.is_some() {
+            let value = self.value();
+            if let Some(f) = &mut self.on_update {
+                f(value);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+}
+
+struct Delay(Duration);
+
+impl Animation for Delay {
+    fn duration(&self) -> Duration {
+        self.0
+    }
+
+    fn seek(&mut self, _: Duration) {}
+}
+
+struct Call<F> {
+    f: F,
+    called: bool,
+}
+
+impl<F: FnMut() + Send> Animation for Call<F> {
+    fn duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn seek(&mut self, _: Duration) {
+        if !self.called {
+            self.called = true;
+            (self.f)();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.called = false;
+    }
+}
+
+/// Animations played one after another.
+#[derive(Default)]
+pub struct Sequence {
+    steps: Vec<Box<dyn Animation>>,
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Sequence::default()
+    }
+
+    pub fn then(mut self, animation: impl Animation + 'static) -> Self {
+        self.steps.push(Box::new(animation));
+        self
+    }
+
+    pub fn delay(self, duration: Duration) -> Self {
+        self.then(Delay(duration))
+    }
+
+    /// Call the function once when the sequence reaches this point.
+    pub fn call(self, f: impl FnMut() + Send + 'static) -> Self {
+        self.then(Call { f, called: false })
+    }
+}
+
+impl Animation for Sequence {
+    fn duration(&self) -> Duration {
+        self.steps.iter().map(|s| s.duration()).sum()
+    }
+
+    fn seek(&mut self, time: Duration) {
+        let mut start = Duration::ZERO;
+        for step in &mut self.steps {
+            if time < start {
+                break;
+            }
+            step.seek(time - start);
+            start += step.duration();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.steps.iter_mut().for_each(|s| s.reset());
+    }
+}
+
+/// Animations played at the same time. The group lasts as long as its longest animation.
+#[derive(Default)]
+pub struct Parallel {
+    animations: Vec<Box<dyn Animation>>,
+}
+
+impl Parallel {
+    pub fn new() -> Self {
+        Parallel::default()
+    }
+
+    pub fn with(mut self, animation: impl Animation + 'static) -> Self {
+        self.animations.push(Box::new(animation));
+        self
+    }
+}
+
+impl Animation for Parallel {
+    fn duration(&self) -> Duration {
+        self.animations.iter().map(|a| a.duration()).max().unwrap_or_default()
+    }
+
+    fn seek(&mut self, time: Duration) {
+        self.animations.iter_mut().for_each(|a| a.seek(time));
+    }
+
+    fn reset(&mut self) {
+        self.animations.iter_mut().for_each(|a| a.reset());
+    }
+}
+
+/// Plays an [`Animation`] by advancing it with the frame time, with support for pausing, speed
+/// changes and repetition.
+///
+/// ```
+/// use mvutils::tween::{Easing, Sequence, Timeline, Tween};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// let alpha = Arc::new(Mutex::new(0.0f32));
+/// let target = alpha.clone();
+/// let mut timeline = Timeline::new(
+///     Sequence::new()
+///         .then(Tween::new(0.0, 1.0, Duration::from_millis(200)).easing(Easing::QuadOut).on_update(move |v| *target.lock().unwrap() = v))
+///         .delay(Duration::from_millis(100)),
+/// );
+///
+/// timeline.tick(Duration::from_millis(250));
+/// assert_eq!(*alpha.lock().unwrap(), 1.0);
+/// assert!(!timeline.is_finished());
+/// ```
+pub struct Timeline {
+    animation: Box<dyn Animation>,
+    elapsed: Duration,
+    speed: f32,
+    paused: bool,
+    repeat: Option<u32>,
+    iteration: u32,
+}
+
+impl Timeline {
+    pub fn new(animation: impl Animation + 'static) -> Self {
+        Timeline {
+            animation: Box::new(animation),
+            elapsed: Duration::ZERO,
+            speed: 1.0,
+            paused: false,
+            repeat: Some(0),
+            iteration: 0,
+        }
+    }
+
+    /// How often to repeat the animation after it first finished, or [`None`] to loop forever.
+    pub fn repeat(mut self, repeat: Option<u32>) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The time elapsed in the current iteration.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.animation.duration()
+    }
+
+    /// The progress of the current iteration from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let duration = self.duration();
+        if duration.is_zero() {
+            1.0
+        } else {
+            self.elapsed.as_secs_f32() / duration.as_secs_f32()
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.repeat.is_some_and(|r| self.iteration >= r) && self.elapsed >= self.duration()
+    }
+
+    /// Restart the animation from the beginning.
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.iteration = 0;
+        self.animation.reset();
+        self.animation.seek(Duration::ZERO);
+    }
+
+    /// Advance the animation, returning whether it is still running.
+    pub fn tick(&mut self, delta: Duration) -> bool {
+        if self.paused || self.is_finished() {
+            return !self.is_finished();
+        }
+        let duration = self.duration();
+        let mut remaining = delta.mul_f64(self.speed as f64);
+        loop {
+            let left = duration.saturating_sub(self.elapsed);
+            if remaining < left || self.repeat.is_some_and(|r| self.iteration >= r) || duration.is_zero() {
+                self.elapsed = (self.elapsed + remaining).min(duration);
+                self.animation.seek(self.elapsed);
+                break;
+            }
+            self.animation.seek(duration);
+            remaining -= left;
+            self.iteration += 1;
+            self.elapsed = Duration::ZERO;
+            self.animation.reset();
+        }
+        !self.is_finished()
+    }
+}
@@ -0,0 +1,179 @@
+use crate::save::{Loader, Savable, SaveError, Saver};
+
+/// A reversible change to a target, for use with an [`UndoStack`].
+pub trait Command {
+    type Target;
+
+    fn execute(&mut self, target: &mut Self::Target);
+
+    fn undo(&mut self, target: &mut Self::Target);
+
+    /// Try to absorb the next command, which was already executed, into this one so both are undone
+    /// at once, for example consecutive typed characters. Returns whether the command was merged.
+    fn merge(&mut self, _next: &Self) -> bool {
+        false
+    }
+}
+
+/// A history of executed [`Command`]s which can be undone and redone.
+///
+/// The stack tracks whether the target changed since it was last marked as clean, for example after
+/// saving a document.
+pub struct UndoStack<C> {
+    undo: Vec<C>,
+    redo: Vec<C>,
+    capacity: Option<usize>,
+    clean: Option<usize>,
+    merge: bool,
+}
+
+impl<C: Command> UndoStack<C> {
+    pub fn new() -> Self {
+        UndoStack {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            capacity: None,
+            clean: Some(0),
+            merge: true,
+        }
+    }
+
+    /// A stack which forgets the oldest commands once it holds more than `capacity` of them.
+    pub fn with_capacity(capacity: usize) -> Self {
+        UndoStack {
+            capacity: Some(capacity),
+            ..UndoStack::new()
+        }
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Execute the command and push it onto the stack, clearing all commands that could be redone.
+    pub fn execute(&mut self, mut command: C, target: &mut C::Target) {
+        command.execute(target);
+        if self.clean.is_some_and(|c| c > self.undo.len()) {
+            self.clean = None;
+        }
+        self.redo.clear();
+
+        let can_merge = self.merge && self.clean != Some(self.undo.len());
+        self.merge = true;
+        if can_merge {
+            if let Some(last) = self.undo.last_mut() {
+                if last.merge(&command) {
+                    return;
+                }
+            }
+        }
+
+        self.undo.push(command);
+        if self.capacity.is_some_and(|c| self.undo.len() > c) {
+            self.undo.remove(0);
+            self.clean = match self.clean {
+                Some(0) | None => None,
+                Some(c) => Some(c - 1),
+            };
+        }
+    }
+
+    /// Undo the last command, returning whether there was one.
+    pub fn undo(&mut self, target: &mut C::Target) -> bool {
+        match self.undo.pop() {
+            Some(mut command) => {
+                command.undo(target);
+                self.redo.push(command);
+                self.merge = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone command, returning whether there was one.
+    pub fn redo(&mut self, target: &mut C::Target) -> bool {
+        match self.redo.pop() {
+            Some(mut command) => {
+                command.execute(target);
+                self.undo.push(command);
+                self.merge = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The command that would be undone next.
+    pub fn peek_undo(&self) -> Option<&C> {
+        self.undo.last()
+    }
+
+    /// The command that would be redone next.
+    pub fn peek_redo(&self) -> Option<&C> {
+        self.redo.last()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Prevent the next executed command from being merged into the previous one.
+    pub fn break_merge(&mut self) {
+        self.merge = false;
+    }
+
+    /// Whether the target changed since the stack was last marked as clean.
+    pub fn is_dirty(&self) -> bool {
+        self.clean != Some(self.undo.len())
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.clean = Some(self.undo.len());
+    }
+
+    /// Forget all commands. The current state of the target is considered clean.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.clean = Some(0);
+        self.merge = true;
+    }
+}
+
+impl<C: Command> Default for UndoStack<C> {
+    fn default() -> Self {
+        UndoStack::new()
+    }
+}
+
+impl<C: Savable> Savable for UndoStack<C> {
+    fn save(&self, saver: &mut impl Saver) {
+        self.undo.save(saver);
+        self.redo.save(saver);
+        self.capacity.map(|c| c as u64).save(saver);
+        self.clean.map(|c| c as u64).save(saver);
+    }
+
+    fn load(loader: &mut impl Loader) -> Result<Self, SaveError> {
+        Ok(UndoStack {
+            undo: Vec::load(loader)?,
+            redo: Vec::load(loader)?,
+            capacity: Option::<u64>::load(loader)?.map(|c| c as usize),
+            clean: Option::<u64>::load(loader)?.map(|c| c as usize),
+            merge: false,
+        })
+    }
+}
@@ -0,0 +1,233 @@
+//! Typed units for ticks and time, so frame counters and durations cannot be mixed up.
+//!
+//! ```
+//! use mvutils::units::{Millis, TickRate, Ticks};
+//!
+//! let rate = TickRate::per_second(20.0);
+//! assert_eq!(Ticks(40).to_millis(rate), Millis(2000));
+//! assert_eq!(Millis(175).to_ticks(rate), Ticks(3));
+//! assert_eq!(Ticks(3) + Ticks(4), Ticks(7));
+//! ```
+
+use crate as mvutils;
+use mvutils_proc_macro::Savable;
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Rem, Sub, SubAssign};
+use std::time::Duration;
+
+macro_rules! unit {
+    ($(#[$meta:meta])* $name:ident, $suffix:literal) => {
+        $(#[$meta])*
+        #[derive(Savable, Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        pub struct $name(pub u64);
+
+        impl $name {
+            pub const ZERO: $name = $name(0);
+
+            pub const fn get(self) -> u64 {
+                self.0
+            }
+
+            pub const fn checked_sub(self, rhs: $name) -> Option<$name> {
+                match self.0.checked_sub(rhs.0) {
+                    Some(value) => Some($name(value)),
+                    None => None,
+                }
+            }
+
+            pub const fn saturating_sub(self, rhs: $name) -> $name {
+                $name(self.0.saturating_sub(rhs.0))
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, rhs: $name) -> $name {
+                $name(self.0 + rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: $name) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, rhs: $name) -> $name {
+                $name(self.0 - rhs.0)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: $name) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Mul<u64> for $name {
+            type Output = $name;
+
+            fn mul(self, rhs: u64) -> $name {
+                $name(self.0 * rhs)
+            }
+        }
+
+        impl Div<u64> for $name {
+            type Output = $name;
+
+            fn div(self, rhs: u64) -> $name {
+                $name(self.0 / rhs)
+            }
+        }
+
+        /// How many times `rhs` fits into the value.
+        impl Div for $name {
+            type Output = u64;
+
+            fn div(self, rhs: $name) -> u64 {
+                self.0 / rhs.0
+            }
+        }
+
+        impl Rem for $name {
+            type Output = $name;
+
+            fn rem(self, rhs: $name) -> $name {
+                $name(self.0 % rhs.0)
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = $name>>(iter: I) -> $name {
+                $name(iter.map(|v| v.0).sum())
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, concat!("{}", $suffix), self.0)
+            }
+        }
+    };
+}
+
+unit!(
+    /// A number of ticks, converted to and from time with a [`TickRate`].
+    Ticks, " ticks"
+);
+
+unit!(
+    /// A number of milliseconds.
+    Millis, "ms"
+);
+
+unit!(
+    /// A number of microseconds.
+    Micros, "µs"
+);
+
+impl Ticks {
+    /// The time these ticks take at the given rate, rounded to the nearest millisecond.
+    pub fn to_millis(self, rate: TickRate) -> Millis {
+        Millis((self.0 as f64 * 1000.0 / rate.0).round() as u64)
+    }
+
+    /// The time these ticks take at the given rate, rounded to the nearest microsecond.
+    pub fn to_micros(self, rate: TickRate) -> Micros {
+        Micros((self.0 as f64 * 1_000_000.0 / rate.0).round() as u64)
+    }
+
+    pub fn to_duration(self, rate: TickRate) -> Duration {
+        Duration::from_secs_f64(self.0 as f64 / rate.0)
+    }
+}
+
+impl Millis {
+    /// The number of whole ticks in this time at the given rate.
+    pub fn to_ticks(self, rate: TickRate) -> Ticks {
+        rate.ticks_in(Duration::from(self))
+    }
+}
+
+impl Micros {
+    /// The number of whole ticks in this time at the given rate.
+    pub fn to_ticks(self, rate: TickRate) -> Ticks {
+        rate.ticks_in(Duration::from(self))
+    }
+}
+
+impl From<Millis> for Micros {
+    fn from(value: Millis) -> Self {
+        Micros(value.0 * 1000)
+    }
+}
+
+/// Truncates to whole milliseconds.
+impl From<Micros> for Millis {
+    fn from(value: Micros) -> Self {
+        Millis(value.0 / 1000)
+    }
+}
+
+impl From<Millis> for Duration {
+    fn from(value: Millis) -> Self {
+        Duration::from_millis(value.0)
+    }
+}
+
+impl From<Micros> for Duration {
+    fn from(value: Micros) -> Self {
+        Duration::from_micros(value.0)
+    }
+}
+
+/// Truncates to whole milliseconds.
+impl From<Duration> for Millis {
+    fn from(value: Duration) -> Self {
+        Millis(value.as_millis() as u64)
+    }
+}
+
+/// Truncates to whole microseconds.
+impl From<Duration> for Micros {
+    fn from(value: Duration) -> Self {
+        Micros(value.as_micros() as u64)
+    }
+}
+
+/// The number of ticks per second, used to convert between [`Ticks`] and time.
+#[derive(Savable, Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct TickRate(f64);
+
+impl TickRate {
+    /// # Panics
+    /// If the rate is not positive and finite.
+    pub fn per_second(ticks: f64) -> Self {
+        assert!(ticks > 0.0 && ticks.is_finite(), "Tick rate must be positive, got {}", ticks);
+        TickRate(ticks)
+    }
+
+    /// The rate at which one tick takes the given duration.
+    pub fn from_interval(interval: Duration) -> Self {
+        TickRate::per_second(1.0 / interval.as_secs_f64())
+    }
+
+    pub fn ticks_per_second(self) -> f64 {
+        self.0
+    }
+
+    pub fn interval(self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.0)
+    }
+
+    /// The number of whole ticks in the given time.
+    pub fn ticks_in(self, time: Duration) -> Ticks {
+        // The epsilon keeps exact multiples of the interval from being rounded down a tick.
+        Ticks((time.as_secs_f64() * self.0 + 1e-9).floor() as u64)
+    }
+}
@@ -2,14 +2,61 @@ use std::alloc::Layout;
 use std::cell::UnsafeCell;
 use std::ffi::c_void;
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+/// A value whose [`UnsafeRef`]s detect when it was dropped. With the `unsafe_debug` feature in debug
+/// builds, dereferencing an [`UnsafeRef`] created using [`UnsafeRef::tracked`] after the value was
+/// dropped panics instead of reading freed memory. Otherwise, this is a plain wrapper around the value.
+///
+/// The value must not be moved while references to it exist, so it is usually boxed or stored in a
+/// collection that is not resized.
+pub struct Tracked<T> {
+    value: T,
+    #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+    canary: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl<T> Tracked<T> {
+    pub fn new(value: T) -> Self {
+        Tracked {
+            value,
+            #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+            canary: Arc::new(std::sync::atomic::AtomicBool::new(true)),
+        }
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+#[cfg(all(feature = "unsafe_debug", debug_assertions))]
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        self.canary.store(false, Ordering::Release);
+    }
+}
+
 pub struct UnsafeRef<T> {
     ptr: *mut c_void,
     phantom: PhantomData<T>,
+    #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+    canary: Option<Arc<std::sync::atomic::AtomicBool>>,
 }
 
 impl<T> UnsafeRef<T> {
@@ -23,6 +70,33 @@ impl<T> UnsafeRef<T> {
         Self {
             ptr: data as *const T as *mut c_void,
             phantom: PhantomData,
+            #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+            canary: None,
+        }
+    }
+
+    /// Initialize an [`UnsafeRef<T>`] pointing to a tracked value. With the `unsafe_debug` feature
+    /// in debug builds, using the reference after the value was dropped panics.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the user to ensure that the tracked value is not moved while this reference is used.
+    pub unsafe fn tracked(data: &Tracked<T>) -> Self {
+        Self {
+            ptr: &data.value as *const T as *mut c_void,
+            phantom: PhantomData,
+            #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+            canary: Some(data.canary.clone()),
+        }
+    }
+
+    #[inline(always)]
+    fn check(&self) {
+        #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+        if let Some(canary) = &self.canary {
+            if !canary.load(Ordering::Acquire) {
+                panic!("Use after free: UnsafeRef<{}> points to a value that has been dropped!", std::any::type_name::<T>());
+            }
         }
     }
 
@@ -36,6 +110,8 @@ impl<T> UnsafeRef<T> {
         Self {
             ptr: std::ptr::null_mut(),
             phantom: PhantomData,
+            #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+            canary: None,
         }
     }
 
@@ -54,6 +130,8 @@ impl<T> UnsafeRef<T> {
         UnsafeRef {
             ptr: self.ptr,
             phantom: PhantomData,
+            #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+            canary: self.canary.clone(),
         }
     }
 
@@ -68,6 +146,7 @@ impl<T> UnsafeRef<T> {
     /// It is entirely up to the user to ensure that the pointer is valid , and will remain valid for
     /// the rest of the program.
     pub unsafe fn as_static(&self) -> &'static T {
+        self.check();
         (self.ptr as *const T)
             .as_ref()
             .expect("Failed to dereference UnsafeRef, perhaps the value has been dropped.")
@@ -80,6 +159,7 @@ impl<T> UnsafeRef<T> {
     /// It is entirely up to the user to ensure that the pointer is valid , and will remain valid for
     /// the rest of the program.
     pub unsafe fn as_static_mut(&mut self) -> &'static mut T {
+        self.check();
         (self.ptr as *mut T)
             .as_mut()
             .expect("Failed to dereference UnsafeRef, perhaps the value has been dropped.")
@@ -96,6 +176,7 @@ impl<T> Deref for UnsafeRef<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
+        self.check();
         unsafe {
             (self.ptr as *const T)
                 .as_ref()
@@ -106,6 +187,7 @@ impl<T> Deref for UnsafeRef<T> {
 
 impl<T> DerefMut for UnsafeRef<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.check();
         unsafe {
             (self.ptr as *mut T)
                 .as_mut()
@@ -119,6 +201,8 @@ impl<T> Clone for UnsafeRef<T> {
         Self {
             ptr: self.ptr,
             phantom: PhantomData,
+            #[cfg(all(feature = "unsafe_debug", debug_assertions))]
+            canary: self.canary.clone(),
         }
     }
 }
@@ -543,6 +627,35 @@ impl Unsafe {
             ptr.as_mut().unwrap()
         }
     }
+
+    /// View the bytes of the value.
+    ///
+    /// # Safety
+    /// The type [`T`] must not contain padding bytes, as reading them is undefined behaviour.
+    pub unsafe fn as_bytes<T>(value: &T) -> &[u8] {
+        std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>())
+    }
+
+    /// Reinterpret the bytes as a value of type [`T`], returning an error if the length does not
+    /// match the size of [`T`] or the bytes are not aligned for it.
+    ///
+    /// # Safety
+    /// It is entirely up to the user to ensure that the bytes are a valid value of type [`T`].
+    pub unsafe fn from_bytes<T>(bytes: &[u8]) -> Result<&T, String> {
+        check_cast::<T>(bytes, false)?;
+        Ok(&*(bytes.as_ptr() as *const T))
+    }
+
+    /// Reinterpret the bytes as a slice of values of type [`T`], returning an error if the length is
+    /// not a multiple of the size of [`T`] or the bytes are not aligned for it.
+    ///
+    /// # Safety
+    /// It is entirely up to the user to ensure that the bytes are valid values of type [`T`].
+    pub unsafe fn slice_from_bytes<T>(bytes: &[u8]) -> Result<&[T], String> {
+        check_cast::<T>(bytes, true)?;
+        let len = if size_of::<T>() == 0 { 0 } else { bytes.len() / size_of::<T>() };
+        Ok(std::slice::from_raw_parts(bytes.as_ptr() as *const T, len))
+    }
 }
 
 pub struct UnsafeRc<T> {
@@ -728,6 +841,8 @@ unsafe impl<T: Send> Send for UnsafeArc<T> {}
 
 unsafe impl<T: Sync> Sync for UnsafeArc<T> {}
 
+/// A cell allowing shared mutable access without any synchronization or borrow checking. It is up
+/// to the user to make sure that no reference obtained from it is used while the value is changed.
 #[repr(transparent)]
 pub struct DangerousCell<T> {
     inner: UnsafeCell<T>,
@@ -752,17 +867,47 @@ impl<T> DangerousCell<T> {
         unsafe { self.inner.get().as_mut().unwrap() }
     }
 
+    /// Set the value, dropping the previous one.
     #[inline(always)]
-    pub fn replace(&self, value: T) {
-        unsafe {
-            self.inner.get().write(value);
-        }
+    pub fn set(&self, value: T) {
+        *self.get_mut() = value;
+    }
+
+    /// Set the value, returning the previous one.
+    #[inline(always)]
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Change the value in place, returning the result of the function.
+    #[inline(always)]
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(self.get_mut())
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: Default> DangerousCell<T> {
+    /// Take the value, leaving the default value in its place.
+    #[inline(always)]
+    pub fn take(&self) -> T {
+        self.replace(T::default())
     }
 }
 
 impl<T: Copy> DangerousCell<T> {
     #[inline(always)]
     pub fn get_val(&self) -> T {
+        self.get_copy_unsync()
+    }
+
+    /// Copy the value out of the cell. The read is not synchronized, so when another thread writes
+    /// to the cell at the same time, the result is undefined behaviour and may be a torn value.
+    #[inline(always)]
+    pub fn get_copy_unsync(&self) -> T {
         unsafe { *self.inner.get() }
     }
 }
@@ -773,6 +918,327 @@ impl<T> From<T> for DangerousCell<T> {
     }
 }
 
+impl<T: Default> Default for DangerousCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for DangerousCell<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.get().clone())
+    }
+}
+
+impl<T: Debug> Debug for DangerousCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DangerousCell").field(self.get()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DangerousCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Eq> Eq for DangerousCell<T> {}
+
+impl<T: PartialOrd> PartialOrd for DangerousCell<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.get().partial_cmp(other.get())
+    }
+}
+
+impl<T: Ord> Ord for DangerousCell<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.get().cmp(other.get())
+    }
+}
+
+impl<T: Hash> Hash for DangerousCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
+
+/// Types that are valid for any bit pattern and contain no padding bytes, so they can be safely
+/// viewed as bytes and created from them. Use `#[derive(Pod)]` for structs, which checks that all
+/// fields are [`Pod`] and that the struct has no padding.
+///
+/// # Safety
+/// The type must be [`Copy`], have no padding bytes, contain no pointers or references, and every
+/// bit pattern must be a valid value.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl Pod for $t {}
+        )*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// View the bytes of the value.
+pub fn bytes_of<T: Pod>(value: &T) -> &[u8] {
+    unsafe { Unsafe::as_bytes(value) }
+}
+
+/// View the bytes of the value mutably.
+pub fn bytes_of_mut<T: Pod>(value: &mut T) -> &mut [u8] {
+    unsafe { std::slice::from_raw_parts_mut(value as *mut T as *mut u8, size_of::<T>()) }
+}
+
+/// Reinterpret the bytes as a value, returning an error if the length does not match the size of
+/// [`T`] or the bytes are not aligned for it. Use [`read_pod`] for unaligned data.
+pub fn from_bytes<T: Pod>(bytes: &[u8]) -> Result<&T, String> {
+    unsafe { Unsafe::from_bytes(bytes) }
+}
+
+/// Copy a value out of the bytes, which do not need to be aligned.
+pub fn read_pod<T: Pod>(bytes: &[u8]) -> Result<T, String> {
+    if bytes.len() != size_of::<T>() {
+        return Err(format!("{} bytes cannot be viewed as {}", bytes.len(), std::any::type_name::<T>()));
+    }
+    Ok(unsafe { (bytes.as_ptr() as *const T).read_unaligned() })
+}
+
+/// Reinterpret a slice as a slice of another type, returning an error if the size in bytes is not
+/// a multiple of the size of [`B`] or the data is not aligned for it.
+///
+/// ```
+/// use mvutils::unsafe_utils::cast_slice;
+///
+/// let vertices = [[0.0f32, 1.0], [2.0, 3.0]];
+/// let floats: &[f32] = cast_slice(&vertices).unwrap();
+/// assert_eq!(floats, &[0.0, 1.0, 2.0, 3.0]);
+/// ```
+pub fn cast_slice<A: Pod, B: Pod>(slice: &[A]) -> Result<&[B], String> {
+    let bytes = unsafe { std::slice::from_raw_parts(slice.as_ptr() as *const u8, std::mem::size_of_val(slice)) };
+    unsafe { Unsafe::slice_from_bytes(bytes) }
+}
+
+/// Reinterpret a mutable slice as a slice of another type, returning an error if the size in bytes
+/// is not a multiple of the size of [`B`] or the data is not aligned for it.
+pub fn cast_slice_mut<A: Pod, B: Pod>(slice: &mut [A]) -> Result<&mut [B], String> {
+    let len = std::mem::size_of_val(slice);
+    let bytes = unsafe { std::slice::from_raw_parts(slice.as_ptr() as *const u8, len) };
+    check_cast::<B>(bytes, true)?;
+    let len = if size_of::<B>() == 0 { 0 } else { len / size_of::<B>() };
+    Ok(unsafe { std::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut B, len) })
+}
+
+/// A value with all bytes set to zero.
+pub fn zeroed<T: Pod>() -> T {
+    unsafe { std::mem::zeroed() }
+}
+
+fn check_cast<T>(bytes: &[u8], slice: bool) -> Result<(), String> {
+    let size = size_of::<T>();
+    let fits = if slice { size == 0 || bytes.len() % size == 0 } else { bytes.len() == size };
+    if !fits {
+        return Err(format!("{} bytes cannot be viewed as {}{}", bytes.len(), if slice { "a slice of " } else { "" }, std::any::type_name::<T>()));
+    }
+    if bytes.as_ptr() as usize % align_of::<T>() != 0 {
+        return Err(format!("Bytes are not aligned to {} bytes for {}", align_of::<T>(), std::any::type_name::<T>()));
+    }
+    Ok(())
+}
+
+/// A heap allocated value aligned to at least `ALIGN` bytes, for example for memory that is
+/// uploaded to the GPU.
+///
+/// # Panics
+/// When created, if `ALIGN` is not a power of two.
+pub struct AlignedBox<T, const ALIGN: usize> {
+    ptr: *mut T,
+}
+
+impl<T, const ALIGN: usize> AlignedBox<T, ALIGN> {
+    fn layout() -> Layout {
+        Layout::from_size_align(size_of::<T>().max(1), ALIGN.max(align_of::<T>()))
+            .unwrap_or_else(|_| panic!("Invalid alignment {ALIGN} for AlignedBox, it must be a power of two!"))
+    }
+
+    pub fn new(value: T) -> Self {
+        let layout = Self::layout();
+        unsafe {
+            let ptr = std::alloc::alloc(layout) as *mut T;
+            if ptr.is_null() {
+                std::alloc::handle_alloc_error(layout);
+            }
+            ptr.write(value);
+            AlignedBox { ptr }
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
+    }
+
+    pub fn into_inner(self) -> T {
+        let this = std::mem::ManuallyDrop::new(self);
+        unsafe {
+            let value = this.ptr.read();
+            std::alloc::dealloc(this.ptr as *mut u8, Self::layout());
+            value
+        }
+    }
+}
+
+impl<T: Default, const ALIGN: usize> Default for AlignedBox<T, ALIGN> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, const ALIGN: usize> Deref for AlignedBox<T, ALIGN> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl<T, const ALIGN: usize> DerefMut for AlignedBox<T, ALIGN> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.ptr }
+    }
+}
+
+impl<T: Debug, const ALIGN: usize> Debug for AlignedBox<T, ALIGN> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T, const ALIGN: usize> Drop for AlignedBox<T, ALIGN> {
+    fn drop(&mut self) {
+        unsafe {
+            self.ptr.drop_in_place();
+            std::alloc::dealloc(self.ptr as *mut u8, Self::layout());
+        }
+    }
+}
+
+unsafe impl<T: Send, const ALIGN: usize> Send for AlignedBox<T, ALIGN> {}
+
+unsafe impl<T: Sync, const ALIGN: usize> Sync for AlignedBox<T, ALIGN> {}
+
+/// A zero initialized, aligned heap allocation of raw bytes, which is freed when dropped.
+///
+/// ```
+/// use mvutils::unsafe_utils::RawBuffer;
+///
+/// let mut buffer = RawBuffer::alloc(64, 256);
+/// assert_eq!(buffer.as_ptr() as usize % 256, 0);
+/// unsafe { buffer.as_mut_slice::<f32>() }.unwrap()[0] = 1.0;
+/// assert_eq!(&buffer.as_bytes()[..4], &1.0f32.to_ne_bytes());
+/// ```
+pub struct RawBuffer {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl RawBuffer {
+    /// # Panics
+    /// If the alignment is not a power of two.
+    pub fn alloc(len: usize, align: usize) -> Self {
+        let layout = Layout::from_size_align(len, align)
+            .unwrap_or_else(|_| panic!("Invalid alignment {align} for RawBuffer, it must be a power of two!"));
+        let ptr = if len == 0 {
+            align as *mut u8
+        } else {
+            let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+            if ptr.is_null() {
+                std::alloc::handle_alloc_error(layout);
+            }
+            ptr
+        };
+        RawBuffer { ptr, layout }
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.size() == 0
+    }
+
+    pub fn align(&self) -> usize {
+        self.layout.align()
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len()) }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len()) }
+    }
+
+    /// View the buffer as a slice of [`Pod`] values, returning an error if the length is not a
+    /// multiple of the size of [`T`] or the buffer is not aligned for it.
+    pub fn view<T: Pod>(&self) -> Result<&[T], String> {
+        unsafe { self.as_slice() }
+    }
+
+    /// View the buffer as a mutable slice of [`Pod`] values, returning an error if the length is not
+    /// a multiple of the size of [`T`] or the buffer is not aligned for it.
+    pub fn view_mut<T: Pod>(&mut self) -> Result<&mut [T], String> {
+        unsafe { self.as_mut_slice() }
+    }
+
+    /// View the buffer as a slice of [`T`], returning an error if the length is not a multiple of the
+    /// size of [`T`] or the buffer is not aligned for it.
+    ///
+    /// # Safety
+    /// It is entirely up to the user to ensure that the bytes are valid values of type [`T`].
+    pub unsafe fn as_slice<T>(&self) -> Result<&[T], String> {
+        Unsafe::slice_from_bytes(self.as_bytes())
+    }
+
+    /// View the buffer as a mutable slice of [`T`], returning an error if the length is not a multiple
+    /// of the size of [`T`] or the buffer is not aligned for it.
+    ///
+    /// # Safety
+    /// It is entirely up to the user to ensure that the bytes are valid values of type [`T`].
+    pub unsafe fn as_mut_slice<T>(&mut self) -> Result<&mut [T], String> {
+        check_cast::<T>(self.as_bytes(), true)?;
+        let len = if size_of::<T>() == 0 { 0 } else { self.len() / size_of::<T>() };
+        Ok(std::slice::from_raw_parts_mut(self.ptr as *mut T, len))
+    }
+}
+
+impl Drop for RawBuffer {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { std::alloc::dealloc(self.ptr, self.layout) };
+        }
+    }
+}
+
+unsafe impl Send for RawBuffer {}
+
+unsafe impl Sync for RawBuffer {}
+
 /// A copy of the [`From<T>`] trait, but for types where this operation is unsafe.
 ///
 /// # Safety
@@ -1,7 +1,9 @@
 use crate::lazy;
+use crate::platform;
 use num_traits::One;
-use parking_lot::Mutex;
-use std::collections::HashMap;
+use crate::sync::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display, Formatter};
 use std::ops::Range;
 use std::ops::{Add, AddAssign, Div, Mul, Rem, Sub, SubAssign};
 use std::panic::PanicHookInfo;
@@ -216,7 +218,7 @@ pub trait Time {
 
 impl Time for u128 {
     fn time_millis() -> Self {
-        SystemTime::now()
+        platform::system_now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|e| {
                 panic!(
@@ -228,7 +230,7 @@ impl Time for u128 {
     }
 
     fn time_nanos() -> Self {
-        SystemTime::now()
+        platform::system_now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|e| {
                 panic!(
@@ -312,6 +314,67 @@ macro_rules! enum_val_ref {
     };
 }
 
+/// Assert a condition at compile time, failing the build if it does not hold.
+///
+/// ```
+/// mvutils::const_assert!(u64::BITS == 64);
+/// mvutils::const_assert!(usize::MAX as u128 >= u32::MAX as u128, "32 bit targets and up are supported");
+/// ```
+#[macro_export]
+macro_rules! const_assert {
+    ($cond:expr $(,)?) => {
+        const _: () = ::core::assert!($cond, concat!("Static assertion failed: ", stringify!($cond)));
+    };
+    ($cond:expr, $msg:literal $(,)?) => {
+        const _: () = ::core::assert!($cond, $msg);
+    };
+}
+
+/// Assert the size of a type in bytes at compile time.
+///
+/// ```compile_fail
+/// mvutils::assert_size_of!(u32, 8);
+/// ```
+#[macro_export]
+macro_rules! assert_size_of {
+    ($t:ty, $size:expr $(,)?) => {
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<$t>() == $size,
+            concat!("The size of ", stringify!($t), " is not ", stringify!($size), " bytes")
+        );
+    };
+}
+
+/// Assert the alignment of a type in bytes at compile time.
+#[macro_export]
+macro_rules! assert_align_of {
+    ($t:ty, $align:expr $(,)?) => {
+        const _: () = ::core::assert!(
+            ::core::mem::align_of::<$t>() == $align,
+            concat!("The alignment of ", stringify!($t), " is not ", stringify!($align), " bytes")
+        );
+    };
+}
+
+/// Assert that a type implements traits at compile time.
+///
+/// ```
+/// mvutils::assert_impl!(String: Send + Sync + Clone);
+/// ```
+///
+/// ```compile_fail
+/// mvutils::assert_impl!(std::rc::Rc<u8>: Send);
+/// ```
+#[macro_export]
+macro_rules! assert_impl {
+    ($t:ty: $($bound:tt)+) => {
+        const _: fn() = || {
+            fn assert_impl<T: ?Sized + $($bound)+>() {}
+            assert_impl::<$t>();
+        };
+    };
+}
+
 pub trait SplitSized {
     fn split_sized(&self, n: usize) -> Vec<Self>
     where
@@ -336,8 +399,127 @@ impl SplitSized for String {
 
 pub type Bytecode = Vec<u8>;
 
+/// A problem found when verifying a value, at the path of the field it was found in.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    /// The path of the field, like `players.0.health`, or empty for the verified value itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(path: &str, message: impl Into<String>) -> Self {
+        Violation {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// All problems found by [`Verify::report`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifyReport(pub Vec<Violation>);
+
+impl VerifyReport {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Violation> {
+        self.0.iter()
+    }
+
+    /// The first problem found in the field at the path.
+    pub fn get(&self, path: &str) -> Option<&Violation> {
+        self.0.iter().find(|v| v.path == path)
+    }
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for violation in &self.0 {
+            writeln!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VerifyReport {}
+
+/// Checking that a value is in a legal state. Can be derived with `#[derive(Verify)]`, which
+/// verifies every field and supports these field attributes:
+///
+/// - `#[verify(skip)]` to not verify the field
+/// - `#[verify(range = 0..=100)]` to require the field to be in the range
+/// - `#[verify(with = path::to::function)]` to check the field with a `fn(&T) -> Result<(), String>`
+///
+/// `#[verify(with = ...)]` can also be put on the type itself to check the whole value.
+///
+/// ```
+/// use mvutils::utils::Verify;
+/// use mvutils::Verify;
+///
+/// fn not_empty(name: &String) -> Result<(), String> {
+///     if name.is_empty() {
+///         Err("name is empty".to_string())
+///     } else {
+///         Ok(())
+///     }
+/// }
+///
+/// #[derive(Verify)]
+/// struct Player {
+///     #[verify(with = not_empty)]
+///     name: String,
+///     #[verify(range = 0..=100)]
+///     health: u32,
+/// }
+///
+/// #[derive(Verify)]
+/// struct Team {
+///     players: Vec<Player>,
+/// }
+///
+/// let team = Team { players: vec![Player { name: String::new(), health: 120 }] };
+/// let report = team.report().unwrap_err();
+/// assert_eq!(report.to_string(), "players[0].name: name is empty\nplayers[0].health: 120 is not in the range 0 ..= 100\n");
+/// ```
 pub trait Verify {
     fn verify(&self) -> bool;
+
+    /// Add the problems with this value to `violations`, where `path` is the path of this value.
+    /// By default, this adds a single problem if [`Verify::verify`] returns false.
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        if !self.verify() {
+            violations.push(Violation::new(path, "verification failed"));
+        }
+    }
+
+    /// Verify the value, returning all problems found.
+    fn report(&self) -> Result<(), VerifyReport> {
+        let mut violations = Vec::new();
+        self.verify_into("", &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(VerifyReport(violations))
+        }
+    }
+
     fn verify_or_panic(&self, message: &str) {
         if !self.verify() {
             panic!("{}", message);
@@ -351,6 +533,129 @@ pub trait Verify {
     }
 }
 
+macro_rules! impl_verify_valid {
+    ($($t:ty),*) => {
+        $(
+            impl Verify for $t {
+                fn verify(&self) -> bool {
+                    true
+                }
+            }
+        )*
+    };
+}
+
+impl_verify_valid!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, String, str, Duration, ());
+
+impl<T: Verify + ?Sized> Verify for &T {
+    fn verify(&self) -> bool {
+        (**self).verify()
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        (**self).verify_into(path, violations);
+    }
+}
+
+impl<T: Verify + ?Sized> Verify for Box<T> {
+    fn verify(&self) -> bool {
+        (**self).verify()
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        (**self).verify_into(path, violations);
+    }
+}
+
+impl<T: Verify> Verify for Option<T> {
+    fn verify(&self) -> bool {
+        self.as_ref().map_or(true, T::verify)
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        if let Some(value) = self {
+            value.verify_into(path, violations);
+        }
+    }
+}
+
+impl<T: Verify> Verify for [T] {
+    fn verify(&self) -> bool {
+        self.iter().all(T::verify)
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        for (i, value) in self.iter().enumerate() {
+            value.verify_into(&format!("{}[{}]", path, i), violations);
+        }
+    }
+}
+
+impl<T: Verify> Verify for Vec<T> {
+    fn verify(&self) -> bool {
+        self.as_slice().verify()
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        self.as_slice().verify_into(path, violations);
+    }
+}
+
+impl<T: Verify, const N: usize> Verify for [T; N] {
+    fn verify(&self) -> bool {
+        self.as_slice().verify()
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        self.as_slice().verify_into(path, violations);
+    }
+}
+
+impl<K: Debug, V: Verify> Verify for BTreeMap<K, V> {
+    fn verify(&self) -> bool {
+        self.values().all(V::verify)
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        for (key, value) in self {
+            value.verify_into(&format!("{}[{:?}]", path, key), violations);
+        }
+    }
+}
+
+impl<K: Debug, V: Verify> Verify for HashMap<K, V> {
+    fn verify(&self) -> bool {
+        self.values().all(V::verify)
+    }
+
+    fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        for (key, value) in self {
+            value.verify_into(&format!("{}[{:?}]", path, key), violations);
+        }
+    }
+}
+
+macro_rules! impl_verify_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Verify),+> Verify for ($($name,)+) {
+            fn verify(&self) -> bool {
+                $(self.$index.verify())&&+
+            }
+
+            fn verify_into(&self, path: &str, violations: &mut Vec<Violation>) {
+                $(self.$index.verify_into(&crate::diff::join(path, stringify!($index)), violations);)+
+            }
+        }
+    };
+}
+
+impl_verify_tuple!(A 0);
+impl_verify_tuple!(A 0, B 1);
+impl_verify_tuple!(A 0, B 1, C 2);
+impl_verify_tuple!(A 0, B 1, C 2, D 3);
+impl_verify_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_verify_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+
 #[macro_export]
 macro_rules! sealable {
     () => {
@@ -708,6 +1013,88 @@ impl<T> Recover<T> for LockResult<T> {
     }
 }
 
+fn log_recovered(error: &dyn std::fmt::Debug, location: &std::panic::Location) {
+    let thread = std::thread::current().name().unwrap_or("unknown").to_string();
+    eprintln!("Thread '{}' recovered from error at {}: {:?}", thread, location, error);
+}
+
+/// Error tolerant combinators for [`Result`], which log the error to the standard error output
+/// instead of propagating it.
+pub trait RecoverExt<T, E> {
+    /// Convert into an [`Option`], logging the error.
+    fn ok_or_log(self) -> Option<T>;
+
+    /// Unwrap the value or return the default, logging the error.
+    fn unwrap_or_default_log(self) -> T
+    where
+        T: Default;
+
+    /// Unwrap the value or compute one from the error, logging the error.
+    fn recover_with(self, f: impl FnOnce(E) -> T) -> T;
+}
+
+impl<T, E: std::fmt::Debug> RecoverExt<T, E> for Result<T, E> {
+    #[track_caller]
+    fn ok_or_log(self) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(e) => {
+                log_recovered(&e, std::panic::Location::caller());
+                None
+            }
+        }
+    }
+
+    #[track_caller]
+    fn unwrap_or_default_log(self) -> T
+    where
+        T: Default,
+    {
+        self.ok_or_log().unwrap_or_default()
+    }
+
+    #[track_caller]
+    fn recover_with(self, f: impl FnOnce(E) -> T) -> T {
+        match self {
+            Ok(value) => value,
+            Err(e) => {
+                log_recovered(&e, std::panic::Location::caller());
+                f(e)
+            }
+        }
+    }
+}
+
+/// Access the guard of a lock regardless of poisoning. Implemented for std [`LockResult`]s and
+/// parking_lot guards (which never poison), so code can switch between both lock types.
+pub trait IgnorePoison<T> {
+    fn ignore_poison(self) -> T;
+}
+
+impl<T> IgnorePoison<T> for LockResult<T> {
+    fn ignore_poison(self) -> T {
+        self.unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<'a, T: ?Sized> IgnorePoison<parking_lot::MutexGuard<'a, T>> for parking_lot::MutexGuard<'a, T> {
+    fn ignore_poison(self) -> parking_lot::MutexGuard<'a, T> {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> IgnorePoison<parking_lot::RwLockReadGuard<'a, T>> for parking_lot::RwLockReadGuard<'a, T> {
+    fn ignore_poison(self) -> parking_lot::RwLockReadGuard<'a, T> {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> IgnorePoison<parking_lot::RwLockWriteGuard<'a, T>> for parking_lot::RwLockWriteGuard<'a, T> {
+    fn ignore_poison(self) -> parking_lot::RwLockWriteGuard<'a, T> {
+        self
+    }
+}
+
 pub trait RwUnchecked<T> {
     fn read_unchecked(&self) -> RwLockReadGuard<T>;
     fn write_unchecked(&self) -> RwLockWriteGuard<T>;
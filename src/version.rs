@@ -1,15 +1,19 @@
 use crate as mvutils;
-use mvutils_proc_macro::Savable;
+use mvutils_proc_macro::{Getters, Savable, Setters};
 use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
-#[derive(Eq, PartialEq, Copy, Clone, Savable)]
+#[derive(Eq, PartialEq, Copy, Clone, Savable, Getters, Setters)]
 pub struct Version {
+    #[getter(copy)]
     variant: u16,
+    #[getter(copy)]
     major: u16,
+    #[getter(copy)]
     minor: u16,
+    #[getter(copy)]
     patch: u16,
 }
 
@@ -90,38 +94,6 @@ impl Version {
             | ((self.minor as u32) << 12)
             | (self.patch as u32)
     }
-
-    pub fn variant(&self) -> u16 {
-        self.variant
-    }
-
-    pub fn major(&self) -> u16 {
-        self.major
-    }
-
-    pub fn minor(&self) -> u16 {
-        self.minor
-    }
-
-    pub fn patch(&self) -> u16 {
-        self.patch
-    }
-
-    pub fn set_variant(&mut self, variant: u16) {
-        self.variant = variant;
-    }
-
-    pub fn set_major(&mut self, major: u16) {
-        self.major = major;
-    }
-
-    pub fn set_minor(&mut self, minor: u16) {
-        self.minor = minor;
-    }
-
-    pub fn set_patch(&mut self, patch: u16) {
-        self.patch = patch;
-    }
 }
 
 impl Default for Version {